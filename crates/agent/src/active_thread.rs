@@ -16,6 +16,7 @@ use crate::ui::{
 use anyhow::Context as _;
 use assistant_settings::{AssistantSettings, NotifyWhenAgentWaiting};
 use assistant_tool::ToolUseStatus;
+use citations::Citation;
 use collections::{HashMap, HashSet};
 use editor::actions::{MoveUp, Paste};
 use editor::scroll::Autoscroll;
@@ -51,6 +52,7 @@ use ui::{
 use util::ResultExt as _;
 use util::markdown::MarkdownCodeBlock;
 use workspace::Workspace;
+use zed_actions::answer_library::OpenAnswerLibrary;
 use zed_actions::assistant::OpenRulesLibrary;
 
 pub struct ActiveThread {
@@ -494,6 +496,13 @@ pub(crate) fn default_markdown_style(window: &Window, cx: &App) -> MarkdownStyle
                     background_color: Some(colors.element_background),
                     ..Default::default()
                 })
+            } else if Citation::is_citation(url) {
+                let colors = cx.theme().colors();
+                Some(TextStyleRefinement {
+                    color: Some(colors.text_accent),
+                    background_color: Some(colors.element_background),
+                    ..Default::default()
+                })
             } else {
                 None
             }
@@ -872,6 +881,11 @@ fn open_markdown_link(
         return;
     };
 
+    if let Some(citation) = Citation::parse(&text) {
+        citations::open_citation(&citation, &workspace, window, cx).detach_and_log_err(cx);
+        return;
+    }
+
     match MentionLink::try_parse(&text, &workspace, cx) {
         Some(MentionLink::File(path, entry)) => workspace.update(cx, |workspace, cx| {
             if entry.is_dir() {
@@ -960,6 +974,12 @@ fn open_markdown_link(
             }),
             cx,
         ),
+        Some(MentionLink::Answer(answer_id)) => window.dispatch_action(
+            Box::new(OpenAnswerLibrary {
+                answer_to_select: Some(answer_id.0),
+            }),
+            cx,
+        ),
         None => cx.open_url(&text),
     }
 }
@@ -3808,6 +3828,13 @@ pub(crate) fn open_context(
             cx,
         ),
 
+        AgentContextHandle::Answer(answer_context) => window.dispatch_action(
+            Box::new(OpenAnswerLibrary {
+                answer_to_select: Some(answer_context.answer_id.0),
+            }),
+            cx,
+        ),
+
         AgentContextHandle::Image(_) => {}
     }
 }
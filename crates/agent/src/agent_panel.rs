@@ -48,7 +48,9 @@ use util::{ResultExt as _, maybe};
 use workspace::dock::{DockPosition, Panel, PanelEvent};
 use workspace::{CollaboratorId, DraggedSelection, DraggedTab, ToolbarItemView, Workspace};
 use zed_actions::agent::{OpenConfiguration, OpenOnboardingModal, ResetOnboarding};
+use zed_actions::answer_library::OpenAnswerLibrary;
 use zed_actions::assistant::{OpenRulesLibrary, ToggleFocus};
+use zed_actions::new_tender::StartNewTender;
 use zed_actions::{DecreaseBufferFontSize, IncreaseBufferFontSize, ResetBufferFontSize};
 use zed_llm_client::UsageLimit;
 
@@ -1675,6 +1677,7 @@ impl AgentPanel {
 
                     menu = menu
                         .action("Rules…", Box::new(OpenRulesLibrary::default()))
+                        .action("Answer Library…", Box::new(OpenAnswerLibrary::default()))
                         .action("Settings", Box::new(OpenConfiguration));
                     menu
                 }))
@@ -2071,20 +2074,20 @@ impl AgentPanel {
                                     ),
                                 )
                                 .child(
-                            Button::new("new-thread", "Start New Tender")
+                            Button::new("new-tender", "Start New Tender")
                                         .icon(IconName::Plus)
                                         .icon_position(IconPosition::Start)
                                         .icon_size(IconSize::Small)
                                         .icon_color(Color::Muted)
                                         .full_width()
                                         .key_binding(KeyBinding::for_action_in(
-                                            &NewThread::default(),
+                                            &StartNewTender,
                                             &focus_handle,
                                             window,
                                             cx,
                                         ))
                                         .on_click(|_event, window, cx| {
-                                            window.dispatch_action(NewThread::default().boxed_clone(), cx)
+                                            window.dispatch_action(StartNewTender.boxed_clone(), cx)
                                         }),
                                 )
                                 .child(
@@ -3,6 +3,7 @@ use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::{ops::Range, path::Path, sync::Arc};
 
+use answer_store::AnswerId;
 use assistant_context_editor::AssistantContext;
 use assistant_tool::outline;
 use collections::{HashMap, HashSet};
@@ -38,6 +39,7 @@ pub enum ContextKind {
     Thread,
     TextThread,
     Rules,
+    Answer,
     Image,
 }
 
@@ -52,6 +54,7 @@ impl ContextKind {
             ContextKind::Thread => IconName::MessageBubbles,
             ContextKind::TextThread => IconName::MessageBubbles,
             ContextKind::Rules => RULES_ICON,
+            ContextKind::Answer => IconName::Library,
             ContextKind::Image => IconName::Image,
         }
     }
@@ -72,6 +75,7 @@ pub enum AgentContextHandle {
     Thread(ThreadContextHandle),
     TextThread(TextThreadContextHandle),
     Rules(RulesContextHandle),
+    Answer(AnswerContext),
     Image(ImageContext),
 }
 
@@ -86,6 +90,7 @@ impl AgentContextHandle {
             Self::Thread(context) => context.context_id,
             Self::TextThread(context) => context.context_id,
             Self::Rules(context) => context.context_id,
+            Self::Answer(context) => context.context_id,
             Self::Image(context) => context.context_id,
         }
     }
@@ -107,6 +112,7 @@ pub enum AgentContext {
     Thread(ThreadContext),
     TextThread(TextThreadContext),
     Rules(RulesContext),
+    Answer(AnswerContext),
     Image(ImageContext),
 }
 
@@ -127,6 +133,7 @@ impl AgentContext {
                 AgentContextHandle::TextThread(context.handle.clone())
             }
             AgentContext::Rules(context) => AgentContextHandle::Rules(context.handle.clone()),
+            AgentContext::Answer(context) => AgentContextHandle::Answer(context.clone()),
             AgentContext::Image(context) => AgentContextHandle::Image(context.clone()),
         }
     }
@@ -738,6 +745,57 @@ impl Display for RulesContext {
     }
 }
 
+/// An approved answer from the answer library, with the tender's details
+/// filled in. Like fetched URLs, its text is captured when it's added.
+#[derive(Debug, Clone)]
+pub struct AnswerContext {
+    pub answer_id: AnswerId,
+    pub title: SharedString,
+    pub text: SharedString,
+    /// Why the answer needs checking before it's reused, such as it having
+    /// expired. Not used by `PartialEq` or `Hash` for `AgentContextKey`.
+    pub warning: Option<SharedString>,
+    pub context_id: ContextId,
+}
+
+impl AnswerContext {
+    pub fn eq_for_key(&self, other: &Self) -> bool {
+        self.answer_id == other.answer_id
+    }
+
+    pub fn hash_for_key<H: Hasher>(&self, state: &mut H) {
+        self.answer_id.hash(state)
+    }
+
+    pub fn lookup_key(answer_id: AnswerId) -> AgentContextKey {
+        AgentContextKey(AgentContextHandle::Answer(AnswerContext {
+            answer_id,
+            title: "".into(),
+            text: "".into(),
+            warning: None,
+            context_id: ContextId::for_lookup(),
+        }))
+    }
+
+    pub fn load(self) -> Task<Option<(AgentContext, Vec<Entity<Buffer>>)>> {
+        Task::ready(Some((AgentContext::Answer(self), vec![])))
+    }
+}
+
+impl Display for AnswerContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Answer title: {}\n", self.title)?;
+        if let Some(warning) = &self.warning {
+            write!(f, "Warning: {}\n", warning)?;
+        }
+        let code_block = MarkdownCodeBlock {
+            tag: "",
+            text: self.text.trim(),
+        };
+        write!(f, "{code_block}")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ImageContext {
     pub project_path: Option<ProjectPath>,
@@ -846,6 +904,7 @@ pub fn load_context(
             AgentContextHandle::Thread(context) => load_tasks.push(context.load(cx)),
             AgentContextHandle::TextThread(context) => load_tasks.push(context.load(cx)),
             AgentContextHandle::Rules(context) => load_tasks.push(context.load(prompt_store, cx)),
+            AgentContextHandle::Answer(context) => load_tasks.push(context.load()),
             AgentContextHandle::Image(context) => load_tasks.push(context.load(cx)),
         }
     }
@@ -877,6 +936,7 @@ pub fn load_context(
         let mut thread_context = Vec::new();
         let mut text_thread_context = Vec::new();
         let mut rules_context = Vec::new();
+        let mut answer_context = Vec::new();
         let mut images = Vec::new();
         for context in &contexts {
             match context {
@@ -888,6 +948,7 @@ pub fn load_context(
                 AgentContext::Thread(context) => thread_context.push(context),
                 AgentContext::TextThread(context) => text_thread_context.push(context),
                 AgentContext::Rules(context) => rules_context.push(context),
+                AgentContext::Answer(context) => answer_context.push(context),
                 AgentContext::Image(context) => images.extend(context.image()),
             }
         }
@@ -902,6 +963,7 @@ pub fn load_context(
             && thread_context.is_empty()
             && text_thread_context.is_empty()
             && rules_context.is_empty()
+            && answer_context.is_empty()
         {
             return ContextLoadResult {
                 loaded_context: LoadedContext {
@@ -994,6 +1056,20 @@ pub fn load_context(
             text.push_str("</user_rules>\n");
         }
 
+        if !answer_context.is_empty() {
+            text.push_str(
+                "<library_answers>\n\
+                The user has attached these approved answers from their answer library. \
+                Reuse their wording where it fits, and don't present an answer with a warning \
+                as current without saying so:\n",
+            );
+            for context in answer_context {
+                text.push('\n');
+                let _ = write!(text, "{context}");
+            }
+            text.push_str("</library_answers>\n");
+        }
+
         text.push_str("</context>\n");
 
         ContextLoadResult {
@@ -1332,6 +1408,11 @@ impl PartialEq for AgentContextKey {
                     return context.eq_for_key(other_context);
                 }
             }
+            AgentContextHandle::Answer(context) => {
+                if let AgentContextHandle::Answer(other_context) = &other.0 {
+                    return context.eq_for_key(other_context);
+                }
+            }
             AgentContextHandle::Image(context) => {
                 if let AgentContextHandle::Image(other_context) = &other.0 {
                     return context.eq_for_key(other_context);
@@ -1358,6 +1439,7 @@ impl Hash for AgentContextKey {
             AgentContextHandle::Thread(context) => context.hash_for_key(state),
             AgentContextHandle::TextThread(context) => context.hash_for_key(state),
             AgentContextHandle::Rules(context) => context.hash_for_key(state),
+            AgentContextHandle::Answer(context) => context.hash_for_key(state),
             AgentContextHandle::Image(context) => context.hash_for_key(state),
         }
     }
@@ -1,3 +1,4 @@
+mod answers_context_picker;
 mod completion_provider;
 mod fetch_context_picker;
 mod file_context_picker;
@@ -9,6 +10,8 @@ use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use answer_store::{AnswerId, AnswerStore};
+use answers_context_picker::{AnswerContextEntry, AnswersContextPicker};
 use anyhow::{Result, anyhow};
 pub use completion_provider::ContextPickerCompletionProvider;
 use editor::display_map::{Crease, CreaseId, CreaseMetadata, FoldId};
@@ -78,6 +81,7 @@ enum ContextPickerMode {
     Fetch,
     Thread,
     Rules,
+    Answers,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -115,6 +119,7 @@ impl TryFrom<&str> for ContextPickerMode {
             "fetch" => Ok(Self::Fetch),
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
+            "answer" => Ok(Self::Answers),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -128,6 +133,7 @@ impl ContextPickerMode {
             Self::Fetch => "fetch",
             Self::Thread => "thread",
             Self::Rules => "rule",
+            Self::Answers => "answer",
         }
     }
 
@@ -138,6 +144,7 @@ impl ContextPickerMode {
             Self::Fetch => "Fetch",
             Self::Thread => "Threads",
             Self::Rules => "Rules",
+            Self::Answers => "Answer Library",
         }
     }
 
@@ -148,6 +155,7 @@ impl ContextPickerMode {
             Self::Fetch => IconName::Globe,
             Self::Thread => IconName::MessageBubbles,
             Self::Rules => RULES_ICON,
+            Self::Answers => IconName::Library,
         }
     }
 }
@@ -160,6 +168,7 @@ enum ContextPickerState {
     Fetch(Entity<FetchContextPicker>),
     Thread(Entity<ThreadContextPicker>),
     Rules(Entity<RulesContextPicker>),
+    Answers(Entity<AnswersContextPicker>),
 }
 
 pub(super) struct ContextPicker {
@@ -334,6 +343,19 @@ impl ContextPicker {
                         }));
                     }
                 }
+                ContextPickerMode::Answers => {
+                    if let Some(answer_store) = AnswerStore::try_global(cx) {
+                        self.mode = ContextPickerState::Answers(cx.new(|cx| {
+                            AnswersContextPicker::new(
+                                answer_store,
+                                context_picker.clone(),
+                                self.context_store.clone(),
+                                window,
+                                cx,
+                            )
+                        }));
+                    }
+                }
                 ContextPickerMode::Fetch => {
                     self.mode = ContextPickerState::Fetch(cx.new(|cx| {
                         FetchContextPicker::new(
@@ -545,6 +567,7 @@ impl ContextPicker {
             ContextPickerState::Fetch(entity) => entity.update(cx, |_, cx| cx.notify()),
             ContextPickerState::Thread(entity) => entity.update(cx, |_, cx| cx.notify()),
             ContextPickerState::Rules(entity) => entity.update(cx, |_, cx| cx.notify()),
+            ContextPickerState::Answers(entity) => entity.update(cx, |_, cx| cx.notify()),
         }
     }
 }
@@ -560,6 +583,7 @@ impl Focusable for ContextPicker {
             ContextPickerState::Fetch(fetch_picker) => fetch_picker.focus_handle(cx),
             ContextPickerState::Thread(thread_picker) => thread_picker.focus_handle(cx),
             ContextPickerState::Rules(user_rules_picker) => user_rules_picker.focus_handle(cx),
+            ContextPickerState::Answers(answers_picker) => answers_picker.focus_handle(cx),
         }
     }
 }
@@ -578,6 +602,7 @@ impl Render for ContextPicker {
                 ContextPickerState::Rules(user_rules_picker) => {
                     parent.child(user_rules_picker.clone())
                 }
+                ContextPickerState::Answers(answers_picker) => parent.child(answers_picker.clone()),
             })
     }
 }
@@ -621,6 +646,10 @@ fn available_context_picker_entries(
         entries.push(ContextPickerEntry::Mode(ContextPickerMode::Rules));
     }
 
+    if AnswerStore::try_global(cx).is_some() {
+        entries.push(ContextPickerEntry::Mode(ContextPickerMode::Answers));
+    }
+
     entries.push(ContextPickerEntry::Mode(ContextPickerMode::Fetch));
 
     entries
@@ -886,6 +915,7 @@ pub enum MentionLink {
     Thread(ThreadId),
     TextThread(Arc<Path>),
     Rule(UserPromptId),
+    Answer(AnswerId),
 }
 
 impl MentionLink {
@@ -895,6 +925,7 @@ impl MentionLink {
     const THREAD: &str = "@thread";
     const FETCH: &str = "@fetch";
     const RULE: &str = "@rule";
+    const ANSWER: &str = "@answer";
 
     const TEXT_THREAD_URL_PREFIX: &str = "text-thread://";
 
@@ -907,6 +938,7 @@ impl MentionLink {
             || url.starts_with(Self::SELECTION)
             || url.starts_with(Self::THREAD)
             || url.starts_with(Self::RULE)
+            || url.starts_with(Self::ANSWER)
     }
 
     pub fn for_file(file_name: &str, full_path: &str) -> String {
@@ -963,6 +995,15 @@ impl MentionLink {
         format!("[@{}]({}:{})", rule.title, Self::RULE, rule.prompt_id.0)
     }
 
+    pub fn for_answer(answer: &AnswerContextEntry) -> String {
+        format!(
+            "[@{}]({}:{})",
+            answer.title,
+            Self::ANSWER,
+            answer.answer_id.0
+        )
+    }
+
     pub fn try_parse(link: &str, workspace: &Entity<Workspace>, cx: &App) -> Option<Self> {
         fn extract_project_path_from_link(
             path: &str,
@@ -1029,6 +1070,10 @@ impl MentionLink {
                 let prompt_id = UserPromptId(Uuid::try_parse(argument).ok()?);
                 Some(MentionLink::Rule(prompt_id))
             }
+            Self::ANSWER => {
+                let answer_id = AnswerId(Uuid::try_parse(argument).ok()?);
+                Some(MentionLink::Answer(answer_id))
+            }
             _ => None,
         }
     }
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use answer_store::{AnswerId, AnswerStore, today};
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use ui::{ListItem, Tooltip, prelude::*};
+use util::ResultExt as _;
+
+use crate::context_picker::ContextPicker;
+use crate::context_store::{self, ContextStore};
+
+pub struct AnswersContextPicker {
+    picker: Entity<Picker<AnswersContextPickerDelegate>>,
+}
+
+impl AnswersContextPicker {
+    pub fn new(
+        answer_store: Entity<AnswerStore>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            AnswersContextPickerDelegate::new(answer_store, context_picker, context_store);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        AnswersContextPicker { picker }
+    }
+}
+
+impl Focusable for AnswersContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for AnswersContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnswerContextEntry {
+    pub answer_id: AnswerId,
+    pub title: SharedString,
+    /// Set when the answer has expired or is about to.
+    pub expiry_warning: Option<SharedString>,
+}
+
+pub struct AnswersContextPickerDelegate {
+    answer_store: Entity<AnswerStore>,
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<context_store::ContextStore>,
+    matches: Vec<AnswerContextEntry>,
+    selected_index: usize,
+}
+
+impl AnswersContextPickerDelegate {
+    pub fn new(
+        answer_store: Entity<AnswerStore>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+    ) -> Self {
+        AnswersContextPickerDelegate {
+            answer_store,
+            context_picker,
+            context_store,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for AnswersContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search the answer library…".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let search_task = search_answers(
+            query,
+            Arc::new(AtomicBool::default()),
+            &self.answer_store,
+            cx,
+        );
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = search_task.await;
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+
+        let Some(task) = self
+            .context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_answer(entry.answer_id, true, cx)
+            })
+            .log_err()
+        else {
+            return;
+        };
+        task.detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let answer = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_answer_context_entry(answer, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+pub fn render_answer_context_entry(
+    answer: &AnswerContextEntry,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let added = context_store.upgrade().map_or(false, |context_store| {
+        context_store.read(cx).includes_answer(answer.answer_id)
+    });
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .justify_between()
+        .child(
+            h_flex()
+                .gap_1p5()
+                .max_w_72()
+                .child(
+                    Icon::new(IconName::Library)
+                        .size(IconSize::XSmall)
+                        .color(Color::Muted),
+                )
+                .child(Label::new(answer.title.clone()).truncate())
+                .when_some(answer.expiry_warning.clone(), |el, warning| {
+                    el.child(
+                        div()
+                            .id("expiry-warning")
+                            .child(
+                                Icon::new(IconName::Warning)
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Warning),
+                            )
+                            .tooltip(Tooltip::text(warning)),
+                    )
+                }),
+        )
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
+
+pub(crate) fn search_answers(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    answer_store: &Entity<AnswerStore>,
+    cx: &mut App,
+) -> Task<Vec<AnswerContextEntry>> {
+    let search_task = answer_store.read(cx).search(query, cancellation_flag, cx);
+    cx.background_spawn(async move {
+        let today = today();
+        search_task
+            .await
+            .into_iter()
+            .map(|metadata| AnswerContextEntry {
+                answer_id: metadata.id,
+                title: metadata.display_title(),
+                expiry_warning: metadata.expiry_warning(today).map(Into::into),
+            })
+            .collect::<Vec<_>>()
+    })
+}
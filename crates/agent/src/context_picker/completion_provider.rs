@@ -5,6 +5,7 @@ use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
+use answer_store::AnswerStore;
 use anyhow::Result;
 use editor::{CompletionProvider, Editor, ExcerptId, ToOffset as _};
 use file_icons::FileIcons;
@@ -14,6 +15,7 @@ use http_client::HttpClientWithUrl;
 use itertools::Itertools;
 use language::{Buffer, CodeLabel, HighlightId};
 use lsp::CompletionContext;
+use project::lsp_store::CompletionDocumentation;
 use project::{Completion, CompletionIntent, ProjectPath, Symbol, WorktreeId};
 use prompt_store::PromptStore;
 use rope::Point;
@@ -27,6 +29,7 @@ use crate::context::{AgentContextHandle, AgentContextKey, ContextCreasesAddon, R
 use crate::context_store::ContextStore;
 use crate::thread_store::{TextThreadStore, ThreadStore};
 
+use super::answers_context_picker::{AnswerContextEntry, search_answers};
 use super::fetch_context_picker::fetch_url_content;
 use super::file_context_picker::{FileMatch, search_files};
 use super::rules_context_picker::{RulesContextEntry, search_rules};
@@ -44,6 +47,7 @@ pub(crate) enum Match {
     Thread(ThreadMatch),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    Answer(AnswerContextEntry),
     Entry(EntryMatch),
 }
 
@@ -61,6 +65,7 @@ impl Match {
             Match::Symbol(_) => 1.,
             Match::Fetch(_) => 1.,
             Match::Rules(_) => 1.,
+            Match::Answer(_) => 1.,
         }
     }
 }
@@ -150,6 +155,22 @@ fn search(
             }
         }
 
+        Some(ContextPickerMode::Answers) => {
+            if let Some(answer_store) = AnswerStore::try_global(cx) {
+                let search_answers_task =
+                    search_answers(query.clone(), cancellation_flag.clone(), &answer_store, cx);
+                cx.background_spawn(async move {
+                    search_answers_task
+                        .await
+                        .into_iter()
+                        .map(Match::Answer)
+                        .collect::<Vec<_>>()
+                })
+            } else {
+                Task::ready(Vec::new())
+            }
+        }
+
         None => {
             if query.is_empty() {
                 let mut matches = recent_entries
@@ -518,6 +539,45 @@ impl ContextPickerCompletionProvider {
         }
     }
 
+    fn completion_for_answer(
+        answer: AnswerContextEntry,
+        excerpt_id: ExcerptId,
+        source_range: Range<Anchor>,
+        editor: Entity<Editor>,
+        context_store: Entity<ContextStore>,
+    ) -> Completion {
+        let new_text = MentionLink::for_answer(&answer);
+        let new_text_len = new_text.len();
+        Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label: CodeLabel::plain(answer.title.to_string(), None),
+            documentation: answer
+                .expiry_warning
+                .as_ref()
+                .map(|warning| CompletionDocumentation::SingleLine(warning.clone())),
+            insert_text_mode: None,
+            source: project::CompletionSource::Custom,
+            icon_path: Some(IconName::Library.path().into()),
+            confirm: Some(confirm_completion_callback(
+                IconName::Library.path().into(),
+                answer.title.clone(),
+                excerpt_id,
+                source_range.start,
+                new_text_len,
+                editor.clone(),
+                context_store.clone(),
+                move |_, cx| {
+                    let answer_id = answer.answer_id;
+                    let context = context_store.update(cx, |context_store, cx| {
+                        context_store.add_answer(answer_id, false, cx)
+                    });
+                    cx.spawn(async move |_| context.await.log_err().flatten())
+                },
+            )),
+        }
+    }
+
     fn completion_for_fetch(
         source_range: Range<Anchor>,
         url_to_fetch: SharedString,
@@ -878,6 +938,14 @@ impl CompletionProvider for ContextPickerCompletionProvider {
                             context_store.clone(),
                         )),
 
+                        Match::Answer(answer) => Some(Self::completion_for_answer(
+                            answer,
+                            excerpt_id,
+                            source_range.clone(),
+                            editor.clone(),
+                            context_store.clone(),
+                        )),
+
                         Match::Fetch(url) => Some(Self::completion_for_fetch(
                             source_range.clone(),
                             url,
@@ -2,6 +2,7 @@ use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use answer_store::{AnswerId, AnswerStore, fill_from_manifest, reuse_warning, today};
 use anyhow::{Result, anyhow};
 use assistant_context_editor::AssistantContext;
 use collections::{HashSet, IndexSet};
@@ -13,12 +14,13 @@ use project::image_store::is_image_file;
 use project::{Project, ProjectItem, ProjectPath, Symbol};
 use prompt_store::UserPromptId;
 use ref_cast::RefCast as _;
+use tender_manifest::TenderManifestStore;
 use text::{Anchor, OffsetRangeExt};
 
 use crate::ThreadStore;
 use crate::context::{
-    AgentContextHandle, AgentContextKey, ContextId, DirectoryContextHandle, FetchedUrlContext,
-    FileContextHandle, ImageContext, RulesContextHandle, SelectionContextHandle,
+    AgentContextHandle, AgentContextKey, AnswerContext, ContextId, DirectoryContextHandle,
+    FetchedUrlContext, FileContextHandle, ImageContext, RulesContextHandle, SelectionContextHandle,
     SymbolContextHandle, TextThreadContextHandle, ThreadContextHandle,
 };
 use crate::context_strip::SuggestedContext;
@@ -296,6 +298,53 @@ impl ContextStore {
         context
     }
 
+    /// Adds an answer from the answer library, with the tender's details filled
+    /// in from the project's manifest.
+    pub fn add_answer(
+        &mut self,
+        answer_id: AnswerId,
+        remove_if_exists: bool,
+        cx: &mut Context<ContextStore>,
+    ) -> Task<Result<Option<AgentContextHandle>>> {
+        if let Some(existing) = self.context_set.get(&AnswerContext::lookup_key(answer_id)) {
+            let existing = existing.as_ref().clone();
+            if remove_if_exists {
+                self.remove_context(&existing, cx);
+                return Task::ready(Ok(None));
+            }
+            return Task::ready(Ok(Some(existing)));
+        }
+
+        let Some(answer_store) = AnswerStore::try_global(cx) else {
+            return Task::ready(Err(anyhow!("The answer library hasn't loaded yet")));
+        };
+        let Some(metadata) = answer_store.read(cx).metadata(answer_id) else {
+            return Task::ready(Err(anyhow!("Answer not found")));
+        };
+        let manifest = self.project.upgrade().and_then(|project| {
+            TenderManifestStore::for_project(&project, cx)
+                .read(cx)
+                .manifest()
+        });
+        let body = answer_store.read(cx).load(answer_id, cx);
+        cx.spawn(async move |this, cx| {
+            let body = body.await?;
+            let filled = fill_from_manifest(&body, manifest.as_deref());
+            let warning = reuse_warning(&metadata, &filled, today());
+            this.update(cx, |this, cx| {
+                let context = AgentContextHandle::Answer(AnswerContext {
+                    answer_id,
+                    title: metadata.display_title(),
+                    text: filled.text.into(),
+                    warning: warning.map(Into::into),
+                    context_id: this.next_context_id.post_inc(),
+                });
+                this.insert_context(context.clone(), cx);
+                Some(context)
+            })
+        })
+    }
+
     pub fn add_image_from_path(
         &mut self,
         project_path: ProjectPath,
@@ -526,6 +575,11 @@ impl ContextStore {
             .contains(&RulesContextHandle::lookup_key(prompt_id))
     }
 
+    pub fn includes_answer(&self, answer_id: AnswerId) -> bool {
+        self.context_set
+            .contains(&AnswerContext::lookup_key(answer_id))
+    }
+
     pub fn includes_url(&self, url: impl Into<SharedString>) -> bool {
         self.context_set
             .contains(&FetchedUrlContext::lookup_key(url.into()))
@@ -551,6 +605,7 @@ impl ContextStore {
                 | AgentContextHandle::Thread(_)
                 | AgentContextHandle::TextThread(_)
                 | AgentContextHandle::Rules(_)
+                | AgentContextHandle::Answer(_)
                 | AgentContextHandle::Image(_) => None,
             })
             .collect()
@@ -8,6 +8,7 @@ use anyhow::{Context as _, Result, anyhow};
 use assistant_settings::{AgentProfile, AgentProfileId, AssistantSettings, CompletionMode};
 use assistant_tool::{ToolId, ToolSource, ToolWorkingSet};
 use chrono::{DateTime, Utc};
+use clarifications::ClarificationStore;
 use collections::HashMap;
 use context_server::ContextServerId;
 use futures::channel::{mpsc, oneshot};
@@ -23,11 +24,13 @@ use language_model::{LanguageModelToolUseId, Role, TokenUsage};
 use project::context_server_store::{ContextServerStatus, ContextServerStore};
 use project::{Project, ProjectItem, ProjectPath, Worktree};
 use prompt_store::{
-    ProjectContext, PromptBuilder, PromptId, PromptStore, PromptsUpdatedEvent, RulesFileContext,
-    UserRulesContext, WorktreeContext,
+    ClarificationsContext, ProjectContext, PromptBuilder, PromptId, PromptStore,
+    PromptsUpdatedEvent, RulesFileContext, TenderManifestContext, UserRulesContext,
+    WorktreeContext,
 };
 use serde::{Deserialize, Serialize};
 use settings::{Settings as _, SettingsStore};
+use tender_manifest::TenderManifestStore;
 use ui::Window;
 use util::ResultExt as _;
 
@@ -111,11 +114,19 @@ impl ThreadStore {
         prompt_store: Option<Entity<PromptStore>>,
         cx: &mut Context<Self>,
     ) -> (Self, oneshot::Receiver<()>) {
+        let tender_manifest_store = TenderManifestStore::for_project(&project, cx);
+        let clarification_store = ClarificationStore::for_project(&project, cx);
         let mut subscriptions = vec![
             cx.observe_global::<SettingsStore>(move |this: &mut Self, cx| {
                 this.load_default_profile(cx);
             }),
             cx.subscribe(&project, Self::handle_project_event),
+            cx.observe(&tender_manifest_store, |this, _, _| {
+                this.enqueue_system_prompt_reload();
+            }),
+            cx.observe(&clarification_store, |this, _, _| {
+                this.enqueue_system_prompt_reload();
+            }),
         ];
 
         if let Some(prompt_store) = prompt_store.as_ref() {
@@ -225,6 +236,8 @@ impl ThreadStore {
                 cx.background_spawn(future::join_all(load_tasks))
             }),
         };
+        let tender_manifest = self.tender_manifest_context(cx);
+        let clarifications = self.clarifications_context(cx);
 
         cx.spawn(async move |this, cx| {
             let (worktrees, default_user_rules) =
@@ -264,13 +277,56 @@ impl ThreadStore {
                 .collect::<Vec<_>>();
 
             this.update(cx, |this, _cx| {
-                *this.project_context.0.borrow_mut() =
-                    Some(ProjectContext::new(worktrees, default_user_rules));
+                *this.project_context.0.borrow_mut() = Some(ProjectContext::new(
+                    worktrees,
+                    default_user_rules,
+                    tender_manifest,
+                    clarifications,
+                ));
             })
             .ok();
         })
     }
 
+    fn tender_manifest_context(&self, cx: &mut App) -> Option<TenderManifestContext> {
+        let store = TenderManifestStore::for_project(&self.project, cx);
+        let store = store.read(cx);
+        let manifest = store.manifest()?;
+        let path = store.path()?;
+        let worktree = self
+            .project
+            .read(cx)
+            .worktree_for_id(path.worktree_id, cx)?;
+        Some(TenderManifestContext {
+            path: Path::new(worktree.read(cx).root_name())
+                .join(&path.path)
+                .to_string_lossy()
+                .into_owned(),
+            summary: manifest.summary(),
+        })
+    }
+
+    fn clarifications_context(&self, cx: &mut App) -> Option<ClarificationsContext> {
+        let store = ClarificationStore::for_project(&self.project, cx);
+        let store = store.read(cx);
+        let answers = store.register()?.to_markdown();
+        if answers.is_empty() {
+            return None;
+        }
+        let path = store.path()?;
+        let worktree = self
+            .project
+            .read(cx)
+            .worktree_for_id(path.worktree_id, cx)?;
+        Some(ClarificationsContext {
+            path: Path::new(worktree.read(cx).root_name())
+                .join(&path.path)
+                .to_string_lossy()
+                .into_owned(),
+            answers,
+        })
+    }
+
     fn load_worktree_info_for_system_prompt(
         worktree: Entity<Worktree>,
         project: Entity<Project>,
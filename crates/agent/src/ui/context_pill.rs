@@ -13,7 +13,7 @@ use rope::Point;
 use ui::{IconButtonShape, Tooltip, prelude::*, tooltip_container};
 
 use crate::context::{
-    AgentContext, AgentContextHandle, ContextId, ContextKind, DirectoryContext,
+    AgentContext, AgentContextHandle, AnswerContext, ContextId, ContextKind, DirectoryContext,
     DirectoryContextHandle, FetchedUrlContext, FileContext, FileContextHandle, ImageContext,
     ImageStatus, RulesContext, RulesContextHandle, SelectionContext, SelectionContextHandle,
     SymbolContext, SymbolContextHandle, TextThreadContext, TextThreadContextHandle, ThreadContext,
@@ -304,6 +304,7 @@ impl AddedContext {
             AgentContextHandle::Thread(handle) => Some(Self::pending_thread(handle, cx)),
             AgentContextHandle::TextThread(handle) => Some(Self::pending_text_thread(handle, cx)),
             AgentContextHandle::Rules(handle) => Self::pending_rules(handle, prompt_store, cx),
+            AgentContextHandle::Answer(handle) => Some(Self::answer(handle)),
             AgentContextHandle::Image(handle) => Some(Self::image(handle)),
         }
     }
@@ -318,6 +319,7 @@ impl AddedContext {
             AgentContext::Thread(context) => Self::attached_thread(context),
             AgentContext::TextThread(context) => Self::attached_text_thread(context),
             AgentContext::Rules(context) => Self::attached_rules(context),
+            AgentContext::Answer(context) => Self::answer(context.clone()),
             AgentContext::Image(context) => Self::image(context.clone()),
         }
     }
@@ -480,6 +482,31 @@ impl AddedContext {
         }
     }
 
+    /// Answers that need checking, such as ones that have expired, are shown
+    /// as errors so they aren't sent without being noticed.
+    fn answer(context: AnswerContext) -> AddedContext {
+        AddedContext {
+            kind: ContextKind::Answer,
+            name: context.title.clone(),
+            parent: None,
+            tooltip: None,
+            icon_path: None,
+            status: match &context.warning {
+                Some(warning) => ContextStatus::Error {
+                    message: warning.clone(),
+                },
+                None => ContextStatus::Ready,
+            },
+            render_hover: {
+                let text = context.text.clone();
+                Some(Rc::new(move |_, cx| {
+                    ContextPillHover::new_text(text.clone(), cx).into()
+                }))
+            },
+            handle: AgentContextHandle::Answer(context),
+        }
+    }
+
     fn pending_thread(handle: ThreadContextHandle, cx: &App) -> AddedContext {
         AddedContext {
             kind: ContextKind::Thread,
@@ -0,0 +1,32 @@
+//! The answer library window, where approved answers are written and kept up
+//! to date, and the modal for inserting them into a response with the
+//! tender's details filled in.
+
+mod insert_answer_modal;
+mod library;
+
+use answer_store::AnswerId;
+use gpui::{App, actions};
+use insert_answer_modal::InsertAnswerModal;
+use workspace::Workspace;
+use zed_actions::answer_library::OpenAnswerLibrary;
+
+pub use library::{AnswerLibrary, open_answer_library};
+
+actions!(answer_library, [NewAnswer, DeleteAnswer, InsertAnswer]);
+
+pub fn init(cx: &mut App) {
+    answer_store::init(cx);
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(|workspace, action: &OpenAnswerLibrary, _, cx| {
+                let language_registry = workspace.project().read(cx).languages().clone();
+                let answer_to_select = action.answer_to_select.map(AnswerId);
+                open_answer_library(language_registry, answer_to_select, cx).detach_and_log_err(cx);
+            })
+            .register_action(|workspace, _: &InsertAnswer, window, cx| {
+                InsertAnswerModal::toggle(workspace, window, cx)
+            });
+    })
+    .detach();
+}
@@ -0,0 +1,324 @@
+use answer_store::{AnswerMetadata, AnswerStore, fill_from_manifest, reuse_warning, today};
+use chrono::NaiveDate;
+use editor::Editor;
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity,
+};
+use picker::{Picker, PickerDelegate};
+use semantic_index::SemanticDb;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use tender_manifest::{TenderManifest, TenderManifestStore};
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::notifications::NotificationId;
+use workspace::{ModalView, Toast, Workspace};
+
+/// How long to wait after the query last changed before searching answers by
+/// meaning, which has to embed the query.
+const SEMANTIC_SEARCH_DEBOUNCE: Duration = Duration::from_millis(250);
+const SEMANTIC_SEARCH_LIMIT: usize = 10;
+
+/// Picks an answer from the library to insert at the cursor, with the
+/// tender's details filled in.
+pub(crate) struct InsertAnswerModal {
+    picker: Entity<Picker<InsertAnswerDelegate>>,
+}
+
+impl InsertAnswerModal {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            struct NoEditor;
+
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<NoEditor>(),
+                    "Open a response to insert an answer into it",
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        };
+        let Some(store) = AnswerStore::try_global(cx) else {
+            struct NoAnswerStore;
+
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<NoAnswerStore>(),
+                    "The answer library is still loading",
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        };
+        let manifest = TenderManifestStore::for_project(workspace.project(), cx)
+            .read(cx)
+            .manifest();
+
+        let workspace_handle = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate = InsertAnswerDelegate {
+                modal: cx.entity().downgrade(),
+                workspace: workspace_handle,
+                editor: editor.downgrade(),
+                store,
+                manifest,
+                matches: Vec::new(),
+                selected_index: 0,
+                today: today(),
+            };
+            Self {
+                picker: cx.new(|cx| Picker::uniform_list(delegate, window, cx)),
+            }
+        });
+    }
+}
+
+impl Render for InsertAnswerModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for InsertAnswerModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for InsertAnswerModal {}
+impl ModalView for InsertAnswerModal {}
+
+struct AnswerEntry {
+    metadata: AnswerMetadata,
+    /// Whether the answer was found by its meaning rather than its title or
+    /// tags.
+    related: bool,
+}
+
+struct InsertAnswerDelegate {
+    modal: WeakEntity<InsertAnswerModal>,
+    workspace: WeakEntity<Workspace>,
+    editor: WeakEntity<Editor>,
+    store: Entity<AnswerStore>,
+    manifest: Option<Arc<TenderManifest>>,
+    matches: Vec<AnswerEntry>,
+    selected_index: usize,
+    today: NaiveDate,
+}
+
+impl PickerDelegate for InsertAnswerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search the answer library…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, cx: &mut App) -> Option<SharedString> {
+        if self.store.read(cx).answer_count() == 0 {
+            Some("No answers yet. Add some in the answer library".into())
+        } else {
+            Some("No matching answers".into())
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let store = self.store.read(cx);
+        let search = store.search(query.clone(), Arc::new(AtomicBool::default()), cx);
+        let semantic_search = cx
+            .has_global::<SemanticDb>()
+            .then(|| cx.global::<SemanticDb>().embedding_provider());
+        let store = self.store.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = search.await;
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches
+                    .into_iter()
+                    .map(|metadata| AnswerEntry {
+                        metadata,
+                        related: false,
+                    })
+                    .collect();
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+
+            let Some(embedding_provider) = semantic_search else {
+                return;
+            };
+            if query.trim().is_empty() {
+                return;
+            }
+            cx.background_executor()
+                .timer(SEMANTIC_SEARCH_DEBOUNCE)
+                .await;
+            let Some(semantic_search) = cx
+                .update(|_, cx| {
+                    store.read(cx).semantic_search(
+                        query,
+                        embedding_provider,
+                        SEMANTIC_SEARCH_LIMIT,
+                        cx,
+                    )
+                })
+                .log_err()
+            else {
+                return;
+            };
+            let Some(related) = semantic_search.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                for answer in related {
+                    if !delegate
+                        .matches
+                        .iter()
+                        .any(|entry| entry.metadata.id == answer.metadata.id)
+                    {
+                        delegate.matches.push(AnswerEntry {
+                            metadata: answer.metadata,
+                            related: true,
+                        });
+                    }
+                }
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let metadata = entry.metadata.clone();
+        let body = self.store.read(cx).load(metadata.id, cx);
+        let manifest = self.manifest.clone();
+        let editor = self.editor.clone();
+        let workspace = self.workspace.clone();
+        let today = self.today;
+        cx.spawn_in(window, async move |_, cx| {
+            let body = body.await?;
+            let filled = fill_from_manifest(&body, manifest.as_deref());
+            editor.update_in(cx, |editor, window, cx| {
+                editor.insert(&filled.text, window, cx);
+            })?;
+            if let Some(warning) = reuse_warning(&metadata, &filled, today) {
+                struct AnswerInserted;
+
+                workspace.update(cx, |workspace, cx| {
+                    workspace.show_toast(
+                        Toast::new(NotificationId::unique::<AnswerInserted>(), warning),
+                        cx,
+                    );
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = self.matches.get(ix)?;
+        let metadata = &entry.metadata;
+        let expired = metadata.is_expired(self.today);
+        let expiry_warning = metadata.expiry_warning(self.today);
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Label::new(metadata.display_title()).truncate())
+                        .when(!metadata.tags.is_empty(), |this| {
+                            this.child(
+                                Label::new(metadata.tags.join(", "))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .truncate(),
+                            )
+                        }),
+                )
+                .end_slot::<AnyElement>(
+                    h_flex()
+                        .gap_1()
+                        .when(entry.related, |this| {
+                            this.child(
+                                Label::new("Related")
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                        })
+                        .when_some(expiry_warning, |this, warning| {
+                            this.child(
+                                div()
+                                    .id("expiry-warning")
+                                    .child(
+                                        Icon::new(IconName::Warning).size(IconSize::Small).color(
+                                            if expired {
+                                                Color::Error
+                                            } else {
+                                                Color::Warning
+                                            },
+                                        ),
+                                    )
+                                    .tooltip(Tooltip::text(warning)),
+                            )
+                        })
+                        .into_any_element(),
+                ),
+        )
+    }
+}
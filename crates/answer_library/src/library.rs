@@ -0,0 +1,1079 @@
+use crate::{DeleteAnswer, NewAnswer};
+use answer_store::{
+    AnswerDetails, AnswerId, AnswerMetadata, AnswerStore, Expiry, MANIFEST_VARIABLES, today,
+    variables,
+};
+use anyhow::Result;
+use chrono::NaiveDate;
+use collections::HashMap;
+use editor::{CurrentLineHighlight, Editor, EditorElement, EditorEvent, EditorStyle, actions::Tab};
+use gpui::{
+    Action, App, Bounds, Entity, EventEmitter, Focusable, PromptLevel, Subscription, Task,
+    TextStyle, TitlebarOptions, WindowBounds, WindowHandle, WindowOptions, point, size,
+    transparent_black,
+};
+use language::{Buffer, LanguageRegistry, language_settings::SoftWrap};
+use picker::{Picker, PickerDelegate};
+use release_channel::ReleaseChannel;
+use rope::Rope;
+use settings::Settings;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use theme::ThemeSettings;
+use ui::{
+    Context, IconButtonShape, KeyBinding, ListItem, ListItemSpacing, ParentElement, Render,
+    SharedString, Styled, Tooltip, Window, div, prelude::*,
+};
+use util::{ResultExt, TryFutureExt};
+
+/// The date formats accepted in the approval fields, the first of which is
+/// used to show them.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d/%m/%Y"];
+
+/// This function opens a new answer library window if one doesn't exist
+/// already. If one exists, it brings it to the foreground.
+///
+/// Note that, when opening a new window, this waits for the AnswerStore to be
+/// initialized. If it was initialized successfully, it returns a window handle
+/// to an answer library.
+pub fn open_answer_library(
+    language_registry: Arc<LanguageRegistry>,
+    answer_to_select: Option<AnswerId>,
+    cx: &mut App,
+) -> Task<Result<WindowHandle<AnswerLibrary>>> {
+    let store = AnswerStore::global(cx);
+    cx.spawn(async move |cx| {
+        // We query windows in spawn so that all windows have been returned to GPUI
+        let existing_window = cx
+            .update(|cx| {
+                let existing_window = cx
+                    .windows()
+                    .into_iter()
+                    .find_map(|window| window.downcast::<AnswerLibrary>());
+                if let Some(existing_window) = existing_window {
+                    existing_window
+                        .update(cx, |answer_library, window, cx| {
+                            if let Some(answer_to_select) = answer_to_select {
+                                answer_library.load_answer(answer_to_select, true, window, cx);
+                            }
+                            window.activate_window()
+                        })
+                        .ok();
+
+                    Some(existing_window)
+                } else {
+                    None
+                }
+            })
+            .ok()
+            .flatten();
+
+        if let Some(existing_window) = existing_window {
+            return Ok(existing_window);
+        }
+
+        let store = store.await?;
+        cx.update(|cx| {
+            let app_id = ReleaseChannel::global(cx).app_id();
+            let bounds = Bounds::centered(None, size(px(1024.0), px(768.0)), cx);
+            cx.open_window(
+                WindowOptions {
+                    titlebar: Some(TitlebarOptions {
+                        title: Some("Answer Library".into()),
+                        appears_transparent: cfg!(target_os = "macos"),
+                        traffic_light_position: Some(point(px(9.0), px(9.0))),
+                    }),
+                    app_id: Some(app_id.to_owned()),
+                    window_bounds: Some(WindowBounds::Windowed(bounds)),
+                    ..Default::default()
+                },
+                |window, cx| {
+                    cx.new(|cx| {
+                        AnswerLibrary::new(store, language_registry, answer_to_select, window, cx)
+                    })
+                },
+            )
+        })?
+    })
+}
+
+pub struct AnswerLibrary {
+    store: Entity<AnswerStore>,
+    language_registry: Arc<LanguageRegistry>,
+    answer_editors: HashMap<AnswerId, AnswerEditor>,
+    active_answer_id: Option<AnswerId>,
+    picker: Entity<Picker<AnswerPickerDelegate>>,
+    pending_load: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct AnswerEditor {
+    title_editor: Entity<Editor>,
+    tags_editor: Entity<Editor>,
+    approved_on_editor: Entity<Editor>,
+    expires_on_editor: Entity<Editor>,
+    body_editor: Entity<Editor>,
+    /// Whether the approval dates as typed could be parsed. Dates that can't
+    /// be are left as they were last saved.
+    approved_on_is_valid: bool,
+    expires_on_is_valid: bool,
+    next_answer_to_save: Option<(AnswerDetails, Rope)>,
+    pending_save: Option<Task<Option<()>>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct AnswerPickerDelegate {
+    store: Entity<AnswerStore>,
+    selected_index: usize,
+    matches: Vec<AnswerMetadata>,
+    today: NaiveDate,
+}
+
+enum AnswerPickerEvent {
+    Selected { answer_id: AnswerId },
+    Confirmed { answer_id: AnswerId },
+    Deleted { answer_id: AnswerId },
+}
+
+impl EventEmitter<AnswerPickerEvent> for Picker<AnswerPickerDelegate> {}
+
+impl PickerDelegate for AnswerPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, cx: &mut App) -> Option<SharedString> {
+        let text = if self.store.read(cx).answer_count() == 0 {
+            "No answers.".into()
+        } else {
+            "No answers found matching your search.".into()
+        };
+        Some(text)
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+        if let Some(answer) = self.matches.get(self.selected_index) {
+            cx.emit(AnswerPickerEvent::Selected {
+                answer_id: answer.id,
+            });
+        }
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search titles and tags...".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let cancellation_flag = Arc::new(AtomicBool::default());
+        let search = self.store.read(cx).search(query, cancellation_flag, cx);
+        let prev_answer_id = self.matches.get(self.selected_index).map(|mat| mat.id);
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = search.await;
+            let selected_index = prev_answer_id
+                .and_then(|prev_answer_id| {
+                    matches.iter().position(|entry| entry.id == prev_answer_id)
+                })
+                .unwrap_or(0);
+
+            this.update_in(cx, |this, window, cx| {
+                this.delegate.matches = matches;
+                this.delegate.today = today();
+                this.delegate.set_selected_index(selected_index, window, cx);
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(answer) = self.matches.get(self.selected_index) {
+            cx.emit(AnswerPickerEvent::Confirmed {
+                answer_id: answer.id,
+            });
+        }
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, _cx: &mut Context<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let answer = self.matches.get(ix)?;
+        let answer_id = answer.id;
+        let expiry = answer.expiry(self.today);
+        let expiry_warning = answer.expiry_warning(self.today);
+        let element = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected)
+            .child(
+                h_flex()
+                    .h_5()
+                    .gap_2()
+                    .line_height(relative(1.))
+                    .child(Label::new(answer.display_title()).truncate())
+                    .when(!answer.tags.is_empty(), |this| {
+                        this.child(
+                            Label::new(answer.tags.join(", "))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        )
+                    }),
+            )
+            .end_slot::<AnyElement>(expiry_warning.map(|warning| {
+                div()
+                    .id("expiry-warning")
+                    .child(
+                        Icon::new(IconName::Warning)
+                            .size(IconSize::Small)
+                            .color(expiry_color(expiry)),
+                    )
+                    .tooltip(Tooltip::text(warning))
+                    .into_any_element()
+            }))
+            .end_hover_slot(
+                IconButton::new("delete-answer", IconName::Trash)
+                    .icon_color(Color::Muted)
+                    .shape(IconButtonShape::Square)
+                    .tooltip(Tooltip::text("Delete Answer"))
+                    .on_click(cx.listener(move |_, _, _, cx| {
+                        cx.emit(AnswerPickerEvent::Deleted { answer_id })
+                    })),
+            );
+        Some(element)
+    }
+
+    fn render_editor(
+        &self,
+        editor: &Entity<Editor>,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Div {
+        h_flex()
+            .bg(cx.theme().colors().editor_background)
+            .rounded_sm()
+            .overflow_hidden()
+            .flex_none()
+            .py_1()
+            .px_2()
+            .mx_1()
+            .child(editor.clone())
+    }
+}
+
+impl AnswerLibrary {
+    fn new(
+        store: Entity<AnswerStore>,
+        language_registry: Arc<LanguageRegistry>,
+        answer_to_select: Option<AnswerId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let (selected_index, matches) = if let Some(answer_to_select) = answer_to_select {
+            let matches = store.read(cx).all_answer_metadata();
+            let selected_index = matches
+                .iter()
+                .position(|metadata| metadata.id == answer_to_select)
+                .unwrap_or(0);
+            (selected_index, matches)
+        } else {
+            (0, vec![])
+        };
+
+        let delegate = AnswerPickerDelegate {
+            store: store.clone(),
+            selected_index,
+            matches,
+            today: today(),
+        };
+
+        let picker = cx.new(|cx| {
+            let picker = Picker::uniform_list(delegate, window, cx)
+                .modal(false)
+                .max_height(None);
+            picker.focus(window, cx);
+            picker
+        });
+        Self {
+            store: store.clone(),
+            language_registry,
+            answer_editors: HashMap::default(),
+            active_answer_id: None,
+            pending_load: Task::ready(()),
+            _subscriptions: vec![cx.subscribe_in(&picker, window, Self::handle_picker_event)],
+            picker,
+        }
+    }
+
+    fn handle_picker_event(
+        &mut self,
+        _: &Entity<Picker<AnswerPickerDelegate>>,
+        event: &AnswerPickerEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            AnswerPickerEvent::Selected { answer_id } => {
+                self.load_answer(*answer_id, false, window, cx);
+            }
+            AnswerPickerEvent::Confirmed { answer_id } => {
+                self.load_answer(*answer_id, true, window, cx);
+            }
+            AnswerPickerEvent::Deleted { answer_id } => {
+                self.delete_answer(*answer_id, window, cx);
+            }
+        }
+    }
+
+    pub fn new_answer(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        // If we already have an untitled answer, use that instead
+        // of creating a new one.
+        if let Some(metadata) = self.store.read(cx).first() {
+            if metadata.title.is_none() {
+                self.load_answer(metadata.id, true, window, cx);
+                return;
+            }
+        }
+
+        let answer_id = AnswerId::new();
+        let save = self.store.update(cx, |store, cx| {
+            store.save(answer_id, AnswerDetails::default(), "".into(), cx)
+        });
+        self.picker
+            .update(cx, |picker, cx| picker.refresh(window, cx));
+        cx.spawn_in(window, async move |this, cx| {
+            save.await?;
+            this.update_in(cx, |this, window, cx| {
+                this.load_answer(answer_id, true, window, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn save_answer(
+        &mut self,
+        answer_id: AnswerId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        const SAVE_THROTTLE: Duration = Duration::from_millis(500);
+
+        let Some(answer_metadata) = self.store.read(cx).metadata(answer_id) else {
+            return;
+        };
+        let Some(answer_editor) = self.answer_editors.get_mut(&answer_id) else {
+            return;
+        };
+
+        let title = answer_editor.title_editor.read(cx).text(cx);
+        let approved_on = parse_date(&answer_editor.approved_on_editor.read(cx).text(cx));
+        let expires_on = parse_date(&answer_editor.expires_on_editor.read(cx).text(cx));
+        answer_editor.approved_on_is_valid = approved_on.is_ok();
+        answer_editor.expires_on_is_valid = expires_on.is_ok();
+        let details = AnswerDetails {
+            title: (!title.trim().is_empty()).then(|| title.into()),
+            tags: parse_tags(&answer_editor.tags_editor.read(cx).text(cx)),
+            approved_on: approved_on.unwrap_or(answer_metadata.approved_on),
+            expires_on: expires_on.unwrap_or(answer_metadata.expires_on),
+        };
+        let body = answer_editor.body_editor.update(cx, |editor, cx| {
+            editor
+                .buffer()
+                .read(cx)
+                .as_singleton()
+                .unwrap()
+                .read(cx)
+                .as_rope()
+                .clone()
+        });
+
+        let store = self.store.clone();
+        let executor = cx.background_executor().clone();
+
+        answer_editor.next_answer_to_save = Some((details, body));
+        if answer_editor.pending_save.is_none() {
+            answer_editor.pending_save = Some(cx.spawn_in(window, async move |this, cx| {
+                async move {
+                    loop {
+                        let next_answer = this.update(cx, |this, _| {
+                            this.answer_editors
+                                .get_mut(&answer_id)?
+                                .next_answer_to_save
+                                .take()
+                        })?;
+
+                        if let Some((details, body)) = next_answer {
+                            cx.update(|_window, cx| {
+                                store.update(cx, |store, cx| {
+                                    store.save(answer_id, details, body, cx)
+                                })
+                            })?
+                            .await
+                            .log_err();
+                            this.update_in(cx, |this, window, cx| {
+                                this.picker
+                                    .update(cx, |picker, cx| picker.refresh(window, cx));
+                                cx.notify();
+                            })?;
+
+                            executor.timer(SAVE_THROTTLE).await;
+                        } else {
+                            break;
+                        }
+                    }
+
+                    this.update(cx, |this, _cx| {
+                        if let Some(answer_editor) = this.answer_editors.get_mut(&answer_id) {
+                            answer_editor.pending_save = None;
+                        }
+                    })
+                }
+                .log_err()
+                .await
+            }));
+        }
+        cx.notify();
+    }
+
+    pub fn delete_active_answer(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(active_answer_id) = self.active_answer_id {
+            self.delete_answer(active_answer_id, window, cx);
+        }
+    }
+
+    pub fn load_answer(
+        &mut self,
+        answer_id: AnswerId,
+        focus: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(answer_editor) = self.answer_editors.get(&answer_id) {
+            if focus {
+                answer_editor
+                    .body_editor
+                    .update(cx, |editor, cx| window.focus(&editor.focus_handle(cx)));
+            }
+            self.set_active_answer(Some(answer_id), window, cx);
+        } else if let Some(answer_metadata) = self.store.read(cx).metadata(answer_id) {
+            let language_registry = self.language_registry.clone();
+            let answer = self.store.read(cx).load(answer_id, cx);
+            self.pending_load = cx.spawn_in(window, async move |this, cx| {
+                let answer = answer.await;
+                let markdown = language_registry.language_for_name("Markdown").await;
+                this.update_in(cx, |this, window, cx| match answer {
+                    Ok(answer) => {
+                        let title_editor = cx.new(|cx| {
+                            let mut editor = Editor::auto_width(window, cx);
+                            editor.set_placeholder_text("Untitled", cx);
+                            editor.set_text(
+                                answer_metadata.title.clone().unwrap_or_default(),
+                                window,
+                                cx,
+                            );
+                            editor
+                        });
+                        let tags_editor = field_editor(
+                            "whs, safety, policy",
+                            answer_metadata.tags.join(", "),
+                            window,
+                            cx,
+                        );
+                        let approved_on_editor = field_editor(
+                            "YYYY-MM-DD",
+                            format_date(answer_metadata.approved_on),
+                            window,
+                            cx,
+                        );
+                        let expires_on_editor = field_editor(
+                            "YYYY-MM-DD",
+                            format_date(answer_metadata.expires_on),
+                            window,
+                            cx,
+                        );
+                        let body_editor = cx.new(|cx| {
+                            let buffer = cx.new(|cx| {
+                                let mut buffer = Buffer::local(answer, cx);
+                                buffer.set_language(markdown.log_err(), cx);
+                                buffer.set_language_registry(language_registry);
+                                buffer
+                            });
+
+                            let mut editor = Editor::for_buffer(buffer, None, window, cx);
+                            editor.set_soft_wrap_mode(SoftWrap::EditorWidth, cx);
+                            editor.set_show_gutter(false, cx);
+                            editor.set_show_wrap_guides(false, cx);
+                            editor.set_show_indent_guides(false, cx);
+                            editor.set_use_modal_editing(false);
+                            editor.set_current_line_highlight(Some(CurrentLineHighlight::None));
+                            if focus {
+                                window.focus(&editor.focus_handle(cx));
+                            }
+                            editor
+                        });
+                        let _subscriptions = [
+                            &title_editor,
+                            &tags_editor,
+                            &approved_on_editor,
+                            &expires_on_editor,
+                            &body_editor,
+                        ]
+                        .into_iter()
+                        .map(|editor| {
+                            cx.subscribe_in(
+                                editor,
+                                window,
+                                move |this, editor, event, window, cx| {
+                                    this.handle_answer_editor_event(
+                                        answer_id, editor, event, window, cx,
+                                    )
+                                },
+                            )
+                        })
+                        .collect();
+                        this.answer_editors.insert(
+                            answer_id,
+                            AnswerEditor {
+                                title_editor,
+                                tags_editor,
+                                approved_on_editor,
+                                expires_on_editor,
+                                body_editor,
+                                approved_on_is_valid: true,
+                                expires_on_is_valid: true,
+                                next_answer_to_save: None,
+                                pending_save: None,
+                                _subscriptions,
+                            },
+                        );
+                        this.set_active_answer(Some(answer_id), window, cx);
+                    }
+                    Err(error) => {
+                        // TODO: we should show the error in the UI.
+                        log::error!("error while loading answer: {:?}", error);
+                    }
+                })
+                .ok();
+            });
+        }
+    }
+
+    fn set_active_answer(
+        &mut self,
+        answer_id: Option<AnswerId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.active_answer_id = answer_id;
+        self.picker.update(cx, |picker, cx| {
+            if let Some(answer_id) = answer_id {
+                if picker
+                    .delegate
+                    .matches
+                    .get(picker.delegate.selected_index())
+                    .map_or(true, |old_selected_answer| {
+                        old_selected_answer.id != answer_id
+                    })
+                {
+                    if let Some(ix) = picker
+                        .delegate
+                        .matches
+                        .iter()
+                        .position(|mat| mat.id == answer_id)
+                    {
+                        picker.set_selected_index(ix, None, true, window, cx);
+                    }
+                }
+            } else {
+                picker.focus(window, cx);
+            }
+        });
+        cx.notify();
+    }
+
+    pub fn delete_answer(
+        &mut self,
+        answer_id: AnswerId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(metadata) = self.store.read(cx).metadata(answer_id) {
+            let confirmation = window.prompt(
+                PromptLevel::Warning,
+                &format!(
+                    "Are you sure you want to delete {}",
+                    metadata.display_title()
+                ),
+                None,
+                &["Delete", "Cancel"],
+                cx,
+            );
+
+            cx.spawn_in(window, async move |this, cx| {
+                if confirmation.await.ok() == Some(0) {
+                    this.update_in(cx, |this, window, cx| {
+                        if this.active_answer_id == Some(answer_id) {
+                            this.set_active_answer(None, window, cx);
+                        }
+                        this.answer_editors.remove(&answer_id);
+                        this.store
+                            .update(cx, |store, cx| store.delete(answer_id, cx))
+                            .detach_and_log_err(cx);
+                        this.picker
+                            .update(cx, |picker, cx| picker.refresh(window, cx));
+                        cx.notify();
+                    })?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    fn focus_active_answer(&mut self, _: &Tab, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(active_answer) = self.active_answer_id {
+            self.answer_editors[&active_answer]
+                .body_editor
+                .update(cx, |editor, cx| window.focus(&editor.focus_handle(cx)));
+            cx.stop_propagation();
+        }
+    }
+
+    fn focus_picker(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker
+            .update(cx, |picker, cx| picker.focus(window, cx));
+    }
+
+    fn handle_answer_editor_event(
+        &mut self,
+        answer_id: AnswerId,
+        editor: &Entity<Editor>,
+        event: &EditorEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            EditorEvent::BufferEdited => {
+                self.save_answer(answer_id, window, cx);
+            }
+            EditorEvent::Blurred => {
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, window, cx, |selections| {
+                        let cursor = selections.oldest_anchor().head();
+                        selections.select_anchor_ranges([cursor..cursor]);
+                    });
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn render_answer_list(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("answer-list")
+            .capture_action(cx.listener(Self::focus_active_answer))
+            .bg(cx.theme().colors().panel_background)
+            .h_full()
+            .px_1()
+            .w_1_3()
+            .overflow_x_hidden()
+            .child(
+                h_flex()
+                    .p(DynamicSpacing::Base04.rems(cx))
+                    .h_9()
+                    .w_full()
+                    .flex_none()
+                    .justify_end()
+                    .child(
+                        IconButton::new("new-answer", IconName::Plus)
+                            .style(ButtonStyle::Transparent)
+                            .shape(IconButtonShape::Square)
+                            .tooltip(move |window, cx| {
+                                Tooltip::for_action("New Answer", &NewAnswer, window, cx)
+                            })
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(Box::new(NewAnswer), cx);
+                            }),
+                    ),
+            )
+            .child(div().flex_grow().child(self.picker.clone()))
+    }
+
+    fn render_field(
+        &self,
+        label: &'static str,
+        editor: &Entity<Editor>,
+        error: Option<&'static str>,
+        cx: &App,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .gap_1()
+            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(if error.is_some() {
+                        cx.theme().status().error_border
+                    } else {
+                        cx.theme().colors().border_variant
+                    })
+                    .child(editor.clone()),
+            )
+            .children(
+                error.map(|error| Label::new(error).size(LabelSize::Small).color(Color::Error)),
+            )
+    }
+
+    fn render_active_answer(&mut self, cx: &mut Context<AnswerLibrary>) -> gpui::Stateful<Div> {
+        div()
+            .w_2_3()
+            .h_full()
+            .id("answer-editor")
+            .border_l_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_background)
+            .flex_none()
+            .min_w_64()
+            .children(self.active_answer_id.and_then(|answer_id| {
+                let answer_metadata = self.store.read(cx).metadata(answer_id)?;
+                let answer_editor = &self.answer_editors[&answer_id];
+                let focus_handle = answer_editor.body_editor.focus_handle(cx);
+                let settings = ThemeSettings::get_global(cx);
+                let today = today();
+                let expiry = answer_metadata.expiry(today);
+                let expiry_warning = answer_metadata.expiry_warning(today);
+                let used_variables = variables(&answer_editor.body_editor.read(cx).text(cx));
+                let date_error = "Use YYYY-MM-DD";
+
+                Some(
+                    v_flex()
+                        .id("answer-editor-inner")
+                        .size_full()
+                        .relative()
+                        .overflow_hidden()
+                        .pl(DynamicSpacing::Base16.rems(cx))
+                        .pt(DynamicSpacing::Base08.rems(cx))
+                        .on_click(cx.listener(move |_, _, window, _| {
+                            window.focus(&focus_handle);
+                        }))
+                        .child(
+                            h_flex()
+                                .group("active-editor-header")
+                                .pr(DynamicSpacing::Base16.rems(cx))
+                                .pt(DynamicSpacing::Base02.rems(cx))
+                                .pb(DynamicSpacing::Base08.rems(cx))
+                                .justify_between()
+                                .child(
+                                    div()
+                                        .max_w_80()
+                                        .border_1()
+                                        .border_color(transparent_black())
+                                        .rounded_sm()
+                                        .group_hover("active-editor-header", |this| {
+                                            this.border_color(cx.theme().colors().border_variant)
+                                        })
+                                        .child(EditorElement::new(
+                                            &answer_editor.title_editor,
+                                            EditorStyle {
+                                                background: cx.theme().system().transparent,
+                                                local_player: cx.theme().players().local(),
+                                                text: TextStyle {
+                                                    color: cx.theme().colors().editor_foreground,
+                                                    font_family: settings.ui_font.family.clone(),
+                                                    font_features: settings
+                                                        .ui_font
+                                                        .features
+                                                        .clone(),
+                                                    font_size: HeadlineSize::Large.rems().into(),
+                                                    font_weight: settings.ui_font.weight,
+                                                    line_height: relative(
+                                                        settings.buffer_line_height.value(),
+                                                    ),
+                                                    ..Default::default()
+                                                },
+                                                scrollbar_width: Pixels::ZERO,
+                                                syntax: cx.theme().syntax().clone(),
+                                                status: cx.theme().status().clone(),
+                                                inlay_hints_style: editor::make_inlay_hints_style(
+                                                    cx,
+                                                ),
+                                                inline_completion_styles:
+                                                    editor::make_suggestion_styles(cx),
+                                                ..EditorStyle::default()
+                                            },
+                                        )),
+                                )
+                                .child(
+                                    IconButton::new("delete-answer", IconName::Trash)
+                                        .size(ButtonSize::Large)
+                                        .style(ButtonStyle::Transparent)
+                                        .shape(IconButtonShape::Square)
+                                        .tooltip(move |window, cx| {
+                                            Tooltip::for_action(
+                                                "Delete Answer",
+                                                &DeleteAnswer,
+                                                window,
+                                                cx,
+                                            )
+                                        })
+                                        .on_click(|_, window, cx| {
+                                            window.dispatch_action(Box::new(DeleteAnswer), cx);
+                                        }),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .pr(DynamicSpacing::Base16.rems(cx))
+                                .pb(DynamicSpacing::Base08.rems(cx))
+                                .gap_2()
+                                .items_start()
+                                .child(self.render_field(
+                                    "Tags",
+                                    &answer_editor.tags_editor,
+                                    None,
+                                    cx,
+                                ))
+                                .child(self.render_field(
+                                    "Approved",
+                                    &answer_editor.approved_on_editor,
+                                    (!answer_editor.approved_on_is_valid).then_some(date_error),
+                                    cx,
+                                ))
+                                .child(self.render_field(
+                                    "Expires",
+                                    &answer_editor.expires_on_editor,
+                                    (!answer_editor.expires_on_is_valid).then_some(date_error),
+                                    cx,
+                                )),
+                        )
+                        .when_some(expiry_warning, |this, warning| {
+                            this.child(
+                                h_flex()
+                                    .mr(DynamicSpacing::Base16.rems(cx))
+                                    .mb(DynamicSpacing::Base08.rems(cx))
+                                    .px_2()
+                                    .py_1()
+                                    .gap_2()
+                                    .rounded_sm()
+                                    .bg(match expiry {
+                                        Expiry::Expired { .. } => {
+                                            cx.theme().status().error_background
+                                        }
+                                        _ => cx.theme().status().warning_background,
+                                    })
+                                    .child(
+                                        Icon::new(IconName::Warning)
+                                            .size(IconSize::Small)
+                                            .color(expiry_color(expiry)),
+                                    )
+                                    .child(Label::new(warning).size(LabelSize::Small)),
+                            )
+                        })
+                        .child(
+                            div()
+                                .pr(DynamicSpacing::Base16.rems(cx))
+                                .pb(DynamicSpacing::Base08.rems(cx))
+                                .child(
+                                    Label::new(variables_hint(&used_variables))
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        )
+                        .child(
+                            div()
+                                .on_action(cx.listener(Self::focus_picker))
+                                .flex_grow()
+                                .h_full()
+                                .child(answer_editor.body_editor.clone()),
+                        ),
+                )
+            }))
+    }
+}
+
+impl Render for AnswerLibrary {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let ui_font = theme::setup_ui_font(window, cx);
+        let theme = cx.theme().clone();
+
+        h_flex()
+            .id("answer-library")
+            .key_context("AnswerLibrary")
+            .on_action(cx.listener(|this, &NewAnswer, window, cx| this.new_answer(window, cx)))
+            .on_action(
+                cx.listener(|this, &DeleteAnswer, window, cx| {
+                    this.delete_active_answer(window, cx)
+                }),
+            )
+            .size_full()
+            .overflow_hidden()
+            .font(ui_font)
+            .text_color(theme.colors().text)
+            .child(self.render_answer_list(cx))
+            .map(|el| {
+                if self.store.read(cx).answer_count() == 0 {
+                    el.child(
+                        v_flex()
+                            .w_2_3()
+                            .h_full()
+                            .items_center()
+                            .justify_center()
+                            .gap_4()
+                            .bg(cx.theme().colors().editor_background)
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        Icon::new(IconName::Library)
+                                            .size(IconSize::Medium)
+                                            .color(Color::Muted),
+                                    )
+                                    .child(
+                                        Label::new("No answers yet")
+                                            .size(LabelSize::Large)
+                                            .color(Color::Muted),
+                                    ),
+                            )
+                            .child(
+                                v_flex()
+                                    .gap_1()
+                                    .child(Label::new("Save your first approved answer:"))
+                                    .child(
+                                        Button::new("create-answer", "New Answer")
+                                            .full_width()
+                                            .key_binding(KeyBinding::for_action(
+                                                &NewAnswer, window, cx,
+                                            ))
+                                            .on_click(|_, window, cx| {
+                                                window.dispatch_action(NewAnswer.boxed_clone(), cx)
+                                            }),
+                                    ),
+                            ),
+                    )
+                } else {
+                    el.child(self.render_active_answer(cx))
+                }
+            })
+    }
+}
+
+fn field_editor(
+    placeholder: &'static str,
+    text: String,
+    window: &mut Window,
+    cx: &mut Context<AnswerLibrary>,
+) -> Entity<Editor> {
+    cx.new(|cx| {
+        let mut editor = Editor::single_line(window, cx);
+        editor.set_placeholder_text(placeholder, cx);
+        editor.set_text(text, window, cx);
+        editor
+    })
+}
+
+fn expiry_color(expiry: Expiry) -> Color {
+    match expiry {
+        Expiry::Expired { .. } => Color::Error,
+        Expiry::ExpiresSoon { .. } | Expiry::Current => Color::Warning,
+    }
+}
+
+/// Lists the variables the answer uses that won't be filled from the
+/// manifest, or else the ones that can be.
+fn variables_hint(used_variables: &[String]) -> String {
+    let unknown = used_variables
+        .iter()
+        .filter(|name| !MANIFEST_VARIABLES.iter().any(|(known, _)| known == name))
+        .map(|name| format!("{{{{{name}}}}}"))
+        .collect::<Vec<_>>();
+    if unknown.is_empty() {
+        let known = MANIFEST_VARIABLES
+            .iter()
+            .map(|(name, _)| format!("{{{{{name}}}}}"))
+            .collect::<Vec<_>>();
+        format!("Filled from tender.toml: {}", known.join(", "))
+    } else {
+        format!(
+            "Not in tender.toml, so filled in by hand: {}",
+            unknown.join(", ")
+        )
+    }
+}
+
+fn format_date(date: Option<NaiveDate>) -> String {
+    date.map(|date| date.format(DATE_FORMATS[0]).to_string())
+        .unwrap_or_default()
+}
+
+/// Parses a date typed into one of the approval fields, which may be empty.
+fn parse_date(text: &str) -> Result<Option<NaiveDate>, ()> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .map(Some)
+        .ok_or(())
+}
+
+fn parse_tags(text: &str) -> Vec<SharedString> {
+    let mut tags = Vec::<SharedString>::new();
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(tag))
+        {
+            tags.push(tag.to_string().into());
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_date() {
+        let date = NaiveDate::from_ymd_opt(2027, 6, 30);
+        assert_eq!(parse_date("2027-06-30"), Ok(date));
+        assert_eq!(parse_date(" 30/06/2027 "), Ok(date));
+        assert_eq!(parse_date(""), Ok(None));
+        assert_eq!(parse_date("June 2027"), Err(()));
+        assert_eq!(format_date(date), "2027-06-30");
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(
+            parse_tags("WHS, safety,, policy , whs"),
+            ["WHS", "safety", "policy"]
+        );
+        assert!(parse_tags(" , ").is_empty());
+    }
+}
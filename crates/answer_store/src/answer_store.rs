@@ -0,0 +1,648 @@
+//! The answer library: approved boilerplate, like a WHS policy or insurance
+//! statement, that's reused from one tender to the next.
+//!
+//! Each answer records when it was approved and when it expires, so that
+//! out-of-date answers can be flagged before they're submitted, and may
+//! contain `{{client_name}}`-style variables that are filled from the
+//! tender's manifest when the answer is used.
+
+mod variables;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use collections::HashMap;
+use futures::FutureExt as _;
+use futures::future::Shared;
+use fuzzy::StringMatchCandidate;
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, Global, ReadGlobal, SharedString, Task,
+};
+use heed::{
+    Database, RoTxn,
+    types::{SerdeBincode, SerdeJson, Str},
+};
+use parking_lot::RwLock;
+use rope::Rope;
+use semantic_index::{Embedding, EmbeddingProvider, TextToEmbed};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    future::Future,
+    path::PathBuf,
+    sync::{Arc, atomic::AtomicBool},
+};
+use text::LineEnding;
+use uuid::Uuid;
+
+pub use variables::*;
+
+/// How many days before an answer expires it starts being flagged.
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Init starts loading the AnswerStore in the background and assigns
+/// a shared future to a global.
+pub fn init(cx: &mut App) {
+    let db_path = paths::answers_dir().join("answers-library-db.0.mdb");
+    let answer_store_task = AnswerStore::new(db_path, cx);
+    let answer_store_entity_task = cx
+        .spawn(async move |cx| {
+            answer_store_task
+                .await
+                .and_then(|answer_store| cx.new(|_cx| answer_store))
+                .map_err(Arc::new)
+        })
+        .shared();
+    cx.set_global(GlobalAnswerStore(answer_store_entity_task))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AnswerId(pub Uuid);
+
+impl AnswerId {
+    pub fn new() -> AnswerId {
+        AnswerId(Uuid::new_v4())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnswerMetadata {
+    pub id: AnswerId,
+    pub title: Option<SharedString>,
+    #[serde(default)]
+    pub tags: Vec<SharedString>,
+    pub approved_on: Option<NaiveDate>,
+    pub expires_on: Option<NaiveDate>,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Where an answer is in its approval period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expiry {
+    Current,
+    ExpiresSoon { days: i64 },
+    Expired { days: i64 },
+}
+
+impl AnswerMetadata {
+    pub fn expiry(&self, today: NaiveDate) -> Expiry {
+        let Some(expires_on) = self.expires_on else {
+            return Expiry::Current;
+        };
+        let days = (expires_on - today).num_days();
+        if days < 0 {
+            Expiry::Expired { days: -days }
+        } else if days <= EXPIRY_WARNING_DAYS {
+            Expiry::ExpiresSoon { days }
+        } else {
+            Expiry::Current
+        }
+    }
+
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        matches!(self.expiry(today), Expiry::Expired { .. })
+    }
+
+    /// A warning to show wherever the answer is used, if it has expired or
+    /// is about to.
+    pub fn expiry_warning(&self, today: NaiveDate) -> Option<String> {
+        let expires_on = self.expires_on?.format("%-d %b %Y");
+        match self.expiry(today) {
+            Expiry::Current => None,
+            Expiry::ExpiresSoon { days: 0 } => Some("Expires today".into()),
+            Expiry::ExpiresSoon { days: 1 } => Some(format!("Expires tomorrow, {expires_on}")),
+            Expiry::ExpiresSoon { days } => Some(format!("Expires in {days} days, {expires_on}")),
+            Expiry::Expired { .. } => Some(format!(
+                "Expired on {expires_on}. Check it's still accurate before submitting it"
+            )),
+        }
+    }
+
+    /// The title, or "Untitled" for answers that don't have one yet.
+    pub fn display_title(&self) -> SharedString {
+        self.title.clone().unwrap_or_else(|| "Untitled".into())
+    }
+}
+
+/// The current date, which answers' expiry is checked against.
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Describes what to check in an answer that's about to be reused: whether
+/// it's out of date, and any variables that couldn't be filled in.
+pub fn reuse_warning(
+    metadata: &AnswerMetadata,
+    filled: &FilledAnswer,
+    today: NaiveDate,
+) -> Option<String> {
+    let mut warnings = Vec::new();
+    if let Some(expiry_warning) = metadata.expiry_warning(today) {
+        warnings.push(format!(
+            "\"{}\": {expiry_warning}.",
+            metadata.display_title()
+        ));
+    }
+    if !filled.missing.is_empty() {
+        let variables = filled
+            .missing
+            .iter()
+            .map(|name| format!("{{{{{name}}}}}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        warnings.push(format!("Fill in {variables} by hand."));
+    }
+    (!warnings.is_empty()).then(|| warnings.join(" "))
+}
+
+/// The fields of an answer that are edited alongside its body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnswerDetails {
+    pub title: Option<SharedString>,
+    pub tags: Vec<SharedString>,
+    pub approved_on: Option<NaiveDate>,
+    pub expires_on: Option<NaiveDate>,
+}
+
+/// An answer found by [`AnswerStore::semantic_search`].
+#[derive(Clone, Debug)]
+pub struct AnswerMatch {
+    pub metadata: AnswerMetadata,
+    pub score: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEmbedding {
+    digest: [u8; 32],
+    embedding: Embedding,
+}
+
+pub struct AnswerStore {
+    env: heed::Env,
+    metadata_cache: RwLock<MetadataCache>,
+    metadata: Database<SerdeJson<AnswerId>, SerdeJson<AnswerMetadata>>,
+    bodies: Database<SerdeJson<AnswerId>, Str>,
+    embeddings: Database<SerdeJson<AnswerId>, SerdeBincode<StoredEmbedding>>,
+}
+
+pub struct AnswersUpdatedEvent;
+
+impl EventEmitter<AnswersUpdatedEvent> for AnswerStore {}
+
+#[derive(Default)]
+struct MetadataCache {
+    metadata: Vec<AnswerMetadata>,
+    metadata_by_id: HashMap<AnswerId, AnswerMetadata>,
+}
+
+impl MetadataCache {
+    fn from_db(
+        db: Database<SerdeJson<AnswerId>, SerdeJson<AnswerMetadata>>,
+        txn: &RoTxn,
+    ) -> Result<Self> {
+        let mut cache = MetadataCache::default();
+        for result in db.iter(txn)? {
+            let (answer_id, metadata) = result?;
+            cache.metadata.push(metadata.clone());
+            cache.metadata_by_id.insert(answer_id, metadata);
+        }
+        cache.sort();
+        Ok(cache)
+    }
+
+    fn insert(&mut self, metadata: AnswerMetadata) {
+        self.metadata_by_id.insert(metadata.id, metadata.clone());
+        if let Some(old_metadata) = self.metadata.iter_mut().find(|m| m.id == metadata.id) {
+            *old_metadata = metadata;
+        } else {
+            self.metadata.push(metadata);
+        }
+        self.sort();
+    }
+
+    fn remove(&mut self, id: AnswerId) {
+        self.metadata.retain(|metadata| metadata.id != id);
+        self.metadata_by_id.remove(&id);
+    }
+
+    fn sort(&mut self) {
+        self.metadata.sort_unstable_by(|a, b| {
+            a.title
+                .cmp(&b.title)
+                .then_with(|| b.saved_at.cmp(&a.saved_at))
+        });
+    }
+}
+
+impl AnswerStore {
+    pub fn global(cx: &App) -> impl Future<Output = Result<Entity<Self>>> + use<> {
+        let store = GlobalAnswerStore::global(cx).0.clone();
+        async move { store.await.map_err(|err| anyhow!(err)) }
+    }
+
+    /// Returns the store if it has finished loading.
+    pub fn try_global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalAnswerStore>()?
+            .0
+            .peek()?
+            .as_ref()
+            .ok()
+            .cloned()
+    }
+
+    pub fn new(db_path: PathBuf, cx: &App) -> Task<Result<Self>> {
+        cx.background_spawn(async move {
+            std::fs::create_dir_all(&db_path)?;
+
+            let db_env = unsafe {
+                heed::EnvOpenOptions::new()
+                    .map_size(1024 * 1024 * 1024) // 1GB
+                    .max_dbs(3) // Metadata, bodies and embeddings
+                    .open(db_path)?
+            };
+
+            let mut txn = db_env.write_txn()?;
+            let metadata = db_env.create_database(&mut txn, Some("metadata"))?;
+            let bodies = db_env.create_database(&mut txn, Some("bodies"))?;
+            let embeddings = db_env.create_database(&mut txn, Some("embeddings"))?;
+            txn.commit()?;
+
+            let txn = db_env.read_txn()?;
+            let metadata_cache = MetadataCache::from_db(metadata, &txn)?;
+            txn.commit()?;
+
+            Ok(AnswerStore {
+                env: db_env,
+                metadata_cache: RwLock::new(metadata_cache),
+                metadata,
+                bodies,
+                embeddings,
+            })
+        })
+    }
+
+    pub fn load(&self, id: AnswerId, cx: &App) -> Task<Result<String>> {
+        let env = self.env.clone();
+        let bodies = self.bodies;
+        cx.background_spawn(async move {
+            let txn = env.read_txn()?;
+            let mut answer = bodies
+                .get(&txn, &id)?
+                .ok_or_else(|| anyhow!("answer not found"))?
+                .into();
+            LineEnding::normalize(&mut answer);
+            Ok(answer)
+        })
+    }
+
+    pub fn all_answer_metadata(&self) -> Vec<AnswerMetadata> {
+        self.metadata_cache.read().metadata.clone()
+    }
+
+    /// Returns the number of answers in the store.
+    pub fn answer_count(&self) -> usize {
+        self.metadata_cache.read().metadata.len()
+    }
+
+    pub fn metadata(&self, id: AnswerId) -> Option<AnswerMetadata> {
+        self.metadata_cache.read().metadata_by_id.get(&id).cloned()
+    }
+
+    pub fn first(&self) -> Option<AnswerMetadata> {
+        self.metadata_cache.read().metadata.first().cloned()
+    }
+
+    pub fn delete(&self, id: AnswerId, cx: &Context<Self>) -> Task<Result<()>> {
+        self.metadata_cache.write().remove(id);
+
+        let db_connection = self.env.clone();
+        let bodies = self.bodies;
+        let metadata = self.metadata;
+        let embeddings = self.embeddings;
+
+        let task = cx.background_spawn(async move {
+            let mut txn = db_connection.write_txn()?;
+
+            metadata.delete(&mut txn, &id)?;
+            bodies.delete(&mut txn, &id)?;
+            embeddings.delete(&mut txn, &id)?;
+
+            txn.commit()?;
+            anyhow::Ok(())
+        });
+
+        cx.spawn(async move |this, cx| {
+            task.await?;
+            this.update(cx, |_, cx| cx.emit(AnswersUpdatedEvent)).ok();
+            anyhow::Ok(())
+        })
+    }
+
+    /// Fuzzy-matches the query against answers' titles and tags.
+    pub fn search(
+        &self,
+        query: String,
+        cancellation_flag: Arc<AtomicBool>,
+        cx: &App,
+    ) -> Task<Vec<AnswerMetadata>> {
+        let cached_metadata = self.metadata_cache.read().metadata.clone();
+        let executor = cx.background_executor().clone();
+        cx.background_spawn(async move {
+            if query.is_empty() {
+                return cached_metadata;
+            }
+
+            let candidates = cached_metadata
+                .iter()
+                .enumerate()
+                .map(|(ix, metadata)| {
+                    let mut text = metadata.title.as_deref().unwrap_or_default().to_string();
+                    for tag in &metadata.tags {
+                        text.push(' ');
+                        text.push_str(tag);
+                    }
+                    StringMatchCandidate::new(ix, &text)
+                })
+                .collect::<Vec<_>>();
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                100,
+                &cancellation_flag,
+                executor,
+            )
+            .await;
+            matches
+                .into_iter()
+                .map(|mat| cached_metadata[mat.candidate_id].clone())
+                .collect()
+        })
+    }
+
+    /// Ranks answers by how similar their meaning is to the query, using the
+    /// semantic index's embedding provider.
+    ///
+    /// Answers' embeddings are cached, and only recomputed once an answer
+    /// has been edited.
+    pub fn semantic_search(
+        &self,
+        query: String,
+        embedding_provider: Arc<dyn EmbeddingProvider>,
+        limit: usize,
+        cx: &App,
+    ) -> Task<Result<Vec<AnswerMatch>>> {
+        let cached_metadata = self.metadata_cache.read().metadata.clone();
+        let env = self.env.clone();
+        let bodies = self.bodies;
+        let embeddings = self.embeddings;
+        cx.background_spawn(async move {
+            if query.trim().is_empty() || cached_metadata.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let texts = {
+                let txn = env.read_txn()?;
+                cached_metadata
+                    .iter()
+                    .map(|metadata| {
+                        let body = bodies.get(&txn, &metadata.id)?.unwrap_or_default();
+                        Ok(embedding_text(metadata, body))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            };
+
+            let mut answer_embeddings = {
+                let txn = env.read_txn()?;
+                cached_metadata
+                    .iter()
+                    .zip(&texts)
+                    .map(|(metadata, text)| {
+                        let digest = TextToEmbed::new(text).digest;
+                        Ok(embeddings
+                            .get(&txn, &metadata.id)?
+                            .filter(|stored| stored.digest == digest)
+                            .map(|stored| stored.embedding))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            };
+
+            let stale = answer_embeddings
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, embedding)| embedding.is_none().then_some(ix))
+                .collect::<Vec<_>>();
+            for batch in stale.chunks(embedding_provider.batch_size().max(1)) {
+                let to_embed = batch
+                    .iter()
+                    .map(|ix| TextToEmbed::new(&texts[*ix]))
+                    .collect::<Vec<_>>();
+                let embedded = embedding_provider.embed(&to_embed).await?;
+
+                let mut txn = env.write_txn()?;
+                for ((ix, text), embedding) in batch.iter().zip(&to_embed).zip(embedded) {
+                    embeddings.put(
+                        &mut txn,
+                        &cached_metadata[*ix].id,
+                        &StoredEmbedding {
+                            digest: text.digest,
+                            embedding: embedding.clone(),
+                        },
+                    )?;
+                    answer_embeddings[*ix] = Some(embedding);
+                }
+                txn.commit()?;
+            }
+
+            let query_embedding = embedding_provider
+                .embed(&[TextToEmbed::new(&query)])
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow!("no embedding returned for the query"))?;
+            let answers = cached_metadata
+                .into_iter()
+                .zip(answer_embeddings)
+                .filter_map(|(metadata, embedding)| Some((metadata, embedding?)))
+                .collect();
+            Ok(rank_answers(&query_embedding, answers, limit))
+        })
+    }
+
+    pub fn save(
+        &self,
+        id: AnswerId,
+        details: AnswerDetails,
+        body: Rope,
+        cx: &Context<Self>,
+    ) -> Task<Result<()>> {
+        let answer_metadata = AnswerMetadata {
+            id,
+            title: details.title,
+            tags: details.tags,
+            approved_on: details.approved_on,
+            expires_on: details.expires_on,
+            saved_at: Utc::now(),
+        };
+        self.metadata_cache.write().insert(answer_metadata.clone());
+
+        let db_connection = self.env.clone();
+        let bodies = self.bodies;
+        let metadata = self.metadata;
+
+        let task = cx.background_spawn(async move {
+            let mut txn = db_connection.write_txn()?;
+
+            metadata.put(&mut txn, &id, &answer_metadata)?;
+            bodies.put(&mut txn, &id, &body.to_string())?;
+
+            txn.commit()?;
+
+            anyhow::Ok(())
+        });
+
+        cx.spawn(async move |this, cx| {
+            task.await?;
+            this.update(cx, |_, cx| cx.emit(AnswersUpdatedEvent)).ok();
+            anyhow::Ok(())
+        })
+    }
+}
+
+/// The text an answer's embedding is computed from.
+fn embedding_text(metadata: &AnswerMetadata, body: &str) -> String {
+    let mut text = metadata.title.as_deref().unwrap_or_default().to_string();
+    if !metadata.tags.is_empty() {
+        text.push('\n');
+        text.push_str(&metadata.tags.join(", "));
+    }
+    text.push_str("\n\n");
+    text.push_str(body);
+    text
+}
+
+fn rank_answers(
+    query_embedding: &Embedding,
+    answers: Vec<(AnswerMetadata, Embedding)>,
+    limit: usize,
+) -> Vec<AnswerMatch> {
+    let mut matches = answers
+        .into_iter()
+        .map(|(metadata, embedding)| AnswerMatch {
+            score: query_embedding
+                .similarity(std::slice::from_ref(&embedding))
+                .0,
+            metadata,
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    matches.truncate(limit);
+    matches
+}
+
+/// Wraps a shared future to an answer store so it can be assigned as a context global.
+pub struct GlobalAnswerStore(Shared<Task<Result<Entity<AnswerStore>, Arc<anyhow::Error>>>>);
+
+impl Global for GlobalAnswerStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn metadata(title: &str, expires_on: Option<NaiveDate>) -> AnswerMetadata {
+        AnswerMetadata {
+            id: AnswerId::new(),
+            title: Some(title.to_string().into()),
+            tags: Vec::new(),
+            approved_on: None,
+            expires_on,
+            saved_at: Utc::now(),
+        }
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_expiry() {
+        let today = date("2026-10-18");
+
+        let answer = metadata("Insurance", None);
+        assert_eq!(answer.expiry(today), Expiry::Current);
+        assert_eq!(answer.expiry_warning(today), None);
+
+        let answer = metadata("Insurance", Some(date("2027-06-30")));
+        assert_eq!(answer.expiry(today), Expiry::Current);
+
+        let answer = metadata("Insurance", Some(date("2026-10-30")));
+        assert_eq!(answer.expiry(today), Expiry::ExpiresSoon { days: 12 });
+        assert_eq!(
+            answer.expiry_warning(today).as_deref(),
+            Some("Expires in 12 days, 30 Oct 2026")
+        );
+
+        let answer = metadata("Insurance", Some(date("2026-10-18")));
+        assert_eq!(
+            answer.expiry_warning(today).as_deref(),
+            Some("Expires today")
+        );
+        assert!(!answer.is_expired(today));
+
+        let answer = metadata("Insurance", Some(date("2026-09-30")));
+        assert_eq!(answer.expiry(today), Expiry::Expired { days: 18 });
+        assert!(answer.is_expired(today));
+        assert_eq!(
+            answer.expiry_warning(today).as_deref(),
+            Some("Expired on 30 Sep 2026. Check it's still accurate before submitting it")
+        );
+    }
+
+    #[test]
+    fn test_reuse_warning() {
+        let today = date("2026-10-18");
+        let mut answer = metadata("Public liability insurance", Some(date("2027-06-30")));
+        let mut filled = FilledAnswer {
+            text: "We hold $20M cover.".into(),
+            missing: Vec::new(),
+        };
+        assert_eq!(reuse_warning(&answer, &filled, today), None);
+
+        answer.expires_on = Some(date("2026-09-30"));
+        filled.missing = vec!["broker".into(), "policy_number".into()];
+        assert_eq!(
+            reuse_warning(&answer, &filled, today).as_deref(),
+            Some(
+                "\"Public liability insurance\": Expired on 30 Sep 2026. Check it's still accurate \
+                 before submitting it. Fill in {{broker}}, {{policy_number}} by hand."
+            )
+        );
+    }
+
+    #[test]
+    fn test_rank_answers() {
+        let answers = vec![
+            (
+                metadata("WHS policy", None),
+                Embedding::new(vec![1., 0., 0.]),
+            ),
+            (
+                metadata("Quality accreditation", None),
+                Embedding::new(vec![0., 1., 0.]),
+            ),
+            (
+                metadata("Insurance", None),
+                Embedding::new(vec![0.2, 0.9, 0.1]),
+            ),
+        ];
+        let query = Embedding::new(vec![0.1, 1., 0.]);
+
+        let matches = rank_answers(&query, answers, 2);
+        let titles = matches
+            .iter()
+            .map(|mat| mat.metadata.display_title())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, ["Quality accreditation", "Insurance"]);
+        assert!(matches[0].score > matches[1].score);
+    }
+}
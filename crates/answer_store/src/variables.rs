@@ -0,0 +1,171 @@
+use collections::HashMap;
+use std::ops::Range;
+use tender_manifest::TenderManifest;
+
+/// The variables that are filled from the tender's manifest, with a
+/// description of each for the library's help text.
+pub const MANIFEST_VARIABLES: &[(&str, &str)] = &[
+    ("client_name", "The client issuing the tender"),
+    ("tender_title", "The tender's title"),
+    ("tender_reference", "The tender's reference number"),
+    ("closing_date", "The date submissions close"),
+    ("submission_portal", "Where the response is submitted"),
+];
+
+/// An answer's text with its variables filled in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilledAnswer {
+    pub text: String,
+    /// The variables there were no values for, which are left as they were
+    /// written.
+    pub missing: Vec<String>,
+}
+
+/// Returns the names of the `{{variable}}`s in an answer, in the order they
+/// first appear.
+pub fn variables(body: &str) -> Vec<String> {
+    let mut names = Vec::<String>::new();
+    for (_, name) in variable_ranges(body) {
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Replaces each `{{variable}}` in an answer with its value.
+pub fn fill_variables(body: &str, values: &HashMap<&str, String>) -> FilledAnswer {
+    let mut text = String::with_capacity(body.len());
+    let mut missing = Vec::<String>::new();
+    let mut last_end = 0;
+    for (range, name) in variable_ranges(body) {
+        let Some(value) = values.get(name) else {
+            if !missing.iter().any(|existing| existing == name) {
+                missing.push(name.to_string());
+            }
+            continue;
+        };
+        text.push_str(&body[last_end..range.start]);
+        text.push_str(value);
+        last_end = range.end;
+    }
+    text.push_str(&body[last_end..]);
+    FilledAnswer { text, missing }
+}
+
+/// The values of [`MANIFEST_VARIABLES`] for a tender.
+pub fn manifest_values(manifest: &TenderManifest) -> HashMap<&'static str, String> {
+    let mut values = HashMap::default();
+    if let Some(client) = &manifest.client {
+        values.insert("client_name", client.clone());
+    }
+    if let Some(title) = &manifest.title {
+        values.insert("tender_title", title.clone());
+    }
+    if let Some(reference) = &manifest.reference {
+        values.insert("tender_reference", reference.clone());
+    }
+    if let Some(closes_at) = manifest.closes_at {
+        values.insert("closing_date", closes_at.format("%-d %B %Y").to_string());
+    }
+    if let Some(portal) = &manifest.portal {
+        values.insert("submission_portal", portal.clone());
+    }
+    values
+}
+
+/// Fills an answer's variables from the tender's manifest, if the project
+/// has one.
+pub fn fill_from_manifest(body: &str, manifest: Option<&TenderManifest>) -> FilledAnswer {
+    let values = manifest.map(manifest_values).unwrap_or_default();
+    fill_variables(body, &values)
+}
+
+/// Finds each `{{name}}` in `body`, allowing whitespace inside the braces.
+/// Anything between braces that isn't a name is left alone.
+fn variable_ranges(body: &str) -> Vec<(Range<usize>, &str)> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = body[offset..].find("{{") {
+        let start = offset + start;
+        let Some(end) = body[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + end + 2;
+        let name = body[start + 2..end - 2].trim();
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '_')
+        {
+            ranges.push((start..end, name));
+            offset = end;
+        } else {
+            offset = start + 2;
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_variables() {
+        assert_eq!(
+            variables(
+                "{{client_name}} requires {{ insurance_level }}. {{client_name}} {{not a variable}} {{}}"
+            ),
+            ["client_name", "insurance_level"]
+        );
+        assert!(variables("No variables {here}.").is_empty());
+    }
+
+    #[test]
+    fn test_fill_variables() {
+        let values = HashMap::from_iter([("client_name", "City of Example".to_string())]);
+        assert_eq!(
+            fill_variables(
+                "We hold cover acceptable to {{client_name}}, naming {{ client_name }} and {{broker}}.",
+                &values
+            ),
+            FilledAnswer {
+                text: "We hold cover acceptable to City of Example, naming City of Example and {{broker}}."
+                    .into(),
+                missing: vec!["broker".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_fill_from_manifest() {
+        let manifest = TenderManifest {
+            client: Some("City of Example".into()),
+            reference: Some("RFT-2026-014".into()),
+            closes_at: Some(DateTime::parse_from_rfc3339("2026-11-03T14:00:00+10:00").unwrap()),
+            ..Default::default()
+        };
+        let body = "Our response to {{tender_reference}} for {{client_name}}, valid from {{closing_date}} at {{submission_portal}}.";
+
+        assert_eq!(
+            fill_from_manifest(body, Some(&manifest)),
+            FilledAnswer {
+                text: "Our response to RFT-2026-014 for City of Example, valid from 3 November 2026 at {{submission_portal}}."
+                    .into(),
+                missing: vec!["submission_portal".into()],
+            }
+        );
+        assert_eq!(
+            fill_from_manifest(body, None).missing,
+            [
+                "tender_reference",
+                "client_name",
+                "closing_date",
+                "submission_portal"
+            ]
+        );
+    }
+}
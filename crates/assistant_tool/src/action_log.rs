@@ -2,13 +2,34 @@ use anyhow::{Context as _, Result};
 use buffer_diff::BufferDiff;
 use collections::BTreeMap;
 use futures::{StreamExt, channel::mpsc};
-use gpui::{App, AppContext, AsyncApp, Context, Entity, Subscription, Task, WeakEntity};
+use gpui::{App, AppContext, AsyncApp, Context, Entity, Global, Subscription, Task, WeakEntity};
 use language::{Anchor, Buffer, BufferEvent, DiskState, Point, ToPoint};
 use project::{Project, ProjectItem, lsp_store::OpenLspBufferHandle};
 use std::{cmp, ops::Range, sync::Arc};
 use text::{Edit, Patch, Rope};
 use util::RangeExt;
 
+/// Saves tracked buffers that stand in for a file the project can't save as
+/// text, such as the cells of a spreadsheet, when their edits are rejected.
+pub trait TrackedBufferSaver {
+    fn can_save(&self, buffer: &Buffer, cx: &App) -> bool;
+
+    fn save(
+        &self,
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Result<()>>;
+}
+
+struct GlobalTrackedBufferSaver(Arc<dyn TrackedBufferSaver>);
+
+impl Global for GlobalTrackedBufferSaver {}
+
+pub fn set_tracked_buffer_saver(saver: impl TrackedBufferSaver + 'static, cx: &mut App) {
+    cx.set_global(GlobalTrackedBufferSaver(Arc::new(saver)));
+}
+
 /// Tracks actions performed by tools in a thread
 pub struct ActionLog {
     /// Buffers that we want to notify the model about when they change.
@@ -398,8 +419,7 @@ impl ActionLog {
                         }
                         buffer.end_transaction(cx);
                     });
-                    self.project
-                        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+                    self.save_buffer(buffer.clone(), cx)
                 } else {
                     buffer
                         .read(cx)
@@ -419,9 +439,7 @@ impl ActionLog {
                 buffer.update(cx, |buffer, cx| {
                     buffer.set_text(tracked_buffer.base_text.to_string(), cx)
                 });
-                let save = self
-                    .project
-                    .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx));
+                let save = self.save_buffer(buffer.clone(), cx);
 
                 // Clear all tracked changes for this buffer and start over as if we just read it.
                 self.tracked_buffers.remove(&buffer);
@@ -480,12 +498,24 @@ impl ActionLog {
 
                     buffer.edit(edits_to_revert, None, cx);
                 });
-                self.project
-                    .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                self.save_buffer(buffer, cx)
             }
         }
     }
 
+    fn save_buffer(&self, buffer: Entity<Buffer>, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let saver = cx
+            .try_global::<GlobalTrackedBufferSaver>()
+            .map(|saver| saver.0.clone())
+            .filter(|saver| saver.can_save(buffer.read(cx), cx));
+        match saver {
+            Some(saver) => saver.save(buffer, self.project.clone(), cx),
+            None => self
+                .project
+                .update(cx, |project, cx| project.save_buffer(buffer, cx)),
+        }
+    }
+
     pub fn keep_all_edits(&mut self, cx: &mut Context<Self>) {
         self.tracked_buffers
             .retain(|_buffer, tracked_buffer| match tracked_buffer.status {
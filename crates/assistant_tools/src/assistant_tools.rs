@@ -1,9 +1,11 @@
+mod compare_documents_tool;
 mod copy_path_tool;
 mod create_directory_tool;
 mod delete_path_tool;
 mod diagnostics_tool;
 mod edit_agent;
 mod edit_file_tool;
+mod extract_requirements_tool;
 mod fetch_tool;
 mod find_path_tool;
 mod grep_tool;
@@ -13,7 +15,9 @@ mod now_tool;
 mod open_tool;
 mod read_file_tool;
 mod schema;
+mod score_response_tool;
 mod semantic_search_tool;
+mod spreadsheet_tool;
 mod templates;
 mod terminal_tool;
 mod thinking_tool;
@@ -32,16 +36,20 @@ use web_search_tool::WebSearchTool;
 
 pub(crate) use templates::*;
 
+use crate::compare_documents_tool::CompareDocumentsTool;
 use crate::create_directory_tool::CreateDirectoryTool;
 use crate::delete_path_tool::DeletePathTool;
 use crate::diagnostics_tool::DiagnosticsTool;
 use crate::edit_file_tool::EditFileTool;
+use crate::extract_requirements_tool::ExtractRequirementsTool;
 use crate::fetch_tool::FetchTool;
 use crate::find_path_tool::FindPathTool;
 use crate::grep_tool::GrepTool;
 use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
+use crate::score_response_tool::ScoreResponseTool;
 use crate::semantic_search_tool::SemanticSearchTool;
+use crate::spreadsheet_tool::SpreadsheetTool;
 use crate::thinking_tool::ThinkingTool;
 
 pub use edit_file_tool::EditFileToolInput;
@@ -70,6 +78,10 @@ pub fn init(http_client: Arc<HttpClientWithUrl>, cx: &mut App) {
     registry.register_tool(FetchTool::new(http_client));
     registry.register_tool(EditFileTool);
     registry.register_tool(SemanticSearchTool);
+    registry.register_tool(ExtractRequirementsTool);
+    registry.register_tool(CompareDocumentsTool);
+    registry.register_tool(ScoreResponseTool);
+    registry.register_tool(SpreadsheetTool);
 
     register_web_search_tool(&LanguageModelRegistry::global(cx), cx);
     cx.subscribe(
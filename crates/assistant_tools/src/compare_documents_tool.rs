@@ -0,0 +1,105 @@
+use crate::schema::json_schema_for;
+use anyhow::{Result, anyhow};
+use assistant_tool::{ActionLog, Tool, ToolResult};
+use document_compare::compare_project_documents;
+use gpui::{AnyWindowHandle, App, AppContext as _, Entity, Task};
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use ui::IconName;
+use util::markdown::MarkdownInlineCode;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CompareDocumentsToolInput {
+    /// The earlier version of the document.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    ///
+    /// <example>
+    /// If the project has the following root directories:
+    ///
+    /// - lorem
+    /// - ipsum
+    ///
+    /// If you wanna compare `rft.pdf` in `ipsum`, you should use the path `ipsum/rft.pdf`.
+    /// </example>
+    pub old_path: String,
+    /// The later version of the document, in the same form as `old_path`.
+    pub new_path: String,
+}
+
+pub struct CompareDocumentsTool;
+
+impl Tool for CompareDocumentsTool {
+    fn name(&self) -> String {
+        "compare_documents".into()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &App) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./compare_documents_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Diff
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        json_schema_for::<CompareDocumentsToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<CompareDocumentsToolInput>(input.clone()) {
+            Ok(input) => format!(
+                "Compare {} with {}",
+                MarkdownInlineCode(&input.old_path),
+                MarkdownInlineCode(&input.new_path)
+            ),
+            Err(_) => "Compare documents".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _request: Arc<LanguageModelRequest>,
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        _model: Arc<dyn LanguageModel>,
+        _window: Option<AnyWindowHandle>,
+        cx: &mut App,
+    ) -> ToolResult {
+        let input = match serde_json::from_value::<CompareDocumentsToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))).into(),
+        };
+
+        let mut project_paths = Vec::new();
+        for path in [&input.old_path, &input.new_path] {
+            let Some(project_path) = project.read(cx).find_project_path(path, cx) else {
+                return Task::ready(Err(anyhow!("Could not find path {path} in project"))).into();
+            };
+            project_paths.push(project_path);
+        }
+
+        let comparison =
+            compare_project_documents(&project, &project_paths[0], &project_paths[1], cx);
+        cx.background_spawn(async move {
+            let comparison = comparison.await?;
+            Ok(format!(
+                "Changes from {} to {}:\n\n{}",
+                input.old_path,
+                input.new_path,
+                comparison.to_markdown()
+            )
+            .into())
+        })
+        .into()
+    }
+}
@@ -26,10 +26,12 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use std::{
+    fmt::Write as _,
     path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
+use tender_manifest::TenderManifestStore;
 use theme::ThemeSettings;
 use ui::{Disclosure, Tooltip, prelude::*};
 use util::ResultExt;
@@ -180,7 +182,12 @@ impl Tool for EditFileTool {
 
         let card_clone = card.clone();
         let task = cx.spawn(async move |cx: &mut AsyncApp| {
-            let edit_agent = EditAgent::new(model, project.clone(), action_log, Templates::new());
+            let edit_agent = EditAgent::new(
+                model,
+                project.clone(),
+                action_log.clone(),
+                Templates::new(),
+            );
 
             let buffer = project
                 .update(cx, |project, cx| {
@@ -251,6 +258,66 @@ impl Tool for EditFileTool {
             }
             let agent_output = output.await?;
 
+            // Evaluators discard text beyond a section's limit, so an edit
+            // that pushes a section past one is undone instead of saved.
+            let is_markdown = buffer.read_with(cx, |buffer, _| prose::is_markdown(buffer))?;
+            if is_markdown {
+                let proposed_snapshot = buffer.read_with(cx, |buffer, _cx| buffer.snapshot())?;
+                let (manifest, is_response) = cx.update(|cx| {
+                    let store = TenderManifestStore::for_project(&project, cx);
+                    let store = store.read(cx);
+                    (store.manifest(), store.is_response(&project_path))
+                })?;
+                let limit_breaches = cx
+                    .background_spawn({
+                        let old_text = old_text.clone();
+                        async move {
+                            tender_manifest::limit_breaches(
+                                &old_text,
+                                &proposed_snapshot.text(),
+                                manifest.as_deref(),
+                                is_response,
+                            )
+                        }
+                    })
+                    .await;
+                if !limit_breaches.is_empty() {
+                    let diff = buffer
+                        .read_with(cx, |buffer, cx| buffer.diff(old_text.clone(), cx))?
+                        .await;
+                    buffer.update(cx, |buffer, cx| buffer.apply_diff(diff, cx))?;
+                    action_log.update(cx, |log, cx| log.buffer_edited(buffer.clone(), cx))?;
+                    if exists {
+                        project
+                            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))?
+                            .await?;
+                    }
+                    if let Some(card) = card_clone {
+                        card.update(cx, |card, cx| {
+                            card.set_diff(
+                                project_path.path.clone(),
+                                old_text.clone(),
+                                old_text,
+                                cx,
+                            );
+                        })
+                        .log_err();
+                    }
+
+                    let mut message = format!(
+                        "The edit to {} was undone, because it puts text past a limit, and evaluators would discard it:\n",
+                        input.path.display()
+                    );
+                    for breach in &limit_breaches {
+                        writeln!(message, "- {}", breach.message())?;
+                    }
+                    message.push_str(
+                        "Make the edit again with these sections shortened until they're within their limits.",
+                    );
+                    return Err(anyhow!(message));
+                }
+            }
+
             project
                 .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))?
                 .await?;
@@ -260,11 +327,44 @@ impl Tool for EditFileTool {
                 let new_snapshot = new_snapshot.clone();
                 async move { new_snapshot.text() }
             });
-            let diff = cx.background_spawn(async move {
-                language::unified_diff(&old_snapshot.text(), &new_snapshot.text())
+            let diff = cx.background_spawn({
+                let new_snapshot = new_snapshot.clone();
+                async move { language::unified_diff(&old_snapshot.text(), &new_snapshot.text()) }
             });
             let (new_text, diff) = futures::join!(new_text, diff);
 
+            // Drafted claims should be grounded in the tender's sources, so
+            // flag any sentences this edit added without citing one.
+            let uncited_claims: Vec<String> = if is_markdown {
+                let (uncited, inserted) = cx
+                    .background_spawn({
+                        let old_text = old_text.clone();
+                        let new_text = new_text.clone();
+                        async move {
+                            (
+                                citations::uncited_sentences(&new_text),
+                                citations::uncited_insertions(&old_text, &new_text),
+                            )
+                        }
+                    })
+                    .await;
+                cx.update(|cx| {
+                    citations::flag_uncited_sentences(
+                        &buffer,
+                        &new_snapshot,
+                        &uncited,
+                        &inserted,
+                        cx,
+                    )
+                })?;
+                inserted
+                    .into_iter()
+                    .map(|range| new_text[range].to_string())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
             let output = EditFileToolOutput {
                 original_path: project_path.path.to_path_buf(),
                 new_text: new_text.clone(),
@@ -291,8 +391,20 @@ impl Tool for EditFileTool {
                     Ok("No edits were made.".to_string().into())
                 }
             } else {
+                let mut content = format!("Edited {}:\n\n```diff\n{}\n```", input_path, diff);
+                if !uncited_claims.is_empty() {
+                    content.push_str(
+                        "\n\nThese sentences make claims that no source is cited for:\n",
+                    );
+                    for sentence in &uncited_claims {
+                        writeln!(content, "- {sentence}")?;
+                    }
+                    content.push_str(
+                        "Cite the search results that support them, or remove them if nothing does.",
+                    );
+                }
                 Ok(ToolResultOutput {
-                    content: format!("Edited {}:\n\n```diff\n{}\n```", input_path, diff),
+                    content,
                     output: serde_json::to_value(output).ok(),
                 })
             }
@@ -851,8 +963,9 @@ async fn build_buffer_diff(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use fs::FakeFs;
+    use fs::{FakeFs, Fs as _};
     use gpui::TestAppContext;
+    use indoc::indoc;
     use language_model::fake_provider::FakeLanguageModel;
     use serde_json::json;
     use settings::SettingsStore;
@@ -894,6 +1007,71 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_edit_past_limit_is_undone(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                "tender.toml": indoc! {r#"
+                    client = "Department of Transport"
+                    reference = "DOT-2026-041"
+                    closes = 2026-11-14T14:00:00+11:00
+
+                    [limits]
+                    words = 5
+                "#},
+                "response": {
+                    "Response.md": "We deliver help desks.\n",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/tender").as_ref()], cx).await;
+        cx.update(|cx| TenderManifestStore::for_project(&project, cx));
+        cx.run_until_parked();
+
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let model = Arc::new(FakeLanguageModel::default());
+        let result = cx.update(|cx| {
+            let input = serde_json::to_value(EditFileToolInput {
+                display_description: "Expand the response".into(),
+                path: "tender/response/Response.md".into(),
+                create_or_overwrite: true,
+            })
+            .unwrap();
+            Arc::new(EditFileTool)
+                .run(
+                    input,
+                    Arc::default(),
+                    project.clone(),
+                    action_log,
+                    model.clone(),
+                    None,
+                    cx,
+                )
+                .output
+        });
+        cx.run_until_parked();
+        model.stream_last_completion_response("We deliver help desks across every state.\n".into());
+        model.end_last_completion_stream();
+
+        assert_eq!(
+            result.await.unwrap_err().to_string(),
+            "The edit to tender/response/Response.md was undone, because it puts text past a limit, and evaluators would discard it:\n\
+             - This document is 7 words, over its 5-word limit. Evaluators will discard the last 2 words.\n\
+             Make the edit again with these sections shortened until they're within their limits."
+        );
+        assert_eq!(
+            fs.load(path!("/tender/response/Response.md").as_ref())
+                .await
+                .unwrap(),
+            "We deliver help desks.\n"
+        );
+    }
+
     #[test]
     fn still_streaming_ui_text_with_path() {
         let input = json!({
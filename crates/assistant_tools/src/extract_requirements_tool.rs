@@ -0,0 +1,154 @@
+use crate::schema::json_schema_for;
+use anyhow::{Result, anyhow};
+use assistant_tool::{ActionLog, Tool, ToolResult};
+use gpui::{AnyWindowHandle, App, AppContext as _, Entity, Task};
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
+use project::Project;
+use requirements::{Requirement, extract_project_requirements};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use ui::IconName;
+use util::markdown::MarkdownInlineCode;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExtractRequirementsToolInput {
+    /// The tender documents to extract requirements from, in the order they
+    /// were issued. If empty, every tender document in the project's
+    /// documents folder is scanned.
+    ///
+    /// These paths should never be absolute, and the first component
+    /// of each path should always be a root directory in a project.
+    ///
+    /// <example>
+    /// If the project has the following root directories:
+    ///
+    /// - lorem
+    /// - ipsum
+    ///
+    /// If you wanna scan `rft.pdf` in `ipsum`, you should use the path `ipsum/rft.pdf`.
+    /// </example>
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+pub struct ExtractRequirementsTool;
+
+impl Tool for ExtractRequirementsTool {
+    fn name(&self) -> String {
+        "extract_requirements".into()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &App) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./extract_requirements_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::ListTree
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        json_schema_for::<ExtractRequirementsToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<ExtractRequirementsToolInput>(input.clone()) {
+            Ok(input) if input.paths.len() == 1 => {
+                format!(
+                    "Extract requirements from {}",
+                    MarkdownInlineCode(&input.paths[0])
+                )
+            }
+            Ok(input) if !input.paths.is_empty() => {
+                format!("Extract requirements from {} documents", input.paths.len())
+            }
+            _ => "Extract requirements".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _request: Arc<LanguageModelRequest>,
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        _model: Arc<dyn LanguageModel>,
+        _window: Option<AnyWindowHandle>,
+        cx: &mut App,
+    ) -> ToolResult {
+        let input = match serde_json::from_value::<ExtractRequirementsToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))).into(),
+        };
+
+        let paths = if input.paths.is_empty() {
+            None
+        } else {
+            let mut project_paths = Vec::new();
+            for path in &input.paths {
+                let Some(project_path) = project.read(cx).find_project_path(path, cx) else {
+                    return Task::ready(Err(anyhow!("Could not find path {path} in project")))
+                        .into();
+                };
+                project_paths.push(project_path);
+            }
+            Some(project_paths)
+        };
+
+        let extraction = extract_project_requirements(&project, paths, cx);
+        cx.background_spawn(async move {
+            let extraction = extraction.await?;
+            let mut output = extraction.summary_message();
+            output.push('\n');
+
+            let sections = [
+                ("Added", &extraction.summary.added),
+                ("Amended", &extraction.summary.amended),
+            ];
+            for (heading, ids) in sections {
+                if ids.is_empty() {
+                    continue;
+                }
+                writeln!(output, "\n## {heading}\n")?;
+                for requirement in ids
+                    .iter()
+                    .filter_map(|id| extraction.register.requirement(id))
+                {
+                    write_requirement(&mut output, requirement)?;
+                }
+            }
+
+            if !extraction.summary.skipped_documents.is_empty() {
+                writeln!(
+                    output,
+                    "\nSkipped unchanged documents: {}",
+                    extraction.summary.skipped_documents.join(", ")
+                )?;
+            }
+
+            Ok(output.into())
+        })
+        .into()
+    }
+}
+
+fn write_requirement(output: &mut String, requirement: &Requirement) -> std::fmt::Result {
+    write!(
+        output,
+        "- {} [{}]",
+        requirement.id,
+        requirement.priority.label()
+    )?;
+    if let Some(clause) = &requirement.clause {
+        write!(output, " clause {clause}")?;
+    }
+    write!(output, " ({}", requirement.source.path)?;
+    if let Some(page) = requirement.source.page {
+        write!(output, ", p. {page}")?;
+    }
+    writeln!(output, "): {}", requirement.text)
+}
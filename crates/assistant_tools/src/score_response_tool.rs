@@ -0,0 +1,91 @@
+use crate::schema::json_schema_for;
+use anyhow::{Result, anyhow};
+use assistant_tool::{ActionLog, Tool, ToolResult};
+use gpui::{AnyWindowHandle, App, AppContext as _, Entity, Task};
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use scorecard::score_project_response;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use ui::IconName;
+use util::markdown::MarkdownInlineCode;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScoreResponseToolInput {
+    /// The response document to score.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    ///
+    /// <example>
+    /// If the project has the following root directories:
+    ///
+    /// - lorem
+    /// - ipsum
+    ///
+    /// If you wanna score `response.md` in `ipsum`, you should use the path `ipsum/response.md`.
+    /// </example>
+    pub path: String,
+}
+
+pub struct ScoreResponseTool;
+
+impl Tool for ScoreResponseTool {
+    fn name(&self) -> String {
+        "score_response".into()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &App) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./score_response_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Star
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        json_schema_for::<ScoreResponseToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<ScoreResponseToolInput>(input.clone()) {
+            Ok(input) => format!("Score {}", MarkdownInlineCode(&input.path)),
+            Err(_) => "Score response".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _request: Arc<LanguageModelRequest>,
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        model: Arc<dyn LanguageModel>,
+        _window: Option<AnyWindowHandle>,
+        cx: &mut App,
+    ) -> ToolResult {
+        let input = match serde_json::from_value::<ScoreResponseToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))).into(),
+        };
+        let Some(project_path) = project.read(cx).find_project_path(&input.path, cx) else {
+            return Task::ready(Err(anyhow!(
+                "Could not find path {} in project",
+                input.path
+            )))
+            .into();
+        };
+
+        let scoring = score_project_response(&project, project_path, model, cx);
+        cx.background_spawn(async move {
+            let scoring = scoring.await?;
+            Ok(format!("Scorecard for {}:\n\n{}", input.path, scoring.to_markdown()).into())
+        })
+        .into()
+    }
+}
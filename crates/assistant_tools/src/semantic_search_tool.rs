@@ -1,6 +1,7 @@
 use crate::schema::json_schema_for;
 use anyhow::{Context as _, Result, anyhow};
 use assistant_tool::{ActionLog, Tool, ToolResult, ToolResultOutput};
+use citations::Citation;
 use gpui::{AnyWindowHandle, App, Entity, Task};
 use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
 use project::Project;
@@ -191,7 +192,10 @@ impl Tool for SemanticSearchTool {
             // Convert to output format
             let results: Vec<SearchResult> = loaded_results
                 .into_iter()
-                .map(|result| SearchResult {
+                .enumerate()
+                .map(|(ix, result)| SearchResult {
+                    citation: Citation::for_search_result(&result).to_markdown(ix + 1),
+                    is_clarification: clarifications::is_clarifications_path(&result.path),
                     file_path: result.path.to_string_lossy().to_string(),
                     excerpt: result.excerpt_content,
                     line_start: *result.row_range.start(),
@@ -208,10 +212,16 @@ impl Tool for SemanticSearchTool {
                 format!("{}:\n\n{}", 
                     message,
                     results.iter()
-                        .map(|r| format!("**{}** (lines {}-{}):\n{}\n", 
+                        .map(|r| format!("**{}** (lines {}-{}, cite as {}){}:\n{}\n", 
                             r.file_path, 
                             r.line_start, 
                             r.line_end,
+                            r.citation,
+                            if r.is_clarification {
+                                ", a published buyer clarification that overrides the tender documents"
+                            } else {
+                                ""
+                            },
                             r.excerpt))
                         .collect::<Vec<_>>()
                         .join("\n")
@@ -237,6 +247,11 @@ impl Tool for SemanticSearchTool {
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SearchResult {
     pub file_path: String,
+    /// A Markdown link citing the excerpt, for drafts that draw on it.
+    pub citation: String,
+    /// Whether the excerpt is from the clarification register, whose
+    /// answers take precedence over the tender documents.
+    pub is_clarification: bool,
     pub excerpt: String,
     pub line_start: u32,
     pub line_end: u32,
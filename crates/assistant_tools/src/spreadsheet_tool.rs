@@ -0,0 +1,232 @@
+use crate::schema::json_schema_for;
+use anyhow::{Result, anyhow};
+use assistant_tool::{ActionLog, Tool, ToolResult, ToolResultOutput};
+use gpui::{AnyWindowHandle, App, AppContext as _, AsyncApp, Entity, Task};
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use spreadsheet::{
+    Cell, CellAddress, CellRange, load_workbook, open_sheet, save_sheet_buffer, set_cell_values,
+};
+use std::{fmt::Write as _, sync::Arc};
+use ui::IconName;
+use util::markdown::MarkdownInlineCode;
+
+/// The most cells listed by one read, so that a large sheet doesn't fill the
+/// context window.
+const MAX_CELLS: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SpreadsheetToolInput {
+    /// The XLSX workbook to read or write.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    ///
+    /// <example>
+    /// If the project has the following root directories:
+    ///
+    /// - lorem
+    /// - ipsum
+    ///
+    /// If you wanna read `pricing.xlsx` in `ipsum`, you should use the path `ipsum/pricing.xlsx`.
+    /// </example>
+    pub path: String,
+    /// The name of the sheet. Defaults to the workbook's first sheet.
+    #[serde(default)]
+    pub sheet: Option<String>,
+    /// The cells to read, like `A1:F40`. Defaults to every cell in the sheet.
+    /// Ignored when writing.
+    #[serde(default)]
+    pub range: Option<String>,
+    /// The values to write. If empty, the cells are read instead.
+    #[serde(default)]
+    pub writes: Vec<CellWrite>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CellWrite {
+    /// The address of the cell, like `D12`.
+    pub cell: String,
+    /// The value to write. Numbers are stored as numbers, and an empty
+    /// value clears the cell.
+    pub value: String,
+}
+
+pub struct SpreadsheetTool;
+
+impl Tool for SpreadsheetTool {
+    fn name(&self) -> String {
+        "spreadsheet".into()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &App) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./spreadsheet_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Hash
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        json_schema_for::<SpreadsheetToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<SpreadsheetToolInput>(input.clone()) {
+            Ok(input) if input.writes.len() == 1 => {
+                format!("Write 1 cell in {}", MarkdownInlineCode(&input.path))
+            }
+            Ok(input) if !input.writes.is_empty() => format!(
+                "Write {} cells in {}",
+                input.writes.len(),
+                MarkdownInlineCode(&input.path)
+            ),
+            Ok(input) => format!("Read {}", MarkdownInlineCode(&input.path)),
+            Err(_) => "Read spreadsheet".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _request: Arc<LanguageModelRequest>,
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+        _model: Arc<dyn LanguageModel>,
+        _window: Option<AnyWindowHandle>,
+        cx: &mut App,
+    ) -> ToolResult {
+        let input = match serde_json::from_value::<SpreadsheetToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))).into(),
+        };
+        let Some(project_path) = project.read(cx).find_project_path(&input.path, cx) else {
+            return Task::ready(Err(anyhow!("Path {} not found in project", input.path))).into();
+        };
+
+        if input.writes.is_empty() {
+            read_cells(input, project_path, project, cx).into()
+        } else {
+            write_cells(input, project_path, project, action_log, cx).into()
+        }
+    }
+}
+
+fn read_cells(
+    input: SpreadsheetToolInput,
+    project_path: ProjectPath,
+    project: Entity<Project>,
+    cx: &mut App,
+) -> Task<Result<ToolResultOutput>> {
+    let range = match input.range.as_deref().map(CellRange::parse).transpose() {
+        Ok(range) => range,
+        Err(err) => return Task::ready(Err(err)),
+    };
+    let workbook = load_workbook(&project, &project_path, cx);
+    cx.background_spawn(async move {
+        let workbook = workbook.await?;
+        let sheet_names = workbook
+            .sheets
+            .iter()
+            .map(|sheet| sheet.name.as_str())
+            .collect::<Vec<_>>();
+        let sheet = match &input.sheet {
+            Some(name) => workbook.sheet(name).ok_or_else(|| {
+                anyhow!(
+                    "The workbook has no sheet named {name:?}. Its sheets are: {}",
+                    sheet_names.join(", ")
+                )
+            })?,
+            None => &workbook.sheets[0],
+        };
+
+        let mut output = format!("Sheet `{}` of `{}`", sheet.name, input.path);
+        if sheet.protected {
+            output.push_str(", which is protected");
+        }
+        if sheet_names.len() > 1 {
+            write!(output, ". The workbook's sheets are: {}", sheet_names.join(", ")).unwrap();
+        }
+        output.push_str(".\n\n");
+
+        let Some(range) = range.or_else(|| sheet.used_range()) else {
+            output.push_str("The sheet is empty.");
+            return Ok(output.into());
+        };
+        let mut cells = sheet.cells_in(range);
+        for cell in cells.by_ref().take(MAX_CELLS) {
+            output.push_str(&describe_cell(cell));
+            output.push('\n');
+        }
+        let remaining = cells.count();
+        if remaining > 0 {
+            write!(
+                output,
+                "\n{remaining} more cells in {range} weren't listed. Read a smaller range to see them."
+            )
+            .unwrap();
+        }
+        Ok(output.into())
+    })
+}
+
+fn describe_cell(cell: &Cell) -> String {
+    let mut description = format!("{}: {}", cell.address, cell.value);
+    match cell.formula.as_deref() {
+        Some("") => description.push_str(" (shared formula)"),
+        Some(formula) => write!(description, " (formula ={formula})").unwrap(),
+        None if cell.writable => description.push_str(" (writable)"),
+        None => {}
+    }
+    description
+}
+
+fn write_cells(
+    input: SpreadsheetToolInput,
+    project_path: ProjectPath,
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+    cx: &mut App,
+) -> Task<Result<ToolResultOutput>> {
+    let mut values = Vec::new();
+    for write in &input.writes {
+        match CellAddress::parse(&write.cell) {
+            Ok(address) => values.push((address, write.value.clone())),
+            Err(err) => return Task::ready(Err(err)),
+        }
+    }
+
+    let open = open_sheet(&project, &project_path, input.sheet, cx);
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        let open = open.await?;
+        let buffer = open.buffer.clone();
+        action_log.update(cx, |log, cx| log.buffer_read(buffer.clone(), cx))?;
+        buffer.update(cx, |buffer, cx| {
+            set_cell_values(buffer, &open.sheet, &values, cx)
+        })??;
+        action_log.update(cx, |log, cx| log.buffer_edited(buffer.clone(), cx))?;
+        cx.update(|cx| save_sheet_buffer(&buffer, &project, cx))?
+            .await?;
+
+        let mut output = format!(
+            "Wrote {} cells in sheet `{}` of `{}`:\n\n",
+            values.len(),
+            open.sheet.name,
+            input.path
+        );
+        for (address, value) in &values {
+            let previous = open
+                .sheet
+                .cell(*address)
+                .map_or("", |cell| cell.value.as_str());
+            writeln!(output, "{address}: {previous:?} -> {value:?}").unwrap();
+        }
+        Ok(output.into())
+    })
+}
@@ -0,0 +1,313 @@
+//! Citations tie drafted text back to the indexed source it was drawn from.
+//!
+//! The agent cites the excerpts returned by semantic search with Markdown
+//! links whose destination uses the `cite:` scheme, such as
+//! `[1](cite:tender/RFP.pdf#page=4&lines=120-134)`. The thread and the
+//! Markdown preview render these as chips that open the source, and sentences
+//! the agent inserts without one are flagged for review.
+
+mod verify;
+
+use anyhow::{Context as _, Result};
+use clarifications::{CLARIFICATIONS_FILE_NAME, ClarificationRegisterView, ClarificationStore};
+use document_viewer::{is_converted_document, open_document_viewer};
+use editor::Editor;
+use gpui::{App, Entity, Task, Window};
+use language::{Buffer, BufferSnapshot, Diagnostic, DiagnosticSeverity, Point};
+use project::{Project, ProjectPath};
+use prose::DiagnosticSource;
+use semantic_index::LoadedSearchResult;
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+use std::ops::{Range, RangeInclusive};
+use std::path::PathBuf;
+use workspace::Workspace;
+
+pub use verify::*;
+
+const SCHEME: &str = "cite:";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Citation {
+    /// The cited file, starting with the name of its worktree.
+    pub path: PathBuf,
+    /// The 1-based pages cited, for documents converted with page breaks.
+    pub pages: Option<RangeInclusive<u32>>,
+    /// The 1-based lines cited, in the file's text or its conversion.
+    pub lines: Option<RangeInclusive<u32>>,
+}
+
+impl Citation {
+    /// Cites the excerpt of a semantic search result.
+    pub fn for_search_result(result: &LoadedSearchResult) -> Self {
+        Self {
+            path: result.full_path.clone(),
+            pages: result.page_range.clone(),
+            lines: Some(result.row_range.start() + 1..=result.row_range.end() + 1),
+        }
+    }
+
+    pub fn is_citation(url: &str) -> bool {
+        url.starts_with(SCHEME)
+    }
+
+    pub fn parse(url: &str) -> Option<Self> {
+        let url = url.strip_prefix(SCHEME)?;
+        let (path, fragment) = url.split_once('#').unwrap_or((url, ""));
+        let path = urlencoding::decode(path).ok()?;
+        if path.is_empty() {
+            return None;
+        }
+
+        let mut citation = Self {
+            path: PathBuf::from(path.as_ref()),
+            pages: None,
+            lines: None,
+        };
+        for parameter in fragment.split('&') {
+            let Some((key, value)) = parameter.split_once('=') else {
+                continue;
+            };
+            match key {
+                "page" => citation.pages = parse_range(value),
+                "lines" => citation.lines = parse_range(value),
+                _ => {}
+            }
+        }
+        Some(citation)
+    }
+
+    pub fn to_url(&self) -> String {
+        let mut url = SCHEME.to_string();
+        for (ix, component) in self.path.iter().enumerate() {
+            if ix > 0 {
+                url.push('/');
+            }
+            url.push_str(&urlencoding::encode(&component.to_string_lossy()));
+        }
+        let mut separator = '#';
+        if let Some(pages) = &self.pages {
+            write!(url, "{separator}page={}", format_range(pages, "-")).ok();
+            separator = '&';
+        }
+        if let Some(lines) = &self.lines {
+            write!(url, "{separator}lines={}", format_range(lines, "-")).ok();
+        }
+        url
+    }
+
+    /// The citation as a numbered Markdown link.
+    pub fn to_markdown(&self, number: usize) -> String {
+        format!("[{number}]({})", self.to_url())
+    }
+
+    /// A description of the cited source, such as "RFP.pdf, page 4, lines
+    /// 120–134".
+    pub fn label(&self) -> String {
+        let mut label = self
+            .path
+            .file_name()
+            .unwrap_or(self.path.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        if let Some(pages) = &self.pages {
+            let noun = if pages.start() == pages.end() {
+                "page"
+            } else {
+                "pages"
+            };
+            write!(label, ", {noun} {}", format_range(pages, "–")).ok();
+        }
+        if let Some(lines) = &self.lines {
+            let noun = if lines.start() == lines.end() {
+                "line"
+            } else {
+                "lines"
+            };
+            write!(label, ", {noun} {}", format_range(lines, "–")).ok();
+        }
+        label
+    }
+
+    pub fn project_path(&self, project: &Entity<Project>, cx: &App) -> Option<ProjectPath> {
+        project.read(cx).find_project_path(&self.path, cx)
+    }
+}
+
+fn parse_range(value: &str) -> Option<RangeInclusive<u32>> {
+    let (start, end) = value.split_once('-').unwrap_or((value, value));
+    let start = start.trim().parse::<u32>().ok()?;
+    let end = end.trim().parse::<u32>().ok()?;
+    (start >= 1 && start <= end).then(|| start..=end)
+}
+
+fn format_range(range: &RangeInclusive<u32>, separator: &str) -> String {
+    if range.start() == range.end() {
+        range.start().to_string()
+    } else {
+        format!("{}{separator}{}", range.start(), range.end())
+    }
+}
+
+/// Opens the cited source, scrolled to the first cited line when it opens in
+/// an editor, or to the first cited page of a converted document.
+pub fn open_citation(
+    citation: &Citation,
+    workspace: &Entity<Workspace>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let project = workspace.read(cx).project().clone();
+    let Some(project_path) = citation.project_path(&project, cx) else {
+        return Task::ready(Err(anyhow::anyhow!(
+            "{} is not part of this project",
+            citation.path.display()
+        )));
+    };
+    if citation.path.file_name() == Some(OsStr::new(CLARIFICATIONS_FILE_NAME)) {
+        // The lines of a clarification citation are those of the register as
+        // indexed, so show the clarification in the register instead.
+        let store = ClarificationStore::for_project(&project, cx);
+        let focus_id = store.read(cx).register().and_then(|register| {
+            let line = *citation.lines.as_ref()?.start();
+            Some(register.clarification_at_line(line)?.id.clone())
+        });
+        workspace.update(cx, |workspace, cx| {
+            ClarificationRegisterView::open(workspace, focus_id, window, cx);
+        });
+        return Task::ready(Ok(()));
+    }
+
+    let target = citation
+        .lines
+        .as_ref()
+        .map(|lines| Point::new(lines.start() - 1, 0));
+    let page = citation.pages.as_ref().map(|pages| *pages.start());
+    if is_converted_document(&project_path.path) {
+        // Cited lines are lines of the converted text, which the viewer shows
+        // as is.
+        let open_task = workspace.update(cx, |workspace, cx| {
+            open_document_viewer(workspace, project_path, None, window, cx)
+        });
+        return window.spawn(cx, async move |cx| {
+            let viewer = open_task.await.context("failed to open cited source")?;
+            viewer.downgrade().update_in(cx, |viewer, window, cx| {
+                if let Some(target) = target {
+                    viewer.go_to_row(target.row, window, cx);
+                } else if let Some(page) = page {
+                    viewer.go_to_page(page, window, cx);
+                }
+            })?;
+            Ok(())
+        });
+    }
+
+    let open_task = workspace.update(cx, |workspace, cx| {
+        workspace.open_path(project_path, None, true, window, cx)
+    });
+    window.spawn(cx, async move |cx| {
+        let item = open_task.await.context("failed to open cited source")?;
+        if let Some((editor, target)) = item.downcast::<Editor>().zip(target) {
+            editor.downgrade().update_in(cx, |editor, window, cx| {
+                editor.go_to_singleton_buffer_point(target, window, cx);
+            })?;
+        }
+        Ok(())
+    })
+}
+
+/// Flags the sentences an edit inserted without a citation, keeping the flags
+/// on earlier insertions that still lack one.
+///
+/// `uncited` and `inserted` are the results of [`uncited_sentences`] and
+/// [`uncited_insertions`] for the text of `snapshot`.
+pub fn flag_uncited_sentences(
+    buffer: &Entity<Buffer>,
+    snapshot: &BufferSnapshot,
+    uncited: &[Range<usize>],
+    inserted: &[Range<usize>],
+    cx: &mut App,
+) {
+    let flagged =
+        prose::diagnostics_in_range(snapshot, DiagnosticSource::Citation, 0..snapshot.len())
+            .map(|entry| entry.range)
+            .collect::<Vec<_>>();
+    let diagnostics = uncited
+        .iter()
+        .filter(|range| {
+            inserted.contains(range)
+                || flagged
+                    .iter()
+                    .any(|flagged| flagged.start < range.end && range.start < flagged.end)
+        })
+        .map(|range| {
+            let diagnostic = Diagnostic {
+                severity: DiagnosticSeverity::WARNING,
+                message: "No citation supports this claim".to_string(),
+                ..Default::default()
+            };
+            (range.clone(), diagnostic)
+        })
+        .collect();
+    prose::publish_diagnostics(
+        buffer,
+        DiagnosticSource::Citation,
+        snapshot,
+        diagnostics,
+        cx,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_round_trip() {
+        let citation = Citation {
+            path: PathBuf::from("tender/Request for Tender.pdf"),
+            pages: Some(4..=5),
+            lines: Some(120..=134),
+        };
+        let url = citation.to_url();
+        assert_eq!(
+            url,
+            "cite:tender/Request%20for%20Tender.pdf#page=4-5&lines=120-134"
+        );
+        assert_eq!(Citation::parse(&url), Some(citation.clone()));
+        assert_eq!(
+            citation.label(),
+            "Request for Tender.pdf, pages 4–5, lines 120–134"
+        );
+
+        let citation = Citation {
+            path: PathBuf::from("tender/pricing.md"),
+            pages: None,
+            lines: Some(7..=7),
+        };
+        assert_eq!(
+            citation.to_markdown(2),
+            "[2](cite:tender/pricing.md#lines=7)"
+        );
+        assert_eq!(
+            Citation::parse("cite:tender/pricing.md#lines=7"),
+            Some(citation.clone())
+        );
+        assert_eq!(citation.label(), "pricing.md, line 7");
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        assert_eq!(Citation::parse("https://example.com"), None);
+        assert_eq!(Citation::parse("cite:"), None);
+        assert_eq!(
+            Citation::parse("cite:tender/RFP.pdf#page=0&lines=9-3&foo=bar"),
+            Some(Citation {
+                path: PathBuf::from("tender/RFP.pdf"),
+                pages: None,
+                lines: None,
+            })
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use crate::Citation;
+use prose::PARSE_OPTIONS;
+use pulldown_cmark::{Event, Parser, Tag};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Sentences shorter than this, such as "Yes." or a short lead-in, aren't
+/// expected to carry a citation.
+const MIN_CLAIM_WORDS: usize = 6;
+
+/// Returns the sentences of a Markdown document that make a claim without
+/// citing a source for it.
+///
+/// A citation supports the sentence it appears in, or the one before it when
+/// it's placed after the sentence's closing punctuation. Headings, tables,
+/// questions and sentences that introduce a list aren't claims.
+pub fn uncited_sentences(text: &str) -> Vec<Range<usize>> {
+    let mut citations = Vec::new();
+    let mut excluded = Vec::new();
+    for (event, range) in Parser::new_ext(text, PARSE_OPTIONS).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link { dest_url, .. }) if Citation::is_citation(&dest_url) => {
+                citations.push(range);
+            }
+            Event::Start(Tag::Heading { .. } | Tag::Table(_)) => excluded.push(range),
+            _ => {}
+        }
+    }
+
+    // Blank out citations so that the dots in their paths can't end a
+    // sentence, and soft line breaks so that wrapped lines don't either.
+    let mut masked = text.as_bytes().to_vec();
+    for range in &citations {
+        masked[range.clone()].fill(b' ');
+    }
+    for byte in &mut masked {
+        if matches!(byte, b'\n' | b'\r') {
+            *byte = b' ';
+        }
+    }
+    let Ok(masked) = String::from_utf8(masked) else {
+        return Vec::new();
+    };
+
+    let mut uncited = Vec::new();
+    for block in prose::prose_blocks(text) {
+        if excluded
+            .iter()
+            .any(|range| range.start <= block.range.start && block.range.end <= range.end)
+        {
+            continue;
+        }
+
+        // A block ends with its last text node, which may be the label of a
+        // trailing citation rather than the end of its link.
+        let start = block.range.start;
+        let mut end = block.range.end;
+        for citation in &citations {
+            if (start..=end).contains(&citation.start) {
+                end = end.max(citation.end);
+            }
+        }
+
+        for (offset, sentence) in masked[start..end].split_sentence_bound_indices() {
+            let sentence_start = start + offset;
+            let sentence_end = sentence_start + sentence.len();
+            let is_cited = citations
+                .iter()
+                .any(|citation| (sentence_start..sentence_end).contains(&citation.start));
+            if is_cited {
+                continue;
+            }
+
+            let trimmed = sentence.trim();
+            let is_claim = !trimmed.ends_with(['?', ':'])
+                && trimmed.unicode_words().count() >= MIN_CLAIM_WORDS;
+            if is_claim {
+                let leading = sentence.len() - sentence.trim_start().len();
+                uncited.push(sentence_start + leading..sentence_start + leading + trimmed.len());
+            }
+        }
+    }
+    uncited
+}
+
+/// Returns the uncited sentences of `new_text` that don't appear in
+/// `old_text`, so that an edit is only held to account for what it added.
+pub fn uncited_insertions(old_text: &str, new_text: &str) -> Vec<Range<usize>> {
+    uncited_sentences(new_text)
+        .into_iter()
+        .filter(|range| !old_text.contains(&new_text[range.clone()]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn uncited(text: &str) -> Vec<&str> {
+        uncited_sentences(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_uncited_sentences() {
+        let text = indoc! {"
+            # Our approach to transition and onboarding
+
+            We will complete transition within six weeks of award [1](cite:tender/RFP.pdf#page=4&lines=12-14).
+            Our team has delivered eleven similar transitions for state agencies.
+            The service desk operates around the clock from two locations. [2](cite:tender/Our%20Capability.md#lines=3)
+
+            Does the Principal require a parallel run?
+
+            Key milestones are as follows:
+
+            - Knowledge transfer completes in the second week of transition.
+            - Go live.
+
+            | Milestone | Week |
+            | --------- | ---- |
+            | Knowledge transfer from the incumbent provider completes | 2 |
+        "};
+        assert_eq!(
+            uncited(text),
+            [
+                "Our team has delivered eleven similar transitions for state agencies.",
+                "Knowledge transfer completes in the second week of transition.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrapped_sentences() {
+        let text = indoc! {"
+            The Supplier holds ISO 27001 certification
+            across all of its delivery centres [1](cite:tender/certs.pdf#page=2). It
+            renews the certification every three years through an accredited body.
+        "};
+        assert_eq!(
+            uncited(text),
+            ["It\nrenews the certification every three years through an accredited body."]
+        );
+    }
+
+    #[test]
+    fn test_uncited_insertions() {
+        let old_text = "Our team has delivered eleven similar transitions for state agencies.\n";
+        let new_text = indoc! {"
+            Our team has delivered eleven similar transitions for state agencies.
+
+            Each transition was completed ahead of its contracted schedule.
+        "};
+        assert_eq!(
+            uncited_insertions(old_text, new_text)
+                .into_iter()
+                .map(|range| &new_text[range])
+                .collect::<Vec<_>>(),
+            ["Each transition was completed ahead of its contracted schedule."]
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! The clarification register: the questions a bidder asks the buyer during a
+//! tender, and the answers the buyer publishes, recorded in a
+//! `clarifications.json` at the root of the tender project.
+//!
+//! Published answers can change what the tender documents ask for, so they
+//! override the documents wherever the two disagree. The register is given to
+//! the agent in full, ranked first in semantic search, and linked from the
+//! compliance panel to the requirements each answer modifies.
+
+mod register;
+mod register_view;
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, SharedString, Subscription, Task,
+    WeakEntity, actions,
+};
+use language::{Buffer, BufferEvent};
+use project::{Project, ProjectPath};
+use std::{path::Path, sync::Arc, time::Duration};
+use workspace::Workspace;
+
+pub use register::*;
+pub use register_view::ClarificationRegisterView;
+
+actions!(clarifications, [OpenClarifications, AddClarification]);
+
+/// The name of the register file, at the root of the project's worktree.
+pub const CLARIFICATIONS_FILE_NAME: &str = "clarifications.json";
+
+const PARSE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Default)]
+struct GlobalClarificationStores(HashMap<EntityId, Entity<ClarificationStore>>);
+
+impl Global for GlobalClarificationStores {}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, cx| {
+        ClarificationStore::for_project(workspace.project(), cx);
+        workspace.register_action(|workspace, _: &OpenClarifications, window, cx| {
+            ClarificationRegisterView::open(workspace, None, window, cx);
+        });
+        workspace.register_action(|workspace, _: &AddClarification, window, cx| {
+            ClarificationRegisterView::add(workspace, Vec::new(), window, cx);
+        });
+    })
+    .detach();
+}
+
+/// Whether `path` is a clarification register, relative to its worktree.
+pub fn is_clarifications_path(path: &Path) -> bool {
+    path == Path::new(CLARIFICATIONS_FILE_NAME)
+}
+
+/// Tracks the clarification register of a project, from the first visible
+/// worktree with a `clarifications.json` at its root.
+pub struct ClarificationStore {
+    project: WeakEntity<Project>,
+    path: Option<ProjectPath>,
+    buffer: Option<Entity<Buffer>>,
+    register: Option<Arc<ClarificationRegister>>,
+    error: Option<SharedString>,
+    load: Task<()>,
+    parse: Task<()>,
+    _buffer_subscription: Option<Subscription>,
+    _project_subscription: Subscription,
+}
+
+impl ClarificationStore {
+    /// Returns the store for `project`, creating it the first time.
+    pub fn for_project(project: &Entity<Project>, cx: &mut App) -> Entity<Self> {
+        let project_id = project.entity_id();
+        if let Some(store) = cx
+            .default_global::<GlobalClarificationStores>()
+            .0
+            .get(&project_id)
+        {
+            return store.clone();
+        }
+
+        let store = cx.new(|cx| Self::new(project, cx));
+        cx.default_global::<GlobalClarificationStores>()
+            .0
+            .insert(project_id, store.clone());
+        cx.observe_release(project, move |_, cx| {
+            cx.default_global::<GlobalClarificationStores>()
+                .0
+                .remove(&project_id);
+        })
+        .detach();
+        store
+    }
+
+    fn new(project: &Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.subscribe(project, |this, _, event, cx| match event {
+            project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                this.reload(cx)
+            }
+            project::Event::WorktreeUpdatedEntries(_, changes) => {
+                if changes
+                    .iter()
+                    .any(|(path, _, _)| is_clarifications_path(path))
+                {
+                    this.reload(cx);
+                }
+            }
+            _ => {}
+        });
+        let mut this = Self {
+            project: project.downgrade(),
+            path: None,
+            buffer: None,
+            register: None,
+            error: None,
+            load: Task::ready(()),
+            parse: Task::ready(()),
+            _buffer_subscription: None,
+            _project_subscription: subscription,
+        };
+        this.reload(cx);
+        this
+    }
+
+    /// The project's register. While the file is being edited by hand, this
+    /// is the last version that could be parsed.
+    pub fn register(&self) -> Option<Arc<ClarificationRegister>> {
+        self.register.clone()
+    }
+
+    /// The location of the register, if the project has one.
+    pub fn path(&self) -> Option<&ProjectPath> {
+        self.path.as_ref()
+    }
+
+    /// Why the register file couldn't be parsed, if it couldn't.
+    pub fn error(&self) -> Option<SharedString> {
+        self.error.clone()
+    }
+
+    /// Replaces the register and saves it, creating `clarifications.json` at
+    /// the root of the first worktree if the project doesn't have one yet.
+    pub fn save(
+        &mut self,
+        register: ClarificationRegister,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(project) = self.project.upgrade() else {
+            return Task::ready(Err(anyhow!("The project was closed")));
+        };
+        let path = self.path.clone().or_else(|| {
+            let worktree = project.read(cx).visible_worktrees(cx).next()?;
+            Some(ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: Path::new(CLARIFICATIONS_FILE_NAME).into(),
+            })
+        });
+        let Some(path) = path else {
+            return Task::ready(Err(anyhow!(
+                "Open a tender project to record clarifications"
+            )));
+        };
+
+        let json = register.to_json();
+        self.register = Some(Arc::new(register));
+        self.error = None;
+        cx.notify();
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(path, cx));
+        cx.spawn(async move |_, cx| {
+            let buffer = open_buffer
+                .await
+                .with_context(|| format!("Failed to open {CLARIFICATIONS_FILE_NAME}"))?;
+            buffer.update(cx, |buffer, cx| {
+                if buffer.text() != json {
+                    buffer.set_text(json, cx);
+                }
+            })?;
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer, cx))?
+                .await
+        })
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let path = project.read(cx).visible_worktrees(cx).find_map(|worktree| {
+            let worktree = worktree.read(cx);
+            worktree
+                .entry_for_path(CLARIFICATIONS_FILE_NAME)
+                .filter(|entry| entry.is_file())
+                .map(|entry| ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: entry.path.clone(),
+                })
+        });
+        if path == self.path && (path.is_none() || self.buffer.is_some()) {
+            return;
+        }
+
+        self.buffer = None;
+        self._buffer_subscription = None;
+        self.parse = Task::ready(());
+        self.path = path.clone();
+        let Some(path) = path else {
+            self.load = Task::ready(());
+            self.register = None;
+            self.error = None;
+            cx.notify();
+            return;
+        };
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(path, cx));
+        self.load = cx.spawn(async move |this, cx| {
+            let buffer = match open_buffer.await {
+                Ok(buffer) => buffer,
+                Err(error) => {
+                    log::error!("failed to open {CLARIFICATIONS_FILE_NAME}: {error:#}");
+                    return;
+                }
+            };
+            this.update(cx, |this, cx| {
+                this._buffer_subscription =
+                    Some(cx.subscribe(&buffer, |this, buffer, event, cx| {
+                        if matches!(event, BufferEvent::Edited | BufferEvent::Reloaded) {
+                            this.parse_buffer(buffer, Some(PARSE_DEBOUNCE), cx);
+                        }
+                    }));
+                this.buffer = Some(buffer.clone());
+                this.parse_buffer(buffer, None, cx);
+            })
+            .ok();
+        });
+    }
+
+    fn parse_buffer(
+        &mut self,
+        buffer: Entity<Buffer>,
+        debounce: Option<Duration>,
+        cx: &mut Context<Self>,
+    ) {
+        let text = buffer.read(cx).as_rope().clone();
+        self.parse = cx.spawn(async move |this, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+            let parsed = cx
+                .background_spawn(
+                    async move { ClarificationRegister::from_json(&text.to_string()) },
+                )
+                .await;
+            this.update(cx, |this, cx| {
+                match parsed {
+                    Ok(register) => {
+                        if this.register.as_deref() != Some(&register) {
+                            this.register = Some(Arc::new(register));
+                        }
+                        this.error = None;
+                    }
+                    Err(error) => {
+                        this.error = Some(
+                            format!("Failed to parse {CLARIFICATIONS_FILE_NAME}: {error}").into(),
+                        );
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::{FakeFs, Fs as _};
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_store_saves_register(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/tender"), json!({ "response.md": "" }))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/tender").as_ref()], cx).await;
+        let store = cx.update(|cx| ClarificationStore::for_project(&project, cx));
+        cx.run_until_parked();
+        assert_eq!(store.read_with(cx, |store, _| store.register()), None);
+
+        let mut register = ClarificationRegister::default();
+        let id = register.push(vec!["REQ-002".into()]);
+        register.clarifications[0].question = "Must reports be submitted monthly?".into();
+        store
+            .update(cx, |store, cx| store.save(register.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        let json = fs
+            .load(path!("/tender/clarifications.json").as_ref())
+            .await
+            .unwrap();
+        assert_eq!(ClarificationRegister::from_json(&json).unwrap(), register);
+        assert!(store.read_with(cx, |store, _| store.path().is_some()));
+
+        let buffer = store.read_with(cx, |store, _| store.buffer.clone().unwrap());
+        buffer.update(cx, |buffer, cx| {
+            let start = buffer.text().find("monthly").unwrap();
+            buffer.edit([(start..start + "monthly".len(), "weekly")], None, cx);
+        });
+        cx.executor().advance_clock(PARSE_DEBOUNCE);
+        cx.run_until_parked();
+        let register = store.read_with(cx, |store, _| store.register().unwrap());
+        assert_eq!(
+            register.clarification(&id).unwrap().question,
+            "Must reports be submitted weekly?"
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// The clarification register stored in `clarifications.json`: the questions
+/// asked of the buyer during the tender and the answers they published.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClarificationRegister {
+    #[serde(default)]
+    pub clarifications: Vec<Clarification>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clarification {
+    pub id: String,
+    /// The buyer's number for the question, if they gave it one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    pub question: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asked_on: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_on: Option<NaiveDate>,
+    /// The IDs of the requirements in the compliance register that the
+    /// answer modifies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requirement_ids: Vec<String>,
+}
+
+impl Clarification {
+    pub fn is_answered(&self) -> bool {
+        self.answer
+            .as_ref()
+            .is_some_and(|answer| !answer.trim().is_empty())
+    }
+
+    /// The clarification's ID, followed by the buyer's reference if it has
+    /// one, e.g. "QA-003 (Q12)".
+    pub fn display_id(&self) -> String {
+        match &self.reference {
+            Some(reference) => format!("{} ({reference})", self.id),
+            None => self.id.clone(),
+        }
+    }
+}
+
+impl ClarificationRegister {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).unwrap();
+        json.push('\n');
+        json
+    }
+
+    pub fn clarification(&self, id: &str) -> Option<&Clarification> {
+        self.clarifications
+            .iter()
+            .find(|clarification| clarification.id == id)
+    }
+
+    /// The clarifications whose answers modify the given requirement, most
+    /// recently published last.
+    pub fn for_requirement<'a>(
+        &'a self,
+        requirement_id: &'a str,
+    ) -> impl Iterator<Item = &'a Clarification> {
+        self.clarifications.iter().filter(move |clarification| {
+            clarification
+                .requirement_ids
+                .iter()
+                .any(|id| id == requirement_id)
+        })
+    }
+
+    /// Adds an unanswered clarification with a new ID, returning the ID.
+    pub fn push(&mut self, requirement_ids: Vec<String>) -> String {
+        let id = self.next_id();
+        self.clarifications.push(Clarification {
+            id: id.clone(),
+            requirement_ids,
+            ..Default::default()
+        });
+        id
+    }
+
+    fn next_id(&self) -> String {
+        let last = self
+            .clarifications
+            .iter()
+            .filter_map(|clarification| clarification.id.strip_prefix("QA-")?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("QA-{:03}", last + 1)
+    }
+
+    /// The answered clarifications as Markdown, for the language model and
+    /// the semantic index.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        for clarification in &self.clarifications {
+            let Some(answer) = clarification
+                .answer
+                .as_deref()
+                .map(str::trim)
+                .filter(|answer| !answer.is_empty())
+            else {
+                continue;
+            };
+            write!(markdown, "### {}", clarification.display_id()).unwrap();
+            if let Some(published_on) = clarification.published_on {
+                write!(markdown, ", published {published_on}").unwrap();
+            }
+            markdown.push_str("\n\n");
+            writeln!(
+                markdown,
+                "**Question:** {}\n",
+                clarification.question.trim()
+            )
+            .unwrap();
+            writeln!(markdown, "**Answer:** {answer}\n").unwrap();
+            if !clarification.requirement_ids.is_empty() {
+                writeln!(
+                    markdown,
+                    "Modifies {}.\n",
+                    clarification.requirement_ids.join(", ")
+                )
+                .unwrap();
+            }
+        }
+        markdown
+    }
+
+    /// The clarification shown at a 1-based line of [`Self::to_markdown`],
+    /// for following citations of the indexed register back to it.
+    pub fn clarification_at_line(&self, line: u32) -> Option<&Clarification> {
+        let markdown = self.to_markdown();
+        let heading = markdown
+            .lines()
+            .take(line as usize)
+            .filter_map(|line| line.strip_prefix("### "))
+            .last()?;
+        let id = heading.split([' ', ',']).next()?;
+        self.clarification(id)
+    }
+}
+
+/// Parses the requirement IDs typed into a clarification, separated by
+/// commas or spaces, ignoring case and duplicates.
+pub fn parse_requirement_ids(text: &str) -> Vec<String> {
+    let mut ids = Vec::<String>::new();
+    for id in text
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+    {
+        let id = id.to_uppercase();
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_register() {
+        let mut register = ClarificationRegister::from_json("").unwrap();
+        assert_eq!(register.push(vec!["REQ-002".into()]), "QA-001");
+        assert_eq!(register.push(Vec::new()), "QA-002");
+
+        let clarification = &mut register.clarifications[0];
+        clarification.reference = Some("Q12".into());
+        clarification.question = "Must reports be submitted monthly?".into();
+        clarification.answer = Some("Reports are due monthly and quarterly.".into());
+        clarification.published_on = NaiveDate::from_ymd_opt(2026, 10, 2);
+        register.clarifications[1].question = "Is a parallel run required?".into();
+
+        let register = ClarificationRegister::from_json(&register.to_json()).unwrap();
+        assert_eq!(
+            register
+                .for_requirement("REQ-002")
+                .map(|clarification| clarification.id.as_str())
+                .collect::<Vec<_>>(),
+            ["QA-001"]
+        );
+        assert_eq!(
+            register.to_markdown(),
+            indoc! {"
+                ### QA-001 (Q12), published 2026-10-02
+
+                **Question:** Must reports be submitted monthly?
+
+                **Answer:** Reports are due monthly and quarterly.
+
+                Modifies REQ-002.
+
+            "}
+        );
+        assert_eq!(
+            register
+                .clarification_at_line(5)
+                .map(|clarification| clarification.id.as_str()),
+            Some("QA-001")
+        );
+    }
+
+    #[test]
+    fn test_parse_requirement_ids() {
+        assert_eq!(
+            parse_requirement_ids("req-001, REQ-004;REQ-001  REQ-010"),
+            ["REQ-001", "REQ-004", "REQ-010"]
+        );
+        assert!(parse_requirement_ids(" , ").is_empty());
+    }
+}
@@ -0,0 +1,527 @@
+use crate::{
+    AddClarification, CLARIFICATIONS_FILE_NAME, Clarification, ClarificationRegister,
+    ClarificationStore, parse_requirement_ids,
+};
+use chrono::NaiveDate;
+use editor::{Editor, EditorEvent};
+use gpui::{App, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Subscription, Task};
+use language::language_settings::SoftWrap;
+use std::time::Duration;
+use ui::{Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::Workspace;
+use workspace::item::{Item, ItemEvent};
+
+/// The date formats accepted in the date columns, the first of which is used
+/// to show them.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d/%m/%Y"];
+
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The editors for one clarification in the register.
+struct Row {
+    id: String,
+    reference: Entity<Editor>,
+    question: Entity<Editor>,
+    answer: Entity<Editor>,
+    asked_on: Entity<Editor>,
+    published_on: Entity<Editor>,
+    requirement_ids: Entity<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// A table of the project's clarification register, in which questions and
+/// the buyer's answers are recorded and edited.
+pub struct ClarificationRegisterView {
+    store: Entity<ClarificationStore>,
+    /// The register as it was last loaded or saved by this view.
+    register: ClarificationRegister,
+    rows: Vec<Row>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    save: Task<()>,
+    _store_subscription: Subscription,
+}
+
+impl ClarificationRegisterView {
+    /// Opens the project's clarification register, reusing the view if it's
+    /// already open, and focuses the clarification with the given ID.
+    pub fn open(
+        workspace: &mut Workspace,
+        focus_id: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let view = match workspace.items_of_type::<Self>(cx).next() {
+            Some(existing) => {
+                workspace.activate_item(&existing, true, true, window, cx);
+                existing
+            }
+            None => {
+                let store = ClarificationStore::for_project(workspace.project(), cx);
+                let view = cx.new(|cx| Self::new(store, window, cx));
+                workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, window, cx);
+                view
+            }
+        };
+        if let Some(focus_id) = focus_id {
+            view.update(cx, |view, cx| {
+                view.focus_clarification(&focus_id, window, cx)
+            });
+        }
+        view
+    }
+
+    /// Opens the register with a new, unanswered clarification that modifies
+    /// the given requirements, ready for its question to be typed.
+    pub fn add(
+        workspace: &mut Workspace,
+        requirement_ids: Vec<String>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let view = Self::open(workspace, None, window, cx);
+        view.update(cx, |view, cx| {
+            view.add_clarification(requirement_ids, window, cx)
+        });
+    }
+
+    fn new(store: Entity<ClarificationStore>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.observe_in(&store, window, |this, store, window, cx| {
+            let register = store.read(cx).register().unwrap_or_default();
+            if *register != this.register {
+                this.set_register((*register).clone(), window, cx);
+            }
+            cx.notify();
+        });
+        let register = store.read(cx).register().unwrap_or_default();
+        let mut this = Self {
+            store,
+            register: ClarificationRegister::default(),
+            rows: Vec::new(),
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            save: Task::ready(()),
+            _store_subscription: subscription,
+        };
+        this.set_register((*register).clone(), window, cx);
+        this
+    }
+
+    fn set_register(
+        &mut self,
+        register: ClarificationRegister,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.rows = register
+            .clarifications
+            .iter()
+            .map(|clarification| Self::build_row(clarification, window, cx))
+            .collect();
+        self.register = register;
+    }
+
+    fn build_row(
+        clarification: &Clarification,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Row {
+        let reference = field_editor(
+            "Q1",
+            clarification.reference.clone().unwrap_or_default(),
+            window,
+            cx,
+        );
+        let question = text_editor(
+            "The question asked of the buyer",
+            clarification.question.clone(),
+            window,
+            cx,
+        );
+        let answer = text_editor(
+            "The buyer's published answer",
+            clarification.answer.clone().unwrap_or_default(),
+            window,
+            cx,
+        );
+        let asked_on = field_editor(
+            "YYYY-MM-DD",
+            format_date(clarification.asked_on),
+            window,
+            cx,
+        );
+        let published_on = field_editor(
+            "YYYY-MM-DD",
+            format_date(clarification.published_on),
+            window,
+            cx,
+        );
+        let requirement_ids = field_editor(
+            "REQ-001",
+            clarification.requirement_ids.join(", "),
+            window,
+            cx,
+        );
+
+        let _subscriptions = [
+            &reference,
+            &question,
+            &answer,
+            &asked_on,
+            &published_on,
+            &requirement_ids,
+        ]
+        .into_iter()
+        .map(|editor| {
+            cx.subscribe(editor, |this, _, event, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    this.schedule_save(cx);
+                }
+            })
+        })
+        .collect();
+
+        Row {
+            id: clarification.id.clone(),
+            reference,
+            question,
+            answer,
+            asked_on,
+            published_on,
+            requirement_ids,
+            _subscriptions,
+        }
+    }
+
+    /// The register as it stands in the editors. Dates that can't be parsed
+    /// keep their last valid value until they're corrected.
+    fn edited_register(&self, cx: &App) -> ClarificationRegister {
+        let clarifications = self
+            .rows
+            .iter()
+            .map(|row| {
+                let previous = self.register.clarification(&row.id);
+                let date = |editor: &Entity<Editor>, previous: Option<NaiveDate>| {
+                    parse_date(&editor.read(cx).text(cx)).unwrap_or(previous)
+                };
+                Clarification {
+                    id: row.id.clone(),
+                    reference: non_empty(row.reference.read(cx).text(cx)),
+                    question: row.question.read(cx).text(cx).trim().to_string(),
+                    answer: non_empty(row.answer.read(cx).text(cx)),
+                    asked_on: date(&row.asked_on, previous.and_then(|c| c.asked_on)),
+                    published_on: date(&row.published_on, previous.and_then(|c| c.published_on)),
+                    requirement_ids: parse_requirement_ids(&row.requirement_ids.read(cx).text(cx)),
+                }
+            })
+            .collect();
+        ClarificationRegister { clarifications }
+    }
+
+    fn schedule_save(&mut self, cx: &mut Context<Self>) {
+        self.save = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(SAVE_DEBOUNCE).await;
+            this.update(cx, |this, cx| this.save_now(cx)).ok();
+        });
+    }
+
+    fn save_now(&mut self, cx: &mut Context<Self>) {
+        let register = self.edited_register(cx);
+        if register == self.register {
+            return;
+        }
+        self.register = register.clone();
+        let save = self.store.update(cx, |store, cx| store.save(register, cx));
+        self.save = cx.spawn(async move |_, _| {
+            save.await.log_err();
+        });
+    }
+
+    fn add_clarification(
+        &mut self,
+        requirement_ids: Vec<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut register = self.edited_register(cx);
+        let id = register.push(requirement_ids);
+        self.set_register(register.clone(), window, cx);
+        let save = self.store.update(cx, |store, cx| store.save(register, cx));
+        self.save = cx.spawn(async move |_, _| {
+            save.await.log_err();
+        });
+        self.focus_clarification(&id, window, cx);
+        cx.notify();
+    }
+
+    fn delete_clarification(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let mut register = self.edited_register(cx);
+        if ix >= register.clarifications.len() {
+            return;
+        }
+        register.clarifications.remove(ix);
+        self.set_register(register.clone(), window, cx);
+        let save = self.store.update(cx, |store, cx| store.save(register, cx));
+        self.save = cx.spawn(async move |_, _| {
+            save.await.log_err();
+        });
+        cx.notify();
+    }
+
+    fn focus_clarification(&mut self, id: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.rows.iter().position(|row| row.id == id) else {
+            return;
+        };
+        self.scroll_handle.scroll_to_item(ix);
+        let row = &self.rows[ix];
+        let editor = if row.question.read(cx).text(cx).is_empty() {
+            &row.question
+        } else {
+            &row.answer
+        };
+        window.focus(&editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let answered = self
+            .register
+            .clarifications
+            .iter()
+            .filter(|clarification| clarification.is_answered())
+            .count();
+        let error = self.store.read(cx).error();
+
+        h_flex()
+            .justify_between()
+            .gap_4()
+            .child(
+                v_flex()
+                    .child(Headline::new("Clarifications").size(HeadlineSize::Small))
+                    .child(
+                        Label::new(format!(
+                            "{answered} of {} questions answered. Published answers override the tender documents.",
+                            self.register.clarifications.len()
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .when_some(error, |this, error| {
+                        this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+                    }),
+            )
+            .child(
+                Button::new("add-clarification", "Add Question")
+                    .icon(IconName::Plus)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|window, cx| {
+                        Tooltip::for_action("Add a Question", &AddClarification, window, cx)
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.add_clarification(Vec::new(), window, cx)
+                    })),
+            )
+    }
+
+    fn render_column_headings(&self) -> impl IntoElement {
+        let heading =
+            |label: &'static str| Label::new(label).size(LabelSize::Small).color(Color::Muted);
+        h_flex()
+            .gap_2()
+            .px_2()
+            .child(div().w_20().child(heading("ID")))
+            .child(div().w_16().child(heading("Ref")))
+            .child(div().flex_1().child(heading("Question")))
+            .child(div().flex_1().child(heading("Answer")))
+            .child(div().w_24().child(heading("Asked")))
+            .child(div().w_24().child(heading("Published")))
+            .child(div().w_32().child(heading("Modifies")))
+            .child(div().w_6())
+    }
+
+    fn render_row(&self, ix: usize, row: &Row, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_answered = self
+            .register
+            .clarification(&row.id)
+            .is_some_and(Clarification::is_answered);
+
+        h_flex()
+            .id(("clarification", ix))
+            .items_start()
+            .gap_2()
+            .p_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                div()
+                    .w_20()
+                    .child(Label::new(row.id.clone()).size(LabelSize::Small).color(
+                        if is_answered {
+                            Color::Default
+                        } else {
+                            Color::Muted
+                        },
+                    )),
+            )
+            .child(cell(&row.reference, false, cx).w_16())
+            .child(cell(&row.question, false, cx).flex_1())
+            .child(cell(&row.answer, false, cx).flex_1())
+            .child(cell(&row.asked_on, is_invalid_date(&row.asked_on, cx), cx).w_24())
+            .child(
+                cell(
+                    &row.published_on,
+                    is_invalid_date(&row.published_on, cx),
+                    cx,
+                )
+                .w_24(),
+            )
+            .child(cell(&row.requirement_ids, false, cx).w_32())
+            .child(
+                div().w_6().child(
+                    IconButton::new(("delete-clarification", ix), IconName::Trash)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Muted)
+                        .tooltip(Tooltip::text("Delete Question"))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.delete_clarification(ix, window, cx)
+                        })),
+                ),
+            )
+    }
+}
+
+impl Render for ClarificationRegisterView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("Clarifications")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_4()
+            .gap_4()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .map(|this| {
+                if self.rows.is_empty() {
+                    this.child(
+                        Label::new(format!(
+                            "No questions have been asked yet. They'll be recorded in {CLARIFICATIONS_FILE_NAME}."
+                        ))
+                        .color(Color::Muted),
+                    )
+                } else {
+                    this.child(self.render_column_headings()).child(
+                        v_flex()
+                            .id("clarification-rows")
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .track_scroll(&self.scroll_handle)
+                            .children(
+                                self.rows
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(ix, row)| self.render_row(ix, row, cx)),
+                            ),
+                    )
+                }
+            })
+    }
+}
+
+impl Focusable for ClarificationRegisterView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for ClarificationRegisterView {}
+
+impl Item for ClarificationRegisterView {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::MessageBubbles))
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Clarifications".into()
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+fn cell(editor: &Entity<Editor>, is_invalid: bool, cx: &App) -> Div {
+    div()
+        .px_1()
+        .rounded_sm()
+        .border_1()
+        .border_color(if is_invalid {
+            cx.theme().status().error_border
+        } else {
+            cx.theme().colors().border_transparent
+        })
+        .child(editor.clone())
+}
+
+fn field_editor(
+    placeholder: &'static str,
+    text: String,
+    window: &mut Window,
+    cx: &mut Context<ClarificationRegisterView>,
+) -> Entity<Editor> {
+    cx.new(|cx| {
+        let mut editor = Editor::single_line(window, cx);
+        editor.set_placeholder_text(placeholder, cx);
+        editor.set_text(text, window, cx);
+        editor
+    })
+}
+
+fn text_editor(
+    placeholder: &'static str,
+    text: String,
+    window: &mut Window,
+    cx: &mut Context<ClarificationRegisterView>,
+) -> Entity<Editor> {
+    cx.new(|cx| {
+        let mut editor = Editor::auto_height(12, window, cx);
+        editor.set_soft_wrap_mode(SoftWrap::EditorWidth, cx);
+        editor.set_placeholder_text(placeholder, cx);
+        editor.set_text(text, window, cx);
+        editor
+    })
+}
+
+fn is_invalid_date(editor: &Entity<Editor>, cx: &App) -> bool {
+    parse_date(&editor.read(cx).text(cx)).is_err()
+}
+
+fn non_empty(text: String) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn format_date(date: Option<NaiveDate>) -> String {
+    date.map(|date| date.format(DATE_FORMATS[0]).to_string())
+        .unwrap_or_default()
+}
+
+/// Parses a date typed into one of the date columns, which may be empty.
+fn parse_date(text: &str) -> Result<Option<NaiveDate>, ()> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .map(Some)
+        .ok_or(())
+}
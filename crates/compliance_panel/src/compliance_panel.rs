@@ -0,0 +1,1053 @@
+//! A panel showing how the response covers the requirements in the project's
+//! compliance register.
+//!
+//! A section of a Markdown response file addresses a requirement when its
+//! heading or text mentions the requirement's ID, e.g. `## Help Desk (REQ-001)`
+//! or `<!-- REQ-001 -->`. Its status is set with a marker such as
+//! `<!-- REQ-001: reviewed -->`; a written section without one counts as
+//! drafted.
+
+mod compliance_panel_settings;
+mod matrix;
+mod xlsx;
+
+use anyhow::{Context as _, Result, anyhow};
+use clarifications::{ClarificationRegisterView, ClarificationStore};
+use collections::{HashMap, HashSet};
+use compliance_panel_settings::{ComplianceDockPosition, CompliancePanelSettings};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{Bias, Editor, scroll::Autoscroll};
+use fs::Fs;
+use gpui::{
+    Action, App, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, Pixels, Render,
+    Subscription, Task, UniformListScrollHandle, WeakEntity, actions, uniform_list,
+};
+use language::{Buffer, BufferEvent, BufferId, Point};
+use project::buffer_store::BufferStoreEvent;
+use project::{Project, ProjectPath};
+use requirements::{ExtractRequirements, REQUIREMENTS_FILE_NAME, RequirementsRegister};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use ui::{ContextMenu, Indicator, ListItem, Tooltip, prelude::*, right_click_menu};
+use util::{ResultExt as _, TryFutureExt as _};
+use workspace::dock::{DockPosition, Panel, PanelEvent};
+use workspace::notifications::NotificationId;
+use workspace::{Toast, Workspace};
+
+pub use matrix::{
+    ComplianceMatrix, ComplianceStatus, MatrixRow, ResponseSection, SectionLink, response_sections,
+    status_marker_edit,
+};
+
+const COMPLIANCE_PANEL_KEY: &str = "CompliancePanel";
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+const CSV_EXPORT_FILE_NAME: &str = "compliance_matrix.csv";
+const XLSX_EXPORT_FILE_NAME: &str = "compliance_matrix.xlsx";
+
+actions!(compliance_panel, [ToggleFocus, ExportCsv, ExportXlsx]);
+
+pub fn init_settings(cx: &mut App) {
+    CompliancePanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut App) {
+    init_settings(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<CompliancePanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedCompliancePanel {
+    width: Option<Pixels>,
+}
+
+/// A row of the panel's list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ListEntry {
+    Requirement {
+        row_ix: usize,
+    },
+    Link {
+        row_ix: usize,
+        link_ix: usize,
+    },
+    Clarification {
+        row_ix: usize,
+        clarification_ix: usize,
+    },
+}
+
+pub struct CompliancePanel {
+    fs: Arc<dyn Fs>,
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    clarification_store: Entity<ClarificationStore>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    pending_serialization: Task<Option<()>>,
+    register_path: Option<ProjectPath>,
+    matrix: ComplianceMatrix,
+    entries: Vec<ListEntry>,
+    error: Option<SharedString>,
+    refresh_task: Task<()>,
+    buffer_subscriptions: HashMap<BufferId, Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl CompliancePanel {
+    pub async fn load(
+        workspace: WeakEntity<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<Entity<Self>> {
+        let serialized_panel = cx
+            .background_spawn(async move { KEY_VALUE_STORE.read_kvp(COMPLIANCE_PANEL_KEY) })
+            .await
+            .context("loading compliance panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedCompliancePanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            let panel = Self::new(workspace, window, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|width| width.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    pub fn new(
+        workspace: &mut Workspace,
+        _window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let buffer_store = project.read(cx).buffer_store().clone();
+        let workspace_handle = cx.entity().downgrade();
+        let clarification_store = ClarificationStore::for_project(&project, cx);
+
+        cx.new(|cx| {
+            let project_subscription = cx.subscribe(&project, |this, _, event, cx| match event {
+                project::Event::WorktreeUpdatedEntries(_, changes) => {
+                    if changes
+                        .iter()
+                        .any(|(path, _, _)| is_relevant_path(path, this.register_path.as_ref()))
+                    {
+                        this.schedule_refresh(true, cx);
+                    }
+                }
+                project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                    this.schedule_refresh(false, cx);
+                }
+                _ => {}
+            });
+            let buffer_store_subscription = cx.subscribe(&buffer_store, |this, _, event, cx| {
+                if let BufferStoreEvent::BufferAdded(buffer) = event {
+                    this.observe_buffer(buffer, cx);
+                }
+            });
+            let clarification_subscription = cx.observe(&clarification_store, |this, _, cx| {
+                this.schedule_refresh(false, cx);
+            });
+
+            let mut this = Self {
+                fs,
+                project: project.clone(),
+                workspace: workspace_handle,
+                clarification_store,
+                width: None,
+                focus_handle: cx.focus_handle(),
+                scroll_handle: UniformListScrollHandle::new(),
+                pending_serialization: Task::ready(None),
+                register_path: None,
+                matrix: ComplianceMatrix::default(),
+                entries: Vec::new(),
+                error: None,
+                refresh_task: Task::ready(()),
+                buffer_subscriptions: HashMap::default(),
+                _subscriptions: vec![
+                    project_subscription,
+                    buffer_store_subscription,
+                    clarification_subscription,
+                ],
+            };
+            for buffer in project.read(cx).opened_buffers(cx) {
+                this.observe_buffer(&buffer, cx);
+            }
+            this.schedule_refresh(false, cx);
+            this
+        })
+    }
+
+    pub fn matrix(&self) -> &ComplianceMatrix {
+        &self.matrix
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        COMPLIANCE_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedCompliancePanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Refreshes the matrix when a Markdown buffer or the register is edited,
+    /// without waiting for it to be saved.
+    fn observe_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if self.buffer_subscriptions.contains_key(&buffer_id) {
+            return;
+        }
+        let subscription = cx.subscribe(buffer, |this, buffer, event, cx| match event {
+            BufferEvent::Edited | BufferEvent::FileHandleChanged => {
+                let is_relevant = buffer
+                    .read(cx)
+                    .file()
+                    .is_some_and(|file| is_relevant_path(file.path(), this.register_path.as_ref()));
+                if is_relevant {
+                    this.schedule_refresh(true, cx);
+                }
+            }
+            _ => {}
+        });
+        self.buffer_subscriptions.insert(buffer_id, subscription);
+    }
+
+    fn schedule_refresh(&mut self, debounce: bool, cx: &mut Context<Self>) {
+        self.refresh_task = cx.spawn(async move |this, cx| {
+            if debounce {
+                cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            }
+            let Some(load) = this.update(cx, |this, cx| this.load_matrix(cx)).ok() else {
+                return;
+            };
+            let result = load.await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(Some((register_path, matrix))) => {
+                        this.register_path = Some(register_path);
+                        this.set_matrix(matrix);
+                        this.error = None;
+                    }
+                    Ok(None) => {
+                        this.register_path = None;
+                        this.set_matrix(ComplianceMatrix::default());
+                        this.error = None;
+                    }
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn set_matrix(&mut self, matrix: ComplianceMatrix) {
+        self.entries.clear();
+        for (row_ix, row) in matrix.rows.iter().enumerate() {
+            self.entries.push(ListEntry::Requirement { row_ix });
+            self.entries
+                .extend((0..row.links.len()).map(|link_ix| ListEntry::Link { row_ix, link_ix }));
+            self.entries
+                .extend((0..row.clarifications.len()).map(|clarification_ix| {
+                    ListEntry::Clarification {
+                        row_ix,
+                        clarification_ix,
+                    }
+                }));
+        }
+        self.matrix = matrix;
+    }
+
+    /// Reads the register and every Markdown file in the project, preferring
+    /// the text of open buffers, and builds the matrix from them.
+    fn load_matrix(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<(ProjectPath, ComplianceMatrix)>>> {
+        let project = self.project.read(cx);
+        let open_buffers = project
+            .opened_buffers(cx)
+            .into_iter()
+            .map(|buffer| buffer.read(cx).remote_id())
+            .collect::<HashSet<_>>();
+        self.buffer_subscriptions
+            .retain(|buffer_id, _| open_buffers.contains(buffer_id));
+
+        let register_path = project.visible_worktrees(cx).find_map(|worktree| {
+            let worktree = worktree.read(cx);
+            worktree
+                .entry_for_path(REQUIREMENTS_FILE_NAME)
+                .map(|_| ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: Path::new(REQUIREMENTS_FILE_NAME).into(),
+                })
+        });
+        let Some(register_path) = register_path else {
+            return Task::ready(Ok(None));
+        };
+
+        let clarifications = self.clarification_store.read(cx).register();
+        let is_local = project.is_local();
+        let mut sources = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            for entry in worktree.files(false, 0) {
+                if !is_markdown(&entry.path) && *entry.path != *register_path.path {
+                    continue;
+                }
+                let project_path = ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: entry.path.clone(),
+                };
+                let source = if let Some(buffer) = project.get_open_buffer(&project_path, cx) {
+                    DocumentSource::Text(buffer.read(cx).text())
+                } else if is_local {
+                    DocumentSource::File(worktree.abs_path().join(&entry.path))
+                } else {
+                    continue;
+                };
+                sources.push((project_path, source));
+            }
+        }
+
+        let fs = self.fs.clone();
+        cx.background_spawn(async move {
+            let mut register_json = None;
+            let mut documents = Vec::new();
+            for (project_path, source) in sources {
+                let text = match source {
+                    DocumentSource::Text(text) => text,
+                    DocumentSource::File(abs_path) => match fs.load(&abs_path).await {
+                        Ok(text) => text,
+                        Err(error) => {
+                            log::warn!("failed to load {}: {error:#}", abs_path.display());
+                            continue;
+                        }
+                    },
+                };
+                if project_path == register_path {
+                    register_json = Some(text);
+                } else {
+                    documents.push((project_path, text));
+                }
+            }
+
+            let register_json =
+                register_json.ok_or_else(|| anyhow!("Failed to read {REQUIREMENTS_FILE_NAME}"))?;
+            let register = RequirementsRegister::from_json(&register_json)
+                .with_context(|| format!("Failed to parse {REQUIREMENTS_FILE_NAME}"))?;
+            Ok(Some((
+                register_path,
+                ComplianceMatrix::new(&register, clarifications.as_deref(), &documents),
+            )))
+        })
+    }
+
+    fn open_link(&mut self, link: SectionLink, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let open = workspace.update(cx, |workspace, cx| {
+            workspace.open_path(link.path.clone(), None, true, window, cx)
+        });
+        cx.spawn_in(window, async move |_, cx| {
+            let item = open.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update_in(cx, |editor, window, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let point = snapshot.clip_point(Point::new(link.row, 0), Bias::Left);
+                    editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn open_clarification(&mut self, id: String, window: &mut Window, cx: &mut Context<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ClarificationRegisterView::open(workspace, Some(id), window, cx);
+            })
+            .ok();
+    }
+
+    /// Opens the clarification register with a new question about the
+    /// requirement.
+    fn add_clarification(&mut self, row_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(row) = self.matrix.rows.get(row_ix) else {
+            return;
+        };
+        let requirement_ids = vec![row.requirement.id.clone()];
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ClarificationRegisterView::add(workspace, requirement_ids, window, cx);
+            })
+            .ok();
+    }
+
+    /// Records `status` in every response section that addresses the
+    /// requirement, by editing or adding its status markers.
+    fn set_status(&mut self, row_ix: usize, status: ComplianceStatus, cx: &mut Context<Self>) {
+        let Some(row) = self.matrix.rows.get(row_ix) else {
+            return;
+        };
+        let id = row.requirement.id.clone();
+        let mut heading_rows = HashMap::<ProjectPath, Vec<u32>>::default();
+        for link in &row.links {
+            heading_rows
+                .entry(link.path.clone())
+                .or_default()
+                .push(link.row);
+        }
+
+        let edits = heading_rows
+            .into_iter()
+            .map(|(path, rows)| {
+                let buffer = self
+                    .project
+                    .update(cx, |project, cx| project.open_buffer(path, cx));
+                (buffer, rows)
+            })
+            .collect::<Vec<_>>();
+        cx.spawn(async move |_, cx| {
+            for (buffer, mut rows) in edits {
+                let buffer = buffer.await?;
+                // Edit the last sections first, so that added markers don't
+                // move the headings still to be edited.
+                rows.sort_unstable_by(|a, b| b.cmp(a));
+                buffer.update(cx, |buffer, cx| {
+                    for row in rows {
+                        let (range, new_text) =
+                            status_marker_edit(&buffer.text(), row, &id, status);
+                        buffer.edit([(range, new_text)], None, cx);
+                    }
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn export_csv(&mut self, _: &ExportCsv, window: &mut Window, cx: &mut Context<Self>) {
+        let csv = self.matrix.to_csv();
+        self.export(
+            CSV_EXPORT_FILE_NAME,
+            async move { Ok(csv.into_bytes()) },
+            window,
+            cx,
+        );
+    }
+
+    fn export_xlsx(&mut self, _: &ExportXlsx, window: &mut Window, cx: &mut Context<Self>) {
+        let table = self.matrix.table();
+        self.export(
+            XLSX_EXPORT_FILE_NAME,
+            async move { xlsx::write_workbook("Compliance Matrix", &table).await },
+            window,
+            cx,
+        );
+    }
+
+    /// Writes an export next to the register and offers to reveal it.
+    fn export(
+        &mut self,
+        file_name: &'static str,
+        contents: impl Future<Output = Result<Vec<u8>>> + Send + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(register_path) = self.register_path.clone() else {
+            return;
+        };
+        let Some(root) = self
+            .project
+            .read(cx)
+            .worktree_for_id(register_path.worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+        else {
+            return;
+        };
+        let abs_path = root.join(file_name);
+        let fs = self.fs.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let contents = contents.await?;
+            fs.write(&abs_path, &contents).await?;
+            workspace.update(cx, |workspace, cx| {
+                struct ComplianceMatrixExported;
+
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<ComplianceMatrixExported>(),
+                        format!("Exported the compliance matrix to {file_name}."),
+                    )
+                    .on_click("Reveal", move |_, cx| cx.reveal_path(&abs_path)),
+                    cx,
+                );
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let total = self.matrix.rows.len();
+        let compliant = self.matrix.count(ComplianceStatus::Compliant);
+        let has_register = self.register_path.is_some();
+
+        h_flex()
+            .h(Tab::container_height(cx))
+            .flex_none()
+            .px_2()
+            .gap_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                Label::new(format!("{compliant} of {total} compliant"))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                h_flex()
+                    .gap_0p5()
+                    .child(
+                        IconButton::new("export-csv", IconName::FileText)
+                            .icon_size(IconSize::Small)
+                            .disabled(!has_register)
+                            .tooltip(Tooltip::text("Export as CSV"))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(ExportCsv.boxed_clone(), cx)
+                            }),
+                    )
+                    .child(
+                        IconButton::new("export-xlsx", IconName::Download)
+                            .icon_size(IconSize::Small)
+                            .disabled(!has_register)
+                            .tooltip(Tooltip::text("Export as XLSX"))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(ExportXlsx.boxed_clone(), cx)
+                            }),
+                    ),
+            )
+    }
+
+    fn render_empty_state(&self) -> impl IntoElement {
+        v_flex()
+            .id("empty-compliance-panel")
+            .size_full()
+            .p_4()
+            .gap_2()
+            .justify_center()
+            .items_center()
+            .child(
+                Label::new(match &self.error {
+                    Some(error) => error.clone(),
+                    None => "No requirements register in this project.".into(),
+                })
+                .color(Color::Muted),
+            )
+            .when(self.error.is_none(), |this| {
+                this.child(
+                    Button::new("extract-requirements", "Extract Requirements")
+                        .full_width()
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(ExtractRequirements.boxed_clone(), cx)
+                        }),
+                )
+            })
+    }
+
+    fn render_entries(
+        &mut self,
+        range: std::ops::Range<usize>,
+        cx: &mut Context<Self>,
+    ) -> Vec<AnyElement> {
+        range
+            .filter_map(|ix| {
+                let entry = *self.entries.get(ix)?;
+                Some(match entry {
+                    ListEntry::Requirement { row_ix } => self.render_requirement(ix, row_ix, cx),
+                    ListEntry::Link { row_ix, link_ix } => {
+                        self.render_link(ix, row_ix, link_ix, cx)
+                    }
+                    ListEntry::Clarification {
+                        row_ix,
+                        clarification_ix,
+                    } => self.render_clarification(ix, row_ix, clarification_ix, cx),
+                })
+            })
+            .collect()
+    }
+
+    fn render_requirement(&self, ix: usize, row_ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let row = &self.matrix.rows[row_ix];
+        let requirement = &row.requirement;
+        let status = row.status;
+        let id = requirement.id.clone();
+        let text = requirement.text.clone();
+        let tooltip_title = format!("{} · {}", requirement.id, status.label());
+        let tooltip_meta = match &requirement.clause {
+            Some(clause) => format!("Clause {clause} · {}", requirement.text),
+            None => requirement.text.clone(),
+        };
+        let has_links = !row.links.is_empty();
+        let on_click = row.links.first().cloned().map(|link| {
+            cx.listener(move |this, _, window, cx| this.open_link(link.clone(), window, cx))
+        });
+        let panel = cx.entity().downgrade();
+
+        right_click_menu(("compliance-requirement-menu", ix))
+            .menu(move |window, cx| {
+                let panel = panel.clone();
+                ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    if has_links {
+                        for status in [
+                            ComplianceStatus::Drafted,
+                            ComplianceStatus::Reviewed,
+                            ComplianceStatus::Compliant,
+                        ] {
+                            let panel = panel.clone();
+                            menu = menu.entry(
+                                format!("Mark as {}", status.label()),
+                                None,
+                                move |_, cx| {
+                                    panel
+                                        .update(cx, |panel, cx| {
+                                            panel.set_status(row_ix, status, cx)
+                                        })
+                                        .ok();
+                                },
+                            );
+                        }
+                    } else {
+                        menu = menu.header("Not addressed in any response section");
+                    }
+                    menu.separator()
+                        .entry("Add Clarification", None, move |window, cx| {
+                            panel
+                                .update(cx, |panel, cx| panel.add_clarification(row_ix, window, cx))
+                                .ok();
+                        })
+                })
+            })
+            .trigger(move |_| {
+                ListItem::new(("compliance-requirement", ix))
+                    .spacing(ui::ListItemSpacing::Sparse)
+                    .start_slot(Indicator::dot().color(status_color(status)))
+                    .child(
+                        h_flex()
+                            .gap_1p5()
+                            .min_w_0()
+                            .child(Label::new(id).size(LabelSize::Small).color(Color::Muted))
+                            .child(Label::new(text).truncate()),
+                    )
+                    .tooltip(move |window, cx| {
+                        Tooltip::with_meta(
+                            tooltip_title.clone(),
+                            None,
+                            tooltip_meta.clone(),
+                            window,
+                            cx,
+                        )
+                    })
+                    .when_some(on_click, |item, on_click| item.on_click(on_click))
+            })
+            .into_any_element()
+    }
+
+    fn render_link(
+        &self,
+        ix: usize,
+        row_ix: usize,
+        link_ix: usize,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let link = self.matrix.rows[row_ix].links[link_ix].clone();
+        let file_name = link
+            .path
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let status = link.status.unwrap_or(ComplianceStatus::Unaddressed);
+
+        ListItem::new(("compliance-link", ix))
+            .indent_level(1)
+            .indent_step_size(px(12.))
+            .start_slot(
+                Icon::new(IconName::FileText)
+                    .size(IconSize::XSmall)
+                    .color(status_color(status)),
+            )
+            .child(
+                h_flex()
+                    .gap_1p5()
+                    .min_w_0()
+                    .child(
+                        Label::new(link.heading.clone())
+                            .size(LabelSize::Small)
+                            .truncate(),
+                    )
+                    .child(
+                        Label::new(file_name)
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    ),
+            )
+            .on_click(
+                cx.listener(move |this, _, window, cx| this.open_link(link.clone(), window, cx)),
+            )
+            .into_any_element()
+    }
+
+    fn render_clarification(
+        &self,
+        ix: usize,
+        row_ix: usize,
+        clarification_ix: usize,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let clarification = &self.matrix.rows[row_ix].clarifications[clarification_ix];
+        let id = clarification.id.clone();
+        let (summary, color) = match clarification.answer.as_deref() {
+            Some(answer) if clarification.is_answered() => (answer.to_string(), Color::Accent),
+            _ => (clarification.question.clone(), Color::Muted),
+        };
+
+        ListItem::new(("compliance-clarification", ix))
+            .indent_level(1)
+            .indent_step_size(px(12.))
+            .start_slot(
+                Icon::new(IconName::MessageBubbles)
+                    .size(IconSize::XSmall)
+                    .color(color),
+            )
+            .child(
+                h_flex()
+                    .gap_1p5()
+                    .min_w_0()
+                    .child(
+                        Label::new(clarification.display_id())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(summary).size(LabelSize::Small).truncate()),
+            )
+            .tooltip(Tooltip::text(if clarification.is_answered() {
+                "Answered by the buyer; overrides the tender documents"
+            } else {
+                "Awaiting the buyer's answer"
+            }))
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.open_clarification(id.clone(), window, cx)
+            }))
+            .into_any_element()
+    }
+}
+
+enum DocumentSource {
+    Text(String),
+    File(PathBuf),
+}
+
+fn status_color(status: ComplianceStatus) -> Color {
+    match status {
+        ComplianceStatus::Unaddressed => Color::Error,
+        ComplianceStatus::Drafted => Color::Warning,
+        ComplianceStatus::Reviewed => Color::Info,
+        ComplianceStatus::Compliant => Color::Success,
+    }
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| matches!(extension, "md" | "markdown"))
+}
+
+fn is_relevant_path(path: &Path, register_path: Option<&ProjectPath>) -> bool {
+    is_markdown(path)
+        || match register_path {
+            Some(register_path) => *register_path.path == *path,
+            None => path == Path::new(REQUIREMENTS_FILE_NAME),
+        }
+}
+
+impl Panel for CompliancePanel {
+    fn persistent_name() -> &'static str {
+        "Compliance Panel"
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        match CompliancePanelSettings::get_global(cx).dock {
+            ComplianceDockPosition::Left => DockPosition::Left,
+            ComplianceDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file::<CompliancePanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => ComplianceDockPosition::Left,
+                    DockPosition::Right => ComplianceDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, _: &Window, cx: &App) -> Pixels {
+        self.width
+            .unwrap_or_else(|| CompliancePanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        CompliancePanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Check)
+    }
+
+    fn icon_tooltip(&self, _window: &Window, _: &App) -> Option<&'static str> {
+        Some("Compliance Panel")
+    }
+
+    fn icon_label(&self, _: &Window, _: &App) -> Option<String> {
+        let unaddressed = self.matrix.count(ComplianceStatus::Unaddressed);
+        (unaddressed > 0).then(|| unaddressed.to_string())
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        4
+    }
+}
+
+impl Focusable for CompliancePanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for CompliancePanel {}
+
+impl Render for CompliancePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("compliance-panel")
+            .key_context("CompliancePanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::export_csv))
+            .on_action(cx.listener(Self::export_xlsx))
+            .size_full()
+            .overflow_hidden()
+            .bg(cx.theme().colors().panel_background)
+            .child(self.render_header(cx))
+            .map(|this| {
+                if self.entries.is_empty() {
+                    this.child(self.render_empty_state())
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.entity(),
+                            "compliance-entries",
+                            self.entries.len(),
+                            |this, range, _, cx| this.render_entries(range, cx),
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarifications::ClarificationRegister;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            super::init(cx);
+        });
+    }
+
+    fn statuses(
+        panel: &Entity<CompliancePanel>,
+        cx: &mut TestAppContext,
+    ) -> Vec<(String, ComplianceStatus)> {
+        panel.read_with(cx, |panel, _| {
+            panel
+                .matrix()
+                .rows
+                .iter()
+                .map(|row| (row.requirement.id.clone(), row.status))
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_statuses_follow_buffer_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut register = RequirementsRegister::default();
+        register.merge(vec![requirements::ExtractedDocument {
+            path: "RFT.pdf".into(),
+            digest: "a".into(),
+            requirements: requirements::extract_requirements(indoc! {"
+                3.1 The Supplier must provide a help desk.
+
+                3.2 Reports must be submitted monthly.
+            "}),
+        }]);
+
+        let mut clarifications = ClarificationRegister::default();
+        clarifications.push(vec!["REQ-002".into()]);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                "clarifications.json": clarifications.to_json(),
+                "requirements.json": register.to_json(),
+                "response.md": indoc! {"
+                    # Help Desk (REQ-001)
+                    <!-- REQ-001: reviewed -->
+                    Our help desk is open 24/7.
+
+                    # Reporting (REQ-002)
+                "},
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/tender").as_ref()], cx).await;
+        let workspace =
+            cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let panel = workspace
+            .update(cx, |workspace, window, cx| {
+                CompliancePanel::new(workspace, window, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&panel, cx),
+            [
+                ("REQ-001".to_string(), ComplianceStatus::Reviewed),
+                ("REQ-002".to_string(), ComplianceStatus::Unaddressed),
+            ]
+        );
+        assert_eq!(
+            panel.read_with(cx, |panel, _| panel
+                .matrix()
+                .rows
+                .iter()
+                .map(|row| row.clarifications.len())
+                .collect::<Vec<_>>()),
+            [0, 1]
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                let path = project.find_project_path("tender/response.md", cx).unwrap();
+                project.open_buffer(path, cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            let end = buffer.len();
+            buffer.edit([(end..end, "Reports are emailed monthly.\n")], None, cx);
+        });
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&panel, cx),
+            [
+                ("REQ-001".to_string(), ComplianceStatus::Reviewed),
+                ("REQ-002".to_string(), ComplianceStatus::Drafted),
+            ]
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.set_status(1, ComplianceStatus::Compliant, cx)
+        });
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            indoc! {"
+                # Help Desk (REQ-001)
+                <!-- REQ-001: reviewed -->
+                Our help desk is open 24/7.
+
+                # Reporting (REQ-002)
+                <!-- REQ-002: compliant -->
+                Reports are emailed monthly.
+            "}
+        );
+        assert_eq!(
+            statuses(&panel, cx),
+            [
+                ("REQ-001".to_string(), ComplianceStatus::Reviewed),
+                ("REQ-002".to_string(), ComplianceStatus::Compliant),
+            ]
+        );
+    }
+}
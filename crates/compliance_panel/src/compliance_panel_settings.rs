@@ -0,0 +1,49 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplianceDockPosition {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CompliancePanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: ComplianceDockPosition,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct CompliancePanelSettingsContent {
+    /// Whether to show the compliance panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width (in pixels) taken by compliance panel
+    ///
+    /// Default: 320
+    pub default_width: Option<f32>,
+    /// The position of compliance panel
+    ///
+    /// Default: left
+    pub dock: Option<ComplianceDockPosition>,
+}
+
+impl Settings for CompliancePanelSettings {
+    const KEY: Option<&'static str> = Some("compliance_panel");
+
+    type FileContent = CompliancePanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::App,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+
+    fn import_from_vscode(_vscode: &settings::VsCodeSettings, _current: &mut Self::FileContent) {}
+}
@@ -0,0 +1,606 @@
+use clarifications::{Clarification, ClarificationRegister};
+use collections::HashMap;
+use project::ProjectPath;
+use requirements::{Requirement, RequirementsRegister};
+use std::ops::Range;
+
+/// How far the response to a requirement has progressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComplianceStatus {
+    Unaddressed,
+    Drafted,
+    Reviewed,
+    Compliant,
+}
+
+impl ComplianceStatus {
+    pub const ALL: [ComplianceStatus; 4] = [
+        ComplianceStatus::Unaddressed,
+        ComplianceStatus::Drafted,
+        ComplianceStatus::Reviewed,
+        ComplianceStatus::Compliant,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComplianceStatus::Unaddressed => "Unaddressed",
+            ComplianceStatus::Drafted => "Drafted",
+            ComplianceStatus::Reviewed => "Reviewed",
+            ComplianceStatus::Compliant => "Compliant",
+        }
+    }
+
+    /// The keyword used for this status in status markers.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ComplianceStatus::Unaddressed => "unaddressed",
+            ComplianceStatus::Drafted => "drafted",
+            ComplianceStatus::Reviewed => "reviewed",
+            ComplianceStatus::Compliant => "compliant",
+        }
+    }
+
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.keyword().eq_ignore_ascii_case(keyword))
+    }
+}
+
+/// A section of a Markdown response file, from a heading to the next one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseSection {
+    pub heading: String,
+    /// The 0-based row of the heading.
+    pub row: u32,
+    /// The requirements the section mentions, in order of first mention.
+    pub requirement_ids: Vec<String>,
+    /// Statuses set with markers such as `<!-- REQ-001: reviewed -->`.
+    pub statuses: HashMap<String, ComplianceStatus>,
+    /// Whether the section has any text besides its heading and comments.
+    pub has_prose: bool,
+}
+
+impl ResponseSection {
+    /// The status the section gives a requirement it addresses: the one set
+    /// by a marker, or drafted if the section has been written. Sections that
+    /// are still empty don't count towards a requirement's status.
+    pub fn status_of(&self, requirement_id: &str) -> Option<ComplianceStatus> {
+        self.statuses
+            .get(requirement_id)
+            .copied()
+            .or_else(|| self.has_prose.then_some(ComplianceStatus::Drafted))
+    }
+
+    fn scan(&mut self, line: &str) {
+        for id in requirement_ids(line) {
+            if !self.requirement_ids.iter().any(|known| known == id) {
+                self.requirement_ids.push(id.to_string());
+            }
+        }
+        for comment in comments(line) {
+            for entry in comment.split([',', ';']) {
+                let Some((id, status)) = entry.split_once(':') else {
+                    continue;
+                };
+                let id = id.trim();
+                if let Some(status) = ComplianceStatus::from_keyword(status.trim()) {
+                    if requirement_ids(id).next() == Some(id) {
+                        self.statuses.insert(id.to_string(), status);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Splits Markdown text into the sections under each heading. Text before
+/// the first heading is ignored, as is anything inside fenced code blocks
+/// other than counting as prose.
+pub fn response_sections(text: &str) -> Vec<ResponseSection> {
+    let mut sections = Vec::<ResponseSection>::new();
+    let mut fence: Option<&str> = None;
+
+    for (row, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let in_code = if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            true
+        } else if let Some(marker) = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker))
+        {
+            fence = Some(marker);
+            true
+        } else if let Some(heading) = heading_text(trimmed) {
+            let mut section = ResponseSection {
+                heading: strip_comments(heading).trim().to_string(),
+                row: row as u32,
+                requirement_ids: Vec::new(),
+                statuses: HashMap::default(),
+                has_prose: false,
+            };
+            section.scan(heading);
+            sections.push(section);
+            continue;
+        } else {
+            false
+        };
+
+        if let Some(section) = sections.last_mut() {
+            if in_code {
+                section.has_prose = true;
+            } else {
+                section.scan(line);
+                section.has_prose |= !strip_comments(line).trim().is_empty();
+            }
+        }
+    }
+
+    sections
+}
+
+/// The edit that records `status` for requirement `id` in the section whose
+/// heading is on `heading_row`: replacing the status in an existing marker,
+/// or adding a marker on the line after the heading.
+pub fn status_marker_edit(
+    text: &str,
+    heading_row: u32,
+    id: &str,
+    status: ComplianceStatus,
+) -> (Range<usize>, String) {
+    let mut offset = 0;
+    let mut heading_end = text.len();
+    for (row, line) in text.split_inclusive('\n').enumerate() {
+        let line_start = offset;
+        offset += line.len();
+        let row = row as u32;
+        if row < heading_row {
+            continue;
+        } else if row == heading_row {
+            heading_end = line_start + line.trim_end_matches(['\n', '\r']).len();
+        } else if heading_text(line.trim_start()).is_some() {
+            break;
+        } else if let Some(range) = marker_status_range(line, id) {
+            return (
+                line_start + range.start..line_start + range.end,
+                status.keyword().to_string(),
+            );
+        }
+    }
+    (
+        heading_end..heading_end,
+        format!("\n<!-- {id}: {} -->", status.keyword()),
+    )
+}
+
+/// The range of the status keyword following `id` in one of the line's
+/// status markers.
+fn marker_status_range(line: &str, id: &str) -> Option<Range<usize>> {
+    let mut offset = 0;
+    while let Some(start) = line[offset..].find("<!--") {
+        let comment_start = offset + start + "<!--".len();
+        let comment_end = comment_start + line[comment_start..].find("-->")?;
+        let comment = &line[comment_start..comment_end];
+        for (id_start, _) in comment.match_indices(id) {
+            let after_id = &comment[id_start + id.len()..];
+            let Some(after_colon) = after_id.trim_start().strip_prefix(':') else {
+                continue;
+            };
+            let keyword = after_colon.trim_start();
+            let keyword_len = keyword
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(keyword.len());
+            if ComplianceStatus::from_keyword(&keyword[..keyword_len]).is_some() {
+                let keyword_start = comment_start + comment.len() - keyword.len();
+                return Some(keyword_start..keyword_start + keyword_len);
+            }
+        }
+        offset = comment_end;
+    }
+    None
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() {
+        Some(rest)
+    } else if rest.starts_with([' ', '\t']) {
+        Some(rest.trim().trim_end_matches('#').trim_end())
+    } else {
+        None
+    }
+}
+
+/// Finds requirement IDs such as `REQ-001` in `text`.
+fn requirement_ids(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices("REQ-").filter_map(move |(start, _)| {
+        if text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return None;
+        }
+        let digits_start = start + "REQ-".len();
+        let digits_len = text[digits_start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len() - digits_start);
+        let end = digits_start + digits_len;
+        if digits_len == 0 || text[end..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            return None;
+        }
+        Some(&text[start..end])
+    })
+}
+
+fn comments(line: &str) -> impl Iterator<Item = &str> {
+    line.split("<!--")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("-->").map(|(comment, _)| comment))
+}
+
+fn strip_comments(line: &str) -> String {
+    let mut stripped = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("<!--") {
+        stripped.push_str(&rest[..start]);
+        match rest[start..].find("-->") {
+            Some(end) => rest = &rest[start + end + "-->".len()..],
+            None => return stripped,
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// A response section that addresses a requirement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionLink {
+    pub path: ProjectPath,
+    pub heading: String,
+    pub row: u32,
+    pub status: Option<ComplianceStatus>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatrixRow {
+    pub requirement: Requirement,
+    pub status: ComplianceStatus,
+    pub links: Vec<SectionLink>,
+    /// The clarifications whose answers modify the requirement.
+    pub clarifications: Vec<Clarification>,
+}
+
+/// Every requirement in the register, with the response sections that
+/// address it and the resulting status.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ComplianceMatrix {
+    pub rows: Vec<MatrixRow>,
+}
+
+impl ComplianceMatrix {
+    /// Builds the matrix from the register and the text of every response
+    /// file.
+    ///
+    /// A requirement's status is the lowest status of the sections that
+    /// address it, since it is only as far along as its least finished part.
+    /// A requirement no written section addresses is unaddressed.
+    pub fn new(
+        register: &RequirementsRegister,
+        clarifications: Option<&ClarificationRegister>,
+        documents: &[(ProjectPath, String)],
+    ) -> Self {
+        let mut links = HashMap::<&str, Vec<SectionLink>>::default();
+        for (path, text) in documents {
+            for section in response_sections(text) {
+                for id in &section.requirement_ids {
+                    let Some(requirement) = register.requirement(id) else {
+                        continue;
+                    };
+                    links
+                        .entry(requirement.id.as_str())
+                        .or_default()
+                        .push(SectionLink {
+                            path: path.clone(),
+                            heading: section.heading.clone(),
+                            row: section.row,
+                            status: section.status_of(id),
+                        });
+                }
+            }
+        }
+
+        let rows = register
+            .requirements
+            .iter()
+            .map(|requirement| {
+                let links = links.remove(requirement.id.as_str()).unwrap_or_default();
+                let status = links
+                    .iter()
+                    .filter_map(|link| link.status)
+                    .min()
+                    .unwrap_or(ComplianceStatus::Unaddressed);
+                let clarifications = clarifications
+                    .map(|clarifications| {
+                        clarifications
+                            .for_requirement(&requirement.id)
+                            .cloned()
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                MatrixRow {
+                    requirement: requirement.clone(),
+                    status,
+                    links,
+                    clarifications,
+                }
+            })
+            .collect();
+        Self { rows }
+    }
+
+    pub fn count(&self, status: ComplianceStatus) -> usize {
+        self.rows.iter().filter(|row| row.status == status).count()
+    }
+
+    /// The matrix as a table for export, starting with a header row.
+    pub fn table(&self) -> Vec<Vec<String>> {
+        let header = [
+            "ID",
+            "Clause",
+            "Requirement",
+            "Priority",
+            "Source",
+            "Status",
+            "Response Sections",
+            "Clarifications",
+        ];
+        let mut table = vec![header.iter().map(|cell| cell.to_string()).collect()];
+        for row in &self.rows {
+            let requirement = &row.requirement;
+            let source = match requirement.source.page {
+                Some(page) => format!("{}, p. {page}", requirement.source.path),
+                None => requirement.source.path.clone(),
+            };
+            let sections = row
+                .links
+                .iter()
+                .map(|link| format!("{}: {}", link.path.path.display(), link.heading))
+                .collect::<Vec<_>>()
+                .join("; ");
+            let clarifications = row
+                .clarifications
+                .iter()
+                .map(Clarification::display_id)
+                .collect::<Vec<_>>()
+                .join("; ");
+            table.push(vec![
+                requirement.id.clone(),
+                requirement.clause.clone().unwrap_or_default(),
+                requirement.text.clone(),
+                requirement.priority.label().to_string(),
+                source,
+                row.status.label().to_string(),
+                sections,
+                clarifications,
+            ]);
+        }
+        table
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in self.table() {
+            let cells = row.iter().map(|cell| csv_cell(cell)).collect::<Vec<_>>();
+            csv.push_str(&cells.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use project::WorktreeId;
+    use requirements::{Priority, RequirementSource};
+    use std::path::Path;
+
+    fn requirement(id: &str, text: &str) -> Requirement {
+        Requirement {
+            id: id.into(),
+            clause: Some("3.1".into()),
+            text: text.into(),
+            priority: Priority::Mandatory,
+            source: RequirementSource {
+                path: "RFT.pdf".into(),
+                page: Some(4),
+            },
+            added_in: 1,
+            amended_in: None,
+        }
+    }
+
+    #[test]
+    fn test_response_sections() {
+        let sections = response_sections(indoc! {"
+            Preamble mentioning REQ-009.
+
+            # Help Desk (REQ-001)
+            <!-- REQ-001: reviewed -->
+            Our help desk is staffed 24/7.
+
+            ## Reporting
+            <!-- REQ-002, REQ-003: compliant -->
+
+            ```
+            # not a heading REQ-004
+            ```
+            ## Pricing ##
+            Not REQ-0012X or XREQ-005.
+        "});
+        assert_eq!(
+            sections
+                .iter()
+                .map(|section| (
+                    section.heading.as_str(),
+                    section.row,
+                    section.requirement_ids.clone(),
+                    section.has_prose
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Help Desk (REQ-001)", 2, vec!["REQ-001".to_string()], true),
+                (
+                    "Reporting",
+                    6,
+                    vec!["REQ-002".into(), "REQ-003".into()],
+                    true
+                ),
+                ("Pricing", 12, vec![], true),
+            ]
+        );
+        assert_eq!(
+            sections[0].status_of("REQ-001"),
+            Some(ComplianceStatus::Reviewed)
+        );
+        assert_eq!(
+            sections[1].status_of("REQ-002"),
+            Some(ComplianceStatus::Drafted)
+        );
+        assert_eq!(
+            sections[1].status_of("REQ-003"),
+            Some(ComplianceStatus::Compliant)
+        );
+    }
+
+    #[test]
+    fn test_status_marker_edit() {
+        let text = indoc! {"
+            # Help Desk
+            <!-- REQ-002: drafted, REQ-001 : drafted -->
+            We provide a help desk.
+
+            # Reporting
+            Monthly reports (REQ-001).
+        "};
+        let apply = |heading_row, status| {
+            let (range, new_text) = status_marker_edit(text, heading_row, "REQ-001", status);
+            let mut text = text.to_string();
+            text.replace_range(range, &new_text);
+            text
+        };
+
+        assert_eq!(
+            apply(0, ComplianceStatus::Compliant),
+            indoc! {"
+                # Help Desk
+                <!-- REQ-002: drafted, REQ-001 : compliant -->
+                We provide a help desk.
+
+                # Reporting
+                Monthly reports (REQ-001).
+            "}
+        );
+        assert_eq!(
+            apply(4, ComplianceStatus::Reviewed),
+            indoc! {"
+                # Help Desk
+                <!-- REQ-002: drafted, REQ-001 : drafted -->
+                We provide a help desk.
+
+                # Reporting
+                <!-- REQ-001: reviewed -->
+                Monthly reports (REQ-001).
+            "}
+        );
+    }
+
+    #[test]
+    fn test_compliance_matrix() {
+        let register = RequirementsRegister {
+            version: 1,
+            sources: Vec::new(),
+            requirements: vec![
+                requirement("REQ-001", "The Supplier must provide a help desk."),
+                requirement("REQ-002", "Reports must be submitted monthly, by email."),
+                requirement("REQ-003", "Staff must hold a \"current\" police check."),
+            ],
+        };
+        let path = |path: &str| ProjectPath {
+            worktree_id: WorktreeId::from_usize(1),
+            path: Path::new(path).into(),
+        };
+        let documents = vec![
+            (
+                path("response/services.md"),
+                indoc! {"
+                    # Help Desk
+                    <!-- REQ-001: compliant -->
+                    We provide a help desk.
+
+                    # Reporting
+                    <!-- addresses REQ-002 -->
+                "}
+                .to_string(),
+            ),
+            (
+                path("response/summary.md"),
+                indoc! {"
+                    # Summary
+                    Our help desk (REQ-001) is described below.
+                "}
+                .to_string(),
+            ),
+        ];
+
+        let mut clarifications = ClarificationRegister::default();
+        clarifications.push(vec!["REQ-002".into()]);
+        clarifications.clarifications[0].reference = Some("Q12".into());
+
+        let matrix = ComplianceMatrix::new(&register, Some(&clarifications), &documents);
+        assert_eq!(
+            matrix
+                .rows
+                .iter()
+                .map(|row| (
+                    row.requirement.id.as_str(),
+                    row.status,
+                    row.links.len(),
+                    row.clarifications.len()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("REQ-001", ComplianceStatus::Drafted, 2, 0),
+                ("REQ-002", ComplianceStatus::Unaddressed, 1, 1),
+                ("REQ-003", ComplianceStatus::Unaddressed, 0, 0),
+            ]
+        );
+        assert_eq!(matrix.count(ComplianceStatus::Unaddressed), 2);
+
+        assert_eq!(
+            matrix.to_csv(),
+            "ID,Clause,Requirement,Priority,Source,Status,Response Sections,Clarifications\r\n\
+             REQ-001,3.1,The Supplier must provide a help desk.,Mandatory,\"RFT.pdf, p. 4\",Drafted,response/services.md: Help Desk; response/summary.md: Summary,\r\n\
+             REQ-002,3.1,\"Reports must be submitted monthly, by email.\",Mandatory,\"RFT.pdf, p. 4\",Unaddressed,response/services.md: Reporting,QA-001 (Q12)\r\n\
+             REQ-003,3.1,\"Staff must hold a \"\"current\"\" police check.\",Mandatory,\"RFT.pdf, p. 4\",Unaddressed,,\r\n"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use spreadsheet::column_name;
+use std::fmt::Write as _;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/></Types>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const WORKBOOK_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#;
+
+/// Two cell formats: the default, and bold wrapped text for the header row.
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts><fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills><borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="3"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0" applyAlignment="1"><alignment vertical="top" wrapText="1"/></xf></cellXfs></styleSheet>"#;
+
+/// Column widths, in characters, wide enough for IDs and short values but
+/// capped so long text wraps.
+const MIN_COLUMN_WIDTH: usize = 8;
+const MAX_COLUMN_WIDTH: usize = 60;
+
+/// Writes `rows` as the only sheet of an XLSX workbook, with the first row
+/// formatted as a frozen header.
+pub async fn write_workbook(sheet_name: &str, rows: &[Vec<String>]) -> Result<Vec<u8>> {
+    let workbook = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+        escape(sheet_name)
+    );
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+        ("_rels/.rels", ROOT_RELS.to_string()),
+        ("xl/workbook.xml", workbook),
+        ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS.to_string()),
+        ("xl/styles.xml", STYLES.to_string()),
+        ("xl/worksheets/sheet1.xml", worksheet(rows)),
+    ];
+
+    let mut writer = ZipFileWriter::new(Vec::new());
+    for (name, content) in parts {
+        let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+        writer.write_entry_whole(entry, content.as_bytes()).await?;
+    }
+    Ok(writer.close().await?)
+}
+
+fn worksheet(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    );
+    if rows.len() > 1 {
+        xml.push_str(r#"<sheetViews><sheetView workbookViewId="0"><pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/></sheetView></sheetViews>"#);
+    }
+    if columns > 0 {
+        xml.push_str("<cols>");
+        for column in 0..columns {
+            let width = rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+                .clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+            write!(
+                xml,
+                r#"<col min="{0}" max="{0}" width="{1}" customWidth="1"/>"#,
+                column + 1,
+                width + 2
+            )
+            .unwrap();
+        }
+        xml.push_str("</cols>");
+    }
+
+    xml.push_str("<sheetData>");
+    for (row_ix, row) in rows.iter().enumerate() {
+        let style = if row_ix == 0 { 1 } else { 2 };
+        write!(xml, r#"<row r="{}">"#, row_ix + 1).unwrap();
+        for (column_ix, cell) in row.iter().enumerate() {
+            write!(
+                xml,
+                r#"<c r="{}{}" s="{style}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                column_name(column_ix as u32),
+                row_ix + 1,
+                escape(cell)
+            )
+            .unwrap();
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Control characters other than tabs and newlines aren't allowed in XML.
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_zip::base::read::mem::ZipFileReader;
+    use futures::AsyncReadExt as _;
+
+    #[gpui::test]
+    async fn test_write_workbook() {
+        let rows = vec![
+            vec!["ID".to_string(), "Requirement".to_string()],
+            vec!["REQ-001".to_string(), "Terms & <conditions>".to_string()],
+        ];
+        let bytes = write_workbook("Compliance", &rows).await.unwrap();
+
+        let reader = ZipFileReader::new(bytes).await.unwrap();
+        let entries = reader
+            .file()
+            .entries()
+            .iter()
+            .map(|entry| entry.filename().as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert!(entries.contains(&"xl/workbook.xml".to_string()));
+
+        let sheet_ix = entries
+            .iter()
+            .position(|name| name == "xl/worksheets/sheet1.xml")
+            .unwrap();
+        let mut sheet = String::new();
+        reader
+            .reader_with_entry(sheet_ix)
+            .await
+            .unwrap()
+            .read_to_string(&mut sheet)
+            .await
+            .unwrap();
+        assert!(sheet.contains(
+            r#"<c r="B2" s="2" t="inlineStr"><is><t xml:space="preserve">Terms &amp; &lt;conditions&gt;</t></is></c>"#
+        ));
+        assert!(sheet.contains(r#"<c r="A1" s="1" t="inlineStr">"#));
+    }
+}
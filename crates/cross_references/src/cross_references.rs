@@ -0,0 +1,567 @@
+//! Numbered headings, figures and tables, and cross-references to them.
+//!
+//! Headings are labelled with an attribute, as in `## Pricing {#sec:pricing}`.
+//! Figures are labelled by following the image with one, as in
+//! `![Team structure](team.png){#fig:team}`. Tables are labelled in a caption
+//! paragraph straight after them, as in `Table: Day rates {#tbl:rates}`.
+//!
+//! Text refers to a label with `@sec:pricing`, which the preview and exports
+//! show as "Section 4.2". Headings are numbered 1, 1.1, 1.1.1 and so on from
+//! the document's top heading level, and figures and tables from 1 in the
+//! order they appear, so references stay right as the document changes.
+//!
+//! Labels are resolved within a single document.
+
+mod navigation;
+mod reference_check;
+
+use gpui::App;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use std::borrow::Cow;
+use std::ops::Range;
+
+const TABLE_CAPTION_PREFIX: &str = "Table:";
+
+pub fn init(cx: &mut App) {
+    navigation::init(cx);
+    reference_check::init(cx);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelKind {
+    Section,
+    Figure,
+    Table,
+}
+
+impl LabelKind {
+    const ALL: [LabelKind; 3] = [LabelKind::Section, LabelKind::Figure, LabelKind::Table];
+
+    /// The prefix that the names of labels of this kind start with.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            LabelKind::Section => "sec:",
+            LabelKind::Figure => "fig:",
+            LabelKind::Table => "tbl:",
+        }
+    }
+
+    /// The word that references to labels of this kind are shown with.
+    pub fn title(self) -> &'static str {
+        match self {
+            LabelKind::Section => "Section",
+            LabelKind::Figure => "Figure",
+            LabelKind::Table => "Table",
+        }
+    }
+
+    /// The kind of label that `name` is, from its prefix.
+    pub fn for_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| name.starts_with(kind.prefix()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub kind: LabelKind,
+    /// The label's name, including its prefix, such as `sec:pricing`.
+    pub name: String,
+    /// The range of the name in the source.
+    pub range: Range<usize>,
+    /// The range of the heading, image or table caption that is labelled.
+    pub target_range: Range<usize>,
+    /// The heading's text, or the figure's or table's caption.
+    pub title: String,
+    pub number: String,
+}
+
+impl Label {
+    /// How references to the label are shown, such as "Table 7".
+    pub fn display(&self) -> String {
+        format!("{} {}", self.kind.title(), self.number)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reference {
+    /// The name of the label referred to.
+    pub name: String,
+    /// The range of the reference in the source, including its `@`.
+    pub range: Range<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NumberedHeading {
+    pub range: Range<usize>,
+    pub number: String,
+}
+
+/// The labels and references in a Markdown document.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrossReferences {
+    pub headings: Vec<NumberedHeading>,
+    pub labels: Vec<Label>,
+    pub references: Vec<Reference>,
+    /// Source text that is shown differently in the preview and exports:
+    /// attributes are hidden, table captions numbered and references
+    /// replaced by what they refer to. Sorted by position, and each within a
+    /// single run of text.
+    pub replacements: Vec<(Range<usize>, String)>,
+}
+
+impl CrossReferences {
+    pub fn parse(text: &str) -> Self {
+        let events = Parser::new_ext(text, Options::all())
+            .into_offset_iter()
+            .collect::<Vec<_>>();
+        let top_level = events
+            .iter()
+            .filter_map(|(event, _)| match event {
+                Event::Start(Tag::Heading { level, .. }) => Some(*level as usize),
+                _ => None,
+            })
+            .min()
+            .unwrap_or(1);
+
+        let mut this = Self::default();
+        let mut heading_counters = [0; 6];
+        let mut heading: Option<(Range<usize>, Option<String>, String)> = None;
+        let mut image: Option<String> = None;
+        let mut figure_count = 0;
+        let mut table_count = 0;
+        let mut code_block_depth = 0;
+        for (ix, (event, range)) in events.iter().enumerate() {
+            match event {
+                Event::Start(Tag::Heading { level, id, .. }) => {
+                    let depth = *level as usize - top_level;
+                    heading_counters[depth] += 1;
+                    heading_counters[depth + 1..].fill(0);
+                    let number = heading_counters[..=depth]
+                        .iter()
+                        .map(|counter| counter.to_string())
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    this.headings.push(NumberedHeading {
+                        range: range.clone(),
+                        number,
+                    });
+                    heading = Some((
+                        range.clone(),
+                        id.as_ref().map(|id| id.to_string()),
+                        String::new(),
+                    ));
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    let Some((range, Some(id), title)) = heading.take() else {
+                        continue;
+                    };
+                    if LabelKind::for_name(&id) != Some(LabelKind::Section) {
+                        continue;
+                    }
+                    // The attribute follows the heading's text.
+                    let Some(offset) = text[range.clone()].rfind(&format!("#{id}")) else {
+                        continue;
+                    };
+                    let start = range.start + offset + 1;
+                    this.labels.push(Label {
+                        kind: LabelKind::Section,
+                        name: id.clone(),
+                        range: start..start + id.len(),
+                        target_range: range.clone(),
+                        title: title.trim().to_string(),
+                        number: this.headings.last().unwrap().number.clone(),
+                    });
+                }
+                Event::Start(Tag::Image { .. }) => image = Some(String::new()),
+                Event::End(TagEnd::Image) => {
+                    let alt_text = image.take().unwrap_or_default();
+                    // An attribute straight after an image labels it as a figure.
+                    let Some((Event::Text(next), next_range)) = events.get(ix + 1) else {
+                        continue;
+                    };
+                    if next_range.start != range.end || text[next_range.clone()] != **next {
+                        continue;
+                    }
+                    let Some(end) = next.starts_with('{').then(|| next.find('}')).flatten() else {
+                        continue;
+                    };
+                    let Some(name_range) = attribute_label(&next[..=end]) else {
+                        continue;
+                    };
+                    let name = &next[name_range.clone()];
+                    if LabelKind::for_name(name) != Some(LabelKind::Figure) {
+                        continue;
+                    }
+                    figure_count += 1;
+                    this.labels.push(Label {
+                        kind: LabelKind::Figure,
+                        name: name.to_string(),
+                        range: next_range.start + name_range.start
+                            ..next_range.start + name_range.end,
+                        target_range: range.clone(),
+                        title: alt_text,
+                        number: figure_count.to_string(),
+                    });
+                    this.replacements
+                        .push((next_range.start..next_range.start + end + 1, String::new()));
+                }
+                Event::End(TagEnd::Table) => {
+                    let Some((Event::Start(Tag::Paragraph), caption_range)) = events.get(ix + 1)
+                    else {
+                        continue;
+                    };
+                    if this.number_table_caption(text, caption_range, table_count + 1) {
+                        table_count += 1;
+                    }
+                }
+                Event::Start(Tag::CodeBlock(_)) => code_block_depth += 1,
+                Event::End(TagEnd::CodeBlock) => code_block_depth -= 1,
+                Event::Text(fragment) | Event::Code(fragment) => {
+                    if let Some((_, _, title)) = heading.as_mut() {
+                        title.push_str(fragment);
+                    }
+                    if let Some(alt_text) = image.as_mut() {
+                        alt_text.push_str(fragment);
+                    }
+                    let is_text = matches!(event, Event::Text(_));
+                    if is_text && code_block_depth == 0 && text[range.clone()] == **fragment {
+                        this.references
+                            .extend(references_in(fragment).map(|reference| Reference {
+                                name: fragment[reference.start + 1..reference.end].to_string(),
+                                range: range.start + reference.start..range.start + reference.end,
+                            }));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for reference in &this.references {
+            if let Some(label) = this.label(&reference.name) {
+                this.replacements
+                    .push((reference.range.clone(), label.display()));
+            }
+        }
+        this.replacements.sort_by_key(|(range, _)| range.start);
+        this
+    }
+
+    /// Numbers the caption of a table and adds its label, if the paragraph
+    /// at `range` is one.
+    fn number_table_caption(&mut self, text: &str, range: &Range<usize>, number: usize) -> bool {
+        let Some(caption) = text[range.clone()].strip_prefix(TABLE_CAPTION_PREFIX) else {
+            return false;
+        };
+        let prefix_range = range.start..range.start + TABLE_CAPTION_PREFIX.len();
+        self.replacements
+            .push((prefix_range.clone(), format!("Table {number}:")));
+
+        let caption = caption.trim_end();
+        let attribute_start = caption.ends_with('}').then(|| caption.rfind('{')).flatten();
+        let label = attribute_start.and_then(|attribute_start| {
+            let name_range = attribute_label(&caption[attribute_start..])?;
+            let name = &caption[attribute_start..][name_range.clone()];
+            if LabelKind::for_name(name) != Some(LabelKind::Table) {
+                return None;
+            }
+            let offset = prefix_range.end + attribute_start;
+            let title = caption[..attribute_start].trim();
+            // Hide the attribute along with the space before it.
+            let hidden_start =
+                prefix_range.end + title.len() + (caption.len() - caption.trim_start().len());
+            self.replacements.push((
+                hidden_start..prefix_range.end + caption.len(),
+                String::new(),
+            ));
+            Some(Label {
+                kind: LabelKind::Table,
+                name: name.to_string(),
+                range: offset + name_range.start..offset + name_range.end,
+                target_range: range.clone(),
+                title: title.to_string(),
+                number: number.to_string(),
+            })
+        });
+        self.labels.extend(label);
+        true
+    }
+
+    /// The first label with the given name.
+    pub fn label(&self, name: &str) -> Option<&Label> {
+        self.labels.iter().find(|label| label.name == name)
+    }
+
+    /// The number of the heading at `range`.
+    pub fn heading_number(&self, range: &Range<usize>) -> Option<&str> {
+        self.headings
+            .iter()
+            .find(|heading| heading.range == *range)
+            .map(|heading| heading.number.as_str())
+    }
+
+    /// The name of the label or reference at `offset`.
+    pub fn name_at(&self, offset: usize) -> Option<&str> {
+        let label = self
+            .labels
+            .iter()
+            .find(|label| label.range.start <= offset && offset <= label.range.end)
+            .map(|label| label.name.as_str());
+        label.or_else(|| {
+            self.references
+                .iter()
+                .find(|reference| reference.range.start <= offset && offset <= reference.range.end)
+                .map(|reference| reference.name.as_str())
+        })
+    }
+
+    pub fn references_to<'a>(&'a self, name: &'a str) -> impl 'a + Iterator<Item = &'a Reference> {
+        self.references
+            .iter()
+            .filter(move |reference| reference.name == name)
+    }
+
+    /// References to labels that don't exist.
+    pub fn dangling_references(&self) -> impl '_ + Iterator<Item = &Reference> {
+        self.references
+            .iter()
+            .filter(|reference| self.label(&reference.name).is_none())
+    }
+
+    /// Labels whose names are already used by an earlier label.
+    pub fn duplicate_labels(&self) -> impl '_ + Iterator<Item = &Label> {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(|(ix, label)| {
+                self.labels[..*ix]
+                    .iter()
+                    .any(|earlier| earlier.name == label.name)
+            })
+            .map(|(_, label)| label)
+    }
+
+    /// How the run of text at `range` in the source is shown in the preview
+    /// and exports.
+    pub fn replace_in<'a>(&self, range: Range<usize>, fragment: &'a str) -> Cow<'a, str> {
+        // Text that differs from its source, such as an escaped character,
+        // can't be mapped back to it.
+        if fragment.len() != range.len() {
+            return Cow::Borrowed(fragment);
+        }
+        let mut replacements = self
+            .replacements
+            .iter()
+            .filter(|(replaced, _)| range.start <= replaced.start && replaced.end <= range.end)
+            .peekable();
+        if replacements.peek().is_none() {
+            return Cow::Borrowed(fragment);
+        }
+
+        let mut result = String::new();
+        let mut offset = 0;
+        for (replaced, replacement) in replacements {
+            let start = replaced.start - range.start;
+            if start < offset {
+                continue;
+            }
+            result.push_str(&fragment[offset..start]);
+            result.push_str(replacement);
+            offset = replaced.end - range.start;
+        }
+        result.push_str(&fragment[offset..]);
+        Cow::Owned(result)
+    }
+}
+
+/// Finds references such as `@sec:pricing` in a run of text, returning the
+/// range of each including its `@`.
+fn references_in(text: &str) -> impl '_ + Iterator<Item = Range<usize>> {
+    text.match_indices('@').filter_map(move |(ix, _)| {
+        // Skip email addresses.
+        if text[..ix]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric())
+        {
+            return None;
+        }
+        let rest = &text[ix + 1..];
+        let kind = LabelKind::for_name(rest)?;
+        let len = rest.find(|c: char| !is_label_char(c)).unwrap_or(rest.len());
+        // Punctuation after a reference isn't part of it.
+        let name = rest[..len].trim_end_matches(['.', ':', '-']);
+        (name.len() > kind.prefix().len()).then(|| ix..ix + 1 + name.len())
+    })
+}
+
+fn is_label_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.')
+}
+
+/// The range of the label in an attribute such as `{#fig:team width=60%}`.
+fn attribute_label(attribute: &str) -> Option<Range<usize>> {
+    let contents = attribute.strip_prefix('{')?.strip_suffix('}')?;
+    let mut offset = 1;
+    for part in contents.split(' ') {
+        if let Some(name) = part.strip_prefix('#') {
+            return (!name.is_empty()).then(|| offset + 1..offset + 1 + name.len());
+        }
+        offset += part.len() + 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn shown(text: &str) -> String {
+        let cross_references = CrossReferences::parse(text);
+        let mut shown = text.to_string();
+        for (range, replacement) in cross_references.replacements.iter().rev() {
+            shown.replace_range(range.clone(), replacement);
+        }
+        shown
+    }
+
+    #[test]
+    fn test_numbering_and_references() {
+        let text = indoc! {"
+            # Response
+
+            ## Approach {#sec:approach}
+
+            ### Phases {#sec:phases}
+
+            As @sec:approach explains, see @fig:team and @tbl:rates.
+
+            ![Team structure](team.png){#fig:team}
+
+            | Role | Rate |
+            |------|------|
+            | Lead | 1200 |
+
+            Table: Day rates {#tbl:rates}
+
+            ## Pricing
+
+            Email bids@example.com, not @sec:missing.
+        "};
+        let cross_references = CrossReferences::parse(text);
+
+        let headings = cross_references
+            .headings
+            .iter()
+            .map(|heading| heading.number.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(headings, ["1", "1.1", "1.1.1", "1.2"]);
+
+        let labels = cross_references
+            .labels
+            .iter()
+            .map(|label| {
+                (
+                    &text[label.range.clone()],
+                    label.display(),
+                    label.title.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                ("sec:approach", "Section 1.1".to_string(), "Approach"),
+                ("sec:phases", "Section 1.1.1".to_string(), "Phases"),
+                ("fig:team", "Figure 1".to_string(), "Team structure"),
+                ("tbl:rates", "Table 1".to_string(), "Day rates"),
+            ]
+        );
+
+        let references = cross_references
+            .references
+            .iter()
+            .map(|reference| &text[reference.range.clone()])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            references,
+            ["@sec:approach", "@fig:team", "@tbl:rates", "@sec:missing"]
+        );
+        let dangling = cross_references
+            .dangling_references()
+            .map(|reference| reference.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(dangling, ["sec:missing"]);
+
+        assert_eq!(
+            shown(text),
+            indoc! {"
+                # Response
+
+                ## Approach {#sec:approach}
+
+                ### Phases {#sec:phases}
+
+                As Section 1.1 explains, see Figure 1 and Table 1.
+
+                ![Team structure](team.png)
+
+                | Role | Rate |
+                |------|------|
+                | Lead | 1200 |
+
+                Table 1: Day rates
+
+                ## Pricing
+
+                Email bids@example.com, not @sec:missing.
+            "}
+        );
+    }
+
+    #[test]
+    fn test_name_at_and_duplicates() {
+        let text = indoc! {"
+            ## Scope {#sec:scope}
+
+            ## Scope again {#sec:scope}
+
+            See @sec:scope.
+        "};
+        let cross_references = CrossReferences::parse(text);
+        let label_offset = text.find("sec:scope").unwrap();
+        let reference_offset = text.find("@sec:scope").unwrap();
+        assert_eq!(cross_references.name_at(label_offset), Some("sec:scope"));
+        assert_eq!(
+            cross_references.name_at(reference_offset + 3),
+            Some("sec:scope")
+        );
+        assert_eq!(cross_references.name_at(0), None);
+        assert_eq!(cross_references.references_to("sec:scope").count(), 1);
+
+        let duplicates = cross_references
+            .duplicate_labels()
+            .map(|label| label.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(duplicates, ["Scope again"]);
+        assert_eq!(
+            cross_references.label("sec:scope").map(Label::display),
+            Some("Section 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_references_ignore_code() {
+        let text = indoc! {"
+            Write `@sec:intro` to refer to a section.
+
+            ```
+            @fig:diagram
+            ```
+        "};
+        assert_eq!(CrossReferences::parse(text).references, []);
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::Result;
+use editor::{Editor, GotoDefinitionKind, SemanticsProvider};
+use gpui::{App, Entity, Task};
+use language::{Anchor, Buffer, BufferSnapshot, LanguageServerId, ToOffset as _};
+use project::{DocumentHighlight, Hover, InlayHint, Location, LocationLink, ProjectTransaction};
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::CrossReferences;
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|editor: &mut Editor, _, _| {
+        if !editor.mode().is_full() {
+            return;
+        }
+        if let Some(provider) = editor.semantics_provider() {
+            editor.set_semantics_provider(Some(Rc::new(CrossReferenceNavigation(provider))));
+        }
+    })
+    .detach();
+}
+
+/// Goes to the labels that references in Markdown refer to, and finds the
+/// references to them, leaving everything else to the editor's own provider.
+struct CrossReferenceNavigation(Rc<dyn SemanticsProvider>);
+
+impl SemanticsProvider for CrossReferenceNavigation {
+    fn hover(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Vec<Hover>>> {
+        self.0.hover(buffer, position, cx)
+    }
+
+    fn inline_values(
+        &self,
+        buffer: Entity<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<InlayHint>>>> {
+        self.0.inline_values(buffer, range, cx)
+    }
+
+    fn inlay_hints(
+        &self,
+        buffer: Entity<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<InlayHint>>>> {
+        self.0.inlay_hints(buffer, range, cx)
+    }
+
+    fn resolve_inlay_hint(
+        &self,
+        hint: InlayHint,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        cx: &mut App,
+    ) -> Option<Task<Result<InlayHint>>> {
+        self.0.resolve_inlay_hint(hint, buffer, server_id, cx)
+    }
+
+    fn supports_inlay_hints(&self, buffer: &Entity<Buffer>, cx: &mut App) -> bool {
+        self.0.supports_inlay_hints(buffer, cx)
+    }
+
+    fn document_highlights(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<DocumentHighlight>>>> {
+        self.0.document_highlights(buffer, position, cx)
+    }
+
+    fn definitions(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        kind: GotoDefinitionKind,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<LocationLink>>>> {
+        if let Some(definition) = label_definition(buffer, position, cx) {
+            return Some(Task::ready(Ok(vec![definition])));
+        }
+        self.0.definitions(buffer, position, kind, cx)
+    }
+
+    fn references(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<Location>>>> {
+        if let Some(references) = label_references(buffer, position, cx) {
+            return Some(Task::ready(Ok(references)));
+        }
+        self.0.references(buffer, position, cx)
+    }
+
+    fn range_for_rename(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Range<Anchor>>>>> {
+        self.0.range_for_rename(buffer, position, cx)
+    }
+
+    fn perform_rename(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        new_name: String,
+        cx: &mut App,
+    ) -> Option<Task<Result<ProjectTransaction>>> {
+        self.0.perform_rename(buffer, position, new_name, cx)
+    }
+}
+
+/// The cross-references in a Markdown buffer, if `position` is on one of
+/// its labels or references.
+fn cross_references_at(
+    buffer: &Entity<Buffer>,
+    position: Anchor,
+    cx: &App,
+) -> Option<(BufferSnapshot, CrossReferences, usize)> {
+    let buffer = buffer.read(cx);
+    if !prose::is_markdown(buffer) {
+        return None;
+    }
+    let snapshot = buffer.snapshot();
+    let offset = position.to_offset(&snapshot);
+    let cross_references = CrossReferences::parse(&snapshot.text());
+    cross_references.name_at(offset)?;
+    Some((snapshot, cross_references, offset))
+}
+
+/// Where the label that the reference at `position` refers to is defined.
+fn label_definition(buffer: &Entity<Buffer>, position: Anchor, cx: &App) -> Option<LocationLink> {
+    let (snapshot, cross_references, offset) = cross_references_at(buffer, position, cx)?;
+    let reference = cross_references
+        .references
+        .iter()
+        .find(|reference| reference.range.start <= offset && offset <= reference.range.end)?;
+    let label = cross_references.label(&reference.name)?;
+    Some(LocationLink {
+        origin: Some(location(buffer, &snapshot, reference.range.clone())),
+        target: location(buffer, &snapshot, label.range.clone()),
+    })
+}
+
+/// The label at `position`, or that the reference there refers to, followed
+/// by the references to it.
+fn label_references(buffer: &Entity<Buffer>, position: Anchor, cx: &App) -> Option<Vec<Location>> {
+    let (snapshot, cross_references, offset) = cross_references_at(buffer, position, cx)?;
+    let name = cross_references.name_at(offset)?;
+    let labels = cross_references
+        .labels
+        .iter()
+        .filter(|label| label.name == name)
+        .map(|label| label.range.clone());
+    let references = cross_references
+        .references_to(name)
+        .map(|reference| reference.range.clone());
+    Some(
+        labels
+            .chain(references)
+            .map(|range| location(buffer, &snapshot, range))
+            .collect(),
+    )
+}
+
+fn location(buffer: &Entity<Buffer>, snapshot: &BufferSnapshot, range: Range<usize>) -> Location {
+    Location {
+        buffer: buffer.clone(),
+        range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use indoc::indoc;
+    use language::{Language, LanguageConfig, Point, ToPoint as _};
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+
+    fn text_at(buffer: &Entity<Buffer>, location: &Location, cx: &TestAppContext) -> String {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .text_for_range(location.range.clone())
+                .collect::<String>()
+        })
+    }
+
+    #[gpui::test]
+    fn test_definition_and_references(cx: &mut TestAppContext) {
+        let text = indoc! {"
+            ## Rates {#tbl:rates-section}
+
+            | Role | Rate |
+            |------|------|
+            | Lead | 1200 |
+
+            Table: Day rates {#tbl:rates}
+
+            See @tbl:rates, and @tbl:rates again.
+        "};
+        let language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "Markdown".into(),
+                ..Default::default()
+            },
+            None,
+        ));
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language, cx));
+        let position = |cx: &mut TestAppContext, point: Point| {
+            buffer.read_with(cx, |buffer, _| buffer.anchor_before(point))
+        };
+
+        let reference = position(cx, Point::new(8, 6));
+        let definition = cx
+            .update(|cx| label_definition(&buffer, reference, cx))
+            .unwrap();
+        assert_eq!(text_at(&buffer, &definition.target, cx), "tbl:rates");
+        assert_eq!(
+            text_at(&buffer, definition.origin.as_ref().unwrap(), cx),
+            "@tbl:rates"
+        );
+
+        let references = cx
+            .update(|cx| label_references(&buffer, reference, cx))
+            .unwrap();
+        let references = references
+            .iter()
+            .map(|location| {
+                buffer.read_with(cx, |buffer, _| {
+                    location.range.start.to_point(&buffer.snapshot()).row
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(references, [6, 8, 8]);
+
+        let elsewhere = position(cx, Point::new(8, 0));
+        assert!(
+            cx.update(|cx| label_definition(&buffer, elsewhere, cx))
+                .is_none()
+        );
+    }
+}
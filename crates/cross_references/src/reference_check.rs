@@ -0,0 +1,208 @@
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use gpui::{App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task};
+use language::{Buffer, BufferEvent, Diagnostic, DiagnosticSeverity};
+use prose::DiagnosticSource;
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::CrossReferences;
+
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(300);
+
+struct GlobalReferenceChecker(Entity<ReferenceChecker>);
+
+impl Global for GlobalReferenceChecker {}
+
+pub(crate) fn init(cx: &mut App) {
+    let reference_checker = cx.new(|_| ReferenceChecker::default());
+    cx.set_global(GlobalReferenceChecker(reference_checker));
+
+    cx.observe_new(|editor: &mut Editor, _, cx| register_editor(editor, cx))
+        .detach();
+}
+
+fn register_editor(editor: &mut Editor, cx: &mut Context<Editor>) {
+    if !editor.mode().is_full() || editor.project.is_none() {
+        return;
+    }
+
+    let reference_checker = cx.global::<GlobalReferenceChecker>().0.clone();
+    let buffers = editor.buffer().read(cx).all_buffers();
+    for buffer in buffers {
+        reference_checker.update(cx, |reference_checker, cx| {
+            reference_checker.watch_buffer(buffer, cx)
+        });
+    }
+    cx.subscribe(&cx.entity(), move |_, _, event, cx| {
+        if let EditorEvent::ExcerptsAdded { buffer, .. } = event {
+            reference_checker.update(cx, |reference_checker, cx| {
+                reference_checker.watch_buffer(buffer.clone(), cx)
+            });
+        }
+    })
+    .detach();
+}
+
+/// Reports references to labels that don't exist, and labels defined more
+/// than once, in every Markdown buffer open in an editor.
+#[derive(Default)]
+struct ReferenceChecker {
+    buffers: HashMap<EntityId, WatchedBuffer>,
+}
+
+struct WatchedBuffer {
+    check: Task<()>,
+    _subscriptions: [Subscription; 2],
+}
+
+impl ReferenceChecker {
+    fn watch_buffer(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.entity_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+
+        let subscriptions = [
+            cx.subscribe(&buffer, |this, buffer, event, cx| match event {
+                BufferEvent::Edited | BufferEvent::LanguageChanged => {
+                    this.check_buffer(&buffer, Some(CHECK_DEBOUNCE), cx)
+                }
+                _ => {}
+            }),
+            cx.observe_release(&buffer, move |this, _, _| {
+                this.buffers.remove(&buffer_id);
+            }),
+        ];
+        self.buffers.insert(
+            buffer_id,
+            WatchedBuffer {
+                check: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.check_buffer(&buffer, None, cx);
+    }
+
+    fn check_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        debounce: Option<Duration>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(watched) = self.buffers.get_mut(&buffer.entity_id()) else {
+            return;
+        };
+        if !prose::is_markdown(buffer.read(cx)) {
+            watched.check = Task::ready(());
+            prose::clear_diagnostics(buffer, DiagnosticSource::CrossReference, cx);
+            return;
+        }
+
+        let snapshot = buffer.read(cx).snapshot();
+        let weak_buffer = buffer.downgrade();
+        watched.check = cx.spawn(async move |_, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+            let diagnostics = cx
+                .background_spawn({
+                    let text = snapshot.text();
+                    async move { reference_diagnostics(&CrossReferences::parse(&text)) }
+                })
+                .await;
+            if let Some(buffer) = weak_buffer.upgrade() {
+                cx.update(|cx| {
+                    prose::publish_diagnostics(
+                        &buffer,
+                        DiagnosticSource::CrossReference,
+                        &snapshot,
+                        diagnostics,
+                        cx,
+                    )
+                })
+                .ok();
+            }
+        });
+    }
+}
+
+fn reference_diagnostics(cross_references: &CrossReferences) -> Vec<(Range<usize>, Diagnostic)> {
+    let dangling = cross_references.dangling_references().map(|reference| {
+        let diagnostic = Diagnostic {
+            severity: DiagnosticSeverity::ERROR,
+            message: format!("There's no label named \"{}\".", reference.name),
+            ..Default::default()
+        };
+        (reference.range.clone(), diagnostic)
+    });
+    let duplicates = cross_references.duplicate_labels().map(|label| {
+        let diagnostic = Diagnostic {
+            severity: DiagnosticSeverity::WARNING,
+            message: format!(
+                "\"{}\" is already used as a label. References go to the first one.",
+                label.name
+            ),
+            ..Default::default()
+        };
+        (label.range.clone(), diagnostic)
+    });
+    dangling.chain(duplicates).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use language::{Language, LanguageConfig};
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_dangling_references_are_reported(cx: &mut TestAppContext) {
+        let text = indoc! {"
+            ## Approach {#sec:approach}
+
+            See @sec:approach and @sec:pricing.
+        "};
+        let language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "Markdown".into(),
+                ..Default::default()
+            },
+            None,
+        ));
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language, cx));
+        let reference_checker = cx.new(|_| ReferenceChecker::default());
+        reference_checker.update(cx, |reference_checker, cx| {
+            reference_checker.watch_buffer(buffer.clone(), cx)
+        });
+        cx.run_until_parked();
+
+        let reported = |cx: &mut TestAppContext| {
+            buffer.read_with(cx, |buffer, _| {
+                let snapshot = buffer.snapshot();
+                prose::diagnostics_in_range(
+                    &snapshot,
+                    DiagnosticSource::CrossReference,
+                    0..snapshot.len(),
+                )
+                .map(|entry| snapshot.text_for_range(entry.range).collect::<String>())
+                .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(reported(cx), ["@sec:pricing"]);
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(
+                [(buffer.len()..buffer.len(), "\n## Pricing {#sec:pricing}\n")],
+                None,
+                cx,
+            );
+        });
+        cx.executor().advance_clock(CHECK_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(reported(cx), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,574 @@
+use collections::HashMap;
+use language::{DiffOptions, text_diff_with_options};
+use requirements::classify_line;
+use std::{fmt::Write as _, ops::Range};
+
+/// A numbered clause of a document, or the text before its first clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clause {
+    pub number: Option<String>,
+    pub range: Range<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClauseChange {
+    Added,
+    Removed,
+    Amended,
+    /// The clause's text is unchanged, but it has a new number because
+    /// clauses before it were added or removed.
+    Renumbered {
+        from: String,
+    },
+}
+
+/// A clause that differs between two versions of a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangedClause {
+    /// The clause's number in the new document, or in the old document for
+    /// a removed clause. `None` for the text before the first clause.
+    pub number: Option<String>,
+    pub change: ClauseChange,
+    /// The clause in the old document. For an added clause, this is the
+    /// empty range where it would have been.
+    pub old_range: Range<usize>,
+    /// The clause in the new document. For a removed clause, this is the
+    /// empty range where it used to be.
+    pub new_range: Range<usize>,
+    pub word_edits: Vec<WordEdit>,
+}
+
+/// A run of words that was replaced, inserted or deleted within a clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordEdit {
+    pub old_range: Range<usize>,
+    pub new_range: Range<usize>,
+}
+
+/// The differences between two versions of a document, such as an RFT and
+/// the same RFT as amended by an addendum.
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    /// The old document's text, reflowed. All old ranges are into this.
+    pub old_text: String,
+    /// The new document's text, reflowed. All new ranges are into this.
+    pub new_text: String,
+    pub changed_clauses: Vec<ChangedClause>,
+}
+
+impl ChangedClause {
+    pub fn label(&self) -> String {
+        let clause = match &self.number {
+            Some(number) => format!("Clause {number}"),
+            None => "Preamble".to_string(),
+        };
+        match &self.change {
+            ClauseChange::Added => format!("{clause} added"),
+            ClauseChange::Removed => format!("{clause} removed"),
+            ClauseChange::Amended => format!("{clause} amended"),
+            ClauseChange::Renumbered { from } => format!("{clause} renumbered from {from}"),
+        }
+    }
+}
+
+impl Comparison {
+    /// Compares two versions of a converted document clause by clause.
+    ///
+    /// Clauses are matched by number. A clause whose text is unchanged but
+    /// whose number isn't is reported as renumbered, so that inserting a
+    /// clause doesn't make every clause after it look amended. Within each
+    /// amended clause, the changes are found word by word.
+    pub fn new(old_text: &str, new_text: &str) -> Self {
+        let old_text = reflow(old_text);
+        let new_text = reflow(new_text);
+        let old_clauses = clauses(&old_text);
+        let new_clauses = clauses(&new_text);
+        let old_clause_text = |ix: usize| old_text[old_clauses[ix].range.clone()].trim_end();
+        let new_clause_text = |ix: usize| new_text[new_clauses[ix].range.clone()].trim_end();
+
+        let mut old_ix_by_key = HashMap::default();
+        for (ix, key) in clause_keys(&old_clauses).into_iter().enumerate() {
+            old_ix_by_key.insert(key, ix);
+        }
+        let new_keys = clause_keys(&new_clauses);
+
+        let mut old_matched = vec![false; old_clauses.len()];
+        let mut new_matches: Vec<Option<(usize, Option<ClauseChange>)>> =
+            vec![None; new_clauses.len()];
+        for (new_ix, key) in new_keys.iter().enumerate() {
+            if let Some(&old_ix) = old_ix_by_key.get(key) {
+                if old_clause_text(old_ix) == new_clause_text(new_ix) {
+                    old_matched[old_ix] = true;
+                    new_matches[new_ix] = Some((old_ix, None));
+                }
+            }
+        }
+        for (new_ix, new_clause) in new_clauses.iter().enumerate() {
+            let Some(new_number) = &new_clause.number else {
+                continue;
+            };
+            if new_matches[new_ix].is_some() {
+                continue;
+            }
+            let new_body = clause_body(new_clause_text(new_ix), new_number);
+            if new_body.is_empty() {
+                continue;
+            }
+            let renumbered = old_clauses
+                .iter()
+                .enumerate()
+                .find_map(|(old_ix, old_clause)| {
+                    let old_number = old_clause.number.as_ref()?;
+                    (!old_matched[old_ix]
+                        && clause_body(old_clause_text(old_ix), old_number) == new_body)
+                        .then(|| (old_ix, old_number.clone()))
+                });
+            if let Some((old_ix, from)) = renumbered {
+                old_matched[old_ix] = true;
+                new_matches[new_ix] = Some((old_ix, Some(ClauseChange::Renumbered { from })));
+            }
+        }
+        for (new_ix, key) in new_keys.iter().enumerate() {
+            if new_matches[new_ix].is_some() {
+                continue;
+            }
+            if let Some(&old_ix) = old_ix_by_key.get(key) {
+                if !old_matched[old_ix] {
+                    old_matched[old_ix] = true;
+                    new_matches[new_ix] = Some((old_ix, Some(ClauseChange::Amended)));
+                }
+            }
+        }
+
+        // List the changes in the order of the new document, with each
+        // removed clause where it used to be.
+        let mut changed_clauses = Vec::new();
+        let mut removed = old_matched
+            .iter()
+            .map(|matched| !matched)
+            .collect::<Vec<_>>();
+        let mut old_position = 0;
+        let mut push_removed_before =
+            |old_ix: usize, new_position: usize, changed_clauses: &mut Vec<ChangedClause>| {
+                for (removed_ix, is_removed) in removed.iter_mut().enumerate().take(old_ix) {
+                    if std::mem::take(is_removed) {
+                        changed_clauses.push(ChangedClause {
+                            number: old_clauses[removed_ix].number.clone(),
+                            change: ClauseChange::Removed,
+                            old_range: old_clauses[removed_ix].range.clone(),
+                            new_range: new_position..new_position,
+                            word_edits: Vec::new(),
+                        });
+                    }
+                }
+            };
+        for (new_ix, new_clause) in new_clauses.iter().enumerate() {
+            match new_matches[new_ix].clone() {
+                Some((old_ix, change)) => {
+                    push_removed_before(old_ix, new_clause.range.start, &mut changed_clauses);
+                    let old_range = old_clauses[old_ix].range.clone();
+                    old_position = old_range.end;
+                    if let Some(change) = change {
+                        changed_clauses.push(ChangedClause {
+                            number: new_clause.number.clone(),
+                            change,
+                            word_edits: word_edits(
+                                &old_text,
+                                old_range.clone(),
+                                &new_text,
+                                new_clause.range.clone(),
+                            ),
+                            old_range,
+                            new_range: new_clause.range.clone(),
+                        });
+                    }
+                }
+                None => changed_clauses.push(ChangedClause {
+                    number: new_clause.number.clone(),
+                    change: ClauseChange::Added,
+                    old_range: old_position..old_position,
+                    new_range: new_clause.range.clone(),
+                    word_edits: Vec::new(),
+                }),
+            }
+        }
+        push_removed_before(old_clauses.len(), new_text.len(), &mut changed_clauses);
+
+        Self {
+            old_text,
+            new_text,
+            changed_clauses,
+        }
+    }
+
+    pub fn old_clause_text(&self, clause: &ChangedClause) -> &str {
+        self.old_text[clause.old_range.clone()].trim()
+    }
+
+    pub fn new_clause_text(&self, clause: &ChangedClause) -> &str {
+        self.new_text[clause.new_range.clone()].trim()
+    }
+
+    /// Describes a clause's word-level changes, such as `"14" replaced with
+    /// "10"`.
+    pub fn word_edit_descriptions(&self, clause: &ChangedClause) -> Vec<String> {
+        clause
+            .word_edits
+            .iter()
+            .filter_map(|edit| {
+                let old = self.old_text[edit.old_range.clone()].trim();
+                let new = self.new_text[edit.new_range.clone()].trim();
+                match (old.is_empty(), new.is_empty()) {
+                    (true, true) => None,
+                    (true, false) => Some(format!("added \"{new}\"")),
+                    (false, true) => Some(format!("removed \"{old}\"")),
+                    (false, false) => Some(format!("\"{old}\" replaced with \"{new}\"")),
+                }
+            })
+            .collect()
+    }
+
+    /// Lists the changed clauses as Markdown, with the text of each clause
+    /// before and after the change.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        if self.changed_clauses.is_empty() {
+            markdown.push_str("No clauses changed.\n");
+            return markdown;
+        }
+
+        for clause in &self.changed_clauses {
+            writeln!(markdown, "## {}\n", clause.label()).ok();
+            match clause.change {
+                ClauseChange::Added => write_quote(&mut markdown, self.new_clause_text(clause)),
+                ClauseChange::Removed => write_quote(&mut markdown, self.old_clause_text(clause)),
+                ClauseChange::Amended => {
+                    markdown.push_str("Before:\n\n");
+                    write_quote(&mut markdown, self.old_clause_text(clause));
+                    markdown.push_str("After:\n\n");
+                    write_quote(&mut markdown, self.new_clause_text(clause));
+                    for description in self.word_edit_descriptions(clause) {
+                        writeln!(markdown, "- {description}").ok();
+                    }
+                    if !clause.word_edits.is_empty() {
+                        markdown.push('\n');
+                    }
+                }
+                ClauseChange::Renumbered { .. } => {}
+            }
+        }
+        markdown
+    }
+}
+
+fn write_quote(markdown: &mut String, text: &str) {
+    for line in text.lines() {
+        if line.is_empty() {
+            markdown.push_str(">\n");
+        } else {
+            writeln!(markdown, "> {line}").ok();
+        }
+    }
+    markdown.push('\n');
+}
+
+/// Rewraps a converted document so that each paragraph is on one line.
+///
+/// Documents converted from PDF are broken into lines where the page was, so
+/// a one-word amendment can move every line break after it. Joining each
+/// paragraph's lines, and dropping page breaks and runs of whitespace, keeps
+/// the differences to what was actually reworded.
+pub fn reflow(text: &str) -> String {
+    let mut reflowed = String::new();
+    let mut in_paragraph = false;
+    let mut after_blank_line = false;
+    for line in text.lines() {
+        let line = line.replace('\x0c', "");
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if words.is_empty() {
+            in_paragraph = false;
+            after_blank_line = !reflowed.is_empty();
+            continue;
+        }
+
+        let (_, starts_block) = classify_line(&line);
+        if in_paragraph && !starts_block {
+            reflowed.push(' ');
+        } else {
+            if !reflowed.is_empty() {
+                reflowed.push('\n');
+            }
+            if after_blank_line {
+                reflowed.push('\n');
+            }
+        }
+        reflowed.push_str(&words.join(" "));
+
+        // Headings and table rows are a single line, so nothing joins them.
+        in_paragraph = !(words[0].starts_with('#') || words[0].starts_with('|'));
+        after_blank_line = false;
+    }
+    if !reflowed.is_empty() {
+        reflowed.push('\n');
+    }
+    reflowed
+}
+
+/// Splits a document into its numbered clauses, and the text before the
+/// first one if there is any.
+pub fn clauses(text: &str) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+    let mut clause_start = 0;
+    let mut number = None;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        if let (Some(line_number), _) = classify_line(line) {
+            if number.is_some() || !text[clause_start..line_start].trim().is_empty() {
+                clauses.push(Clause {
+                    number: number.take(),
+                    range: clause_start..line_start,
+                });
+            }
+            clause_start = line_start;
+            number = Some(line_number);
+        }
+        line_start += line.len();
+    }
+    if number.is_some() || !text[clause_start..].trim().is_empty() {
+        clauses.push(Clause {
+            number,
+            range: clause_start..text.len(),
+        });
+    }
+    clauses
+}
+
+/// Identifies each clause by its number, and how many clauses before it have
+/// the same number, since a table of contents repeats the numbers of the
+/// clauses it lists.
+fn clause_keys(clauses: &[Clause]) -> Vec<(Option<&str>, usize)> {
+    let mut occurrences = HashMap::default();
+    clauses
+        .iter()
+        .map(|clause| {
+            let number = clause.number.as_deref();
+            let occurrence = occurrences.entry(number).or_insert(0);
+            *occurrence += 1;
+            (number, *occurrence - 1)
+        })
+        .collect()
+}
+
+/// A clause's text without its number.
+fn clause_body<'a>(text: &'a str, number: &str) -> &'a str {
+    match text.find(number) {
+        Some(ix) => text[ix + number.len()..]
+            .trim_start_matches(['.', ')'])
+            .trim(),
+        None => text,
+    }
+}
+
+fn word_edits(
+    old_text: &str,
+    old_range: Range<usize>,
+    new_text: &str,
+    new_range: Range<usize>,
+) -> Vec<WordEdit> {
+    let options = DiffOptions {
+        language_scope: None,
+        max_word_diff_len: usize::MAX,
+        max_word_diff_line_count: usize::MAX,
+    };
+    let mut delta = 0isize;
+    text_diff_with_options(
+        &old_text[old_range.clone()],
+        &new_text[new_range.clone()],
+        options,
+    )
+    .into_iter()
+    .map(|(range, replacement)| {
+        let new_start = (range.start as isize + delta) as usize;
+        delta += replacement.len() as isize - range.len() as isize;
+        WordEdit {
+            old_range: old_range.start + range.start..old_range.start + range.end,
+            new_range: new_range.start + new_start..new_range.start + new_start + replacement.len(),
+        }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_reflow() {
+        let text = indoc! {"
+            # Request for Tender
+
+            The Department invites
+            tenders   for help desk
+            services.
+            \x0c
+            3.1 The Supplier must
+            respond within
+            4 hours.
+            3.2 The Supplier must
+            - keep records
+            - report monthly
+
+            | Item | Qty |
+            | Desks | 4 |
+        "};
+        assert_eq!(
+            reflow(text),
+            indoc! {"
+                # Request for Tender
+
+                The Department invites tenders for help desk services.
+
+                3.1 The Supplier must respond within 4 hours.
+                3.2 The Supplier must
+                - keep records
+                - report monthly
+
+                | Item | Qty |
+                | Desks | 4 |
+            "}
+        );
+    }
+
+    #[test]
+    fn test_clauses() {
+        let text = indoc! {"
+            # Request for Tender
+
+            ## 1 Introduction
+
+            1.1 The Department invites tenders.
+
+            1.2 Tenders close on Friday.
+        "};
+        let clauses = clauses(text)
+            .into_iter()
+            .map(|clause| (clause.number, text[clause.range].trim()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            clauses,
+            [
+                (None, "# Request for Tender"),
+                (Some("1".to_string()), "## 1 Introduction"),
+                (
+                    Some("1.1".to_string()),
+                    "1.1 The Department invites tenders."
+                ),
+                (Some("1.2".to_string()), "1.2 Tenders close on Friday."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let old_text = indoc! {"
+            ## 3 Delivery
+
+            3.1 The Supplier must deliver within
+            14 days of the order.
+
+            3.2 The Supplier must keep records.
+
+            3.3 Invoices are due monthly.
+        "};
+        let new_text = indoc! {"
+            ## 3 Delivery
+
+            3.1 The Supplier must deliver within 10 days of the order.
+
+            3.2 The Supplier must hold an ISO 27001 certification.
+
+            3.3 The Supplier must keep records.
+
+            3.4 Invoices are due monthly.
+
+            3.5 Prices are fixed for the term.
+        "};
+        let comparison = Comparison::new(old_text, new_text);
+        let changes = comparison
+            .changed_clauses
+            .iter()
+            .map(|clause| clause.label())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "Clause 3.1 amended",
+                "Clause 3.2 added",
+                "Clause 3.3 renumbered from 3.2",
+                "Clause 3.4 renumbered from 3.3",
+                "Clause 3.5 added",
+            ]
+        );
+
+        let amended = &comparison.changed_clauses[0];
+        assert_eq!(
+            comparison.word_edit_descriptions(amended),
+            ["\"14\" replaced with \"10\""]
+        );
+        let edit = &amended.word_edits[0];
+        assert_eq!(&comparison.old_text[edit.old_range.clone()], "14");
+        assert_eq!(&comparison.new_text[edit.new_range.clone()], "10");
+
+        let comparison = Comparison::new(new_text, old_text);
+        let changes = comparison
+            .changed_clauses
+            .iter()
+            .map(|clause| clause.label())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "Clause 3.1 amended",
+                "Clause 3.2 removed",
+                "Clause 3.2 renumbered from 3.3",
+                "Clause 3.3 renumbered from 3.4",
+                "Clause 3.5 removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let comparison = Comparison::new(
+            "1.1 Tenders close on Friday.\n1.2 Late tenders are excluded.\n",
+            "1.1 Tenders close on Monday.\n",
+        );
+        assert_eq!(
+            comparison.to_markdown(),
+            indoc! {r#"
+                ## Clause 1.1 amended
+
+                Before:
+
+                > 1.1 Tenders close on Friday.
+
+                After:
+
+                > 1.1 Tenders close on Monday.
+
+                - "Friday" replaced with "Monday"
+
+                ## Clause 1.2 removed
+
+                > 1.2 Late tenders are excluded.
+
+            "#}
+        );
+        assert_eq!(
+            Comparison::new("Same.\n", "Same.\n").to_markdown(),
+            "No clauses changed.\n"
+        );
+    }
+}
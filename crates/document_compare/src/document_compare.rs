@@ -0,0 +1,507 @@
+//! Compares two versions of a tender document, such as an RFT and the RFT as
+//! amended by an addendum.
+//!
+//! Both documents are converted like any other tender document, and the new
+//! version is shown with its differences from the old as diff hunks, with the
+//! changed words highlighted, beside a list of the clauses that were added,
+//! removed, amended or renumbered.
+
+mod comparison;
+mod document_picker;
+
+use anyhow::{Context as _, Result, anyhow};
+use buffer_diff::{BufferDiff, BufferDiffSnapshot};
+use document_picker::DocumentPicker;
+use editor::{Editor, EditorEvent, MultiBuffer, PathKey, scroll::Autoscroll};
+use gpui::{
+    AnyView, App, AppContext as _, AsyncApp, Entity, EventEmitter, FocusHandle, Focusable, Task,
+    actions,
+};
+use language::{
+    Anchor, Buffer, Capability, LanguageRegistry, LineEnding, OffsetRangeExt as _, Rope, TextBuffer,
+};
+use project::{Project, ProjectPath};
+use std::{any::TypeId, sync::Arc};
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use workspace::{
+    Item, ItemNavHistory, Workspace, notifications::NotifyTaskExt as _,
+    searchable::SearchableItemHandle,
+};
+
+pub use comparison::*;
+
+actions!(document_compare, [CompareDocuments]);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &CompareDocuments, window, cx| {
+            DocumentPicker::toggle(workspace, window, cx);
+        });
+    })
+    .detach();
+}
+
+/// Orders two documents by when they were probably issued: an addendum after
+/// the documents it amends, and otherwise by name, so that `RFT_v1.pdf` comes
+/// before `RFT_v2.pdf`.
+pub fn issue_order(a: ProjectPath, b: ProjectPath) -> (ProjectPath, ProjectPath) {
+    let key = |path: &ProjectPath| (requirements::is_addendum(&path.path), path.path.clone());
+    if key(&b) < key(&a) { (b, a) } else { (a, b) }
+}
+
+/// Converts two documents in the project and compares them.
+pub fn compare_project_documents(
+    project: &Entity<Project>,
+    old_path: &ProjectPath,
+    new_path: &ProjectPath,
+    cx: &mut App,
+) -> Task<Result<Comparison>> {
+    if !project.read(cx).is_local() {
+        return Task::ready(Err(anyhow!(
+            "Documents can only be compared in local projects"
+        )));
+    }
+
+    let loads = [old_path, new_path].map(|path| {
+        let load = semantic_index::load_document(project, path, cx);
+        (path.path.clone(), load)
+    });
+    cx.background_spawn(async move {
+        let mut texts = Vec::new();
+        for (path, load) in loads {
+            let text = load
+                .await
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            texts.push(text);
+        }
+        Ok(Comparison::new(&texts[0], &texts[1]))
+    })
+}
+
+/// Opens a view of the changes from the document at `old_path` to the one at
+/// `new_path`.
+pub fn open_comparison(
+    workspace: &mut Workspace,
+    old_path: ProjectPath,
+    new_path: ProjectPath,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let comparison = compare_project_documents(&project, &old_path, &new_path, cx);
+    let language_registry = project.read(cx).languages().clone();
+    let title = SharedString::from(format!(
+        "{} → {}",
+        file_name(&old_path),
+        file_name(&new_path)
+    ));
+    cx.spawn_in(window, async move |workspace, cx| {
+        let comparison = comparison.await?;
+        let buffer = build_buffer(comparison.new_text.clone(), &language_registry, cx).await?;
+        let diff =
+            build_buffer_diff(comparison.old_text.clone(), &buffer, &language_registry, cx).await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            let view = cx.new(|cx| {
+                DocumentComparison::new(title, comparison, buffer, diff, project, window, cx)
+            });
+            workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+        })
+    })
+    .detach_and_notify_err(window, cx);
+}
+
+fn file_name(path: &ProjectPath) -> String {
+    path.path
+        .file_name()
+        .unwrap_or(path.path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Shows the changes between two versions of a document.
+pub struct DocumentComparison {
+    title: SharedString,
+    comparison: Comparison,
+    buffer: Entity<Buffer>,
+    editor: Entity<Editor>,
+    selected_clause: Option<usize>,
+}
+
+impl DocumentComparison {
+    fn new(
+        title: SharedString,
+        comparison: Comparison,
+        buffer: Entity<Buffer>,
+        diff: Entity<BufferDiff>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer =
+                MultiBuffer::new(Capability::ReadOnly).with_title(title.to_string());
+            let snapshot = buffer.read(cx).snapshot();
+            let hunk_ranges = diff
+                .read(cx)
+                .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot, cx)
+                .map(|hunk| hunk.buffer_range.to_point(&snapshot))
+                .collect::<Vec<_>>();
+            multibuffer.set_excerpts_for_path(
+                PathKey::for_buffer(&buffer, cx),
+                buffer.clone(),
+                hunk_ranges,
+                editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                cx,
+            );
+            multibuffer.add_diff(diff, cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_read_only(true);
+            editor
+        });
+
+        let this = Self {
+            title,
+            comparison,
+            buffer,
+            editor,
+            selected_clause: None,
+        };
+        this.highlight_changed_words(cx);
+        this
+    }
+
+    pub fn comparison(&self) -> &Comparison {
+        &self.comparison
+    }
+
+    /// Highlights the words that each amendment inserted. The words it
+    /// replaced are shown in the deleted lines above them.
+    fn highlight_changed_words(&self, cx: &mut App) {
+        enum ChangedWords {}
+
+        let snapshot = self.buffer.read(cx).snapshot();
+        self.editor.update(cx, |editor, cx| {
+            let multibuffer = editor.buffer().read(cx);
+            let ranges = self
+                .comparison
+                .changed_clauses
+                .iter()
+                .flat_map(|clause| &clause.word_edits)
+                .filter(|edit| {
+                    !self.comparison.new_text[edit.new_range.clone()]
+                        .trim()
+                        .is_empty()
+                })
+                .filter_map(|edit| {
+                    let start = snapshot.offset_to_point(edit.new_range.start);
+                    let end = snapshot.offset_to_point(edit.new_range.end);
+                    Some(
+                        multibuffer.buffer_point_to_anchor(&self.buffer, start, cx)?
+                            ..multibuffer.buffer_point_to_anchor(&self.buffer, end, cx)?,
+                    )
+                })
+                .collect::<Vec<_>>();
+            editor.highlight_background::<ChangedWords>(
+                &ranges,
+                |colors| colors.version_control_added.opacity(0.3),
+                cx,
+            );
+        });
+    }
+
+    fn reveal_clause(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(clause) = self.comparison.changed_clauses.get(ix) else {
+            return;
+        };
+        self.selected_clause = Some(ix);
+        let point = self
+            .buffer
+            .read(cx)
+            .snapshot()
+            .offset_to_point(clause.new_range.start);
+        self.editor.update(cx, |editor, cx| {
+            let anchor = editor
+                .buffer()
+                .read(cx)
+                .buffer_point_to_anchor(&self.buffer, point, cx);
+            if let Some(anchor) = anchor {
+                editor.change_selections(Some(Autoscroll::top_relative(4)), window, cx, |s| {
+                    s.select_anchor_ranges([anchor..anchor])
+                });
+            }
+        });
+        window.focus(&self.editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    fn render_changed_clauses(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let heading = match self.comparison.changed_clauses.len() {
+            0 => "No clauses changed".to_string(),
+            1 => "1 clause changed".to_string(),
+            count => format!("{count} clauses changed"),
+        };
+
+        v_flex()
+            .id("changed-clauses")
+            .flex_none()
+            .w(rems(18.))
+            .h_full()
+            .overflow_y_scroll()
+            .border_r_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().panel_background)
+            .child(
+                h_flex().px_2().py_1p5().child(
+                    Label::new(heading)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .children(
+                self.comparison
+                    .changed_clauses
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, clause)| {
+                        let (icon, color) = match clause.change {
+                            ClauseChange::Added => (IconName::Plus, Color::Created),
+                            ClauseChange::Removed => (IconName::Dash, Color::Deleted),
+                            ClauseChange::Amended => (IconName::Pencil, Color::Modified),
+                            ClauseChange::Renumbered { .. } => (IconName::Hash, Color::Muted),
+                        };
+                        let word_edits = self.comparison.word_edit_descriptions(clause);
+                        ListItem::new(ix)
+                            .spacing(ListItemSpacing::Sparse)
+                            .toggle_state(self.selected_clause == Some(ix))
+                            .start_slot(Icon::new(icon).size(IconSize::Small).color(color))
+                            .child(Label::new(clause.label()).size(LabelSize::Small))
+                            .when(!word_edits.is_empty(), |item| {
+                                let word_edits = word_edits.join("\n");
+                                item.tooltip(Tooltip::text(word_edits))
+                            })
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.reveal_clause(ix, window, cx)
+                            }))
+                    }),
+            )
+    }
+}
+
+impl Render for DocumentComparison {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .key_context("DocumentComparison")
+            .size_full()
+            .child(self.render_changed_clauses(cx))
+            .child(div().h_full().flex_1().child(self.editor.clone()))
+    }
+}
+
+impl Focusable for DocumentComparison {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for DocumentComparison {}
+
+impl Item for DocumentComparison {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff))
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        self.title.clone()
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+}
+
+async fn build_buffer(
+    text: String,
+    language_registry: &Arc<LanguageRegistry>,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Buffer>> {
+    let language = language_registry.language_for_name("Markdown").await.ok();
+    cx.new(|cx| {
+        let buffer = TextBuffer::new_normalized(
+            0,
+            cx.entity_id().as_non_zero_u64().into(),
+            LineEnding::Unix,
+            Rope::from(text),
+        );
+        let mut buffer = Buffer::build(buffer, None, Capability::ReadOnly);
+        buffer.set_language(language, cx);
+        buffer
+    })
+}
+
+async fn build_buffer_diff(
+    old_text: String,
+    buffer: &Entity<Buffer>,
+    language_registry: &Arc<LanguageRegistry>,
+    cx: &mut AsyncApp,
+) -> Result<Entity<BufferDiff>> {
+    let buffer = cx.update(|cx| buffer.read(cx).snapshot())?;
+
+    let base_buffer = cx
+        .update(|cx| {
+            Buffer::build_snapshot(
+                old_text.clone().into(),
+                buffer.language().cloned(),
+                Some(language_registry.clone()),
+                cx,
+            )
+        })?
+        .await;
+
+    let diff_snapshot = cx
+        .update(|cx| {
+            BufferDiffSnapshot::new_with_base_buffer(
+                buffer.text.clone(),
+                Some(old_text.into()),
+                base_buffer,
+                cx,
+            )
+        })?
+        .await;
+
+    let secondary_diff = cx.new(|cx| {
+        let mut diff = BufferDiff::new(&buffer, cx);
+        diff.set_snapshot(diff_snapshot.clone(), &buffer, cx);
+        diff
+    })?;
+
+    cx.new(|cx| {
+        let mut diff = BufferDiff::new(&buffer.text, cx);
+        diff.set_snapshot(diff_snapshot, &buffer, cx);
+        diff.set_secondary_diff(secondary_diff);
+        diff
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_compare_project_documents(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                "RFT.md": indoc! {"
+                    ## 3 Delivery
+
+                    3.1 The Supplier must deliver within
+                    14 days of the order.
+                "},
+                "RFT Addendum 1.md": indoc! {"
+                    ## 3 Delivery
+
+                    3.1 The Supplier must deliver within 10 days of the order.
+
+                    3.2 The Supplier must hold an ISO 27001 certification.
+                "},
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/tender").as_ref()], cx).await;
+        let (old_path, new_path) = project.read_with(cx, |project, cx| {
+            let addendum = project
+                .find_project_path("tender/RFT Addendum 1.md", cx)
+                .unwrap();
+            let rft = project.find_project_path("tender/RFT.md", cx).unwrap();
+            issue_order(addendum, rft)
+        });
+        assert_eq!(old_path.path.as_ref(), std::path::Path::new("RFT.md"));
+
+        let comparison = cx
+            .update(|cx| compare_project_documents(&project, &old_path, &new_path, cx))
+            .await
+            .unwrap();
+        let changes = comparison
+            .changed_clauses
+            .iter()
+            .map(|clause| clause.label())
+            .collect::<Vec<_>>();
+        assert_eq!(changes, ["Clause 3.1 amended", "Clause 3.2 added"]);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::open_comparison;
+use file_icons::FileIcons;
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity,
+};
+use picker::{Picker, PickerDelegate};
+use project::ProjectPath;
+use requirements::TENDER_DOCUMENT_EXTENSIONS;
+use std::sync::Arc;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+/// Picks the two documents to compare: first the original, then the revised
+/// version.
+pub(crate) struct DocumentPicker {
+    picker: Entity<Picker<DocumentPickerDelegate>>,
+}
+
+impl DocumentPicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let project = workspace.project().read(cx);
+        let include_root_name = project.visible_worktrees(cx).count() > 1;
+        let documents = project
+            .visible_worktrees(cx)
+            .flat_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let worktree_id = worktree.id();
+                let root_name = worktree.root_name().to_string();
+                worktree
+                    .files(false, 0)
+                    .filter(|entry| {
+                        entry
+                            .path
+                            .extension()
+                            .and_then(|extension| extension.to_str())
+                            .is_some_and(|extension| {
+                                TENDER_DOCUMENT_EXTENSIONS
+                                    .contains(&extension.to_lowercase().as_str())
+                            })
+                    })
+                    .map(|entry| {
+                        let path = ProjectPath {
+                            worktree_id,
+                            path: entry.path.clone(),
+                        };
+                        let label = if include_root_name {
+                            format!("{root_name}/{}", entry.path.display())
+                        } else {
+                            entry.path.display().to_string()
+                        };
+                        (path, label)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let workspace_handle = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate =
+                DocumentPickerDelegate::new(cx.entity().downgrade(), workspace_handle, documents);
+            Self {
+                picker: cx.new(|cx| Picker::uniform_list(delegate, window, cx)),
+            }
+        });
+    }
+}
+
+impl Render for DocumentPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for DocumentPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for DocumentPicker {}
+impl ModalView for DocumentPicker {}
+
+struct DocumentPickerDelegate {
+    document_picker: WeakEntity<DocumentPicker>,
+    workspace: WeakEntity<Workspace>,
+    documents: Vec<(ProjectPath, String)>,
+    /// The original document, once it's been picked.
+    old_document: Option<usize>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl DocumentPickerDelegate {
+    fn new(
+        document_picker: WeakEntity<DocumentPicker>,
+        workspace: WeakEntity<Workspace>,
+        documents: Vec<(ProjectPath, String)>,
+    ) -> Self {
+        Self {
+            document_picker,
+            workspace,
+            documents,
+            old_document: None,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for DocumentPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.old_document {
+            None => "Select the original document…".into(),
+            Some(_) => "Select the revised document to compare it with…".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No tender documents found".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .documents
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| Some(*ix) != self.old_document)
+            .map(|(ix, (_, label))| StringMatchCandidate::new(ix, label))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(document_ix) = self
+            .matches
+            .get(self.selected_index)
+            .map(|mat| mat.candidate_id)
+        else {
+            return;
+        };
+
+        let Some(old_document_ix) = self.old_document else {
+            self.old_document = Some(document_ix);
+            self.selected_index = 0;
+            cx.defer_in(window, |picker, window, cx| {
+                picker.set_query("", window, cx);
+                picker.refresh_placeholder(window, cx);
+                picker.refresh(window, cx);
+            });
+            return;
+        };
+
+        let old_path = self.documents[old_document_ix].0.clone();
+        let new_path = self.documents[document_ix].0.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                open_comparison(workspace, old_path, new_path, window, cx)
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.document_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let (path, label) = self.documents.get(mat.candidate_id)?;
+        let icon = FileIcons::get_icon(&path.path, cx)
+            .map(|icon| Icon::from_path(icon).color(Color::Muted));
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot::<Icon>(icon)
+                .child(HighlightedLabel::new(label.clone(), mat.positions.clone())),
+        )
+    }
+}
@@ -0,0 +1,838 @@
+//! Exports Markdown responses to the formats buyers ask for, written next to
+//! the Markdown file they're exported from.
+//!
+//! PDFs are laid out and written in-process, so the same layout can be used
+//! to check a response against its page limits before it's submitted.
+//!
+//! Projects with a `submission.toml` can also be built into a single bundle
+//! from all of their response files, with a cover page and appendices.
+//!
+//! Word response templates can be imported as Markdown to be filled in, and
+//! the responses written back into the original template.
+//!
+//! The same layout also backs the Markdown preview's print layout mode.
+
+mod docx;
+mod docx_import;
+mod fonts;
+mod layout;
+mod pdf;
+mod pdf_reader;
+mod print_preview;
+mod submission;
+mod text_runs;
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use editor::Editor;
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, PathPromptOptions, Task, Window, actions};
+use markdown_preview::markdown_elements::{
+    Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown, ParsedMarkdownElement,
+};
+use markdown_preview::markdown_parser::parse_markdown;
+use markdown_preview::markdown_preview_view::MarkdownPreviewView;
+use project::DirectoryLister;
+use settings::{Settings as _, SettingsLocation};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tender_manifest::{TenderManifest, TenderManifestStore, section_limits};
+use workspace::notifications::{NotificationId, NotifyTaskExt as _};
+use workspace::{OpenOptions, Toast, Workspace};
+use writing_app_settings::WritingAppSettings;
+
+pub use docx::write_docx;
+pub use docx_import::{
+    DocxField, DocxMapping, FieldLocation, ImportedDocx, MAPPING_EXTENSION, WrittenBack,
+    import_docx, write_back_docx,
+};
+pub use fonts::{FontStyle, Fonts};
+pub use layout::{
+    HeadingPages, Layout, LayoutOptions, LinkTarget, Page, PageItem, TextColor, layout_document,
+};
+pub use pdf::{AttachedPdfs, write_pdf};
+pub use pdf_reader::PdfFile;
+pub use submission::{
+    Appendix, Assembly, BrokenReference, HeadingNumbering, SUBMISSION_FILE_NAME,
+    SubmissionDefinition, SubmissionFile, SubmissionFormat, assemble,
+};
+
+actions!(
+    document_export,
+    [
+        ExportDocx,
+        ExportPdf,
+        CheckPageLimits,
+        BuildSubmission,
+        ImportDocx,
+        WriteBackDocx
+    ]
+);
+
+/// Points per millimetre, for the page margin setting.
+const POINTS_PER_MM: f32 = 72. / 25.4;
+
+pub fn init(cx: &mut App) {
+    markdown_preview::print_layout::set_print_layout_provider(
+        print_preview::DocumentPrintLayout,
+        cx,
+    );
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(|workspace, _: &ExportDocx, window, cx| {
+                export_docx(workspace, cx).detach_and_notify_err(window, cx);
+            })
+            .register_action(|workspace, _: &ExportPdf, window, cx| {
+                export_pdf(workspace, true, cx).detach_and_notify_err(window, cx);
+            })
+            .register_action(|workspace, _: &CheckPageLimits, window, cx| {
+                export_pdf(workspace, false, cx).detach_and_notify_err(window, cx);
+            })
+            .register_action(|workspace, _: &BuildSubmission, window, cx| {
+                build_submission(workspace, cx).detach_and_notify_err(window, cx);
+            })
+            .register_action(|workspace, _: &ImportDocx, window, cx| {
+                import_docx_template(workspace, window, cx).detach_and_notify_err(window, cx);
+            })
+            .register_action(|workspace, _: &WriteBackDocx, window, cx| {
+                write_back_docx_template(workspace, cx).detach_and_notify_err(window, cx);
+            });
+    })
+    .detach();
+}
+
+/// The Markdown document to export: the active editor's, or the one shown in
+/// the active preview.
+fn active_markdown_editor(workspace: &Workspace, cx: &mut App) -> Option<Entity<Editor>> {
+    let item = workspace.active_item(cx)?;
+    if let Some(preview) = item.downcast::<MarkdownPreviewView>() {
+        return preview.read(cx).active_editor().cloned();
+    }
+    let editor = item.act_as::<Editor>(cx)?;
+    let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+    let is_markdown = buffer
+        .read(cx)
+        .language()
+        .is_some_and(|language| language.name() == "Markdown".into());
+    is_markdown.then_some(editor)
+}
+
+/// A saved Markdown document to export, and the settings that apply to it.
+struct ExportSource {
+    abs_path: PathBuf,
+    text: String,
+    settings: WritingAppSettings,
+    worktree_root: Option<Arc<Path>>,
+    manifest: Option<Arc<TenderManifest>>,
+}
+
+fn export_source(workspace: &Workspace, cx: &mut App) -> Result<ExportSource> {
+    let buffer = active_markdown_editor(workspace, cx)
+        .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        .context("Open a Markdown document to export it")?;
+    let buffer = buffer.read(cx);
+    let file = buffer
+        .file()
+        .and_then(|file| file.as_local())
+        .context("Save the document before exporting it")?;
+    let settings = WritingAppSettings::get(
+        Some(SettingsLocation {
+            worktree_id: file.worktree_id(cx),
+            path: file.path(),
+        }),
+        cx,
+    )
+    .clone();
+    let worktree_root = workspace
+        .project()
+        .read(cx)
+        .worktree_for_id(file.worktree_id(cx), cx)
+        .map(|worktree| worktree.read(cx).abs_path());
+    let manifest = TenderManifestStore::for_project(workspace.project(), cx)
+        .read(cx)
+        .manifest();
+    Ok(ExportSource {
+        abs_path: file.abs_path(cx),
+        text: buffer.text(),
+        settings,
+        worktree_root,
+        manifest,
+    })
+}
+
+fn export_docx(workspace: &mut Workspace, cx: &mut Context<Workspace>) -> Task<Result<()>> {
+    let source = match export_source(workspace, cx) {
+        Ok(source) => source,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let reference_path = source
+        .settings
+        .docx_reference
+        .as_ref()
+        .map(|reference| resolve_path(reference, source.worktree_root.as_deref()));
+    let fs = workspace.app_state().fs.clone();
+
+    cx.spawn(async move |workspace, cx| {
+        let abs_path = source.abs_path;
+        let markdown = parse_document(
+            &source.text,
+            abs_path.parent().map(Path::to_path_buf),
+            &source.settings,
+        )
+        .await;
+        let images = load_images(&fs, &markdown).await;
+        let reference = match &reference_path {
+            Some(path) => Some(fs.load_bytes(path).await.with_context(|| {
+                format!("failed to read the reference document {}", path.display())
+            })?),
+            None => None,
+        };
+        let contents = write_docx(&markdown, reference, &images)
+            .await
+            .with_context(|| format!("failed to export {}", abs_path.display()))?;
+
+        let export_path = abs_path.with_extension("docx");
+        fs.write(&export_path, &contents).await?;
+        workspace.update(cx, |workspace, cx| {
+            let message = format!("Exported {}.", file_name(&export_path));
+            show_export_toast(workspace, message, Some(export_path), cx);
+        })
+    })
+}
+
+/// Lays out the active document as a PDF and checks it against its page
+/// limits, writing the PDF next to it if `write` is set.
+fn export_pdf(
+    workspace: &mut Workspace,
+    write: bool,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let source = match export_source(workspace, cx) {
+        Ok(source) => source,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let options = layout_options(&source.settings, source.manifest.as_deref());
+    let fs = workspace.app_state().fs.clone();
+
+    cx.spawn(async move |workspace, cx| {
+        let abs_path = source.abs_path;
+        let markdown = parse_document(
+            &source.text,
+            abs_path.parent().map(Path::to_path_buf),
+            &source.settings,
+        )
+        .await;
+        let images = load_images(&fs, &markdown).await;
+        let title = abs_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned());
+        let text = source.text;
+        let manifest = source.manifest;
+        let (contents, page_count, breaches) = cx
+            .background_spawn(async move {
+                let fonts = Fonts::load()?;
+                let layout = layout_document(&markdown, &images, &fonts, &options);
+                let breaches = page_limit_breaches(&text, &layout, manifest.as_deref());
+                let contents = if write {
+                    Some(write_pdf(
+                        &layout,
+                        &fonts,
+                        &images,
+                        title.as_deref(),
+                        &AttachedPdfs::default(),
+                    )?)
+                } else {
+                    None
+                };
+                anyhow::Ok((contents, layout.page_count(), breaches))
+            })
+            .await
+            .with_context(|| format!("failed to lay out {}", abs_path.display()))?;
+
+        let export_path = abs_path.with_extension("pdf");
+        if let Some(contents) = &contents {
+            fs.write(&export_path, contents).await?;
+        }
+        workspace.update(cx, |workspace, cx| {
+            let mut message = if contents.is_some() {
+                format!("Exported {} ({page_count} pages).", file_name(&export_path))
+            } else if breaches.is_empty() {
+                format!(
+                    "{} fits its page limits at {page_count} pages.",
+                    file_name(&abs_path)
+                )
+            } else {
+                String::new()
+            };
+            for breach in breaches {
+                if !message.is_empty() {
+                    message.push(' ');
+                }
+                message.push_str(&breach);
+            }
+            show_export_toast(workspace, message, contents.map(|_| export_path), cx);
+        })
+    })
+}
+
+/// Builds the submission described by the project's `submission.toml` in
+/// each of its formats, unless any of its cross-references are broken.
+fn build_submission(workspace: &mut Workspace, cx: &mut Context<Workspace>) -> Task<Result<()>> {
+    let Some(worktree) = workspace
+        .project()
+        .read(cx)
+        .visible_worktrees(cx)
+        .find(|worktree| {
+            worktree
+                .read(cx)
+                .entry_for_path(SUBMISSION_FILE_NAME)
+                .is_some()
+        })
+    else {
+        return Task::ready(Err(anyhow!(
+            "Add a {SUBMISSION_FILE_NAME} to the project to build a submission"
+        )));
+    };
+    let worktree = worktree.read(cx);
+    let root = worktree.abs_path();
+    let project_files = worktree
+        .files(false, 0)
+        .map(|entry| entry.path.to_path_buf())
+        .collect::<Vec<_>>();
+    let settings = WritingAppSettings::get(
+        Some(SettingsLocation {
+            worktree_id: worktree.id(),
+            path: Path::new(SUBMISSION_FILE_NAME),
+        }),
+        cx,
+    )
+    .clone();
+    let manifest = TenderManifestStore::for_project(workspace.project(), cx)
+        .read(cx)
+        .manifest();
+    let fs = workspace.app_state().fs.clone();
+
+    cx.spawn(async move |workspace, cx| {
+        let definition =
+            SubmissionDefinition::parse(&fs.load(&root.join(SUBMISSION_FILE_NAME)).await?)?;
+        let mut files = Vec::new();
+        for path in definition.response_files(&project_files)? {
+            let text = fs
+                .load(&root.join(&path))
+                .await
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            files.push(SubmissionFile { path, text });
+        }
+        anyhow::ensure!(
+            !files.is_empty(),
+            "{SUBMISSION_FILE_NAME} doesn't list any response files"
+        );
+
+        let output = PathBuf::from(&definition.output);
+        let output_dir = output.parent().unwrap_or(Path::new("")).to_path_buf();
+        let assembly = assemble(
+            &files,
+            definition.numbering,
+            definition.numbering_depth,
+            &output_dir,
+        );
+        if !assembly.broken_references.is_empty() {
+            let messages = assembly
+                .broken_references
+                .iter()
+                .map(BrokenReference::message)
+                .collect::<Vec<_>>();
+            return Err(anyhow!(
+                "Fix these references before building the submission:\n{}",
+                messages.join("\n")
+            ));
+        }
+
+        let output_path = root.join(&output);
+        let output_dir = root.join(&output_dir);
+        fs.create_dir(&output_dir).await?;
+        let with_contents = format!("{}\n{}", assembly.contents_markdown(3), assembly.markdown);
+        let mut exported = Vec::new();
+        let mut breaches = Vec::new();
+        for format in &definition.formats {
+            let export_path = output_path.with_extension(format.extension());
+            let contents = match format {
+                SubmissionFormat::Markdown => with_contents.clone().into_bytes(),
+                SubmissionFormat::Docx => {
+                    let markdown =
+                        parse_markdown(&with_contents, Some(output_dir.clone()), None).await;
+                    let images = load_images(&fs, &markdown).await;
+                    let reference = match &settings.docx_reference {
+                        Some(reference) => {
+                            let path = resolve_path(reference, Some(root.as_ref()));
+                            Some(fs.load_bytes(&path).await.with_context(|| {
+                                format!("failed to read the reference document {}", path.display())
+                            })?)
+                        }
+                        None => None,
+                    };
+                    write_docx(&markdown, reference, &images).await?
+                }
+                SubmissionFormat::Pdf => {
+                    let markdown =
+                        parse_markdown(&assembly.markdown, Some(output_dir.clone()), None).await;
+                    let images = load_images(&fs, &markdown).await;
+                    let mut attached = Vec::new();
+                    for path in definition
+                        .cover
+                        .iter()
+                        .chain(definition.appendices.iter().map(Appendix::path))
+                    {
+                        let path = root.join(path);
+                        let data = fs
+                            .load_bytes(&path)
+                            .await
+                            .with_context(|| format!("failed to read {}", path.display()))?;
+                        attached.push((path, data));
+                    }
+                    let has_cover = definition.cover.is_some();
+                    let appendix_titles = definition
+                        .appendices
+                        .iter()
+                        .map(Appendix::title)
+                        .collect::<Vec<_>>();
+                    let options = layout_options(&settings, manifest.as_deref());
+                    let text = assembly.markdown.clone();
+                    let manifest = manifest.clone();
+                    let title = output
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned());
+                    let (contents, pdf_breaches) = cx
+                        .background_spawn(async move {
+                            let mut pdfs = attached
+                                .into_iter()
+                                .map(|(path, data)| {
+                                    PdfFile::parse(data).with_context(|| {
+                                        format!("failed to read {}", path.display())
+                                    })
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            let before = if has_cover {
+                                vec![pdfs.remove(0)]
+                            } else {
+                                Vec::new()
+                            };
+                            let mut options = options;
+                            for (title, pdf) in appendix_titles.into_iter().zip(&pdfs) {
+                                options.appendices.push((title, pdf.pages()?.len()));
+                            }
+                            let attached = AttachedPdfs {
+                                before,
+                                after: pdfs,
+                            };
+                            let fonts = Fonts::load()?;
+                            let layout = layout_document(&markdown, &images, &fonts, &options);
+                            let breaches = page_limit_breaches(&text, &layout, manifest.as_deref());
+                            let contents =
+                                write_pdf(&layout, &fonts, &images, title.as_deref(), &attached)?;
+                            anyhow::Ok((contents, breaches))
+                        })
+                        .await?;
+                    breaches = pdf_breaches;
+                    contents
+                }
+            };
+            fs.write(&export_path, &contents).await?;
+            exported.push(export_path);
+        }
+
+        workspace.update(cx, |workspace, cx| {
+            let names = exported
+                .iter()
+                .map(|path| file_name(path))
+                .collect::<Vec<_>>();
+            let mut message = format!("Built the submission: {}.", names.join(", "));
+            for breach in breaches {
+                message.push(' ');
+                message.push_str(&breach);
+            }
+            show_export_toast(workspace, message, exported.into_iter().next(), cx);
+        })
+    })
+}
+
+/// Imports a Word template chosen by the user, writing its Markdown and the
+/// mapping of its fields next to it, and opens the Markdown.
+fn import_docx_template(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let fs = workspace.app_state().fs.clone();
+    let paths = workspace.prompt_for_open_path(
+        PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        },
+        DirectoryLister::Local(fs.clone()),
+        window,
+        cx,
+    );
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let Some(template_path) = paths.await?.and_then(|mut paths| paths.pop()) else {
+            return Ok(());
+        };
+        let markdown_path = template_path.with_extension("md");
+        anyhow::ensure!(
+            !fs.is_file(&markdown_path).await,
+            "{} already exists",
+            file_name(&markdown_path)
+        );
+        let bytes = fs.load_bytes(&template_path).await?;
+        let imported = import_docx(bytes, file_name(&template_path))
+            .await
+            .with_context(|| format!("failed to import {}", template_path.display()))?;
+        fs.write(&markdown_path, imported.markdown.as_bytes())
+            .await?;
+        fs.write(
+            &markdown_path.with_extension(MAPPING_EXTENSION),
+            serde_json::to_string_pretty(&imported.mapping)?.as_bytes(),
+        )
+        .await?;
+
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_abs_path(markdown_path, OpenOptions::default(), window, cx)
+            })?
+            .await?;
+        Ok(())
+    })
+}
+
+/// Writes the active document's fields back into the Word template it was
+/// imported from, saving the result as a copy of the template.
+fn write_back_docx_template(
+    workspace: &mut Workspace,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let source = match export_source(workspace, cx) {
+        Ok(source) => source,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let fs = workspace.app_state().fs.clone();
+
+    cx.spawn(async move |workspace, cx| {
+        let mapping_path = source.abs_path.with_extension(MAPPING_EXTENSION);
+        let mapping = fs
+            .load(&mapping_path)
+            .await
+            .context("This document wasn't imported from a Word template")?;
+        let mapping = serde_json::from_str::<DocxMapping>(&mapping)
+            .with_context(|| format!("invalid mapping {}", mapping_path.display()))?;
+        let template_path = mapping_path
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(&mapping.template);
+        let template = fs
+            .load_bytes(&template_path)
+            .await
+            .with_context(|| format!("failed to read the template {}", template_path.display()))?;
+        let written = write_back_docx(template, &mapping, &source.text).await?;
+
+        let stem = template_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let export_path = template_path.with_file_name(format!("{stem} (completed).docx"));
+        fs.write(&export_path, &written.docx).await?;
+        workspace.update(cx, |workspace, cx| {
+            let mut message = format!(
+                "Wrote {} fields into {}.",
+                written.fields_written,
+                file_name(&export_path)
+            );
+            for warning in written.warnings {
+                message.push(' ');
+                message.push_str(&warning);
+            }
+            show_export_toast(workspace, message, Some(export_path), cx);
+        })
+    })
+}
+
+fn show_export_toast(
+    workspace: &mut Workspace,
+    message: String,
+    export_path: Option<PathBuf>,
+    cx: &mut Context<Workspace>,
+) {
+    struct DocumentExported;
+
+    let mut toast = Toast::new(NotificationId::unique::<DocumentExported>(), message);
+    if let Some(export_path) = export_path {
+        toast = toast.on_click("Reveal", move |_, cx| cx.reveal_path(&export_path));
+    }
+    workspace.show_toast(toast, cx);
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The text shown at the top of each page: the tender's reference and title.
+fn running_header(manifest: &TenderManifest) -> Option<String> {
+    let parts = [manifest.reference.as_deref(), manifest.title.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join(" — "))
+}
+
+/// How a document's pages are laid out, from its settings and the tender's
+/// manifest.
+fn layout_options(
+    settings: &WritingAppSettings,
+    manifest: Option<&TenderManifest>,
+) -> LayoutOptions {
+    LayoutOptions {
+        page_size: settings.page_size,
+        margin: settings.page_margin_mm * POINTS_PER_MM,
+        font_size: settings.page_font_size,
+        header: manifest.and_then(running_header),
+        ..Default::default()
+    }
+}
+
+/// Checks a laid-out document against its page limits: the manifest's limit
+/// for the whole document, and the limits of its sections from annotations
+/// or the manifest. The table of contents doesn't count towards them.
+pub fn page_limit_breaches(
+    text: &str,
+    layout: &Layout,
+    manifest: Option<&TenderManifest>,
+) -> Vec<String> {
+    let mut breaches = Vec::new();
+    if let Some(limit) = manifest.and_then(|manifest| manifest.limits.pages) {
+        let pages = layout.body_page_count();
+        if pages > limit {
+            breaches.push(format!(
+                "This document is {pages} pages, over its {limit}-page limit."
+            ));
+        }
+    }
+
+    for (section, heading, limit) in limited_sections(text, layout, manifest) {
+        let pages = heading.page_count();
+        if pages > limit {
+            breaches.push(format!(
+                "\"{}\" is {pages} pages, over its {limit}-page limit.",
+                section.title
+            ));
+        }
+    }
+    breaches
+}
+
+/// The sections of a document that have page limits, with their headings in
+/// its layout and their limits.
+fn limited_sections<'a>(
+    text: &str,
+    layout: &'a Layout,
+    manifest: Option<&TenderManifest>,
+) -> Vec<(prose::Section, &'a HeadingPages, usize)> {
+    let sections = prose::sections(text);
+    let limits = section_limits(text, &sections, manifest);
+    sections
+        .into_iter()
+        .zip(limits)
+        .filter_map(|(section, limit)| {
+            let limit = limit.pages?;
+            let heading = layout.headings.iter().find(|heading| {
+                heading.source_range.start < section.heading_range.end
+                    && section.heading_range.start < heading.source_range.end
+            })?;
+            Some((section, heading, limit))
+        })
+        .collect()
+}
+
+/// A path from the settings, which may be relative to the project root or
+/// start with `~`.
+fn resolve_path(path: &str, root: Option<&Path>) -> PathBuf {
+    let path = match path.strip_prefix("~/") {
+        Some(relative) => util::paths::home_dir().join(relative),
+        None => PathBuf::from(path),
+    };
+    match root {
+        Some(root) if path.is_relative() => root.join(path),
+        _ => path,
+    }
+}
+
+/// Parses a document to be exported, numbering its headings if the settings
+/// ask for it.
+async fn parse_document(
+    text: &str,
+    file_location_directory: Option<PathBuf>,
+    settings: &WritingAppSettings,
+) -> ParsedMarkdown {
+    let mut markdown = parse_markdown(text, file_location_directory, None).await;
+    if settings.number_headings {
+        markdown.number_headings();
+    }
+    markdown
+}
+
+/// Loads the local images a document shows, so that they can be embedded.
+/// Images that can't be read are left out, and their alt text is exported
+/// instead.
+async fn load_images(fs: &Arc<dyn Fs>, markdown: &ParsedMarkdown) -> HashMap<PathBuf, Vec<u8>> {
+    let mut paths = Vec::new();
+    collect_image_paths(&markdown.children, &mut paths);
+
+    let mut images = HashMap::default();
+    for path in paths {
+        if images.contains_key(&path) {
+            continue;
+        }
+        match fs.load_bytes(&path).await {
+            Ok(bytes) => {
+                images.insert(path, bytes);
+            }
+            Err(error) => log::warn!("failed to load image {}: {error:#}", path.display()),
+        }
+    }
+    images
+}
+
+fn collect_image_paths(elements: &[ParsedMarkdownElement], paths: &mut Vec<PathBuf>) {
+    fn collect(paragraph: &MarkdownParagraph, paths: &mut Vec<PathBuf>) {
+        for chunk in paragraph {
+            if let MarkdownParagraphChunk::Image(image) = chunk {
+                if let Link::Path { path, .. } = &image.link {
+                    paths.push(path.clone());
+                }
+            }
+        }
+    }
+
+    for element in elements {
+        match element {
+            ParsedMarkdownElement::Paragraph(paragraph) => collect(paragraph, paths),
+            ParsedMarkdownElement::Heading(heading) => collect(&heading.contents, paths),
+            ParsedMarkdownElement::ListItem(item) => collect_image_paths(&item.content, paths),
+            ParsedMarkdownElement::BlockQuote(block_quote) => {
+                collect_image_paths(&block_quote.children, paths)
+            }
+            ParsedMarkdownElement::Callout(callout) => {
+                collect_image_paths(&callout.children, paths)
+            }
+            ParsedMarkdownElement::FootnoteDefinition(footnote) => {
+                collect_image_paths(&footnote.children, paths)
+            }
+            ParsedMarkdownElement::DefinitionList(list) => {
+                for item in &list.items {
+                    collect(&item.term, paths);
+                    for definition in &item.definitions {
+                        collect_image_paths(definition, paths);
+                    }
+                }
+            }
+            ParsedMarkdownElement::Table(table) => {
+                for cell in std::iter::once(&table.header)
+                    .chain(&table.body)
+                    .flat_map(|row| &row.children)
+                {
+                    collect(cell, paths);
+                }
+            }
+            ParsedMarkdownElement::CodeBlock(_)
+            | ParsedMarkdownElement::HorizontalRule(_)
+            | ParsedMarkdownElement::PageBreak(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tender_manifest::{Limits, SectionLimit};
+    use util::path;
+
+    #[gpui::test]
+    async fn test_page_limit_breaches() {
+        let text = format!(
+            "# Response\n\n## Methodology\n\n{}\n\n## Pricing\n\n<!-- limit: 1 page -->\n\nSee the schedule.\n",
+            "We will deliver the service in three phases. ".repeat(300)
+        );
+        let markdown = parse_markdown(&text, None, None).await;
+        let fonts = Fonts::load().unwrap();
+        let layout = layout_document(
+            &markdown,
+            &HashMap::default(),
+            &fonts,
+            &LayoutOptions::default(),
+        );
+        let methodology_pages = layout.headings[1].page_count();
+        assert!(methodology_pages > 1);
+
+        let manifest = TenderManifest {
+            limits: Limits {
+                pages: Some(1),
+                sections: vec![SectionLimit {
+                    heading: "Methodology".to_string(),
+                    pages: Some(1),
+                    words: None,
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            page_limit_breaches(&text, &layout, Some(&manifest)),
+            [
+                format!(
+                    "This document is {} pages, over its 1-page limit.",
+                    layout.body_page_count()
+                ),
+                format!("\"Methodology\" is {methodology_pages} pages, over its 1-page limit."),
+            ]
+        );
+        assert_eq!(
+            page_limit_breaches(&text, &layout, None),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_running_header() {
+        let manifest = TenderManifest {
+            reference: Some("RFT-2026-041".to_string()),
+            title: Some("Help desk services".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            running_header(&manifest).as_deref(),
+            Some("RFT-2026-041 — Help desk services")
+        );
+        assert_eq!(running_header(&TenderManifest::default()), None);
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let root = Path::new(path!("/tenders/help-desk"));
+        assert_eq!(
+            resolve_path("templates/Corporate.docx", Some(root)),
+            root.join("templates/Corporate.docx")
+        );
+        assert_eq!(
+            resolve_path(path!("/shared/Corporate.docx"), Some(root)),
+            PathBuf::from(path!("/shared/Corporate.docx"))
+        );
+        assert_eq!(
+            resolve_path("~/Corporate.docx", Some(root)),
+            util::paths::home_dir().join("Corporate.docx")
+        );
+    }
+}
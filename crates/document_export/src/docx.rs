@@ -0,0 +1,1147 @@
+use anyhow::{Context as _, Result};
+use async_zip::base::read::mem::ZipFileReader;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use collections::HashMap;
+use futures::AsyncReadExt as _;
+use image::{ImageFormat, ImageReader};
+use markdown_preview::markdown_elements::{
+    HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
+    ParsedMarkdownElement, ParsedMarkdownFootnoteDefinition, ParsedMarkdownListItem,
+    ParsedMarkdownListItemType, ParsedMarkdownTable, ParsedMarkdownTableAlignment,
+    ParsedMarkdownText,
+};
+use std::fmt::Write as _;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use crate::text_runs::{Run, text_runs};
+
+pub(crate) const DOCUMENT_PART: &str = "word/document.xml";
+const DOCUMENT_RELS_PART: &str = "word/_rels/document.xml.rels";
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+
+const RELATIONSHIP_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const DOCUMENT_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml";
+const TEMPLATE_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.template.main+xml";
+const NUMBERING_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml";
+const STYLES_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml";
+
+/// The namespaces used in the document body, which are added to a reference
+/// document's root element if it doesn't already declare them.
+const DOCUMENT_NAMESPACES: &[(&str, &str)] = &[
+    (
+        "w",
+        "http://schemas.openxmlformats.org/wordprocessingml/2006/main",
+    ),
+    (
+        "r",
+        "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+    ),
+    (
+        "wp",
+        "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing",
+    ),
+    ("a", "http://schemas.openxmlformats.org/drawingml/2006/main"),
+    (
+        "pic",
+        "http://schemas.openxmlformats.org/drawingml/2006/picture",
+    ),
+];
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+/// An A4 page with 1 inch margins, used when there's no reference document.
+const DEFAULT_SECTION: &str = r#"<w:sectPr><w:pgSz w:w="11906" w:h="16838"/><w:pgMar w:top="1440" w:right="1440" w:bottom="1440" w:left="1440" w:header="708" w:footer="708" w:gutter="0"/></w:sectPr>"#;
+
+/// The text width of that page, in EMUs, which images are scaled down to fit.
+const MAX_IMAGE_WIDTH: u64 = 5_731_510;
+/// EMUs per pixel, at 96 pixels per inch.
+const EMUS_PER_PIXEL: u64 = 9525;
+
+/// The numbering definitions for lists. They're numbered well above those
+/// Word assigns, so that they can be added to a reference document's own.
+const BULLET_ABSTRACT_NUM_ID: usize = 9001;
+const ORDERED_ABSTRACT_NUM_ID: usize = 9002;
+const BULLET_NUM_ID: usize = 9001;
+const FIRST_ORDERED_NUM_ID: usize = 9002;
+
+const DOC_DEFAULTS: &str = r#"<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Calibri" w:cs="Calibri"/><w:sz w:val="22"/><w:szCs w:val="22"/><w:lang w:val="en-AU"/></w:rPr></w:rPrDefault><w:pPrDefault><w:pPr><w:spacing w:after="160" w:line="259" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>"#;
+
+/// The styles the export uses, by ID. A reference document's own definitions
+/// take precedence, and these fill in any it lacks.
+const DEFAULT_STYLES: &[(&str, &str)] = &[
+    (
+        "Normal",
+        r#"<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>"#,
+    ),
+    (
+        "Heading1",
+        r#"<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="32"/><w:szCs w:val="32"/></w:rPr></w:style>"#,
+    ),
+    (
+        "Heading2",
+        r#"<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="28"/><w:szCs w:val="28"/></w:rPr></w:style>"#,
+    ),
+    (
+        "Heading3",
+        r#"<w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="200" w:after="80"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:b/><w:sz w:val="26"/><w:szCs w:val="26"/></w:rPr></w:style>"#,
+    ),
+    (
+        "Heading4",
+        r#"<w:style w:type="paragraph" w:styleId="Heading4"><w:name w:val="heading 4"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="160" w:after="40"/><w:outlineLvl w:val="3"/></w:pPr><w:rPr><w:b/><w:i/><w:sz w:val="24"/><w:szCs w:val="24"/></w:rPr></w:style>"#,
+    ),
+    (
+        "Heading5",
+        r#"<w:style w:type="paragraph" w:styleId="Heading5"><w:name w:val="heading 5"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="160" w:after="40"/><w:outlineLvl w:val="4"/></w:pPr><w:rPr><w:b/></w:rPr></w:style>"#,
+    ),
+    (
+        "Heading6",
+        r#"<w:style w:type="paragraph" w:styleId="Heading6"><w:name w:val="heading 6"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="160" w:after="40"/><w:outlineLvl w:val="5"/></w:pPr><w:rPr><w:i/></w:rPr></w:style>"#,
+    ),
+    (
+        "ListBullet",
+        r#"<w:style w:type="paragraph" w:styleId="ListBullet"><w:name w:val="List Bullet"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="80"/><w:contextualSpacing/></w:pPr></w:style>"#,
+    ),
+    (
+        "ListNumber",
+        r#"<w:style w:type="paragraph" w:styleId="ListNumber"><w:name w:val="List Number"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="80"/><w:contextualSpacing/></w:pPr></w:style>"#,
+    ),
+    (
+        "ListContinue",
+        r#"<w:style w:type="paragraph" w:styleId="ListContinue"><w:name w:val="List Continue"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="80"/><w:ind w:left="720"/></w:pPr></w:style>"#,
+    ),
+    (
+        "Quote",
+        r#"<w:style w:type="paragraph" w:styleId="Quote"><w:name w:val="Quote"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:ind w:left="720" w:right="720"/></w:pPr><w:rPr><w:i/><w:color w:val="404040"/></w:rPr></w:style>"#,
+    ),
+    (
+        "DefinitionTerm",
+        r#"<w:style w:type="paragraph" w:styleId="DefinitionTerm"><w:name w:val="Definition Term"/><w:basedOn w:val="Normal"/><w:next w:val="Definition"/><w:pPr><w:keepNext/><w:spacing w:after="40"/></w:pPr><w:rPr><w:b/></w:rPr></w:style>"#,
+    ),
+    (
+        "Definition",
+        r#"<w:style w:type="paragraph" w:styleId="Definition"><w:name w:val="Definition"/><w:basedOn w:val="Normal"/><w:pPr><w:ind w:left="720"/></w:pPr></w:style>"#,
+    ),
+    (
+        "FootnoteText",
+        r#"<w:style w:type="paragraph" w:styleId="FootnoteText"><w:name w:val="footnote text"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="40" w:line="240" w:lineRule="auto"/><w:ind w:left="360" w:hanging="360"/></w:pPr><w:rPr><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:style>"#,
+    ),
+    (
+        "SourceCode",
+        r#"<w:style w:type="paragraph" w:styleId="SourceCode"><w:name w:val="Source Code"/><w:basedOn w:val="Normal"/><w:pPr><w:shd w:val="clear" w:color="auto" w:fill="F2F2F2"/><w:spacing w:after="160" w:line="240" w:lineRule="auto"/></w:pPr><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:style>"#,
+    ),
+    (
+        "VerbatimChar",
+        r#"<w:style w:type="character" w:styleId="VerbatimChar"><w:name w:val="Verbatim Char"/><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:style>"#,
+    ),
+    (
+        "Hyperlink",
+        r#"<w:style w:type="character" w:styleId="Hyperlink"><w:name w:val="Hyperlink"/><w:rPr><w:color w:val="0563C1"/><w:u w:val="single"/></w:rPr></w:style>"#,
+    ),
+    (
+        "TableGrid",
+        r#"<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:pPr><w:spacing w:after="0" w:line="240" w:lineRule="auto"/></w:pPr><w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:left w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:bottom w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:right w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:insideH w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="auto"/></w:tblBorders><w:tblCellMar><w:left w:w="108" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr><w:tblStylePr w:type="firstRow"><w:rPr><w:b/></w:rPr></w:tblStylePr></w:style>"#,
+    ),
+];
+
+/// Writes Markdown as a Word document.
+///
+/// Elements are given Word's built-in style names (`Heading1`, `ListBullet`,
+/// `Quote`, `TableGrid` and so on), so that a `reference` document can
+/// restyle them. Its styles, page setup, headers and footers are kept, and
+/// only its body is replaced. Images are embedded from `images`, keyed by
+/// their absolute paths, and citation links are left out.
+pub async fn write_docx(
+    markdown: &ParsedMarkdown,
+    reference: Option<Vec<u8>>,
+    images: &HashMap<PathBuf, Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let reference = match reference {
+        Some(bytes) => Some(
+            DocxPackage::read(bytes)
+                .await
+                .context("failed to read the reference document")?,
+        ),
+        None => None,
+    };
+
+    let mut body = Body::new(images);
+    body.write_elements(&markdown.children, None);
+
+    let mut parts = Vec::new();
+    match &reference {
+        Some(reference) => body.package_with_reference(reference, &mut parts)?,
+        None => body.package(&mut parts),
+    }
+
+    zip_parts(parts).await
+}
+
+/// Packages the parts of a `.docx`.
+pub(crate) async fn zip_parts(parts: Vec<(String, Vec<u8>)>) -> Result<Vec<u8>> {
+    let mut writer = ZipFileWriter::new(Vec::new());
+    for (name, content) in parts {
+        let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+        writer.write_entry_whole(entry, &content).await?;
+    }
+    Ok(writer.close().await?)
+}
+
+/// The parts of a `.docx`: a reference document or a template.
+pub(crate) struct DocxPackage {
+    pub parts: Vec<(String, Vec<u8>)>,
+}
+
+impl DocxPackage {
+    pub async fn read(bytes: Vec<u8>) -> Result<Self> {
+        let reader = ZipFileReader::new(bytes).await?;
+        let mut parts = Vec::new();
+        for ix in 0..reader.file().entries().len() {
+            let name = reader.file().entries()[ix].filename().as_str()?.to_string();
+            if name.ends_with('/') {
+                continue;
+            }
+            let mut contents = Vec::new();
+            reader
+                .reader_with_entry(ix)
+                .await?
+                .read_to_end(&mut contents)
+                .await?;
+            parts.push((name, contents));
+        }
+        anyhow::ensure!(
+            parts.iter().any(|(name, _)| name == DOCUMENT_PART),
+            "it isn't a Word document"
+        );
+        Ok(Self { parts })
+    }
+
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.parts
+            .iter()
+            .find(|(part, _)| part == name)
+            .map(|(_, contents)| String::from_utf8_lossy(contents).into_owned())
+    }
+}
+
+struct Relationship {
+    id: String,
+    kind: &'static str,
+    target: String,
+    external: bool,
+}
+
+/// The media embedded for an image.
+struct Media {
+    part: String,
+    extension: &'static str,
+    content_type: &'static str,
+    contents: Vec<u8>,
+}
+
+/// The document body being written, with the relationships and media it
+/// refers to.
+struct Body<'a> {
+    images: &'a HashMap<PathBuf, Vec<u8>>,
+    xml: String,
+    relationships: Vec<Relationship>,
+    media: Vec<Media>,
+    /// The level and first number of each ordered list, which each get their
+    /// own numbering so that they count from their first item's number.
+    ordered_lists: Vec<(usize, u64)>,
+    /// The numbering of the ordered lists in progress, by level.
+    list_levels: Vec<Option<usize>>,
+    drawings: usize,
+}
+
+impl<'a> Body<'a> {
+    fn new(images: &'a HashMap<PathBuf, Vec<u8>>) -> Self {
+        Self {
+            images,
+            xml: String::new(),
+            relationships: Vec::new(),
+            media: Vec::new(),
+            ordered_lists: Vec::new(),
+            list_levels: Vec::new(),
+            drawings: 0,
+        }
+    }
+
+    fn write_elements(&mut self, elements: &[ParsedMarkdownElement], style: Option<&str>) {
+        let mut in_list = false;
+        for element in elements {
+            if !element.is_list_item() {
+                in_list = false;
+            }
+            match element {
+                ParsedMarkdownElement::Heading(heading) => {
+                    let level = match heading.level {
+                        HeadingLevel::H1 => 1,
+                        HeadingLevel::H2 => 2,
+                        HeadingLevel::H3 => 3,
+                        HeadingLevel::H4 => 4,
+                        HeadingLevel::H5 => 5,
+                        HeadingLevel::H6 => 6,
+                    };
+                    let properties = format!(r#"<w:pStyle w:val="Heading{level}"/>"#);
+                    self.write_paragraph(&heading.contents, &properties);
+                }
+                ParsedMarkdownElement::Paragraph(paragraph) => {
+                    self.write_paragraph(paragraph, &paragraph_style(style));
+                }
+                ParsedMarkdownElement::ListItem(item) => {
+                    if !in_list {
+                        self.list_levels.clear();
+                        in_list = true;
+                    }
+                    self.write_list_item(item);
+                }
+                ParsedMarkdownElement::Table(table) => self.write_table(table),
+                ParsedMarkdownElement::BlockQuote(block_quote) => {
+                    self.write_elements(&block_quote.children, Some("Quote"));
+                }
+                ParsedMarkdownElement::Callout(callout) => {
+                    self.xml.push_str(
+                        r#"<w:p><w:pPr><w:pStyle w:val="Quote"/><w:keepNext/></w:pPr><w:r><w:rPr><w:b/></w:rPr>"#,
+                    );
+                    write_text(&mut self.xml, callout.kind.title());
+                    self.xml.push_str("</w:r></w:p>");
+                    self.write_elements(&callout.children, Some("Quote"));
+                }
+                ParsedMarkdownElement::DefinitionList(list) => {
+                    for item in &list.items {
+                        self.write_paragraph(&item.term, &paragraph_style(Some("DefinitionTerm")));
+                        for definition in &item.definitions {
+                            self.write_elements(definition, Some("Definition"));
+                        }
+                    }
+                }
+                ParsedMarkdownElement::FootnoteDefinition(footnote) => {
+                    self.write_footnote(footnote);
+                }
+                ParsedMarkdownElement::CodeBlock(code_block) => {
+                    self.xml
+                        .push_str(r#"<w:p><w:pPr><w:pStyle w:val="SourceCode"/></w:pPr><w:r>"#);
+                    write_text(&mut self.xml, code_block.contents.trim_end_matches('\n'));
+                    self.xml.push_str("</w:r></w:p>");
+                }
+                ParsedMarkdownElement::HorizontalRule(_) => {
+                    self.xml.push_str(r#"<w:p><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="6" w:space="1" w:color="auto"/></w:pBdr></w:pPr></w:p>"#);
+                }
+                ParsedMarkdownElement::PageBreak(_) => {
+                    self.xml
+                        .push_str(r#"<w:p><w:r><w:br w:type="page"/></w:r></w:p>"#);
+                }
+            }
+        }
+    }
+
+    /// Writes a footnote definition where it appears in the document, with
+    /// its number before its first paragraph.
+    fn write_footnote(&mut self, footnote: &ParsedMarkdownFootnoteDefinition) {
+        let properties = paragraph_style(Some("FootnoteText"));
+        write!(self.xml, "<w:p><w:pPr>{properties}</w:pPr><w:r>").unwrap();
+        write_text(&mut self.xml, &format!("{}.\t", footnote.number));
+        self.xml.push_str("</w:r>");
+
+        let mut children = footnote.children.iter();
+        match children.next() {
+            Some(ParsedMarkdownElement::Paragraph(paragraph)) => {
+                self.write_chunks(paragraph);
+                self.xml.push_str("</w:p>");
+            }
+            Some(element) => {
+                self.xml.push_str("</w:p>");
+                self.write_elements(std::slice::from_ref(element), Some("FootnoteText"));
+            }
+            None => self.xml.push_str("</w:p>"),
+        }
+        let rest = children.as_slice();
+        if !rest.is_empty() {
+            self.write_elements(rest, Some("FootnoteText"));
+        }
+    }
+
+    fn write_list_item(&mut self, item: &ParsedMarkdownListItem) {
+        let level = (item.depth as usize).saturating_sub(1).min(8);
+        self.list_levels.truncate(level + 1);
+        self.list_levels.resize(level + 1, None);
+
+        let (style, num_id) = match &item.item_type {
+            ParsedMarkdownListItemType::Ordered(start) => {
+                let ix = *self.list_levels[level].get_or_insert_with(|| {
+                    self.ordered_lists.push((level, *start));
+                    self.ordered_lists.len() - 1
+                });
+                ("ListNumber", FIRST_ORDERED_NUM_ID + ix)
+            }
+            ParsedMarkdownListItemType::Task(..) | ParsedMarkdownListItemType::Unordered => {
+                self.list_levels[level] = None;
+                ("ListBullet", BULLET_NUM_ID)
+            }
+        };
+        let properties = format!(
+            r#"<w:pStyle w:val="{style}"/><w:numPr><w:ilvl w:val="{level}"/><w:numId w:val="{num_id}"/></w:numPr>"#
+        );
+
+        let mut content = item.content.iter();
+        match content.next() {
+            Some(ParsedMarkdownElement::Paragraph(paragraph)) => {
+                let checkbox = match &item.item_type {
+                    ParsedMarkdownListItemType::Task(true, _) => Some("☒ "),
+                    ParsedMarkdownListItemType::Task(false, _) => Some("☐ "),
+                    _ => None,
+                };
+                self.xml.push_str("<w:p><w:pPr>");
+                self.xml.push_str(&properties);
+                self.xml.push_str("</w:pPr>");
+                if let Some(checkbox) = checkbox {
+                    self.xml.push_str("<w:r>");
+                    write_text(&mut self.xml, checkbox);
+                    self.xml.push_str("</w:r>");
+                }
+                self.write_chunks(paragraph);
+                self.xml.push_str("</w:p>");
+            }
+            Some(element) => {
+                write!(self.xml, "<w:p><w:pPr>{properties}</w:pPr></w:p>").unwrap();
+                self.write_elements(std::slice::from_ref(element), Some("ListContinue"));
+            }
+            None => write!(self.xml, "<w:p><w:pPr>{properties}</w:pPr></w:p>").unwrap(),
+        }
+        let rest = content.as_slice();
+        if !rest.is_empty() {
+            self.write_elements(rest, Some("ListContinue"));
+        }
+    }
+
+    fn write_table(&mut self, table: &ParsedMarkdownTable) {
+        let columns = table.column_alignments.len().max(1);
+        // The width of the page between the default margins, in twentieths
+        // of a point, shared equally until Word fits the columns to their
+        // contents.
+        let column_width = 9026 / columns;
+        self.xml.push_str(r#"<w:tbl><w:tblPr><w:tblStyle w:val="TableGrid"/><w:tblW w:w="5000" w:type="pct"/><w:tblLook w:val="04A0" w:firstRow="1" w:lastRow="0" w:firstColumn="0" w:lastColumn="0" w:noHBand="0" w:noVBand="1"/></w:tblPr><w:tblGrid>"#);
+        for _ in 0..columns {
+            write!(self.xml, r#"<w:gridCol w:w="{column_width}"/>"#).unwrap();
+        }
+        self.xml.push_str("</w:tblGrid>");
+
+        for (row_ix, row) in std::iter::once(&table.header)
+            .chain(&table.body)
+            .enumerate()
+        {
+            self.xml.push_str("<w:tr>");
+            if row_ix == 0 {
+                self.xml.push_str("<w:trPr><w:tblHeader/></w:trPr>");
+            }
+            for column in 0..columns {
+                let alignment = match table.column_alignments.get(column) {
+                    Some(ParsedMarkdownTableAlignment::Left) => Some("left"),
+                    Some(ParsedMarkdownTableAlignment::Center) => Some("center"),
+                    Some(ParsedMarkdownTableAlignment::Right) => Some("right"),
+                    _ => None,
+                };
+                let properties = alignment
+                    .map(|alignment| format!(r#"<w:jc w:val="{alignment}"/>"#))
+                    .unwrap_or_default();
+                write!(
+                    self.xml,
+                    r#"<w:tc><w:tcPr><w:tcW w:w="{column_width}" w:type="dxa"/></w:tcPr>"#
+                )
+                .unwrap();
+                match row.children.get(column) {
+                    Some(cell) => self.write_paragraph(cell, &properties),
+                    None => self.write_paragraph(&Vec::new(), &properties),
+                }
+                self.xml.push_str("</w:tc>");
+            }
+            self.xml.push_str("</w:tr>");
+        }
+        // Word merges tables that aren't separated by a paragraph.
+        self.xml.push_str("</w:tbl><w:p/>");
+    }
+
+    fn write_paragraph(&mut self, paragraph: &MarkdownParagraph, properties: &str) {
+        self.xml.push_str("<w:p>");
+        if !properties.is_empty() {
+            write!(self.xml, "<w:pPr>{properties}</w:pPr>").unwrap();
+        }
+        self.write_chunks(paragraph);
+        self.xml.push_str("</w:p>");
+    }
+
+    fn write_chunks(&mut self, paragraph: &MarkdownParagraph) {
+        for chunk in paragraph {
+            match chunk {
+                MarkdownParagraphChunk::Text(text) => self.write_text_runs(text),
+                MarkdownParagraphChunk::Image(image) => self.write_image(image),
+            }
+        }
+    }
+
+    fn write_text_runs(&mut self, text: &ParsedMarkdownText) {
+        let runs = text_runs(text);
+        let mut ix = 0;
+        while ix < runs.len() {
+            let Some(url) = runs[ix].url else {
+                write_run(&mut self.xml, &runs[ix]);
+                ix += 1;
+                continue;
+            };
+            let id = self.add_relationship("hyperlink", url.to_string(), true);
+            write!(self.xml, r#"<w:hyperlink r:id="{id}">"#).unwrap();
+            while ix < runs.len() && runs[ix].url == Some(url) {
+                write_run(&mut self.xml, &runs[ix]);
+                ix += 1;
+            }
+            self.xml.push_str("</w:hyperlink>");
+        }
+    }
+
+    fn write_image(&mut self, image: &Image) {
+        let alt_text = image.alt_text.as_deref().unwrap_or_default();
+        let embedded = match &image.link {
+            Link::Path { path, .. } => self
+                .images
+                .get(path)
+                .and_then(|bytes| Some((path, bytes, image_info(bytes)?))),
+            Link::Web { .. } | Link::Citation(_) | Link::Footnote { .. } => None,
+        };
+        let Some((path, bytes, (extension, content_type, width, height))) = embedded else {
+            if !alt_text.is_empty() {
+                self.xml.push_str("<w:r>");
+                write_text(&mut self.xml, alt_text);
+                self.xml.push_str("</w:r>");
+            }
+            return;
+        };
+
+        self.drawings += 1;
+        let number = self.drawings;
+        let part = format!("media/export-image{number}.{extension}");
+        let id = self.add_relationship("image", part.clone(), false);
+        self.media.push(Media {
+            part: format!("word/{part}"),
+            extension,
+            content_type,
+            contents: bytes.clone(),
+        });
+
+        let mut cx = width as u64 * EMUS_PER_PIXEL;
+        let mut cy = height as u64 * EMUS_PER_PIXEL;
+        if cx > MAX_IMAGE_WIDTH {
+            cy = cy * MAX_IMAGE_WIDTH / cx;
+            cx = MAX_IMAGE_WIDTH;
+        }
+        let name = escape(
+            &path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        );
+        // Drawing IDs are kept clear of any in the reference document's
+        // headers and footers.
+        let drawing_id = 1000 + number;
+        write!(
+            self.xml,
+            r#"<w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0"><wp:extent cx="{cx}" cy="{cy}"/><wp:docPr id="{drawing_id}" name="{name}" descr="{}"/><wp:cNvGraphicFramePr><a:graphicFrameLocks noChangeAspect="1"/></wp:cNvGraphicFramePr><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic><pic:nvPicPr><pic:cNvPr id="{drawing_id}" name="{name}"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed="{id}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"#,
+            escape(alt_text)
+        )
+        .unwrap();
+    }
+
+    fn add_relationship(&mut self, kind: &'static str, target: String, external: bool) -> String {
+        // Prefixed so as not to clash with a reference document's IDs.
+        let id = format!("rIdExport{}", self.relationships.len() + 1);
+        self.relationships.push(Relationship {
+            id: id.clone(),
+            kind,
+            target,
+            external,
+        });
+        id
+    }
+
+    fn document(&self, root: &str, section: &str) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n{root}<w:body>{}{section}</w:body></w:document>",
+            self.xml
+        )
+    }
+
+    fn write_relationships(&self, xml: &mut String) {
+        for relationship in &self.relationships {
+            write!(
+                xml,
+                r#"<Relationship Id="{}" Type="{RELATIONSHIP_TYPE}/{}" Target="{}"{}/>"#,
+                relationship.id,
+                relationship.kind,
+                escape(&relationship.target),
+                if relationship.external {
+                    r#" TargetMode="External""#
+                } else {
+                    ""
+                }
+            )
+            .unwrap();
+        }
+    }
+
+    fn numbering_definitions(&self) -> (String, String) {
+        let mut abstract_nums = String::new();
+        for (id, ordered) in [
+            (BULLET_ABSTRACT_NUM_ID, false),
+            (ORDERED_ABSTRACT_NUM_ID, true),
+        ] {
+            write!(
+                abstract_nums,
+                r#"<w:abstractNum w:abstractNumId="{id}"><w:multiLevelType w:val="hybridMultilevel"/>"#
+            )
+            .unwrap();
+            for level in 0..9 {
+                let (format, text) = if ordered {
+                    let format = ["decimal", "lowerLetter", "lowerRoman"][level % 3];
+                    (format, format!("%{}.", level + 1))
+                } else {
+                    ("bullet", ["•", "◦", "▪"][level % 3].to_string())
+                };
+                write!(
+                    abstract_nums,
+                    r#"<w:lvl w:ilvl="{level}"><w:start w:val="1"/><w:numFmt w:val="{format}"/><w:lvlText w:val="{text}"/><w:lvlJc w:val="left"/><w:pPr><w:ind w:left="{}" w:hanging="360"/></w:pPr></w:lvl>"#,
+                    720 * (level + 1)
+                )
+                .unwrap();
+            }
+            abstract_nums.push_str("</w:abstractNum>");
+        }
+
+        let mut nums = format!(
+            r#"<w:num w:numId="{BULLET_NUM_ID}"><w:abstractNumId w:val="{BULLET_ABSTRACT_NUM_ID}"/></w:num>"#
+        );
+        for (ix, (level, start)) in self.ordered_lists.iter().enumerate() {
+            write!(
+                nums,
+                r#"<w:num w:numId="{}"><w:abstractNumId w:val="{ORDERED_ABSTRACT_NUM_ID}"/><w:lvlOverride w:ilvl="{level}"><w:startOverride w:val="{start}"/></w:lvlOverride></w:num>"#,
+                FIRST_ORDERED_NUM_ID + ix
+            )
+            .unwrap();
+        }
+        (abstract_nums, nums)
+    }
+
+    /// A document with the default styles and page setup.
+    fn package(mut self, parts: &mut Vec<(String, Vec<u8>)>) {
+        let mut content_types = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/>"#,
+        );
+        self.write_media_defaults(&mut content_types);
+        write!(
+            content_types,
+            r#"<Override PartName="/word/document.xml" ContentType="{DOCUMENT_CONTENT_TYPE}"/><Override PartName="/word/styles.xml" ContentType="{STYLES_CONTENT_TYPE}"/><Override PartName="/word/numbering.xml" ContentType="{NUMBERING_CONTENT_TYPE}"/></Types>"#
+        )
+        .unwrap();
+
+        let mut styles = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+        );
+        styles.push_str(DOC_DEFAULTS);
+        for (_, style) in DEFAULT_STYLES {
+            styles.push_str(style);
+        }
+        styles.push_str("</w:styles>");
+
+        let (abstract_nums, nums) = self.numbering_definitions();
+        let numbering = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">{abstract_nums}{nums}</w:numbering>"#
+        );
+
+        self.add_relationship("styles", "styles.xml".into(), false);
+        self.add_relationship("numbering", "numbering.xml".into(), false);
+        let mut rels = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        );
+        self.write_relationships(&mut rels);
+        rels.push_str("</Relationships>");
+
+        let document = self.document(&document_root(None), DEFAULT_SECTION);
+        parts.extend([
+            (CONTENT_TYPES_PART.to_string(), content_types.into_bytes()),
+            ("_rels/.rels".to_string(), ROOT_RELS.as_bytes().to_vec()),
+            (DOCUMENT_PART.to_string(), document.into_bytes()),
+            (DOCUMENT_RELS_PART.to_string(), rels.into_bytes()),
+            ("word/styles.xml".to_string(), styles.into_bytes()),
+            ("word/numbering.xml".to_string(), numbering.into_bytes()),
+        ]);
+        for media in self.media {
+            parts.push((media.part, media.contents));
+        }
+    }
+
+    /// The reference document with its body replaced, and the styles and
+    /// numbering it lacks added.
+    fn package_with_reference(
+        mut self,
+        reference: &DocxPackage,
+        parts: &mut Vec<(String, Vec<u8>)>,
+    ) -> Result<()> {
+        let reference_document = reference.text(DOCUMENT_PART).unwrap_or_default();
+        let reference_rels = reference.text(DOCUMENT_RELS_PART).unwrap_or_else(|| {
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"></Relationships>"#
+                .to_string()
+        });
+        let mut content_types = reference
+            .text(CONTENT_TYPES_PART)
+            .context("the reference document has no content types")?
+            .replace(TEMPLATE_CONTENT_TYPE, DOCUMENT_CONTENT_TYPE);
+
+        let mut replaced_parts = vec![
+            CONTENT_TYPES_PART.to_string(),
+            DOCUMENT_PART.to_string(),
+            DOCUMENT_RELS_PART.to_string(),
+        ];
+        let mut added_parts = Vec::new();
+
+        let styles_part = relationship_target(&reference_rels, "styles");
+        let mut styles = styles_part
+            .as_ref()
+            .and_then(|part| reference.text(part))
+            .unwrap_or_else(|| {
+                format!(
+                    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">{DOC_DEFAULTS}</w:styles>"#
+                )
+            });
+        let missing_styles = DEFAULT_STYLES
+            .iter()
+            .filter(|(id, _)| !styles.contains(&format!(r#"w:styleId="{id}""#)))
+            .map(|(_, style)| *style)
+            .collect::<String>();
+        insert_before(&mut styles, "</w:styles>", &missing_styles);
+        let styles_part = match styles_part {
+            Some(part) => part,
+            None => {
+                self.add_relationship("styles", "styles.xml".into(), false);
+                add_override(&mut content_types, "/word/styles.xml", STYLES_CONTENT_TYPE);
+                "word/styles.xml".to_string()
+            }
+        };
+        replaced_parts.push(styles_part.clone());
+        added_parts.push((styles_part, styles.into_bytes()));
+
+        let (abstract_nums, nums) = self.numbering_definitions();
+        let numbering_part = relationship_target(&reference_rels, "numbering");
+        let numbering = match numbering_part
+            .as_ref()
+            .and_then(|part| reference.text(part))
+        {
+            Some(mut numbering) => {
+                // Abstract numbering definitions have to come before the
+                // numbering instances that use them.
+                if numbering.contains("<w:num ") {
+                    insert_before(&mut numbering, "<w:num ", &abstract_nums);
+                } else {
+                    insert_before(&mut numbering, "</w:numbering>", &abstract_nums);
+                }
+                insert_before(&mut numbering, "</w:numbering>", &nums);
+                numbering
+            }
+            None => format!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">{abstract_nums}{nums}</w:numbering>"#
+            ),
+        };
+        let numbering_part = match numbering_part {
+            Some(part) => part,
+            None => {
+                self.add_relationship("numbering", "numbering.xml".into(), false);
+                add_override(
+                    &mut content_types,
+                    "/word/numbering.xml",
+                    NUMBERING_CONTENT_TYPE,
+                );
+                "word/numbering.xml".to_string()
+            }
+        };
+        replaced_parts.push(numbering_part.clone());
+        added_parts.push((numbering_part, numbering.into_bytes()));
+
+        let mut media_defaults = String::new();
+        self.write_media_defaults(&mut media_defaults);
+        for default in media_defaults.split_inclusive("/>") {
+            let extension = default
+                .split('"')
+                .nth(1)
+                .map(|extension| format!(r#"Extension="{extension}""#))
+                .unwrap_or_default();
+            if !content_types.to_ascii_lowercase().contains(&extension) {
+                insert_before(&mut content_types, "</Types>", default);
+            }
+        }
+
+        let mut rels = reference_rels;
+        let mut relationships = String::new();
+        self.write_relationships(&mut relationships);
+        insert_before(&mut rels, "</Relationships>", &relationships);
+
+        let document = self.document(
+            &document_root(Some(&reference_document)),
+            body_section(&reference_document).unwrap_or(DEFAULT_SECTION),
+        );
+
+        parts.push((CONTENT_TYPES_PART.to_string(), content_types.into_bytes()));
+        parts.push((DOCUMENT_PART.to_string(), document.into_bytes()));
+        parts.push((DOCUMENT_RELS_PART.to_string(), rels.into_bytes()));
+        parts.extend(added_parts);
+        for (name, contents) in &reference.parts {
+            if !replaced_parts.contains(name) {
+                parts.push((name.clone(), contents.clone()));
+            }
+        }
+        for media in self.media {
+            parts.push((media.part, media.contents));
+        }
+        Ok(())
+    }
+
+    fn write_media_defaults(&self, xml: &mut String) {
+        let mut written = Vec::new();
+        for media in &self.media {
+            if !written.contains(&media.extension) {
+                write!(
+                    xml,
+                    r#"<Default Extension="{}" ContentType="{}"/>"#,
+                    media.extension, media.content_type
+                )
+                .unwrap();
+                written.push(media.extension);
+            }
+        }
+    }
+}
+
+fn write_run(xml: &mut String, run: &Run) {
+    if run.text.is_empty() {
+        return;
+    }
+    xml.push_str("<w:r>");
+    let style = if run.url.is_some() {
+        Some("Hyperlink")
+    } else if run.code {
+        Some("VerbatimChar")
+    } else {
+        None
+    };
+    if style.is_some() || run.bold || run.italic || run.strikethrough || run.underline {
+        xml.push_str("<w:rPr>");
+        if let Some(style) = style {
+            write!(xml, r#"<w:rStyle w:val="{style}"/>"#).unwrap();
+        }
+        if run.bold {
+            xml.push_str("<w:b/>");
+        }
+        if run.italic {
+            xml.push_str("<w:i/>");
+        }
+        if run.strikethrough {
+            xml.push_str("<w:strike/>");
+        }
+        // Links are underlined by their style.
+        if run.underline && run.url.is_none() {
+            xml.push_str(r#"<w:u w:val="single"/>"#);
+        }
+        xml.push_str("</w:rPr>");
+    }
+    write_text(xml, &run.text);
+    xml.push_str("</w:r>");
+}
+
+fn paragraph_style(style: Option<&str>) -> String {
+    style
+        .map(|style| format!(r#"<w:pStyle w:val="{style}"/>"#))
+        .unwrap_or_default()
+}
+
+/// Writes the text of a run, with its line breaks and tabs.
+fn write_text(xml: &mut String, text: &str) {
+    for (ix, line) in text.split('\n').enumerate() {
+        if ix > 0 {
+            xml.push_str("<w:br/>");
+        }
+        for (ix, part) in line.split('\t').enumerate() {
+            if ix > 0 {
+                xml.push_str("<w:tab/>");
+            }
+            if !part.is_empty() {
+                write!(xml, r#"<w:t xml:space="preserve">{}</w:t>"#, escape(part)).unwrap();
+            }
+        }
+    }
+}
+
+/// The file extension, content type and size in pixels of an image Word can
+/// display.
+fn image_info(bytes: &[u8]) -> Option<(&'static str, &'static str, u32, u32)> {
+    let format = image::guess_format(bytes).ok()?;
+    let (extension, content_type) = match format {
+        ImageFormat::Png => ("png", "image/png"),
+        ImageFormat::Jpeg => ("jpeg", "image/jpeg"),
+        ImageFormat::Gif => ("gif", "image/gif"),
+        ImageFormat::Bmp => ("bmp", "image/bmp"),
+        _ => return None,
+    };
+    let (width, height) = ImageReader::with_format(Cursor::new(bytes), format)
+        .into_dimensions()
+        .ok()?;
+    Some((extension, content_type, width, height))
+}
+
+/// The opening tag of the document, declaring the namespaces the body uses.
+fn document_root(reference_document: Option<&str>) -> String {
+    let mut root = reference_document
+        .and_then(|document| {
+            let start = document.find("<w:document")?;
+            let end = start + document[start..].find('>')?;
+            Some(document[start..end].trim_end_matches('/').to_string())
+        })
+        .unwrap_or_else(|| "<w:document".to_string());
+    for (prefix, uri) in DOCUMENT_NAMESPACES {
+        if !root.contains(&format!("xmlns:{prefix}=")) {
+            write!(root, r#" xmlns:{prefix}="{uri}""#).unwrap();
+        }
+    }
+    root.push('>');
+    root
+}
+
+/// The section properties at the end of a document's body, which hold its
+/// page setup and refer to its headers and footers.
+fn body_section(document: &str) -> Option<&str> {
+    let start = document.rfind("<w:sectPr")?;
+    let tag_end = start + document[start..].find('>')?;
+    if document[..tag_end].ends_with('/') {
+        return Some(&document[start..=tag_end]);
+    }
+    let end = start + document[start..].find("</w:sectPr>")? + "</w:sectPr>".len();
+    Some(&document[start..end])
+}
+
+/// The part that a document relationship of the given kind targets.
+fn relationship_target(rels: &str, kind: &str) -> Option<String> {
+    let type_attribute = format!(r#"Type="{RELATIONSHIP_TYPE}/{kind}""#);
+    let position = rels.find(&type_attribute)?;
+    let start = rels[..position].rfind("<Relationship")?;
+    let end = position + rels[position..].find('>')?;
+    let element = &rels[start..end];
+    let target = element.split("Target=\"").nth(1)?.split('"').next()?;
+    Some(match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("word/{target}"),
+    })
+}
+
+fn add_override(content_types: &mut String, part_name: &str, content_type: &str) {
+    if !content_types.contains(&format!(r#"PartName="{part_name}""#)) {
+        let content_override =
+            format!(r#"<Override PartName="{part_name}" ContentType="{content_type}"/>"#);
+        insert_before(content_types, "</Types>", &content_override);
+    }
+}
+
+fn insert_before(xml: &mut String, pattern: &str, insertion: &str) {
+    match xml.find(pattern) {
+        Some(ix) => xml.insert_str(ix, insertion),
+        None => xml.push_str(insertion),
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown_preview::markdown_parser::parse_markdown;
+    use pretty_assertions::assert_eq;
+
+    async fn read_part(bytes: Vec<u8>, name: &str) -> Option<String> {
+        let reader = ZipFileReader::new(bytes).await.unwrap();
+        let ix = reader
+            .file()
+            .entries()
+            .iter()
+            .position(|entry| entry.filename().as_str().unwrap() == name)?;
+        let mut contents = String::new();
+        reader
+            .reader_with_entry(ix)
+            .await
+            .unwrap()
+            .read_to_string(&mut contents)
+            .await
+            .unwrap();
+        Some(contents)
+    }
+
+    async fn export(markdown: &str, reference: Option<Vec<u8>>) -> Vec<u8> {
+        let markdown = parse_markdown(markdown, None, None).await;
+        write_docx(&markdown, reference, &HashMap::default())
+            .await
+            .unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_write_docx() {
+        let bytes = export(
+            "# Our Approach\n\n\
+             We **will** deliver in six weeks [1](cite:tender/RFP.pdf#page=4). See [the plan](https://example.com/plan).\n\n\
+             - Discovery\n  1. Workshops\n  2. Interviews\n- Delivery\n\n\
+             3. Third\n4. Fourth\n\n\
+             > Quoted & noted\n\n\
+             | Role | Days |\n|:-----|-----:|\n| Lead | 10 |\n",
+            None,
+        )
+        .await;
+
+        let document = read_part(bytes.clone(), DOCUMENT_PART).await.unwrap();
+        assert!(document.contains(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t xml:space="preserve">Our Approach</w:t></w:r></w:p>"#
+        ));
+        assert!(document.contains(
+            r#"<w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve">will</w:t></w:r><w:r><w:t xml:space="preserve"> deliver in six weeks</w:t></w:r><w:r><w:t xml:space="preserve">. See </w:t></w:r>"#
+        ));
+        assert!(!document.contains("cite:"));
+        assert!(document.contains(r#"<w:hyperlink r:id="rIdExport1"><w:r><w:rPr><w:rStyle w:val="Hyperlink"/></w:rPr><w:t xml:space="preserve">the plan</w:t></w:r></w:hyperlink>"#));
+
+        assert!(document.contains(
+            r#"<w:pStyle w:val="ListBullet"/><w:numPr><w:ilvl w:val="0"/><w:numId w:val="9001"/></w:numPr></w:pPr><w:r><w:t xml:space="preserve">Discovery</w:t>"#
+        ));
+        assert!(document.contains(
+            r#"<w:pStyle w:val="ListNumber"/><w:numPr><w:ilvl w:val="1"/><w:numId w:val="9002"/></w:numPr></w:pPr><w:r><w:t xml:space="preserve">Interviews</w:t>"#
+        ));
+        assert!(document.contains(
+            r#"<w:pStyle w:val="ListNumber"/><w:numPr><w:ilvl w:val="0"/><w:numId w:val="9003"/></w:numPr></w:pPr><w:r><w:t xml:space="preserve">Fourth</w:t>"#
+        ));
+        assert!(document.contains(
+            r#"<w:p><w:pPr><w:pStyle w:val="Quote"/></w:pPr><w:r><w:t xml:space="preserve">Quoted &amp; noted</w:t></w:r></w:p>"#
+        ));
+        assert!(document.contains(r#"<w:tblStyle w:val="TableGrid"/>"#));
+        assert!(document.contains(r#"<w:trPr><w:tblHeader/></w:trPr>"#));
+        assert!(document.contains(
+            r#"<w:p><w:pPr><w:jc w:val="right"/></w:pPr><w:r><w:t xml:space="preserve">10</w:t></w:r></w:p>"#
+        ));
+        assert!(document.contains(DEFAULT_SECTION));
+
+        let numbering = read_part(bytes.clone(), "word/numbering.xml")
+            .await
+            .unwrap();
+        assert!(numbering.contains(
+            r#"<w:num w:numId="9003"><w:abstractNumId w:val="9002"/><w:lvlOverride w:ilvl="0"><w:startOverride w:val="3"/></w:lvlOverride></w:num>"#
+        ));
+        let rels = read_part(bytes, DOCUMENT_RELS_PART).await.unwrap();
+        assert!(rels.contains(r#"Target="https://example.com/plan" TargetMode="External""#));
+    }
+
+    #[gpui::test]
+    async fn test_write_docx_with_reference() {
+        let reference_document = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:w14="http://schemas.microsoft.com/office/word/2010/wordml"><w:body><w:p><w:r><w:t>Replace me</w:t></w:r></w:p><w:sectPr><w:headerReference w:type="default" r:id="rId8"/><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>"#;
+        let reference_rels = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/><Relationship Id="rId8" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header1.xml"/></Relationships>"#;
+        let reference_styles = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:rPr><w:rFonts w:ascii="Georgia"/></w:rPr></w:style></w:styles>"#;
+        let content_types = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="{TEMPLATE_CONTENT_TYPE}"/></Types>"#
+        );
+
+        let mut writer = ZipFileWriter::new(Vec::new());
+        for (name, content) in [
+            (CONTENT_TYPES_PART, content_types.as_str()),
+            (DOCUMENT_PART, reference_document),
+            (DOCUMENT_RELS_PART, reference_rels),
+            ("word/styles.xml", reference_styles),
+            ("word/header1.xml", "<w:hdr>Acme Pty Ltd</w:hdr>"),
+        ] {
+            let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+            writer
+                .write_entry_whole(entry, content.as_bytes())
+                .await
+                .unwrap();
+        }
+        let reference = writer.close().await.unwrap();
+
+        let bytes = export("# Our Approach\n\n- Discovery\n", Some(reference)).await;
+
+        let document = read_part(bytes.clone(), DOCUMENT_PART).await.unwrap();
+        assert!(!document.contains("Replace me"));
+        assert!(
+            document
+                .contains(r#"xmlns:w14="http://schemas.microsoft.com/office/word/2010/wordml""#)
+        );
+        assert!(document.contains(r#"xmlns:wp="#));
+        assert!(document.ends_with(
+            r#"<w:sectPr><w:headerReference w:type="default" r:id="rId8"/><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>"#
+        ));
+
+        let styles = read_part(bytes.clone(), "word/styles.xml").await.unwrap();
+        assert!(styles.contains(r#"<w:rFonts w:ascii="Georgia"/>"#));
+        assert_eq!(styles.matches(r#"w:styleId="Heading1""#).count(), 1);
+        assert!(styles.contains(r#"w:styleId="ListBullet""#));
+
+        assert_eq!(
+            read_part(bytes.clone(), "word/header1.xml")
+                .await
+                .as_deref(),
+            Some("<w:hdr>Acme Pty Ltd</w:hdr>")
+        );
+        let numbering = read_part(bytes.clone(), "word/numbering.xml")
+            .await
+            .unwrap();
+        assert!(numbering.contains(r#"<w:abstractNum w:abstractNumId="9001">"#));
+        let rels = read_part(bytes.clone(), DOCUMENT_RELS_PART).await.unwrap();
+        assert!(rels.contains(r#"Id="rId8""#));
+        assert!(rels.contains(r#"Target="numbering.xml""#));
+        let content_types = read_part(bytes, CONTENT_TYPES_PART).await.unwrap();
+        assert!(content_types.contains(DOCUMENT_CONTENT_TYPE));
+        assert!(content_types.contains(r#"PartName="/word/numbering.xml""#));
+    }
+
+    #[test]
+    fn test_body_section() {
+        assert_eq!(
+            body_section(
+                r#"<w:p><w:pPr><w:sectPr/></w:pPr></w:p><w:sectPr w:rsidR="1"><w:pgSz/></w:sectPr></w:body>"#
+            ),
+            Some(r#"<w:sectPr w:rsidR="1"><w:pgSz/></w:sectPr>"#)
+        );
+        assert_eq!(
+            body_section("<w:body><w:sectPr/></w:body>"),
+            Some("<w:sectPr/>")
+        );
+        assert_eq!(body_section("<w:body></w:body>"), None);
+    }
+}
@@ -0,0 +1,1142 @@
+//! Imports Word response templates as Markdown, and writes responses back
+//! into them.
+//!
+//! Importing a template also produces a mapping of its fields: the table
+//! cells and content controls responses are written in. Writing back puts
+//! the text of each field that was edited into its place in the original
+//! template, set in the formatting the template gives it there. Everything
+//! else in the template is left exactly as it was.
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use roxmltree::{Document, Node, NodeId};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use crate::docx::{DOCUMENT_PART, DocxPackage, escape, zip_parts};
+
+const WORD_NAMESPACE: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const SETTINGS_PART: &str = "word/settings.xml";
+const FIELD_START: &str = "<!-- field ";
+const FIELD_END: &str = "<!-- /field -->";
+
+/// The extension of the mapping written next to an imported template's
+/// Markdown, replacing the Markdown's own.
+pub const MAPPING_EXTENSION: &str = "docx-map.json";
+
+/// Where the fields of an imported template are, and what they held.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DocxMapping {
+    /// The template, relative to the mapping.
+    pub template: String,
+    pub fields: Vec<DocxField>,
+    /// The field each cell of the Markdown's tables fills, by table, row and
+    /// column. Cells without one are read-only.
+    pub tables: Vec<Vec<Vec<Option<usize>>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DocxField {
+    pub location: FieldLocation,
+    /// The title or tag of a content control.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The field's Markdown as it was imported. Fields whose Markdown hasn't
+    /// changed aren't written back.
+    pub markdown: String,
+}
+
+impl DocxField {
+    fn description(&self) -> String {
+        match (&self.location, &self.name) {
+            (FieldLocation::ContentControl { .. }, Some(name)) => {
+                format!("the \"{name}\" content control")
+            }
+            (FieldLocation::ContentControl { index }, None) => {
+                format!("content control {}", index + 1)
+            }
+            (FieldLocation::Cell { table, row, cell }, _) => {
+                format!("row {}, cell {} of table {}", row + 1, cell + 1, table + 1)
+            }
+        }
+    }
+}
+
+/// Where a field is in the template. Tables and content controls are
+/// counted in document order, including nested ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldLocation {
+    Cell {
+        table: usize,
+        row: usize,
+        cell: usize,
+    },
+    ContentControl {
+        index: usize,
+    },
+}
+
+pub struct ImportedDocx {
+    pub markdown: String,
+    pub mapping: DocxMapping,
+}
+
+/// Converts a template to Markdown. Content controls that can be edited are
+/// marked with `<!-- field N -->` and `<!-- /field -->` comments, and table
+/// cells are fields unless the template is protected from editing.
+pub async fn import_docx(bytes: Vec<u8>, template: String) -> Result<ImportedDocx> {
+    let package = DocxPackage::read(bytes).await?;
+    let document = package.text(DOCUMENT_PART).unwrap_or_default();
+    let protected = package
+        .text(SETTINGS_PART)
+        .is_some_and(|settings| is_protected(&settings));
+    let xml = Document::parse(&document).context("failed to parse the document")?;
+    let mut importer = Importer {
+        elements: Elements::new(&xml),
+        protected,
+        fields: Vec::new(),
+        tables: Vec::new(),
+    };
+    let mut blocks = Vec::new();
+    if let Some(body) = xml.descendants().find(|node| is(*node, "body")) {
+        importer.blocks(body, &mut blocks);
+    }
+    let mut markdown = blocks.join("\n\n");
+    markdown.push('\n');
+    Ok(ImportedDocx {
+        markdown,
+        mapping: DocxMapping {
+            template,
+            fields: importer.fields,
+            tables: importer.tables,
+        },
+    })
+}
+
+/// Whether editing is restricted to parts of the document, in which case
+/// its table cells aren't fields.
+fn is_protected(settings: &str) -> bool {
+    let Some(start) = settings.find("<w:documentProtection") else {
+        return false;
+    };
+    let element = &settings[start..];
+    let element = &element[..element.find('>').unwrap_or(element.len())];
+    let enforced = [
+        "w:enforcement=\"1\"",
+        "w:enforcement=\"true\"",
+        "w:enforcement=\"on\"",
+    ]
+    .iter()
+    .any(|attribute| element.contains(attribute));
+    let restricted = ["readOnly", "forms", "comments"]
+        .iter()
+        .any(|edit| element.contains(&format!("w:edit=\"{edit}\"")));
+    enforced && restricted
+}
+
+struct Importer<'a, 'input> {
+    elements: Elements<'a, 'input>,
+    protected: bool,
+    fields: Vec<DocxField>,
+    tables: Vec<Vec<Vec<Option<usize>>>>,
+}
+
+impl<'a, 'input> Importer<'a, 'input> {
+    fn add_field(
+        &mut self,
+        location: FieldLocation,
+        name: Option<String>,
+        markdown: &str,
+    ) -> usize {
+        self.fields.push(DocxField {
+            location,
+            name,
+            markdown: markdown.to_string(),
+        });
+        self.fields.len() - 1
+    }
+
+    fn blocks(&mut self, parent: Node<'a, 'input>, blocks: &mut Vec<String>) {
+        for child in parent.children().filter(Node::is_element) {
+            match local_name(child) {
+                "p" => {
+                    let paragraph = self.paragraph(child);
+                    if !paragraph.is_empty() {
+                        blocks.push(paragraph);
+                    }
+                }
+                "tbl" => blocks.push(self.table(child)),
+                "sdt" => self.block_control(child, blocks),
+                "customXml" | "ins" | "moveTo" | "smartTag" => self.blocks(child, blocks),
+                _ => {}
+            }
+        }
+    }
+
+    fn paragraph(&mut self, paragraph: Node<'a, 'input>) -> String {
+        // A content control that's all there is in its paragraph is shown
+        // like one that holds whole paragraphs.
+        let content = paragraph
+            .children()
+            .filter(|child| {
+                child.is_element()
+                    && !matches!(
+                        local_name(*child),
+                        "pPr" | "bookmarkStart" | "bookmarkEnd" | "proofErr"
+                    )
+            })
+            .collect::<Vec<_>>();
+        if let [control] = content.as_slice() {
+            if is(*control, "sdt") && self.is_editable(*control) {
+                let text = self.inline(*control);
+                let (id, text) = self.add_control_field(*control, text);
+                return self.block_field(id, &text);
+            }
+        }
+
+        let text = self.inline(paragraph);
+        if text.is_empty() {
+            return text;
+        }
+        let properties = child(paragraph, "pPr");
+        let style = properties
+            .and_then(|properties| child(properties, "pStyle"))
+            .and_then(|style| style.attribute((WORD_NAMESPACE, "val")))
+            .unwrap_or_default();
+        if let Some(level) = heading_level(style) {
+            return format!("{} {text}", "#".repeat(level));
+        }
+        let list_level = properties
+            .and_then(|properties| child(properties, "numPr"))
+            .map(|numbering| {
+                child(numbering, "ilvl")
+                    .and_then(|level| level.attribute((WORD_NAMESPACE, "val")))
+                    .and_then(|level| level.parse::<usize>().ok())
+                    .unwrap_or(0)
+            });
+        match list_level {
+            Some(level) => format!("{}- {text}", "  ".repeat(level)),
+            None => text,
+        }
+    }
+
+    /// The Markdown of the runs in an element, with the content controls
+    /// among them marked as fields.
+    fn inline(&mut self, node: Node<'a, 'input>) -> String {
+        let mut writer = InlineWriter::default();
+        self.write_inline(node, &mut writer);
+        writer.finish()
+    }
+
+    fn write_inline(&mut self, node: Node<'a, 'input>, writer: &mut InlineWriter) {
+        for child in node.children().filter(Node::is_element) {
+            match local_name(child) {
+                "r" => writer.run(child),
+                "sdt" if self.is_editable(child) => {
+                    let text = self.inline(child);
+                    let (id, text) = self.add_control_field(child, text);
+                    let name = self.fields[id].name.clone();
+                    writer.push_markdown(&format!(
+                        "{}{text}{FIELD_END}",
+                        field_start(id, name.as_deref())
+                    ));
+                }
+                "sdt" => {
+                    if let Some(content) = self.control_content(child) {
+                        self.write_inline(content, writer);
+                    }
+                }
+                "pPr" | "rPr" | "sdtPr" | "sdtEndPr" | "del" | "moveFrom" => {}
+                _ => self.write_inline(child, writer),
+            }
+        }
+    }
+
+    fn table(&mut self, table: Node<'a, 'input>) -> String {
+        let table_index = self.elements.tables[&table.id()];
+        let mut rows = Vec::new();
+        for (row_index, row) in children(table, "tr").enumerate() {
+            let mut cells = Vec::new();
+            for (cell_index, cell) in children(row, "tc").enumerate() {
+                let properties = child(cell, "tcPr");
+                let span = properties
+                    .and_then(|properties| child(properties, "gridSpan"))
+                    .and_then(|span| span.attribute((WORD_NAMESPACE, "val")))
+                    .and_then(|span| span.parse::<usize>().ok())
+                    .unwrap_or(1);
+                // Cells merged into the one above them continue it.
+                let is_merged = properties
+                    .and_then(|properties| child(properties, "vMerge"))
+                    .is_some_and(|merge| {
+                        merge.attribute((WORD_NAMESPACE, "val")) != Some("restart")
+                    });
+                if is_merged {
+                    cells.push((String::new(), None));
+                } else {
+                    let text = self.cell(cell);
+                    let is_field = !self.protected
+                        && !cell
+                            .descendants()
+                            .skip(1)
+                            .any(|node| is(node, "sdt") || is(node, "tbl"));
+                    let field = is_field.then(|| {
+                        let location = FieldLocation::Cell {
+                            table: table_index,
+                            row: row_index,
+                            cell: cell_index,
+                        };
+                        self.add_field(location, None, &text)
+                    });
+                    cells.push((text, field));
+                }
+                for _ in 1..span {
+                    cells.push((String::new(), None));
+                }
+            }
+            rows.push(cells);
+        }
+
+        let column_count = rows.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        for row in &mut rows {
+            row.resize(column_count, (String::new(), None));
+        }
+        if rows.is_empty() {
+            rows.push(vec![(String::new(), None); column_count]);
+        }
+
+        let mut markdown = String::new();
+        for (ix, row) in rows.iter().enumerate() {
+            let cells = row
+                .iter()
+                .map(|(text, _)| text.as_str())
+                .collect::<Vec<_>>();
+            writeln!(markdown, "| {} |", cells.join(" | ")).unwrap();
+            if ix == 0 {
+                writeln!(markdown, "|{}", " --- |".repeat(column_count)).unwrap();
+            }
+        }
+        self.tables.push(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(|(_, field)| field).collect())
+                .collect(),
+        );
+        markdown.trim_end().to_string()
+    }
+
+    /// The Markdown of a table cell, with its paragraphs separated by line
+    /// breaks.
+    fn cell(&mut self, cell: Node<'a, 'input>) -> String {
+        let mut paragraphs = Vec::new();
+        self.cell_paragraphs(cell, &mut paragraphs);
+        paragraphs.retain(|paragraph| !paragraph.is_empty());
+        paragraphs.join("<br>")
+    }
+
+    fn cell_paragraphs(&mut self, parent: Node<'a, 'input>, paragraphs: &mut Vec<String>) {
+        for child in parent.children().filter(Node::is_element) {
+            match local_name(child) {
+                "p" => paragraphs.push(self.inline(child)),
+                "sdt" if self.is_editable(child) => {
+                    let mut content = Vec::new();
+                    if let Some(control_content) = self.control_content(child) {
+                        self.cell_paragraphs(control_content, &mut content);
+                    }
+                    content.retain(|paragraph| !paragraph.is_empty());
+                    let (id, text) = self.add_control_field(child, content.join("<br>"));
+                    let name = self.fields[id].name.clone();
+                    paragraphs.push(format!(
+                        "{}{text}{FIELD_END}",
+                        field_start(id, name.as_deref())
+                    ));
+                }
+                "sdt" => {
+                    if let Some(content) = self.control_content(child) {
+                        self.cell_paragraphs(content, paragraphs);
+                    }
+                }
+                "tbl" => {
+                    for row in children(child, "tr") {
+                        for cell in children(row, "tc") {
+                            self.cell_paragraphs(cell, paragraphs);
+                        }
+                    }
+                }
+                "customXml" | "ins" | "moveTo" => self.cell_paragraphs(child, paragraphs),
+                _ => {}
+            }
+        }
+    }
+
+    fn block_control(&mut self, control: Node<'a, 'input>, blocks: &mut Vec<String>) {
+        let Some(content) = self.control_content(control) else {
+            return;
+        };
+        if !self.is_editable(control) {
+            self.blocks(content, blocks);
+            return;
+        }
+        let paragraphs = children(content, "p")
+            .map(|paragraph| self.paragraph(paragraph))
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let (id, paragraphs) = self.add_control_field(control, paragraphs);
+        blocks.push(self.block_field(id, &paragraphs));
+    }
+
+    fn block_field(&self, id: usize, text: &str) -> String {
+        let start = field_start(id, self.fields[id].name.as_deref());
+        if text.is_empty() {
+            format!("{start}\n\n{FIELD_END}")
+        } else {
+            format!("{start}\n\n{text}\n\n{FIELD_END}")
+        }
+    }
+
+    /// Adds a content control's field, returning its ID and its Markdown,
+    /// which is empty while the control shows its placeholder text.
+    fn add_control_field(&mut self, control: Node<'a, 'input>, text: String) -> (usize, String) {
+        let properties = child(control, "sdtPr");
+        let text = if properties
+            .and_then(|properties| child(properties, "showingPlcHdr"))
+            .is_some()
+        {
+            String::new()
+        } else {
+            text
+        };
+        let name = properties
+            .and_then(|properties| child(properties, "alias").or_else(|| child(properties, "tag")))
+            .and_then(|name| name.attribute((WORD_NAMESPACE, "val")))
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        let location = FieldLocation::ContentControl {
+            index: self.elements.controls[&control.id()],
+        };
+        (self.add_field(location, name, &text), text)
+    }
+
+    fn control_content(&self, control: Node<'a, 'input>) -> Option<Node<'a, 'input>> {
+        child(control, "sdtContent")
+    }
+
+    /// Whether a content control's content can be edited and written back
+    /// as a whole: it isn't locked, and holds no tables or other controls.
+    fn is_editable(&self, control: Node) -> bool {
+        let properties = child(control, "sdtPr");
+        let is_locked = properties
+            .and_then(|properties| child(properties, "lock"))
+            .and_then(|lock| lock.attribute((WORD_NAMESPACE, "val")))
+            .is_some_and(|lock| matches!(lock, "contentLocked" | "sdtContentLocked"));
+        let has_nested_content = child(control, "sdtContent").is_some_and(|content| {
+            content
+                .descendants()
+                .any(|node| is(node, "sdt") || is(node, "tbl"))
+        });
+        !is_locked && !has_nested_content
+    }
+}
+
+/// The tables and content controls of a document, by their position in it.
+struct Elements<'a, 'input> {
+    tables: HashMap<NodeId, usize>,
+    controls: HashMap<NodeId, usize>,
+    table_nodes: Vec<Node<'a, 'input>>,
+    control_nodes: Vec<Node<'a, 'input>>,
+}
+
+impl<'a, 'input> Elements<'a, 'input> {
+    fn new(xml: &'a Document<'input>) -> Self {
+        let table_nodes = xml
+            .descendants()
+            .filter(|node| is(*node, "tbl"))
+            .collect::<Vec<_>>();
+        let control_nodes = xml
+            .descendants()
+            .filter(|node| is(*node, "sdt"))
+            .collect::<Vec<_>>();
+        Self {
+            tables: positions(&table_nodes),
+            controls: positions(&control_nodes),
+            table_nodes,
+            control_nodes,
+        }
+    }
+
+    fn find(&self, location: FieldLocation) -> Option<Node<'a, 'input>> {
+        match location {
+            FieldLocation::Cell { table, row, cell } => {
+                let row = children(*self.table_nodes.get(table)?, "tr").nth(row)?;
+                children(row, "tc").nth(cell)
+            }
+            FieldLocation::ContentControl { index } => self.control_nodes.get(index).copied(),
+        }
+    }
+}
+
+fn positions(nodes: &[Node]) -> HashMap<NodeId, usize> {
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(ix, node)| (node.id(), ix))
+        .collect()
+}
+
+/// Collects the text of runs, merging runs with the same formatting so that
+/// their emphasis is written once.
+#[derive(Default)]
+struct InlineWriter {
+    markdown: String,
+    runs: Vec<(String, bool, bool)>,
+}
+
+impl InlineWriter {
+    fn run(&mut self, run: Node) {
+        let properties = child(run, "rPr");
+        let bold = properties.is_some_and(|properties| is_toggled(properties, "b"));
+        let italic = properties.is_some_and(|properties| is_toggled(properties, "i"));
+        for child in run.children().filter(Node::is_element) {
+            match local_name(child) {
+                "t" => self.push_text(child.text().unwrap_or_default(), bold, italic),
+                "tab" => self.push_text(" ", bold, italic),
+                "noBreakHyphen" => self.push_text("-", bold, italic),
+                "br" | "cr" => self.push_markdown("<br>"),
+                _ => {}
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str, bold: bool, italic: bool) {
+        match self.runs.last_mut() {
+            Some((last, last_bold, last_italic))
+                if *last_bold == bold && *last_italic == italic =>
+            {
+                last.push_str(text)
+            }
+            _ => self.runs.push((text.to_string(), bold, italic)),
+        }
+    }
+
+    fn push_markdown(&mut self, markdown: &str) {
+        self.flush();
+        self.markdown.push_str(markdown);
+    }
+
+    fn flush(&mut self) {
+        for (text, bold, italic) in self.runs.drain(..) {
+            let text = escape_markdown(&text);
+            let delimiter = match (bold, italic) {
+                (true, true) => "***",
+                (true, false) => "**",
+                (false, true) => "*",
+                (false, false) => "",
+            };
+            let trimmed = text.trim();
+            if delimiter.is_empty() || trimmed.is_empty() {
+                self.markdown.push_str(&text);
+                continue;
+            }
+            // Emphasis can't start or end with whitespace.
+            let leading = &text[..text.len() - text.trim_start().len()];
+            let trailing = &text[text.trim_end().len()..];
+            write!(
+                self.markdown,
+                "{leading}{delimiter}{trimmed}{delimiter}{trailing}"
+            )
+            .unwrap();
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.markdown.trim().to_string()
+    }
+}
+
+/// Whether a toggle property such as bold is on in a run's properties.
+fn is_toggled(properties: Node, name: &str) -> bool {
+    child(properties, name).is_some_and(|property| {
+        !matches!(
+            property.attribute((WORD_NAMESPACE, "val")),
+            Some("0" | "false" | "off")
+        )
+    })
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn heading_level(style: &str) -> Option<usize> {
+    if style == "Title" {
+        return Some(1);
+    }
+    let level = style.strip_prefix("Heading")?.parse::<usize>().ok()?;
+    (1..=6).contains(&level).then_some(level)
+}
+
+fn field_start(id: usize, name: Option<&str>) -> String {
+    match name {
+        Some(name) => {
+            let name = name.replace("--", "-").replace('>', "");
+            format!("{FIELD_START}{id}: {name} -->")
+        }
+        None => format!("{FIELD_START}{id} -->"),
+    }
+}
+
+/// The result of writing a response back into its template.
+pub struct WrittenBack {
+    pub docx: Vec<u8>,
+    pub fields_written: usize,
+    /// Fields that couldn't be written back, and why.
+    pub warnings: Vec<String>,
+}
+
+/// Writes the fields of an imported template's Markdown that have been
+/// edited back into the template.
+pub async fn write_back_docx(
+    template: Vec<u8>,
+    mapping: &DocxMapping,
+    markdown: &str,
+) -> Result<WrittenBack> {
+    let package = DocxPackage::read(template)
+        .await
+        .context("failed to read the template")?;
+    let document = package.text(DOCUMENT_PART).unwrap_or_default();
+    let mut warnings = Vec::new();
+    let values = field_values(markdown, mapping, &mut warnings);
+    let (document, fields_written) = fill_fields(&document, mapping, &values, &mut warnings)?;
+
+    let parts = package
+        .parts
+        .into_iter()
+        .map(|(name, contents)| {
+            if name == DOCUMENT_PART {
+                (name, document.clone().into_bytes())
+            } else {
+                (name, contents)
+            }
+        })
+        .collect();
+    Ok(WrittenBack {
+        docx: zip_parts(parts).await?,
+        fields_written,
+        warnings,
+    })
+}
+
+/// The Markdown of each field in an edited document, for the fields that
+/// could be found.
+fn field_values(
+    markdown: &str,
+    mapping: &DocxMapping,
+    warnings: &mut Vec<String>,
+) -> Vec<Option<String>> {
+    let mut values = vec![None; mapping.fields.len()];
+
+    let mut offset = 0;
+    while let Some(start) = markdown[offset..].find(FIELD_START) {
+        let id_start = offset + start + FIELD_START.len();
+        let Some(content_start) = markdown[id_start..]
+            .find("-->")
+            .map(|end| id_start + end + "-->".len())
+        else {
+            break;
+        };
+        let id = markdown[id_start..]
+            .split(|c: char| !c.is_ascii_digit())
+            .next()
+            .and_then(|id| id.parse::<usize>().ok());
+        let Some(content_end) = markdown[content_start..]
+            .find(FIELD_END)
+            .map(|end| content_start + end)
+        else {
+            warnings.push(format!(
+                "A field starting on line {} has no end marker.",
+                markdown[..id_start].matches('\n').count() + 1
+            ));
+            break;
+        };
+        if let Some(value) = id.and_then(|id| values.get_mut(id)) {
+            *value = Some(markdown[content_start..content_end].trim().to_string());
+        }
+        offset = content_end + FIELD_END.len();
+    }
+
+    let tables = markdown_tables(markdown);
+    if tables.len() != mapping.tables.len() {
+        warnings.push(format!(
+            "The document has {} tables but the template has {}, so no table cells were written back.",
+            tables.len(),
+            mapping.tables.len()
+        ));
+        return values;
+    }
+    for (ix, (table, fields)) in tables.iter().zip(&mapping.tables).enumerate() {
+        if table.len() != fields.len() {
+            warnings.push(format!(
+                "Table {} has {} rows but the template's has {}, so its cells weren't written back.",
+                ix + 1,
+                table.len(),
+                fields.len()
+            ));
+            continue;
+        }
+        for (row, row_fields) in table.iter().zip(fields) {
+            for (cell, field) in row.iter().zip(row_fields) {
+                if let Some(value) = field.and_then(|field| values.get_mut(field)) {
+                    *value = Some(cell.clone());
+                }
+            }
+        }
+    }
+    values
+}
+
+/// The source of each cell of a document's tables, by table and row.
+fn markdown_tables(markdown: &str) -> Vec<Vec<Vec<String>>> {
+    let mut tables: Vec<Vec<Vec<String>>> = Vec::new();
+    for (event, range) in Parser::new_ext(markdown, prose::PARSE_OPTIONS).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Table(_)) => tables.push(Vec::new()),
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) => {
+                if let Some(table) = tables.last_mut() {
+                    table.push(Vec::new());
+                }
+            }
+            Event::Start(Tag::TableCell) => {
+                if let Some(row) = tables.last_mut().and_then(|table| table.last_mut()) {
+                    row.push(cell_source(&markdown[range]));
+                }
+            }
+            _ => {}
+        }
+    }
+    tables
+}
+
+fn cell_source(source: &str) -> String {
+    let mut source = source.trim();
+    source = source.strip_prefix('|').unwrap_or(source);
+    if source.ends_with('|') && !source.ends_with("\\|") {
+        source = &source[..source.len() - 1];
+    }
+    source.trim().to_string()
+}
+
+/// Replaces the content of the fields that have changed in a document's XML.
+fn fill_fields(
+    document: &str,
+    mapping: &DocxMapping,
+    values: &[Option<String>],
+    warnings: &mut Vec<String>,
+) -> Result<(String, usize)> {
+    let xml = Document::parse(document).context("failed to parse the template")?;
+    let elements = Elements::new(&xml);
+    let mut edits = Vec::new();
+    let mut fields_written = 0;
+    for (field, value) in mapping.fields.iter().zip(values) {
+        let Some(value) = value else {
+            continue;
+        };
+        if value.trim() == field.markdown.trim() {
+            continue;
+        }
+        let Some(node) = elements.find(field.location) else {
+            warnings.push(format!(
+                "The template no longer has {}.",
+                field.description()
+            ));
+            continue;
+        };
+        let paragraphs = markdown_paragraphs(value);
+        match field.location {
+            FieldLocation::Cell { .. } => {
+                let formatting = Formatting::of(document, node);
+                edits.push(content_edit(
+                    document,
+                    node,
+                    Some("tcPr"),
+                    formatting.paragraphs(&paragraphs),
+                ));
+            }
+            FieldLocation::ContentControl { .. } => {
+                if let Some(placeholder) =
+                    child(node, "sdtPr").and_then(|properties| child(properties, "showingPlcHdr"))
+                {
+                    edits.push((placeholder.range(), String::new()));
+                }
+                let Some(content) = child(node, "sdtContent") else {
+                    warnings.push(format!("{} has no content.", field.description()));
+                    continue;
+                };
+                let formatting = Formatting::of(document, content);
+                let is_inline = node.ancestors().skip(1).any(|ancestor| is(ancestor, "p"));
+                let xml = if is_inline {
+                    formatting.runs(&paragraphs)
+                } else {
+                    formatting.paragraphs(&paragraphs)
+                };
+                edits.push(content_edit(document, content, None, xml));
+            }
+        }
+        fields_written += 1;
+    }
+
+    edits.sort_by_key(|(range, _): &(Range<usize>, String)| std::cmp::Reverse(range.start));
+    let mut document = document.to_string();
+    for (range, replacement) in edits {
+        document.replace_range(range, &replacement);
+    }
+    Ok((document, fields_written))
+}
+
+/// An edit replacing an element's content, after the properties element it
+/// may start with.
+fn content_edit(
+    source: &str,
+    node: Node,
+    properties: Option<&str>,
+    content: String,
+) -> (Range<usize>, String) {
+    let range = node.range();
+    let element = &source[range.clone()];
+    let name_end = element[1..]
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .map_or(element.len(), |end| end + 1);
+    let name = &element[1..name_end];
+    if element.ends_with("/>") {
+        return (range, format!("<{name}>{content}</{name}>"));
+    }
+    let end = range.start + element.rfind("</").unwrap_or(element.len());
+    let start = match properties.and_then(|properties| child(node, properties)) {
+        Some(properties) => properties.range().end,
+        None => range.start + element.find('>').map_or(0, |end| end + 1),
+    };
+    (start..end, content)
+}
+
+/// The paragraph and run formatting a field's content is written in: that
+/// of its first paragraph and run, so that the template's formatting is kept.
+struct Formatting<'a> {
+    paragraph_properties: &'a str,
+    run_properties: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Formatting<'a> {
+    fn of(source: &'a str, node: Node<'_, 'a>) -> Self {
+        let paragraph_properties = node
+            .descendants()
+            .find(|node| is(*node, "p"))
+            .and_then(|paragraph| child(paragraph, "pPr"))
+            .map_or("", |properties| &source[properties.range()]);
+        let run_properties = node
+            .descendants()
+            .find(|node| is(*node, "r"))
+            .and_then(|run| child(run, "rPr"))
+            .map(|properties| {
+                properties
+                    .children()
+                    .filter(|property| {
+                        property.is_element()
+                            && !matches!(local_name(*property), "b" | "bCs" | "i" | "iCs")
+                            && !(is(*property, "rStyle")
+                                && property.attribute((WORD_NAMESPACE, "val"))
+                                    == Some("PlaceholderText"))
+                    })
+                    .map(|property| (local_name(property), &source[property.range()]))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            paragraph_properties,
+            run_properties,
+        }
+    }
+
+    fn paragraphs(&self, paragraphs: &[Vec<(String, bool, bool)>]) -> String {
+        let mut xml = String::new();
+        for paragraph in paragraphs {
+            xml.push_str("<w:p>");
+            xml.push_str(self.paragraph_properties);
+            for (text, bold, italic) in paragraph {
+                self.write_run(&mut xml, text, *bold, *italic);
+            }
+            xml.push_str("</w:p>");
+        }
+        xml
+    }
+
+    /// Runs for content that has to stay within a paragraph, with line
+    /// breaks between its paragraphs.
+    fn runs(&self, paragraphs: &[Vec<(String, bool, bool)>]) -> String {
+        let mut xml = String::new();
+        for (ix, paragraph) in paragraphs.iter().enumerate() {
+            if ix > 0 {
+                xml.push_str("<w:r><w:br/></w:r>");
+            }
+            for (text, bold, italic) in paragraph {
+                self.write_run(&mut xml, text, *bold, *italic);
+            }
+        }
+        xml
+    }
+
+    fn write_run(&self, xml: &mut String, text: &str, bold: bool, italic: bool) {
+        // Bold and italic come after the style and fonts in run properties.
+        let (leading, trailing): (Vec<_>, Vec<_>) = self
+            .run_properties
+            .iter()
+            .partition(|(name, _)| matches!(*name, "rStyle" | "rFonts"));
+        let mut properties = leading
+            .iter()
+            .map(|(_, source)| *source)
+            .collect::<String>();
+        if bold {
+            properties.push_str("<w:b/>");
+        }
+        if italic {
+            properties.push_str("<w:i/>");
+        }
+        properties.extend(trailing.iter().map(|(_, source)| *source));
+
+        xml.push_str("<w:r>");
+        if !properties.is_empty() {
+            write!(xml, "<w:rPr>{properties}</w:rPr>").unwrap();
+        }
+        write!(
+            xml,
+            r#"<w:t xml:space="preserve">{}</w:t></w:r>"#,
+            escape(text)
+        )
+        .unwrap();
+    }
+}
+
+/// The paragraphs of a field's Markdown, as runs of text and whether they're
+/// bold and italic. Line breaks start new paragraphs, as they separate a
+/// cell's paragraphs.
+fn markdown_paragraphs(markdown: &str) -> Vec<Vec<(String, bool, bool)>> {
+    let mut paragraphs = vec![Vec::new()];
+    let mut bold = 0;
+    let mut italic = 0;
+    for event in Parser::new_ext(markdown, prose::PARSE_OPTIONS) {
+        let mut new_paragraph = false;
+        match event {
+            Event::Start(Tag::Strong) => bold += 1,
+            Event::End(TagEnd::Strong) => bold -= 1,
+            Event::Start(Tag::Emphasis) => italic += 1,
+            Event::End(TagEnd::Emphasis) => italic -= 1,
+            Event::Start(Tag::Paragraph | Tag::Heading { .. } | Tag::Item | Tag::CodeBlock(_))
+            | Event::HardBreak => new_paragraph = true,
+            Event::InlineHtml(html) | Event::Html(html) if html.trim_start().starts_with("<br") => {
+                new_paragraph = true
+            }
+            Event::Text(text) | Event::Code(text) => {
+                let paragraph = paragraphs.last_mut().unwrap();
+                match paragraph.last_mut() {
+                    Some((last, last_bold, last_italic))
+                        if *last_bold == (bold > 0) && *last_italic == (italic > 0) =>
+                    {
+                        last.push_str(&text)
+                    }
+                    _ => paragraph.push((text.to_string(), bold > 0, italic > 0)),
+                }
+            }
+            Event::SoftBreak => {
+                if let Some((last, _, _)) = paragraphs.last_mut().unwrap().last_mut() {
+                    last.push(' ');
+                }
+            }
+            _ => {}
+        }
+        if new_paragraph && !paragraphs.last().unwrap().is_empty() {
+            paragraphs.push(Vec::new());
+        }
+    }
+    if paragraphs.len() > 1 && paragraphs.last().unwrap().is_empty() {
+        paragraphs.pop();
+    }
+    paragraphs
+}
+
+fn is(node: Node, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().name() == name
+        && node.tag_name().namespace() == Some(WORD_NAMESPACE)
+}
+
+fn local_name<'input>(node: Node<'_, 'input>) -> &'input str {
+    if node.tag_name().namespace() == Some(WORD_NAMESPACE) {
+        node.tag_name().name()
+    } else {
+        ""
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is(*child, name))
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |child| is(*child, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Schedule 3</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Answer </w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>every</w:t></w:r><w:r><w:t xml:space="preserve"> question_</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Company: </w:t></w:r><w:sdt><w:sdtPr><w:alias w:val="Company"/><w:showingPlcHdr/></w:sdtPr><w:sdtContent><w:r><w:rPr><w:rStyle w:val="PlaceholderText"/></w:rPr><w:t>Click here</w:t></w:r></w:sdtContent></w:sdt></w:p>
+<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Question</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Response</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:p><w:r><w:t>Years trading</w:t></w:r></w:p></w:tc><w:tc><w:tcPr><w:shd w:fill="FFFF00"/></w:tcPr><w:p><w:pPr><w:jc w:val="right"/></w:pPr><w:r><w:rPr><w:sz w:val="18"/></w:rPr><w:t></w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+<w:sdt><w:sdtPr><w:tag w:val="Methodology"/><w:lock w:val="sdtLocked"/></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Describe your methodology.</w:t></w:r></w:p></w:sdtContent></w:sdt>
+<w:sdt><w:sdtPr><w:lock w:val="contentLocked"/></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Do not edit.</w:t></w:r></w:p></w:sdtContent></w:sdt>
+<w:sectPr/></w:body></w:document>"#;
+
+    async fn template() -> Vec<u8> {
+        zip_parts(vec![
+            (
+                "[Content_Types].xml".to_string(),
+                br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"/>"#.to_vec(),
+            ),
+            (DOCUMENT_PART.to_string(), DOCUMENT.as_bytes().to_vec()),
+        ])
+        .await
+        .unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_import_docx() {
+        let imported = import_docx(template().await, "Schedule 3.docx".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            imported.markdown,
+            "# Schedule 3\n\n\
+             Answer **every** question\\_\n\n\
+             Company: <!-- field 0: Company --><!-- /field -->\n\n\
+             | Question | Response |\n\
+             | --- | --- |\n\
+             | Years trading |  |\n\n\
+             <!-- field 5: Methodology -->\n\n\
+             Describe your methodology.\n\n\
+             <!-- /field -->\n\n\
+             Do not edit.\n"
+        );
+        let mapping = imported.mapping;
+        assert_eq!(
+            mapping.tables,
+            [vec![vec![Some(1), Some(2)], vec![Some(3), Some(4)]]]
+        );
+        assert_eq!(
+            mapping.fields[4],
+            DocxField {
+                location: FieldLocation::Cell {
+                    table: 0,
+                    row: 1,
+                    cell: 1
+                },
+                name: None,
+                markdown: String::new(),
+            }
+        );
+        assert_eq!(
+            mapping.fields[5].location,
+            FieldLocation::ContentControl { index: 1 }
+        );
+        assert_eq!(mapping.fields.len(), 6);
+    }
+
+    #[gpui::test]
+    async fn test_write_back_docx() {
+        let imported = import_docx(template().await, "Schedule 3.docx".to_string())
+            .await
+            .unwrap();
+        let markdown = imported
+            .markdown
+            .replace(
+                "<!-- field 0: Company --><!-- /field -->",
+                "<!-- field 0: Company -->Acme & Sons<!-- /field -->",
+            )
+            .replace("| Years trading |  |", "| Years trading | **12** years |")
+            .replace(
+                "Describe your methodology.",
+                "We work in two phases.\n\nThen we *review*.",
+            )
+            .replace("Do not edit.", "Edited anyway.");
+        let written = write_back_docx(template().await, &imported.mapping, &markdown)
+            .await
+            .unwrap();
+        assert_eq!(written.fields_written, 3);
+        assert_eq!(written.warnings, Vec::<String>::new());
+
+        let document = DocxPackage::read(written.docx)
+            .await
+            .unwrap()
+            .text(DOCUMENT_PART)
+            .unwrap();
+        assert_eq!(
+            document,
+            DOCUMENT
+                .replace(
+                    r#"<w:alias w:val="Company"/><w:showingPlcHdr/></w:sdtPr><w:sdtContent><w:r><w:rPr><w:rStyle w:val="PlaceholderText"/></w:rPr><w:t>Click here</w:t></w:r></w:sdtContent>"#,
+                    r#"<w:alias w:val="Company"/></w:sdtPr><w:sdtContent><w:r><w:t xml:space="preserve">Acme &amp; Sons</w:t></w:r></w:sdtContent>"#,
+                )
+                .replace(
+                    r#"<w:p><w:pPr><w:jc w:val="right"/></w:pPr><w:r><w:rPr><w:sz w:val="18"/></w:rPr><w:t></w:t></w:r></w:p>"#,
+                    r#"<w:p><w:pPr><w:jc w:val="right"/></w:pPr><w:r><w:rPr><w:b/><w:sz w:val="18"/></w:rPr><w:t xml:space="preserve">12</w:t></w:r><w:r><w:rPr><w:sz w:val="18"/></w:rPr><w:t xml:space="preserve"> years</w:t></w:r></w:p>"#,
+                )
+                .replace(
+                    r#"<w:p><w:r><w:t>Describe your methodology.</w:t></w:r></w:p>"#,
+                    r#"<w:p><w:r><w:t xml:space="preserve">We work in two phases.</w:t></w:r></w:p><w:p><w:r><w:t xml:space="preserve">Then we </w:t></w:r><w:r><w:rPr><w:i/></w:rPr><w:t xml:space="preserve">review</w:t></w:r><w:r><w:t xml:space="preserve">.</w:t></w:r></w:p>"#,
+                )
+        );
+
+        // Cells aren't written back into a table whose rows have changed.
+        let markdown = markdown.replace(
+            "| Years trading | **12** years |",
+            "| Years trading | **12** years |\n| Staff | 40 |",
+        );
+        let written = write_back_docx(template().await, &imported.mapping, &markdown)
+            .await
+            .unwrap();
+        assert_eq!(written.fields_written, 2);
+        assert_eq!(
+            written.warnings,
+            ["Table 1 has 3 rows but the template's has 2, so its cells weren't written back."]
+        );
+    }
+
+    #[test]
+    fn test_is_protected() {
+        assert!(is_protected(
+            r#"<w:settings><w:documentProtection w:edit="forms" w:enforcement="1"/></w:settings>"#
+        ));
+        assert!(!is_protected(
+            r#"<w:settings><w:documentProtection w:edit="forms" w:enforcement="0"/></w:settings>"#
+        ));
+        assert!(!is_protected("<w:settings/>"));
+    }
+}
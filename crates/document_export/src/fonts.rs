@@ -0,0 +1,166 @@
+use anyhow::{Context as _, Result, anyhow};
+use assets::Assets;
+use collections::BTreeMap;
+use gpui::AssetSource as _;
+use std::cell::RefCell;
+
+/// The typefaces PDFs are set in, all of which are embedded in the files
+/// that use them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FontStyle {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Mono,
+}
+
+impl FontStyle {
+    pub const ALL: [FontStyle; 5] = [
+        FontStyle::Regular,
+        FontStyle::Bold,
+        FontStyle::Italic,
+        FontStyle::BoldItalic,
+        FontStyle::Mono,
+    ];
+
+    pub fn new(bold: bool, italic: bool) -> Self {
+        match (bold, italic) {
+            (false, false) => FontStyle::Regular,
+            (true, false) => FontStyle::Bold,
+            (false, true) => FontStyle::Italic,
+            (true, true) => FontStyle::BoldItalic,
+        }
+    }
+
+    fn asset_path(&self) -> &'static str {
+        match self {
+            FontStyle::Regular => "fonts/plex-sans/ZedPlexSans-Regular.ttf",
+            FontStyle::Bold => "fonts/plex-sans/ZedPlexSans-Bold.ttf",
+            FontStyle::Italic => "fonts/plex-sans/ZedPlexSans-Italic.ttf",
+            FontStyle::BoldItalic => "fonts/plex-sans/ZedPlexSans-BoldItalic.ttf",
+            FontStyle::Mono => "fonts/plex-mono/ZedPlexMono-Regular.ttf",
+        }
+    }
+
+    /// The PostScript name the font is embedded under.
+    pub fn postscript_name(&self) -> &'static str {
+        match self {
+            FontStyle::Regular => "ZedPlexSans-Regular",
+            FontStyle::Bold => "ZedPlexSans-Bold",
+            FontStyle::Italic => "ZedPlexSans-Italic",
+            FontStyle::BoldItalic => "ZedPlexSans-BoldItalic",
+            FontStyle::Mono => "ZedPlexMono-Regular",
+        }
+    }
+
+    pub fn is_italic(&self) -> bool {
+        matches!(self, FontStyle::Italic | FontStyle::BoldItalic)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub id: u16,
+    /// The advance width, in font units.
+    pub advance: u16,
+}
+
+/// A TrueType font, with the glyphs that text has been measured with so far.
+/// Those are the glyphs a PDF needs widths and Unicode mappings for.
+pub struct Font {
+    pub data: Vec<u8>,
+    pub units_per_em: f32,
+    pub ascender: i16,
+    pub descender: i16,
+    /// The bounding box of all glyphs, in font units.
+    pub bounding_box: [i16; 4],
+    glyphs: RefCell<BTreeMap<char, Glyph>>,
+}
+
+impl Font {
+    fn load(style: FontStyle) -> Result<Self> {
+        let data = Assets
+            .load(style.asset_path())?
+            .with_context(|| format!("missing font {}", style.asset_path()))?
+            .into_owned();
+        let face = parse_face(&data)?;
+        let bbox = face.global_bounding_box();
+        Ok(Self {
+            units_per_em: face.units_per_em() as f32,
+            ascender: face.ascender(),
+            descender: face.descender(),
+            bounding_box: [bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max],
+            glyphs: RefCell::default(),
+            data,
+        })
+    }
+
+    /// The glyph for `c`, or the font's missing glyph if it has none.
+    pub fn glyph(&self, c: char) -> Glyph {
+        if let Some(glyph) = self.glyphs.borrow().get(&c) {
+            return *glyph;
+        }
+        let glyph = parse_face(&self.data)
+            .map(|face| {
+                let id = face.glyph_index(c).unwrap_or(ttf_parser::GlyphId(0));
+                Glyph {
+                    id: id.0,
+                    advance: face.glyph_hor_advance(id).unwrap_or(0),
+                }
+            })
+            .unwrap_or(Glyph { id: 0, advance: 0 });
+        self.glyphs.borrow_mut().insert(c, glyph);
+        glyph
+    }
+
+    /// The width of `text` set at `size` points.
+    pub fn width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = text.chars().map(|c| self.glyph(c).advance as u32).sum();
+        units as f32 * size / self.units_per_em
+    }
+
+    /// The distance from the baseline to the top of the tallest glyphs, as a
+    /// proportion of the font size.
+    pub fn ascent(&self) -> f32 {
+        self.ascender as f32 / self.units_per_em
+    }
+
+    /// Scales a distance in font units to the thousandths of an em PDF font
+    /// metrics are given in.
+    pub fn to_pdf_units(&self, units: f32) -> i32 {
+        (units * 1000. / self.units_per_em).round() as i32
+    }
+
+    /// The characters measured so far and their glyphs.
+    pub fn used_glyphs(&self) -> Vec<(char, Glyph)> {
+        self.glyphs
+            .borrow()
+            .iter()
+            .map(|(c, glyph)| (*c, *glyph))
+            .collect()
+    }
+}
+
+fn parse_face(data: &[u8]) -> Result<ttf_parser::Face<'_>> {
+    ttf_parser::Face::parse(data, 0).map_err(|error| anyhow!("failed to parse font: {error}"))
+}
+
+/// The fonts a document is set in.
+pub struct Fonts {
+    fonts: BTreeMap<FontStyle, Font>,
+}
+
+impl Fonts {
+    pub fn load() -> Result<Self> {
+        let fonts = FontStyle::ALL
+            .into_iter()
+            .map(|style| Ok((style, Font::load(style)?)))
+            .collect::<Result<_>>()?;
+        Ok(Self { fonts })
+    }
+
+    pub fn get(&self, style: FontStyle) -> &Font {
+        &self.fonts[&style]
+    }
+}
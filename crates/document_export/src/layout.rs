@@ -0,0 +1,1331 @@
+//! Lays out Markdown documents on pages, so they can be written to PDF and
+//! their page counts checked without rendering anything.
+
+use collections::HashMap;
+use image::ImageReader;
+use markdown_preview::markdown_elements::{
+    HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
+    ParsedMarkdownDefinitionList, ParsedMarkdownElement, ParsedMarkdownFootnoteDefinition,
+    ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownTable,
+    ParsedMarkdownTableAlignment, ParsedMarkdownText,
+};
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::PathBuf;
+use writing_app_settings::PageSize;
+
+use crate::fonts::{FontStyle, Fonts};
+use crate::text_runs::text_runs;
+
+const BODY_SIZE: f32 = 11.;
+const CODE_SIZE: f32 = 9.;
+const TABLE_SIZE: f32 = 10.;
+const HEADER_SIZE: f32 = 8.5;
+const LINE_HEIGHT: f32 = 1.35;
+const PARAGRAPH_SPACING: f32 = 6.;
+const LIST_INDENT: f32 = 18.;
+const QUOTE_INDENT: f32 = 14.;
+const CELL_PADDING: f32 = 4.;
+/// Images are sized as if shown at 96 pixels per inch.
+const POINTS_PER_PIXEL: f32 = 0.75;
+/// Headings up to this level are listed in the table of contents.
+const CONTENTS_MAX_LEVEL: usize = 3;
+/// The width reserved for page numbers in the table of contents.
+const CONTENTS_NUMBER_WIDTH: f32 = 36.;
+
+#[derive(Clone, Debug)]
+pub struct LayoutOptions {
+    pub page_size: PageSize,
+    /// The margin on each side of the page, in points.
+    pub margin: f32,
+    /// The size of body text, in points. Headings, tables and code are
+    /// scaled with it.
+    pub font_size: f32,
+    /// Shown at the top of every page, such as the tender reference.
+    pub header: Option<String>,
+    pub table_of_contents: bool,
+    /// The titles and page counts of documents appended after this one,
+    /// which are listed in the table of contents and counted in the page
+    /// numbers.
+    pub appendices: Vec<(String, usize)>,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::default(),
+            // 2cm.
+            margin: 56.7,
+            font_size: BODY_SIZE,
+            header: None,
+            table_of_contents: true,
+            appendices: Vec::new(),
+        }
+    }
+}
+
+/// A document laid out on pages. Positions are in points from the top left
+/// of the page.
+#[derive(Debug)]
+pub struct Layout {
+    pub page_width: f32,
+    pub page_height: f32,
+    pub pages: Vec<Page>,
+    /// How many pages the table of contents takes, at the start of the
+    /// document.
+    pub contents_pages: usize,
+    pub headings: Vec<HeadingPages>,
+}
+
+impl Layout {
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// The number of pages of content, not counting the table of contents.
+    pub fn body_page_count(&self) -> usize {
+        self.pages.len() - self.contents_pages
+    }
+}
+
+/// Where a heading's section falls in the laid-out document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadingPages {
+    pub level: usize,
+    pub text: String,
+    pub source_range: Range<usize>,
+    /// The index of the page the heading is on.
+    pub page: usize,
+    /// The index of the page the section's content ends on, including any
+    /// subsections.
+    pub end_page: usize,
+    pub y: f32,
+}
+
+impl HeadingPages {
+    /// The number of pages the section spans.
+    pub fn page_count(&self) -> usize {
+        self.end_page + 1 - self.page
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Page {
+    pub items: Vec<PageItem>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextColor {
+    Normal,
+    Muted,
+    Link,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PageItem {
+    /// A run of text in a single font, positioned by its baseline.
+    Text {
+        x: f32,
+        y: f32,
+        font: FontStyle,
+        size: f32,
+        color: TextColor,
+        text: String,
+    },
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        /// The gray level, from 0 for black to 1 for white.
+        gray: f32,
+    },
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        gray: f32,
+    },
+    Image {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        path: PathBuf,
+    },
+    Link {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        target: LinkTarget,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkTarget {
+    Url(String),
+    /// A position in the document, by page index.
+    Page {
+        page: usize,
+        y: f32,
+    },
+}
+
+/// Lays out `markdown`, followed by a table of contents if the options ask
+/// for one and the document has headings to list.
+pub fn layout_document(
+    markdown: &ParsedMarkdown,
+    images: &HashMap<PathBuf, Vec<u8>>,
+    fonts: &Fonts,
+    options: &LayoutOptions,
+) -> Layout {
+    let mut body = Engine::new(fonts, images, options);
+    body.layout_elements(&markdown.children);
+    body.close_headings(0);
+    let headings = body.headings;
+
+    let mut contents = headings
+        .iter()
+        .filter(|heading| heading.level <= CONTENTS_MAX_LEVEL)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut appendix_page = body.pages.len();
+    for (title, page_count) in &options.appendices {
+        contents.push(HeadingPages {
+            level: 1,
+            text: title.clone(),
+            source_range: 0..0,
+            page: appendix_page,
+            end_page: appendix_page + page_count.saturating_sub(1),
+            y: 0.,
+        });
+        appendix_page += page_count;
+    }
+    let mut pages = Vec::new();
+    let mut contents_pages = 0;
+    if options.table_of_contents && !contents.is_empty() {
+        // The number of pages the contents take doesn't depend on the page
+        // numbers in it, so it's laid out once to count them.
+        contents_pages = layout_contents(&contents, 0, fonts, options).len();
+        pages = layout_contents(&contents, contents_pages, fonts, options);
+    }
+    pages.extend(body.pages);
+
+    let headings = headings
+        .into_iter()
+        .map(|heading| HeadingPages {
+            page: heading.page + contents_pages,
+            end_page: heading.end_page + contents_pages,
+            ..heading
+        })
+        .collect();
+    let (page_width, page_height) = options.page_size.dimensions();
+    let mut layout = Layout {
+        page_width,
+        page_height,
+        pages,
+        contents_pages,
+        headings,
+    };
+    add_headers_and_footers(&mut layout, fonts, options);
+    layout
+}
+
+fn layout_contents(
+    headings: &[HeadingPages],
+    page_offset: usize,
+    fonts: &Fonts,
+    options: &LayoutOptions,
+) -> Vec<Page> {
+    let images = HashMap::default();
+    let mut engine = Engine::new(fonts, &images, options);
+    let title = [Span::plain("Contents", FontStyle::Bold)];
+    engine.layout_heading_lines(&title, 1);
+
+    let size = engine.size(BODY_SIZE);
+    let line_height = size * 1.6;
+    let regular = fonts.get(FontStyle::Regular);
+    let dot_width = regular.width(". ", size);
+    for heading in headings {
+        let font = if heading.level == 1 {
+            FontStyle::Bold
+        } else {
+            FontStyle::Regular
+        };
+        let indent = (heading.level - 1) as f32 * LIST_INDENT;
+        let number = (heading.page + page_offset + 1).to_string();
+        let text_width = engine.width - indent - CONTENTS_NUMBER_WIDTH;
+        let text = truncate(&heading.text, font, size, text_width, fonts);
+
+        engine.ensure_space(line_height);
+        let x = engine.left + indent;
+        let baseline = engine.baseline(line_height, size);
+        let width = fonts.get(font).width(&text, size);
+        let number_width = regular.width(&number, size);
+        let number_x = engine.left + engine.width - number_width;
+        let leader_width = number_x - (x + width) - size;
+        let dots = (leader_width / dot_width).floor().max(0.) as usize;
+        if dots > 0 {
+            let leader = ". ".repeat(dots);
+            let leader_x = number_x - size * 0.5 - regular.width(&leader, size);
+            engine.push(PageItem::Text {
+                x: leader_x,
+                y: baseline,
+                font: FontStyle::Regular,
+                size,
+                color: TextColor::Muted,
+                text: leader,
+            });
+        }
+        engine.push(PageItem::Text {
+            x,
+            y: baseline,
+            font,
+            size,
+            color: TextColor::Normal,
+            text,
+        });
+        engine.push(PageItem::Text {
+            x: number_x,
+            y: baseline,
+            font: FontStyle::Regular,
+            size,
+            color: TextColor::Normal,
+            text: number,
+        });
+        engine.push(PageItem::Link {
+            x,
+            y: engine.y,
+            width: engine.width - indent,
+            height: line_height,
+            target: LinkTarget::Page {
+                page: heading.page + page_offset,
+                y: heading.y,
+            },
+        });
+        engine.y += line_height;
+    }
+    engine.pages
+}
+
+/// Adds the running header and the "Page X of Y" footer to every page. The
+/// total includes the pages of any appendices.
+fn add_headers_and_footers(layout: &mut Layout, fonts: &Fonts, options: &LayoutOptions) {
+    let appendix_pages = options
+        .appendices
+        .iter()
+        .map(|(_, page_count)| page_count)
+        .sum::<usize>();
+    let page_count = layout.pages.len() + appendix_pages;
+    let margin = options.margin;
+    let regular = fonts.get(FontStyle::Regular);
+    for (ix, page) in layout.pages.iter_mut().enumerate() {
+        if let Some(header) = options.header.as_ref().filter(|header| !header.is_empty()) {
+            let text = truncate(
+                header,
+                FontStyle::Regular,
+                HEADER_SIZE,
+                layout.page_width - 2. * margin,
+                fonts,
+            );
+            let baseline = margin * 0.55;
+            page.items.push(PageItem::Text {
+                x: margin,
+                y: baseline,
+                font: FontStyle::Regular,
+                size: HEADER_SIZE,
+                color: TextColor::Muted,
+                text,
+            });
+            page.items.push(PageItem::Line {
+                from: (margin, baseline + HEADER_SIZE * 0.5),
+                to: (layout.page_width - margin, baseline + HEADER_SIZE * 0.5),
+                width: 0.5,
+                gray: 0.7,
+            });
+        }
+
+        let footer = format!("Page {} of {page_count}", ix + 1);
+        let width = regular.width(&footer, HEADER_SIZE);
+        page.items.push(PageItem::Text {
+            x: (layout.page_width - width) / 2.,
+            y: layout.page_height - margin * 0.45,
+            font: FontStyle::Regular,
+            size: HEADER_SIZE,
+            color: TextColor::Muted,
+            text: footer,
+        });
+    }
+}
+
+/// Shortens `text` to fit in `width`, ending it with an ellipsis.
+fn truncate(text: &str, font: FontStyle, size: f32, width: f32, fonts: &Fonts) -> String {
+    let font = fonts.get(font);
+    if font.width(text, size) <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let available = width - font.width("…", size);
+    let mut used = 0.;
+    for c in text.chars() {
+        used += font.width(c.encode_utf8(&mut [0; 4]), size);
+        if used > available {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.truncate(truncated.trim_end().len());
+    truncated.push('…');
+    truncated
+}
+
+/// Text in a single style, before it's broken into lines.
+#[derive(Clone, Debug)]
+struct Span {
+    text: String,
+    font: FontStyle,
+    url: Option<String>,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl Span {
+    fn plain(text: &str, font: FontStyle) -> Self {
+        Self {
+            text: text.to_string(),
+            font,
+            url: None,
+            underline: false,
+            strikethrough: false,
+        }
+    }
+
+    fn same_style(&self, other: &Span) -> bool {
+        self.font == other.font
+            && self.url == other.url
+            && self.underline == other.underline
+            && self.strikethrough == other.strikethrough
+    }
+}
+
+/// A span placed on a line, at an offset from the line's start.
+#[derive(Debug)]
+struct Fragment {
+    x: f32,
+    width: f32,
+    span: Span,
+}
+
+#[derive(Debug, Default)]
+struct Line {
+    fragments: Vec<Fragment>,
+    /// The width up to the end of the last word, leaving out trailing space.
+    width: f32,
+    /// The width including trailing space, where the next word would start.
+    advance: f32,
+}
+
+impl Line {
+    fn push(&mut self, span: Span, width: f32, trailing_space: f32) {
+        match self.fragments.last_mut() {
+            Some(last) if last.span.same_style(&span) => {
+                last.span.text.push_str(&span.text);
+                last.width += width;
+            }
+            _ => self.fragments.push(Fragment {
+                x: self.advance,
+                width,
+                span,
+            }),
+        }
+        self.width = self.advance + width - trailing_space;
+        self.advance += width;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Breaks styled text into lines no wider than `width`, at spaces where it
+/// can and within words that don't fit on a line of their own.
+fn break_lines(spans: &[Span], size: f32, width: f32, fonts: &Fonts) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut line = Line::default();
+    for span in spans {
+        let font = fonts.get(span.font);
+        for piece in span.text.split_inclusive([' ', '\n']) {
+            let newline = piece.ends_with('\n');
+            let piece = piece.trim_end_matches('\n');
+            let word = piece.trim_end_matches(' ');
+            let word_width = font.width(word, size);
+            let space_width = font.width(&piece[word.len()..], size);
+
+            if !line.fragments.is_empty() && line.advance + word_width > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if line.fragments.is_empty() && word_width > width {
+                // Break the word wherever it runs out of room.
+                let mut chunk = String::new();
+                let mut chunk_width = 0.;
+                for c in word.chars() {
+                    let char_width = font.width(c.encode_utf8(&mut [0; 4]), size);
+                    if !chunk.is_empty() && chunk_width + char_width > width {
+                        line.push(span_with_text(span, &chunk), chunk_width, 0.);
+                        lines.push(std::mem::take(&mut line));
+                        chunk.clear();
+                        chunk_width = 0.;
+                    }
+                    chunk.push(c);
+                    chunk_width += char_width;
+                }
+                let text = format!("{chunk}{}", &piece[word.len()..]);
+                line.push(
+                    span_with_text(span, &text),
+                    chunk_width + space_width,
+                    space_width,
+                );
+            } else if !piece.is_empty() && !(line.fragments.is_empty() && word.is_empty()) {
+                line.push(
+                    span_with_text(span, piece),
+                    word_width + space_width,
+                    space_width,
+                );
+            }
+            if newline {
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+    }
+    if !line.fragments.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn span_with_text(span: &Span, text: &str) -> Span {
+    Span {
+        text: text.to_string(),
+        ..span.clone()
+    }
+}
+
+/// Places blocks on pages from top to bottom, starting a new page whenever
+/// the next line, row or image doesn't fit.
+struct Engine<'a> {
+    fonts: &'a Fonts,
+    images: &'a HashMap<PathBuf, Vec<u8>>,
+    page_height: f32,
+    margin: f32,
+    left: f32,
+    width: f32,
+    y: f32,
+    pages: Vec<Page>,
+    /// The page the most recent content was placed on.
+    last_content_page: usize,
+    headings: Vec<HeadingPages>,
+    /// The headings whose sections haven't ended yet.
+    open_headings: Vec<usize>,
+    /// A list marker to place beside the next line.
+    pending_marker: Option<(String, f32)>,
+    in_quote: bool,
+    /// How much text is scaled from its default size.
+    scale: f32,
+}
+
+impl<'a> Engine<'a> {
+    fn new(
+        fonts: &'a Fonts,
+        images: &'a HashMap<PathBuf, Vec<u8>>,
+        options: &LayoutOptions,
+    ) -> Self {
+        let (page_width, page_height) = options.page_size.dimensions();
+        Self {
+            fonts,
+            images,
+            page_height,
+            margin: options.margin,
+            left: options.margin,
+            width: page_width - 2. * options.margin,
+            y: options.margin,
+            pages: vec![Page::default()],
+            last_content_page: 0,
+            headings: Vec::new(),
+            open_headings: Vec::new(),
+            pending_marker: None,
+            in_quote: false,
+            scale: options.font_size / BODY_SIZE,
+        }
+    }
+
+    /// A default text size, scaled to the document's font size.
+    fn size(&self, size: f32) -> f32 {
+        size * self.scale
+    }
+
+    fn top(&self) -> f32 {
+        self.margin
+    }
+
+    fn bottom(&self) -> f32 {
+        self.page_height - self.margin
+    }
+
+    fn page_ix(&self) -> usize {
+        self.pages.len() - 1
+    }
+
+    fn at_page_top(&self) -> bool {
+        self.y <= self.top()
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Page::default());
+        self.y = self.top();
+    }
+
+    /// Starts a new page unless there's `height` left on this one. Content
+    /// taller than a page is placed at the top of one and left to overflow.
+    fn ensure_space(&mut self, height: f32) {
+        if self.y + height > self.bottom() && !self.at_page_top() {
+            self.new_page();
+        }
+    }
+
+    /// Adds vertical space, unless at the top of a page.
+    fn space(&mut self, height: f32) {
+        if !self.at_page_top() {
+            self.y = (self.y + height).min(self.bottom());
+        }
+    }
+
+    fn push(&mut self, item: PageItem) {
+        self.last_content_page = self.page_ix();
+        self.pages.last_mut().unwrap().items.push(item);
+    }
+
+    fn baseline(&self, line_height: f32, size: f32) -> f32 {
+        self.y + (line_height - size) / 2. + size * 0.8
+    }
+
+    fn text_color(&self) -> TextColor {
+        if self.in_quote {
+            TextColor::Muted
+        } else {
+            TextColor::Normal
+        }
+    }
+
+    /// Ends the sections of open headings at `level` or deeper, or all of
+    /// them for level 0.
+    fn close_headings(&mut self, level: usize) {
+        while let Some(&ix) = self.open_headings.last() {
+            if self.headings[ix].level < level {
+                break;
+            }
+            self.headings[ix].end_page = self.last_content_page.max(self.headings[ix].page);
+            self.open_headings.pop();
+        }
+    }
+
+    fn layout_elements(&mut self, elements: &[ParsedMarkdownElement]) {
+        for element in elements {
+            match element {
+                ParsedMarkdownElement::Heading(heading) => {
+                    let level = match heading.level {
+                        HeadingLevel::H1 => 1,
+                        HeadingLevel::H2 => 2,
+                        HeadingLevel::H3 => 3,
+                        HeadingLevel::H4 => 4,
+                        HeadingLevel::H5 => 5,
+                        HeadingLevel::H6 => 6,
+                    };
+                    let spans = self.spans(&heading.contents, true);
+                    self.close_headings(level);
+                    let (page, y) = self.layout_heading_lines(&spans, level);
+                    self.headings.push(HeadingPages {
+                        level,
+                        text: spans.iter().map(|span| span.text.as_str()).collect(),
+                        source_range: heading.source_range.clone(),
+                        page,
+                        end_page: page,
+                        y,
+                    });
+                    self.open_headings.push(self.headings.len() - 1);
+                }
+                ParsedMarkdownElement::Paragraph(paragraph) => {
+                    self.layout_paragraph(paragraph);
+                }
+                ParsedMarkdownElement::ListItem(item) => self.layout_list_item(item),
+                ParsedMarkdownElement::Table(table) => self.layout_table(table),
+                ParsedMarkdownElement::BlockQuote(block_quote) => {
+                    self.layout_quote(None, &block_quote.children);
+                }
+                ParsedMarkdownElement::Callout(callout) => {
+                    self.layout_quote(Some(callout.kind.title()), &callout.children);
+                }
+                ParsedMarkdownElement::DefinitionList(list) => self.layout_definition_list(list),
+                ParsedMarkdownElement::FootnoteDefinition(footnote) => {
+                    self.layout_footnote(footnote)
+                }
+                ParsedMarkdownElement::CodeBlock(code_block) => {
+                    self.layout_code(code_block.contents.trim_end_matches('\n'));
+                }
+                ParsedMarkdownElement::HorizontalRule(_) => {
+                    self.space(PARAGRAPH_SPACING);
+                    self.ensure_space(PARAGRAPH_SPACING);
+                    self.push(PageItem::Line {
+                        from: (self.left, self.y),
+                        to: (self.left + self.width, self.y),
+                        width: 0.75,
+                        gray: 0.6,
+                    });
+                    self.y += PARAGRAPH_SPACING * 2.;
+                }
+                ParsedMarkdownElement::PageBreak(_) => {
+                    if !self.at_page_top() {
+                        self.new_page();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Places a heading, keeping it on the same page as the first lines of
+    /// its section. Returns the page and position it was placed at.
+    fn layout_heading_lines(&mut self, spans: &[Span], level: usize) -> (usize, f32) {
+        let size = self.size(match level {
+            1 => 20.,
+            2 => 16.,
+            3 => 13.5,
+            4 => 12.,
+            _ => BODY_SIZE,
+        });
+        let line_height = size * 1.25;
+        let lines = break_lines(spans, size, self.width, self.fonts);
+        self.space(size * 0.9);
+        let keep_with_next = self.size(BODY_SIZE) * LINE_HEIGHT * 2.;
+        self.ensure_space(lines.len() as f32 * line_height + size * 0.4 + keep_with_next);
+        let position = (self.page_ix(), self.y);
+        for line in &lines {
+            self.place_line(line, size, line_height);
+        }
+        self.y += size * 0.4;
+        position
+    }
+
+    fn layout_paragraph(&mut self, paragraph: &MarkdownParagraph) {
+        let mut spans = Vec::new();
+        for chunk in paragraph {
+            match chunk {
+                MarkdownParagraphChunk::Text(text) => {
+                    spans.extend(self.text_spans(text, false));
+                }
+                MarkdownParagraphChunk::Image(image) => match self.image_size(image) {
+                    Some((path, width, height)) => {
+                        self.layout_text(&spans);
+                        spans.clear();
+                        self.layout_image(path, width, height);
+                    }
+                    None => {
+                        if let Some(alt_text) = &image.alt_text {
+                            spans.push(Span::plain(alt_text, self.body_font()));
+                        }
+                    }
+                },
+            }
+        }
+        if !spans.is_empty() || self.pending_marker.is_some() {
+            self.layout_text(&spans);
+        }
+        self.y += PARAGRAPH_SPACING;
+    }
+
+    fn body_font(&self) -> FontStyle {
+        if self.in_quote {
+            FontStyle::Italic
+        } else {
+            FontStyle::Regular
+        }
+    }
+
+    fn layout_text(&mut self, spans: &[Span]) {
+        let size = self.size(BODY_SIZE);
+        let line_height = size * LINE_HEIGHT;
+        for line in break_lines(spans, size, self.width, self.fonts) {
+            self.place_line(&line, size, line_height);
+        }
+    }
+
+    /// Places a line at the current position, moving to the next page first
+    /// if it doesn't fit.
+    fn place_line(&mut self, line: &Line, size: f32, line_height: f32) {
+        self.ensure_space(line_height);
+        let baseline = self.baseline(line_height, size);
+        if let Some((marker, x)) = self.pending_marker.take() {
+            self.push(PageItem::Text {
+                x,
+                y: baseline,
+                font: FontStyle::Regular,
+                size,
+                color: self.text_color(),
+                text: marker,
+            });
+        }
+        for fragment in &line.fragments {
+            let x = self.left + fragment.x;
+            let text = fragment.span.text.trim_end().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            let text_width = self.fonts.get(fragment.span.font).width(&text, size);
+            let color = if fragment.span.url.is_some() {
+                TextColor::Link
+            } else {
+                self.text_color()
+            };
+            self.push(PageItem::Text {
+                x,
+                y: baseline,
+                font: fragment.span.font,
+                size,
+                color,
+                text,
+            });
+            if fragment.span.underline || fragment.span.url.is_some() {
+                self.push(PageItem::Line {
+                    from: (x, baseline + size * 0.12),
+                    to: (x + text_width, baseline + size * 0.12),
+                    width: size * 0.05,
+                    gray: 0.,
+                });
+            }
+            if fragment.span.strikethrough {
+                self.push(PageItem::Line {
+                    from: (x, baseline - size * 0.28),
+                    to: (x + text_width, baseline - size * 0.28),
+                    width: size * 0.05,
+                    gray: 0.,
+                });
+            }
+            if let Some(url) = &fragment.span.url {
+                self.push(PageItem::Link {
+                    x,
+                    y: self.y,
+                    width: text_width,
+                    height: line_height,
+                    target: LinkTarget::Url(url.clone()),
+                });
+            }
+        }
+        self.y += line_height;
+    }
+
+    fn layout_list_item(&mut self, item: &ParsedMarkdownListItem) {
+        let depth = item.depth.max(1) as f32;
+        let indent = depth * LIST_INDENT;
+        let marker = match &item.item_type {
+            ParsedMarkdownListItemType::Ordered(number) => format!("{number}."),
+            ParsedMarkdownListItemType::Task(true, _) => "[x]".to_string(),
+            ParsedMarkdownListItemType::Task(false, _) => "[ ]".to_string(),
+            ParsedMarkdownListItemType::Unordered => "•".to_string(),
+        };
+        let marker_x = self.left + indent - LIST_INDENT;
+        self.pending_marker = Some((marker, marker_x));
+
+        self.left += indent;
+        self.width -= indent;
+        let y = self.y;
+        self.layout_elements(&item.content);
+        if self.pending_marker.is_some() {
+            self.layout_text(&[]);
+        }
+        self.left -= indent;
+        self.width += indent;
+        // Items are separated by less space than paragraphs.
+        if self.y > y {
+            self.y -= PARAGRAPH_SPACING / 2.;
+        }
+    }
+
+    /// Places a block quote's children beside a bar, under a bold title if it
+    /// is a callout.
+    fn layout_quote(&mut self, title: Option<&str>, children: &[ParsedMarkdownElement]) {
+        let start = (self.page_ix(), self.y);
+        let was_in_quote = self.in_quote;
+        self.in_quote = true;
+        self.left += QUOTE_INDENT;
+        self.width -= QUOTE_INDENT;
+        if let Some(title) = title {
+            self.layout_text(&[Span::plain(title, FontStyle::BoldItalic)]);
+        }
+        self.layout_elements(children);
+        self.left -= QUOTE_INDENT;
+        self.width += QUOTE_INDENT;
+        self.in_quote = was_in_quote;
+        self.layout_quote_bar(start);
+    }
+
+    fn layout_definition_list(&mut self, list: &ParsedMarkdownDefinitionList) {
+        for item in &list.items {
+            let spans = self.spans(&item.term, true);
+            // Keep the term on the same page as the start of its definition.
+            self.ensure_space(self.size(BODY_SIZE) * LINE_HEIGHT * 2.);
+            self.layout_text(&spans);
+            self.left += LIST_INDENT;
+            self.width -= LIST_INDENT;
+            for definition in &item.definitions {
+                self.layout_elements(definition);
+            }
+            self.left -= LIST_INDENT;
+            self.width += LIST_INDENT;
+        }
+    }
+
+    fn layout_footnote(&mut self, footnote: &ParsedMarkdownFootnoteDefinition) {
+        self.pending_marker = Some((format!("{}.", footnote.number), self.left));
+        self.left += LIST_INDENT;
+        self.width -= LIST_INDENT;
+        self.layout_elements(&footnote.children);
+        if self.pending_marker.is_some() {
+            self.layout_text(&[]);
+        }
+        self.left -= LIST_INDENT;
+        self.width += LIST_INDENT;
+    }
+
+    fn layout_quote_bar(&mut self, start: (usize, f32)) {
+        let x = self.left + QUOTE_INDENT / 3.;
+        let end = (self.page_ix(), self.y - PARAGRAPH_SPACING);
+        for page in start.0..=end.0 {
+            let from = if page == start.0 { start.1 } else { self.top() };
+            let to = if page == end.0 { end.1 } else { self.bottom() };
+            if to > from {
+                self.pages[page].items.push(PageItem::Line {
+                    from: (x, from),
+                    to: (x, to),
+                    width: 2.,
+                    gray: 0.8,
+                });
+            }
+        }
+    }
+
+    fn layout_code(&mut self, code: &str) {
+        let font = self.fonts.get(FontStyle::Mono);
+        let line_height = self.size(CODE_SIZE) * 1.4;
+        let padding = CELL_PADDING * 1.5;
+        let char_width = font.width("0", self.size(CODE_SIZE)).max(1.);
+        let columns = (((self.width - 2. * padding) / char_width).floor() as usize).max(1);
+
+        self.space(PARAGRAPH_SPACING / 2.);
+        for source_line in code.split('\n') {
+            let chars = source_line.chars().collect::<Vec<_>>();
+            let mut chunks = chars.chunks(columns).peekable();
+            if chunks.peek().is_none() {
+                self.place_code_line("", line_height, padding);
+            }
+            for chunk in chunks {
+                let text = chunk.iter().collect::<String>();
+                self.place_code_line(&text, line_height, padding);
+            }
+        }
+        self.y += PARAGRAPH_SPACING;
+    }
+
+    fn place_code_line(&mut self, text: &str, line_height: f32, padding: f32) {
+        self.ensure_space(line_height);
+        self.push(PageItem::Rect {
+            x: self.left,
+            y: self.y,
+            width: self.width,
+            height: line_height,
+            gray: 0.95,
+        });
+        if !text.trim().is_empty() {
+            let baseline = self.baseline(line_height, self.size(CODE_SIZE));
+            self.push(PageItem::Text {
+                x: self.left + padding,
+                y: baseline,
+                font: FontStyle::Mono,
+                size: self.size(CODE_SIZE),
+                color: TextColor::Normal,
+                text: text.to_string(),
+            });
+        }
+        self.y += line_height;
+    }
+
+    /// Places a table row by row, repeating the header row at the top of
+    /// each page the table continues on.
+    fn layout_table(&mut self, table: &ParsedMarkdownTable) {
+        let columns = table
+            .column_alignments
+            .len()
+            .max(table.header.children.len())
+            .max(1);
+        let column_width = self.width / columns as f32;
+        let alignments = (0..columns)
+            .map(|column| match table.column_alignments.get(column) {
+                Some(ParsedMarkdownTableAlignment::Center) => Alignment::Center,
+                Some(ParsedMarkdownTableAlignment::Right) => Alignment::Right,
+                _ => Alignment::Left,
+            })
+            .collect::<Vec<_>>();
+        let line_height = self.size(TABLE_SIZE) * LINE_HEIGHT;
+        let layout_row = |engine: &Self, cells: &[MarkdownParagraph], header: bool| {
+            (0..columns)
+                .map(|column| {
+                    let spans = cells
+                        .get(column)
+                        .map(|cell| engine.spans(cell, header))
+                        .unwrap_or_default();
+                    break_lines(
+                        &spans,
+                        engine.size(TABLE_SIZE),
+                        column_width - 2. * CELL_PADDING,
+                        engine.fonts,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let row_height = |cells: &[Vec<Line>]| {
+            cells.iter().map(Vec::len).max().unwrap_or(1) as f32 * line_height + 2. * CELL_PADDING
+        };
+
+        let header = layout_row(self, &table.header.children, true);
+        let header_height = row_height(&header);
+        let body = table
+            .body
+            .iter()
+            .map(|row| layout_row(self, &row.children, false))
+            .collect::<Vec<_>>();
+
+        self.space(PARAGRAPH_SPACING / 2.);
+        let first_row_height = body.first().map_or(0., |row| row_height(row));
+        self.ensure_space(header_height + first_row_height);
+        self.place_row(&header, header_height, column_width, &alignments, true);
+        for row in &body {
+            let height = row_height(row);
+            if self.y + height > self.bottom() && !self.at_page_top() {
+                self.new_page();
+                self.place_row(&header, header_height, column_width, &alignments, true);
+            }
+            self.place_row(row, height, column_width, &alignments, false);
+        }
+        self.y += PARAGRAPH_SPACING * 2.;
+    }
+
+    fn place_row(
+        &mut self,
+        cells: &[Vec<Line>],
+        height: f32,
+        column_width: f32,
+        alignments: &[Alignment],
+        header: bool,
+    ) {
+        let top = self.y;
+        let right = self.left + column_width * cells.len() as f32;
+        if header {
+            self.push(PageItem::Rect {
+                x: self.left,
+                y: top,
+                width: right - self.left,
+                height,
+                gray: 0.92,
+            });
+        }
+        let line_height = self.size(TABLE_SIZE) * LINE_HEIGHT;
+        for (column, lines) in cells.iter().enumerate() {
+            let cell_left = self.left + column as f32 * column_width + CELL_PADDING;
+            self.y = top + CELL_PADDING;
+            for line in lines {
+                // Rows are kept whole, so the lines are placed without
+                // checking for space.
+                let baseline = self.baseline(line_height, self.size(TABLE_SIZE));
+                self.place_cell_line(
+                    line,
+                    baseline,
+                    cell_left,
+                    column_width - 2. * CELL_PADDING,
+                    alignments[column],
+                );
+                self.y += line_height;
+            }
+        }
+        for y in [top, top + height] {
+            self.push(PageItem::Line {
+                from: (self.left, y),
+                to: (right, y),
+                width: 0.5,
+                gray: 0.5,
+            });
+        }
+        for column in 0..=cells.len() {
+            let x = self.left + column as f32 * column_width;
+            self.push(PageItem::Line {
+                from: (x, top),
+                to: (x, top + height),
+                width: 0.5,
+                gray: 0.5,
+            });
+        }
+        self.y = top + height;
+    }
+
+    fn place_cell_line(
+        &mut self,
+        line: &Line,
+        baseline: f32,
+        left: f32,
+        width: f32,
+        alignment: Alignment,
+    ) {
+        let offset = match alignment {
+            Alignment::Left => 0.,
+            Alignment::Center => (width - line.width).max(0.) / 2.,
+            Alignment::Right => (width - line.width).max(0.),
+        };
+        for fragment in &line.fragments {
+            let text = fragment.span.text.trim_end().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            let x = left + offset + fragment.x;
+            let color = if fragment.span.url.is_some() {
+                TextColor::Link
+            } else {
+                TextColor::Normal
+            };
+            if let Some(url) = &fragment.span.url {
+                self.push(PageItem::Link {
+                    x,
+                    y: baseline - self.size(TABLE_SIZE),
+                    width: fragment.width,
+                    height: self.size(TABLE_SIZE) * LINE_HEIGHT,
+                    target: LinkTarget::Url(url.clone()),
+                });
+            }
+            self.push(PageItem::Text {
+                x,
+                y: baseline,
+                font: fragment.span.font,
+                size: self.size(TABLE_SIZE),
+                color,
+                text,
+            });
+        }
+    }
+
+    /// The size an image is shown at, if it's one that can be embedded.
+    fn image_size(&self, image: &Image) -> Option<(PathBuf, f32, f32)> {
+        let Link::Path { path, .. } = &image.link else {
+            return None;
+        };
+        let bytes = self.images.get(path)?;
+        let (width, height) = ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .ok()?
+            .into_dimensions()
+            .ok()?;
+        let mut width = width as f32 * POINTS_PER_PIXEL;
+        let mut height = height as f32 * POINTS_PER_PIXEL;
+        let max_height = self.bottom() - self.top();
+        let scale = (self.width / width).min(max_height / height).min(1.);
+        width *= scale;
+        height *= scale;
+        Some((path.clone(), width, height))
+    }
+
+    fn layout_image(&mut self, path: PathBuf, width: f32, height: f32) {
+        self.ensure_space(height);
+        self.push(PageItem::Image {
+            x: self.left,
+            y: self.y,
+            width,
+            height,
+            path,
+        });
+        self.y += height + PARAGRAPH_SPACING;
+    }
+
+    /// The spans of a paragraph's text, leaving out its images.
+    fn spans(&self, paragraph: &MarkdownParagraph, bold: bool) -> Vec<Span> {
+        paragraph
+            .iter()
+            .flat_map(|chunk| match chunk {
+                MarkdownParagraphChunk::Text(text) => self.text_spans(text, bold),
+                MarkdownParagraphChunk::Image(image) => image
+                    .alt_text
+                    .iter()
+                    .map(|alt_text| Span::plain(alt_text, self.body_font()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn text_spans(&self, text: &ParsedMarkdownText, bold: bool) -> Vec<Span> {
+        text_runs(text)
+            .into_iter()
+            .map(|run| Span {
+                font: if run.code {
+                    FontStyle::Mono
+                } else {
+                    FontStyle::new(run.bold || bold, run.italic || self.in_quote)
+                },
+                url: run.url.map(str::to_string),
+                underline: run.underline,
+                strikethrough: run.strikethrough,
+                text: run.text,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown_preview::markdown_parser::parse_markdown;
+    use pretty_assertions::assert_eq;
+
+    async fn layout(text: &str, options: &LayoutOptions) -> Layout {
+        let markdown = parse_markdown(text, None, None).await;
+        let fonts = Fonts::load().unwrap();
+        layout_document(&markdown, &HashMap::default(), &fonts, options)
+    }
+
+    fn page_text(page: &Page) -> Vec<&str> {
+        page.items
+            .iter()
+            .filter_map(|item| match item {
+                PageItem::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_break_lines() {
+        let fonts = Fonts::load().unwrap();
+        let font = fonts.get(FontStyle::Regular);
+        let spans = [
+            Span::plain("The quick brown ", FontStyle::Regular),
+            Span::plain("fox", FontStyle::Bold),
+            Span::plain(" jumps over the lazy dog", FontStyle::Regular),
+        ];
+        let width = font.width("The quick brown fox jumps", 10.) + 1.;
+        let lines = break_lines(&spans, 10., width, &fonts);
+        let text = lines
+            .iter()
+            .map(|line| {
+                line.fragments
+                    .iter()
+                    .map(|fragment| fragment.span.text.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(text, ["The quick brown fox jumps ", "over the lazy dog"]);
+        assert_eq!(lines[0].fragments.len(), 3);
+        assert_eq!(lines[0].fragments[1].span.font, FontStyle::Bold);
+        assert!(lines.iter().all(|line| line.width <= width));
+
+        let long_word = "x".repeat(100);
+        let lines = break_lines(
+            &[Span::plain(&long_word, FontStyle::Regular)],
+            10.,
+            50.,
+            &fonts,
+        );
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.width <= 50.));
+    }
+
+    #[gpui::test]
+    async fn test_layout_document() {
+        let text = format!(
+            "# Response\n\n## Methodology\n\n{}\n\n## Pricing\n\nSee the schedule.\n",
+            "We will deliver the service in three phases. ".repeat(400)
+        );
+        let options = LayoutOptions {
+            header: Some("RFT-2026-041".to_string()),
+            ..Default::default()
+        };
+        let layout = layout(&text, &options).await;
+
+        assert_eq!(layout.contents_pages, 1);
+        assert!(layout.body_page_count() > 3);
+        let contents = page_text(&layout.pages[0]);
+        assert!(contents.contains(&"Contents"));
+        assert!(contents.contains(&"Methodology"));
+
+        let headings = layout
+            .headings
+            .iter()
+            .map(|heading| (heading.text.as_str(), heading.level, heading.page))
+            .collect::<Vec<_>>();
+        let pricing_page = layout.headings[2].page;
+        assert_eq!(
+            headings,
+            [
+                ("Response", 1, 1),
+                ("Methodology", 2, 1),
+                ("Pricing", 2, pricing_page)
+            ]
+        );
+        // Methodology ends where Pricing starts, or on the page before if
+        // Pricing had to move to a new page.
+        assert!((pricing_page - 1..=pricing_page).contains(&layout.headings[1].end_page));
+        assert_eq!(layout.headings[0].end_page, layout.page_count() - 1);
+        // The contents list each heading's page number.
+        assert!(contents.contains(&(pricing_page + 1).to_string().as_str()));
+
+        for (ix, page) in layout.pages.iter().enumerate() {
+            let text = page_text(page);
+            assert!(text.contains(&"RFT-2026-041"));
+            let footer = format!("Page {} of {}", ix + 1, layout.page_count());
+            assert!(text.contains(&footer.as_str()));
+            for item in &page.items {
+                if let PageItem::Text { y, .. } = item {
+                    assert!(*y <= layout.page_height);
+                }
+            }
+        }
+    }
+
+    #[gpui::test]
+    async fn test_table_header_repeats() {
+        let mut text = "| Item | Price |\n| --- | ---: |\n".to_string();
+        for ix in 0..120 {
+            text.push_str(&format!("| Item {ix} | ${ix}.00 |\n"));
+        }
+        let options = LayoutOptions {
+            table_of_contents: false,
+            ..Default::default()
+        };
+        let layout = layout(&text, &options).await;
+        assert_eq!(layout.contents_pages, 0);
+        assert!(layout.page_count() > 1);
+        for page in &layout.pages {
+            assert_eq!(page_text(page)[..2], ["Item", "Price"]);
+        }
+    }
+
+    #[gpui::test]
+    async fn test_page_break_and_callout() {
+        let text = "Introduction\n\n\\pagebreak\n\n> [!NOTE]\n> Closing remarks\n";
+        let options = LayoutOptions {
+            table_of_contents: false,
+            ..Default::default()
+        };
+        let layout = layout(text, &options).await;
+        assert_eq!(layout.page_count(), 2);
+        assert!(page_text(&layout.pages[0]).contains(&"Introduction"));
+        let second_page = page_text(&layout.pages[1]);
+        assert!(second_page.contains(&"Note"));
+        assert!(!second_page.contains(&"Introduction"));
+    }
+}
@@ -0,0 +1,660 @@
+use anyhow::Result;
+use collections::{BTreeMap, BTreeSet, HashMap};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use crate::fonts::{Font, FontStyle, Fonts, Glyph};
+use crate::layout::{Layout, LinkTarget, PageItem, TextColor};
+use crate::pdf_reader::{Dictionary, Object, PdfFile, write_entries};
+
+/// The color of link text, as RGB components.
+const LINK_COLOR: [f32; 3] = [0.02, 0.36, 0.71];
+const MUTED_GRAY: f32 = 0.4;
+
+/// Existing PDFs whose pages go before and after a laid-out document, such
+/// as a cover page and appendices.
+#[derive(Default)]
+pub struct AttachedPdfs {
+    pub before: Vec<PdfFile>,
+    pub after: Vec<PdfFile>,
+}
+
+/// Writes a laid-out document to a PDF, embedding the fonts and images it
+/// uses, between the pages of any attached PDFs.
+pub fn write_pdf(
+    layout: &Layout,
+    fonts: &Fonts,
+    images: &HashMap<PathBuf, Vec<u8>>,
+    title: Option<&str>,
+    attached: &AttachedPdfs,
+) -> Result<Vec<u8>> {
+    let mut pdf = PdfWriter::default();
+    let catalog_id = pdf.reserve();
+    let pages_id = pdf.reserve();
+    let mut before_ids = Vec::new();
+    for file in &attached.before {
+        before_ids.extend(import_pages(&mut pdf, file, pages_id)?);
+    }
+    let page_ids = layout
+        .pages
+        .iter()
+        .map(|_| pdf.reserve())
+        .collect::<Vec<_>>();
+    let mut after_ids = Vec::new();
+    for file in &attached.after {
+        after_ids.extend(import_pages(&mut pdf, file, pages_id)?);
+    }
+
+    // Fonts are written after the pages, once all their glyphs are known.
+    let mut font_ids = BTreeMap::<FontStyle, usize>::new();
+    let mut image_ids = HashMap::<PathBuf, Option<usize>>::default();
+    for (ix, page) in layout.pages.iter().enumerate() {
+        let mut content = String::new();
+        let mut annotations = Vec::new();
+        let mut page_fonts = BTreeMap::new();
+        let mut page_images = BTreeSet::new();
+        for item in &page.items {
+            match item {
+                PageItem::Text {
+                    x,
+                    y,
+                    font,
+                    size,
+                    color,
+                    text,
+                } => {
+                    let font_id = *font_ids.entry(*font).or_insert_with(|| pdf.reserve());
+                    page_fonts.insert(*font, font_id);
+                    match color {
+                        TextColor::Normal => content.push_str("0 g\n"),
+                        TextColor::Muted => writeln!(content, "{MUTED_GRAY} g").unwrap(),
+                        TextColor::Link => {
+                            let [r, g, b] = LINK_COLOR;
+                            writeln!(content, "{r} {g} {b} rg").unwrap();
+                        }
+                    }
+                    write!(
+                        content,
+                        "BT /F{} {} Tf {} {} Td <",
+                        font_index(*font),
+                        number(*size),
+                        number(*x),
+                        number(layout.page_height - y)
+                    )
+                    .unwrap();
+                    let font = fonts.get(*font);
+                    for c in text.chars() {
+                        write!(content, "{:04X}", font.glyph(c).id).unwrap();
+                    }
+                    content.push_str("> Tj ET\n");
+                }
+                PageItem::Line {
+                    from,
+                    to,
+                    width,
+                    gray,
+                } => {
+                    writeln!(
+                        content,
+                        "{} G {} w {} {} m {} {} l S",
+                        number(*gray),
+                        number(*width),
+                        number(from.0),
+                        number(layout.page_height - from.1),
+                        number(to.0),
+                        number(layout.page_height - to.1)
+                    )
+                    .unwrap();
+                }
+                PageItem::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    gray,
+                } => {
+                    writeln!(
+                        content,
+                        "{} g {} {} {} {} re f",
+                        number(*gray),
+                        number(*x),
+                        number(layout.page_height - y - height),
+                        number(*width),
+                        number(*height)
+                    )
+                    .unwrap();
+                }
+                PageItem::Image {
+                    x,
+                    y,
+                    width,
+                    height,
+                    path,
+                } => {
+                    let image_id = *image_ids.entry(path.clone()).or_insert_with(|| {
+                        let bytes = images.get(path)?;
+                        match write_image(&mut pdf, bytes) {
+                            Ok(id) => Some(id),
+                            Err(error) => {
+                                log::warn!("failed to embed image {}: {error:#}", path.display());
+                                None
+                            }
+                        }
+                    });
+                    if let Some(image_id) = image_id {
+                        page_images.insert(image_id);
+                        writeln!(
+                            content,
+                            "q {} 0 0 {} {} {} cm /Im{image_id} Do Q",
+                            number(*width),
+                            number(*height),
+                            number(*x),
+                            number(layout.page_height - y - height)
+                        )
+                        .unwrap();
+                    }
+                }
+                PageItem::Link {
+                    x,
+                    y,
+                    width,
+                    height,
+                    target,
+                } => {
+                    let rect = format!(
+                        "[{} {} {} {}]",
+                        number(*x),
+                        number(layout.page_height - y - height),
+                        number(x + width),
+                        number(layout.page_height - y)
+                    );
+                    let action = match target {
+                        LinkTarget::Url(url) => {
+                            format!("/A << /S /URI /URI {} >>", literal_string(url))
+                        }
+                        LinkTarget::Page { page, y } => match page_ids
+                            .get(*page)
+                            .or_else(|| after_ids.get(page.checked_sub(page_ids.len())?))
+                        {
+                            Some(page_id) => format!(
+                                "/Dest [{page_id} 0 R /XYZ null {} null]",
+                                number(layout.page_height - y)
+                            ),
+                            None => continue,
+                        },
+                    };
+                    annotations.push(pdf.add(format!(
+                        "<< /Type /Annot /Subtype /Link /Rect {rect} /Border [0 0 0] {action} >>"
+                    )));
+                }
+            }
+        }
+
+        let content_id = pdf.add_stream("", content.as_bytes());
+        let mut resources = String::from("<< /Font <<");
+        for (style, font_id) in &page_fonts {
+            write!(resources, " /F{} {font_id} 0 R", font_index(*style)).unwrap();
+        }
+        resources.push_str(" >>");
+        if !page_images.is_empty() {
+            resources.push_str(" /XObject <<");
+            for image_id in &page_images {
+                write!(resources, " /Im{image_id} {image_id} 0 R").unwrap();
+            }
+            resources.push_str(" >>");
+        }
+        resources.push_str(" >>");
+        let annotations = if annotations.is_empty() {
+            String::new()
+        } else {
+            let refs = annotations
+                .iter()
+                .map(|id| format!("{id} 0 R"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(" /Annots [{refs}]")
+        };
+        pdf.set(
+            page_ids[ix],
+            format!(
+                "<< /Type /Page /Parent {pages_id} 0 R /MediaBox [0 0 {} {}] /Resources {resources} /Contents {content_id} 0 R{annotations} >>",
+                number(layout.page_width),
+                number(layout.page_height)
+            ),
+        );
+    }
+
+    for (style, font_id) in &font_ids {
+        write_font(&mut pdf, *font_id, *style, fonts.get(*style));
+    }
+
+    let all_page_ids = before_ids
+        .iter()
+        .chain(&page_ids)
+        .chain(&after_ids)
+        .collect::<Vec<_>>();
+    let kids = all_page_ids
+        .iter()
+        .map(|id| format!("{id} 0 R"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    pdf.set(
+        pages_id,
+        format!(
+            "<< /Type /Pages /Kids [{kids}] /Count {} >>",
+            all_page_ids.len()
+        ),
+    );
+    pdf.set(
+        catalog_id,
+        format!("<< /Type /Catalog /Pages {pages_id} 0 R >>"),
+    );
+    let mut info = String::from("<< /Producer (Auto Tender)");
+    if let Some(title) = title {
+        write!(info, " /Title {}", text_string(title)).unwrap();
+    }
+    info.push_str(" >>");
+    let info_id = pdf.add(info);
+    Ok(pdf.finish(catalog_id, info_id))
+}
+
+/// Copies a PDF's pages into the document, along with everything their
+/// contents and resources refer to, returning the new pages' object numbers.
+/// Annotations are left out, as they can refer to other parts of the
+/// original document.
+fn import_pages(pdf: &mut PdfWriter, file: &PdfFile, pages_id: usize) -> Result<Vec<usize>> {
+    let pages = file.pages()?;
+    let mut ids = HashMap::<u32, usize>::default();
+    let mut page_ids = Vec::with_capacity(pages.len());
+    for (number, _) in &pages {
+        let id = pdf.reserve();
+        ids.insert(*number, id);
+        page_ids.push(id);
+    }
+
+    let pages = pages
+        .into_iter()
+        .map(|(_, mut page)| {
+            page.retain(|(key, _)| {
+                !matches!(
+                    key.as_slice(),
+                    b"Parent" | b"Annots" | b"B" | b"StructParents" | b"Tabs"
+                )
+            });
+            page
+        })
+        .collect::<Vec<Dictionary>>();
+    let mut queue = Vec::new();
+    for page in &pages {
+        for (_, value) in page {
+            collect_references(value, &mut queue);
+        }
+    }
+    let mut objects = Vec::new();
+    while let Some(number) = queue.pop() {
+        if ids.contains_key(&number) {
+            continue;
+        }
+        let object = file.object(number)?;
+        collect_references(&object, &mut queue);
+        ids.insert(number, pdf.reserve());
+        objects.push((number, object));
+    }
+
+    let reference = |number: u32| ids[&number];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        let mut output = format!("<< /Parent {pages_id} 0 R").into_bytes();
+        write_entries(&mut output, page, &reference);
+        output.extend_from_slice(b" >>");
+        pdf.set(*id, output);
+    }
+    for (number, object) in objects {
+        let mut output = Vec::new();
+        object.write(&mut output, &reference);
+        pdf.set(ids[&number], output);
+    }
+    Ok(page_ids)
+}
+
+fn collect_references(object: &Object, references: &mut Vec<u32>) {
+    match object {
+        Object::Reference(number) => references.push(*number),
+        Object::Array(array) => {
+            for item in array {
+                collect_references(item, references);
+            }
+        }
+        Object::Dictionary(dictionary) | Object::Stream(dictionary, _) => {
+            for (_, value) in dictionary {
+                collect_references(value, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The resource name suffix a font is referred to by in content streams.
+fn font_index(style: FontStyle) -> usize {
+    FontStyle::ALL
+        .iter()
+        .position(|candidate| *candidate == style)
+        .unwrap_or_default()
+        + 1
+}
+
+/// Embeds a TrueType font as a composite font whose character codes are
+/// glyph IDs, with a mapping back to Unicode so its text can be copied and
+/// searched.
+fn write_font(pdf: &mut PdfWriter, font_id: usize, style: FontStyle, font: &Font) {
+    let name = style.postscript_name();
+    let glyphs = font.used_glyphs();
+
+    let file_id = pdf.add_stream(&format!("/Length1 {}", font.data.len()), &font.data);
+    let [x_min, y_min, x_max, y_max] = font
+        .bounding_box
+        .map(|units| font.to_pdf_units(units as f32));
+    let mut flags = 32;
+    if style == FontStyle::Mono {
+        flags |= 1;
+    }
+    let italic_angle = if style.is_italic() {
+        flags |= 64;
+        -12
+    } else {
+        0
+    };
+    let ascent = font.to_pdf_units(font.ascender as f32);
+    let descriptor_id = pdf.add(format!(
+        "<< /Type /FontDescriptor /FontName /{name} /Flags {flags} /FontBBox [{x_min} {y_min} {x_max} {y_max}] /ItalicAngle {italic_angle} /Ascent {ascent} /Descent {} /CapHeight {ascent} /StemV 80 /FontFile2 {file_id} 0 R >>",
+        font.to_pdf_units(font.descender as f32),
+    ));
+
+    let mut widths = String::new();
+    let mut glyph_widths = glyphs
+        .iter()
+        .map(|(_, glyph)| (glyph.id, font.to_pdf_units(glyph.advance as f32)))
+        .collect::<Vec<_>>();
+    glyph_widths.sort_unstable();
+    glyph_widths.dedup_by_key(|(id, _)| *id);
+    for (id, width) in glyph_widths {
+        write!(widths, "{id} [{width}] ").unwrap();
+    }
+    let descendant_id = pdf.add(format!(
+        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{name} /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> /FontDescriptor {descriptor_id} 0 R /CIDToGIDMap /Identity /W [{}] >>",
+        widths.trim_end()
+    ));
+
+    let to_unicode_id = pdf.add_stream("", to_unicode_cmap(&glyphs).as_bytes());
+    pdf.set(
+        font_id,
+        format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{name} /Encoding /Identity-H /DescendantFonts [{descendant_id} 0 R] /ToUnicode {to_unicode_id} 0 R >>"
+        ),
+    );
+}
+
+fn to_unicode_cmap(glyphs: &[(char, Glyph)]) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n/CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let mut mappings = glyphs
+        .iter()
+        .filter(|(_, glyph)| glyph.id != 0)
+        .collect::<Vec<_>>();
+    mappings.sort_by_key(|(_, glyph)| glyph.id);
+    mappings.dedup_by_key(|(_, glyph)| glyph.id);
+    // A CMap section can hold at most 100 mappings.
+    for chunk in mappings.chunks(100) {
+        writeln!(cmap, "{} beginbfchar", chunk.len()).unwrap();
+        for (c, glyph) in chunk {
+            write!(cmap, "<{:04X}> <", glyph.id).unwrap();
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                write!(cmap, "{unit:04X}").unwrap();
+            }
+            cmap.push_str(">\n");
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap
+}
+
+/// Embeds an image as RGB pixels, with any transparency as a soft mask.
+fn write_image(pdf: &mut PdfWriter, bytes: &[u8]) -> Result<usize> {
+    let image = image::load_from_memory(bytes)?;
+    let (width, height) = (image.width(), image.height());
+    let smask = image.color().has_alpha().then(|| {
+        let alpha = image
+            .to_rgba8()
+            .pixels()
+            .map(|pixel| pixel.0[3])
+            .collect::<Vec<_>>();
+        let dictionary = format!(
+            "/Type /XObject /Subtype /Image /Width {width} /Height {height} /ColorSpace /DeviceGray /BitsPerComponent 8"
+        );
+        pdf.add_stream(&dictionary, &alpha)
+    });
+    let smask = smask
+        .map(|id| format!(" /SMask {id} 0 R"))
+        .unwrap_or_default();
+    let dictionary = format!(
+        "/Type /XObject /Subtype /Image /Width {width} /Height {height} /ColorSpace /DeviceRGB /BitsPerComponent 8{smask}"
+    );
+    Ok(pdf.add_stream(&dictionary, image.to_rgb8().as_raw()))
+}
+
+/// Formats a number compactly, to two decimal places.
+fn number(value: f32) -> String {
+    let formatted = format!("{value:.2}");
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" | "" => "0".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+/// A string of bytes, such as a URI, escaped for a PDF literal string.
+fn literal_string(text: &str) -> String {
+    let mut escaped = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => escaped.push(c),
+            c => {
+                for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                    write!(escaped, "\\{byte:03o}").unwrap();
+                }
+            }
+        }
+    }
+    escaped.push(')');
+    escaped
+}
+
+/// Text for display outside the page, such as the document's title, which
+/// PDF readers decode as UTF-16 when it starts with a byte order mark.
+fn text_string(text: &str) -> String {
+    if text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        return literal_string(text);
+    }
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        write!(hex, "{unit:04X}").unwrap();
+    }
+    hex.push('>');
+    hex
+}
+
+/// Assembles a PDF's objects and writes the cross-reference table that lets
+/// readers find them.
+#[derive(Default)]
+struct PdfWriter {
+    /// Each object's serialized contents, by object number less one. Reserved
+    /// objects are empty until they're set.
+    objects: Vec<Vec<u8>>,
+}
+
+impl PdfWriter {
+    fn reserve(&mut self) -> usize {
+        self.objects.push(Vec::new());
+        self.objects.len()
+    }
+
+    fn set(&mut self, id: usize, object: impl Into<Vec<u8>>) {
+        self.objects[id - 1] = object.into();
+    }
+
+    fn add(&mut self, object: impl Into<Vec<u8>>) -> usize {
+        let id = self.reserve();
+        self.set(id, object);
+        id
+    }
+
+    /// Adds a stream, compressed, with the given dictionary entries.
+    fn add_stream(&mut self, dictionary: &str, data: &[u8]) -> usize {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        // Writing to a Vec can't fail.
+        encoder.write_all(data).unwrap();
+        let data = encoder.finish().unwrap();
+        let dictionary = format!("{dictionary} /Filter /FlateDecode /Length {}", data.len());
+        let mut object = format!("<< {} >>\nstream\n", dictionary.trim_start()).into_bytes();
+        object.extend_from_slice(&data);
+        object.extend_from_slice(b"\nendstream");
+        self.add(object)
+    }
+
+    fn finish(self, catalog_id: usize, info_id: usize) -> Vec<u8> {
+        // The comment's high bytes mark the file as binary.
+        let mut output = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (ix, object) in self.objects.iter().enumerate() {
+            offsets.push(output.len());
+            writeln!(output, "{} 0 obj", ix + 1).unwrap();
+            output.extend_from_slice(object);
+            output.extend_from_slice(b"\nendobj\n");
+        }
+        let xref_offset = output.len();
+        writeln!(output, "xref\n0 {}\n0000000000 65535 f ", offsets.len() + 1).unwrap();
+        for offset in offsets {
+            writeln!(output, "{offset:010} 00000 n ").unwrap();
+        }
+        writeln!(
+            output,
+            "trailer\n<< /Size {} /Root {catalog_id} 0 R /Info {info_id} 0 R >>\nstartxref\n{xref_offset}\n%%EOF",
+            self.objects.len() + 1
+        )
+        .unwrap();
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{LayoutOptions, layout_document};
+    use markdown_preview::markdown_parser::parse_markdown;
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(12.), "12");
+        assert_eq!(number(12.5), "12.5");
+        assert_eq!(number(0.333), "0.33");
+        assert_eq!(number(-0.001), "0");
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(literal_string("a (b) \\c"), "(a \\(b\\) \\\\c)");
+        assert_eq!(text_string("Response"), "(Response)");
+        assert_eq!(text_string("Café"), "<FEFF00430061006600E9>");
+    }
+
+    #[gpui::test]
+    async fn test_write_pdf() {
+        let markdown = parse_markdown(
+            "# Response\n\nWe deliver [on time](https://example.com).\n\n## Pricing\n\n| Item | Price |\n| --- | --- |\n| Support | $10 |\n",
+            None,
+            None,
+        )
+        .await;
+        let fonts = Fonts::load().unwrap();
+        let images = HashMap::default();
+        let options = LayoutOptions {
+            header: Some("RFT-2026-041".to_string()),
+            ..Default::default()
+        };
+        let layout = layout_document(&markdown, &images, &fonts, &options);
+        let pdf = write_pdf(
+            &layout,
+            &fonts,
+            &images,
+            Some("Response"),
+            &AttachedPdfs::default(),
+        )
+        .unwrap();
+        let contains = |needle: &str| {
+            pdf.windows(needle.len())
+                .any(|window| window == needle.as_bytes())
+        };
+
+        assert!(pdf.starts_with(b"%PDF-1.7\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        assert!(contains(&format!("/Count {}", layout.page_count())));
+        assert!(contains("/BaseFont /ZedPlexSans-Regular"));
+        assert!(contains("/BaseFont /ZedPlexSans-Bold"));
+        assert!(contains("/FontFile2"));
+        assert!(contains("/URI (https://example.com)"));
+        assert!(contains("/Title (Response)"));
+        // The table of contents links to the headings' pages.
+        assert!(contains("/Dest ["));
+
+        // The cross-reference table points at each object.
+        let trailer_start = pdf
+            .windows(b"startxref".len())
+            .rposition(|window| window == b"startxref")
+            .unwrap();
+        let trailer = std::str::from_utf8(&pdf[trailer_start..]).unwrap();
+        let xref_offset = trailer.lines().nth(1).unwrap().parse::<usize>().unwrap();
+        assert!(pdf[xref_offset..].starts_with(b"xref\n"));
+        let xref = String::from_utf8_lossy(&pdf[xref_offset..trailer_start]);
+        let first_offset = xref.lines().nth(3).unwrap()[..10].parse::<usize>().unwrap();
+        assert!(pdf[first_offset..].starts_with(b"1 0 obj\n"));
+    }
+
+    #[gpui::test]
+    async fn test_attach_pdfs() {
+        let fonts = Fonts::load().unwrap();
+        let images = HashMap::default();
+        let options = LayoutOptions {
+            table_of_contents: false,
+            ..Default::default()
+        };
+        let cover = parse_markdown("# Cover", None, None).await;
+        let cover = layout_document(&cover, &images, &fonts, &options);
+        let cover = write_pdf(&cover, &fonts, &images, None, &AttachedPdfs::default()).unwrap();
+        let cover = PdfFile::parse(cover).unwrap();
+        assert_eq!(cover.pages().unwrap().len(), 1);
+
+        let body = parse_markdown(&"Body text.\n\n".repeat(200), None, None).await;
+        let body = layout_document(&body, &images, &fonts, &options);
+        let body_pages = body.page_count();
+        let attached = AttachedPdfs {
+            before: vec![cover],
+            after: Vec::new(),
+        };
+        let pdf = write_pdf(&body, &fonts, &images, None, &attached).unwrap();
+        let pdf = PdfFile::parse(pdf).unwrap();
+        let pages = pdf.pages().unwrap();
+        assert_eq!(pages.len(), body_pages + 1);
+
+        // The cover's content stream came along with its page.
+        let contents = crate::pdf_reader::get(&pages[0].1, "Contents").unwrap();
+        assert!(matches!(pdf.resolve(contents).unwrap(), Object::Stream(..)));
+    }
+}
@@ -0,0 +1,970 @@
+//! Reads existing PDFs, such as cover pages and appendices, so that their
+//! pages can be copied into exported documents.
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use collections::HashMap;
+use flate2::read::ZlibDecoder;
+use std::io::Read as _;
+
+/// An object in a PDF file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Object {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Real(f64),
+    String(Vec<u8>),
+    Name(Vec<u8>),
+    Array(Vec<Object>),
+    Dictionary(Dictionary),
+    /// A stream's dictionary and its data, still encoded.
+    Stream(Dictionary, Vec<u8>),
+    Reference(u32),
+}
+
+/// A dictionary's entries, in the order they were read.
+pub type Dictionary = Vec<(Vec<u8>, Object)>;
+
+pub fn get<'a>(dictionary: &'a Dictionary, key: &str) -> Option<&'a Object> {
+    dictionary
+        .iter()
+        .find(|(name, _)| name.as_slice() == key.as_bytes())
+        .map(|(_, value)| value)
+}
+
+impl Object {
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Object::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The integer, if it's a count, offset or other value that can't be
+    /// negative and fits in `T`.
+    pub fn as_unsigned<T: TryFrom<i64>>(&self) -> Option<T> {
+        T::try_from(self.as_integer()?).ok()
+    }
+
+    pub fn as_dictionary(&self) -> Option<&Dictionary> {
+        match self {
+            Object::Dictionary(dictionary) | Object::Stream(dictionary, _) => Some(dictionary),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Object]> {
+        match self {
+            Object::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn is_name(&self, name: &str) -> bool {
+        matches!(self, Object::Name(value) if value.as_slice() == name.as_bytes())
+    }
+
+    /// Serializes the object, writing references with `reference`.
+    pub fn write(&self, output: &mut Vec<u8>, reference: &impl Fn(u32) -> usize) {
+        match self {
+            Object::Null => output.extend_from_slice(b"null"),
+            Object::Bool(value) => output.extend_from_slice(value.to_string().as_bytes()),
+            Object::Integer(value) => output.extend_from_slice(value.to_string().as_bytes()),
+            Object::Real(value) => output.extend_from_slice(format!("{value:.4}").as_bytes()),
+            Object::String(bytes) => {
+                output.push(b'<');
+                for byte in bytes {
+                    output.extend_from_slice(format!("{byte:02X}").as_bytes());
+                }
+                output.push(b'>');
+            }
+            Object::Name(name) => write_name(output, name),
+            Object::Array(array) => {
+                output.push(b'[');
+                for (ix, item) in array.iter().enumerate() {
+                    if ix > 0 {
+                        output.push(b' ');
+                    }
+                    item.write(output, reference);
+                }
+                output.push(b']');
+            }
+            Object::Dictionary(dictionary) => write_dictionary(output, dictionary, reference),
+            Object::Stream(dictionary, data) => {
+                let mut dictionary = dictionary.clone();
+                dictionary.retain(|(key, _)| key.as_slice() != b"Length");
+                dictionary.push((b"Length".to_vec(), Object::Integer(data.len() as i64)));
+                write_dictionary(output, &dictionary, reference);
+                output.extend_from_slice(b"\nstream\n");
+                output.extend_from_slice(data);
+                output.extend_from_slice(b"\nendstream");
+            }
+            Object::Reference(number) => {
+                output.extend_from_slice(format!("{} 0 R", reference(*number)).as_bytes());
+            }
+        }
+    }
+}
+
+fn write_dictionary(
+    output: &mut Vec<u8>,
+    dictionary: &Dictionary,
+    reference: &impl Fn(u32) -> usize,
+) {
+    output.extend_from_slice(b"<<");
+    write_entries(output, dictionary, reference);
+    output.extend_from_slice(b" >>");
+}
+
+/// Serializes a dictionary's entries, each preceded by a space.
+pub fn write_entries(
+    output: &mut Vec<u8>,
+    dictionary: &Dictionary,
+    reference: &impl Fn(u32) -> usize,
+) {
+    for (key, value) in dictionary {
+        output.push(b' ');
+        write_name(output, key);
+        output.push(b' ');
+        value.write(output, reference);
+    }
+}
+
+fn write_name(output: &mut Vec<u8>, name: &[u8]) {
+    output.push(b'/');
+    for &byte in name {
+        if byte.is_ascii_graphic() && !is_delimiter(byte) && byte != b'#' {
+            output.push(byte);
+        } else {
+            output.extend_from_slice(format!("#{byte:02X}").as_bytes());
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum XrefEntry {
+    Offset(usize),
+    /// The object is the `index`th in the object stream `stream`.
+    Compressed {
+        stream: u32,
+        index: usize,
+    },
+}
+
+/// A parsed PDF, with its objects read as they're needed.
+pub struct PdfFile {
+    data: Vec<u8>,
+    xref: HashMap<u32, XrefEntry>,
+    trailer: Dictionary,
+    /// The objects stored in object streams, which are decoded up front.
+    compressed: HashMap<u32, Object>,
+}
+
+impl PdfFile {
+    pub fn parse(data: Vec<u8>) -> Result<Self> {
+        if !data.starts_with(b"%PDF-") {
+            bail!("not a PDF file");
+        }
+        let startxref = rfind(&data, b"startxref").context("missing startxref")?;
+        let mut lexer = Lexer::new(&data, startxref + b"startxref".len());
+        let offset = lexer.object()?.as_unsigned().context("invalid startxref")?;
+
+        let mut file = Self {
+            data: Vec::new(),
+            xref: HashMap::default(),
+            trailer: Dictionary::new(),
+            compressed: HashMap::default(),
+        };
+        let mut next = Some(offset);
+        let mut visited = Vec::new();
+        while let Some(offset) = next.take() {
+            if visited.contains(&offset) {
+                break;
+            }
+            visited.push(offset);
+            let trailer = read_xref_section(&data, offset, &mut file.xref)?;
+            if let Some(stream_offset) = get(&trailer, "XRefStm") {
+                let stream_offset = stream_offset.as_unsigned().context("invalid /XRefStm")?;
+                read_xref_section(&data, stream_offset, &mut file.xref)?;
+            }
+            next = get(&trailer, "Prev")
+                .map(|prev| prev.as_unsigned().context("invalid /Prev"))
+                .transpose()?;
+            if file.trailer.is_empty() {
+                file.trailer = trailer;
+            }
+        }
+        if get(&file.trailer, "Encrypt").is_some() {
+            bail!("encrypted PDFs can't be included");
+        }
+
+        file.data = data;
+        let mut streams = file
+            .xref
+            .values()
+            .filter_map(|entry| match entry {
+                XrefEntry::Compressed { stream, .. } => Some(*stream),
+                XrefEntry::Offset(_) => None,
+            })
+            .collect::<Vec<_>>();
+        streams.sort_unstable();
+        streams.dedup();
+        for stream in streams {
+            file.read_object_stream(stream)?;
+        }
+        Ok(file)
+    }
+
+    /// Reads the object with the given number, or null if there's no such
+    /// object.
+    pub fn object(&self, number: u32) -> Result<Object> {
+        match self.xref.get(&number) {
+            Some(XrefEntry::Offset(offset)) => {
+                let mut lexer = Lexer::new(&self.data, *offset);
+                let (_, object) = lexer.indirect_object()?;
+                Ok(object)
+            }
+            Some(XrefEntry::Compressed { .. }) => Ok(self
+                .compressed
+                .get(&number)
+                .cloned()
+                .unwrap_or(Object::Null)),
+            None => Ok(Object::Null),
+        }
+    }
+
+    /// Follows a reference, returning other objects as they are.
+    pub fn resolve(&self, object: &Object) -> Result<Object> {
+        match object {
+            Object::Reference(number) => self.object(*number),
+            object => Ok(object.clone()),
+        }
+    }
+
+    /// The document's pages, in order, each as its object number and its
+    /// dictionary with the attributes it inherits from the page tree filled
+    /// in.
+    pub fn pages(&self) -> Result<Vec<(u32, Dictionary)>> {
+        let root = get(&self.trailer, "Root").context("missing document catalog")?;
+        let root = self.resolve(root)?;
+        let pages = root
+            .as_dictionary()
+            .and_then(|catalog| get(catalog, "Pages"))
+            .context("missing page tree")?
+            .clone();
+        let mut result = Vec::new();
+        self.collect_pages(&pages, &Dictionary::new(), &mut result, 0)?;
+        Ok(result)
+    }
+
+    fn collect_pages(
+        &self,
+        node: &Object,
+        inherited: &Dictionary,
+        pages: &mut Vec<(u32, Dictionary)>,
+        depth: usize,
+    ) -> Result<()> {
+        if depth > 64 {
+            bail!("the page tree is too deep");
+        }
+        let Object::Reference(number) = node else {
+            bail!("invalid page tree");
+        };
+        let object = self.object(*number)?;
+        let dictionary = object.as_dictionary().context("invalid page tree")?;
+        let mut inherited = inherited.clone();
+        for key in ["Resources", "MediaBox", "CropBox", "Rotate"] {
+            if let Some(value) = get(dictionary, key) {
+                inherited.retain(|(name, _)| name.as_slice() != key.as_bytes());
+                inherited.push((key.as_bytes().to_vec(), value.clone()));
+            }
+        }
+
+        if get(dictionary, "Type").is_some_and(|kind| kind.is_name("Page")) {
+            let mut page = dictionary.clone();
+            for (key, value) in inherited {
+                if get(&page, std::str::from_utf8(&key).unwrap_or_default()).is_none() {
+                    page.push((key, value));
+                }
+            }
+            pages.push((*number, page));
+            return Ok(());
+        }
+        let kids = get(dictionary, "Kids").cloned().unwrap_or(Object::Null);
+        let kids = self.resolve(&kids)?;
+        for kid in kids.as_array().unwrap_or_default() {
+            self.collect_pages(kid, &inherited, pages, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn read_object_stream(&mut self, number: u32) -> Result<()> {
+        let Object::Stream(dictionary, _) = self.object(number)? else {
+            bail!("object stream {number} is missing");
+        };
+        let data = self.decode_stream(number)?;
+        let count = get(&dictionary, "N")
+            .map_or(Some(0), Object::as_unsigned::<usize>)
+            .context("invalid object stream")?;
+        let first = get(&dictionary, "First")
+            .map_or(Some(0), Object::as_unsigned::<usize>)
+            .context("invalid object stream")?;
+
+        // The count comes from the file, so the header isn't allocated up
+        // front.
+        let mut lexer = Lexer::new(&data, 0);
+        let mut header = Vec::new();
+        for _ in 0..count {
+            let object_number = lexer
+                .object()?
+                .as_unsigned::<u32>()
+                .context("invalid object stream")?;
+            let offset = lexer
+                .object()?
+                .as_unsigned::<usize>()
+                .and_then(|offset| first.checked_add(offset))
+                .context("invalid object stream")?;
+            header.push((object_number, offset));
+        }
+        for (index, (object_number, offset)) in header.into_iter().enumerate() {
+            // Only the object the cross-reference table points at is used,
+            // in case an updated object stream replaces an older one.
+            let current = matches!(
+                self.xref.get(&object_number),
+                Some(XrefEntry::Compressed { stream, index: entry_index })
+                    if *stream == number && *entry_index == index
+            );
+            if current {
+                let mut lexer = Lexer::new(&data, offset);
+                self.compressed.insert(object_number, lexer.object()?);
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_stream(&self, number: u32) -> Result<Vec<u8>> {
+        let Object::Stream(dictionary, data) = self.object(number)? else {
+            bail!("object {number} isn't a stream");
+        };
+        decode_stream(&dictionary, &data)
+    }
+}
+
+/// Reads a cross-reference section at `offset`, either a table or a stream,
+/// adding the entries that newer sections haven't already defined. Returns
+/// its trailer dictionary.
+fn read_xref_section(
+    data: &[u8],
+    offset: usize,
+    xref: &mut HashMap<u32, XrefEntry>,
+) -> Result<Dictionary> {
+    let mut lexer = Lexer::new(data, offset);
+    lexer.skip_whitespace();
+    if lexer.rest().starts_with(b"xref") {
+        lexer.pos += b"xref".len();
+        loop {
+            lexer.skip_whitespace();
+            if lexer.rest().starts_with(b"trailer") {
+                lexer.pos += b"trailer".len();
+                return match lexer.object()? {
+                    Object::Dictionary(trailer) => Ok(trailer),
+                    _ => Err(anyhow!("invalid trailer")),
+                };
+            }
+            let start = lexer
+                .object()?
+                .as_unsigned::<u32>()
+                .context("invalid xref")?;
+            let count = lexer
+                .object()?
+                .as_unsigned::<u32>()
+                .context("invalid xref")?;
+            let end = start.checked_add(count).context("invalid xref")?;
+            for number in start..end {
+                let offset = lexer
+                    .object()?
+                    .as_unsigned::<usize>()
+                    .context("invalid xref")?;
+                let _generation = lexer.object()?;
+                lexer.skip_whitespace();
+                let kind = lexer.rest().first().copied();
+                lexer.pos += 1;
+                if kind == Some(b'n') {
+                    xref.entry(number).or_insert(XrefEntry::Offset(offset));
+                }
+            }
+        }
+    }
+
+    let (_, object) = lexer.indirect_object()?;
+    let Object::Stream(dictionary, stream) = object else {
+        bail!("invalid cross-reference stream");
+    };
+    let widths = get(&dictionary, "W")
+        .and_then(Object::as_array)
+        .context("invalid cross-reference stream")?
+        .iter()
+        .map(Object::as_unsigned::<usize>)
+        .collect::<Option<Vec<_>>>()
+        .filter(|widths| widths.len() == 3)
+        .context("invalid cross-reference stream")?;
+    let entry_width = widths
+        .iter()
+        .try_fold(0usize, |sum, width| sum.checked_add(*width))
+        .context("invalid cross-reference stream")?;
+    let size = get(&dictionary, "Size")
+        .map_or(Some(0), Object::as_unsigned::<u32>)
+        .context("invalid cross-reference stream")?;
+    let ranges = match get(&dictionary, "Index") {
+        Some(index) => index
+            .as_array()
+            .filter(|index| index.len() % 2 == 0)
+            .and_then(|index| {
+                index
+                    .chunks_exact(2)
+                    .map(|pair| {
+                        let start = pair[0].as_unsigned::<u32>()?;
+                        let count = pair[1].as_unsigned::<u32>()?;
+                        Some(start..start.checked_add(count)?)
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+            .context("invalid cross-reference stream")?,
+        None => vec![0..size],
+    };
+    let data = decode_stream(&dictionary, &stream)?;
+
+    let mut entries = data.chunks_exact(entry_width.max(1));
+    for range in ranges {
+        for number in range {
+            let Some(entry) = entries.next() else {
+                break;
+            };
+            let field = |ix: usize| {
+                let start = widths[..ix].iter().sum::<usize>();
+                entry[start..start + widths[ix]]
+                    .iter()
+                    .fold(0usize, |value, byte| value << 8 | *byte as usize)
+            };
+            let kind = if widths[0] == 0 { 1 } else { field(0) };
+            let entry = match kind {
+                1 => XrefEntry::Offset(field(1)),
+                2 => match u32::try_from(field(1)) {
+                    Ok(stream) => XrefEntry::Compressed {
+                        stream,
+                        index: field(2),
+                    },
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            xref.entry(number).or_insert(entry);
+        }
+    }
+    Ok(dictionary)
+}
+
+/// Decodes a Flate-compressed stream, undoing any PNG predictor.
+fn decode_stream(dictionary: &Dictionary, data: &[u8]) -> Result<Vec<u8>> {
+    let filter = get(dictionary, "Filter");
+    let flate = match filter {
+        None => false,
+        Some(filter) if filter.is_name("FlateDecode") => true,
+        Some(Object::Array(filters)) if filters.len() == 1 && filters[0].is_name("FlateDecode") => {
+            true
+        }
+        Some(_) => bail!("unsupported stream filter"),
+    };
+    if !flate {
+        return Ok(data.to_vec());
+    }
+    let mut decoded = Vec::new();
+    ZlibDecoder::new(data)
+        .read_to_end(&mut decoded)
+        .context("failed to decompress stream")?;
+
+    let params = get(dictionary, "DecodeParms").and_then(|params| match params {
+        Object::Array(params) => params.first().and_then(Object::as_dictionary),
+        params => params.as_dictionary(),
+    });
+    let predictor = params
+        .and_then(|params| get(params, "Predictor"))
+        .and_then(Object::as_integer)
+        .unwrap_or(1);
+    if predictor < 10 {
+        return Ok(decoded);
+    }
+    let columns = params
+        .and_then(|params| get(params, "Columns"))
+        .map_or(Some(1), Object::as_unsigned::<usize>)
+        .filter(|columns| (1..=decoded.len().max(1)).contains(columns))
+        .context("invalid predictor columns")?;
+    Ok(undo_png_predictor(&decoded, columns))
+}
+
+/// Reverses PNG row filtering, for one byte per pixel.
+fn undo_png_predictor(data: &[u8], columns: usize) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len());
+    let mut previous = vec![0u8; columns];
+    for row in data.chunks(columns + 1) {
+        let Some((&filter, row)) = row.split_first() else {
+            continue;
+        };
+        let mut current = vec![0u8; columns];
+        for (ix, &byte) in row.iter().enumerate() {
+            let left = if ix > 0 { current[ix - 1] } else { 0 };
+            let up = previous[ix];
+            let up_left = if ix > 0 { previous[ix - 1] } else { 0 };
+            current[ix] = match filter {
+                1 => byte.wrapping_add(left),
+                2 => byte.wrapping_add(up),
+                3 => byte.wrapping_add(((left as u16 + up as u16) / 2) as u8),
+                4 => byte.wrapping_add(paeth(left, up, up_left)),
+                _ => byte,
+            };
+        }
+        output.extend_from_slice(&current[..row.len()]);
+        previous = current;
+    }
+    output
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance = |value: u8| (estimate - value as i16).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+fn rfind(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\0' | b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+struct Lexer<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        self.data.get(self.pos..).unwrap_or_default()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if is_whitespace(byte) {
+                self.pos += 1;
+            } else if byte == b'%' {
+                while self
+                    .peek()
+                    .is_some_and(|byte| byte != b'\n' && byte != b'\r')
+                {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Reads a run of regular characters, such as a number or keyword.
+    fn token(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|byte| !is_whitespace(byte) && !is_delimiter(byte))
+        {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn expect_keyword(&mut self, keyword: &[u8]) -> Result<()> {
+        self.skip_whitespace();
+        if self.token() == keyword {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "expected {} at offset {}",
+                String::from_utf8_lossy(keyword),
+                self.pos
+            ))
+        }
+    }
+
+    /// Reads an object definition, `12 0 obj ... endobj`.
+    fn indirect_object(&mut self) -> Result<(u32, Object)> {
+        let number = self.object()?.as_unsigned().context("expected an object")?;
+        self.object()?;
+        self.expect_keyword(b"obj")?;
+        let object = self.object()?;
+        Ok((number, object))
+    }
+
+    fn object(&mut self) -> Result<Object> {
+        self.skip_whitespace();
+        let byte = self.peek().context("unexpected end of file")?;
+        match byte {
+            b'/' => {
+                self.pos += 1;
+                Ok(Object::Name(decode_name(self.token())))
+            }
+            b'<' if self.rest().starts_with(b"<<") => {
+                self.pos += 2;
+                let mut dictionary = Dictionary::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.rest().starts_with(b">>") {
+                        self.pos += 2;
+                        break;
+                    }
+                    let Object::Name(key) = self.object()? else {
+                        bail!("expected a dictionary key at offset {}", self.pos);
+                    };
+                    let value = self.object()?;
+                    dictionary.push((key, value));
+                }
+                self.stream_after(dictionary)
+            }
+            b'<' => {
+                self.pos += 1;
+                let end = self
+                    .rest()
+                    .iter()
+                    .position(|byte| *byte == b'>')
+                    .context("unterminated string")?;
+                let digits = self.rest()[..end]
+                    .iter()
+                    .filter(|byte| byte.is_ascii_hexdigit())
+                    .map(|byte| (*byte as char).to_digit(16).unwrap_or(0) as u8)
+                    .collect::<Vec<_>>();
+                self.pos += end + 1;
+                Ok(Object::String(
+                    digits
+                        .chunks(2)
+                        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+                        .collect(),
+                ))
+            }
+            b'(' => self.literal_string(),
+            b'[' => {
+                self.pos += 1;
+                let mut array = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        break;
+                    }
+                    array.push(self.object()?);
+                }
+                Ok(Object::Array(array))
+            }
+            _ => {
+                let token = self.token();
+                if token.is_empty() {
+                    bail!("unexpected character at offset {}", self.pos);
+                }
+                match token {
+                    b"true" => return Ok(Object::Bool(true)),
+                    b"false" => return Ok(Object::Bool(false)),
+                    b"null" => return Ok(Object::Null),
+                    _ => {}
+                }
+                let text = std::str::from_utf8(token)?;
+                if let Ok(integer) = text.parse::<i64>() {
+                    // An integer might start a reference: `12 0 R`.
+                    let saved = self.pos;
+                    self.skip_whitespace();
+                    let generation = self.token();
+                    self.skip_whitespace();
+                    if let Ok(number) = u32::try_from(integer) {
+                        if !generation.is_empty()
+                            && generation.iter().all(u8::is_ascii_digit)
+                            && self.token() == b"R"
+                        {
+                            return Ok(Object::Reference(number));
+                        }
+                    }
+                    self.pos = saved;
+                    return Ok(Object::Integer(integer));
+                }
+                text.parse::<f64>()
+                    .map(Object::Real)
+                    .map_err(|_| anyhow!("unexpected token {text:?} at offset {}", self.pos))
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<Object> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        let mut depth = 0;
+        loop {
+            let byte = self.peek().context("unterminated string")?;
+            self.pos += 1;
+            match byte {
+                b'(' => {
+                    depth += 1;
+                    bytes.push(byte);
+                }
+                b')' if depth == 0 => break,
+                b')' => {
+                    depth -= 1;
+                    bytes.push(byte);
+                }
+                b'\\' => {
+                    let escaped = self.peek().context("unterminated string")?;
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'b' => bytes.push(b'\x08'),
+                        b'f' => bytes.push(b'\x0C'),
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(digit @ b'0'..=b'7') => {
+                                        value = value * 8 + (digit - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            bytes.push(value as u8);
+                        }
+                        // A backslash at the end of a line continues it.
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => bytes.push(other),
+                    }
+                }
+                byte => bytes.push(byte),
+            }
+        }
+        Ok(Object::String(bytes))
+    }
+
+    /// Reads the data of a stream if one follows its dictionary.
+    fn stream_after(&mut self, dictionary: Dictionary) -> Result<Object> {
+        let saved = self.pos;
+        self.skip_whitespace();
+        if !self.rest().starts_with(b"stream") {
+            self.pos = saved;
+            return Ok(Object::Dictionary(dictionary));
+        }
+        self.pos += b"stream".len();
+        if self.rest().starts_with(b"\r\n") {
+            self.pos += 2;
+        } else if self.peek() == Some(b'\n') || self.peek() == Some(b'\r') {
+            self.pos += 1;
+        }
+        let start = self.pos;
+
+        // The length may be an indirect object, which the lexer can't
+        // follow, or just wrong, so fall back to looking for the end of the
+        // stream.
+        let end = get(&dictionary, "Length")
+            .and_then(Object::as_unsigned::<usize>)
+            .and_then(|length| start.checked_add(length))
+            .filter(|&end| {
+                let mut lexer = Lexer::new(self.data, end);
+                lexer.skip_whitespace();
+                lexer.rest().starts_with(b"endstream")
+            });
+        let end = match end {
+            Some(end) => end,
+            None => {
+                let mut end = start
+                    + self
+                        .rest()
+                        .windows(b"endstream".len())
+                        .position(|window| window == b"endstream")
+                        .context("unterminated stream")?;
+                if self.data[..end].ends_with(b"\r\n") {
+                    end -= 2;
+                } else if self.data[..end].ends_with(b"\n") || self.data[..end].ends_with(b"\r") {
+                    end -= 1;
+                }
+                end.max(start)
+            }
+        };
+        let data = self.data[start..end].to_vec();
+        self.pos = end;
+        self.expect_keyword(b"endstream")?;
+        Ok(Object::Stream(dictionary, data))
+    }
+}
+
+fn decode_name(token: &[u8]) -> Vec<u8> {
+    let mut name = Vec::with_capacity(token.len());
+    let mut ix = 0;
+    while ix < token.len() {
+        if token[ix] == b'#' && ix + 3 <= token.len() {
+            let hex = std::str::from_utf8(&token[ix + 1..ix + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                name.push(byte);
+                ix += 3;
+                continue;
+            }
+        }
+        name.push(token[ix]);
+        ix += 1;
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_objects() {
+        let mut lexer = Lexer::new(
+            b"<< /Type /Page /Kids [1 0 R 2 0 R] /Size 3 /Scale -1.5 /Title (A \\(draft\\)\\n) /Id <48656C6C6F> /Odd#20Name true >>",
+            0,
+        );
+        let object = lexer.object().unwrap();
+        let dictionary = object.as_dictionary().unwrap();
+        assert!(get(dictionary, "Type").unwrap().is_name("Page"));
+        assert_eq!(
+            get(dictionary, "Kids"),
+            Some(&Object::Array(vec![
+                Object::Reference(1),
+                Object::Reference(2)
+            ]))
+        );
+        assert_eq!(get(dictionary, "Size"), Some(&Object::Integer(3)));
+        assert_eq!(get(dictionary, "Scale"), Some(&Object::Real(-1.5)));
+        assert_eq!(
+            get(dictionary, "Title"),
+            Some(&Object::String(b"A (draft)\n".to_vec()))
+        );
+        assert_eq!(
+            get(dictionary, "Id"),
+            Some(&Object::String(b"Hello".to_vec()))
+        );
+        assert_eq!(get(dictionary, "Odd Name"), Some(&Object::Bool(true)));
+
+        let mut output = Vec::new();
+        object.write(&mut output, &|number| number as usize + 10);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<< /Type /Page /Kids [11 0 R 12 0 R] /Size 3 /Scale -1.5000 /Title <4120286472616674290A> /Id <48656C6C6F> /Odd#20Name true >>"
+        );
+    }
+
+    #[test]
+    fn test_stream_length() {
+        for length in ["5", "-3", "99", "9223372036854775807"] {
+            let source = format!("<< /Length {length} >>\nstream\nHello\nendstream");
+            let object = Lexer::new(source.as_bytes(), 0).object().unwrap();
+            assert!(
+                matches!(&object, Object::Stream(_, data) if data == b"Hello"),
+                "/Length {length}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_numbers() {
+        let object_stream = |n: &str| {
+            let data = format!("1 0 obj\n<< /N {n} /First 4 >>\nstream\n2 0 7\nendstream\nendobj");
+            let mut file = PdfFile {
+                data: data.into_bytes(),
+                xref: [
+                    (1, XrefEntry::Offset(0)),
+                    (
+                        2,
+                        XrefEntry::Compressed {
+                            stream: 1,
+                            index: 0,
+                        },
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                trailer: Dictionary::new(),
+                compressed: HashMap::default(),
+            };
+            file.read_object_stream(1)?;
+            file.object(2)
+        };
+        assert_eq!(object_stream("1").unwrap(), Object::Integer(7));
+        for n in ["-1", "9223372036854775807"] {
+            assert!(object_stream(n).is_err(), "/N {n}");
+        }
+
+        let xref_stream = |entries: &str| {
+            let data = format!(
+                "1 0 obj\n<< /Type /XRef /Size 1 {entries} >>\nstream\n\x01\x09\x00\nendstream\nendobj"
+            );
+            let mut xref = HashMap::default();
+            read_xref_section(data.as_bytes(), 0, &mut xref)?;
+            anyhow::Ok(xref)
+        };
+        assert_eq!(
+            xref_stream("/W [1 1 1] /Index [3 1]").unwrap().get(&3),
+            Some(&XrefEntry::Offset(9))
+        );
+        for entries in [
+            "/W [1 -1 1]",
+            "/W [2 9223372036854775807 9223372036854775807]",
+            "/W [1 1 1] /Index [0 -1]",
+            "/W [1 1 1] /Index [4294967295 2]",
+            "/W [1 1 1] /Index [0]",
+        ] {
+            assert!(xref_stream(entries).is_err(), "{entries}");
+        }
+
+        for table in [
+            "xref\n4294967295 2\n",
+            "xref\n0 -1\n",
+            "xref\n0 1\n-5 0 n\n",
+        ] {
+            assert!(
+                read_xref_section(table.as_bytes(), 0, &mut HashMap::default()).is_err(),
+                "{table:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_undo_png_predictor() {
+        // Two rows of three columns, filtered with "none" and "up".
+        let data = [0, 1, 2, 3, 2, 1, 1, 1];
+        assert_eq!(undo_png_predictor(&data, 3), [1, 2, 3, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,216 @@
+//! Lays out documents for the Markdown preview's print layout mode, the same
+//! way they're exported to PDF.
+
+use anyhow::{Context as _, Result, anyhow};
+use editor::Editor;
+use fs::Fs;
+use gpui::{App, AppContext as _, Entity, Task};
+use markdown_preview::print_layout::{
+    PrintColor, PrintItem, PrintLayout, PrintLayoutProvider, PrintPage, PrintSection,
+};
+use settings::{Settings as _, SettingsLocation};
+use std::path::Path;
+use tender_manifest::{TenderManifest, TenderManifestStore};
+use writing_app_settings::WritingAppSettings;
+
+use crate::{
+    FontStyle, Fonts, Layout, Page, PageItem, TextColor, layout_document, layout_options,
+    limited_sections, load_images, parse_document,
+};
+
+pub(crate) struct DocumentPrintLayout;
+
+impl PrintLayoutProvider for DocumentPrintLayout {
+    fn layout(&self, editor: &Entity<Editor>, cx: &mut App) -> Task<Result<PrintLayout>> {
+        let editor = editor.read(cx);
+        let project = editor.project.clone();
+        let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+            return Task::ready(Err(anyhow!("Only single files can be laid out")));
+        };
+        let buffer = buffer.read(cx);
+        let text = buffer.text();
+        let file = buffer.file();
+        let settings = WritingAppSettings::get(
+            file.map(|file| SettingsLocation {
+                worktree_id: file.worktree_id(cx),
+                path: file.path(),
+            }),
+            cx,
+        )
+        .clone();
+        let file_location = file
+            .and_then(|file| file.as_local())
+            .and_then(|file| file.abs_path(cx).parent().map(Path::to_path_buf));
+
+        let manifest = project.and_then(|project| {
+            TenderManifestStore::for_project(&project, cx)
+                .read(cx)
+                .manifest()
+        });
+        let options = layout_options(&settings, manifest.as_deref());
+        let fs = <dyn Fs>::global(cx);
+
+        cx.spawn(async move |cx| {
+            let markdown = parse_document(&text, file_location, &settings).await;
+            let images = load_images(&fs, &markdown).await;
+            cx.background_spawn(async move {
+                let fonts = Fonts::load().context("failed to load fonts")?;
+                let layout = layout_document(&markdown, &images, &fonts, &options);
+                Ok(print_layout(&text, &layout, manifest.as_deref()))
+            })
+            .await
+        })
+    }
+}
+
+fn print_layout(text: &str, layout: &Layout, manifest: Option<&TenderManifest>) -> PrintLayout {
+    let limits = limited_sections(text, layout, manifest);
+    let sections = layout
+        .headings
+        .iter()
+        .map(|heading| PrintSection {
+            level: heading.level,
+            title: heading.text.clone(),
+            source_range: heading.source_range.clone(),
+            page: heading.page,
+            page_count: heading.page_count(),
+            page_limit: limits
+                .iter()
+                .find(|(_, limited, _)| std::ptr::eq(*limited, heading))
+                .map(|(_, _, limit)| *limit),
+        })
+        .collect();
+
+    PrintLayout {
+        page_width: layout.page_width,
+        page_height: layout.page_height,
+        pages: layout.pages.iter().map(print_page).collect(),
+        sections,
+        body_page_count: layout.body_page_count(),
+        page_limit: manifest.and_then(|manifest| manifest.limits.pages),
+    }
+}
+
+fn print_page(page: &Page) -> PrintPage {
+    let items = page
+        .items
+        .iter()
+        .filter_map(|item| {
+            Some(match item {
+                PageItem::Text {
+                    x,
+                    y,
+                    font,
+                    size,
+                    color,
+                    text,
+                } => PrintItem::Text {
+                    x: *x,
+                    y: *y,
+                    size: *size,
+                    bold: matches!(font, FontStyle::Bold | FontStyle::BoldItalic),
+                    italic: matches!(font, FontStyle::Italic | FontStyle::BoldItalic),
+                    monospace: *font == FontStyle::Mono,
+                    color: match color {
+                        TextColor::Normal => PrintColor::Normal,
+                        TextColor::Muted => PrintColor::Muted,
+                        TextColor::Link => PrintColor::Link,
+                    },
+                    text: text.clone(),
+                },
+                PageItem::Line {
+                    from,
+                    to,
+                    width,
+                    gray,
+                } => PrintItem::Line {
+                    from: *from,
+                    to: *to,
+                    width: *width,
+                    gray: *gray,
+                },
+                PageItem::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    gray,
+                } => PrintItem::Rect {
+                    x: *x,
+                    y: *y,
+                    width: *width,
+                    height: *height,
+                    gray: *gray,
+                },
+                PageItem::Image {
+                    x,
+                    y,
+                    width,
+                    height,
+                    path,
+                } => PrintItem::Image {
+                    x: *x,
+                    y: *y,
+                    width: *width,
+                    height: *height,
+                    path: path.clone(),
+                },
+                // Links are only clickable in the exported PDF.
+                PageItem::Link { .. } => return None,
+            })
+        })
+        .collect();
+    PrintPage { items }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LayoutOptions;
+    use collections::HashMap;
+    use markdown_preview::markdown_parser::parse_markdown;
+    use tender_manifest::Limits;
+
+    #[gpui::test]
+    async fn test_print_layout_section_limits() {
+        let text = format!(
+            "# Response\n\n## Methodology\n\n<!-- limit: 1 page -->\n\n{}\n\n## Pricing\n\nSee the schedule.\n",
+            "We will deliver the service in three phases. ".repeat(300)
+        );
+        let markdown = parse_markdown(&text, None, None).await;
+        let fonts = Fonts::load().unwrap();
+        let layout = layout_document(
+            &markdown,
+            &HashMap::default(),
+            &fonts,
+            &LayoutOptions::default(),
+        );
+        let manifest = TenderManifest {
+            limits: Limits {
+                pages: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let preview = print_layout(&text, &layout, Some(&manifest));
+        assert_eq!(preview.pages.len(), layout.page_count());
+        assert!(preview.is_over_limit());
+
+        let limits = preview
+            .sections
+            .iter()
+            .map(|section| (section.title.as_str(), section.page_limit))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            limits,
+            [
+                ("Response", None),
+                ("Methodology", Some(1)),
+                ("Pricing", None)
+            ]
+        );
+        assert!(preview.sections[1].is_over_limit());
+        assert!(!preview.sections[2].is_over_limit());
+    }
+}
@@ -0,0 +1,680 @@
+//! Assembles a submission from the response files listed in a project's
+//! `submission.toml`: the files are joined in order, their headings numbered
+//! consistently, and links between them pointed at the headings they refer
+//! to within the bundle.
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use globset::Glob;
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+/// The name of the submission definition, at the root of the project.
+pub const SUBMISSION_FILE_NAME: &str = "submission.toml";
+
+/// How a submission is assembled, as described by its `submission.toml`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubmissionDefinition {
+    /// Where to write the bundle, relative to the project root and without
+    /// an extension.
+    #[serde(default = "default_output")]
+    pub output: String,
+    #[serde(default = "default_formats")]
+    pub formats: Vec<SubmissionFormat>,
+    #[serde(default)]
+    pub numbering: HeadingNumbering,
+    /// How many levels of headings are numbered, from the top level used in
+    /// the response.
+    #[serde(default = "default_numbering_depth")]
+    pub numbering_depth: usize,
+    /// A PDF to put before the response in the PDF bundle.
+    pub cover: Option<String>,
+    /// The response files, in order. Entries can be glob patterns, which add
+    /// the files they match in name order.
+    pub files: Vec<String>,
+    /// PDFs to append to the PDF bundle, in order.
+    #[serde(default)]
+    pub appendices: Vec<Appendix>,
+}
+
+fn default_output() -> String {
+    "submission/Response".to_string()
+}
+
+fn default_formats() -> Vec<SubmissionFormat> {
+    vec![SubmissionFormat::Pdf, SubmissionFormat::Docx]
+}
+
+fn default_numbering_depth() -> usize {
+    3
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionFormat {
+    Pdf,
+    Docx,
+    #[serde(alias = "md")]
+    Markdown,
+}
+
+impl SubmissionFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubmissionFormat::Pdf => "pdf",
+            SubmissionFormat::Docx => "docx",
+            SubmissionFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadingNumbering {
+    /// Headings are numbered 1, 1.1, 1.1.1 and so on, replacing any numbers
+    /// they were written with.
+    #[default]
+    Decimal,
+    /// Headings are left as they are.
+    None,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Appendix {
+    Path(String),
+    Titled { path: String, title: String },
+}
+
+impl Appendix {
+    pub fn path(&self) -> &str {
+        match self {
+            Appendix::Path(path) | Appendix::Titled { path, .. } => path,
+        }
+    }
+
+    /// The title the appendix is listed under in the table of contents,
+    /// which defaults to its file name.
+    pub fn title(&self) -> String {
+        match self {
+            Appendix::Titled { title, .. } => title.clone(),
+            Appendix::Path(path) => Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.clone()),
+        }
+    }
+}
+
+impl SubmissionDefinition {
+    pub fn parse(text: &str) -> Result<Self> {
+        toml::from_str(text).with_context(|| format!("invalid {SUBMISSION_FILE_NAME}"))
+    }
+
+    /// The response files in order, expanding glob patterns against the
+    /// files in the project. Files listed more than once are only included
+    /// the first time.
+    pub fn response_files(&self, project_files: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in &self.files {
+            if entry.contains(['*', '?', '[', '{']) {
+                let matcher = Glob::new(entry)
+                    .with_context(|| format!("invalid file pattern {entry:?}"))?
+                    .compile_matcher();
+                let mut matches = project_files
+                    .iter()
+                    .filter(|path| matcher.is_match(path))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                matches.sort();
+                files.extend(matches);
+            } else {
+                files.push(normalize(Path::new(entry)));
+            }
+        }
+        let mut seen = HashSet::default();
+        files.retain(|path| seen.insert(path.clone()));
+        Ok(files)
+    }
+}
+
+/// A response file, with its path relative to the project root.
+#[derive(Clone, Debug)]
+pub struct SubmissionFile {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// A link that doesn't lead anywhere in the bundle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrokenReference {
+    pub file: PathBuf,
+    /// The line of the link, counting from 1.
+    pub line: usize,
+    pub target: String,
+    pub reason: String,
+}
+
+impl BrokenReference {
+    pub fn message(&self) -> String {
+        format!(
+            "{}:{}: {} ({})",
+            self.file.display(),
+            self.line,
+            self.reason,
+            self.target
+        )
+    }
+}
+
+/// A heading of the assembled submission, for its table of contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentsEntry {
+    pub level: u8,
+    pub title: String,
+    pub anchor: String,
+}
+
+#[derive(Debug)]
+pub struct Assembly {
+    pub markdown: String,
+    pub contents: Vec<ContentsEntry>,
+    pub broken_references: Vec<BrokenReference>,
+}
+
+impl Assembly {
+    /// A linked list of the headings, for bundles that don't generate their
+    /// own table of contents.
+    pub fn contents_markdown(&self, max_level: u8) -> String {
+        let top_level = self
+            .contents
+            .iter()
+            .map(|entry| entry.level)
+            .min()
+            .unwrap_or(1);
+        let mut markdown = String::from("**Contents**\n\n");
+        for entry in &self.contents {
+            if entry.level - top_level >= max_level {
+                continue;
+            }
+            let indent = "  ".repeat((entry.level - top_level) as usize);
+            writeln!(markdown, "{indent}- [{}](#{})", entry.title, entry.anchor).unwrap();
+        }
+        markdown
+    }
+}
+
+struct FileHeading {
+    level: u8,
+    range: Range<usize>,
+    /// The heading's source, without its `#`s or any number it was written
+    /// with.
+    source: String,
+    title: String,
+    anchors: Vec<String>,
+    new_title: String,
+    new_anchor: String,
+}
+
+/// Joins the response files into one Markdown document, numbering their
+/// headings and rewriting links so that they work from `output_dir`, where
+/// the bundle is written. Links to files outside the submission, or to
+/// headings that don't exist, are reported rather than rewritten.
+pub fn assemble(
+    files: &[SubmissionFile],
+    numbering: HeadingNumbering,
+    numbering_depth: usize,
+    output_dir: &Path,
+) -> Assembly {
+    let mut headings = files
+        .iter()
+        .map(|file| file_headings(&file.text, numbering))
+        .collect::<Vec<_>>();
+
+    let top_level = headings
+        .iter()
+        .flatten()
+        .map(|heading| heading.level)
+        .min()
+        .unwrap_or(1);
+    let mut counters = [0usize; 6];
+    let mut contents = Vec::new();
+    for heading in headings.iter_mut().flatten() {
+        let depth = (heading.level - top_level) as usize;
+        heading.new_title = heading.title.clone();
+        if numbering == HeadingNumbering::Decimal && depth < numbering_depth {
+            counters[depth] += 1;
+            counters[depth + 1..].fill(0);
+            let number = counters[..=depth]
+                .iter()
+                .map(|counter| counter.to_string())
+                .collect::<Vec<_>>()
+                .join(".");
+            heading.new_title = format!("{number} {}", heading.title);
+            heading.source = format!("{number} {}", heading.source);
+        }
+        heading.new_anchor = slug(&heading.new_title);
+        contents.push(ContentsEntry {
+            level: heading.level,
+            title: heading.new_title.clone(),
+            anchor: heading.new_anchor.clone(),
+        });
+    }
+
+    let file_anchors = files
+        .iter()
+        .zip(&headings)
+        .map(|(file, headings)| {
+            let anchors = headings
+                .iter()
+                .flat_map(|heading| {
+                    heading
+                        .anchors
+                        .iter()
+                        .map(|anchor| (anchor.clone(), heading.new_anchor.clone()))
+                })
+                .collect::<HashMap<_, _>>();
+            let first = headings.first().map(|heading| heading.new_anchor.clone());
+            (file.path.clone(), (anchors, first))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut markdown = String::new();
+    let mut broken_references = Vec::new();
+    for (file, headings) in files.iter().zip(&headings) {
+        let text = strip_front_matter(&file.text);
+        let offset = file.text.len() - text.len();
+        let mut edits = headings
+            .iter()
+            .map(|heading| {
+                let mut replacement =
+                    format!("{} {}", "#".repeat(heading.level as usize), heading.source);
+                if file.text[heading.range.clone()].ends_with('\n') {
+                    replacement.push('\n');
+                }
+                (heading.range.clone(), replacement)
+            })
+            .collect::<Vec<_>>();
+
+        let directory = file.path.parent().unwrap_or(Path::new(""));
+        for (destination, range) in link_destinations(&file.text) {
+            let broken = |reason: &str| BrokenReference {
+                file: file.path.clone(),
+                line: file.text[..range.start].matches('\n').count() + 1,
+                target: destination.clone(),
+                reason: reason.to_string(),
+            };
+            if destination.contains("://") || destination.starts_with("mailto:") {
+                continue;
+            }
+            let (path, fragment) = match destination.split_once('#') {
+                Some((path, fragment)) => (path, Some(fragment)),
+                None => (destination.as_str(), None),
+            };
+            let replacement = if path.is_empty() {
+                let Some(fragment) = fragment else {
+                    continue;
+                };
+                match file_anchors[&file.path].0.get(fragment) {
+                    Some(anchor) => format!("#{anchor}"),
+                    None => {
+                        broken_references.push(broken("there's no such heading in this file"));
+                        continue;
+                    }
+                }
+            } else if path.ends_with(".md") {
+                let target = normalize(&directory.join(path));
+                let Some((anchors, first)) = file_anchors.get(&target) else {
+                    broken_references.push(broken("the file isn't part of the submission"));
+                    continue;
+                };
+                let anchor = match fragment {
+                    Some(fragment) => match anchors.get(fragment) {
+                        Some(anchor) => anchor.clone(),
+                        None => {
+                            broken_references.push(broken("there's no such heading in that file"));
+                            continue;
+                        }
+                    },
+                    None => first.clone().unwrap_or_default(),
+                };
+                format!("#{anchor}")
+            } else if Path::new(path).is_absolute() {
+                continue;
+            } else {
+                let target = normalize(&directory.join(path));
+                let mut rebased = relative_path(output_dir, &target)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                if let Some(fragment) = fragment {
+                    rebased.push('#');
+                    rebased.push_str(fragment);
+                }
+                rebased
+            };
+            if replacement != destination {
+                edits.push((range, replacement));
+            }
+        }
+
+        edits.retain(|(range, _)| range.start >= offset);
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        let mut text = file.text.clone();
+        for (range, replacement) in edits {
+            text.replace_range(range, &replacement);
+        }
+        let text = &text[offset..];
+
+        if !markdown.is_empty() {
+            markdown.push('\n');
+        }
+        markdown.push_str(text.trim_end());
+        markdown.push('\n');
+    }
+
+    Assembly {
+        markdown,
+        contents,
+        broken_references,
+    }
+}
+
+fn file_headings(text: &str, numbering: HeadingNumbering) -> Vec<FileHeading> {
+    prose::sections(text)
+        .into_iter()
+        .map(|section| {
+            let source = heading_source(&text[section.heading_range.clone()]);
+            let unnumbered_source = match numbering {
+                HeadingNumbering::Decimal => strip_number(&source).to_string(),
+                HeadingNumbering::None => source.clone(),
+            };
+            let title = match numbering {
+                HeadingNumbering::Decimal => strip_number(&section.title).to_string(),
+                HeadingNumbering::None => section.title.clone(),
+            };
+            let mut anchors = vec![slug(&section.title), slug(&title)];
+            anchors.dedup();
+            FileHeading {
+                level: section.level,
+                range: section.heading_range,
+                source: unnumbered_source,
+                title,
+                anchors,
+                new_title: String::new(),
+                new_anchor: String::new(),
+            }
+        })
+        .collect()
+}
+
+/// The inline Markdown of a heading, from either an ATX heading such as
+/// `## Title` or the text above a setext underline.
+fn heading_source(heading: &str) -> String {
+    let heading = heading.trim();
+    if heading.starts_with('#') {
+        let content = heading.trim_start_matches('#');
+        let content = content.trim();
+        // A closing sequence of `#`s is only one if it's set off by a space.
+        let without_closing = content.trim_end_matches('#');
+        if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+            return without_closing.trim_end().to_string();
+        }
+        return content.to_string();
+    }
+    let mut lines = heading.lines().collect::<Vec<_>>();
+    lines.pop();
+    lines
+        .iter()
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Removes a number a heading was written with, such as `3.` or `3.2`. Years
+/// and other long numbers are left alone.
+fn strip_number(title: &str) -> &str {
+    let number_end = title
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(title.len());
+    let number = &title[..number_end];
+    let rest = &title[number_end..];
+    let is_section_number = number.starts_with(|c: char| c.is_ascii_digit())
+        && number.split('.').all(|part| part.len() <= 2);
+    if is_section_number && rest.starts_with([' ', '\t']) {
+        rest.trim_start()
+    } else {
+        title
+    }
+}
+
+/// The anchor a heading is linked to by, as GitHub generates them.
+pub fn slug(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The destinations of a document's inline links and images, with their
+/// source ranges.
+fn link_destinations(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut destinations = Vec::new();
+    for (event, range) in Parser::new_ext(text, prose::PARSE_OPTIONS).into_offset_iter() {
+        let (Event::Start(Tag::Link { dest_url, .. }) | Event::Start(Tag::Image { dest_url, .. })) =
+            event
+        else {
+            continue;
+        };
+        // Reference links, whose destinations are defined elsewhere, and
+        // destinations with escapes can't be found in the link's source.
+        let source = &text[range.clone()];
+        if let Some(start) = source.rfind(&*dest_url) {
+            let start = range.start + start;
+            destinations.push((dest_url.to_string(), start..start + dest_url.len()));
+        }
+    }
+    destinations
+}
+
+fn strip_front_matter(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return text;
+    };
+    match rest.find("\n---\n") {
+        Some(end) => &rest[end + "\n---\n".len()..],
+        None => text,
+    }
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The path from the directory `from` to `to`, both relative to the same
+/// root.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = from.components().collect::<Vec<_>>();
+    let to_components = to.components().collect::<Vec<_>>();
+    let common = from
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    for component in &to_components[common..] {
+        path.push(component);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn file(path: &str, text: &str) -> SubmissionFile {
+        SubmissionFile {
+            path: PathBuf::from(path),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_definition() {
+        let definition = SubmissionDefinition::parse(indoc! {r#"
+            output = "out/Response"
+            formats = ["pdf", "md"]
+            cover = "cover.pdf"
+            files = ["response/*.md", "response/02-approach.md"]
+            appendices = ["appendices/CVs.pdf", { path = "appendices/b.pdf", title = "Insurance" }]
+        "#})
+        .unwrap();
+        assert_eq!(
+            definition.formats,
+            [SubmissionFormat::Pdf, SubmissionFormat::Markdown]
+        );
+        assert_eq!(definition.numbering, HeadingNumbering::Decimal);
+        assert_eq!(definition.appendices[0].title(), "CVs");
+        assert_eq!(definition.appendices[1].title(), "Insurance");
+
+        let project_files = [
+            "response/02-approach.md",
+            "response/01-summary.md",
+            "response/notes.txt",
+            "submission.toml",
+        ]
+        .map(PathBuf::from);
+        assert_eq!(
+            definition.response_files(&project_files).unwrap(),
+            [
+                PathBuf::from("response/01-summary.md"),
+                PathBuf::from("response/02-approach.md")
+            ]
+        );
+
+        assert!(SubmissionDefinition::parse("files = []\nunknown = 1").is_err());
+    }
+
+    #[test]
+    fn test_assemble() {
+        let files = [
+            file(
+                "response/01-summary.md",
+                indoc! {"
+                    ---
+                    owner: Sam
+                    ---
+                    # 1. Executive Summary
+
+                    See [our approach](02-approach.md#staffing) and the [chart](images/chart.png).
+                "},
+            ),
+            file(
+                "response/02-approach.md",
+                indoc! {"
+                    # Approach
+
+                    ## Staffing
+
+                    Back to the [summary](01-summary.md) and [staffing](#staffing).
+                "},
+            ),
+        ];
+        let assembly = assemble(
+            &files,
+            HeadingNumbering::Decimal,
+            3,
+            Path::new("submission"),
+        );
+        assert_eq!(assembly.broken_references, []);
+        assert_eq!(
+            assembly.markdown,
+            indoc! {"
+                # 1 Executive Summary
+
+                See [our approach](#21-staffing) and the [chart](../response/images/chart.png).
+
+                # 2 Approach
+
+                ## 2.1 Staffing
+
+                Back to the [summary](#1-executive-summary) and [staffing](#21-staffing).
+            "}
+        );
+        assert_eq!(
+            assembly.contents_markdown(2),
+            indoc! {"
+                **Contents**
+
+                - [1 Executive Summary](#1-executive-summary)
+                - [2 Approach](#2-approach)
+                  - [2.1 Staffing](#21-staffing)
+            "}
+        );
+    }
+
+    #[test]
+    fn test_broken_references() {
+        let files = [file(
+            "response/01-summary.md",
+            indoc! {"
+                # Summary
+
+                See [pricing](12-pricing.md), [risks](#risks) and [staff](01-summary.md#staff).
+            "},
+        )];
+        let assembly = assemble(&files, HeadingNumbering::None, 3, Path::new(""));
+        let messages = assembly
+            .broken_references
+            .iter()
+            .map(BrokenReference::message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "response/01-summary.md:3: the file isn't part of the submission (12-pricing.md)",
+                "response/01-summary.md:3: there's no such heading in this file (#risks)",
+                "response/01-summary.md:3: there's no such heading in that file (01-summary.md#staff)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heading_source() {
+        assert_eq!(heading_source("## Pricing ##\n"), "Pricing");
+        assert_eq!(heading_source("# C#\n"), "C#");
+        assert_eq!(heading_source("Pricing\n=======\n"), "Pricing");
+        assert_eq!(strip_number("3.2 Pricing"), "Pricing");
+        assert_eq!(strip_number("3. Pricing"), "Pricing");
+        assert_eq!(strip_number("2026 plan"), "2026 plan");
+        assert_eq!(strip_number("Pricing 2026"), "Pricing 2026");
+        assert_eq!(slug("2.1 Staffing & Rates"), "21-staffing--rates");
+    }
+}
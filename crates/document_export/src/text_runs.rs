@@ -0,0 +1,82 @@
+use gpui::FontWeight;
+use markdown_preview::markdown_elements::{Link, MarkdownHighlight, ParsedMarkdownText};
+
+/// A run of text with the same formatting and link.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Run<'a> {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub strikethrough: bool,
+    pub underline: bool,
+    pub code: bool,
+    pub url: Option<&'a str>,
+}
+
+/// Splits text into runs wherever its formatting or links change, leaving
+/// out citations along with the space before them.
+pub(crate) fn text_runs(text: &ParsedMarkdownText) -> Vec<Run<'_>> {
+    let mut boundaries = vec![0, text.contents.len()];
+    for range in text
+        .highlights
+        .iter()
+        .map(|(range, _)| range)
+        .chain(&text.region_ranges)
+    {
+        boundaries.extend([range.start, range.end]);
+    }
+    boundaries.retain(|&offset| offset <= text.contents.len());
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut runs: Vec<Run> = Vec::new();
+    for window in boundaries.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let Some(segment) = text.contents.get(start..end) else {
+            continue;
+        };
+        let region = text
+            .region_ranges
+            .iter()
+            .zip(&text.regions)
+            .find(|(range, _)| range.start <= start && end <= range.end)
+            .map(|(_, region)| region);
+        let mut url = None;
+        let mut code = false;
+        if let Some(region) = region {
+            code = region.code;
+            match &region.link {
+                Some(Link::Citation(_)) => {
+                    if let Some(run) = runs.last_mut() {
+                        run.text.truncate(run.text.trim_end().len());
+                    }
+                    continue;
+                }
+                Some(Link::Web { url: link }) => url = Some(link.as_str()),
+                Some(Link::Path { .. } | Link::Footnote { .. }) | None => {}
+            }
+        }
+
+        let mut run = Run {
+            text: segment.to_string(),
+            bold: false,
+            italic: false,
+            strikethrough: false,
+            underline: false,
+            code,
+            url,
+        };
+        for (range, highlight) in &text.highlights {
+            if let MarkdownHighlight::Style(style) = highlight {
+                if range.start <= start && end <= range.end {
+                    run.bold |= style.weight != FontWeight::default();
+                    run.italic |= style.italic;
+                    run.strikethrough |= style.strikethrough;
+                    run.underline |= style.underline;
+                }
+            }
+        }
+        runs.push(run);
+    }
+    runs
+}
@@ -0,0 +1,269 @@
+use anyhow::{Context as _, Result, anyhow};
+use gpui::{App, AppContext as _, Context, Entity, SharedString, Task};
+use language::{Buffer, Capability, Language, LineEnding, Rope, TextBuffer};
+use project::{Project, ProjectEntryId, ProjectPath};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The formats that are converted to Markdown to be viewed, rather than
+/// opened as text.
+pub const CONVERTED_EXTENSIONS: &[&str] = semantic_index::DOCUMENT_EXTENSIONS;
+
+pub fn is_converted_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| CONVERTED_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// A heading in a converted document, or a page if it has no headings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineEntry {
+    pub title: SharedString,
+    pub level: u8,
+    pub row: u32,
+    pub page: Option<u32>,
+}
+
+/// A binary document in the project, converted to Markdown.
+pub struct ConvertedDocument {
+    abs_path: PathBuf,
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    buffer: Entity<Buffer>,
+    /// The row each page starts on, for formats converted with page breaks.
+    page_rows: Vec<u32>,
+}
+
+impl project::ProjectItem for ConvertedDocument {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        if !is_converted_document(&path.path) {
+            return None;
+        }
+
+        if !project.read(cx).is_local() {
+            return Some(Task::ready(Err(anyhow!(
+                "Documents can only be viewed in local projects"
+            ))));
+        }
+
+        let path = path.clone();
+        // The project index usually has the document converted already.
+        let load = semantic_index::load_document(project, &path, cx);
+        let project = project.read(cx);
+        let languages = project.languages().clone();
+        let abs_path = project.absolute_path(&path, cx);
+        let entry_id = project.entry_for_path(&path, cx).map(|entry| entry.id);
+        Some(cx.spawn(async move |cx| {
+            let abs_path = abs_path.ok_or_else(|| anyhow!("Failed to find the absolute path"))?;
+            let converted = load
+                .await
+                .with_context(|| format!("Failed to convert {}", path.path.display()))?;
+            let (text, page_rows) = split_pages(&converted);
+            let language = languages.language_for_name("Markdown").await.ok();
+            let buffer = cx.new(|cx| build_buffer(Rope::from(text), None, language, cx))?;
+            cx.new(|_| Self {
+                abs_path,
+                project_path: path,
+                entry_id,
+                buffer,
+                page_rows,
+            })
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
+
+impl ConvertedDocument {
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.project_path.path
+    }
+
+    pub fn buffer(&self) -> &Entity<Buffer> {
+        &self.buffer
+    }
+
+    pub fn is_pdf(&self) -> bool {
+        self.abs_path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+    }
+
+    /// The number of pages, or 0 if the conversion has no page breaks.
+    pub fn page_count(&self) -> u32 {
+        self.page_rows.len() as u32
+    }
+
+    /// The row the 1-based `page` starts on.
+    pub fn page_row(&self, page: u32) -> Option<u32> {
+        self.page_rows.get(page.checked_sub(1)? as usize).copied()
+    }
+
+    /// The 1-based page that `row` is on.
+    pub fn page_for_row(&self, row: u32) -> Option<u32> {
+        page_at_row(&self.page_rows, row)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn test(
+        project_path: ProjectPath,
+        abs_path: PathBuf,
+        converted: &str,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        let (text, page_rows) = split_pages(converted);
+        let buffer = cx.new(|cx| build_buffer(Rope::from(text), None, None, cx));
+        cx.new(|_| Self {
+            abs_path,
+            project_path,
+            entry_id: None,
+            buffer,
+            page_rows,
+        })
+    }
+
+    pub fn outline(&self, cx: &App) -> Vec<OutlineEntry> {
+        let snapshot = self.buffer.read(cx).snapshot();
+        let sections = prose::sections(&snapshot.text());
+        if sections.is_empty() {
+            return (1..=self.page_count())
+                .filter_map(|page| {
+                    Some(OutlineEntry {
+                        title: format!("Page {page}").into(),
+                        level: 1,
+                        row: self.page_row(page)?,
+                        page: Some(page),
+                    })
+                })
+                .collect();
+        }
+
+        sections
+            .into_iter()
+            .map(|section| {
+                let row = snapshot.offset_to_point(section.heading_range.start).row;
+                OutlineEntry {
+                    title: section.title.into(),
+                    level: section.level,
+                    row,
+                    page: self.page_for_row(row),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Builds a read-only buffer for converted text.
+pub(crate) fn build_buffer(
+    text: Rope,
+    file: Option<Arc<dyn language::File>>,
+    language: Option<Arc<Language>>,
+    cx: &mut Context<Buffer>,
+) -> Buffer {
+    let buffer = TextBuffer::new_normalized(
+        0,
+        cx.entity_id().as_non_zero_u64().into(),
+        LineEnding::Unix,
+        text,
+    );
+    let mut buffer = Buffer::build(buffer, file, Capability::ReadOnly);
+    buffer.set_language(language, cx);
+    buffer
+}
+
+/// The 1-based page that `row` is on, given the row each page starts on.
+pub(crate) fn page_at_row(page_rows: &[u32], row: u32) -> Option<u32> {
+    if page_rows.is_empty() {
+        return None;
+    }
+    Some(page_rows.partition_point(|start| *start <= row) as u32)
+}
+
+/// Removes the form feeds that separate the pages of a converted document,
+/// returning the text and the row each page starts on. Rows in the
+/// conversion, such as those cited, are the same rows in the text.
+pub(crate) fn split_pages(converted: &str) -> (String, Vec<u32>) {
+    if !converted.contains('\x0c') {
+        return (converted.to_string(), Vec::new());
+    }
+
+    let mut text = String::with_capacity(converted.len());
+    let mut page_rows = vec![0];
+    let mut row = 0;
+    // Pages with no text start where the next page with text does.
+    let mut pending_pages = 0;
+    for c in converted.chars() {
+        match c {
+            '\x0c' => pending_pages += 1,
+            '\n' => {
+                text.push(c);
+                row += 1;
+            }
+            _ => {
+                for _ in 0..pending_pages {
+                    page_rows.push(row);
+                }
+                pending_pages = 0;
+                text.push(c);
+            }
+        }
+    }
+    (text, page_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_split_pages() {
+        let (text, page_rows) = split_pages("Cover\n\x0cScope\nof works\n\x0c\x0cPricing\n\x0c");
+        assert_eq!(text, "Cover\nScope\nof works\nPricing\n");
+        assert_eq!(page_rows, [0, 1, 3, 3]);
+
+        let (text, page_rows) = split_pages("# Response\n");
+        assert_eq!(text, "# Response\n");
+        assert!(page_rows.is_empty());
+    }
+
+    #[test]
+    fn test_page_at_row() {
+        let page_rows = [0, 1, 3, 3];
+        assert_eq!(page_at_row(&page_rows, 0), Some(1));
+        assert_eq!(page_at_row(&page_rows, 2), Some(2));
+        // Blank pages share their row with the next page, which is the one shown.
+        assert_eq!(page_at_row(&page_rows, 3), Some(4));
+        assert_eq!(page_at_row(&page_rows, 10), Some(4));
+        assert_eq!(page_at_row(&[], 0), None);
+    }
+
+    #[test]
+    fn test_is_converted_document() {
+        assert!(is_converted_document(Path::new("tender/RFT.PDF")));
+        assert!(is_converted_document(Path::new("Pricing Schedule.xlsx")));
+        assert!(is_converted_document(Path::new("Pricing Schedule.xlsb")));
+        assert!(!is_converted_document(Path::new("response.md")));
+        assert!(!is_converted_document(Path::new("logo.png")));
+    }
+}
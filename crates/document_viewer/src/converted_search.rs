@@ -0,0 +1,239 @@
+use editor::{
+    ExcerptOpener,
+    display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle},
+};
+use gpui::{App, AppContext as _, Entity, Task};
+use language::{Anchor, Buffer, DiskState, Rope};
+use project::{Project, ProjectPath, WorktreeId, search::SearchQuery};
+use proto::ToProto as _;
+use semantic_index::SemanticDb;
+use std::{
+    any::Any,
+    ffi::OsStr,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::{Pane, Workspace};
+
+use crate::{
+    build_buffer, is_converted_document, open_document_viewer, page_at_row, render_page_marker,
+    split_pages,
+};
+
+/// The file of a buffer holding the converted text of a document, as
+/// searched by project search.
+struct ConvertedFile {
+    worktree_id: WorktreeId,
+    path: Arc<Path>,
+    full_path: PathBuf,
+    page_rows: Vec<u32>,
+}
+
+impl ConvertedFile {
+    fn from_buffer(buffer: &Buffer) -> Option<&Self> {
+        let file: &dyn language::File = buffer.file()?.as_ref();
+        let file: &dyn Any = file;
+        file.downcast_ref()
+    }
+}
+
+impl language::File for ConvertedFile {
+    fn as_local(&self) -> Option<&dyn language::LocalFile> {
+        None
+    }
+
+    fn disk_state(&self) -> DiskState {
+        DiskState::New
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &App) -> PathBuf {
+        self.full_path.clone()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a App) -> &'a OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+
+    fn worktree_id(&self, _: &App) -> WorktreeId {
+        self.worktree_id
+    }
+
+    fn to_proto(&self, _: &App) -> language::proto::File {
+        language::proto::File {
+            worktree_id: self.worktree_id.to_proto(),
+            entry_id: None,
+            path: self.path.as_ref().to_proto(),
+            mtime: None,
+            is_deleted: false,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
+
+/// The 1-based page of a converted document that `row` of a buffer returned
+/// by [`search_converted_documents`] is on.
+pub fn converted_page(buffer: &Buffer, row: u32) -> Option<u32> {
+    page_at_row(&ConvertedFile::from_buffer(buffer)?.page_rows, row)
+}
+
+/// A marker above `position` showing the page of a converted document that
+/// it's on.
+pub fn page_marker(position: editor::Anchor, page: u32) -> BlockProperties<editor::Anchor> {
+    BlockProperties {
+        placement: BlockPlacement::Above(position),
+        height: Some(1),
+        style: BlockStyle::Fixed,
+        render: Arc::new(move |cx: &mut BlockContext| render_page_marker(page, cx)),
+        priority: 0,
+        render_in_minimap: true,
+    }
+}
+
+/// Searches the text that the project's PDFs and Office documents were
+/// converted to when they were indexed, returning a read-only buffer for each
+/// document that matches, along with its matches.
+///
+/// Documents are only searched once the semantic index has converted them.
+pub fn search_converted_documents(
+    project: &Entity<Project>,
+    query: SearchQuery,
+    cx: &mut App,
+) -> Task<Vec<(Entity<Buffer>, Vec<Range<Anchor>>)>> {
+    if !cx.has_global::<SemanticDb>() {
+        return Task::ready(Vec::new());
+    }
+    let Some(project_index) = cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+        semantic_db.project_index(project.clone(), cx)
+    }) else {
+        return Task::ready(Vec::new());
+    };
+
+    let converted_texts = project_index.read(cx).converted_texts(cx);
+    let languages = project.read(cx).languages().clone();
+    cx.spawn(async move |cx| {
+        let Some(converted_texts) = converted_texts.await.log_err() else {
+            return Vec::new();
+        };
+        let language = languages.language_for_name("Markdown").await.ok();
+
+        let mut results = Vec::new();
+        for converted in converted_texts {
+            if !is_converted_document(&converted.path) {
+                continue;
+            }
+            let Some((worktree_id, full_path)) = converted
+                .worktree
+                .read_with(cx, |worktree, _| {
+                    let full_path = Path::new(worktree.root_name()).join(&converted.path);
+                    (worktree.id(), full_path)
+                })
+                .log_err()
+            else {
+                continue;
+            };
+            let path_to_match = if query.match_full_paths() {
+                full_path.as_path()
+            } else {
+                converted.path.as_ref()
+            };
+            if !query.match_path(path_to_match) {
+                continue;
+            }
+
+            let (text, page_rows) = cx
+                .background_spawn(async move {
+                    let (text, page_rows) = split_pages(&converted.text);
+                    (Rope::from(text), page_rows)
+                })
+                .await;
+            let file = Arc::new(ConvertedFile {
+                worktree_id,
+                path: converted.path,
+                full_path,
+                page_rows,
+            });
+            let Some(buffer) = cx
+                .new(|cx| build_buffer(text, Some(file), language.clone(), cx))
+                .log_err()
+            else {
+                continue;
+            };
+            let Some(snapshot) = buffer
+                .read_with(cx, |buffer, _| buffer.snapshot())
+                .log_err()
+            else {
+                continue;
+            };
+
+            let query = query.clone();
+            let ranges = cx
+                .background_spawn(async move {
+                    query
+                        .search(&snapshot, None)
+                        .await
+                        .into_iter()
+                        .map(|range| {
+                            snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            if !ranges.is_empty() {
+                results.push((buffer, ranges));
+            }
+        }
+        results
+    })
+}
+
+/// Opens the excerpts of converted documents in project search results in
+/// the document's viewer.
+pub(crate) struct ConvertedExcerptOpener;
+
+impl ExcerptOpener for ConvertedExcerptOpener {
+    fn can_open(&self, buffer: &Buffer, _: &App) -> bool {
+        ConvertedFile::from_buffer(buffer).is_some()
+    }
+
+    fn open(
+        &self,
+        buffer: Entity<Buffer>,
+        ranges: Vec<Range<usize>>,
+        pane: Entity<Pane>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let buffer = buffer.read(cx);
+        let Some(file) = ConvertedFile::from_buffer(buffer) else {
+            return;
+        };
+        let project_path = ProjectPath {
+            worktree_id: file.worktree_id,
+            path: file.path.clone(),
+        };
+        let row = ranges
+            .first()
+            .map_or(0, |range| buffer.offset_to_point(range.start).row);
+
+        let open_task = open_document_viewer(workspace, project_path, Some(pane), window, cx);
+        cx.spawn_in(window, async move |_, cx| {
+            let viewer = open_task.await?;
+            viewer
+                .downgrade()
+                .update_in(cx, |viewer, window, cx| viewer.go_to_row(row, window, cx))?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
@@ -0,0 +1,642 @@
+//! Views PDFs and Office documents in the project as the Markdown they convert
+//! to, so that they can be read, searched and cited like any other text.
+//!
+//! The converted text is read-only, with a marker where each page starts and
+//! an outline of its headings. PDFs can also be shown as images of their
+//! pages, rendered with Poppler's `pdftoppm`.
+
+mod converted_document;
+mod converted_search;
+
+use anyhow::{Context as _, Result, anyhow};
+use editor::{Editor, EditorEvent, display_map::BlockContext, scroll::Autoscroll};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, AnyView, App, Entity, EventEmitter, FocusHandle, Focusable, Image, ImageFormat,
+    Task, actions,
+};
+use language::Point;
+use project::{Project, ProjectItem as _, ProjectPath};
+use settings::Settings as _;
+use std::{any::TypeId, path::PathBuf, sync::Arc};
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::paths::PathExt as _;
+use workspace::{
+    Item, ItemNavHistory, ItemSettings, Pane, Workspace, item::ProjectItem,
+    searchable::SearchableItemHandle,
+};
+
+pub use converted_document::*;
+pub use converted_search::*;
+
+/// The resolution pages of a PDF are rendered at, in dots per inch.
+const PAGE_RESOLUTION: &str = "110";
+
+actions!(document_viewer, [TogglePageImages, NextPage, PreviousPage]);
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<DocumentViewer>(cx);
+    editor::set_excerpt_opener(ConvertedExcerptOpener, cx);
+}
+
+/// Opens a document in a viewer, even when another item opens it by default,
+/// as the grid does for spreadsheets. Citations and search results point into
+/// the converted text, which only the viewer shows.
+pub fn open_document_viewer(
+    workspace: &mut Workspace,
+    path: ProjectPath,
+    pane: Option<Entity<Pane>>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<Entity<DocumentViewer>>> {
+    let open_viewer = workspace
+        .items_of_type::<DocumentViewer>(cx)
+        .find(|viewer| viewer.read(cx).document.read(cx).project_path(cx) == Some(path.clone()));
+    if let Some(viewer) = open_viewer {
+        workspace.activate_item(&viewer, true, true, window, cx);
+        return Task::ready(Ok(viewer));
+    }
+
+    let Some(open_document) = ConvertedDocument::try_open(workspace.project(), &path, cx) else {
+        return Task::ready(Err(anyhow!(
+            "{} can't be viewed as a document",
+            path.path.display()
+        )));
+    };
+    let pane = pane.unwrap_or_else(|| workspace.active_pane().clone());
+    cx.spawn_in(window, async move |workspace, cx| {
+        let document = open_document.await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.open_project_item::<DocumentViewer>(pane, document, true, true, window, cx)
+        })
+    })
+}
+
+/// Shows a converted document.
+pub struct DocumentViewer {
+    document: Entity<ConvertedDocument>,
+    editor: Entity<Editor>,
+    outline: Vec<OutlineEntry>,
+    page_view: Option<PageView>,
+    focus_handle: FocusHandle,
+}
+
+/// An image of one page of a PDF.
+struct PageView {
+    page: u32,
+    image: PageImage,
+    _render: Task<()>,
+}
+
+enum PageImage {
+    Rendering,
+    Rendered(Arc<Image>),
+    Failed(SharedString),
+}
+
+impl DocumentViewer {
+    pub fn new(
+        document: Entity<ConvertedDocument>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let buffer = document.read(cx).buffer().clone();
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer, Some(project), window, cx);
+            editor.set_read_only(true);
+            editor
+        });
+        cx.on_release_in(window, |this, window, cx| {
+            if let Some(PageImage::Rendered(image)) = this.page_view.take().map(|view| view.image) {
+                image_viewer::release_image(image, window, cx);
+            }
+        })
+        .detach();
+
+        let this = Self {
+            outline: document.read(cx).outline(cx),
+            document,
+            editor,
+            page_view: None,
+            focus_handle: cx.focus_handle(),
+        };
+        this.insert_page_markers(cx);
+        this
+    }
+
+    pub fn document(&self) -> &Entity<ConvertedDocument> {
+        &self.document
+    }
+
+    pub fn editor(&self) -> &Entity<Editor> {
+        &self.editor
+    }
+
+    fn insert_page_markers(&self, cx: &mut App) {
+        let document = self.document.read(cx);
+        let page_rows = (1..=document.page_count())
+            .filter_map(|page| Some((page, document.page_row(page)?)))
+            .collect::<Vec<_>>();
+        self.editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let blocks = page_rows
+                .into_iter()
+                .map(|(page, row)| page_marker(snapshot.anchor_before(Point::new(row, 0)), page));
+            editor.insert_blocks(blocks, None, cx);
+        });
+    }
+
+    /// Shows the given 0-based row of the converted text, or the page it's on
+    /// when page images are shown.
+    pub fn go_to_row(&mut self, row: u32, window: &mut Window, cx: &mut Context<Self>) {
+        if self.page_view.is_some() {
+            if let Some(page) = self.document.read(cx).page_for_row(row) {
+                self.show_page(page, window, cx);
+            }
+            return;
+        }
+        self.editor.update(cx, |editor, cx| {
+            let point = Point::new(row, 0);
+            editor.change_selections(Some(Autoscroll::top_relative(4)), window, cx, |s| {
+                s.select_ranges([point..point])
+            });
+        });
+        window.focus(&self.editor.focus_handle(cx));
+    }
+
+    /// Shows the start of the given 1-based page.
+    pub fn go_to_page(&mut self, page: u32, window: &mut Window, cx: &mut Context<Self>) {
+        if self.page_view.is_some() {
+            self.show_page(page, window, cx);
+        } else if let Some(row) = self.document.read(cx).page_row(page) {
+            self.go_to_row(row, window, cx);
+        }
+    }
+
+    fn current_page(&self, cx: &mut App) -> Option<u32> {
+        if let Some(page_view) = &self.page_view {
+            return Some(page_view.page);
+        }
+        let row = self.editor.update(cx, |editor, cx| {
+            editor.selections.newest::<Point>(cx).head().row
+        });
+        self.document.read(cx).page_for_row(row)
+    }
+
+    fn toggle_page_images(
+        &mut self,
+        _: &TogglePageImages,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.document.read(cx).is_pdf() {
+            return;
+        }
+        if let Some(page_view) = self.page_view.take() {
+            if let PageImage::Rendered(image) = page_view.image {
+                image_viewer::release_image(image, window, cx);
+            }
+            self.go_to_page(page_view.page, window, cx);
+        } else {
+            let page = self.current_page(cx).unwrap_or(1);
+            self.show_page(page, window, cx);
+            window.focus(&self.focus_handle);
+        }
+        cx.notify();
+    }
+
+    fn next_page(&mut self, _: &NextPage, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(page) = self.page_view.as_ref().map(|view| view.page + 1) else {
+            return;
+        };
+        if page <= self.document.read(cx).page_count() {
+            self.show_page(page, window, cx);
+        }
+    }
+
+    fn previous_page(&mut self, _: &PreviousPage, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(page) = self.page_view.as_ref().map(|view| view.page) else {
+            return;
+        };
+        if page > 1 {
+            self.show_page(page - 1, window, cx);
+        }
+    }
+
+    fn show_page(&mut self, page: u32, window: &mut Window, cx: &mut Context<Self>) {
+        if self
+            .page_view
+            .as_ref()
+            .is_some_and(|view| view.page == page)
+        {
+            return;
+        }
+
+        let abs_path = self.document.read(cx).abs_path().to_path_buf();
+        let render = cx.spawn_in(window, async move |this, cx| {
+            let image = render_pdf_page(abs_path, page).await;
+            this.update(cx, |this, cx| {
+                if let Some(page_view) = this.page_view.as_mut().filter(|view| view.page == page) {
+                    page_view.image = match image {
+                        Ok(image) => PageImage::Rendered(image),
+                        Err(error) => PageImage::Failed(format!("{error:#}").into()),
+                    };
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+        let previous = self.page_view.replace(PageView {
+            page,
+            image: PageImage::Rendering,
+            _render: render,
+        });
+        if let Some(PageImage::Rendered(image)) = previous.map(|view| view.image) {
+            image_viewer::release_image(image, window, cx);
+        }
+        cx.notify();
+    }
+
+    fn render_outline(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let document = self.document.read(cx);
+        let heading = match document.page_count() {
+            0 => "Outline".to_string(),
+            1 => "1 page".to_string(),
+            count => format!("{count} pages"),
+        };
+        let is_pdf = document.is_pdf();
+        let current_page = self.page_view.as_ref().map(|view| view.page);
+        let min_level = self.outline.iter().map(|entry| entry.level).min();
+
+        v_flex()
+            .id("document-outline")
+            .flex_none()
+            .w(rems(18.))
+            .h_full()
+            .overflow_y_scroll()
+            .border_r_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().panel_background)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1p5()
+                    .justify_between()
+                    .child(
+                        Label::new(heading)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .when(is_pdf, |header| {
+                        header.child(
+                            IconButton::new("toggle-page-images", IconName::Image)
+                                .icon_size(IconSize::Small)
+                                .toggle_state(self.page_view.is_some())
+                                .tooltip(Tooltip::for_action_title(
+                                    "Show Page Images",
+                                    &TogglePageImages,
+                                ))
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.toggle_page_images(&TogglePageImages, window, cx)
+                                })),
+                        )
+                    }),
+            )
+            .children(self.outline.iter().enumerate().map(|(ix, entry)| {
+                let row = entry.row;
+                let indent_level = min_level.map_or(0, |min_level| entry.level - min_level);
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .indent_level(indent_level as usize)
+                    .toggle_state(current_page.is_some() && current_page == entry.page)
+                    .child(
+                        Label::new(entry.title.clone())
+                            .size(LabelSize::Small)
+                            .truncate(),
+                    )
+                    .end_slot::<Label>(entry.page.map(|page| {
+                        Label::new(page.to_string())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                    }))
+                    .on_click(
+                        cx.listener(move |this, _, window, cx| this.go_to_row(row, window, cx)),
+                    )
+            }))
+    }
+
+    fn render_page_view(&self, page_view: &PageView, cx: &mut Context<Self>) -> impl IntoElement {
+        let page_count = self.document.read(cx).page_count();
+        let page = page_view.page;
+        let content = match &page_view.image {
+            PageImage::Rendering => div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new("Rendering page…").color(Color::Muted))
+                .into_any_element(),
+            PageImage::Rendered(image) => {
+                image_viewer::image_canvas(image.clone(), cx).into_any_element()
+            }
+            PageImage::Failed(error) => div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(error.clone()).color(Color::Error))
+                .into_any_element(),
+        };
+
+        v_flex()
+            .size_full()
+            .track_focus(&self.focus_handle)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .justify_center()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        IconButton::new("previous-page", IconName::ChevronLeft)
+                            .icon_size(IconSize::Small)
+                            .disabled(page <= 1)
+                            .tooltip(Tooltip::for_action_title("Previous Page", &PreviousPage))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.previous_page(&PreviousPage, window, cx)
+                            })),
+                    )
+                    .child(
+                        Label::new(format!("Page {page} of {page_count}")).size(LabelSize::Small),
+                    )
+                    .child(
+                        IconButton::new("next-page", IconName::ChevronRight)
+                            .icon_size(IconSize::Small)
+                            .disabled(page >= page_count)
+                            .tooltip(Tooltip::for_action_title("Next Page", &NextPage))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.next_page(&NextPage, window, cx)
+                            })),
+                    ),
+            )
+            .child(div().flex_1().min_h_0().child(content))
+    }
+}
+
+fn render_page_marker(page: u32, cx: &mut BlockContext) -> AnyElement {
+    h_flex()
+        .id(cx.block_id)
+        .w_full()
+        .h(cx.line_height)
+        .pl(cx.margins.gutter.width)
+        .gap_2()
+        .child(
+            Label::new(format!("Page {page}"))
+                .size(LabelSize::XSmall)
+                .color(Color::Muted),
+        )
+        .child(div().flex_1().h_px().bg(cx.theme().colors().border_variant))
+        .into_any_element()
+}
+
+/// Renders a page of a PDF as a PNG image.
+async fn render_pdf_page(abs_path: PathBuf, page: u32) -> Result<Arc<Image>> {
+    let page = page.to_string();
+    let output = util::command::new_smol_command("pdftoppm")
+        .args(["-png", "-singlefile", "-r", PAGE_RESOLUTION])
+        .args(["-f", &page, "-l", &page])
+        .arg(&abs_path)
+        .output()
+        .await
+        .context("Failed to run pdftoppm. Page images need Poppler to be installed.")?;
+    anyhow::ensure!(
+        output.status.success(),
+        "pdftoppm failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(Arc::new(Image::from_bytes(ImageFormat::Png, output.stdout)))
+}
+
+impl Render for DocumentViewer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = match &self.page_view {
+            Some(page_view) => self.render_page_view(page_view, cx).into_any_element(),
+            None => self.editor.clone().into_any_element(),
+        };
+        h_flex()
+            .key_context("DocumentViewer")
+            .on_action(cx.listener(Self::toggle_page_images))
+            .on_action(cx.listener(Self::next_page))
+            .on_action(cx.listener(Self::previous_page))
+            .size_full()
+            .child(self.render_outline(cx))
+            .child(div().h_full().flex_1().child(content))
+    }
+}
+
+impl Focusable for DocumentViewer {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        if self.page_view.is_some() {
+            self.focus_handle.clone()
+        } else {
+            self.editor.focus_handle(cx)
+        }
+    }
+}
+
+impl EventEmitter<EditorEvent> for DocumentViewer {}
+
+impl Item for DocumentViewer {
+    type Event = EditorEvent;
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        let path = self.document.read(cx).path();
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .to_string()
+            .into()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.document.read(cx).path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.document.read(cx).abs_path();
+        Some(abs_path.compact().to_string_lossy().to_string().into())
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.document.entity_id(), self.document.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &App) -> bool {
+        true
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+}
+
+impl ProjectItem for DocumentViewer {
+    type Item = ConvertedDocument;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, window, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use std::path::Path;
+    use util::path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_open_document_viewer(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({ "notes.md": "# Notes\n", "pricing.xlsx": "" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/tender").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let path = |path: &str| ProjectPath {
+            worktree_id,
+            path: Path::new(path).into(),
+        };
+
+        // Converting takes MarkItDown, so open a conversion made up front.
+        let document = cx.update(|_, cx| {
+            ConvertedDocument::test(
+                path("pricing.xlsx"),
+                path!("/tender/pricing.xlsx").into(),
+                "## Pricing\n\n| Item | Rate |\n| --- | --- |\n| Labour | 95 |\n",
+                cx,
+            )
+        });
+        let viewer = workspace.update_in(cx, |workspace, window, cx| {
+            let pane = workspace.active_pane().clone();
+            workspace.open_project_item::<DocumentViewer>(pane, document, true, true, window, cx)
+        });
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path(path("notes.md"), None, true, window, cx)
+            })
+            .await
+            .unwrap();
+
+        // A search result in the workbook shows its row in the viewer, rather
+        // than in whichever item opens the workbook by default.
+        let opened = workspace
+            .update_in(cx, |workspace, window, cx| {
+                open_document_viewer(workspace, path("pricing.xlsx"), None, window, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(opened, viewer);
+        viewer.update_in(cx, |viewer, window, cx| viewer.go_to_row(4, window, cx));
+        workspace.update(cx, |workspace, cx| {
+            let active_item = workspace.active_item(cx).unwrap();
+            assert_eq!(active_item.item_id(), viewer.entity_id());
+        });
+        let row = viewer.update(cx, |viewer, cx| {
+            viewer.editor.update(cx, |editor, cx| {
+                editor.selections.newest::<Point>(cx).head().row
+            })
+        });
+        assert_eq!(row, 4);
+    }
+}
@@ -20,6 +20,7 @@ pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod focus_mode;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -343,6 +344,7 @@ pub fn init(cx: &mut App) {
     workspace::register_project_item::<Editor>(cx);
     workspace::FollowableViewRegistry::register::<Editor>(cx);
     workspace::register_serializable_item::<Editor>(cx);
+    focus_mode::init(cx);
 
     cx.observe_new(
         |workspace: &mut Workspace, _: Option<&mut Window>, _cx: &mut Context<Workspace>| {
@@ -429,6 +431,35 @@ pub fn set_diagnostic_renderer(renderer: impl DiagnosticRenderer + 'static, cx:
     cx.set_global(GlobalDiagnosticRenderer(Arc::new(renderer)));
 }
 
+/// Opens the excerpts of buffers that are shown by another kind of item, such
+/// as the converted text of a PDF, rather than by an editor of their own.
+pub trait ExcerptOpener {
+    fn can_open(&self, buffer: &Buffer, cx: &App) -> bool;
+
+    fn open(
+        &self,
+        buffer: Entity<Buffer>,
+        ranges: Vec<Range<usize>>,
+        pane: Entity<workspace::Pane>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    );
+}
+
+pub(crate) struct GlobalExcerptOpener(pub Arc<dyn ExcerptOpener>);
+
+impl GlobalExcerptOpener {
+    fn global(cx: &App) -> Option<Arc<dyn ExcerptOpener>> {
+        cx.try_global::<Self>().map(|g| g.0.clone())
+    }
+}
+
+impl gpui::Global for GlobalExcerptOpener {}
+pub fn set_excerpt_opener(opener: impl ExcerptOpener + 'static, cx: &mut App) {
+    cx.set_global(GlobalExcerptOpener(Arc::new(opener)));
+}
+
 pub struct SearchWithinRange;
 
 trait InvalidationRegion {
@@ -14338,8 +14369,10 @@ impl Editor {
 
         let (buffer, head) = multi_buffer.text_anchor_for_position(head, cx)?;
         let workspace = self.workspace()?;
-        let project = workspace.read(cx).project().clone();
-        let references = project.update(cx, |project, cx| project.references(&buffer, head, cx));
+        let references = self
+            .semantics_provider
+            .clone()?
+            .references(&buffer, head, cx)?;
         Some(cx.spawn_in(window, async move |editor, cx| {
             let _cleanup = cx.on_drop(&editor, move |editor, _| {
                 if let Ok(i) = editor
@@ -18398,8 +18431,13 @@ impl Editor {
             }
         }
 
-        new_selections_by_buffer
-            .retain(|buffer, _| Self::can_open_excerpts_in_file(buffer.read(cx).file()));
+        let excerpt_opener = GlobalExcerptOpener::global(cx);
+        new_selections_by_buffer.retain(|buffer, _| {
+            Self::can_open_excerpts_in_file(buffer.read(cx).file())
+                || excerpt_opener
+                    .as_ref()
+                    .is_some_and(|opener| opener.can_open(buffer.read(cx), cx))
+        });
 
         if new_selections_by_buffer.is_empty() {
             return;
@@ -18417,6 +18455,14 @@ impl Editor {
                 };
 
                 for (buffer, (ranges, scroll_offset)) in new_selections_by_buffer {
+                    if let Some(opener) = excerpt_opener
+                        .as_ref()
+                        .filter(|opener| opener.can_open(buffer.read(cx), cx))
+                    {
+                        opener.open(buffer, ranges, pane.clone(), workspace, window, cx);
+                        continue;
+                    }
+
                     let editor = buffer
                         .read(cx)
                         .file()
@@ -19588,6 +19634,13 @@ pub trait SemanticsProvider {
         cx: &mut App,
     ) -> Option<Task<Result<Vec<LocationLink>>>>;
 
+    fn references(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: text::Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<Location>>>>;
+
     fn range_for_rename(
         &self,
         buffer: &Entity<Buffer>,
@@ -20011,6 +20064,15 @@ impl SemanticsProvider for Entity<Project> {
         }))
     }
 
+    fn references(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: text::Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<Location>>>> {
+        Some(self.update(cx, |project, cx| project.references(buffer, position, cx)))
+    }
+
     fn supports_inlay_hints(&self, buffer: &Entity<Buffer>, cx: &mut App) -> bool {
         // TODO: make this work for remote projects
         self.update(cx, |project, cx| {
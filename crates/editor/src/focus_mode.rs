@@ -0,0 +1,218 @@
+//! Writing focus mode: everything but the paragraph or sentence under the
+//! cursor is dimmed, and the cursor line is kept vertically centred.
+//!
+//! Focus mode belongs to the workspace, which hides its chrome when toggled;
+//! every editor in the workspace's panes follows along.
+
+use crate::{Addon, Editor, EditorEvent, scroll::Autoscroll};
+use gpui::{App, Context, HighlightStyle, Subscription, Window};
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot};
+use settings::SettingsStore;
+use std::ops::Range;
+use text::Point;
+use unicode_segmentation::UnicodeSegmentation as _;
+use workspace::Workspace;
+use writing_app_settings::{FocusModeScope, writing_settings};
+
+const DIMMED_TEXT_FADE_OUT: f32 = 0.65;
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        cx.subscribe_in(
+            &cx.entity(),
+            window,
+            |workspace, _, event, window, cx| match event {
+                workspace::Event::FocusModeChanged => {
+                    let focus_mode = workspace.focus_mode();
+                    for editor in workspace.items_of_type::<Editor>(cx).collect::<Vec<_>>() {
+                        editor.update(cx, |editor, cx| {
+                            editor.set_focus_mode(focus_mode, window, cx)
+                        });
+                    }
+                }
+                workspace::Event::ItemAdded { item } => {
+                    if let Some(editor) = item.downcast::<Editor>() {
+                        let focus_mode = workspace.focus_mode();
+                        editor.update(cx, |editor, cx| {
+                            editor.set_focus_mode(focus_mode, window, cx)
+                        });
+                    }
+                }
+                _ => {}
+            },
+        )
+        .detach();
+    })
+    .detach();
+}
+
+struct FocusMode {
+    _subscriptions: [Subscription; 2],
+}
+
+impl Addon for FocusMode {
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Editor {
+    pub fn set_focus_mode(&mut self, enabled: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let enabled = enabled && self.mode.is_full() && self.buffer.read(cx).is_singleton();
+        if enabled == self.addon::<FocusMode>().is_some() {
+            return;
+        }
+
+        if enabled {
+            let subscriptions = [
+                cx.subscribe_in(&cx.entity(), window, |editor, _, event, _, cx| {
+                    if let EditorEvent::SelectionsChanged { local: true } = event {
+                        editor.refresh_focus_mode(cx);
+                        editor.request_autoscroll(Autoscroll::center(), cx);
+                    }
+                }),
+                cx.observe_global::<SettingsStore>(|editor, cx| editor.refresh_focus_mode(cx)),
+            ];
+            self.register_addon(FocusMode {
+                _subscriptions: subscriptions,
+            });
+            self.refresh_focus_mode(cx);
+            self.request_autoscroll(Autoscroll::center(), cx);
+        } else {
+            self.unregister_addon::<FocusMode>();
+            self.clear_highlights::<FocusMode>(cx);
+        }
+    }
+
+    pub fn is_in_focus_mode(&self) -> bool {
+        self.addon::<FocusMode>().is_some()
+    }
+
+    fn refresh_focus_mode(&mut self, cx: &mut Context<Self>) {
+        let buffer = self.buffer.read(cx).as_singleton();
+        let file = buffer.as_ref().and_then(|buffer| buffer.read(cx).file());
+        let scope = writing_settings(file, cx).focus_mode_scope;
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let cursor = self.selections.newest::<Point>(cx).head();
+        let focused = focused_range(&snapshot, cursor, scope);
+
+        let dimmed = [0..focused.start, focused.end..snapshot.len()]
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .map(|range| snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end))
+            .collect();
+        self.highlight_text::<FocusMode>(
+            dimmed,
+            HighlightStyle {
+                fade_out: Some(DIMMED_TEXT_FADE_OUT),
+                ..Default::default()
+            },
+            cx,
+        );
+    }
+}
+
+/// Returns the offset range that stays undimmed for a cursor position. A
+/// paragraph is a run of non-blank lines.
+fn focused_range(
+    snapshot: &MultiBufferSnapshot,
+    cursor: Point,
+    scope: FocusModeScope,
+) -> Range<usize> {
+    let mut start_row = cursor.row;
+    while start_row > 0 && !snapshot.is_line_blank(MultiBufferRow(start_row - 1)) {
+        start_row -= 1;
+    }
+    let mut end_row = cursor.row;
+    while end_row < snapshot.max_point().row && !snapshot.is_line_blank(MultiBufferRow(end_row + 1))
+    {
+        end_row += 1;
+    }
+    let end = Point::new(end_row, snapshot.line_len(MultiBufferRow(end_row)));
+    let paragraph =
+        snapshot.point_to_offset(Point::new(start_row, 0))..snapshot.point_to_offset(end);
+
+    match scope {
+        FocusModeScope::Paragraph => paragraph,
+        FocusModeScope::Sentence => {
+            let cursor = snapshot.point_to_offset(cursor);
+            let text = snapshot
+                .text_for_range(paragraph.clone())
+                .collect::<String>();
+            text.split_sentence_bound_indices()
+                .map(|(offset, sentence)| {
+                    paragraph.start + offset..paragraph.start + offset + sentence.len()
+                })
+                .find(|sentence| sentence.end > cursor || sentence.end == paragraph.end)
+                .unwrap_or_else(|| paragraph.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_test_context::EditorTestContext};
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use writing_app_settings::WritingAppSettings;
+
+    #[gpui::test]
+    async fn test_focus_mode_dims_other_paragraphs(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state(indoc! {"
+            The first paragraph.
+
+            The second ˇparagraph,
+            over two lines.
+
+            The third paragraph."
+        });
+        cx.update_editor(|editor, window, cx| editor.set_focus_mode(true, window, cx));
+        cx.assert_editor_text_highlights::<FocusMode>(indoc! {"
+            «The first paragraph.
+
+            »The second paragraph,
+            over two lines.«
+
+            The third paragraph.»"
+        });
+
+        cx.update_editor(|editor, window, cx| editor.set_focus_mode(false, window, cx));
+        cx.assert_editor_text_highlights::<FocusMode>(indoc! {"
+            The first paragraph.
+
+            The second paragraph,
+            over two lines.
+
+            The third paragraph."
+        });
+    }
+
+    #[gpui::test]
+    async fn test_focus_mode_sentence_scope(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<WritingAppSettings>(cx, |settings| {
+                    settings.focus_mode_scope = Some(FocusModeScope::Sentence);
+                });
+            });
+        });
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("We comply. We ˇexceed the standard. We deliver.");
+        cx.update_editor(|editor, window, cx| editor.set_focus_mode(true, window, cx));
+        cx.assert_editor_text_highlights::<FocusMode>(
+            "«We comply. »We exceed the standard. «We deliver.»",
+        );
+
+        cx.set_selections_state("We comply. We exceed the standard. We ˇdeliver.");
+        cx.assert_editor_text_highlights::<FocusMode>(
+            "«We comply. We exceed the standard. »We deliver.",
+        );
+    }
+}
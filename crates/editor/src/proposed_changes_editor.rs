@@ -504,6 +504,16 @@ impl SemanticsProvider for BranchBufferSemanticsProvider {
         self.0.definitions(&buffer, position, kind, cx)
     }
 
+    fn references(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: text::Anchor,
+        cx: &mut App,
+    ) -> Option<Task<gpui::Result<Vec<project::Location>>>> {
+        let buffer = self.to_base(&buffer, &[position], cx)?;
+        self.0.references(&buffer, position, cx)
+    }
+
     fn range_for_rename(
         &self,
         _: &Entity<Buffer>,
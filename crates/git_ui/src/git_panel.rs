@@ -9,7 +9,7 @@ use crate::{branch_picker, picker_prompt, render_remote_button};
 use crate::{
     git_panel_settings::GitPanelSettings, git_status_icon, repository_selector::RepositorySelector,
 };
-use writing_app_settings::{WritingAppSettings, get_global};
+use writing_app_settings::WritingAppSettings;
 use anyhow::Result;
 use askpass::AskPassDelegate;
 use assistant_settings::AssistantSettings;
@@ -166,7 +166,7 @@ const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
 pub fn register(workspace: &mut Workspace) {
     workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
         // Only toggle if git panel is enabled in writing app settings
-        if get_global(cx).show_git_panel {
+        if WritingAppSettings::get_global(cx).show_git_panel {
             workspace.toggle_panel_focus::<GitPanel>(window, cx);
         }
     });
@@ -405,7 +405,7 @@ impl GitPanel {
         let fs = app_state.fs.clone();
         let git_store = project.read(cx).git_store().clone();
         let active_repository = project.read(cx).active_repository(cx);
-        let is_visible = get_global(cx).show_git_panel;
+        let is_visible = WritingAppSettings::get_global(cx).show_git_panel;
         let workspace = workspace.downgrade();
 
         let focus_handle = cx.focus_handle();
@@ -424,7 +424,7 @@ impl GitPanel {
             was_sort_by_path = is_sort_by_path;
 
             // Update visibility based on writing app settings
-            let new_is_visible = get_global(cx).show_git_panel;
+            let new_is_visible = WritingAppSettings::get_global(cx).show_git_panel;
             if this.is_visible != new_is_visible {
                 this.is_visible = new_is_visible;
                 cx.notify();
@@ -0,0 +1,817 @@
+//! Grammar and style checking for Markdown buffers, backed by a
+//! LanguageTool-compatible server such as a local `languagetool-server`.
+//!
+//! Each prose block is checked on its own and the results are cached by the
+//! block's text, so an edit only re-sends the paragraphs it touched. Problems
+//! are published as buffer diagnostics, with code actions to apply the
+//! suggested replacements.
+
+mod language_tool;
+
+use anyhow::{Result, anyhow};
+use collections::{HashMap, HashSet};
+use editor::{CodeActionProvider, Editor, EditorEvent, ExcerptId};
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity, Window,
+};
+use http_client::HttpClient;
+use language::{AnchorRangeExt as _, Buffer, BufferEvent, Diagnostic, DiagnosticSeverity};
+use lsp::NumberOrString;
+use project::{CodeAction, LspAction, ProjectTransaction};
+use prose::{DiagnosticSource, ProseBlock};
+use serde::{Deserialize, Serialize};
+use settings::SettingsStore;
+use std::{ops::Range, rc::Rc, sync::Arc, time::Duration};
+use writing_app_settings::writing_settings;
+
+use language_tool::AnnotationItem;
+
+pub const DEFAULT_SERVER_URL: &str = "http://localhost:8081";
+
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(750);
+const MAX_REPLACEMENTS: usize = 5;
+/// What inline code and URLs are read as, so that the sentence around them
+/// still parses.
+const PLACEHOLDER: &str = "X";
+const GRAMMAR_CHECK_CODE_ACTION_PROVIDER_ID: &str = "grammar_check";
+
+struct GlobalGrammarChecker(Entity<GrammarChecker>);
+
+impl Global for GlobalGrammarChecker {}
+
+pub fn init(http_client: Arc<dyn HttpClient>, cx: &mut App) {
+    let grammar_checker = cx.new(|cx| GrammarChecker::new(http_client, cx));
+    cx.set_global(GlobalGrammarChecker(grammar_checker));
+
+    cx.observe_new(|editor: &mut Editor, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        register_editor(editor, window, cx);
+    })
+    .detach();
+}
+
+fn register_editor(editor: &mut Editor, window: &mut Window, cx: &mut Context<Editor>) {
+    if !editor.mode().is_full() || editor.project.is_none() {
+        return;
+    }
+
+    let grammar_checker = cx.global::<GlobalGrammarChecker>().0.clone();
+    editor.add_code_action_provider(
+        Rc::new(GrammarCodeActionProvider {
+            grammar_checker: grammar_checker.clone(),
+        }),
+        window,
+        cx,
+    );
+
+    let buffers = editor.buffer().read(cx).all_buffers();
+    for buffer in buffers {
+        grammar_checker.update(cx, |grammar_checker, cx| {
+            grammar_checker.watch_buffer(buffer, cx)
+        });
+    }
+    cx.subscribe(&cx.entity(), move |_, _, event, cx| {
+        if let EditorEvent::ExcerptsAdded { buffer, .. } = event {
+            grammar_checker.update(cx, |grammar_checker, cx| {
+                grammar_checker.watch_buffer(buffer.clone(), cx)
+            });
+        }
+    })
+    .detach();
+}
+
+/// Checks the grammar and style of every Markdown buffer open in an editor.
+pub struct GrammarChecker {
+    http_client: Arc<dyn HttpClient>,
+    disabled_rules: Arc<HashSet<String>>,
+    buffers: HashMap<EntityId, WatchedBuffer>,
+    last_error: Option<String>,
+    _settings_subscription: Subscription,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CheckConfig {
+    server_url: Arc<str>,
+    language: &'static str,
+    /// Misspellings are left to the spell checker when it's enabled.
+    skip_misspellings: bool,
+}
+
+struct WatchedBuffer {
+    buffer: WeakEntity<Buffer>,
+    /// The settings the buffer was last checked with.
+    config: Option<CheckConfig>,
+    /// The issues found in each block, keyed by the block's source text.
+    cache: Arc<HashMap<String, Arc<[Issue]>>>,
+    check: Task<()>,
+    _subscriptions: [Subscription; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Issue {
+    range: Range<usize>,
+    message: String,
+    rule_id: String,
+    issue_type: String,
+    replacements: Vec<String>,
+}
+
+#[derive(Default)]
+struct TextCheck {
+    cache: HashMap<String, Arc<[Issue]>>,
+    issues: Vec<Issue>,
+    error: Option<anyhow::Error>,
+}
+
+impl GrammarChecker {
+    fn new(http_client: Arc<dyn HttpClient>, cx: &mut Context<Self>) -> Self {
+        let settings_subscription =
+            cx.observe_global::<SettingsStore>(|this, cx| this.settings_changed(cx));
+        Self {
+            http_client,
+            disabled_rules: Arc::default(),
+            buffers: HashMap::default(),
+            last_error: None,
+            _settings_subscription: settings_subscription,
+        }
+    }
+
+    fn watch_buffer(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.entity_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+
+        let subscriptions = [
+            cx.subscribe(&buffer, |this, buffer, event, cx| match event {
+                BufferEvent::Edited
+                | BufferEvent::Reloaded
+                | BufferEvent::LanguageChanged
+                | BufferEvent::FileHandleChanged => {
+                    this.check_buffer(&buffer, Some(CHECK_DEBOUNCE), cx)
+                }
+                _ => {}
+            }),
+            cx.observe_release(&buffer, move |this, _, _| {
+                this.buffers.remove(&buffer_id);
+            }),
+        ];
+        self.buffers.insert(
+            buffer_id,
+            WatchedBuffer {
+                buffer: buffer.downgrade(),
+                config: None,
+                cache: Arc::default(),
+                check: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.check_buffer(&buffer, None, cx);
+    }
+
+    fn check_all_buffers(&mut self, cx: &mut Context<Self>) {
+        let buffers = self
+            .buffers
+            .values()
+            .filter_map(|watched| watched.buffer.upgrade())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.check_buffer(&buffer, None, cx);
+        }
+    }
+
+    /// Checks the buffers whose grammar settings have changed.
+    fn settings_changed(&mut self, cx: &mut Context<Self>) {
+        let buffers = self
+            .buffers
+            .values()
+            .filter_map(|watched| {
+                let buffer = watched.buffer.upgrade()?;
+                (check_config(&buffer, cx) != watched.config).then_some(buffer)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.check_buffer(&buffer, None, cx);
+        }
+    }
+
+    fn check_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        debounce: Option<Duration>,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer_id = buffer.entity_id();
+        let config = check_config(buffer, cx);
+        let Some(watched) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        if config != watched.config {
+            if config.is_some() {
+                self.last_error = None;
+            }
+            watched.config = config.clone();
+            watched.cache = Arc::default();
+        }
+        let Some(config) = config else {
+            watched.check = Task::ready(());
+            watched.cache = Arc::default();
+            prose::clear_diagnostics(buffer, DiagnosticSource::Grammar, cx);
+            return;
+        };
+
+        let cache = watched.cache.clone();
+        let http_client = self.http_client.clone();
+        let snapshot = buffer.read(cx).snapshot();
+        let weak_buffer = buffer.downgrade();
+        watched.check = cx.spawn(async move |this, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+            let check = cx
+                .background_spawn({
+                    let snapshot = snapshot.clone();
+                    let config = config.clone();
+                    async move {
+                        let text = snapshot.text();
+                        check_text(http_client.as_ref(), &config, &text, &cache).await
+                    }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.report_error(check.error);
+                if let Some(watched) = this.buffers.get_mut(&buffer_id) {
+                    watched.cache = Arc::new(check.cache);
+                }
+                if let Some(buffer) = weak_buffer.upgrade() {
+                    let diagnostics = diagnostics(check.issues, &config, &this.disabled_rules);
+                    prose::publish_diagnostics(
+                        &buffer,
+                        DiagnosticSource::Grammar,
+                        &snapshot,
+                        diagnostics,
+                        cx,
+                    );
+                }
+            })
+            .ok();
+        });
+    }
+
+    /// Logs a failed check, once per distinct error, as an unreachable server
+    /// would otherwise be reported after every pause in typing.
+    fn report_error(&mut self, error: Option<anyhow::Error>) {
+        let message = error.map(|error| format!("{error:#}"));
+        if let Some(message) = &message {
+            if self.last_error.as_ref() != Some(message) {
+                log::warn!("grammar check failed: {message}");
+            }
+        }
+        self.last_error = message;
+    }
+
+    fn disable_rule(&mut self, rule_id: String, cx: &mut Context<Self>) {
+        Arc::make_mut(&mut self.disabled_rules).insert(rule_id);
+        self.check_all_buffers(cx);
+    }
+}
+
+/// The settings to check `buffer` with, including those of its project, or
+/// `None` if grammar checking is off for it.
+fn check_config(buffer: &Entity<Buffer>, cx: &App) -> Option<CheckConfig> {
+    let buffer = buffer.read(cx);
+    let settings = writing_settings(buffer.file(), cx);
+    (settings.grammar_check_enabled && prose::is_markdown(buffer)).then(|| CheckConfig {
+        server_url: settings
+            .grammar_check_url
+            .as_deref()
+            .unwrap_or(DEFAULT_SERVER_URL)
+            .trim_end_matches('/')
+            .into(),
+        language: settings.spelling_dictionary.language_code(),
+        skip_misspellings: settings.spelling_check_enabled,
+    })
+}
+
+/// Checks every prose block of `text`, reusing the issues in `cache` for
+/// blocks whose text hasn't changed. After the first failed request, the
+/// remaining uncached blocks are skipped.
+async fn check_text(
+    http_client: &dyn HttpClient,
+    config: &CheckConfig,
+    text: &str,
+    cache: &HashMap<String, Arc<[Issue]>>,
+) -> TextCheck {
+    let mut check = TextCheck::default();
+    for block in prose::prose_blocks(text) {
+        if block.prose_ranges.is_empty() {
+            continue;
+        }
+
+        let source = &text[block.range.clone()];
+        let cached = check
+            .cache
+            .get(source)
+            .or_else(|| cache.get(source))
+            .cloned();
+        let block_issues = match cached {
+            Some(block_issues) => block_issues,
+            None if check.error.is_some() => continue,
+            None => {
+                let annotation = annotation(text, &block);
+                match language_tool::check(
+                    http_client,
+                    &config.server_url,
+                    config.language,
+                    &annotation,
+                )
+                .await
+                {
+                    Ok(matches) => matches
+                        .into_iter()
+                        .filter_map(|issue| issue_for_match(source, issue))
+                        .collect(),
+                    Err(error) => {
+                        check.error = Some(error);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        check.issues.extend(block_issues.iter().map(|issue| Issue {
+            range: block.range.start + issue.range.start..block.range.start + issue.range.end,
+            ..issue.clone()
+        }));
+        check.cache.insert(source.to_string(), block_issues);
+    }
+    check
+}
+
+/// Splits a block into the prose to check and the markup around it.
+fn annotation<'a>(text: &'a str, block: &ProseBlock) -> Vec<AnnotationItem<'a>> {
+    let mut segments = block
+        .prose_ranges
+        .iter()
+        .map(|range| (range.clone(), true))
+        .chain(
+            block
+                .placeholder_ranges
+                .iter()
+                .map(|range| (range.clone(), false)),
+        )
+        .collect::<Vec<_>>();
+    segments.sort_by_key(|(range, _)| range.start);
+
+    let mut items = Vec::new();
+    let mut offset = block.range.start;
+    for (range, is_prose) in segments {
+        if range.start > offset {
+            items.push(markup(&text[offset..range.start]));
+        }
+        items.push(if is_prose {
+            AnnotationItem::Text {
+                text: &text[range.clone()],
+            }
+        } else {
+            AnnotationItem::Markup {
+                markup: &text[range.clone()],
+                interpret_as: Some(PLACEHOLDER),
+            }
+        });
+        offset = range.end;
+    }
+    if offset < block.range.end {
+        items.push(markup(&text[offset..block.range.end]));
+    }
+    items
+}
+
+fn markup(markup: &str) -> AnnotationItem<'_> {
+    // Line breaks inside a paragraph still separate words.
+    let interpret_as = markup.contains('\n').then_some(" ");
+    AnnotationItem::Markup {
+        markup,
+        interpret_as,
+    }
+}
+
+fn issue_for_match(source: &str, issue: language_tool::Match) -> Option<Issue> {
+    let start = byte_offset_for_utf16(source, issue.offset);
+    let end = byte_offset_for_utf16(source, issue.offset + issue.length);
+    if start >= end {
+        return None;
+    }
+    Some(Issue {
+        range: start..end,
+        message: issue.message,
+        rule_id: issue.rule.id,
+        issue_type: issue.rule.issue_type,
+        replacements: issue
+            .replacements
+            .into_iter()
+            .map(|replacement| replacement.value)
+            .filter(|replacement| !replacement.is_empty())
+            .take(MAX_REPLACEMENTS)
+            .collect(),
+    })
+}
+
+fn byte_offset_for_utf16(text: &str, utf16_offset: usize) -> usize {
+    let mut utf16 = 0;
+    for (ix, ch) in text.char_indices() {
+        if utf16 >= utf16_offset {
+            return ix;
+        }
+        utf16 += ch.len_utf16();
+    }
+    text.len()
+}
+
+fn diagnostics(
+    issues: Vec<Issue>,
+    config: &CheckConfig,
+    disabled_rules: &HashSet<String>,
+) -> Vec<(Range<usize>, Diagnostic)> {
+    issues
+        .into_iter()
+        .filter(|issue| {
+            !disabled_rules.contains(&issue.rule_id)
+                && !(config.skip_misspellings && issue.issue_type == "misspelling")
+        })
+        .map(|issue| {
+            let severity = match issue.issue_type.as_str() {
+                "grammar" => DiagnosticSeverity::WARNING,
+                _ => DiagnosticSeverity::INFORMATION,
+            };
+            let diagnostic = Diagnostic {
+                code: Some(NumberOrString::String(issue.rule_id)),
+                severity,
+                message: issue.message,
+                data: serde_json::to_value(issue.replacements).ok(),
+                ..Default::default()
+            };
+            (issue.range, diagnostic)
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum GrammarAction {
+    Replace { replacement: String },
+    DisableRule { rule_id: String },
+}
+
+struct GrammarCodeActionProvider {
+    grammar_checker: Entity<GrammarChecker>,
+}
+
+impl CodeActionProvider for GrammarCodeActionProvider {
+    fn id(&self) -> Arc<str> {
+        GRAMMAR_CHECK_CODE_ACTION_PROVIDER_ID.into()
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<text::Anchor>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let snapshot = buffer.read(cx).snapshot();
+        let range = range.to_offset(&snapshot);
+        let mut actions = Vec::new();
+        for entry in prose::diagnostics_in_range(&snapshot, DiagnosticSource::Grammar, range) {
+            let range =
+                snapshot.anchor_before(entry.range.start)..snapshot.anchor_after(entry.range.end);
+            let replacements = entry
+                .diagnostic
+                .data
+                .and_then(|data| serde_json::from_value::<Vec<String>>(data).ok())
+                .unwrap_or_default();
+            for replacement in replacements {
+                actions.push(code_action(
+                    range.clone(),
+                    format!("Change to \"{replacement}\""),
+                    GrammarAction::Replace { replacement },
+                ));
+            }
+            if let Some(NumberOrString::String(rule_id)) = entry.diagnostic.code {
+                actions.push(code_action(
+                    range,
+                    format!("Ignore \"{rule_id}\" for this session"),
+                    GrammarAction::DisableRule { rule_id },
+                ));
+            }
+        }
+        Task::ready(Ok(actions))
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Entity<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        _push_to_history: bool,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<ProjectTransaction>> {
+        let LspAction::Action(lsp_action) = action.lsp_action else {
+            return Task::ready(Ok(ProjectTransaction::default()));
+        };
+        let grammar_action = match lsp_action.data.map(serde_json::from_value::<GrammarAction>) {
+            Some(Ok(grammar_action)) => grammar_action,
+            _ => return Task::ready(Err(anyhow!("invalid grammar code action"))),
+        };
+
+        match grammar_action {
+            GrammarAction::Replace { replacement } => {
+                let transaction = buffer.update(cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    buffer.edit([(action.range, replacement)], None, cx);
+                    buffer.end_transaction(cx);
+                    buffer.finalize_last_transaction().cloned()
+                });
+                let mut project_transaction = ProjectTransaction::default();
+                if let Some(transaction) = transaction {
+                    project_transaction.0.insert(buffer, transaction);
+                }
+                Task::ready(Ok(project_transaction))
+            }
+            GrammarAction::DisableRule { rule_id } => {
+                self.grammar_checker.update(cx, |grammar_checker, cx| {
+                    grammar_checker.disable_rule(rule_id, cx)
+                });
+                Task::ready(Ok(ProjectTransaction::default()))
+            }
+        }
+    }
+}
+
+fn code_action(range: Range<text::Anchor>, title: String, action: GrammarAction) -> CodeAction {
+    CodeAction {
+        server_id: DiagnosticSource::Grammar.server_id(),
+        range,
+        lsp_action: LspAction::Action(Box::new(lsp::CodeAction {
+            title,
+            kind: Some(lsp::CodeActionKind::QUICKFIX),
+            data: serde_json::to_value(action).ok(),
+            ..Default::default()
+        })),
+        resolved: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use futures::AsyncReadExt as _;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, HttpClientWithUrl, Request, Response};
+    use indoc::indoc;
+    use language::{Language, LanguageConfig};
+    use parking_lot::Mutex;
+    use pretty_assertions::assert_eq;
+    use project::Project;
+    use serde_json::json;
+    use util::path;
+    use writing_app_settings::{SpellingDictionary, WritingAppSettings};
+
+    #[test]
+    fn test_annotation() {
+        let text = "Run `cargo` on\nthe **draft** at www.example.com today.";
+        let block = prose::prose_blocks(text).remove(0);
+        assert_eq!(
+            serde_json::to_value(annotation(text, &block)).unwrap(),
+            json!([
+                { "text": "Run " },
+                { "markup": "`cargo`", "interpretAs": "X" },
+                { "text": " on" },
+                { "markup": "\n" , "interpretAs": " " },
+                { "text": "the " },
+                { "markup": "**" },
+                { "text": "draft" },
+                { "markup": "**" },
+                { "text": " at " },
+                { "markup": "www.example.com", "interpretAs": "X" },
+                { "text": " today." },
+            ])
+        );
+    }
+
+    #[gpui::test]
+    async fn test_only_edited_paragraphs_are_rechecked(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = fake_server(requests.clone());
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            writing_app_settings::init(cx);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<WritingAppSettings>(cx, |settings| {
+                    settings.grammar_check_enabled = Some(true);
+                });
+            });
+        });
+        let grammar_checker = cx.new(|cx| GrammarChecker::new(http_client, cx));
+
+        let text = indoc! {"
+            # Pricing
+
+            The tenderer’s team offers a apple to each evaluator.
+
+            We recieve bids daily.
+        "};
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(markdown(), cx));
+        grammar_checker.update(cx, |grammar_checker, cx| {
+            grammar_checker.watch_buffer(buffer.clone(), cx)
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            std::mem::take(&mut *requests.lock()),
+            vec![
+                "Pricing",
+                "The tenderer’s team offers a apple to each evaluator.",
+                "We recieve bids daily.",
+            ]
+        );
+        // The misspelling is left to the spell checker.
+        assert_eq!(
+            grammar_diagnostics(&buffer, cx),
+            vec![(
+                "a apple".to_string(),
+                "Use \"an\" instead of \"a\".".to_string()
+            )]
+        );
+
+        buffer.update(cx, |buffer, cx| {
+            let offset = buffer.text().find("daily").unwrap();
+            buffer.edit([(offset..offset + "daily".len(), "weekly")], None, cx);
+        });
+        cx.executor().advance_clock(CHECK_DEBOUNCE);
+        cx.run_until_parked();
+
+        assert_eq!(
+            std::mem::take(&mut *requests.lock()),
+            vec!["We recieve bids weekly."]
+        );
+        assert_eq!(
+            grammar_diagnostics(&buffer, cx),
+            vec![(
+                "a apple".to_string(),
+                "Use \"an\" instead of \"a\".".to_string()
+            )]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_project_settings_apply(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = fake_server(requests.clone());
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            writing_app_settings::init(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                ".zed": {
+                    "settings.json": r#"{ "writing_app": { "grammar_check_enabled": true } }"#,
+                },
+                "response.md": "We recieve bids daily.\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/tender").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/tender/response.md"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.set_language(Some(markdown()), cx));
+        let other_buffer = cx
+            .new(|cx| Buffer::local("We recieve bids weekly.\n", cx).with_language(markdown(), cx));
+
+        let grammar_checker = cx.new(|cx| GrammarChecker::new(http_client, cx));
+        grammar_checker.update(cx, |grammar_checker, cx| {
+            grammar_checker.watch_buffer(buffer.clone(), cx);
+            grammar_checker.watch_buffer(other_buffer.clone(), cx);
+        });
+        cx.run_until_parked();
+
+        // Grammar checking is off by default, and only turned on for the
+        // project's buffers.
+        assert_eq!(
+            std::mem::take(&mut *requests.lock()),
+            vec!["We recieve bids daily."]
+        );
+
+        // A change to the settings a buffer is checked with checks it again,
+        // without touching the buffers it doesn't apply to.
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<WritingAppSettings>(cx, |settings| {
+                    settings.spelling_dictionary = Some(SpellingDictionary::EnUs);
+                });
+            });
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            std::mem::take(&mut *requests.lock()),
+            vec!["We recieve bids daily."]
+        );
+    }
+
+    fn fake_server(requests: Arc<Mutex<Vec<String>>>) -> Arc<HttpClientWithUrl> {
+        FakeHttpClient::create(move |request| {
+            let requests = requests.clone();
+            async move {
+                let text = annotated_text(request).await?;
+                let matches = stub_matches(&text);
+                requests.lock().push(text);
+                Ok(Response::builder()
+                    .status(200)
+                    .body(json!({ "matches": matches }).to_string().into())
+                    .unwrap())
+            }
+        })
+    }
+
+    fn markdown() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Markdown".into(),
+                ..Default::default()
+            },
+            None,
+        ))
+    }
+
+    async fn annotated_text(request: Request<http_client::AsyncBody>) -> Result<String> {
+        let mut body = String::new();
+        request.into_body().read_to_string(&mut body).await?;
+        let data = url::form_urlencoded::parse(body.as_bytes())
+            .find_map(|(key, value)| (key == "data").then(|| value.into_owned()))
+            .ok_or_else(|| anyhow!("missing data"))?;
+        let data: serde_json::Value = serde_json::from_str(&data)?;
+        Ok(data["annotation"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item["text"].as_str().or(item["markup"].as_str()))
+            .collect())
+    }
+
+    /// Mimics LanguageTool's responses for an article and a spelling rule.
+    fn stub_matches(text: &str) -> Vec<serde_json::Value> {
+        let utf16_offset = |ix: usize| text[..ix].encode_utf16().count();
+        let mut matches = Vec::new();
+        if let Some(ix) = text.find("a apple") {
+            matches.push(json!({
+                "message": "Use \"an\" instead of \"a\".",
+                "replacements": [{ "value": "an apple" }],
+                "offset": utf16_offset(ix),
+                "length": "a apple".len(),
+                "rule": { "id": "EN_A_VS_AN", "issueType": "grammar" },
+            }));
+        }
+        if let Some(ix) = text.find("recieve") {
+            matches.push(json!({
+                "message": "Possible spelling mistake found.",
+                "replacements": [{ "value": "receive" }],
+                "offset": utf16_offset(ix),
+                "length": "recieve".len(),
+                "rule": { "id": "MORFOLOGIK_RULE_EN_AU", "issueType": "misspelling" },
+            }));
+        }
+        matches
+    }
+
+    fn grammar_diagnostics(
+        buffer: &Entity<Buffer>,
+        cx: &mut TestAppContext,
+    ) -> Vec<(String, String)> {
+        buffer.read_with(cx, |buffer, _| {
+            let snapshot = buffer.snapshot();
+            prose::diagnostics_in_range(&snapshot, DiagnosticSource::Grammar, 0..snapshot.len())
+                .map(|entry| {
+                    (
+                        snapshot.text_for_range(entry.range).collect(),
+                        entry.diagnostic.message,
+                    )
+                })
+                .collect()
+        })
+    }
+}
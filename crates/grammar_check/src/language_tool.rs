@@ -0,0 +1,91 @@
+use anyhow::{Context as _, Result, anyhow};
+use futures::AsyncReadExt as _;
+use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use serde::{Deserialize, Serialize};
+
+/// One piece of a document sent for checking. Markup is skipped by the
+/// checker but still counts towards the offsets it reports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum AnnotationItem<'a> {
+    Text {
+        text: &'a str,
+    },
+    Markup {
+        markup: &'a str,
+        #[serde(rename = "interpretAs", skip_serializing_if = "Option::is_none")]
+        interpret_as: Option<&'static str>,
+    },
+}
+
+#[derive(Serialize)]
+struct AnnotatedText<'a> {
+    annotation: &'a [AnnotationItem<'a>],
+}
+
+#[derive(Deserialize)]
+struct CheckResponse {
+    matches: Vec<Match>,
+}
+
+/// A problem found by the checker. `offset` and `length` are in UTF-16 code
+/// units, relative to the concatenation of all annotation items.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Match {
+    pub message: String,
+    #[serde(default)]
+    pub replacements: Vec<Replacement>,
+    pub offset: usize,
+    pub length: usize,
+    pub rule: Rule,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Replacement {
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: String,
+    #[serde(default)]
+    pub issue_type: String,
+}
+
+pub async fn check(
+    client: &dyn HttpClient,
+    api_url: &str,
+    language: &str,
+    annotation: &[AnnotationItem<'_>],
+) -> Result<Vec<Match>> {
+    let uri = format!("{api_url}/v2/check");
+    let data = serde_json::to_string(&AnnotatedText { annotation })?;
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("language", language)
+        .append_pair("data", &data)
+        .finish();
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(AsyncBody::from(body))?;
+
+    let mut response = client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        let response: CheckResponse =
+            serde_json::from_str(&body).context("Unable to parse LanguageTool response")?;
+        Ok(response.matches)
+    } else {
+        Err(anyhow!(
+            "Failed to connect to LanguageTool API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
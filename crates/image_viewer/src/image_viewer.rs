@@ -2,14 +2,15 @@ mod image_info;
 mod image_viewer_settings;
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Context as _;
 use editor::{EditorSettings, items::entry_git_aware_label_color};
 use file_icons::FileIcons;
 use gpui::{
-    AnyElement, App, Bounds, Context, Entity, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, ObjectFit, ParentElement, Render, Styled, Task, WeakEntity,
-    Window, canvas, div, fill, img, opaque_grey, point, size,
+    AnyElement, App, Bounds, Context, Div, Entity, EventEmitter, FocusHandle, Focusable, Image,
+    ImageSource, InteractiveElement, IntoElement, ObjectFit, ParentElement, Render, Styled, Task,
+    WeakEntity, Window, canvas, div, fill, img, opaque_grey, point, size,
 };
 use persistence::IMAGE_VIEWER;
 use project::{ImageItem, Project, ProjectPath, image_store::ImageItemEvent};
@@ -40,11 +41,7 @@ impl ImageView {
     ) -> Self {
         cx.subscribe(&image_item, Self::on_image_event).detach();
         cx.on_release_in(window, |this, window, cx| {
-            let image_data = this.image_item.read(cx).image.clone();
-            if let Some(image) = image_data.clone().get_render_image(window, cx) {
-                cx.drop_image(image, None);
-            }
-            image_data.remove_asset(cx);
+            release_image(this.image_item.read(cx).image.clone(), window, cx);
         })
         .detach();
 
@@ -301,73 +298,79 @@ impl Focusable for ImageView {
 impl Render for ImageView {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let image = self.image_item.read(cx).image.clone();
-        let checkered_background = |bounds: Bounds<Pixels>,
-                                    _,
-                                    window: &mut Window,
-                                    _cx: &mut App| {
-            let square_size = 32.0;
-
-            let start_y = bounds.origin.y.0;
-            let height = bounds.size.height.0;
-            let start_x = bounds.origin.x.0;
-            let width = bounds.size.width.0;
-
-            let mut y = start_y;
-            let mut x = start_x;
-            let mut color_swapper = true;
-            // draw checkerboard pattern
-            while y <= start_y + height {
-                // Keeping track of the grid in order to be resilient to resizing
-                let start_swap = color_swapper;
-                while x <= start_x + width {
-                    let rect =
-                        Bounds::new(point(px(x), px(y)), size(px(square_size), px(square_size)));
-
-                    let color = if color_swapper {
-                        opaque_grey(0.6, 0.4)
-                    } else {
-                        opaque_grey(0.7, 0.4)
-                    };
-
-                    window.paint_quad(fill(rect, color));
-                    color_swapper = !color_swapper;
-                    x += square_size;
-                }
-                x = start_x;
-                color_swapper = !start_swap;
-                y += square_size;
+        image_canvas(image, cx).track_focus(&self.focus_handle(cx))
+    }
+}
+
+/// An image scaled down to fit the space available, over a checkered
+/// background that shows through where it's transparent.
+pub fn image_canvas(image: impl Into<ImageSource>, cx: &App) -> Div {
+    let checkered_background = |bounds: Bounds<Pixels>, _, window: &mut Window, _cx: &mut App| {
+        let square_size = 32.0;
+
+        let start_y = bounds.origin.y.0;
+        let height = bounds.size.height.0;
+        let start_x = bounds.origin.x.0;
+        let width = bounds.size.width.0;
+
+        let mut y = start_y;
+        let mut x = start_x;
+        let mut color_swapper = true;
+        // draw checkerboard pattern
+        while y <= start_y + height {
+            // Keeping track of the grid in order to be resilient to resizing
+            let start_swap = color_swapper;
+            while x <= start_x + width {
+                let rect = Bounds::new(point(px(x), px(y)), size(px(square_size), px(square_size)));
+
+                let color = if color_swapper {
+                    opaque_grey(0.6, 0.4)
+                } else {
+                    opaque_grey(0.7, 0.4)
+                };
+
+                window.paint_quad(fill(rect, color));
+                color_swapper = !color_swapper;
+                x += square_size;
             }
-        };
+            x = start_x;
+            color_swapper = !start_swap;
+            y += square_size;
+        }
+    };
 
-        let checkered_background = canvas(|_, _, _| (), checkered_background)
-            .border_2()
-            .border_color(cx.theme().styles.colors.border)
-            .size_full()
-            .absolute()
-            .top_0()
-            .left_0();
+    let checkered_background = canvas(|_, _, _| (), checkered_background)
+        .border_2()
+        .border_color(cx.theme().styles.colors.border)
+        .size_full()
+        .absolute()
+        .top_0()
+        .left_0();
 
+    div().size_full().child(checkered_background).child(
         div()
-            .track_focus(&self.focus_handle(cx))
-            .size_full()
-            .child(checkered_background)
+            .flex()
+            .justify_center()
+            .items_center()
+            .w_full()
+            // TODO: In browser based Tailwind & Flex this would be h-screen and we'd use w-full
+            .h_full()
             .child(
-                div()
-                    .flex()
-                    .justify_center()
-                    .items_center()
-                    .w_full()
-                    // TODO: In browser based Tailwind & Flex this would be h-screen and we'd use w-full
-                    .h_full()
-                    .child(
-                        img(image)
-                            .object_fit(ObjectFit::ScaleDown)
-                            .max_w_full()
-                            .max_h_full()
-                            .id("img"),
-                    ),
-            )
+                img(image)
+                    .object_fit(ObjectFit::ScaleDown)
+                    .max_w_full()
+                    .max_h_full()
+                    .id("img"),
+            ),
+    )
+}
+
+/// Frees the textures and decoded data of an image that is no longer shown.
+pub fn release_image(image: Arc<Image>, window: &mut Window, cx: &mut App) {
+    if let Some(render_image) = image.clone().get_render_image(window, cx) {
+        cx.drop_image(render_image, None);
     }
+    image.remove_asset(cx);
 }
 
 impl ProjectItem for ImageView {
@@ -1,3 +1,4 @@
+use citations::Citation;
 use gpui::{
     FontStyle, FontWeight, HighlightStyle, SharedString, StrikethroughStyle, UnderlineStyle, px,
 };
@@ -12,9 +13,15 @@ pub enum ParsedMarkdownElement {
     Table(ParsedMarkdownTable),
     BlockQuote(ParsedMarkdownBlockQuote),
     CodeBlock(ParsedMarkdownCodeBlock),
+    /// A block quote introduced with a `[!NOTE]`-style marker.
+    Callout(ParsedMarkdownCallout),
+    DefinitionList(ParsedMarkdownDefinitionList),
+    FootnoteDefinition(ParsedMarkdownFootnoteDefinition),
     /// A paragraph of text and other inline elements.
     Paragraph(MarkdownParagraph),
     HorizontalRule(Range<usize>),
+    /// A paragraph containing only `\pagebreak` or `\newpage`.
+    PageBreak(Range<usize>),
 }
 
 impl ParsedMarkdownElement {
@@ -25,17 +32,61 @@ impl ParsedMarkdownElement {
             Self::Table(table) => table.source_range.clone(),
             Self::BlockQuote(block_quote) => block_quote.source_range.clone(),
             Self::CodeBlock(code_block) => code_block.source_range.clone(),
+            Self::Callout(callout) => callout.source_range.clone(),
+            Self::DefinitionList(list) => list.source_range.clone(),
+            Self::FootnoteDefinition(footnote) => footnote.source_range.clone(),
             Self::Paragraph(text) => match text.get(0)? {
                 MarkdownParagraphChunk::Text(t) => t.source_range.clone(),
                 MarkdownParagraphChunk::Image(image) => image.source_range.clone(),
             },
-            Self::HorizontalRule(range) => range.clone(),
+            Self::HorizontalRule(range) | Self::PageBreak(range) => range.clone(),
         })
     }
 
     pub fn is_list_item(&self) -> bool {
         matches!(self, Self::ListItem(_))
     }
+
+    /// Whether this element, or any element nested within it, references the
+    /// footnote with the given label.
+    pub fn references_footnote(&self, label: &str) -> bool {
+        let paragraph_references = |paragraph: &MarkdownParagraph| {
+            paragraph.iter().any(|chunk| {
+                match chunk {
+                MarkdownParagraphChunk::Text(text) => text.regions.iter().any(|region| {
+                    matches!(&region.link, Some(Link::Footnote { label: l, .. }) if l == label)
+                }),
+                MarkdownParagraphChunk::Image(_) => false,
+            }
+            })
+        };
+        let children_reference = |children: &[ParsedMarkdownElement]| {
+            children
+                .iter()
+                .any(|child| child.references_footnote(label))
+        };
+
+        match self {
+            Self::Heading(heading) => paragraph_references(&heading.contents),
+            Self::ListItem(list_item) => children_reference(&list_item.content),
+            Self::Table(table) => std::iter::once(&table.header)
+                .chain(&table.body)
+                .flat_map(|row| &row.children)
+                .any(paragraph_references),
+            Self::BlockQuote(block_quote) => children_reference(&block_quote.children),
+            Self::Callout(callout) => children_reference(&callout.children),
+            Self::DefinitionList(list) => list.items.iter().any(|item| {
+                paragraph_references(&item.term)
+                    || item
+                        .definitions
+                        .iter()
+                        .any(|definition| children_reference(definition))
+            }),
+            Self::FootnoteDefinition(footnote) => children_reference(&footnote.children),
+            Self::Paragraph(paragraph) => paragraph_references(paragraph),
+            Self::CodeBlock(_) | Self::HorizontalRule(_) | Self::PageBreak(_) => false,
+        }
+    }
 }
 
 pub type MarkdownParagraph = Vec<MarkdownParagraphChunk>;
@@ -53,6 +104,17 @@ pub struct ParsedMarkdown {
     pub children: Vec<ParsedMarkdownElement>,
 }
 
+impl ParsedMarkdown {
+    /// Shows each heading's number before its text.
+    pub fn number_headings(&mut self) {
+        for child in &mut self.children {
+            if let ParsedMarkdownElement::Heading(heading) = child {
+                heading.show_number();
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ParsedMarkdownListItem {
@@ -86,6 +148,39 @@ pub struct ParsedMarkdownHeading {
     pub source_range: Range<usize>,
     pub level: HeadingLevel,
     pub contents: MarkdownParagraph,
+    /// The heading's place in the document's outline, such as "4.2".
+    pub number: Option<SharedString>,
+}
+
+impl ParsedMarkdownHeading {
+    fn show_number(&mut self) {
+        let Some(number) = self.number.as_ref() else {
+            return;
+        };
+        let prefix = format!("{number} ");
+        if let Some(MarkdownParagraphChunk::Text(text)) = self.contents.first_mut() {
+            text.contents.insert_str(0, &prefix);
+            let shift = |range: &mut Range<usize>| {
+                range.start += prefix.len();
+                range.end += prefix.len();
+            };
+            text.highlights
+                .iter_mut()
+                .for_each(|(range, _)| shift(range));
+            text.region_ranges.iter_mut().for_each(shift);
+        } else {
+            self.contents.insert(
+                0,
+                MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                    source_range: self.source_range.clone(),
+                    contents: prefix,
+                    highlights: Vec::new(),
+                    region_ranges: Vec::new(),
+                    regions: Vec::new(),
+                }),
+            );
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -147,6 +242,61 @@ pub struct ParsedMarkdownBlockQuote {
     pub children: Vec<ParsedMarkdownElement>,
 }
 
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownCallout {
+    pub source_range: Range<usize>,
+    pub kind: CalloutKind,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalloutKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl CalloutKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Tip => "Tip",
+            Self::Important => "Important",
+            Self::Warning => "Warning",
+            Self::Caution => "Caution",
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownDefinitionList {
+    pub source_range: Range<usize>,
+    pub items: Vec<ParsedMarkdownDefinitionListItem>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownDefinitionListItem {
+    pub term: MarkdownParagraph,
+    /// Each definition of the term, as a sequence of block elements.
+    pub definitions: Vec<Vec<ParsedMarkdownElement>>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownFootnoteDefinition {
+    pub source_range: Range<usize>,
+    /// The label as written in the source, lowercased.
+    pub label: SharedString,
+    /// The number shown for this footnote, in order of first reference.
+    pub number: usize,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedMarkdownText {
     /// Where the text is located in the source Markdown document.
@@ -246,10 +396,23 @@ pub enum Link {
         /// The absolute path to the item.
         path: PathBuf,
     },
+    /// A citation of an indexed source that the text was drawn from.
+    Citation(Citation),
+    /// A reference to a footnote defined elsewhere in the document.
+    Footnote {
+        /// The footnote label, lowercased.
+        label: SharedString,
+        /// The number shown for the footnote.
+        number: usize,
+    },
 }
 
 impl Link {
     pub fn identify(file_location_directory: Option<PathBuf>, text: String) -> Option<Link> {
+        if let Some(citation) = Citation::parse(&text) {
+            return Some(Link::Citation(citation));
+        }
+
         if text.starts_with("http") {
             return Some(Link::Web { url: text });
         }
@@ -279,6 +442,8 @@ impl Display for Link {
         match self {
             Link::Web { url } => write!(f, "{}", url),
             Link::Path { display_path, .. } => write!(f, "{}", display_path.display()),
+            Link::Citation(citation) => write!(f, "{}", citation.label()),
+            Link::Footnote { number, .. } => write!(f, "Footnote {}", number),
         }
     }
 }
@@ -299,6 +464,9 @@ impl Image {
         file_location_directory: Option<PathBuf>,
     ) -> Option<Self> {
         let link = Link::identify(file_location_directory, text)?;
+        if matches!(link, Link::Citation(_)) {
+            return None;
+        }
         Some(Self {
             source_range,
             link,
@@ -1,9 +1,10 @@
 use crate::markdown_elements::*;
 use async_recursion::async_recursion;
 use collections::FxHashMap;
+use cross_references::{CrossReferences, LabelKind};
 use gpui::FontWeight;
 use language::LanguageRegistry;
-use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{Alignment, BlockQuoteKind, Event, Options, Parser, Tag, TagEnd};
 use std::{ops::Range, path::PathBuf, sync::Arc, vec};
 
 pub async fn parse_markdown(
@@ -11,12 +12,10 @@ pub async fn parse_markdown(
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
 ) -> ParsedMarkdown {
-    let mut options = Options::all();
-    options.remove(pulldown_cmark::Options::ENABLE_DEFINITION_LIST);
-
-    let parser = Parser::new_ext(markdown_input, options);
+    let parser = Parser::new_ext(markdown_input, Options::all());
     let parser = MarkdownParser::new(
         parser.into_offset_iter().collect(),
+        CrossReferences::parse(markdown_input),
         file_location_directory,
         language_registry,
     );
@@ -34,6 +33,9 @@ struct MarkdownParser<'a> {
     parsed: Vec<ParsedMarkdownElement>,
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
+    /// The number shown for each footnote label, in order of first reference.
+    footnote_numbers: FxHashMap<String, usize>,
+    cross_references: CrossReferences,
 }
 
 struct MarkdownListItem {
@@ -53,16 +55,46 @@ impl Default for MarkdownListItem {
 impl<'a> MarkdownParser<'a> {
     fn new(
         tokens: Vec<(Event<'a>, Range<usize>)>,
+        cross_references: CrossReferences,
         file_location_directory: Option<PathBuf>,
         language_registry: Option<Arc<LanguageRegistry>>,
     ) -> Self {
+        let footnote_numbers = Self::number_footnotes(&tokens);
         Self {
             tokens,
             file_location_directory,
             language_registry,
             cursor: 0,
             parsed: vec![],
+            footnote_numbers,
+            cross_references,
+        }
+    }
+
+    /// Footnotes are numbered in the order they are first referenced, followed
+    /// by any definitions that are never referenced.
+    fn number_footnotes(tokens: &[(Event<'a>, Range<usize>)]) -> FxHashMap<String, usize> {
+        let mut numbers = FxHashMap::default();
+        let references = tokens.iter().filter_map(|(event, _)| match event {
+            Event::FootnoteReference(label) => Some(label),
+            _ => None,
+        });
+        let definitions = tokens.iter().filter_map(|(event, _)| match event {
+            Event::Start(Tag::FootnoteDefinition(label)) => Some(label),
+            _ => None,
+        });
+        for label in references.chain(definitions) {
+            let next = numbers.len() + 1;
+            numbers.entry(label.to_lowercase()).or_insert(next);
         }
+        numbers
+    }
+
+    fn footnote_number(&self, label: &str) -> usize {
+        self.footnote_numbers
+            .get(label)
+            .copied()
+            .unwrap_or_default()
     }
 
     fn eof(&self) -> bool {
@@ -132,8 +164,16 @@ impl<'a> MarkdownParser<'a> {
             Event::Start(tag) => match tag {
                 Tag::Paragraph => {
                     self.cursor += 1;
-                    let text = self.parse_text(false, Some(source_range));
-                    Some(vec![ParsedMarkdownElement::Paragraph(text)])
+                    let text = self.parse_text(false, Some(source_range.clone()));
+                    if Self::is_page_break(&text) {
+                        return Some(vec![ParsedMarkdownElement::PageBreak(source_range)]);
+                    }
+                    let captions = self.figure_captions(&source_range);
+                    Some(
+                        std::iter::once(ParsedMarkdownElement::Paragraph(text))
+                            .chain(captions.map(ParsedMarkdownElement::Paragraph))
+                            .collect(),
+                    )
                 }
                 Tag::Heading { level, .. } => {
                     let level = *level;
@@ -153,10 +193,44 @@ impl<'a> MarkdownParser<'a> {
                     let list = self.parse_list(order).await;
                     Some(list)
                 }
-                Tag::BlockQuote(_kind) => {
+                Tag::BlockQuote(kind) => {
+                    let kind = *kind;
                     self.cursor += 1;
                     let block_quote = self.parse_block_quote().await;
-                    Some(vec![ParsedMarkdownElement::BlockQuote(block_quote)])
+                    let Some(kind) = kind else {
+                        return Some(vec![ParsedMarkdownElement::BlockQuote(block_quote)]);
+                    };
+                    Some(vec![ParsedMarkdownElement::Callout(
+                        ParsedMarkdownCallout {
+                            source_range: block_quote.source_range,
+                            kind: match kind {
+                                BlockQuoteKind::Note => CalloutKind::Note,
+                                BlockQuoteKind::Tip => CalloutKind::Tip,
+                                BlockQuoteKind::Important => CalloutKind::Important,
+                                BlockQuoteKind::Warning => CalloutKind::Warning,
+                                BlockQuoteKind::Caution => CalloutKind::Caution,
+                            },
+                            children: block_quote.children,
+                        },
+                    )])
+                }
+                Tag::DefinitionList => {
+                    self.cursor += 1;
+                    let list = self.parse_definition_list(source_range).await;
+                    Some(vec![ParsedMarkdownElement::DefinitionList(list)])
+                }
+                Tag::FootnoteDefinition(label) => {
+                    let label = label.to_lowercase();
+                    self.cursor += 1;
+                    let children = self.parse_blocks_until(TagEnd::FootnoteDefinition).await;
+                    Some(vec![ParsedMarkdownElement::FootnoteDefinition(
+                        ParsedMarkdownFootnoteDefinition {
+                            source_range,
+                            number: self.footnote_number(&label),
+                            label: label.into(),
+                            children,
+                        },
+                    )])
                 }
                 Tag::CodeBlock(kind) => {
                     let language = match kind {
@@ -215,7 +289,7 @@ impl<'a> MarkdownParser<'a> {
                 break;
             }
 
-            let (current, _) = self.current().unwrap();
+            let (current, current_range) = self.current().unwrap();
             let prev_len = text.len();
             match current {
                 Event::SoftBreak => {
@@ -233,7 +307,22 @@ impl<'a> MarkdownParser<'a> {
                 // the text between them
                 Event::InlineHtml(_) => {}
 
+                Event::FootnoteReference(label) => {
+                    let label = label.to_lowercase();
+                    let number = self.footnote_number(&label);
+                    text.push_str(&format!("[{number}]"));
+                    region_ranges.push(prev_len..text.len());
+                    regions.push(ParsedRegion {
+                        code: false,
+                        link: Some(Link::Footnote {
+                            label: label.into(),
+                            number,
+                        }),
+                    });
+                }
+
                 Event::Text(t) => {
+                    let t = self.cross_references.replace_in(current_range.clone(), t);
                     text.push_str(t.as_ref());
                     let mut style = MarkdownHighlightStyle::default();
 
@@ -262,7 +351,7 @@ impl<'a> MarkdownParser<'a> {
                         let mut finder = linkify::LinkFinder::new();
                         finder.kinds(&[linkify::LinkKind::Url]);
                         let mut last_link_len = prev_len;
-                        for link in finder.links(t) {
+                        for link in finder.links(&t) {
                             let start = link.start();
                             let end = link.end();
                             let range = (prev_len + start)..(prev_len + end);
@@ -412,6 +501,15 @@ impl<'a> MarkdownParser<'a> {
         markdown_text_like
     }
 
+    fn is_page_break(text: &MarkdownParagraph) -> bool {
+        match text.as_slice() {
+            [MarkdownParagraphChunk::Text(text)] => {
+                matches!(text.contents.trim(), "\\pagebreak" | "\\newpage")
+            }
+            _ => false,
+        }
+    }
+
     fn parse_heading(&mut self, level: pulldown_cmark::HeadingLevel) -> ParsedMarkdownHeading {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
@@ -431,9 +529,50 @@ impl<'a> MarkdownParser<'a> {
                 pulldown_cmark::HeadingLevel::H6 => HeadingLevel::H6,
             },
             contents: text,
+            number: self
+                .cross_references
+                .heading_number(&source_range)
+                .map(|number| number.to_string().into()),
         }
     }
 
+    /// Captions for the labelled figures in the paragraph at `source_range`,
+    /// such as "Figure 2: Team structure".
+    fn figure_captions(
+        &self,
+        source_range: &Range<usize>,
+    ) -> impl '_ + Iterator<Item = MarkdownParagraph> {
+        let source_range = source_range.clone();
+        self.cross_references
+            .labels
+            .iter()
+            .filter(move |label| {
+                label.kind == LabelKind::Figure
+                    && source_range.start <= label.target_range.start
+                    && label.target_range.end <= source_range.end
+            })
+            .map(|label| {
+                let contents = if label.title.is_empty() {
+                    label.display()
+                } else {
+                    format!("{}: {}", label.display(), label.title)
+                };
+                vec![MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                    source_range: label.target_range.clone(),
+                    highlights: vec![(
+                        0..contents.len(),
+                        MarkdownHighlight::Style(MarkdownHighlightStyle {
+                            italic: true,
+                            ..Default::default()
+                        }),
+                    )],
+                    contents,
+                    region_ranges: vec![],
+                    regions: vec![],
+                })]
+            })
+    }
+
     fn parse_table(&mut self, alignment: Vec<Alignment>) -> ParsedMarkdownTable {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
@@ -697,6 +836,85 @@ impl<'a> MarkdownParser<'a> {
         }
     }
 
+    /// Parses the block content of a container until its end tag, treating any
+    /// bare inline content (as found in tight definitions) as a paragraph.
+    #[async_recursion]
+    async fn parse_blocks_until(&mut self, end: TagEnd) -> Vec<ParsedMarkdownElement> {
+        let mut children = Vec::new();
+
+        while !self.eof() {
+            let (current, source_range) = self.current().unwrap();
+            if current == &Event::End(end) {
+                self.cursor += 1;
+                break;
+            }
+
+            if MarkdownParser::is_text_like(current) {
+                let source_range = source_range.clone();
+                let start = self.cursor;
+                let text = self.parse_text(false, Some(source_range));
+                if self.cursor == start {
+                    self.cursor += 1;
+                }
+                if !text.is_empty() {
+                    children.push(ParsedMarkdownElement::Paragraph(text));
+                }
+            } else if let Some(block) = self.parse_block().await {
+                children.extend(block);
+            } else {
+                self.cursor += 1;
+            }
+        }
+
+        children
+    }
+
+    async fn parse_definition_list(
+        &mut self,
+        source_range: Range<usize>,
+    ) -> ParsedMarkdownDefinitionList {
+        let mut items: Vec<ParsedMarkdownDefinitionListItem> = Vec::new();
+
+        while !self.eof() {
+            let (current, range) = self.current().unwrap();
+            match current {
+                Event::Start(Tag::DefinitionListTitle) => {
+                    let range = range.clone();
+                    self.cursor += 1;
+                    let term = self.parse_text(false, Some(range));
+                    if self.current_event() == Some(&Event::End(TagEnd::DefinitionListTitle)) {
+                        self.cursor += 1;
+                    }
+                    items.push(ParsedMarkdownDefinitionListItem {
+                        term,
+                        definitions: Vec::new(),
+                    });
+                }
+                Event::Start(Tag::DefinitionListDefinition) => {
+                    self.cursor += 1;
+                    let definition = self
+                        .parse_blocks_until(TagEnd::DefinitionListDefinition)
+                        .await;
+                    if let Some(item) = items.last_mut() {
+                        item.definitions.push(definition);
+                    }
+                }
+                Event::End(TagEnd::DefinitionList) => {
+                    self.cursor += 1;
+                    break;
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+
+        ParsedMarkdownDefinitionList {
+            source_range,
+            items,
+        }
+    }
+
     async fn parse_code_block(&mut self, language: Option<String>) -> ParsedMarkdownCodeBlock {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
@@ -752,6 +970,7 @@ mod tests {
     use super::*;
 
     use ParsedMarkdownListItemType::*;
+    use citations::Citation;
     use gpui::BackgroundExecutor;
     use language::{
         HighlightId, Language, LanguageConfig, LanguageMatcher, LanguageRegistry, tree_sitter_rust,
@@ -769,9 +988,9 @@ mod tests {
         assert_eq!(
             parsed.children,
             vec![
-                h1(text("Heading one", 2..13), 0..14),
-                h2(text("Heading two", 17..28), 14..29),
-                h3(text("Heading three", 33..46), 29..46),
+                h1(text("Heading one", 2..13), 0..14, "1"),
+                h2(text("Heading two", 17..28), 14..29, "1.1"),
+                h3(text("Heading three", 33..46), 29..46, "1.1.1"),
             ]
         );
     }
@@ -792,7 +1011,7 @@ mod tests {
 
         assert_eq!(
             parsed.children,
-            vec![h1(text("Zed", 2..5), 0..6), p("The editor", 6..16),]
+            vec![h1(text("Zed", 2..5), 0..6, "1"), p("The editor", 6..16),]
         );
     }
 
@@ -898,6 +1117,78 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_citation_links() {
+        let parsed = parse("Transition takes six weeks [1](cite:tender/RFP.pdf#page=4).").await;
+
+        let paragraph = if let ParsedMarkdownElement::Paragraph(text) = &parsed.children[0] {
+            text
+        } else {
+            panic!("Expected a paragraph");
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("Expected text");
+        };
+        let links = text
+            .regions
+            .iter()
+            .filter_map(|region| region.link.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![Link::Citation(Citation {
+                path: "tender/RFP.pdf".into(),
+                pages: Some(4..=4),
+                lines: None,
+            })]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_cross_references() {
+        let mut parsed = parse(
+            "\
+# Approach {#sec:approach}
+
+![Team structure](team.png){#fig:team}
+
+| Role | Rate |
+|------|------|
+| Lead | 1200 |
+
+Table: Day rates {#tbl:rates}
+
+See @sec:approach, @fig:team and @tbl:rates.
+",
+        )
+        .await;
+        parsed.number_headings();
+
+        let texts = parsed
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                ParsedMarkdownElement::Heading(heading) => Some(&heading.contents),
+                ParsedMarkdownElement::Paragraph(paragraph) => Some(paragraph),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|chunk| match chunk {
+                MarkdownParagraphChunk::Text(text) => Some(text.contents.as_str()),
+                MarkdownParagraphChunk::Image(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            [
+                "1 Approach",
+                "Figure 1: Team structure",
+                "Table 1: Day rates",
+                "See Section 1, Figure 1 and Table 1."
+            ]
+        );
+    }
+
     #[gpui::test]
     async fn test_empty_image() {
         let parsed = parse("![]()").await;
@@ -1316,7 +1607,7 @@ Some other content
             parsed.children,
             vec![block_quote(
                 vec![
-                    h1(text("Heading", 4..11), 2..12),
+                    h1(text("Heading", 4..11), 2..12, "1"),
                     p("More text", 14..26),
                     p("More text", 30..40)
                 ],
@@ -1346,7 +1637,7 @@ More text
                 block_quote(
                     vec![
                         p("A", 2..4),
-                        block_quote(vec![h1(text("B", 12..13), 10..14)], 8..14),
+                        block_quote(vec![h1(text("B", 12..13), 10..14, "1")], 8..14),
                         p("C", 18..20)
                     ],
                     0..20
@@ -1356,6 +1647,92 @@ More text
         );
     }
 
+    #[gpui::test]
+    async fn test_callout_and_page_break() {
+        let parsed = parse("> [!WARNING]\n> Be careful\n\n\\pagebreak\n\nAfter\n").await;
+
+        assert_eq!(
+            parsed.children,
+            vec![
+                ParsedMarkdownElement::Callout(ParsedMarkdownCallout {
+                    source_range: 0..26,
+                    kind: CalloutKind::Warning,
+                    children: vec![p("Be careful", 15..26)],
+                }),
+                ParsedMarkdownElement::PageBreak(27..38),
+                p("After", 39..45),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_definition_list() {
+        let parsed = parse("Term\n: Meaning\n: Second\n").await;
+
+        assert_eq!(
+            parsed.children,
+            vec![ParsedMarkdownElement::DefinitionList(
+                ParsedMarkdownDefinitionList {
+                    source_range: 0..24,
+                    items: vec![ParsedMarkdownDefinitionListItem {
+                        term: text("Term", 0..5),
+                        definitions: vec![vec![p("Meaning", 7..14)], vec![p("Second", 17..23)]],
+                    }],
+                }
+            )]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_footnotes() {
+        let parsed = parse("Text[^A] and[^b].\n\n[^b]: Second.\n\n[^a]: First.\n").await;
+
+        let ParsedMarkdownElement::Paragraph(paragraph) = &parsed.children[0] else {
+            panic!("Expected a paragraph");
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("Expected text");
+        };
+        assert_eq!(text.contents, "Text[1] and[2].");
+        assert_eq!(text.region_ranges, vec![4..7, 11..14]);
+        assert_eq!(
+            text.regions
+                .iter()
+                .filter_map(|region| region.link.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                Link::Footnote {
+                    label: "a".into(),
+                    number: 1
+                },
+                Link::Footnote {
+                    label: "b".into(),
+                    number: 2
+                },
+            ]
+        );
+
+        assert_eq!(
+            parsed.children[1..],
+            [
+                ParsedMarkdownElement::FootnoteDefinition(ParsedMarkdownFootnoteDefinition {
+                    source_range: 19..34,
+                    label: "b".into(),
+                    number: 2,
+                    children: vec![p("Second.", 25..33)],
+                }),
+                ParsedMarkdownElement::FootnoteDefinition(ParsedMarkdownFootnoteDefinition {
+                    source_range: 34..47,
+                    label: "a".into(),
+                    number: 1,
+                    children: vec![p("First.", 40..47)],
+                }),
+            ]
+        );
+        assert!(parsed.children[0].references_footnote("a"));
+        assert!(!parsed.children[1].references_footnote("a"));
+    }
+
     #[gpui::test]
     async fn test_code_block() {
         let parsed = parse(
@@ -1424,27 +1801,42 @@ fn main() {
         ))
     }
 
-    fn h1(contents: MarkdownParagraph, source_range: Range<usize>) -> ParsedMarkdownElement {
+    fn h1(
+        contents: MarkdownParagraph,
+        source_range: Range<usize>,
+        number: &str,
+    ) -> ParsedMarkdownElement {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
             level: HeadingLevel::H1,
             contents,
+            number: Some(number.to_string().into()),
         })
     }
 
-    fn h2(contents: MarkdownParagraph, source_range: Range<usize>) -> ParsedMarkdownElement {
+    fn h2(
+        contents: MarkdownParagraph,
+        source_range: Range<usize>,
+        number: &str,
+    ) -> ParsedMarkdownElement {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
             level: HeadingLevel::H2,
             contents,
+            number: Some(number.to_string().into()),
         })
     }
 
-    fn h3(contents: MarkdownParagraph, source_range: Range<usize>) -> ParsedMarkdownElement {
+    fn h3(
+        contents: MarkdownParagraph,
+        source_range: Range<usize>,
+        number: &str,
+    ) -> ParsedMarkdownElement {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
             level: HeadingLevel::H3,
             contents,
+            number: Some(number.to_string().into()),
         })
     }
 
@@ -6,39 +6,54 @@ use anyhow::Result;
 use editor::scroll::Autoscroll;
 use editor::{Editor, EditorEvent};
 use gpui::{
-    App, ClickEvent, Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
-    IntoElement, ListState, ParentElement, Render, RetainAllImageCache, Styled, Subscription, Task,
-    WeakEntity, Window, list,
+    AnyElement, App, ClickEvent, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ListOffset, ListState, ParentElement, Render,
+    RetainAllImageCache, ScrollStrategy, Styled, Subscription, Task, UniformListScrollHandle,
+    WeakEntity, Window, list, uniform_list,
 };
 use language::LanguageRegistry;
-use settings::Settings;
+use settings::{Settings, SettingsStore};
 use theme::ThemeSettings;
-use ui::prelude::*;
+use ui::{Tooltip, prelude::*};
 use workspace::item::{Item, ItemHandle};
 use workspace::{Pane, Workspace};
+use writing_app_settings::writing_settings;
 
-use crate::OpenPreviewToTheSide;
 use crate::markdown_elements::ParsedMarkdownElement;
+use crate::print_layout::{
+    PrintLayout, PrintSection, page_size_in_pixels, print_layout_provider, render_print_page,
+};
 use crate::{
     OpenPreview,
     markdown_elements::ParsedMarkdown,
     markdown_parser::parse_markdown,
-    markdown_renderer::{RenderContext, render_markdown_block},
+    markdown_renderer::{FootnoteTarget, RenderContext, render_markdown_block},
 };
+use crate::{OpenPreviewToTheSide, TogglePrintLayout};
 
 const REPARSE_DEBOUNCE: Duration = Duration::from_millis(200);
 
+const DEFAULT_PRINT_ZOOM: f32 = 0.75;
+const MIN_PRINT_ZOOM: f32 = 0.25;
+const MAX_PRINT_ZOOM: f32 = 2.;
+const PRINT_ZOOM_STEP: f32 = 0.25;
+/// The space between pages in print layout mode, in pixels.
+const PAGE_GAP: f32 = 16.;
+
 pub struct MarkdownPreviewView {
     workspace: WeakEntity<Workspace>,
     image_cache: Entity<RetainAllImageCache>,
     active_editor: Option<EditorState>,
     focus_handle: FocusHandle,
     contents: Option<ParsedMarkdown>,
+    /// Whether headings were numbered when the contents were parsed.
+    number_headings: bool,
     selected_block: usize,
     list_state: ListState,
     tab_content_text: SharedString,
     language_registry: Arc<LanguageRegistry>,
     parsing_markdown_task: Option<Task<Result<()>>>,
+    print_layout: Option<PrintLayoutState>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -54,6 +69,17 @@ struct EditorState {
     _subscription: Subscription,
 }
 
+/// The preview's print layout mode, which shows the document on pages as it
+/// will be exported.
+struct PrintLayoutState {
+    layout: Option<PrintLayout>,
+    error: Option<SharedString>,
+    zoom: f32,
+    scroll_handle: UniformListScrollHandle,
+    layout_task: Option<Task<()>>,
+    _settings_subscription: Subscription,
+}
+
 impl MarkdownPreviewView {
     pub fn register(workspace: &mut Workspace, _window: &mut Window, _cx: &mut Context<Workspace>) {
         workspace.register_action(move |workspace, _: &OpenPreview, window, cx| {
@@ -186,6 +212,14 @@ impl MarkdownPreviewView {
                                                 }
                                             })
                                         }
+                                    })
+                                    .with_footnote_clicked_callback({
+                                        let view = view.clone();
+                                        move |label, target, _window, cx| {
+                                            view.update(cx, |view, cx| {
+                                                view.scroll_to_footnote(&label, target, cx);
+                                            })
+                                        }
                                     });
 
                             let block = contents.children.get(ix).unwrap();
@@ -261,15 +295,30 @@ impl MarkdownPreviewView {
                 focus_handle: cx.focus_handle(),
                 workspace: workspace.clone(),
                 contents: None,
+                number_headings: false,
                 list_state,
                 tab_content_text,
                 language_registry,
                 parsing_markdown_task: None,
+                print_layout: None,
                 image_cache: RetainAllImageCache::new(cx),
             };
 
             this.set_editor(active_editor, window, cx);
 
+            cx.observe_global_in::<SettingsStore>(window, |this, window, cx| {
+                let number_headings = this
+                    .active_editor
+                    .as_ref()
+                    .map(|state| Self::number_headings(state.editor.read(cx), cx));
+                if number_headings
+                    .is_some_and(|number_headings| number_headings != this.number_headings)
+                {
+                    this.parse_markdown_from_active_editor(false, window, cx);
+                }
+            })
+            .detach();
+
             if mode == MarkdownPreviewMode::Follow {
                 if let Some(workspace) = &workspace.upgrade() {
                     cx.observe_in(workspace, window, |this, workspace, window, cx| {
@@ -313,6 +362,11 @@ impl MarkdownPreviewView {
         false
     }
 
+    /// The editor whose contents are being previewed.
+    pub fn active_editor(&self) -> Option<&Entity<Editor>> {
+        self.active_editor.as_ref().map(|state| &state.editor)
+    }
+
     fn set_editor(&mut self, editor: Entity<Editor>, window: &mut Window, cx: &mut Context<Self>) {
         if let Some(active) = &self.active_editor {
             if active.editor == editor {
@@ -384,28 +438,138 @@ impl MarkdownPreviewView {
                 cx.background_executor().timer(REPARSE_DEBOUNCE).await;
             }
 
-            let (contents, file_location) = view.update(cx, |_, cx| {
+            let (contents, file_location, number_headings) = view.update(cx, |_, cx| {
                 let editor = editor.read(cx);
                 let contents = editor.buffer().read(cx).snapshot(cx).text();
                 let file_location = MarkdownPreviewView::get_folder_for_active_editor(editor, cx);
-                (contents, file_location)
+                let number_headings = Self::number_headings(editor, cx);
+                (contents, file_location, number_headings)
             })?;
 
             let parsing_task = cx.background_spawn(async move {
-                parse_markdown(&contents, file_location, Some(language_registry)).await
+                let mut markdown =
+                    parse_markdown(&contents, file_location, Some(language_registry)).await;
+                if number_headings {
+                    markdown.number_headings();
+                }
+                markdown
             });
             let contents = parsing_task.await;
             view.update(cx, move |view, cx| {
                 let markdown_blocks_count = contents.children.len();
                 view.contents = Some(contents);
+                view.number_headings = number_headings;
                 let scroll_top = view.list_state.logical_scroll_top();
                 view.list_state.reset(markdown_blocks_count);
                 view.list_state.scroll_to(scroll_top);
+                view.refresh_print_layout(cx);
                 cx.notify();
             })
         })
     }
 
+    /// Whether to number the headings of the editor's buffer, which can be set
+    /// for its project.
+    fn number_headings(editor: &Editor, cx: &App) -> bool {
+        let buffer = editor.buffer().read(cx).as_singleton();
+        let file = buffer.as_ref().and_then(|buffer| buffer.read(cx).file());
+        writing_settings(file, cx).number_headings
+    }
+
+    fn toggle_print_layout(
+        &mut self,
+        _: &TogglePrintLayout,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.print_layout.take().is_none() {
+            let settings_subscription =
+                cx.observe_global::<SettingsStore>(|this, cx| this.refresh_print_layout(cx));
+            self.print_layout = Some(PrintLayoutState {
+                layout: None,
+                error: None,
+                zoom: DEFAULT_PRINT_ZOOM,
+                scroll_handle: UniformListScrollHandle::new(),
+                layout_task: None,
+                _settings_subscription: settings_subscription,
+            });
+            self.refresh_print_layout(cx);
+        }
+        cx.notify();
+    }
+
+    /// Lays the document out on pages again, if the preview is in print layout
+    /// mode. The pages already shown stay until the new ones are ready.
+    fn refresh_print_layout(&mut self, cx: &mut Context<Self>) {
+        let Some(state) = self.print_layout.as_mut() else {
+            return;
+        };
+        let Some(editor) = self.active_editor.as_ref().map(|s| s.editor.clone()) else {
+            return;
+        };
+        let Some(provider) = print_layout_provider(cx) else {
+            state.error = Some("Print layout is not available.".into());
+            return;
+        };
+
+        let layout = provider.layout(&editor, cx);
+        state.layout_task = Some(cx.spawn(async move |this, cx| {
+            let layout = layout.await;
+            this.update(cx, |this, cx| {
+                let Some(state) = this.print_layout.as_mut() else {
+                    return;
+                };
+                match layout {
+                    Ok(layout) => {
+                        state.layout = Some(layout);
+                        state.error = None;
+                    }
+                    Err(error) => {
+                        state.error = Some(format!("Failed to lay out pages: {error}").into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn zoom_print_layout(&mut self, step: f32, cx: &mut Context<Self>) {
+        if let Some(state) = self.print_layout.as_mut() {
+            state.zoom = (state.zoom + step).clamp(MIN_PRINT_ZOOM, MAX_PRINT_ZOOM);
+            cx.notify();
+        }
+    }
+
+    fn scroll_to_print_page(&mut self, page: usize, cx: &mut Context<Self>) {
+        if let Some(state) = &self.print_layout {
+            state
+                .scroll_handle
+                .scroll_to_item(page, ScrollStrategy::Top);
+            cx.notify();
+        }
+    }
+
+    fn scroll_to_footnote(&mut self, label: &str, target: FootnoteTarget, cx: &mut Context<Self>) {
+        let Some(contents) = &self.contents else {
+            return;
+        };
+        let index = contents.children.iter().position(|block| match target {
+            FootnoteTarget::Definition => matches!(
+                block,
+                ParsedMarkdownElement::FootnoteDefinition(footnote) if footnote.label == label
+            ),
+            FootnoteTarget::Reference => block.references_footnote(label),
+        });
+        if let Some(index) = index {
+            self.list_state.scroll_to(ListOffset {
+                item_ix: index,
+                offset_in_item: px(0.),
+            });
+            cx.notify();
+        }
+    }
+
     fn move_cursor_to_block(
         &self,
         window: &mut Window,
@@ -467,6 +631,167 @@ impl MarkdownPreviewView {
         block_index.unwrap_or_default()
     }
 
+    fn render_print_layout(&self, state: &PrintLayoutState, cx: &mut Context<Self>) -> AnyElement {
+        let Some(layout) = &state.layout else {
+            let (message, color) = match &state.error {
+                Some(error) => (error.clone(), Color::Error),
+                None => ("Laying out pages…".into(), Color::Muted),
+            };
+            return div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(message).color(color))
+                .into_any_element();
+        };
+
+        let page_count = Label::new(match layout.page_limit {
+            Some(limit) => format!(
+                "{} pages · {} of {limit} counted",
+                layout.pages.len(),
+                layout.body_page_count
+            ),
+            None => format!("{} pages", layout.pages.len()),
+        })
+        .size(LabelSize::Small)
+        .color(if layout.is_over_limit() {
+            Color::Error
+        } else {
+            Color::Muted
+        });
+
+        let toolbar = h_flex()
+            .flex_none()
+            .gap_1()
+            .pb_2()
+            .pr_8()
+            .child(page_count)
+            .when_some(state.error.clone(), |this, error| {
+                this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            })
+            .child(div().flex_grow())
+            .child(
+                IconButton::new("print-layout-zoom-out", IconName::Dash)
+                    .icon_size(IconSize::Small)
+                    .disabled(state.zoom <= MIN_PRINT_ZOOM)
+                    .tooltip(Tooltip::text("Zoom Out"))
+                    .on_click(cx.listener(|this, _, _window, cx| {
+                        this.zoom_print_layout(-PRINT_ZOOM_STEP, cx)
+                    })),
+            )
+            .child(
+                Label::new(format!("{:.0}%", state.zoom * 100.))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                IconButton::new("print-layout-zoom-in", IconName::Plus)
+                    .icon_size(IconSize::Small)
+                    .disabled(state.zoom >= MAX_PRINT_ZOOM)
+                    .tooltip(Tooltip::text("Zoom In"))
+                    .on_click(cx.listener(|this, _, _window, cx| {
+                        this.zoom_print_layout(PRINT_ZOOM_STEP, cx)
+                    })),
+            );
+
+        let sections = v_flex()
+            .id("print-layout-sections")
+            .flex_none()
+            .w(rems(14.))
+            .h_full()
+            .overflow_y_scroll()
+            .pr_2()
+            .gap_0p5()
+            .children(
+                layout
+                    .sections
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, section)| self.render_print_section(ix, section, cx)),
+            );
+
+        let (_, page_height) = page_size_in_pixels(layout, state.zoom);
+        let pages = uniform_list(
+            cx.entity(),
+            "print-layout-pages",
+            layout.pages.len(),
+            move |this, range, _window, _cx| {
+                let Some(state) = &this.print_layout else {
+                    return Vec::new();
+                };
+                let Some(layout) = &state.layout else {
+                    return Vec::new();
+                };
+                range
+                    .filter_map(|ix| layout.pages.get(ix))
+                    .map(|page| {
+                        div()
+                            .flex()
+                            .justify_center()
+                            .h(px(page_height + PAGE_GAP))
+                            .pb(px(PAGE_GAP))
+                            .child(render_print_page(layout, page, state.zoom))
+                    })
+                    .collect()
+            },
+        )
+        .track_scroll(state.scroll_handle.clone())
+        .size_full();
+
+        h_flex()
+            .size_full()
+            .items_start()
+            .when(!layout.sections.is_empty(), |this| this.child(sections))
+            .child(
+                v_flex()
+                    .size_full()
+                    .child(toolbar)
+                    .child(div().flex_grow().min_h_0().child(pages)),
+            )
+            .into_any_element()
+    }
+
+    fn render_print_section(
+        &self,
+        ix: usize,
+        section: &PrintSection,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let page = section.page;
+        let pages = match section.page_limit {
+            Some(limit) => format!("{} / {limit} pp", section.page_count),
+            None => format!("{} pp", section.page_count),
+        };
+        let indent = 0.75 * section.level.saturating_sub(1) as f32;
+
+        h_flex()
+            .id(ix)
+            .gap_2()
+            .pl(rems(0.25 + indent))
+            .pr_1()
+            .rounded_sm()
+            .cursor_pointer()
+            .hover(|style| style.bg(cx.theme().colors().element_hover))
+            .on_click(cx.listener(move |this, _, _window, cx| this.scroll_to_print_page(page, cx)))
+            .child(
+                div().flex_grow().min_w_0().child(
+                    Label::new(section.title.clone())
+                        .size(LabelSize::Small)
+                        .truncate(),
+                ),
+            )
+            .child(
+                Label::new(pages)
+                    .size(LabelSize::Small)
+                    .color(if section.is_over_limit() {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    }),
+            )
+    }
+
     fn should_apply_padding_between(
         current_block: &ParsedMarkdownElement,
         next_block: Option<&ParsedMarkdownElement>,
@@ -509,11 +834,15 @@ impl Render for MarkdownPreviewView {
         let buffer_size = ThemeSettings::get_global(cx).buffer_font_size(cx);
         let buffer_line_height = ThemeSettings::get_global(cx).buffer_line_height;
 
+        let is_print_layout = self.print_layout.is_some();
+
         v_flex()
             .image_cache(self.image_cache.clone())
             .id("MarkdownPreview")
             .key_context("MarkdownPreview")
             .track_focus(&self.focus_handle(cx))
+            .on_action(cx.listener(Self::toggle_print_layout))
+            .relative()
             .size_full()
             .bg(cx.theme().colors().editor_background)
             .p_4()
@@ -522,7 +851,25 @@ impl Render for MarkdownPreviewView {
             .child(
                 div()
                     .flex_grow()
-                    .map(|this| this.child(list(self.list_state.clone()).size_full())),
+                    .min_h_0()
+                    .map(|this| match &self.print_layout {
+                        Some(state) => this.child(self.render_print_layout(state, cx)),
+                        None => this.child(list(self.list_state.clone()).size_full()),
+                    }),
+            )
+            .child(
+                div().absolute().top_3().right_3().child(
+                    IconButton::new("toggle-print-layout", IconName::FileText)
+                        .icon_size(IconSize::Small)
+                        .toggle_state(is_print_layout)
+                        .tooltip(Tooltip::for_action_title(
+                            "Toggle Print Layout",
+                            &TogglePrintLayout,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_print_layout(&TogglePrintLayout, window, cx)
+                        })),
+                ),
             )
     }
 }
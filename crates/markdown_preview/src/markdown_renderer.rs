@@ -1,14 +1,15 @@
 use crate::markdown_elements::{
-    HeadingLevel, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
-    ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
+    CalloutKind, HeadingLevel, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
+    ParsedMarkdownBlockQuote, ParsedMarkdownCallout, ParsedMarkdownCodeBlock,
+    ParsedMarkdownDefinitionList, ParsedMarkdownElement, ParsedMarkdownFootnoteDefinition,
     ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownTable,
     ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
 };
 use gpui::{
     AbsoluteLength, AnyElement, App, AppContext as _, ClipboardItem, Context, DefiniteLength, Div,
-    Element, ElementId, Entity, HighlightStyle, Hsla, ImageSource, InteractiveText, IntoElement,
-    Keystroke, Length, Modifiers, ParentElement, Render, Resource, SharedString, Styled,
-    StyledText, TextStyle, WeakEntity, Window, div, img, rems,
+    Element, ElementId, Entity, FontWeight, HighlightStyle, Hsla, ImageSource, InteractiveText,
+    IntoElement, Keystroke, Length, Modifiers, ParentElement, Render, Resource, SharedString,
+    Styled, StyledText, TextStyle, WeakEntity, Window, div, img, rems,
 };
 use settings::Settings;
 use std::{
@@ -16,9 +17,9 @@ use std::{
     sync::Arc,
     vec,
 };
-use theme::{ActiveTheme, SyntaxTheme, ThemeSettings};
+use theme::{ActiveTheme, StatusColors, SyntaxTheme, ThemeSettings};
 use ui::{
-    ButtonCommon, Clickable, Color, FluentBuilder, IconButton, IconName, IconSize,
+    ButtonCommon, Clickable, Color, FluentBuilder, Icon, IconButton, IconName, IconSize,
     InteractiveElement, Label, LabelCommon, LabelSize, LinkPreview, Pixels, Rems,
     StatefulInteractiveElement, StyledExt, StyledImage, ToggleState, Tooltip, VisibleOnHover,
     h_flex, relative, tooltip_container, v_flex,
@@ -26,6 +27,17 @@ use ui::{
 use workspace::{OpenOptions, OpenVisible, Workspace};
 
 type CheckboxClickedCallback = Arc<Box<dyn Fn(bool, Range<usize>, &mut Window, &mut App)>>;
+type FootnoteClickedCallback =
+    Arc<Box<dyn Fn(SharedString, FootnoteTarget, &mut Window, &mut App)>>;
+
+/// Where clicking a footnote link should navigate to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FootnoteTarget {
+    /// The footnote's definition, from a reference in the text.
+    Definition,
+    /// The first reference to the footnote, from its definition.
+    Reference,
+}
 
 #[derive(Clone)]
 pub struct RenderContext {
@@ -40,9 +52,12 @@ pub struct RenderContext {
     text_muted_color: Hsla,
     code_block_background_color: Hsla,
     code_span_background_color: Hsla,
+    citation_color: Hsla,
+    status_colors: StatusColors,
     syntax_theme: Arc<SyntaxTheme>,
     indent: usize,
     checkbox_clicked_callback: Option<CheckboxClickedCallback>,
+    footnote_clicked_callback: Option<FootnoteClickedCallback>,
 }
 
 impl RenderContext {
@@ -73,7 +88,10 @@ impl RenderContext {
             text_muted_color: theme.colors().text_muted,
             code_block_background_color: theme.colors().surface_background,
             code_span_background_color: theme.colors().editor_document_highlight_read_background,
+            citation_color: theme.colors().text_accent,
+            status_colors: theme.status().clone(),
             checkbox_clicked_callback: None,
+            footnote_clicked_callback: None,
         }
     }
 
@@ -85,6 +103,14 @@ impl RenderContext {
         self
     }
 
+    pub fn with_footnote_clicked_callback(
+        mut self,
+        callback: impl Fn(SharedString, FootnoteTarget, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.footnote_clicked_callback = Some(Arc::new(Box::new(callback)));
+        self
+    }
+
     fn next_id(&mut self, span: &Range<usize>) -> ElementId {
         let id = format!("markdown-{}-{}-{}", self.next_id, span.start, span.end);
         self.next_id += 1;
@@ -148,7 +174,11 @@ pub fn render_markdown_block(block: &ParsedMarkdownElement, cx: &mut RenderConte
         Table(table) => render_markdown_table(table, cx),
         BlockQuote(block_quote) => render_markdown_block_quote(block_quote, cx),
         CodeBlock(code_block) => render_markdown_code_block(code_block, cx),
+        Callout(callout) => render_markdown_callout(callout, cx),
+        DefinitionList(list) => render_markdown_definition_list(list, cx),
+        FootnoteDefinition(footnote) => render_markdown_footnote_definition(footnote, cx),
         HorizontalRule(_) => render_markdown_rule(cx),
+        PageBreak(_) => render_markdown_page_break(cx),
     }
 }
 
@@ -554,6 +584,115 @@ fn render_markdown_block_quote(
         .into_any()
 }
 
+fn render_markdown_callout(parsed: &ParsedMarkdownCallout, cx: &mut RenderContext) -> AnyElement {
+    let (icon, color) = match parsed.kind {
+        CalloutKind::Note => (IconName::Info, cx.status_colors.info),
+        CalloutKind::Tip => (IconName::Sparkle, cx.status_colors.success),
+        CalloutKind::Important => (IconName::Star, cx.status_colors.hint),
+        CalloutKind::Warning => (IconName::Warning, cx.status_colors.warning),
+        CalloutKind::Caution => (IconName::XCircle, cx.status_colors.error),
+    };
+
+    cx.indent += 1;
+
+    let children: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .map(|child| render_markdown_block(child, cx))
+        .collect();
+
+    cx.indent -= 1;
+
+    let title = h_flex()
+        .gap_1()
+        .pb(cx.scaled_rems(0.5))
+        .text_color(color)
+        .font_weight(FontWeight::SEMIBOLD)
+        .child(
+            Icon::new(icon)
+                .size(IconSize::Small)
+                .color(Color::Custom(color)),
+        )
+        .child(parsed.kind.title());
+
+    cx.with_common_p(div())
+        .child(
+            div()
+                .border_l_4()
+                .border_color(color)
+                .pl_3()
+                .child(title)
+                .children(children),
+        )
+        .into_any()
+}
+
+fn render_markdown_definition_list(
+    parsed: &ParsedMarkdownDefinitionList,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    let mut items = Vec::new();
+
+    for item in &parsed.items {
+        items.push(
+            div()
+                .font_weight(FontWeight::BOLD)
+                .children(render_markdown_text(&item.term, cx))
+                .into_any(),
+        );
+
+        cx.indent += 1;
+        for definition in &item.definitions {
+            let children: Vec<AnyElement> = definition
+                .iter()
+                .map(|child| render_markdown_block(child, cx))
+                .collect();
+            items.push(div().pl(cx.scaled_rems(1.5)).children(children).into_any());
+        }
+        cx.indent -= 1;
+    }
+
+    cx.with_common_p(v_flex()).children(items).into_any()
+}
+
+fn render_markdown_footnote_definition(
+    parsed: &ParsedMarkdownFootnoteDefinition,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    cx.indent += 1;
+
+    let children: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .map(|child| render_markdown_block(child, cx))
+        .collect();
+
+    cx.indent -= 1;
+
+    let back_reference = IconButton::new(cx.next_id(&parsed.source_range), IconName::ArrowUp)
+        .icon_size(IconSize::Small)
+        .tooltip(Tooltip::text("Go to reference"))
+        .when_some(cx.footnote_clicked_callback.clone(), |this, callback| {
+            let label = parsed.label.clone();
+            this.on_click(move |_, window, cx| {
+                callback(label.clone(), FootnoteTarget::Reference, window, cx)
+            })
+        });
+
+    cx.with_common_p(h_flex())
+        .items_start()
+        .gap_2()
+        .text_size(cx.scaled_rems(0.875))
+        .child(
+            div()
+                .text_color(cx.text_muted_color)
+                .child(format!("{}.", parsed.number)),
+        )
+        .child(div().flex_1().children(children))
+        .child(back_reference)
+        .into_any()
+}
+
 fn render_markdown_code_block(
     parsed: &ParsedMarkdownCodeBlock,
     cx: &mut RenderContext,
@@ -614,6 +753,7 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
     let syntax_theme = cx.syntax_theme.clone();
     let workspace_clone = cx.workspace.clone();
     let code_span_bg_color = cx.code_span_background_color;
+    let citation_color = cx.citation_color;
     let text_style = cx.text_style.clone();
 
     for parsed_region in parsed_new {
@@ -637,6 +777,23 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                                         ..Default::default()
                                     },
                                 ))
+                            } else if let Some(Link::Citation(_)) = &region.link {
+                                Some((
+                                    range.clone(),
+                                    HighlightStyle {
+                                        color: Some(citation_color),
+                                        background_color: Some(code_span_bg_color),
+                                        ..Default::default()
+                                    },
+                                ))
+                            } else if let Some(Link::Footnote { .. }) = &region.link {
+                                Some((
+                                    range.clone(),
+                                    HighlightStyle {
+                                        color: Some(citation_color),
+                                        ..Default::default()
+                                    },
+                                ))
                             } else {
                                 None
                             }
@@ -652,6 +809,7 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                     }
                 }
                 let workspace = workspace_clone.clone();
+                let footnote_clicked_callback = cx.footnote_clicked_callback.clone();
                 let element = div()
                     .child(
                         InteractiveText::new(
@@ -692,6 +850,24 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                                         });
                                     }
                                 }
+                                Link::Citation(citation) => {
+                                    if let Some(workspace) =
+                                        workspace.as_ref().and_then(|workspace| workspace.upgrade())
+                                    {
+                                        citations::open_citation(citation, &workspace, window, cx)
+                                            .detach_and_log_err(cx);
+                                    }
+                                }
+                                Link::Footnote { label, .. } => {
+                                    if let Some(callback) = &footnote_clicked_callback {
+                                        callback(
+                                            label.clone(),
+                                            FootnoteTarget::Definition,
+                                            window,
+                                            cx,
+                                        );
+                                    }
+                                }
                             },
                         ),
                     )
@@ -703,6 +879,7 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                 let image_resource = match image.link.clone() {
                     Link::Web { url } => Resource::Uri(url.into()),
                     Link::Path { path, .. } => Resource::Path(Arc::from(path)),
+                    Link::Citation(_) | Link::Footnote { .. } => continue,
                 };
 
                 let element_id = cx.next_id(&image.source_range);
@@ -753,6 +930,7 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                                             });
                                         }
                                     }
+                                    Link::Citation(_) | Link::Footnote { .. } => {}
                                 }
                             }
                         }
@@ -771,6 +949,21 @@ fn render_markdown_rule(cx: &mut RenderContext) -> AnyElement {
     div().py(cx.scaled_rems(0.5)).child(rule).into_any()
 }
 
+fn render_markdown_page_break(cx: &mut RenderContext) -> AnyElement {
+    let rule = || div().flex_1().h(cx.scaled_rems(0.0625)).bg(cx.border_color);
+    h_flex()
+        .py(cx.scaled_rems(0.5))
+        .gap_2()
+        .child(rule())
+        .child(
+            Label::new("Page break")
+                .size(LabelSize::XSmall)
+                .color(Color::Muted),
+        )
+        .child(rule())
+        .into_any()
+}
+
 struct InteractiveMarkdownElementTooltip {
     tooltip_text: Option<SharedString>,
     action_text: String,
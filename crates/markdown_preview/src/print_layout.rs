@@ -0,0 +1,242 @@
+//! Pages for the preview's print layout mode.
+//!
+//! Page layout belongs to the document export crate, which depends on this
+//! one, so it registers itself here as the [`PrintLayoutProvider`] and the
+//! preview draws the pages it lays out.
+
+use anyhow::Result;
+use editor::Editor;
+use gpui::{
+    AnyElement, App, Entity, FontWeight, Global, Hsla, IntoElement, ParentElement, Styled, Task,
+    div, hsla, img, px,
+};
+use std::{ops::Range, path::PathBuf, sync::Arc};
+use ui::FluentBuilder as _;
+
+/// Pixels per point at 100% zoom, for a 96 DPI display.
+const PIXELS_PER_POINT: f32 = 96. / 72.;
+
+/// Where a line of text's baseline falls below its top, as a proportion of
+/// its font size.
+const BASELINE_OFFSET: f32 = 0.85;
+
+/// Lays out the Markdown document an editor holds on pages.
+pub trait PrintLayoutProvider: 'static {
+    fn layout(&self, editor: &Entity<Editor>, cx: &mut App) -> Task<Result<PrintLayout>>;
+}
+
+struct GlobalPrintLayoutProvider(Arc<dyn PrintLayoutProvider>);
+
+impl Global for GlobalPrintLayoutProvider {}
+
+pub fn set_print_layout_provider(provider: impl PrintLayoutProvider, cx: &mut App) {
+    cx.set_global(GlobalPrintLayoutProvider(Arc::new(provider)));
+}
+
+pub(crate) fn print_layout_provider(cx: &App) -> Option<Arc<dyn PrintLayoutProvider>> {
+    cx.try_global::<GlobalPrintLayoutProvider>()
+        .map(|provider| provider.0.clone())
+}
+
+/// A document laid out on pages. Positions and sizes are in points from the
+/// top left of the page.
+#[derive(Debug, Default)]
+pub struct PrintLayout {
+    pub page_width: f32,
+    pub page_height: f32,
+    pub pages: Vec<PrintPage>,
+    pub sections: Vec<PrintSection>,
+    /// The number of pages counted against the document's page limit.
+    pub body_page_count: usize,
+    pub page_limit: Option<usize>,
+}
+
+impl PrintLayout {
+    pub fn is_over_limit(&self) -> bool {
+        self.page_limit
+            .is_some_and(|limit| self.body_page_count > limit)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PrintPage {
+    pub items: Vec<PrintItem>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintColor {
+    Normal,
+    Muted,
+    Link,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PrintItem {
+    /// A run of text in a single font, positioned by its baseline.
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        bold: bool,
+        italic: bool,
+        monospace: bool,
+        color: PrintColor,
+        text: String,
+    },
+    /// A horizontal or vertical rule.
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        /// The gray level, from 0 for black to 1 for white.
+        gray: f32,
+    },
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        gray: f32,
+    },
+    Image {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        path: PathBuf,
+    },
+}
+
+/// A heading's section and the pages it spans.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrintSection {
+    pub level: usize,
+    pub title: String,
+    pub source_range: Range<usize>,
+    /// The index of the page the heading is on.
+    pub page: usize,
+    pub page_count: usize,
+    pub page_limit: Option<usize>,
+}
+
+impl PrintSection {
+    pub fn is_over_limit(&self) -> bool {
+        self.page_limit.is_some_and(|limit| self.page_count > limit)
+    }
+}
+
+/// The size of a page in pixels at the given zoom.
+pub(crate) fn page_size_in_pixels(layout: &PrintLayout, zoom: f32) -> (f32, f32) {
+    let scale = PIXELS_PER_POINT * zoom;
+    (layout.page_width * scale, layout.page_height * scale)
+}
+
+/// Draws a page as it will be printed, in black on white whatever the theme.
+pub(crate) fn render_print_page(layout: &PrintLayout, page: &PrintPage, zoom: f32) -> AnyElement {
+    let scale = PIXELS_PER_POINT * zoom;
+    let (width, height) = page_size_in_pixels(layout, zoom);
+
+    div()
+        .relative()
+        .flex_none()
+        .w(px(width))
+        .h(px(height))
+        .overflow_hidden()
+        .bg(gpui::white())
+        .shadow_md()
+        .children(page.items.iter().map(|item| render_item(item, scale)))
+        .into_any_element()
+}
+
+fn render_item(item: &PrintItem, scale: f32) -> AnyElement {
+    match item {
+        PrintItem::Text {
+            x,
+            y,
+            size,
+            bold,
+            italic,
+            monospace,
+            color,
+            text,
+        } => {
+            let color = match color {
+                PrintColor::Normal => gpui::black(),
+                PrintColor::Muted => gray(0.4),
+                PrintColor::Link => hsla(214. / 360., 0.95, 0.39, 1.),
+            };
+            let family = if *monospace {
+                "Zed Plex Mono"
+            } else {
+                "Zed Plex Sans"
+            };
+            div()
+                .absolute()
+                .left(px(x * scale))
+                .top(px((y - size * BASELINE_OFFSET) * scale))
+                .text_size(px(size * scale))
+                .line_height(px(size * scale))
+                .whitespace_nowrap()
+                .font_family(family)
+                .when(*bold, |this| this.font_weight(FontWeight::BOLD))
+                .when(*italic, |this| this.italic())
+                .text_color(color)
+                .child(text.clone())
+                .into_any_element()
+        }
+        PrintItem::Line {
+            from,
+            to,
+            width,
+            gray: level,
+        } => {
+            // Rules are centred on the line between their ends.
+            let (left, top) = if from.1 == to.1 {
+                (from.0.min(to.0), from.1 - width / 2.)
+            } else {
+                (from.0 - width / 2., from.1.min(to.1))
+            };
+            let line_width = (to.0 - from.0).abs().max(*width);
+            let line_height = (to.1 - from.1).abs().max(*width);
+            div()
+                .absolute()
+                .left(px(left * scale))
+                .top(px(top * scale))
+                .w(px((line_width * scale).max(1.)))
+                .h(px((line_height * scale).max(1.)))
+                .bg(gray(*level))
+                .into_any_element()
+        }
+        PrintItem::Rect {
+            x,
+            y,
+            width,
+            height,
+            gray: level,
+        } => div()
+            .absolute()
+            .left(px(x * scale))
+            .top(px(y * scale))
+            .w(px(width * scale))
+            .h(px(height * scale))
+            .bg(gray(*level))
+            .into_any_element(),
+        PrintItem::Image {
+            x,
+            y,
+            width,
+            height,
+            path,
+        } => img(path.clone())
+            .absolute()
+            .left(px(x * scale))
+            .top(px(y * scale))
+            .w(px(width * scale))
+            .h(px(height * scale))
+            .into_any_element(),
+    }
+}
+
+fn gray(level: f32) -> Hsla {
+    hsla(0., 0., level, 1.)
+}
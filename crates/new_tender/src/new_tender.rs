@@ -0,0 +1,24 @@
+//! Starts a new tender: a wizard that creates the tender's project from a
+//! template, imports the tender documents, writes its `tender.toml` and then
+//! opens it to be indexed and have its requirements extracted.
+
+mod scaffold;
+mod template;
+mod wizard;
+
+use gpui::App;
+use workspace::Workspace;
+use zed_actions::new_tender::StartNewTender;
+
+pub use scaffold::{TenderDetails, scaffold_tender};
+pub use template::{TemplateFile, TenderTemplate, load_templates};
+pub use wizard::NewTenderWizard;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &StartNewTender, window, cx| {
+            NewTenderWizard::toggle(workspace, window, cx);
+        });
+    })
+    .detach();
+}
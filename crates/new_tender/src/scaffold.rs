@@ -0,0 +1,241 @@
+use crate::TenderTemplate;
+use anyhow::{Context as _, Result, anyhow};
+use chrono::{DateTime, FixedOffset};
+use fs::{CopyOptions, Fs};
+use std::fmt::Write as _;
+use std::path::{Component, Path, PathBuf};
+use tender_manifest::{Folders, MANIFEST_FILE_NAME};
+
+/// What's known about a tender when its project is created.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TenderDetails {
+    pub title: String,
+    pub client: String,
+    pub reference: String,
+    pub closes_at: Option<DateTime<FixedOffset>>,
+}
+
+impl TenderDetails {
+    /// Replaces the `{{title}}`, `{{client}}` and `{{reference}}` variables
+    /// in a template's text.
+    fn fill(&self, text: &str) -> String {
+        text.replace("{{title}}", &self.title)
+            .replace("{{client}}", &self.client)
+            .replace("{{reference}}", &self.reference)
+    }
+
+    /// The `tender.toml` for a new tender. Details that aren't known yet are
+    /// left for the manifest's diagnostics to point out: the client and
+    /// reference are left empty, and the closing time is a commented-out
+    /// example.
+    pub fn manifest(&self, template: &TenderTemplate) -> String {
+        let mut manifest = String::new();
+        for (key, value) in [("client", &self.client), ("reference", &self.reference)] {
+            writeln!(manifest, "{key} = {}", toml::Value::from(value.trim())).unwrap();
+        }
+        if !self.title.trim().is_empty() {
+            writeln!(manifest, "title = {}", toml::Value::from(self.title.trim())).unwrap();
+        }
+        match self.closes_at {
+            Some(closes_at) => writeln!(manifest, "closes = {}", closes_at.to_rfc3339()).unwrap(),
+            None => manifest.push_str("# closes = 2026-11-14T14:00:00+11:00\n"),
+        }
+        manifest.push_str(&self.fill(&template.manifest));
+        // Manifests default to the standard template's documents folder, so
+        // only another template's folder needs recording.
+        if Path::new(&template.documents) != Folders::default().documents
+            && !template.manifest.contains("[folders]")
+        {
+            let documents = toml::Value::from(template.documents.as_str());
+            write!(manifest, "\n[folders]\ndocuments = {documents}\n").unwrap();
+        }
+        manifest
+    }
+}
+
+/// Creates a tender project in `root` from a template: its folders, the
+/// tender documents copied into the template's documents folder, the
+/// template's files and a `tender.toml`.
+///
+/// Nothing that already exists is overwritten, so a project can be created
+/// in a folder that already holds some of the tender.
+pub async fn scaffold_tender(
+    fs: &dyn Fs,
+    root: &Path,
+    template: &TenderTemplate,
+    details: &TenderDetails,
+    documents: &[PathBuf],
+) -> Result<()> {
+    fs.create_dir(root)
+        .await
+        .with_context(|| format!("failed to create {}", root.display()))?;
+    for directory in &template.directories {
+        fs.create_dir(&join_relative(root, directory)?).await?;
+    }
+
+    let documents_dir = join_relative(root, &template.documents)?;
+    fs.create_dir(&documents_dir).await?;
+    for document in documents {
+        let file_name = document
+            .file_name()
+            .ok_or_else(|| anyhow!("{} is not a file", document.display()))?;
+        fs.copy_file(
+            document,
+            &documents_dir.join(file_name),
+            CopyOptions {
+                overwrite: false,
+                ignore_if_exists: true,
+            },
+        )
+        .await
+        .with_context(|| format!("failed to import {}", document.display()))?;
+    }
+
+    for file in &template.files {
+        let path = join_relative(root, &file.path)?;
+        if !fs.is_file(&path).await {
+            fs.write(&path, details.fill(&file.contents).as_bytes())
+                .await
+                .with_context(|| format!("failed to create {}", path.display()))?;
+        }
+    }
+
+    let manifest_path = root.join(MANIFEST_FILE_NAME);
+    if !fs.is_file(&manifest_path).await {
+        fs.write(&manifest_path, details.manifest(template).as_bytes())
+            .await
+            .with_context(|| format!("failed to create {MANIFEST_FILE_NAME}"))?;
+    }
+    Ok(())
+}
+
+/// Joins a path from a template onto the tender's folder, refusing any that
+/// would lead outside it.
+fn join_relative(root: &Path, relative: &str) -> Result<PathBuf> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(root.join(relative))
+    } else {
+        Err(anyhow!(
+            "template paths must be inside the tender's folder, but {} isn't",
+            relative.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tender_manifest::TenderManifest;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_scaffold_tender(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/downloads"),
+            json!({ "RFT.pdf": "%PDF", "Pricing Schedule.xlsx": "PK" }),
+        )
+        .await;
+        fs.insert_tree(
+            path!("/tenders/help-desk"),
+            json!({ "response": { "Response.md": "# Our draft\n" } }),
+        )
+        .await;
+
+        let root = Path::new(path!("/tenders/help-desk"));
+        let details = TenderDetails {
+            title: "Managed Help Desk Services".into(),
+            client: "Department of Transport".into(),
+            reference: "DOT-2026-041".into(),
+            closes_at: DateTime::parse_from_rfc3339("2026-11-14T14:00:00+11:00").ok(),
+        };
+        scaffold_tender(
+            fs.as_ref(),
+            root,
+            &TenderTemplate::standard(),
+            &details,
+            &[
+                PathBuf::from(path!("/downloads/RFT.pdf")),
+                PathBuf::from(path!("/downloads/Pricing Schedule.xlsx")),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let mut paths = fs
+            .files()
+            .into_iter()
+            .filter_map(|path| Some(path.strip_prefix(root).ok()?.to_path_buf()))
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "pricing/Pricing Notes.md",
+                "response/Response.md",
+                "rft/Pricing Schedule.xlsx",
+                "rft/RFT.pdf",
+                "tender.toml",
+            ]
+            .map(PathBuf::from)
+        );
+        for directory in ["appendices", "pricing", "response", "rft"] {
+            assert!(fs.is_dir(&root.join(directory)).await);
+        }
+        assert_eq!(
+            fs.load(&root.join("response/Response.md")).await.unwrap(),
+            "# Our draft\n"
+        );
+
+        let manifest = fs.load(&root.join("tender.toml")).await.unwrap();
+        assert!(manifest.starts_with(indoc! {r#"
+            client = "Department of Transport"
+            reference = "DOT-2026-041"
+            title = "Managed Help Desk Services"
+            closes = 2026-11-14T14:00:00+11:00
+        "#}));
+        let parsed = TenderManifest::parse(&manifest);
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        assert_eq!(parsed.manifest.unwrap().closes_at, details.closes_at);
+    }
+
+    #[test]
+    fn test_manifest_documents_folder() {
+        let details = TenderDetails {
+            client: "Acme".into(),
+            reference: "ACME-1".into(),
+            ..Default::default()
+        };
+        let mut template = TenderTemplate::standard();
+        let manifest = TenderManifest::parse(&details.manifest(&template))
+            .manifest
+            .unwrap();
+        assert_eq!(manifest.folders.documents, Path::new("rft"));
+
+        template.documents = "issued".into();
+        let manifest = TenderManifest::parse(&details.manifest(&template))
+            .manifest
+            .unwrap();
+        assert_eq!(manifest.folders.documents, Path::new("issued"));
+    }
+
+    #[test]
+    fn test_join_relative() {
+        let root = Path::new("/tender");
+        assert_eq!(
+            join_relative(root, "response/Response.md").unwrap(),
+            root.join("response/Response.md")
+        );
+        assert!(join_relative(root, "../elsewhere").is_err());
+        assert!(join_relative(root, "/etc").is_err());
+    }
+}
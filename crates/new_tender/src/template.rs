@@ -0,0 +1,157 @@
+use anyhow::{Context as _, Result};
+use assets::Assets;
+use fs::Fs;
+use futures::StreamExt as _;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The template written to the templates directory the first time it's
+/// empty, so that there's one to copy.
+const DEFAULT_TEMPLATE_PATH: &str = "tender_templates/standard.toml";
+
+/// The structure of a new tender project, defined by a TOML file in the
+/// user's tender templates directory.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenderTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The folders to create, relative to the tender's folder.
+    #[serde(default)]
+    pub directories: Vec<String>,
+    /// The folder the tender documents are imported into.
+    #[serde(default = "default_documents_directory")]
+    pub documents: String,
+    /// Text added to the generated `tender.toml`, after the tender's details.
+    #[serde(default)]
+    pub manifest: String,
+    #[serde(default)]
+    pub files: Vec<TemplateFile>,
+}
+
+/// A file to create in a new tender, unless one already exists.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateFile {
+    pub path: String,
+    #[serde(default)]
+    pub contents: String,
+}
+
+fn default_documents_directory() -> String {
+    "rft".into()
+}
+
+impl TenderTemplate {
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// The template that ships with Auto Tender.
+    pub fn standard() -> Self {
+        Self::parse(&util::asset_str::<Assets>(DEFAULT_TEMPLATE_PATH))
+            .expect("the standard tender template is valid")
+    }
+}
+
+/// Loads every template in `dir`, sorted by name, first writing the standard
+/// template there if the directory has none.
+///
+/// Templates that can't be parsed are logged and skipped. If none can be
+/// loaded, the standard template is used.
+pub async fn load_templates(fs: Arc<dyn Fs>, dir: &Path) -> Vec<TenderTemplate> {
+    let mut templates = match read_templates(fs.as_ref(), dir).await {
+        Ok(templates) => templates,
+        Err(error) => {
+            log::error!("failed to load tender templates: {error:#}");
+            Vec::new()
+        }
+    };
+    if templates.is_empty() {
+        templates.push(TenderTemplate::standard());
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+async fn read_templates(fs: &dyn Fs, dir: &Path) -> Result<Vec<TenderTemplate>> {
+    let mut paths = Vec::new();
+    if fs.is_dir(dir).await {
+        let mut entries = fs.read_dir(dir).await?;
+        while let Some(path) = entries.next().await {
+            let path = path?;
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+            {
+                paths.push(path);
+            }
+        }
+    }
+
+    if paths.is_empty() {
+        let standard = util::asset_str::<Assets>(DEFAULT_TEMPLATE_PATH);
+        let path = dir.join("standard.toml");
+        fs.create_dir(dir).await?;
+        fs.write(&path, standard.as_bytes())
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        paths.push(path);
+    }
+
+    let mut templates = Vec::new();
+    for path in paths {
+        let template = fs
+            .load(&path)
+            .await
+            .and_then(|text| TenderTemplate::parse(&text));
+        match template {
+            Ok(template) => templates.push(template),
+            Err(error) => log::error!("failed to load {}: {error:#}", path.display()),
+        }
+    }
+    Ok(templates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_load_templates(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let dir = Path::new(path!("/config/tender_templates"));
+
+        let templates = load_templates(fs.clone(), dir).await;
+        assert_eq!(templates, [TenderTemplate::standard()]);
+        assert_eq!(
+            templates[0].directories,
+            ["rft", "response", "appendices", "pricing"]
+        );
+        assert!(fs.is_file(&dir.join("standard.toml")).await);
+
+        fs.insert_tree(
+            dir,
+            json!({
+                "bid.toml": "name = \"Bid\"\ndirectories = [\"documents\"]\ndocuments = \"documents\"\n",
+                "broken.toml": "name = ",
+            }),
+        )
+        .await;
+        let templates = load_templates(fs.clone(), dir).await;
+        assert_eq!(
+            templates
+                .iter()
+                .map(|template| (template.name.as_str(), template.documents.as_str()))
+                .collect::<Vec<_>>(),
+            [("Bid", "documents"), ("Standard", "rft")]
+        );
+    }
+}
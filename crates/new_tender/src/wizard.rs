@@ -0,0 +1,506 @@
+use crate::{TenderDetails, TenderTemplate, load_templates, scaffold_tender};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone as _};
+use futures::future;
+use gpui::{
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, PathPromptOptions, Task, prelude::*,
+};
+use requirements::ExtractRequirements;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tender_manifest::MANIFEST_FILE_NAME;
+use ui::{KeyBinding, Modal, ModalFooter, ModalHeader, Section, Tooltip, prelude::*};
+use ui_input::SingleLineInput;
+use workspace::{AppState, ModalView, OpenOptions, Workspace};
+
+/// The formats accepted for the closing time, besides RFC 3339. Times
+/// without an offset are taken to be local.
+const CLOSING_TIME_FORMATS: &[&str] = &["%Y-%m-%d %H:%M %:z", "%Y-%m-%d %H:%M"];
+
+/// Creates a tender project from a template, imports its documents and opens
+/// it, ready to be indexed and have its requirements extracted.
+pub struct NewTenderWizard {
+    app_state: Arc<AppState>,
+    title: Entity<SingleLineInput>,
+    client: Entity<SingleLineInput>,
+    reference: Entity<SingleLineInput>,
+    closes: Entity<SingleLineInput>,
+    templates: Vec<TenderTemplate>,
+    selected_template: usize,
+    folder: Option<PathBuf>,
+    documents: Vec<PathBuf>,
+    error: Option<SharedString>,
+    creating: Task<()>,
+    is_creating: bool,
+    _load_templates: Task<()>,
+}
+
+impl NewTenderWizard {
+    pub fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let app_state = workspace.app_state().clone();
+        workspace.toggle_modal(window, cx, |window, cx| Self::new(app_state, window, cx));
+    }
+
+    fn new(app_state: Arc<AppState>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let input = |label: &str, placeholder: &str, window: &mut Window, cx: &mut App| {
+            let (label, placeholder) = (label.to_string(), placeholder.to_string());
+            cx.new(|cx| SingleLineInput::new(window, cx, placeholder).label(label))
+        };
+        let title = input("Title", "Managed Help Desk Services", window, cx);
+        let client = input("Client", "Department of Transport", window, cx);
+        let reference = input("Reference", "DOT-2026-041", window, cx);
+        let closes = input("Closes", "2026-11-14 14:00", window, cx);
+
+        let fs = app_state.fs.clone();
+        let load_templates = cx.spawn(async move |this, cx| {
+            let templates = load_templates(fs, paths::tender_templates_dir()).await;
+            this.update(cx, |this, cx| {
+                this.selected_template = 0;
+                this.templates = templates;
+                cx.notify();
+            })
+            .ok();
+        });
+
+        Self {
+            app_state,
+            title,
+            client,
+            reference,
+            closes,
+            templates: vec![TenderTemplate::standard()],
+            selected_template: 0,
+            folder: None,
+            documents: Vec::new(),
+            error: None,
+            creating: Task::ready(()),
+            is_creating: false,
+            _load_templates: load_templates,
+        }
+    }
+
+    fn choose_folder(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let paths = match paths.await {
+                Ok(Ok(Some(paths))) => paths,
+                Ok(Err(error)) => return Err(error),
+                _ => return Ok(()),
+            };
+            this.update(cx, |this, cx| {
+                this.folder = paths.into_iter().next();
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn add_documents(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let paths = match paths.await {
+                Ok(Ok(Some(paths))) => paths,
+                Ok(Err(error)) => return Err(error),
+                _ => return Ok(()),
+            };
+            this.update(cx, |this, cx| {
+                for path in paths {
+                    if !this.documents.contains(&path) {
+                        this.documents.push(path);
+                    }
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_creating {
+            return;
+        }
+        let Some(root) = self.folder.clone() else {
+            self.error = Some("Choose a folder for the tender".into());
+            cx.notify();
+            return;
+        };
+        let text = |input: &Entity<SingleLineInput>, cx: &App| {
+            input.read(cx).editor().read(cx).text(cx).trim().to_string()
+        };
+        let Ok(closes_at) = parse_closing_time(&text(&self.closes, cx)) else {
+            self.error = Some(
+                "Enter the closing time as YYYY-MM-DD HH:MM, optionally followed by a UTC offset such as +11:00"
+                    .into(),
+            );
+            cx.notify();
+            return;
+        };
+        let details = TenderDetails {
+            title: text(&self.title, cx),
+            client: text(&self.client, cx),
+            reference: text(&self.reference, cx),
+            closes_at,
+        };
+        let template = self.templates[self.selected_template].clone();
+        let documents = self.documents.clone();
+        let app_state = self.app_state.clone();
+
+        self.is_creating = true;
+        self.error = None;
+        self.creating = cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                scaffold_tender(
+                    app_state.fs.as_ref(),
+                    &root,
+                    &template,
+                    &details,
+                    &documents,
+                )
+                .await?;
+                let (workspace, _) = cx
+                    .update(|_, cx| {
+                        workspace::open_paths(
+                            &[root.clone()],
+                            app_state.clone(),
+                            OpenOptions::default(),
+                            cx,
+                        )
+                    })?
+                    .await?;
+                let extract = !documents.is_empty();
+                workspace.update(cx, |workspace, window, cx| {
+                    start_tender(workspace, &root, extract, window, cx)
+                })?;
+                anyhow::Ok(())
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(()) => cx.emit(DismissEvent),
+                    Err(error) => {
+                        this.is_creating = false;
+                        this.error = Some(format!("{error:#}").into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_templates(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_1()
+            .child(Label::new("Template").size(LabelSize::Small))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .flex_wrap()
+                    .children(self.templates.iter().enumerate().map(|(ix, template)| {
+                        let description = template.description.clone();
+                        Button::new(("tender-template", ix), template.name.clone())
+                            .style(ButtonStyle::Filled)
+                            .selected_style(ButtonStyle::Tinted(TintColor::Accent))
+                            .toggle_state(ix == self.selected_template)
+                            .when(!description.is_empty(), |button| {
+                                button.tooltip(Tooltip::text(description))
+                            })
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.selected_template = ix;
+                                cx.notify();
+                            }))
+                    }))
+                    .child(
+                        Button::new("edit-templates", "Edit Templates…")
+                            .style(ButtonStyle::Subtle)
+                            .label_size(LabelSize::Small)
+                            .tooltip(Tooltip::text(
+                                "Each .toml file in the tender templates folder is a template",
+                            ))
+                            .on_click(|_, _, cx| cx.reveal_path(paths::tender_templates_dir())),
+                    ),
+            )
+    }
+
+    fn render_files(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let template = &self.templates[self.selected_template];
+        let folder_label = match &self.folder {
+            Some(folder) => folder.display().to_string(),
+            None => "No folder chosen".into(),
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_between()
+                    .child(
+                        v_flex()
+                            .min_w_0()
+                            .child(Label::new("Folder").size(LabelSize::Small))
+                            .child(
+                                Label::new(folder_label)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .truncate(),
+                            ),
+                    )
+                    .child(
+                        Button::new("choose-folder", "Choose…")
+                            .icon(IconName::Folder)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.choose_folder(window, cx)),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_between()
+                    .child(
+                        v_flex()
+                            .min_w_0()
+                            .child(Label::new("Tender Documents").size(LabelSize::Small))
+                            .child(
+                                Label::new(if self.documents.is_empty() {
+                                    format!(
+                                        "None yet. They'll be copied into {}/.",
+                                        template.documents
+                                    )
+                                } else {
+                                    format!("Copied into {}/:", template.documents)
+                                })
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                            ),
+                    )
+                    .child(
+                        Button::new("add-documents", "Add…")
+                            .icon(IconName::Plus)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.add_documents(window, cx)),
+                            ),
+                    ),
+            )
+            .children(self.documents.iter().enumerate().map(|(ix, document)| {
+                let file_name = document
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                h_flex()
+                    .pl_2()
+                    .gap_1()
+                    .justify_between()
+                    .child(Label::new(file_name).size(LabelSize::Small).truncate())
+                    .child(
+                        IconButton::new(("remove-document", ix), IconName::Close)
+                            .icon_size(IconSize::XSmall)
+                            .icon_color(Color::Muted)
+                            .tooltip(Tooltip::text("Remove"))
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.documents.remove(ix);
+                                cx.notify();
+                            })),
+                    )
+            }))
+    }
+}
+
+/// Opens a new tender's manifest for its details to be checked and, once
+/// the project has been scanned, extracts the requirements from its
+/// documents. The project is indexed for search as soon as it's opened.
+fn start_tender(
+    workspace: &mut Workspace,
+    root: &Path,
+    extract_requirements: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    workspace
+        .open_abs_path(
+            root.join(MANIFEST_FILE_NAME),
+            OpenOptions {
+                visible: Some(workspace::OpenVisible::None),
+                ..Default::default()
+            },
+            window,
+            cx,
+        )
+        .detach_and_log_err(cx);
+
+    if !extract_requirements {
+        return;
+    }
+    let scans = workspace
+        .project()
+        .read(cx)
+        .worktrees(cx)
+        .filter_map(|worktree| Some(worktree.read(cx).as_local()?.scan_complete()))
+        .collect::<Vec<_>>();
+    cx.spawn_in(window, async move |_, cx| {
+        future::join_all(scans).await;
+        cx.update(|window, cx| window.dispatch_action(Box::new(ExtractRequirements), cx))
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Parses the closing time typed into the wizard, which may be empty.
+fn parse_closing_time(text: &str) -> Result<Option<DateTime<FixedOffset>>, ()> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(closes_at) = DateTime::parse_from_rfc3339(text) {
+        return Ok(Some(closes_at));
+    }
+    if let Ok(closes_at) = DateTime::parse_from_str(text, CLOSING_TIME_FORMATS[0]) {
+        return Ok(Some(closes_at));
+    }
+    let naive = NaiveDateTime::parse_from_str(text, CLOSING_TIME_FORMATS[1]).map_err(|_| ())?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|closes_at| Some(closes_at.fixed_offset()))
+        .ok_or(())
+}
+
+impl ModalView for NewTenderWizard {}
+
+impl EventEmitter<DismissEvent> for NewTenderWizard {}
+
+impl Focusable for NewTenderWizard {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.title.focus_handle(cx)
+    }
+}
+
+impl Render for NewTenderWizard {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let can_create = self.folder.is_some() && !self.is_creating;
+
+        div()
+            .elevation_3(cx)
+            .w(rems(36.))
+            .key_context("NewTenderWizard")
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                Modal::new("new-tender", None)
+                    .header(ModalHeader::new().headline("Start New Tender"))
+                    .section(
+                        Section::new().child(
+                            v_flex()
+                                .gap_2()
+                                .child(self.title.clone())
+                                .child(
+                                    h_flex()
+                                        .gap_2()
+                                        .child(div().flex_1().child(self.client.clone()))
+                                        .child(div().flex_1().child(self.reference.clone())),
+                                )
+                                .child(self.closes.clone())
+                                .child(self.render_templates(cx))
+                                .child(self.render_files(cx))
+                                .when_some(self.error.clone(), |this, error| {
+                                    this.child(
+                                        Label::new(error)
+                                            .size(LabelSize::Small)
+                                            .color(Color::Error),
+                                    )
+                                }),
+                        ),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("cancel", "Cancel")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Cancel,
+                                                &focus_handle,
+                                                window,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.cancel(&menu::Cancel, window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new(
+                                        "create-tender",
+                                        if self.is_creating {
+                                            "Creating…"
+                                        } else {
+                                            "Create Tender"
+                                        },
+                                    )
+                                    .disabled(!can_create)
+                                    .key_binding(
+                                        KeyBinding::for_action_in(
+                                            &menu::Confirm,
+                                            &focus_handle,
+                                            window,
+                                            cx,
+                                        )
+                                        .map(|kb| kb.size(rems_from_px(12.))),
+                                    )
+                                    .when(self.folder.is_none(), |button| {
+                                        button.tooltip(Tooltip::text("Choose a folder first"))
+                                    })
+                                    .on_click(cx.listener(
+                                        |this, _, window, cx| {
+                                            this.confirm(&menu::Confirm, window, cx)
+                                        },
+                                    )),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_closing_time() {
+        assert_eq!(parse_closing_time(" "), Ok(None));
+        let expected = DateTime::parse_from_rfc3339("2026-11-14T14:00:00+11:00").ok();
+        assert_eq!(
+            parse_closing_time("2026-11-14T14:00:00+11:00"),
+            Ok(expected)
+        );
+        assert_eq!(parse_closing_time("2026-11-14 14:00 +11:00"), Ok(expected));
+        assert_eq!(
+            parse_closing_time("2026-11-14 14:00")
+                .unwrap()
+                .map(|closes_at| closes_at.naive_local().to_string()),
+            Some("2026-11-14 14:00:00".to_string())
+        );
+        assert_eq!(parse_closing_time("14 November"), Err(()));
+    }
+}
@@ -225,6 +225,14 @@ pub fn snippets_dir() -> &'static PathBuf {
     SNIPPETS_DIR.get_or_init(|| config_dir().join("snippets"))
 }
 
+/// Returns the path to the dictionaries directory.
+///
+/// This is where Hunspell dictionaries used for spell checking are looked up first.
+pub fn dictionaries_dir() -> &'static PathBuf {
+    static DICTIONARIES_DIR: OnceLock<PathBuf> = OnceLock::new();
+    DICTIONARIES_DIR.get_or_init(|| config_dir().join("dictionaries"))
+}
+
 /// Returns the path to the contexts directory.
 ///
 /// This is where the saved contexts from the Assistant are stored.
@@ -253,6 +261,29 @@ pub fn prompts_dir() -> &'static PathBuf {
     })
 }
 
+/// Returns the path to the answers directory.
+///
+/// This is where the answer library's approved boilerplate is stored.
+pub fn answers_dir() -> &'static PathBuf {
+    static ANSWERS_DIR: OnceLock<PathBuf> = OnceLock::new();
+    ANSWERS_DIR.get_or_init(|| {
+        if cfg!(target_os = "macos") {
+            config_dir().join("answers")
+        } else {
+            data_dir().join("answers")
+        }
+    })
+}
+
+/// Returns the path to the tender templates directory.
+///
+/// This is where the project structures offered when starting a new tender
+/// are defined.
+pub fn tender_templates_dir() -> &'static PathBuf {
+    static TENDER_TEMPLATES_DIR: OnceLock<PathBuf> = OnceLock::new();
+    TENDER_TEMPLATES_DIR.get_or_init(|| config_dir().join("tender_templates"))
+}
+
 /// Returns the path to the prompt templates directory.
 ///
 /// This is where the prompt templates for core features can be overridden with templates.
@@ -354,6 +385,11 @@ pub fn local_tasks_file_relative_path() -> &'static Path {
     Path::new(".editor/tasks.json")
 }
 
+/// Returns the relative path to a `dictionary.txt` file within a project.
+pub fn local_dictionary_file_relative_path() -> &'static Path {
+    Path::new(".editor/dictionary.txt")
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static Path {
     Path::new(".vscode/tasks.json")
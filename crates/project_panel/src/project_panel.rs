@@ -194,6 +194,7 @@ actions!(
         ExpandSelectedEntry,
         CollapseSelectedEntry,
         CollapseAllEntries,
+        CompareDocuments,
         NewDirectory,
         NewFile,
         Copy,
@@ -736,6 +737,7 @@ impl ProjectPanel {
             let is_read_only = project.is_read_only(cx);
             let is_remote = project.is_via_collab();
             let is_local = project.is_local();
+            let can_compare = is_local && self.marked_file_pair(cx).is_some();
 
             let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
                 menu.context(self.focus_handle.clone()).map(|menu| {
@@ -767,6 +769,10 @@ impl ProjectPanel {
                             .when(is_foldable, |menu| {
                                 menu.action("Fold Directory", Box::new(FoldDirectory))
                             })
+                            .when(can_compare, |menu| {
+                                menu.separator()
+                                    .action("Compare Documents", Box::new(CompareDocuments))
+                            })
                             .separator()
                             .action("Cut", Box::new(Cut))
                             .action("Copy", Box::new(Copy))
@@ -2416,6 +2422,41 @@ impl ProjectPanel {
         }
     }
 
+    fn compare_documents(
+        &mut self,
+        _: &CompareDocuments,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((a, b)) = self.marked_file_pair(cx) else {
+            return;
+        };
+        let (old_path, new_path) = document_compare::issue_order(a, b);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                document_compare::open_comparison(workspace, old_path, new_path, window, cx)
+            })
+            .ok();
+    }
+
+    /// The two files to act on, when exactly two files are marked.
+    fn marked_file_pair(&self, cx: &App) -> Option<(ProjectPath, ProjectPath)> {
+        let entries = self.effective_entries();
+        if entries.len() != 2 {
+            return None;
+        }
+        let project = self.project.read(cx);
+        let mut paths = entries.iter().filter_map(|selection| {
+            let worktree = project.worktree_for_id(selection.worktree_id, cx)?;
+            let entry = worktree.read(cx).entry_for_id(selection.entry_id)?;
+            entry.is_file().then(|| ProjectPath {
+                worktree_id: selection.worktree_id,
+                path: entry.path.clone(),
+            })
+        });
+        Some((paths.next()?, paths.next()?))
+    }
+
     fn open_in_terminal(
         &mut self,
         _: &OpenInTerminal,
@@ -4708,6 +4749,7 @@ impl Render for ProjectPanel {
                     el.on_action(cx.listener(Self::reveal_in_finder))
                         .on_action(cx.listener(Self::open_system))
                         .on_action(cx.listener(Self::open_in_terminal))
+                        .on_action(cx.listener(Self::compare_documents))
                 })
                 .when(project.is_via_ssh(), |el| {
                     el.on_action(cx.listener(Self::open_in_terminal))
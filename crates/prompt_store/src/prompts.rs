@@ -26,13 +26,20 @@ pub struct ProjectContext {
     pub user_rules: Vec<UserRulesContext>,
     /// `!user_rules.is_empty()` - provided as a field because handlebars can't do this.
     pub has_user_rules: bool,
+    pub tender_manifest: Option<TenderManifestContext>,
+    pub clarifications: Option<ClarificationsContext>,
     pub os: String,
     pub arch: String,
     pub shell: String,
 }
 
 impl ProjectContext {
-    pub fn new(worktrees: Vec<WorktreeContext>, default_user_rules: Vec<UserRulesContext>) -> Self {
+    pub fn new(
+        worktrees: Vec<WorktreeContext>,
+        default_user_rules: Vec<UserRulesContext>,
+        tender_manifest: Option<TenderManifestContext>,
+        clarifications: Option<ClarificationsContext>,
+    ) -> Self {
         let has_rules = worktrees
             .iter()
             .any(|worktree| worktree.rules_file.is_some());
@@ -41,6 +48,8 @@ impl ProjectContext {
             has_rules,
             has_user_rules: !default_user_rules.is_empty(),
             user_rules: default_user_rules,
+            tender_manifest,
+            clarifications,
             os: std::env::consts::OS.to_string(),
             arch: std::env::consts::ARCH.to_string(),
             shell: get_system_shell(),
@@ -87,6 +96,22 @@ pub struct RulesFileContext {
     pub project_entry_id: usize,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct TenderManifestContext {
+    /// The manifest's path, starting with its worktree's root name.
+    pub path: String,
+    /// A Markdown list of the tender's details, limits and weightings.
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClarificationsContext {
+    /// The register's path, starting with its worktree's root name.
+    pub path: String,
+    /// The answered clarifications, as Markdown.
+    pub answers: String,
+}
+
 #[derive(Serialize)]
 pub struct ContentPromptDiagnosticContext {
     pub line_number: usize,
@@ -466,7 +491,7 @@ mod test {
             title: Some("Rules title".into()),
             contents: "Rules contents".into(),
         }];
-        let project_context = ProjectContext::new(worktrees, default_user_rules);
+        let project_context = ProjectContext::new(worktrees, default_user_rules, None, None);
         let model_context = ModelContext {
             available_tools: ["grep".into()].to_vec(),
         };
@@ -480,6 +505,68 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_assistant_system_prompt_includes_tender_manifest() {
+        let worktrees = vec![WorktreeContext {
+            root_name: "tender".into(),
+            rules_file: None,
+        }];
+        let tender_manifest = TenderManifestContext {
+            path: "tender/tender.toml".into(),
+            summary: "- Client: Department of Transport & Main Roads\n".into(),
+        };
+        let model_context = ModelContext {
+            available_tools: Vec::new(),
+        };
+        let prompt_builder = PromptBuilder::new(None).unwrap();
+
+        let project_context =
+            ProjectContext::new(worktrees.clone(), Vec::new(), Some(tender_manifest), None);
+        let prompt = prompt_builder
+            .generate_assistant_system_prompt(&project_context, &model_context)
+            .unwrap();
+        assert!(prompt.contains("`tender/tender.toml`"));
+        assert!(prompt.contains("- Client: Department of Transport & Main Roads"));
+
+        let project_context = ProjectContext::new(worktrees, Vec::new(), None, None);
+        let prompt = prompt_builder
+            .generate_assistant_system_prompt(&project_context, &model_context)
+            .unwrap();
+        assert!(!prompt.contains("tender.toml"));
+    }
+
+    #[test]
+    fn test_assistant_system_prompt_includes_clarifications() {
+        let worktrees = vec![WorktreeContext {
+            root_name: "tender".into(),
+            rules_file: None,
+        }];
+        let clarifications = ClarificationsContext {
+            path: "tender/clarifications.json".into(),
+            answers:
+                "### QA-001 (Q12), published 2026-10-02\n\n**Answer:** Reports are due weekly.\n"
+                    .into(),
+        };
+        let model_context = ModelContext {
+            available_tools: Vec::new(),
+        };
+        let prompt_builder = PromptBuilder::new(None).unwrap();
+
+        let project_context =
+            ProjectContext::new(worktrees.clone(), Vec::new(), None, Some(clarifications));
+        let prompt = prompt_builder
+            .generate_assistant_system_prompt(&project_context, &model_context)
+            .unwrap();
+        assert!(prompt.contains("`tender/clarifications.json`"));
+        assert!(prompt.contains("**Answer:** Reports are due weekly."));
+
+        let project_context = ProjectContext::new(worktrees, Vec::new(), None, None);
+        let prompt = prompt_builder
+            .generate_assistant_system_prompt(&project_context, &model_context)
+            .unwrap();
+        assert!(!prompt.contains("clarifications.json"));
+    }
+
     #[test]
     fn test_assistant_system_prompt_depends_on_enabled_tools() {
         let worktrees = vec![WorktreeContext {
@@ -487,7 +574,7 @@ mod test {
             rules_file: None,
         }];
         let default_user_rules = vec![];
-        let project_context = ProjectContext::new(worktrees, default_user_rules);
+        let project_context = ProjectContext::new(worktrees, default_user_rules, None, None);
         let prompt_builder = PromptBuilder::new(None).unwrap();
 
         // When the `grep` tool is enabled, it should be mentioned in the prompt
@@ -0,0 +1,98 @@
+use gpui::{App, Entity};
+use language::{
+    Buffer, BufferSnapshot, Diagnostic, DiagnosticEntry, DiagnosticSet, LanguageServerId,
+};
+use std::ops::Range;
+
+/// A producer of diagnostics that runs in-process rather than in a language
+/// server.
+///
+/// Buffers key their diagnostics by the server that produced them, so each
+/// source claims an id from the top of the id space, where no real language
+/// server will ever be allocated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DiagnosticSource {
+    Spelling,
+    Grammar,
+    TenderManifest,
+    WordLimit,
+    Citation,
+    CrossReference,
+}
+
+impl DiagnosticSource {
+    pub fn server_id(self) -> LanguageServerId {
+        LanguageServerId(usize::MAX - self as usize)
+    }
+
+    /// The name shown alongside the diagnostic message, also stored in
+    /// [`Diagnostic::source`] so that entries can be traced back to us.
+    pub fn name(self) -> &'static str {
+        match self {
+            DiagnosticSource::Spelling => "spelling",
+            DiagnosticSource::Grammar => "grammar",
+            DiagnosticSource::TenderManifest => "tender manifest",
+            DiagnosticSource::WordLimit => "limit",
+            DiagnosticSource::Citation => "citation",
+            DiagnosticSource::CrossReference => "cross-reference",
+        }
+    }
+
+    pub fn is_source_of(self, diagnostic: &Diagnostic) -> bool {
+        diagnostic.source.as_deref() == Some(self.name())
+    }
+}
+
+/// Replaces all diagnostics from `source` on `buffer`.
+///
+/// The ranges are byte offsets into `snapshot`, which may be older than the
+/// buffer's current contents; they are anchored against it so that edits made
+/// while the diagnostics were being computed are accounted for.
+pub fn publish_diagnostics(
+    buffer: &Entity<Buffer>,
+    source: DiagnosticSource,
+    snapshot: &BufferSnapshot,
+    mut diagnostics: Vec<(Range<usize>, Diagnostic)>,
+    cx: &mut App,
+) {
+    diagnostics.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+    let entries = diagnostics
+        .into_iter()
+        .enumerate()
+        .map(|(group_id, (range, diagnostic))| DiagnosticEntry {
+            range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+            diagnostic: Diagnostic {
+                source: Some(source.name().to_string()),
+                group_id,
+                is_primary: true,
+                ..diagnostic
+            },
+        })
+        .collect::<Vec<_>>();
+
+    buffer.update(cx, |buffer, cx| {
+        let set = DiagnosticSet::from_sorted_entries(entries, &buffer.text_snapshot());
+        buffer.update_diagnostics(source.server_id(), set, cx);
+    });
+}
+
+/// Removes all diagnostics from `source` on `buffer`.
+pub fn clear_diagnostics(buffer: &Entity<Buffer>, source: DiagnosticSource, cx: &mut App) {
+    buffer.update(cx, |buffer, cx| {
+        if buffer.get_diagnostics(source.server_id()).is_some() {
+            let set = DiagnosticSet::new([], &buffer.text_snapshot());
+            buffer.update_diagnostics(source.server_id(), set, cx);
+        }
+    });
+}
+
+/// Returns the diagnostics from `source` that intersect `range`.
+pub fn diagnostics_in_range(
+    snapshot: &BufferSnapshot,
+    source: DiagnosticSource,
+    range: Range<usize>,
+) -> impl '_ + Iterator<Item = DiagnosticEntry<usize>> {
+    snapshot
+        .diagnostics_in_range::<_, usize>(range, false)
+        .filter(move |entry| source.is_source_of(&entry.diagnostic))
+}
@@ -0,0 +1,450 @@
+//! Helpers shared by the writing tools that treat Markdown buffers as prose.
+//!
+//! Spell checking, grammar checking and word counting all care about the text
+//! a writer actually wrote, not code spans, HTML or URLs, and all of them
+//! report their findings as buffer diagnostics without a language server.
+
+mod diagnostics;
+
+use language::Buffer;
+use linkify::LinkFinder;
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub use diagnostics::*;
+
+/// The Markdown extensions that prose is parsed with.
+pub const PARSE_OPTIONS: Options = Options::ENABLE_TABLES
+    .union(Options::ENABLE_FOOTNOTES)
+    .union(Options::ENABLE_STRIKETHROUGH)
+    .union(Options::ENABLE_TASKLISTS)
+    .union(Options::ENABLE_HEADING_ATTRIBUTES)
+    .union(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS)
+    .union(Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS);
+
+/// Returns whether the buffer should be treated as Markdown prose.
+pub fn is_markdown(buffer: &Buffer) -> bool {
+    if let Some(language) = buffer.language() {
+        return language.name() == "Markdown".into();
+    }
+    buffer
+        .file()
+        .and_then(|file| file.path().extension())
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| {
+            matches!(extension.to_ascii_lowercase().as_str(), "md" | "markdown")
+        })
+}
+
+/// A leaf block of Markdown prose: a paragraph, heading, table cell or the
+/// text of a tight list item.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProseBlock {
+    /// From the start of the block's first text node to the end of its last.
+    pub range: Range<usize>,
+    /// The prose inside `range`.
+    pub prose_ranges: Vec<Range<usize>>,
+    /// Inline code, autolinks and bare URLs inside `range`. These aren't
+    /// prose, but stand in for a word when the block is read as a sentence.
+    pub placeholder_ranges: Vec<Range<usize>>,
+}
+
+/// Returns the prose blocks of a Markdown document, in document order.
+///
+/// Code blocks, HTML blocks and front matter never contribute to a block.
+pub fn prose_blocks(text: &str) -> Vec<ProseBlock> {
+    let mut blocks: Vec<ProseBlock> = Vec::new();
+    // The block index of each open container, assigned on its first text node.
+    let mut containers: Vec<Option<usize>> = Vec::new();
+    let mut excluded_depth = 0;
+    let mut link_stack = Vec::new();
+
+    for (event, range) in Parser::new_ext(text, PARSE_OPTIONS).into_offset_iter() {
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::CodeBlock(_) | Tag::HtmlBlock | Tag::MetadataBlock(_) => {
+                    excluded_depth += 1;
+                }
+                Tag::Paragraph | Tag::Heading { .. } | Tag::TableCell | Tag::Item => {
+                    containers.push(None);
+                }
+                Tag::Link { link_type, .. } => {
+                    let is_autolink = matches!(link_type, LinkType::Autolink | LinkType::Email);
+                    if is_autolink {
+                        if excluded_depth == 0 {
+                            let block = current_block(&mut blocks, &mut containers, &range);
+                            block.placeholder_ranges.push(range);
+                        }
+                        excluded_depth += 1;
+                    }
+                    link_stack.push(is_autolink);
+                }
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::CodeBlock | TagEnd::HtmlBlock | TagEnd::MetadataBlock(_) => {
+                    excluded_depth -= 1;
+                }
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::TableCell | TagEnd::Item => {
+                    containers.pop();
+                }
+                TagEnd::Link => {
+                    if link_stack.pop().unwrap_or(false) {
+                        excluded_depth -= 1;
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(_) if excluded_depth == 0 => {
+                let block = current_block(&mut blocks, &mut containers, &range);
+                push_range(&mut block.prose_ranges, range);
+            }
+            Event::Code(_) if excluded_depth == 0 => {
+                let block = current_block(&mut blocks, &mut containers, &range);
+                block.placeholder_ranges.push(range);
+            }
+            _ => {}
+        }
+    }
+
+    for block in &mut blocks {
+        let links = remove_links(text, &mut block.prose_ranges);
+        block.placeholder_ranges.extend(links);
+        block.placeholder_ranges.sort_by_key(|range| range.start);
+    }
+    blocks.sort_by_key(|block| block.range.start);
+    blocks
+}
+
+fn current_block<'a>(
+    blocks: &'a mut Vec<ProseBlock>,
+    containers: &mut Vec<Option<usize>>,
+    range: &Range<usize>,
+) -> &'a mut ProseBlock {
+    let ix = match containers.last_mut() {
+        Some(Some(ix)) => *ix,
+        container => {
+            let ix = blocks.len();
+            blocks.push(ProseBlock {
+                range: range.clone(),
+                ..Default::default()
+            });
+            if let Some(container) = container {
+                *container = Some(ix);
+            }
+            ix
+        }
+    };
+    let block = &mut blocks[ix];
+    block.range.end = block.range.end.max(range.end);
+    block
+}
+
+/// Returns the byte ranges of `text` that contain prose: Markdown text nodes
+/// outside of code, HTML, front matter and autolinks, with bare URLs and
+/// email addresses cut out.
+///
+/// Adjacent text nodes are coalesced, so a word split across several parser
+/// events is still reported as a single range.
+pub fn prose_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = prose_blocks(text)
+        .into_iter()
+        .flat_map(|block| block.prose_ranges)
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+    if let Some(last) = ranges.last_mut() {
+        if last.end == range.start {
+            last.end = range.end;
+            return;
+        }
+    }
+    ranges.push(range);
+}
+
+/// Cuts bare URLs and email addresses out of `ranges`, returning their ranges.
+fn remove_links(text: &str, ranges: &mut Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    let mut links = Vec::new();
+    let mut result = Vec::with_capacity(ranges.len());
+    for range in ranges.drain(..) {
+        let mut start = range.start;
+        for link in finder.links(&text[range.clone()]) {
+            let link_start = range.start + link.start();
+            if link_start > start {
+                result.push(start..link_start);
+            }
+            start = range.start + link.end();
+            links.push(link_start..start);
+        }
+        if start < range.end {
+            result.push(start..range.end);
+        }
+    }
+    *ranges = result;
+    links
+}
+
+/// Returns the byte ranges of the words inside the given prose ranges, as
+/// defined by Unicode word boundaries. Numbers count as words.
+pub fn words<'a>(
+    text: &'a str,
+    prose_ranges: &'a [Range<usize>],
+) -> impl 'a + Iterator<Item = Range<usize>> {
+    prose_ranges.iter().flat_map(move |range| {
+        text[range.clone()]
+            .unicode_word_indices()
+            .map(move |(offset, word)| {
+                let start = range.start + offset;
+                start..start + word.len()
+            })
+    })
+}
+
+/// Word and character counts for a stretch of prose.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextStats {
+    pub words: usize,
+    pub characters: usize,
+}
+
+/// Counts the words and characters of the prose that falls inside `range`.
+pub fn text_stats(text: &str, prose_ranges: &[Range<usize>], range: Range<usize>) -> TextStats {
+    let clipped = prose_ranges
+        .iter()
+        .filter_map(|prose_range| {
+            let start = prose_range.start.max(range.start);
+            let end = prose_range.end.min(range.end);
+            (start < end).then_some(start..end)
+        })
+        .collect::<Vec<_>>();
+    TextStats {
+        words: words(text, &clipped).count(),
+        characters: clipped
+            .iter()
+            .map(|range| text[range.clone()].chars().count())
+            .sum(),
+    }
+}
+
+/// A heading and the content beneath it, up to the next heading of the same
+/// or a higher level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    pub title: String,
+    pub level: u8,
+    pub heading_range: Range<usize>,
+    pub range: Range<usize>,
+    /// Counts for the section's content, excluding the heading itself but
+    /// including any subsections.
+    pub stats: TextStats,
+}
+
+/// Returns the sections of a Markdown document, in document order.
+pub fn sections(text: &str) -> Vec<Section> {
+    let mut headings = Vec::new();
+    let mut current = None;
+    for (event, range) in Parser::new_ext(text, PARSE_OPTIONS).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some((level as u8, String::new(), range));
+            }
+            Event::Text(fragment) | Event::Code(fragment) => {
+                if let Some((_, title, _)) = current.as_mut() {
+                    title.push_str(&fragment);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => headings.extend(current.take()),
+            _ => {}
+        }
+    }
+
+    let prose_ranges = prose_ranges(text);
+    headings
+        .iter()
+        .enumerate()
+        .map(|(ix, (level, title, heading_range))| {
+            let end = headings[ix + 1..]
+                .iter()
+                .find(|(next_level, _, _)| next_level <= level)
+                .map_or(text.len(), |(_, _, next_range)| next_range.start);
+            Section {
+                title: title.trim().to_string(),
+                level: *level,
+                heading_range: heading_range.clone(),
+                range: heading_range.start..end,
+                stats: text_stats(text, &prose_ranges, heading_range.end..end),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn prose(text: &str) -> Vec<&str> {
+        prose_ranges(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    fn word_list(text: &str) -> Vec<&str> {
+        let ranges = prose_ranges(text);
+        words(text, &ranges).map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn test_prose_ranges_skip_code_and_html() {
+        let text = indoc! {"
+            ---
+            title: Draft
+            ---
+            # Executive Summary
+
+            We deliver `cargo build` outcomes.
+
+            ```rust
+            fn main() {}
+            ```
+
+            <div>ignored</div>
+        "};
+        assert_eq!(
+            prose(text),
+            vec!["Executive Summary", "We deliver ", " outcomes."]
+        );
+    }
+
+    #[test]
+    fn test_prose_ranges_skip_urls() {
+        let text =
+            "See <https://example.com> or www.example.com/path and email bids@example.com today.";
+        assert_eq!(prose(text), vec!["See ", " or ", " and email ", " today."]);
+
+        let text = "Read the [tender portal](https://tenders.example.com) now.";
+        assert_eq!(prose(text), vec!["Read the ", "tender portal", " now."]);
+    }
+
+    #[test]
+    fn test_prose_blocks() {
+        let text = indoc! {"
+            # Scope
+
+            We use `cargo` and <https://example.com> daily.
+
+            - Tight item
+              - Nested item
+            - Second item
+
+            | Clause | Response |
+            |--------|----------|
+            | 4.1    | Complies |
+        "};
+        let blocks = prose_blocks(text)
+            .into_iter()
+            .map(|block| {
+                (
+                    &text[block.range],
+                    block
+                        .placeholder_ranges
+                        .into_iter()
+                        .map(|range| &text[range])
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            blocks,
+            vec![
+                ("Scope", vec![]),
+                (
+                    "We use `cargo` and <https://example.com> daily.",
+                    vec!["`cargo`", "<https://example.com>"]
+                ),
+                ("Tight item", vec![]),
+                ("Nested item", vec![]),
+                ("Second item", vec![]),
+                ("Clause", vec![]),
+                ("Response", vec![]),
+                ("4.1", vec![]),
+                ("Complies", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let text = indoc! {"
+            Preamble is not part of any section.
+
+            # Response to `RFT-42`
+
+            Our team has delivered similar work.
+
+            ## Methodology
+
+            We follow a staged approach.
+
+            ## Pricing
+
+            Fixed price.
+
+            # Appendix
+        "};
+        let sections = sections(text)
+            .into_iter()
+            .map(|section| (section.level, section.title, section.stats.words))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            vec![
+                (1, "Response to RFT-42".to_string(), 15),
+                (2, "Methodology".to_string(), 5),
+                (2, "Pricing".to_string(), 2),
+                (1, "Appendix".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_stats() {
+        let text = "Our *bid* is `final`.";
+        let ranges = prose_ranges(text);
+        assert_eq!(
+            text_stats(text, &ranges, 0..text.len()),
+            TextStats {
+                words: 3,
+                characters: 12,
+            }
+        );
+        assert_eq!(
+            text_stats(text, &ranges, 0..3),
+            TextStats {
+                words: 1,
+                characters: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            word_list("We'll submit 3 copies by 5pm, *per* clause 4.2."),
+            vec![
+                "We'll", "submit", "3", "copies", "by", "5pm", "per", "clause", "4.2"
+            ]
+        );
+    }
+}
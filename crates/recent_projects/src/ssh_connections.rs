@@ -595,6 +595,9 @@ pub async fn open_ssh_project(
             cx.new(|cx| {
                 let mut workspace = Workspace::new(None, project, app_state.clone(), window, cx);
                 workspace.centered_layout = workspace_position.centered_layout;
+                if let Some(focus_mode) = workspace_position.focus_mode {
+                    workspace.apply_focus_mode(focus_mode, cx);
+                }
                 workspace
             })
         })?
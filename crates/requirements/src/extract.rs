@@ -0,0 +1,356 @@
+use crate::Priority;
+use unicode_segmentation::UnicodeSegmentation as _;
+
+/// Phrases that make a sentence a mandatory requirement.
+const MANDATORY_PHRASES: &[&str] = &[
+    "must",
+    "shall",
+    "is required to",
+    "are required to",
+    "will be required to",
+    "mandatory",
+];
+
+/// Phrases that make a sentence a desirable requirement, unless it also
+/// contains a mandatory phrase.
+const DESIRABLE_PHRASES: &[&str] = &[
+    "should",
+    "desirable",
+    "preferred",
+    "preferably",
+    "is encouraged to",
+    "are encouraged to",
+    "ideally",
+];
+
+/// Sentences shorter than this are headings or fragments, not requirements.
+const MIN_REQUIREMENT_WORDS: usize = 4;
+
+/// A requirement found in a single tender document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtractedRequirement {
+    pub clause: Option<String>,
+    pub text: String,
+    pub priority: Priority,
+    /// The 1-based page the requirement starts on. Only known for documents
+    /// converted from paginated formats, whose pages are separated by form
+    /// feeds.
+    pub page: Option<u32>,
+}
+
+/// Extracts the requirements from the Markdown text of a converted tender
+/// document.
+///
+/// Each sentence containing a mandatory or desirable phrase is a requirement.
+/// It is attributed to the most recent numbered clause: a heading that starts
+/// with a number, or a line that starts with a multi-level number such as
+/// `3.2.1`.
+pub fn extract_requirements(text: &str) -> Vec<ExtractedRequirement> {
+    let paginated = text.contains('\x0c');
+    let mut requirements = Vec::new();
+    let mut clause = None;
+    let mut paragraph = String::new();
+    let mut paragraph_page = 1;
+    let mut page = 1;
+
+    for line in text.split('\n') {
+        let page_breaks = line.matches('\x0c').count() as u32;
+        let line = line.replace('\x0c', "");
+        let (line_clause, content, starts_block) = parse_line(&line);
+
+        if starts_block || line_clause.is_some() {
+            flush_paragraph(
+                &mut paragraph,
+                clause.as_deref(),
+                paginated.then_some(paragraph_page),
+                &mut requirements,
+            );
+        }
+        if let Some(line_clause) = line_clause {
+            clause = Some(line_clause);
+        }
+        if !content.is_empty() {
+            if paragraph.is_empty() {
+                paragraph_page = page;
+            } else {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(&content);
+        }
+        page += page_breaks;
+    }
+    flush_paragraph(
+        &mut paragraph,
+        clause.as_deref(),
+        paginated.then_some(paragraph_page),
+        &mut requirements,
+    );
+
+    requirements
+}
+
+fn flush_paragraph(
+    paragraph: &mut String,
+    clause: Option<&str>,
+    page: Option<u32>,
+    requirements: &mut Vec<ExtractedRequirement>,
+) {
+    for sentence in paragraph.unicode_sentences() {
+        let text = sentence.trim();
+        if text.unicode_words().count() < MIN_REQUIREMENT_WORDS {
+            continue;
+        }
+        if let Some(priority) = classify(text) {
+            requirements.push(ExtractedRequirement {
+                clause: clause.map(Into::into),
+                text: text.into(),
+                priority,
+                page,
+            });
+        }
+    }
+    paragraph.clear();
+}
+
+fn classify(sentence: &str) -> Option<Priority> {
+    let words = sentence
+        .unicode_words()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    let contains = |phrase: &str| {
+        let phrase = phrase.split(' ').collect::<Vec<_>>();
+        words.windows(phrase.len()).any(|window| window == phrase)
+    };
+    if MANDATORY_PHRASES.iter().any(|phrase| contains(phrase)) {
+        Some(Priority::Mandatory)
+    } else if DESIRABLE_PHRASES.iter().any(|phrase| contains(phrase)) {
+        Some(Priority::Desirable)
+    } else {
+        None
+    }
+}
+
+/// Returns the clause number a line of a converted document introduces, if
+/// any, and whether the line starts a new block rather than continuing the
+/// paragraph before it.
+pub fn classify_line(line: &str) -> (Option<String>, bool) {
+    let (clause, _, starts_block) = parse_line(line);
+    (clause, starts_block)
+}
+
+/// Splits a line into the clause number it introduces, its prose content,
+/// and whether it starts a new block (a blank line, heading, list item or
+/// table row).
+fn parse_line(line: &str) -> (Option<String>, String, bool) {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return (None, String::new(), true);
+    }
+
+    if trimmed.starts_with('|') {
+        let mut cells = trimmed
+            .trim_matches('|')
+            .split('|')
+            .map(str::trim)
+            .filter(|cell| !cell.is_empty())
+            .peekable();
+        let clause = cells
+            .peek()
+            .and_then(|cell| clause_number(cell, false))
+            .filter(|(_, rest)| rest.is_empty())
+            .map(|(number, _)| number.to_string());
+        if clause.is_some() {
+            cells.next();
+        }
+        return (clause, cells.collect::<Vec<_>>().join(" "), true);
+    }
+
+    let heading = trimmed.starts_with('#');
+    let mut content = trimmed.trim_start_matches('#').trim_start();
+    let mut starts_block = heading;
+    if let Some(rest) = strip_list_marker(content) {
+        content = rest;
+        starts_block = true;
+    }
+    for prefix in ["Clause ", "Section "] {
+        if let Some(rest) = content.strip_prefix(prefix) {
+            if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                content = rest;
+                break;
+            }
+        }
+    }
+
+    match clause_number(content, heading) {
+        Some((number, rest)) => (Some(number.to_string()), rest.to_string(), true),
+        None => (None, content.to_string(), starts_block),
+    }
+}
+
+/// Parses a clause number such as `3`, `3.2.1` or `3.2.1.` at the start of
+/// `text`, returning it and the text after it. Outside headings, only
+/// multi-level numbers followed by the start of a sentence count, so that
+/// quantities like `3.5 million` aren't mistaken for clauses.
+fn clause_number(text: &str, heading: bool) -> Option<(&str, &str)> {
+    let number_len = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let number = text[..number_len].trim_end_matches('.');
+    if number.is_empty() || number.starts_with('.') || number.contains("..") {
+        return None;
+    }
+    if !heading && !number.contains('.') {
+        return None;
+    }
+
+    let rest = &text[number_len..];
+    let rest = rest.strip_prefix(')').unwrap_or(rest);
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim();
+    if !heading && rest.starts_with(|c: char| c.is_lowercase()) {
+        return None;
+    }
+    Some((number, rest))
+}
+
+fn strip_list_marker(line: &str) -> Option<&str> {
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Some(rest.trim_start());
+        }
+    }
+    let digits = line.find(|c: char| !c.is_ascii_digit())?;
+    if digits == 0 {
+        return None;
+    }
+    let rest = &line[digits..];
+    rest.strip_prefix(". ")
+        .or_else(|| rest.strip_prefix(") "))
+        .map(str::trim_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn requirement(
+        clause: Option<&str>,
+        text: &str,
+        priority: Priority,
+        page: Option<u32>,
+    ) -> ExtractedRequirement {
+        ExtractedRequirement {
+            clause: clause.map(Into::into),
+            text: text.into(),
+            priority,
+            page,
+        }
+    }
+
+    #[test]
+    fn test_extract_requirements() {
+        let text = indoc! {"
+            # 3 Service Requirements
+
+            This section describes the services.
+
+            3.1 The Supplier must provide a help desk
+            between 8am and 6pm. Calls should be answered within 30 seconds.
+
+            3.2 Reporting
+            - The Supplier shall submit monthly reports.
+            - Reports may include charts.
+
+            | Ref | Requirement |
+            | --- | --- |
+            | 3.3 | Staff are required to hold a current police check. |
+        "};
+        assert_eq!(
+            extract_requirements(text),
+            vec![
+                requirement(
+                    Some("3.1"),
+                    "The Supplier must provide a help desk between 8am and 6pm.",
+                    Priority::Mandatory,
+                    None
+                ),
+                requirement(
+                    Some("3.1"),
+                    "Calls should be answered within 30 seconds.",
+                    Priority::Desirable,
+                    None
+                ),
+                requirement(
+                    Some("3.2"),
+                    "The Supplier shall submit monthly reports.",
+                    Priority::Mandatory,
+                    None
+                ),
+                requirement(
+                    Some("3.3"),
+                    "Staff are required to hold a current police check.",
+                    Priority::Mandatory,
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_requirements_pages() {
+        let text = "## 1 Scope\n\nThe Supplier must be accredited.\x0c\n## 2 Pricing\n\nPrices must include GST.\n\x0c";
+        assert_eq!(
+            extract_requirements(text),
+            vec![
+                requirement(
+                    Some("1"),
+                    "The Supplier must be accredited.",
+                    Priority::Mandatory,
+                    Some(1)
+                ),
+                requirement(
+                    Some("2"),
+                    "Prices must include GST.",
+                    Priority::Mandatory,
+                    Some(2)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("## 4.2 Delivery"),
+            (Some("4.2".into()), "Delivery".into(), true)
+        );
+        assert_eq!(
+            parse_line("Clause 12.1.3. Insurance must be held."),
+            (
+                Some("12.1.3".into()),
+                "Insurance must be held.".into(),
+                true
+            )
+        );
+        assert_eq!(
+            parse_line("1. First item"),
+            (None, "First item".into(), true)
+        );
+        assert_eq!(
+            parse_line("2025 was a big year."),
+            (None, "2025 was a big year.".into(), false)
+        );
+        assert_eq!(
+            parse_line("3.5 million users"),
+            (None, "3.5 million users".into(), false)
+        );
+        assert_eq!(
+            parse_line("| 7.1 | Bids must be signed. |"),
+            (Some("7.1".into()), "Bids must be signed.".into(), true)
+        );
+    }
+}
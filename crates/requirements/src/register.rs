@@ -0,0 +1,352 @@
+use crate::ExtractedRequirement;
+use collections::HashSet;
+use serde::{Deserialize, Serialize};
+
+/// Two requirements under the same clause whose word overlap (Jaccard index)
+/// is at least this are treated as the same requirement, amended.
+const AMENDMENT_SIMILARITY: f32 = 0.5;
+
+/// Whether a requirement has to be met for a response to be compliant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Mandatory,
+    Desirable,
+}
+
+impl Priority {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::Mandatory => "Mandatory",
+            Priority::Desirable => "Desirable",
+        }
+    }
+}
+
+/// The compliance register stored in `requirements.json`: every requirement
+/// extracted from the tender documents, with stable IDs.
+///
+/// The register is versioned. Each extraction run that adds or amends a
+/// requirement increments `version`, and requirements record the version
+/// they were added and last amended in. Requirements are never removed,
+/// since addenda usually restate only what they change.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RequirementsRegister {
+    pub version: u32,
+    #[serde(default)]
+    pub sources: Vec<SourceDocument>,
+    #[serde(default)]
+    pub requirements: Vec<Requirement>,
+}
+
+/// A tender document the register was extracted from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceDocument {
+    /// The document's path relative to the worktree root.
+    pub path: String,
+    /// A digest of the document's converted text, used to skip documents
+    /// that haven't changed since they were last extracted.
+    pub digest: String,
+    /// The register version the document was last extracted in.
+    pub version: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Requirement {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clause: Option<String>,
+    pub text: String,
+    pub priority: Priority,
+    pub source: RequirementSource,
+    pub added_in: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amended_in: Option<u32>,
+}
+
+/// Where a requirement's current text comes from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequirementSource {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+}
+
+/// The IDs of the requirements an extraction run changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub added: Vec<String>,
+    pub amended: Vec<String>,
+    pub unchanged: usize,
+    /// Documents skipped because their text hasn't changed.
+    pub skipped_documents: Vec<String>,
+}
+
+impl MergeSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.amended.is_empty()
+    }
+}
+
+/// The requirements extracted from one document in an extraction run.
+#[derive(Clone, Debug)]
+pub struct ExtractedDocument {
+    pub path: String,
+    pub digest: String,
+    pub requirements: Vec<ExtractedRequirement>,
+}
+
+impl RequirementsRegister {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).unwrap();
+        json.push('\n');
+        json
+    }
+
+    pub fn requirement(&self, id: &str) -> Option<&Requirement> {
+        self.requirements
+            .iter()
+            .find(|requirement| requirement.id == id)
+    }
+
+    /// Merges the requirements extracted from `documents` into the register.
+    ///
+    /// A requirement whose text already appears in the register is left as
+    /// is. One under the same clause as an existing requirement, with
+    /// similar wording, amends it in place. Anything else is added with a new
+    /// ID. Documents should be given in issue order, so that addenda amend
+    /// the original request for tender.
+    pub fn merge(&mut self, documents: Vec<ExtractedDocument>) -> MergeSummary {
+        let version = self.version + 1;
+        let mut summary = MergeSummary::default();
+        let mut known_texts = self
+            .requirements
+            .iter()
+            .map(|requirement| normalize(&requirement.text))
+            .collect::<HashSet<_>>();
+        let mut amended_ids = HashSet::default();
+
+        for document in documents {
+            let source = self
+                .sources
+                .iter_mut()
+                .find(|source| source.path == document.path);
+            match source {
+                Some(source) if source.digest == document.digest => {
+                    summary.skipped_documents.push(document.path);
+                    continue;
+                }
+                Some(source) => {
+                    source.digest = document.digest;
+                    source.version = version;
+                }
+                None => self.sources.push(SourceDocument {
+                    path: document.path.clone(),
+                    digest: document.digest,
+                    version,
+                }),
+            }
+
+            for extracted in document.requirements {
+                let normalized = normalize(&extracted.text);
+                if known_texts.contains(&normalized) {
+                    summary.unchanged += 1;
+                    continue;
+                }
+
+                let source = RequirementSource {
+                    path: document.path.clone(),
+                    page: extracted.page,
+                };
+                let amended = extracted.clause.as_ref().and_then(|clause| {
+                    self.requirements
+                        .iter_mut()
+                        .filter(|requirement| {
+                            requirement.clause.as_ref() == Some(clause)
+                                && !amended_ids.contains(&requirement.id)
+                        })
+                        .map(|requirement| {
+                            let similarity = similarity(&requirement.text, &extracted.text);
+                            (requirement, similarity)
+                        })
+                        .filter(|(_, similarity)| *similarity >= AMENDMENT_SIMILARITY)
+                        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                        .map(|(requirement, _)| requirement)
+                });
+
+                if let Some(requirement) = amended {
+                    requirement.text = extracted.text;
+                    requirement.priority = extracted.priority;
+                    requirement.source = source;
+                    requirement.amended_in = Some(version);
+                    amended_ids.insert(requirement.id.clone());
+                    summary.amended.push(requirement.id.clone());
+                } else {
+                    let id = self.next_id();
+                    self.requirements.push(Requirement {
+                        id: id.clone(),
+                        clause: extracted.clause,
+                        text: extracted.text,
+                        priority: extracted.priority,
+                        source,
+                        added_in: version,
+                        amended_in: None,
+                    });
+                    summary.added.push(id);
+                }
+                known_texts.insert(normalized);
+            }
+        }
+
+        if summary.is_empty() {
+            for source in &mut self.sources {
+                if source.version == version {
+                    source.version = self.version;
+                }
+            }
+        } else {
+            self.version = version;
+        }
+        summary
+    }
+
+    fn next_id(&self) -> String {
+        let last = self
+            .requirements
+            .iter()
+            .filter_map(|requirement| requirement.id.strip_prefix("REQ-")?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("REQ-{:03}", last + 1)
+    }
+}
+
+/// Lowercases `text` and reduces it to its words, so that differences in
+/// punctuation, spacing and line wrapping don't count as changes.
+fn normalize(text: &str) -> String {
+    words(text).collect::<Vec<_>>().join(" ")
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn similarity(a: &str, b: &str) -> f32 {
+    let a = words(a).collect::<HashSet<_>>();
+    let b = words(b).collect::<HashSet<_>>();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn extracted(clause: &str, text: &str, priority: Priority) -> ExtractedRequirement {
+        ExtractedRequirement {
+            clause: Some(clause.into()),
+            text: text.into(),
+            priority,
+            page: Some(1),
+        }
+    }
+
+    fn document(
+        path: &str,
+        digest: &str,
+        requirements: Vec<ExtractedRequirement>,
+    ) -> ExtractedDocument {
+        ExtractedDocument {
+            path: path.into(),
+            digest: digest.into(),
+            requirements,
+        }
+    }
+
+    #[test]
+    fn test_merge_addendum() {
+        let mut register = RequirementsRegister::default();
+        let rft = document(
+            "RFT.pdf",
+            "a",
+            vec![
+                extracted(
+                    "3.1",
+                    "The Supplier must provide a help desk.",
+                    Priority::Mandatory,
+                ),
+                extracted(
+                    "3.2",
+                    "Reports should be submitted monthly.",
+                    Priority::Desirable,
+                ),
+            ],
+        );
+        let summary = register.merge(vec![rft.clone()]);
+        assert_eq!(summary.added, ["REQ-001", "REQ-002"]);
+        assert_eq!(register.version, 1);
+
+        // Re-running on an unchanged document changes nothing.
+        let summary = register.merge(vec![rft.clone()]);
+        assert!(summary.is_empty());
+        assert_eq!(summary.skipped_documents, ["RFT.pdf"]);
+        assert_eq!(register.version, 1);
+
+        let addendum = document(
+            "Addendum 1.pdf",
+            "b",
+            vec![
+                extracted(
+                    "3.1",
+                    "The Supplier must provide a help  desk.",
+                    Priority::Mandatory,
+                ),
+                extracted(
+                    "3.2",
+                    "Reports must be submitted monthly and quarterly.",
+                    Priority::Mandatory,
+                ),
+                extracted(
+                    "3.3",
+                    "Staff must hold a police check.",
+                    Priority::Mandatory,
+                ),
+            ],
+        );
+        let summary = register.merge(vec![rft, addendum]);
+        assert_eq!(summary.added, ["REQ-003"]);
+        assert_eq!(summary.amended, ["REQ-002"]);
+        assert_eq!(summary.unchanged, 1);
+        assert_eq!(register.version, 2);
+        assert_eq!(
+            register.requirement("REQ-002"),
+            Some(&Requirement {
+                id: "REQ-002".into(),
+                clause: Some("3.2".into()),
+                text: "Reports must be submitted monthly and quarterly.".into(),
+                priority: Priority::Mandatory,
+                source: RequirementSource {
+                    path: "Addendum 1.pdf".into(),
+                    page: Some(1),
+                },
+                added_in: 1,
+                amended_in: Some(2),
+            })
+        );
+        assert_eq!(register.requirements.len(), 3);
+
+        let register = RequirementsRegister::from_json(&register.to_json()).unwrap();
+        assert_eq!(register.version, 2);
+        assert_eq!(register.sources.len(), 2);
+    }
+}
@@ -0,0 +1,319 @@
+//! The compliance register: every requirement in a tender's documents,
+//! extracted into a versioned `requirements.json` at the project root so that
+//! responses can be checked against it.
+
+mod extract;
+mod register;
+
+use anyhow::{Context as _, Result, anyhow};
+use gpui::{App, AppContext as _, Entity, Task, actions};
+use project::{Project, ProjectPath};
+use sha2::{Digest as _, Sha256};
+use std::path::Path;
+use tender_manifest::{Folders, TenderManifestStore};
+use workspace::notifications::{NotificationId, NotifyTaskExt as _};
+use workspace::{Toast, Workspace};
+
+pub use extract::*;
+pub use register::*;
+
+actions!(requirements, [ExtractRequirements]);
+
+/// The name of the register file, at the root of the project's worktree.
+pub const REQUIREMENTS_FILE_NAME: &str = "requirements.json";
+
+/// Extensions of the files searched for requirements when no documents are
+/// given explicitly. Markdown files aren't included, since in a tender
+/// project they're usually the response being drafted.
+pub const TENDER_DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "docx", "odt"];
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ExtractRequirements, window, cx| {
+            let project = workspace.project().clone();
+            let extraction = extract_project_requirements(&project, None, cx);
+            cx.spawn_in(window, async move |workspace, cx| {
+                let extraction = extraction.await?;
+                workspace.update_in(cx, |workspace, _, cx| {
+                    struct RequirementsExtracted;
+
+                    let register_path = extraction.register_path.clone();
+                    let workspace_handle = cx.entity().downgrade();
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<RequirementsExtracted>(),
+                            extraction.summary_message(),
+                        )
+                        .on_click("Open Register", move |window, cx| {
+                            workspace_handle
+                                .update(cx, |workspace, cx| {
+                                    workspace
+                                        .open_path(register_path.clone(), None, true, window, cx)
+                                        .detach_and_log_err(cx);
+                                })
+                                .ok();
+                        }),
+                        cx,
+                    );
+                })
+            })
+            .detach_and_notify_err(window, cx);
+        });
+    })
+    .detach();
+}
+
+/// The result of an extraction run.
+pub struct Extraction {
+    pub register_path: ProjectPath,
+    pub register: RequirementsRegister,
+    pub summary: MergeSummary,
+}
+
+impl Extraction {
+    pub fn summary_message(&self) -> String {
+        if self.summary.is_empty() {
+            return format!(
+                "No new requirements. The register has {} requirements.",
+                self.register.requirements.len()
+            );
+        }
+        format!(
+            "Requirements register updated to version {}: {} added, {} amended, {} unchanged.",
+            self.register.version,
+            self.summary.added.len(),
+            self.summary.amended.len(),
+            self.summary.unchanged
+        )
+    }
+}
+
+/// Extracts the requirements from tender documents and merges them into the
+/// project's `requirements.json`, creating it if needed.
+///
+/// `paths` are processed in the order given, which should be the order they
+/// were issued in, so that later documents amend the requirements they
+/// restate. When `paths` is `None`, every tender document in the manifest's
+/// documents folder is used, with addenda last.
+pub fn extract_project_requirements(
+    project: &Entity<Project>,
+    paths: Option<Vec<ProjectPath>>,
+    cx: &mut App,
+) -> Task<Result<Extraction>> {
+    if !project.read(cx).is_local() {
+        return Task::ready(Err(anyhow!(
+            "Requirements can only be extracted in local projects"
+        )));
+    }
+
+    let documents_folder = TenderManifestStore::for_project(project, cx)
+        .read(cx)
+        .manifest()
+        .map_or_else(
+            || Folders::default().documents,
+            |manifest| manifest.folders.documents.clone(),
+        );
+    let worktree = match paths.as_ref().and_then(|paths| paths.first()) {
+        Some(path) => project.read(cx).worktree_for_id(path.worktree_id, cx),
+        None => project.read(cx).visible_worktrees(cx).next(),
+    };
+    let Some(worktree) = worktree else {
+        return Task::ready(Err(anyhow!("No worktree to extract requirements into")));
+    };
+    let worktree = worktree.read(cx);
+    let worktree_id = worktree.id();
+    let root = worktree.abs_path();
+
+    let explicit = paths.is_some();
+    let documents = match paths {
+        Some(paths) => {
+            if let Some(path) = paths.iter().find(|path| path.worktree_id != worktree_id) {
+                return Task::ready(Err(anyhow!(
+                    "{} is not in the same worktree as the other documents",
+                    path.path.display()
+                )));
+            }
+            paths.into_iter().map(|path| path.path).collect::<Vec<_>>()
+        }
+        None => {
+            let mut documents = worktree
+                .files(false, 0)
+                .filter(|entry| {
+                    entry.path.starts_with(&documents_folder)
+                        && entry
+                            .path
+                            .extension()
+                            .and_then(|extension| extension.to_str())
+                            .is_some_and(|extension| {
+                                TENDER_DOCUMENT_EXTENSIONS
+                                    .contains(&extension.to_lowercase().as_str())
+                            })
+                })
+                .map(|entry| entry.path.clone())
+                .collect::<Vec<_>>();
+            documents.sort_by_key(|path| (is_addendum(path), path.clone()));
+            documents
+        }
+    };
+    let loads = documents
+        .into_iter()
+        .map(|path| {
+            let load = semantic_index::load_document(
+                project,
+                &ProjectPath {
+                    worktree_id,
+                    path: path.clone(),
+                },
+                cx,
+            );
+            (path, load)
+        })
+        .collect::<Vec<_>>();
+
+    let register_path = ProjectPath {
+        worktree_id,
+        path: Path::new(REQUIREMENTS_FILE_NAME).into(),
+    };
+    let fs = project.read(cx).fs().clone();
+    cx.background_spawn(async move {
+        let register_abs_path = root.join(REQUIREMENTS_FILE_NAME);
+        let mut register = if fs.is_file(&register_abs_path).await {
+            let json = fs.load(&register_abs_path).await?;
+            RequirementsRegister::from_json(&json)
+                .with_context(|| format!("Failed to parse {REQUIREMENTS_FILE_NAME}"))?
+        } else {
+            RequirementsRegister::default()
+        };
+
+        let mut extracted = Vec::new();
+        for (path, load) in loads {
+            let text = match load.await {
+                Ok(text) => text,
+                Err(error) if explicit => {
+                    return Err(error.context(format!("Failed to read {}", path.display())));
+                }
+                Err(error) => {
+                    log::warn!("skipping {}: {error:#}", path.display());
+                    continue;
+                }
+            };
+            extracted.push(ExtractedDocument {
+                path: path.to_string_lossy().into_owned(),
+                digest: format!("{:x}", Sha256::digest(text.as_bytes())),
+                requirements: extract_requirements(&text),
+            });
+        }
+        if extracted.is_empty() {
+            return Err(anyhow!(
+                "No tender documents found in {}/",
+                documents_folder.display()
+            ));
+        }
+
+        let summary = register.merge(extracted);
+        if !summary.is_empty() {
+            fs.atomic_write(register_abs_path, register.to_json())
+                .await?;
+        }
+
+        Ok(Extraction {
+            register_path,
+            register,
+            summary,
+        })
+    })
+}
+
+/// Whether a document's name marks it as an addendum or amendment to the
+/// original tender documents.
+pub fn is_addendum(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    name.contains("addend") || name.contains("amendment")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_extract_project_requirements(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                "Addendum 1.md": indoc! {"
+                    3.2 Reports must be submitted monthly and quarterly.
+                "},
+                "RFT.md": indoc! {"
+                    3.1 The Supplier must provide a help desk.
+
+                    3.2 Reports should be submitted monthly.
+                "},
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/tender").as_ref()], cx).await;
+        let paths = project.read_with(cx, |project, cx| {
+            ["tender/Addendum 1.md", "tender/RFT.md"]
+                .iter()
+                .map(|path| project.find_project_path(path, cx).unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let extraction = cx
+            .update(|cx| extract_project_requirements(&project, Some(paths[1..].to_vec()), cx))
+            .await
+            .unwrap();
+        assert_eq!(extraction.summary.added, ["REQ-001", "REQ-002"]);
+
+        // Explicit paths are processed in the order given, as the order they
+        // were issued in.
+        let issue_order = vec![paths[1].clone(), paths[0].clone()];
+        let extraction = cx
+            .update(|cx| extract_project_requirements(&project, Some(issue_order), cx))
+            .await
+            .unwrap();
+        assert_eq!(extraction.summary.added, Vec::<String>::new());
+        assert_eq!(extraction.summary.amended, ["REQ-002"]);
+        assert_eq!(extraction.summary.skipped_documents, ["RFT.md"]);
+
+        let json = fs
+            .load(path!("/tender/requirements.json").as_ref())
+            .await
+            .unwrap();
+        let register = RequirementsRegister::from_json(&json).unwrap();
+        assert_eq!(register.version, 2);
+        assert_eq!(register.requirements.len(), 2);
+        assert_eq!(
+            register.requirement("REQ-002").unwrap().source.path,
+            "Addendum 1.md"
+        );
+
+        // Without paths, only the documents folder is scanned.
+        let error = cx
+            .update(|cx| extract_project_requirements(&project, None, cx))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "No tender documents found in rft/");
+    }
+}
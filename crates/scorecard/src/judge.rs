@@ -0,0 +1,267 @@
+use crate::{CriterionScore, Gap};
+use anyhow::{Context as _, Result, anyhow};
+use futures::StreamExt as _;
+use gpui::AsyncApp;
+use handlebars::Handlebars;
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelRequestMessage, Role};
+use serde::Serialize;
+use std::sync::Arc;
+use tender_manifest::{EvaluationCriterion, TenderManifest};
+
+const PROMPT_TEMPLATE_NAME: &str = "score_criterion_prompt";
+
+/// The highest score a criterion can be given.
+pub const MAX_SCORE: u32 = 10;
+
+#[derive(Serialize)]
+struct ScoreCriterionPrompt<'a> {
+    client: Option<&'a str>,
+    title: Option<&'a str>,
+    criterion: &'a str,
+    weight: String,
+    response: &'a str,
+    sections: &'a [String],
+}
+
+/// Renders the prompt asking the model to score `response` against
+/// `criterion`. `sections` are the titles of the response's sections.
+pub(crate) fn score_criterion_prompt(
+    manifest: &TenderManifest,
+    criterion: &EvaluationCriterion,
+    response: &str,
+    sections: &[String],
+) -> Result<String> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_template_string(
+        PROMPT_TEMPLATE_NAME,
+        include_str!("score_criterion_prompt.hbs"),
+    )?;
+    Ok(handlebars.render(
+        PROMPT_TEMPLATE_NAME,
+        &ScoreCriterionPrompt {
+            client: manifest.client.as_deref(),
+            title: manifest.title.as_deref(),
+            criterion: &criterion.criterion,
+            weight: criterion.weight.to_string(),
+            response,
+            sections,
+        },
+    )?)
+}
+
+/// Asks `model` to score a response against one criterion.
+pub(crate) async fn score_criterion(
+    model: Arc<dyn LanguageModel>,
+    manifest: &TenderManifest,
+    criterion: &EvaluationCriterion,
+    response: &str,
+    sections: &[String],
+    cx: &AsyncApp,
+) -> Result<CriterionScore> {
+    let prompt = score_criterion_prompt(manifest, criterion, response, sections)?;
+    let request = LanguageModelRequest {
+        thread_id: None,
+        prompt_id: None,
+        mode: None,
+        messages: vec![LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![prompt.into()],
+            cache: false,
+        }],
+        tools: Vec::new(),
+        tool_choice: None,
+        stop: Vec::new(),
+        temperature: None,
+    };
+
+    let mut messages = model.stream_completion_text(request, cx).await?;
+    let mut judgement = String::new();
+    while let Some(chunk) = messages.stream.next().await {
+        judgement.push_str(&chunk?);
+    }
+    parse_judgement(criterion, &judgement)
+        .with_context(|| format!("Failed to score {:?}", criterion.criterion))
+}
+
+/// Parses the model's answer to the prompt from [`score_criterion_prompt`].
+pub(crate) fn parse_judgement(
+    criterion: &EvaluationCriterion,
+    judgement: &str,
+) -> Result<CriterionScore> {
+    let rationale = tag_content("analysis", judgement)
+        .ok_or_else(|| anyhow!("The judgement has no analysis"))?;
+    let score =
+        tag_content("score", judgement).ok_or_else(|| anyhow!("The judgement has no score"))?;
+    let score = score
+        .strip_suffix(&format!("/{MAX_SCORE}"))
+        .unwrap_or(score)
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|score| *score <= MAX_SCORE)
+        .ok_or_else(|| anyhow!("{score:?} isn't a score out of {MAX_SCORE}"))?;
+    let sections = tag_content("sections", judgement)
+        .map(|sections| {
+            sections
+                .lines()
+                .map(|line| line.trim().trim_start_matches(['-', '*']).trim())
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let gaps = tag_content("gaps", judgement)
+        .map(parse_gaps)
+        .unwrap_or_default();
+
+    Ok(CriterionScore {
+        criterion: criterion.criterion.clone(),
+        weight: criterion.weight,
+        score,
+        rationale: rationale.to_string(),
+        sections,
+        gaps,
+    })
+}
+
+/// Returns the trimmed text between the first `<name>` and the `</name>`
+/// after it.
+fn tag_content<'a>(name: &str, text: &'a str) -> Option<&'a str> {
+    let start_tag = format!("<{name}>");
+    let end_tag = format!("</{name}>");
+    let start = text.find(&start_tag)? + start_tag.len();
+    let end = start + text[start..].find(&end_tag)?;
+    Some(text[start..end].trim())
+}
+
+/// Parses a list of `<gap section="…">…</gap>` elements.
+fn parse_gaps(text: &str) -> Vec<Gap> {
+    let mut gaps = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<gap") {
+        rest = &rest[start + "<gap".len()..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let attributes = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+        let Some(end) = rest.find("</gap>") else {
+            break;
+        };
+        let description = rest[..end].split_whitespace().collect::<Vec<_>>().join(" ");
+        rest = &rest[end + "</gap>".len()..];
+
+        if !description.is_empty() {
+            gaps.push(Gap {
+                section: attribute("section", attributes)
+                    .filter(|section| !section.is_empty())
+                    .map(str::to_string),
+                description,
+            });
+        }
+    }
+    gaps
+}
+
+fn attribute<'a>(name: &str, attributes: &'a str) -> Option<&'a str> {
+    let prefix = format!("{name}=\"");
+    let start = attributes.find(&prefix)? + prefix.len();
+    let end = start + attributes[start..].find('"')?;
+    Some(attributes[start..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn criterion() -> EvaluationCriterion {
+        EvaluationCriterion {
+            criterion: "Methodology".into(),
+            weight: 40.,
+        }
+    }
+
+    #[test]
+    fn test_score_criterion_prompt() {
+        let manifest = TenderManifest {
+            client: Some("City of Example".into()),
+            title: Some("Parks Maintenance".into()),
+            ..Default::default()
+        };
+        let sections = vec!["Approach".to_string(), "Staffing".to_string()];
+        let prompt =
+            score_criterion_prompt(&manifest, &criterion(), "# Approach\n\n…", &sections).unwrap();
+
+        assert!(prompt.starts_with(
+            "You are a member of the panel evaluating responses to City of Example's tender \"Parks Maintenance\"."
+        ));
+        assert!(prompt.contains("The criterion is worth 40% of the total score"));
+        assert!(prompt.contains("<criterion>\nMethodology\n</criterion>"));
+        assert!(prompt.contains("- Approach"));
+        assert!(prompt.contains("- Staffing"));
+
+        let prompt =
+            score_criterion_prompt(&TenderManifest::default(), &criterion(), "Text", &[]).unwrap();
+        assert!(
+            prompt.starts_with("You are a member of the panel evaluating responses to a tender.")
+        );
+        assert!(!prompt.contains("divided into the following sections"));
+    }
+
+    #[test]
+    fn test_parse_judgement() {
+        let judgement = indoc! {r#"
+            Let me work through the response.
+
+            <analysis>
+            The approach is clear, but the transition plan is generic.
+            </analysis>
+            <score>6</score>
+            <sections>
+            - Approach
+            Transition
+            </sections>
+            <gaps>
+            <gap section="Transition">No timeline for
+                handing over from the incumbent.</gap>
+            <gap>No quality assurance section.</gap>
+            <gap section="Approach"></gap>
+            </gaps>
+        "#};
+
+        assert_eq!(
+            parse_judgement(&criterion(), judgement).unwrap(),
+            CriterionScore {
+                criterion: "Methodology".into(),
+                weight: 40.,
+                score: 6,
+                rationale: "The approach is clear, but the transition plan is generic.".into(),
+                sections: vec!["Approach".into(), "Transition".into()],
+                gaps: vec![
+                    Gap {
+                        section: Some("Transition".into()),
+                        description: "No timeline for handing over from the incumbent.".into(),
+                    },
+                    Gap {
+                        section: None,
+                        description: "No quality assurance section.".into(),
+                    },
+                ],
+            }
+        );
+
+        let judgement = "<analysis>Fine.</analysis><score>7/10</score>";
+        let score = parse_judgement(&criterion(), judgement).unwrap();
+        assert_eq!(score.score, 7);
+        assert!(score.sections.is_empty());
+        assert!(score.gaps.is_empty());
+
+        assert!(parse_judgement(&criterion(), "<score>7</score>").is_err());
+        assert!(parse_judgement(&criterion(), "<analysis>Fine.</analysis>").is_err());
+        assert!(
+            parse_judgement(&criterion(), "<analysis>Fine.</analysis><score>11</score>").is_err()
+        );
+    }
+}
@@ -0,0 +1,498 @@
+use crate::MAX_SCORE;
+use chrono::{DateTime, Utc};
+use collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// How many runs are kept for each response.
+pub const MAX_RUNS_PER_RESPONSE: usize = 20;
+
+/// How much of the wording two gaps need to share to count as the same gap in
+/// consecutive runs, since the model rarely words a gap the same way twice.
+const SAME_GAP_SIMILARITY: f64 = 0.5;
+
+/// A response's score against one evaluation criterion.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CriterionScore {
+    pub criterion: String,
+    /// The criterion's share of the total score, as a percentage.
+    pub weight: f64,
+    /// The score out of [`MAX_SCORE`].
+    pub score: u32,
+    pub rationale: String,
+    /// The titles of the sections that address the criterion.
+    #[serde(default)]
+    pub sections: Vec<String>,
+    #[serde(default)]
+    pub gaps: Vec<Gap>,
+}
+
+impl CriterionScore {
+    /// The criterion's contribution to the total, in percentage points.
+    pub fn weighted_score(&self) -> f64 {
+        self.weight * self.score as f64 / MAX_SCORE as f64
+    }
+}
+
+/// Something an evaluator would mark the response down for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gap {
+    /// The title of the section the gap should be fixed in, or `None` if the
+    /// response doesn't have a section for it yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub description: String,
+}
+
+/// A scoring of a response against every evaluation criterion.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScorecardRun {
+    /// The response, relative to the root of its worktree.
+    pub response: PathBuf,
+    pub scored_at: DateTime<Utc>,
+    /// The name of the model that did the scoring.
+    pub model: String,
+    /// The titles of the response's sections when it was scored.
+    #[serde(default)]
+    pub sections: Vec<String>,
+    pub criteria: Vec<CriterionScore>,
+}
+
+impl ScorecardRun {
+    /// The weighted total, as a percentage. Weightings that don't add up to
+    /// 100 are scaled so that they do.
+    pub fn total(&self) -> f64 {
+        let total_weight = self
+            .criteria
+            .iter()
+            .map(|criterion| criterion.weight)
+            .sum::<f64>();
+        if total_weight <= 0. {
+            return 0.;
+        }
+        self.criteria
+            .iter()
+            .map(CriterionScore::weighted_score)
+            .sum::<f64>()
+            * 100.
+            / total_weight
+    }
+
+    /// The run's findings grouped by the response's sections, in document
+    /// order, followed by any sections the gaps cite that the response
+    /// doesn't have.
+    pub fn section_summaries(&self) -> Vec<SectionSummary> {
+        let mut summaries = self
+            .sections
+            .iter()
+            .map(|title| SectionSummary {
+                title: Some(title.clone()),
+                criteria: Vec::new(),
+                gaps: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let mut missing = SectionSummary {
+            title: None,
+            criteria: Vec::new(),
+            gaps: Vec::new(),
+        };
+
+        for (criterion_ix, criterion) in self.criteria.iter().enumerate() {
+            for section in &criterion.sections {
+                if let Some(ix) = section_ix(&summaries, section) {
+                    if !summaries[ix].criteria.contains(&criterion_ix) {
+                        summaries[ix].criteria.push(criterion_ix);
+                    }
+                }
+            }
+            for (gap_ix, gap) in criterion.gaps.iter().enumerate() {
+                let summary = match &gap.section {
+                    Some(section) => {
+                        let ix = section_ix(&summaries, section).unwrap_or_else(|| {
+                            summaries.push(SectionSummary {
+                                title: Some(section.clone()),
+                                criteria: Vec::new(),
+                                gaps: Vec::new(),
+                            });
+                            summaries.len() - 1
+                        });
+                        &mut summaries[ix]
+                    }
+                    None => &mut missing,
+                };
+                summary.gaps.push((criterion_ix, gap_ix));
+            }
+        }
+
+        if !missing.gaps.is_empty() {
+            summaries.push(missing);
+        }
+        summaries
+    }
+}
+
+/// A section of a response, with the criteria it addresses and the gaps cited
+/// in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionSummary {
+    /// The section's title, or `None` for the gaps that belong in sections the
+    /// response doesn't have yet.
+    pub title: Option<String>,
+    /// Indices of the criteria the section addresses.
+    pub criteria: Vec<usize>,
+    /// Indices of the criteria and their gaps cited in the section.
+    pub gaps: Vec<(usize, usize)>,
+}
+
+fn section_ix(summaries: &[SectionSummary], title: &str) -> Option<usize> {
+    summaries.iter().position(|summary| {
+        summary
+            .title
+            .as_deref()
+            .is_some_and(|summary_title| same_title(summary_title, title))
+    })
+}
+
+/// Formats a weighting without a fractional part, unless it has one.
+pub(crate) fn format_weight(weight: f64) -> String {
+    if weight.fract() == 0. {
+        format!("{weight:.0}")
+    } else {
+        weight.to_string()
+    }
+}
+
+/// Whether a section title cited by the model refers to a section's title.
+pub fn same_title(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// Every scoring run in a project, as stored in its `scorecards.json`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScorecardHistory {
+    pub runs: Vec<ScorecardRun>,
+}
+
+impl ScorecardHistory {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).unwrap();
+        json.push('\n');
+        json
+    }
+
+    /// The runs for a response, oldest first.
+    pub fn runs_for<'a>(&'a self, response: &'a Path) -> impl Iterator<Item = &'a ScorecardRun> {
+        self.runs.iter().filter(move |run| run.response == response)
+    }
+
+    /// The most recent run for a response and the one before it.
+    pub fn latest(&self, response: &Path) -> (Option<&ScorecardRun>, Option<&ScorecardRun>) {
+        let mut runs = self
+            .runs
+            .iter()
+            .rev()
+            .filter(|run| run.response == response);
+        let latest = runs.next();
+        (latest, runs.next())
+    }
+
+    /// Records a run, dropping the response's oldest runs beyond
+    /// [`MAX_RUNS_PER_RESPONSE`].
+    pub fn push(&mut self, run: ScorecardRun) {
+        let response = run.response.clone();
+        self.runs.push(run);
+        let mut excess = self
+            .runs_for(&response)
+            .count()
+            .saturating_sub(MAX_RUNS_PER_RESPONSE);
+        self.runs.retain(|run| {
+            if excess > 0 && run.response == response {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+/// How a criterion's score compares with the previous run.
+#[derive(Debug, PartialEq)]
+pub struct CriterionChange<'a> {
+    pub current: &'a CriterionScore,
+    /// The criterion's score in the previous run, or `None` if it wasn't
+    /// scored then.
+    pub previous: Option<&'a CriterionScore>,
+    /// Indices of the current gaps that weren't cited in the previous run.
+    pub new_gaps: Vec<usize>,
+    /// Gaps cited in the previous run that no longer are.
+    pub resolved_gaps: Vec<&'a Gap>,
+}
+
+impl CriterionChange<'_> {
+    pub fn score_delta(&self) -> Option<i64> {
+        self.previous
+            .map(|previous| self.current.score as i64 - previous.score as i64)
+    }
+}
+
+/// Compares each criterion of a run with the same criterion in the previous
+/// run.
+pub fn compare_runs<'a>(
+    current: &'a ScorecardRun,
+    previous: Option<&'a ScorecardRun>,
+) -> Vec<CriterionChange<'a>> {
+    current
+        .criteria
+        .iter()
+        .map(|criterion| {
+            let previous = previous.and_then(|previous| {
+                previous
+                    .criteria
+                    .iter()
+                    .find(|previous| same_title(&previous.criterion, &criterion.criterion))
+            });
+            let Some(previous_criterion) = previous else {
+                return CriterionChange {
+                    current: criterion,
+                    previous: None,
+                    new_gaps: Vec::new(),
+                    resolved_gaps: Vec::new(),
+                };
+            };
+
+            let new_gaps = criterion
+                .gaps
+                .iter()
+                .enumerate()
+                .filter(|(_, gap)| {
+                    !previous_criterion
+                        .gaps
+                        .iter()
+                        .any(|previous_gap| same_gap(gap, previous_gap))
+                })
+                .map(|(ix, _)| ix)
+                .collect();
+            let resolved_gaps = previous_criterion
+                .gaps
+                .iter()
+                .filter(|previous_gap| {
+                    !criterion.gaps.iter().any(|gap| same_gap(gap, previous_gap))
+                })
+                .collect();
+            CriterionChange {
+                current: criterion,
+                previous,
+                new_gaps,
+                resolved_gaps,
+            }
+        })
+        .collect()
+}
+
+/// Whether two gaps describe the same problem, judged by the share of
+/// significant words they have in common.
+fn same_gap(a: &Gap, b: &Gap) -> bool {
+    fn words(text: &str) -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.chars().count() > 3)
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    let a = words(&a.description);
+    let b = words(&b.description);
+    if a.is_empty() || b.is_empty() {
+        return a == b;
+    }
+    let shared = a.intersection(&b).count();
+    let all = a.union(&b).count();
+    shared as f64 / all as f64 >= SAME_GAP_SIMILARITY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn gap(section: Option<&str>, description: &str) -> Gap {
+        Gap {
+            section: section.map(str::to_string),
+            description: description.into(),
+        }
+    }
+
+    fn criterion(name: &str, weight: f64, score: u32, gaps: Vec<Gap>) -> CriterionScore {
+        CriterionScore {
+            criterion: name.into(),
+            weight,
+            score,
+            rationale: String::new(),
+            sections: vec!["Approach".into()],
+            gaps,
+        }
+    }
+
+    fn run(response: &str, criteria: Vec<CriterionScore>) -> ScorecardRun {
+        ScorecardRun {
+            response: response.into(),
+            scored_at: DateTime::UNIX_EPOCH,
+            model: "fake".into(),
+            sections: vec!["Approach".into(), "Staffing".into()],
+            criteria,
+        }
+    }
+
+    #[test]
+    fn test_total() {
+        let run = run(
+            "response.md",
+            vec![
+                criterion("Methodology", 60., 5, Vec::new()),
+                criterion("Price", 40., 10, Vec::new()),
+            ],
+        );
+        assert_eq!(run.total(), 70.);
+
+        // Weightings are scaled to add up to 100.
+        let mut unscaled = run.clone();
+        unscaled.criteria[0].weight = 3.;
+        unscaled.criteria[1].weight = 2.;
+        assert_eq!(unscaled.total(), 70.);
+
+        assert_eq!(self::run("response.md", Vec::new()).total(), 0.);
+    }
+
+    #[test]
+    fn test_section_summaries() {
+        let mut run = run(
+            "response.md",
+            vec![
+                criterion(
+                    "Methodology",
+                    60.,
+                    5,
+                    vec![
+                        gap(Some("approach"), "No timeline."),
+                        gap(None, "No quality assurance."),
+                    ],
+                ),
+                criterion("Price", 40., 8, vec![gap(Some("Pricing"), "No rates.")]),
+            ],
+        );
+        run.criteria[1].sections = vec!["Approach".into(), "Unknown".into()];
+
+        assert_eq!(
+            run.section_summaries(),
+            vec![
+                SectionSummary {
+                    title: Some("Approach".into()),
+                    criteria: vec![0, 1],
+                    gaps: vec![(0, 0)],
+                },
+                SectionSummary {
+                    title: Some("Staffing".into()),
+                    criteria: Vec::new(),
+                    gaps: Vec::new(),
+                },
+                SectionSummary {
+                    title: Some("Pricing".into()),
+                    criteria: Vec::new(),
+                    gaps: vec![(1, 0)],
+                },
+                SectionSummary {
+                    title: None,
+                    criteria: Vec::new(),
+                    gaps: vec![(0, 1)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = ScorecardHistory::default();
+        for score in 0..MAX_RUNS_PER_RESPONSE as u32 + 2 {
+            history.push(run(
+                "a.md",
+                vec![criterion("Methodology", 100., score % 11, Vec::new())],
+            ));
+            if score == 0 {
+                history.push(run("b.md", Vec::new()));
+            }
+        }
+
+        assert_eq!(
+            history.runs_for(Path::new("a.md")).count(),
+            MAX_RUNS_PER_RESPONSE
+        );
+        assert_eq!(history.runs_for(Path::new("b.md")).count(), 1);
+        let (latest, previous) = history.latest(Path::new("a.md"));
+        assert_eq!(latest.unwrap().criteria[0].score, 10);
+        assert_eq!(previous.unwrap().criteria[0].score, 9);
+        assert_eq!(history.latest(Path::new("c.md")), (None, None));
+
+        assert_eq!(
+            ScorecardHistory::from_json(&history.to_json()).unwrap(),
+            history
+        );
+    }
+
+    #[test]
+    fn test_compare_runs() {
+        let previous = run(
+            "response.md",
+            vec![
+                criterion(
+                    "Methodology",
+                    60.,
+                    5,
+                    vec![
+                        gap(Some("Approach"), "No timeline for the transition period."),
+                        gap(None, "No quality assurance section."),
+                    ],
+                ),
+                criterion("Price", 40., 8, Vec::new()),
+            ],
+        );
+        let current = run(
+            "response.md",
+            vec![
+                criterion(
+                    "methodology",
+                    60.,
+                    7,
+                    vec![
+                        gap(Some("Approach"), "The transition period has no timeline."),
+                        gap(Some("Staffing"), "Key personnel aren't named."),
+                    ],
+                ),
+                criterion(
+                    "Local content",
+                    40.,
+                    6,
+                    vec![gap(None, "No local suppliers.")],
+                ),
+            ],
+        );
+
+        let changes = compare_runs(&current, Some(&previous));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].score_delta(), Some(2));
+        assert_eq!(changes[0].new_gaps, vec![1]);
+        assert_eq!(
+            changes[0].resolved_gaps,
+            vec![&previous.criteria[0].gaps[1]]
+        );
+        assert_eq!(changes[1].score_delta(), None);
+        assert!(changes[1].new_gaps.is_empty());
+        assert!(changes[1].resolved_gaps.is_empty());
+
+        let changes = compare_runs(&current, None);
+        assert!(changes.iter().all(|change| change.previous.is_none()));
+    }
+}
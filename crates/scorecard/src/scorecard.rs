@@ -0,0 +1,360 @@
+//! Self-scoring of a draft response against the tender's evaluation criteria.
+//!
+//! The language model is put on the evaluation panel and scores the response
+//! against each criterion in `tender.toml`, with its rationale, the sections
+//! that address the criterion and the gaps it would mark the response down
+//! for. Each run is recorded in a `scorecards.json` at the project root, so
+//! that after editing the response it can be scored again and compared with
+//! the run before.
+
+mod judge;
+mod run;
+mod scorecard_view;
+
+use anyhow::{Context as _, Result, anyhow};
+use chrono::Utc;
+use fs::Fs;
+use futures::future;
+use gpui::{App, Entity, Task, actions};
+use language_model::{ConfiguredModel, LanguageModel, LanguageModelRegistry};
+use project::{Project, ProjectPath};
+use run::format_weight;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tender_manifest::{MANIFEST_FILE_NAME, TenderManifestStore};
+use workspace::notifications::NotificationId;
+use workspace::{Toast, Workspace};
+
+pub use judge::MAX_SCORE;
+pub use run::*;
+pub use scorecard_view::ScorecardView;
+
+actions!(scorecard, [ScoreResponse]);
+
+/// The name of the file runs are recorded in, at the root of the response's
+/// worktree.
+pub const SCORECARDS_FILE_NAME: &str = "scorecards.json";
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ScoreResponse, window, cx| {
+            let Some(response_path) = workspace
+                .active_item(cx)
+                .and_then(|item| item.project_path(cx))
+            else {
+                struct NoResponse;
+
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<NoResponse>(),
+                        "Open a response to score it against the evaluation criteria",
+                    )
+                    .autohide(),
+                    cx,
+                );
+                return;
+            };
+            ScorecardView::open(workspace, response_path, window, cx);
+        });
+    })
+    .detach();
+}
+
+/// The result of scoring a response.
+pub struct Scoring {
+    pub run: ScorecardRun,
+    /// The response's previous run, if it had been scored before.
+    pub previous: Option<ScorecardRun>,
+}
+
+impl Scoring {
+    /// Describes the run and how it compares with the previous one.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        let total = self.run.total();
+        write!(markdown, "Total: {total:.1}%").unwrap();
+        if let Some(previous) = &self.previous {
+            let delta = total - previous.total();
+            if delta.abs() < 0.05 {
+                markdown.push_str(" (unchanged since the previous run)");
+            } else {
+                write!(markdown, " ({delta:+.1} since the previous run)").unwrap();
+            }
+        }
+        markdown.push('\n');
+
+        for change in compare_runs(&self.run, self.previous.as_ref()) {
+            let criterion = change.current;
+            write!(
+                markdown,
+                "\n## {} ({}%): {}/{MAX_SCORE}",
+                criterion.criterion,
+                format_weight(criterion.weight),
+                criterion.score
+            )
+            .unwrap();
+            if let Some(delta) = change.score_delta().filter(|delta| *delta != 0) {
+                write!(markdown, " ({delta:+})").unwrap();
+            }
+            write!(markdown, "\n\n{}\n", criterion.rationale).unwrap();
+
+            if !criterion.sections.is_empty() {
+                writeln!(
+                    markdown,
+                    "\nAddressed in: {}",
+                    criterion.sections.join(", ")
+                )
+                .unwrap();
+            }
+            if !criterion.gaps.is_empty() {
+                markdown.push_str("\nGaps:\n");
+                for (ix, gap) in criterion.gaps.iter().enumerate() {
+                    markdown.push_str("- ");
+                    if let Some(section) = &gap.section {
+                        write!(markdown, "[{section}] ").unwrap();
+                    }
+                    markdown.push_str(&gap.description);
+                    if change.new_gaps.contains(&ix) {
+                        markdown.push_str(" (new)");
+                    }
+                    markdown.push('\n');
+                }
+            }
+            if !change.resolved_gaps.is_empty() {
+                markdown.push_str("\nResolved since the previous run:\n");
+                for gap in &change.resolved_gaps {
+                    writeln!(markdown, "- {}", gap.description).unwrap();
+                }
+            }
+        }
+        markdown
+    }
+}
+
+/// The model responses are scored with by default: the agent's, if its
+/// provider is authenticated.
+pub fn scoring_model(cx: &App) -> Option<Arc<dyn LanguageModel>> {
+    let ConfiguredModel { provider, model } =
+        LanguageModelRegistry::read_global(cx).default_model()?;
+    provider.is_authenticated(cx).then_some(model)
+}
+
+/// Scores a response against each of the tender's evaluation criteria, and
+/// records the run in the project's `scorecards.json`.
+///
+/// The response's buffer is scored, so edits that haven't been saved yet are
+/// included.
+pub fn score_project_response(
+    project: &Entity<Project>,
+    response_path: ProjectPath,
+    model: Arc<dyn LanguageModel>,
+    cx: &mut App,
+) -> Task<Result<Scoring>> {
+    let Some(manifest) = TenderManifestStore::for_project(project, cx)
+        .read(cx)
+        .manifest()
+    else {
+        return Task::ready(Err(anyhow!(
+            "Add a {MANIFEST_FILE_NAME} with the tender's evaluation criteria to score responses"
+        )));
+    };
+    if manifest.evaluation.is_empty() {
+        return Task::ready(Err(anyhow!(
+            "{MANIFEST_FILE_NAME} has no [[evaluation]] criteria to score responses against"
+        )));
+    }
+    let history_abs_path = match history_abs_path(project, &response_path, cx) {
+        Ok(path) => path,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let fs = project.read(cx).fs().clone();
+    let open_buffer = project.update(cx, |project, cx| {
+        project.open_buffer(response_path.clone(), cx)
+    });
+
+    cx.spawn(async move |cx| {
+        let buffer = open_buffer.await?;
+        let text = buffer.read_with(cx, |buffer, _| buffer.text())?;
+        if text.trim().is_empty() {
+            return Err(anyhow!(
+                "{} is empty, so there's nothing to score",
+                response_path.path.display()
+            ));
+        }
+        let sections = prose::sections(&text)
+            .into_iter()
+            .map(|section| section.title)
+            .collect::<Vec<_>>();
+
+        let criteria = future::try_join_all(manifest.evaluation.iter().map(|criterion| {
+            judge::score_criterion(model.clone(), &manifest, criterion, &text, &sections, cx)
+        }))
+        .await?;
+        let run = ScorecardRun {
+            response: response_path.path.to_path_buf(),
+            scored_at: Utc::now(),
+            model: model.name().0.to_string(),
+            sections,
+            criteria,
+        };
+
+        let mut history = load_history(&fs, &history_abs_path).await?;
+        let previous = history.latest(&run.response).0.cloned();
+        history.push(run.clone());
+        fs.atomic_write(history_abs_path, history.to_json()).await?;
+
+        Ok(Scoring { run, previous })
+    })
+}
+
+/// Loads the runs recorded in the `scorecards.json` of a response's worktree.
+pub fn load_project_history(
+    project: &Entity<Project>,
+    response_path: &ProjectPath,
+    cx: &App,
+) -> Task<Result<ScorecardHistory>> {
+    let history_abs_path = match history_abs_path(project, response_path, cx) {
+        Ok(path) => path,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let fs = project.read(cx).fs().clone();
+    cx.background_spawn(async move { load_history(&fs, &history_abs_path).await })
+}
+
+fn history_abs_path(
+    project: &Entity<Project>,
+    response_path: &ProjectPath,
+    cx: &App,
+) -> Result<PathBuf> {
+    let project = project.read(cx);
+    if !project.is_local() {
+        return Err(anyhow!("Responses can only be scored in local projects"));
+    }
+    let worktree = project
+        .worktree_for_id(response_path.worktree_id, cx)
+        .ok_or_else(|| anyhow!("The response's worktree was closed"))?;
+    Ok(worktree.read(cx).abs_path().join(SCORECARDS_FILE_NAME))
+}
+
+async fn load_history(fs: &Arc<dyn Fs>, abs_path: &Path) -> Result<ScorecardHistory> {
+    if !fs.is_file(abs_path).await {
+        return Ok(ScorecardHistory::default());
+    }
+    let json = fs.load(abs_path).await?;
+    ScorecardHistory::from_json(&json)
+        .with_context(|| format!("Failed to parse {SCORECARDS_FILE_NAME}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use language_model::fake_provider::FakeLanguageModel;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_score_project_response(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                "tender.toml": indoc! {r#"
+                    client = "City of Example"
+
+                    [[evaluation]]
+                    criterion = "Methodology"
+                    weight = 60
+
+                    [[evaluation]]
+                    criterion = "Price"
+                    weight = 40
+                "#},
+                "response.md": "# Approach\n\nWe'll mow weekly.\n\n# Pricing\n\nSee the schedule.\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/tender").as_ref()], cx).await;
+        cx.update(|cx| TenderManifestStore::for_project(&project, cx));
+        cx.run_until_parked();
+
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let response_path = ProjectPath {
+            worktree_id,
+            path: Path::new("response.md").into(),
+        };
+        let model = Arc::new(FakeLanguageModel::default());
+        let score = |cx: &mut TestAppContext| {
+            let task = cx.update(|cx| {
+                score_project_response(&project, response_path.clone(), model.clone(), cx)
+            });
+            cx.run_until_parked();
+            let completions = model.pending_completions();
+            assert_eq!(completions.len(), 2);
+            for (completion, judgement) in completions.iter().zip([
+                "<analysis>Generic.</analysis><score>4</score><sections>Approach</sections>\
+                 <gaps><gap section=\"Approach\">No mowing schedule.</gap></gaps>",
+                "<analysis>Clear.</analysis><score>8</score><sections>Pricing</sections>",
+            ]) {
+                model.stream_completion_response(completion, judgement.into());
+                model.end_completion_stream(completion);
+            }
+            task
+        };
+
+        let scoring = score(cx).await.unwrap();
+        assert_eq!(scoring.previous, None);
+        assert_eq!(scoring.run.sections, ["Approach", "Pricing"]);
+        assert_eq!(scoring.run.criteria[0].criterion, "Methodology");
+        assert_eq!(scoring.run.criteria[0].score, 4);
+        assert_eq!(
+            scoring.run.criteria[0].gaps[0].section.as_deref(),
+            Some("Approach")
+        );
+        assert_eq!(scoring.run.criteria[1].score, 8);
+        assert_eq!(scoring.run.total(), 56.);
+
+        let rescoring = score(cx).await.unwrap();
+        assert_eq!(rescoring.previous, Some(scoring.run));
+        assert_eq!(
+            rescoring.to_markdown(),
+            indoc! {"
+                Total: 56.0% (unchanged since the previous run)
+
+                ## Methodology (60%): 4/10
+
+                Generic.
+
+                Addressed in: Approach
+
+                Gaps:
+                - [Approach] No mowing schedule.
+
+                ## Price (40%): 8/10
+
+                Clear.
+
+                Addressed in: Pricing
+            "}
+        );
+
+        let history = cx
+            .update(|cx| load_project_history(&project, &response_path, cx))
+            .await
+            .unwrap();
+        assert_eq!(history.runs_for(Path::new("response.md")).count(), 2);
+    }
+}
@@ -0,0 +1,481 @@
+use crate::run::format_weight;
+use crate::{
+    CriterionChange, MAX_SCORE, ScorecardRun, compare_runs, load_project_history, same_title,
+    score_project_response, scoring_model,
+};
+use chrono::Local;
+use editor::{Editor, scroll::Autoscroll};
+use gpui::{App, Entity, EventEmitter, FocusHandle, Focusable, FontWeight, Task, WeakEntity};
+use project::{Project, ProjectPath};
+use ui::{Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::Workspace;
+use workspace::item::{Item, ItemEvent};
+
+enum Status {
+    Idle,
+    Scoring,
+    Failed(SharedString),
+}
+
+/// The scorecard of a response: its latest run against the evaluation
+/// criteria, compared with the run before it.
+pub struct ScorecardView {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    response_path: ProjectPath,
+    run: Option<ScorecardRun>,
+    previous: Option<ScorecardRun>,
+    status: Status,
+    focus_handle: FocusHandle,
+    _load_history: Task<()>,
+    _scoring: Task<()>,
+}
+
+impl ScorecardView {
+    /// Opens a response's scorecard and scores the response again, reusing
+    /// the scorecard if it's already open.
+    pub fn open(
+        workspace: &mut Workspace,
+        response_path: ProjectPath,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .items_of_type::<Self>(cx)
+            .find(|view| view.read(cx).response_path == response_path);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            existing.update(cx, |view, cx| view.score(cx));
+            return;
+        }
+
+        let workspace_handle = workspace.weak_handle();
+        let project = workspace.project().clone();
+        let view = cx.new(|cx| Self::new(workspace_handle, project, response_path, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        project: Entity<Project>,
+        response_path: ProjectPath,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let load_history = load_project_history(&project, &response_path, cx);
+        let response = response_path.path.clone();
+        let load_history = cx.spawn(async move |this, cx| {
+            let Some(history) = load_history.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                // Show the last run while the response is scored again, unless
+                // scoring has already finished.
+                if this.run.is_none() {
+                    let (latest, previous) = history.latest(&response);
+                    this.run = latest.cloned();
+                    this.previous = previous.cloned();
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+
+        let mut this = Self {
+            workspace,
+            project,
+            response_path,
+            run: None,
+            previous: None,
+            status: Status::Idle,
+            focus_handle: cx.focus_handle(),
+            _load_history: load_history,
+            _scoring: Task::ready(()),
+        };
+        this.score(cx);
+        this
+    }
+
+    pub fn run(&self) -> Option<&ScorecardRun> {
+        self.run.as_ref()
+    }
+
+    pub fn is_scoring(&self) -> bool {
+        matches!(self.status, Status::Scoring)
+    }
+
+    fn score(&mut self, cx: &mut Context<Self>) {
+        if self.is_scoring() {
+            return;
+        }
+        let Some(model) = scoring_model(cx) else {
+            self.status = Status::Failed("Configure a language model to score the response".into());
+            cx.notify();
+            return;
+        };
+
+        let scoring = score_project_response(&self.project, self.response_path.clone(), model, cx);
+        self.status = Status::Scoring;
+        self._scoring = cx.spawn(async move |this, cx| {
+            let scoring = scoring.await;
+            this.update(cx, |this, cx| {
+                match scoring {
+                    Ok(scoring) => {
+                        this.run = Some(scoring.run);
+                        this.previous = scoring.previous;
+                        this.status = Status::Idle;
+                    }
+                    Err(error) => this.status = Status::Failed(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    /// Opens the response at the heading of the section with the given title.
+    fn open_section(&mut self, title: String, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let response_path = self.response_path.clone();
+        let open = workspace.update(cx, |workspace, cx| {
+            workspace.open_path(response_path, None, true, window, cx)
+        });
+        cx.spawn_in(window, async move |_, cx| {
+            let item = open.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update_in(cx, |editor, window, cx| {
+                    let text = editor.buffer().read(cx).snapshot(cx).text();
+                    let Some(section) = prose::sections(&text)
+                        .into_iter()
+                        .find(|section| same_title(&section.title, &title))
+                    else {
+                        return;
+                    };
+                    let offset = section.heading_range.start;
+                    editor.change_selections(Some(Autoscroll::top_relative(4)), window, cx, |s| {
+                        s.select_ranges([offset..offset])
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let status = match &self.status {
+            Status::Idle => None,
+            Status::Scoring => Some(
+                Label::new("Scoring…")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            ),
+            Status::Failed(error) => Some(
+                Label::new(error.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Error),
+            ),
+        };
+
+        h_flex()
+            .justify_between()
+            .gap_4()
+            .child(
+                v_flex()
+                    .child(
+                        Headline::new(format!(
+                            "Scorecard for {}",
+                            self.response_path.path.display()
+                        ))
+                        .size(HeadlineSize::Small),
+                    )
+                    .when_some(self.run.as_ref(), |this, run| {
+                        let scored_at = run.scored_at.with_timezone(&Local);
+                        this.child(
+                            Label::new(format!(
+                                "Scored by {} on {}",
+                                run.model,
+                                scored_at.format("%-d %B %Y at %-I:%M %p")
+                            ))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        )
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .children(status)
+                    .when_some(self.run.as_ref(), |this, run| {
+                        let total = run.total();
+                        this.child(Label::new(format!("{total:.1}%")).size(LabelSize::Large))
+                            .when_some(self.previous.as_ref(), |this, previous| {
+                                this.child(render_delta(total - previous.total(), 1))
+                            })
+                    })
+                    .child(
+                        Button::new("rescore", "Re-run")
+                            .icon(IconName::RotateCw)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .disabled(self.is_scoring())
+                            .tooltip(Tooltip::text(
+                                "Score the response again, including unsaved edits",
+                            ))
+                            .on_click(cx.listener(|this, _, _, cx| this.score(cx))),
+                    ),
+            )
+    }
+
+    fn render_section_button(
+        &self,
+        id: impl Into<ElementId>,
+        title: &str,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let title = title.to_string();
+        Button::new(id, title.clone())
+            .style(ButtonStyle::Filled)
+            .label_size(LabelSize::Small)
+            .tooltip(Tooltip::text("Open the section"))
+            .on_click(
+                cx.listener(move |this, _, window, cx| {
+                    this.open_section(title.clone(), window, cx)
+                }),
+            )
+    }
+
+    fn render_criterion(
+        &self,
+        ix: usize,
+        change: &CriterionChange,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let criterion = change.current;
+        let id = |name: &str| SharedString::from(format!("criterion-{ix}-{name}"));
+
+        v_flex()
+            .p_3()
+            .gap_2()
+            .border_1()
+            .rounded_md()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(criterion.criterion.clone()).weight(FontWeight::SEMIBOLD))
+                    .child(
+                        Label::new(format!("{}% of the score", format_weight(criterion.weight)))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(div().flex_1())
+                    .child(Label::new(format!("{}/{MAX_SCORE}", criterion.score)))
+                    .children(
+                        change
+                            .score_delta()
+                            .map(|delta| render_delta(delta as f64, 0)),
+                    ),
+            )
+            .child(Label::new(criterion.rationale.clone()).size(LabelSize::Small))
+            .when(!criterion.sections.is_empty(), |this| {
+                this.child(
+                    h_flex()
+                        .flex_wrap()
+                        .gap_1()
+                        .child(
+                            Label::new("Addressed in")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .children(criterion.sections.iter().enumerate().map(
+                            |(section_ix, section)| {
+                                self.render_section_button((id("section"), section_ix), section, cx)
+                            },
+                        )),
+                )
+            })
+            .children(criterion.gaps.iter().enumerate().map(|(gap_ix, gap)| {
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::Warning)
+                            .size(IconSize::Small)
+                            .color(Color::Warning),
+                    )
+                    .child(Label::new(gap.description.clone()).size(LabelSize::Small))
+                    .when(change.new_gaps.contains(&gap_ix), |this| {
+                        this.child(
+                            Label::new("New")
+                                .size(LabelSize::XSmall)
+                                .color(Color::Accent),
+                        )
+                    })
+                    .when_some(gap.section.as_ref(), |this, section| {
+                        this.child(self.render_section_button((id("gap"), gap_ix), section, cx))
+                    })
+            }))
+            .children(change.resolved_gaps.iter().map(|gap| {
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(
+                        Label::new(gap.description.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .strikethrough(),
+                    )
+                    .child(
+                        Label::new("Resolved")
+                            .size(LabelSize::XSmall)
+                            .color(Color::Success),
+                    )
+            }))
+    }
+
+    fn render_sections(&self, run: &ScorecardRun, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_3()
+            .child(Headline::new("By Section").size(HeadlineSize::XSmall))
+            .children(
+                run.section_summaries()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, summary)| {
+                        let criteria = summary
+                            .criteria
+                            .iter()
+                            .map(|&criterion_ix| {
+                                let criterion = &run.criteria[criterion_ix];
+                                format!("{} ({}/{MAX_SCORE})", criterion.criterion, criterion.score)
+                            })
+                            .collect::<Vec<_>>();
+                        let addresses = if criteria.is_empty() {
+                            Label::new("Doesn't address any criterion").color(Color::Warning)
+                        } else {
+                            Label::new(format!("Addresses {}", criteria.join(", ")))
+                                .color(Color::Muted)
+                        };
+
+                        v_flex()
+                            .gap_1()
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(match &summary.title {
+                                        Some(title) => self
+                                            .render_section_button(("section", ix), title, cx)
+                                            .into_any_element(),
+                                        None => Label::new("Sections the response doesn't have")
+                                            .weight(FontWeight::SEMIBOLD)
+                                            .into_any_element(),
+                                    })
+                                    .when(summary.title.is_some(), |this| {
+                                        this.child(addresses.size(LabelSize::Small))
+                                    }),
+                            )
+                            .children(summary.gaps.iter().map(|&(criterion_ix, gap_ix)| {
+                                let criterion = &run.criteria[criterion_ix];
+                                Label::new(format!(
+                                    "• {} ({})",
+                                    criterion.gaps[gap_ix].description, criterion.criterion
+                                ))
+                                .size(LabelSize::Small)
+                            }))
+                    }),
+            )
+    }
+}
+
+/// Renders the change in a score, with the given number of decimal places.
+fn render_delta(delta: f64, precision: usize) -> Label {
+    let label = Label::new(format!("{delta:+.precision$}")).size(LabelSize::Small);
+    if delta.abs() < 0.5 * 0.1_f64.powi(precision as i32) {
+        Label::new("No change")
+            .size(LabelSize::Small)
+            .color(Color::Muted)
+    } else if delta > 0. {
+        label.color(Color::Success)
+    } else {
+        label.color(Color::Error)
+    }
+}
+
+impl Render for ScorecardView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("scorecard")
+            .key_context("Scorecard")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .overflow_y_scroll()
+            .p_4()
+            .gap_4()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .map(|this| match &self.run {
+                Some(run) => {
+                    let changes = compare_runs(run, self.previous.as_ref());
+                    this.children(
+                        changes
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, change)| self.render_criterion(ix, change, cx)),
+                    )
+                    .child(self.render_sections(run, cx))
+                }
+                None => this.child(
+                    Label::new(match self.status {
+                        Status::Scoring => "Scoring the response against each criterion…",
+                        _ => "The response hasn't been scored yet.",
+                    })
+                    .color(Color::Muted),
+                ),
+            })
+    }
+}
+
+impl Focusable for ScorecardView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for ScorecardView {}
+
+impl Item for ScorecardView {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Star))
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        let file_name = self
+            .response_path
+            .path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .unwrap_or_default();
+        format!("Scorecard: {file_name}").into()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("Scorecard for {}", self.response_path.path.display()).into())
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
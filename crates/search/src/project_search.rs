@@ -1,13 +1,15 @@
 use crate::{
     BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext,
-    SearchOptions, SelectNextMatch, SelectPreviousMatch, ToggleCaseSensitive, ToggleIncludeIgnored,
-    ToggleRegex, ToggleReplace, ToggleWholeWord, buffer_search::Deploy,
+    SearchOptions, SelectNextMatch, SelectPreviousMatch, ToggleCaseSensitive,
+    ToggleIncludeDocuments, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    buffer_search::Deploy,
 };
 use anyhow::Context as _;
 use collections::{HashMap, HashSet};
 use editor::{
     Anchor, Editor, EditorElement, EditorEvent, EditorSettings, EditorStyle, MAX_TAB_TITLE_LEN,
-    MultiBuffer, actions::SelectAll, items::active_match_index, scroll::Autoscroll,
+    MultiBuffer, actions::SelectAll, display_map::CustomBlockId, items::active_match_index,
+    scroll::Autoscroll,
 };
 use futures::{StreamExt, stream::FuturesOrdered};
 use gpui::{
@@ -16,7 +18,7 @@ use gpui::{
     Render, SharedString, Styled, Subscription, Task, TextStyle, UpdateGlobal, WeakEntity, Window,
     actions, div,
 };
-use language::{Buffer, Language};
+use language::{Buffer, Language, ToPoint as _};
 use menu::Confirm;
 use project::{
     Project, ProjectPath,
@@ -136,7 +138,11 @@ pub struct ProjectSearch {
     excerpts: Entity<MultiBuffer>,
     pending_search: Option<Task<Option<()>>>,
     match_ranges: Vec<Range<Anchor>>,
+    /// The page of a converted document that the first match on each page is
+    /// on.
+    page_markers: Vec<(Anchor, u32)>,
     active_query: Option<SearchQuery>,
+    includes_documents: bool,
     last_search_query_text: Option<String>,
     search_id: usize,
     no_results: Option<bool>,
@@ -170,6 +176,9 @@ pub struct ProjectSearchView {
     replace_enabled: bool,
     included_opened_only: bool,
     regex_language: Option<Arc<Language>>,
+    page_marker_blocks: Vec<CustomBlockId>,
+    /// The search and number of page markers that `page_marker_blocks` show.
+    page_markers_shown: (usize, usize),
     _subscriptions: Vec<Subscription>,
 }
 
@@ -193,7 +202,9 @@ impl ProjectSearch {
             excerpts: cx.new(|_| MultiBuffer::new(capability)),
             pending_search: Default::default(),
             match_ranges: Default::default(),
+            page_markers: Default::default(),
             active_query: None,
+            includes_documents: false,
             last_search_query_text: None,
             search_id: 0,
             no_results: None,
@@ -212,7 +223,9 @@ impl ProjectSearch {
                 .update(cx, |excerpts, cx| cx.new(|cx| excerpts.clone(cx))),
             pending_search: Default::default(),
             match_ranges: self.match_ranges.clone(),
+            page_markers: self.page_markers.clone(),
             active_query: self.active_query.clone(),
+            includes_documents: self.includes_documents,
             last_search_query_text: self.last_search_query_text.clone(),
             search_id: self.search_id,
             no_results: self.no_results,
@@ -237,7 +250,7 @@ impl ProjectSearch {
         }
     }
 
-    fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
+    fn search(&mut self, query: SearchQuery, include_documents: bool, cx: &mut Context<Self>) {
         let search = self.project.update(cx, |project, cx| {
             project
                 .search_history_mut(SearchInputKind::Query)
@@ -258,13 +271,16 @@ impl ProjectSearch {
         });
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
-        self.active_query = Some(query);
+        self.active_query = Some(query.clone());
+        self.includes_documents = include_documents;
         self.match_ranges.clear();
+        self.page_markers.clear();
         self.pending_search = Some(cx.spawn(async move |project_search, cx| {
             let mut matches = pin!(search.ready_chunks(1024));
             project_search
                 .update(cx, |project_search, cx| {
                     project_search.match_ranges.clear();
+                    project_search.page_markers.clear();
                     project_search
                         .excerpts
                         .update(cx, |excerpts, cx| excerpts.clear(cx));
@@ -314,6 +330,73 @@ impl ProjectSearch {
                 }
             }
 
+            if include_documents {
+                let documents = project_search
+                    .update(cx, |project_search, cx| {
+                        document_viewer::search_converted_documents(
+                            &project_search.project,
+                            query,
+                            cx,
+                        )
+                    })
+                    .ok()?
+                    .await;
+                let excerpts = project_search
+                    .update(cx, |project_search, _| project_search.excerpts.clone())
+                    .ok()?;
+                let mut document_ranges = Vec::new();
+                for (buffer, ranges) in documents {
+                    let pages = buffer
+                        .read_with(cx, |buffer, _| {
+                            ranges
+                                .iter()
+                                .map(|range| {
+                                    let row = range.start.to_point(buffer).row;
+                                    document_viewer::converted_page(buffer, row)
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .ok()?;
+                    let new_ranges = excerpts
+                        .update(cx, |excerpts, cx| {
+                            excerpts.set_anchored_excerpts_for_path(
+                                buffer,
+                                ranges,
+                                editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                                cx,
+                            )
+                        })
+                        .ok()?
+                        .await;
+                    project_search
+                        .update(cx, |project_search, _| {
+                            let mut last_page = None;
+                            for (range, page) in new_ranges.iter().zip(pages) {
+                                if let Some(page) = page.filter(|page| last_page != Some(*page)) {
+                                    project_search.page_markers.push((range.start, page));
+                                    last_page = Some(page);
+                                }
+                            }
+                        })
+                        .ok()?;
+                    document_ranges.extend(new_ranges);
+                }
+
+                if !document_ranges.is_empty() {
+                    project_search
+                        .update(cx, |project_search, cx| {
+                            // Documents are listed among the other files by
+                            // path, so keep the matches in the same order.
+                            let snapshot = project_search.excerpts.read(cx).snapshot(cx);
+                            project_search.match_ranges.extend(document_ranges);
+                            project_search
+                                .match_ranges
+                                .sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+                        })
+                        .ok()?;
+                }
+            }
+
             project_search
                 .update(cx, |project_search, cx| {
                     if !project_search.match_ranges.is_empty() {
@@ -710,6 +793,7 @@ impl ProjectSearchView {
                 query_text = active_query.as_str().to_string();
                 replacement_text = active_query.replacement().map(ToOwned::to_owned);
                 options = SearchOptions::from_query(active_query);
+                options.set(SearchOptions::INCLUDE_DOCUMENTS, entity.includes_documents);
             }
         }
         subscriptions.push(cx.observe_in(&entity, window, |this, _, window, cx| {
@@ -838,6 +922,8 @@ impl ProjectSearchView {
             replace_enabled: false,
             included_opened_only: false,
             regex_language: None,
+            page_marker_blocks: Vec::new(),
+            page_markers_shown: (0, 0),
             _subscriptions: subscriptions,
         };
         this.entity_changed(window, cx);
@@ -1000,7 +1086,11 @@ impl ProjectSearchView {
 
     fn search(&mut self, cx: &mut Context<Self>) {
         if let Some(query) = self.build_search_query(cx) {
-            self.entity.update(cx, |model, cx| model.search(query, cx));
+            let include_documents = self
+                .search_options
+                .contains(SearchOptions::INCLUDE_DOCUMENTS);
+            self.entity
+                .update(cx, |model, cx| model.search(query, include_documents, cx));
         }
     }
 
@@ -1269,11 +1359,34 @@ impl ProjectSearchView {
                 self.focus_results_editor(window, cx);
             }
         }
+        self.update_page_markers(cx);
 
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
     }
 
+    /// Shows which page of a converted document the matches in it are on.
+    fn update_page_markers(&mut self, cx: &mut Context<Self>) {
+        let entity = self.entity.read(cx);
+        let shown = (entity.search_id, entity.page_markers.len());
+        if self.page_markers_shown == shown {
+            return;
+        }
+        self.page_markers_shown = shown;
+        let page_markers = entity.page_markers.clone();
+        self.results_editor.update(cx, |editor, cx| {
+            let old_blocks = mem::take(&mut self.page_marker_blocks);
+            editor.remove_blocks(old_blocks.into_iter().collect(), None, cx);
+            self.page_marker_blocks = editor.insert_blocks(
+                page_markers
+                    .into_iter()
+                    .map(|(position, page)| document_viewer::page_marker(position, page)),
+                None,
+                cx,
+            );
+        });
+    }
+
     fn update_match_index(&mut self, cx: &mut Context<Self>) {
         let results_editor = self.results_editor.read(cx);
         let new_index = active_match_index(
@@ -2117,6 +2230,17 @@ impl Render for ProjectSearchBar {
                                     this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
                                 }),
                             ),
+                        )
+                        .child(
+                            SearchOptions::INCLUDE_DOCUMENTS.as_button(
+                                search
+                                    .search_options
+                                    .contains(SearchOptions::INCLUDE_DOCUMENTS),
+                                focus_handle.clone(),
+                                cx.listener(|this, _, _, cx| {
+                                    this.toggle_search_option(SearchOptions::INCLUDE_DOCUMENTS, cx);
+                                }),
+                            ),
                         ),
                 )
         });
@@ -2178,6 +2302,11 @@ impl Render for ProjectSearchBar {
                 this.on_action(cx.listener(|this, _: &ToggleIncludeIgnored, _, cx| {
                     this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
                 }))
+                .on_action(cx.listener(
+                    |this, _: &ToggleIncludeDocuments, _, cx| {
+                        this.toggle_search_option(SearchOptions::INCLUDE_DOCUMENTS, cx);
+                    },
+                ))
             })
             .on_action(cx.listener(Self::select_next_match))
             .on_action(cx.listener(Self::select_prev_match))
@@ -27,6 +27,7 @@ actions!(
         ToggleWholeWord,
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
+        ToggleIncludeDocuments,
         ToggleRegex,
         ToggleReplace,
         ToggleSelection,
@@ -51,6 +52,9 @@ bitflags! {
         const ONE_MATCH_PER_LINE = 0b100000;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 0b10000;
+        /// If set, project search also searches the text that PDFs and Office
+        /// documents were converted to when they were indexed.
+        const INCLUDE_DOCUMENTS = 0b1000000;
     }
 }
 
@@ -60,6 +64,7 @@ impl SearchOptions {
             SearchOptions::WHOLE_WORD => "Match Whole Words",
             SearchOptions::CASE_SENSITIVE => "Match Case Sensitively",
             SearchOptions::INCLUDE_IGNORED => "Also search files ignored by configuration",
+            SearchOptions::INCLUDE_DOCUMENTS => "Also search PDFs and Office documents",
             SearchOptions::REGEX => "Use Regular Expressions",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
@@ -70,6 +75,7 @@ impl SearchOptions {
             SearchOptions::WHOLE_WORD => ui::IconName::WholeWord,
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::Sliders,
+            SearchOptions::INCLUDE_DOCUMENTS => ui::IconName::FileDoc,
             SearchOptions::REGEX => ui::IconName::Regex,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
@@ -80,6 +86,7 @@ impl SearchOptions {
             SearchOptions::WHOLE_WORD => Box::new(ToggleWholeWord),
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
+            SearchOptions::INCLUDE_DOCUMENTS => Box::new(ToggleIncludeDocuments),
             SearchOptions::REGEX => Box::new(ToggleRegex),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
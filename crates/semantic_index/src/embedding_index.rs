@@ -4,6 +4,7 @@ use crate::{
     indexing::{IndexingEntryHandle, IndexingEntrySet},
 };
 use anyhow::{Context as _, Result, anyhow};
+use clarifications::ClarificationRegister;
 use collections::Bound;
 use fs::Fs;
 use fs::MTime;
@@ -45,6 +46,11 @@ pub const MARKITDOWN_EXTENSIONS: &[&str] = &[
     "msg", "eml"
 ];
 
+/// The office and PDF formats at the start of [`MARKITDOWN_EXTENSIONS`]. They
+/// have no text of their own, so they're viewed as the Markdown they convert
+/// to.
+pub const DOCUMENT_EXTENSIONS: &[&str] = MARKITDOWN_EXTENSIONS.split_at(12).0;
+
 pub struct EmbeddingIndex {
     worktree: Entity<Worktree>,
     db_connection: heed::Env,
@@ -270,9 +276,29 @@ impl EmbeddingIndex {
                                 // Determine extension of the file
                                 let ext = entry.path.extension().and_then(|s| s.to_str()).unwrap_or("");
                                 // Prepare text and language for chunking
-                                let (text, language) = if MARKITDOWN_EXTENSIONS.contains(&ext) {
+                                let (text, language) = if clarifications::is_clarifications_path(&entry.path) {
+                                    // Index the answered clarifications as Markdown, so that
+                                    // results quote them rather than their JSON.
+                                    let register = match fs.load(&entry_abs_path).await {
+                                        Ok(json) => ClarificationRegister::from_json(&json),
+                                        Err(_) => continue,
+                                    };
+                                    match register {
+                                        Ok(register) => {
+                                            let lang = language_registry
+                                                .language_for_file_path(&PathBuf::from("file.md"))
+                                                .await
+                                                .ok();
+                                            (register.to_markdown(), lang)
+                                        }
+                                        Err(e) => {
+                                            log::error!("Failed to parse {:?}: {}", entry_abs_path, e);
+                                            continue;
+                                        }
+                                    }
+                                } else if MARKITDOWN_EXTENSIONS.contains(&ext) {
                                     // Convert document to Markdown using MarkItDown
-                                    match convert_document_with_markitdown(path_for_convert).await {
+                                    match convert_document(path_for_convert).await {
                                         Ok(markdown) => {
                                             // Use Markdown language for chunking
                                             let lang = language_registry
@@ -479,6 +505,27 @@ impl EmbeddingIndex {
                 .clone())
         })
     }
+
+    /// The text `path` was indexed with, as long as the version that was
+    /// indexed is the one modified at `mtime`.
+    pub fn text_for_path(
+        &self,
+        path: Arc<Path>,
+        mtime: Option<MTime>,
+        cx: &App,
+    ) -> Task<Result<Option<String>>> {
+        let connection = self.db_connection.clone();
+        let db = self.db;
+        cx.background_spawn(async move {
+            let tx = connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            Ok(db
+                .get(&tx, &db_key_for_path(&path))?
+                .filter(|file| mtime.is_some() && file.mtime == mtime)
+                .map(|file| file.text))
+        })
+    }
 }
 
 struct ScanEntries {
@@ -523,8 +570,10 @@ fn db_key_for_path(path: &Arc<Path>) -> String {
     path.to_string_lossy().replace('/', "\0")
 }
 
-// Convert document using Microsoft MarkItDown
-async fn convert_document_with_markitdown(file_path: PathBuf) -> Result<String> {
+/// Converts a document in one of the [`MARKITDOWN_EXTENSIONS`] formats to
+/// Markdown using Microsoft MarkItDown. Pages of paginated formats such as PDF
+/// are separated by form feeds.
+pub async fn convert_document(file_path: PathBuf) -> Result<String> {
     smol::unblock(move || {
         let output = Command::new("markitdown")
             .arg(file_path.to_str().unwrap())
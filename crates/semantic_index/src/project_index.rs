@@ -12,7 +12,7 @@ use gpui::{
 };
 use language::LanguageRegistry;
 use log;
-use project::{Project, Worktree, WorktreeId};
+use project::{Project, ProjectPath, Worktree, WorktreeId};
 use serde::{Deserialize, Serialize};
 use smol::channel;
 use std::{
@@ -25,6 +25,11 @@ use std::{
 };
 use util::ResultExt;
 
+/// Added to the similarity of chunks from the clarification register, whose
+/// answers override the tender documents, so that they outrank the clauses
+/// they modify.
+const CLARIFICATION_SCORE_BOOST: f32 = 0.1;
+
 #[derive(Debug)]
 pub struct SearchResult {
     pub worktree: Entity<Worktree>,
@@ -40,9 +45,19 @@ pub struct LoadedSearchResult {
     pub full_path: PathBuf,
     pub excerpt_content: String,
     pub row_range: RangeInclusive<u32>,
+    /// The 1-based pages of a converted document the excerpt spans, when the
+    /// conversion kept page breaks.
+    pub page_range: Option<RangeInclusive<u32>>,
     pub query_index: usize,
 }
 
+/// The text a document was converted to when it was indexed.
+pub struct ConvertedText {
+    pub worktree: Entity<Worktree>,
+    pub path: Arc<Path>,
+    pub text: String,
+}
+
 pub struct WorktreeSearchResult {
     pub worktree_id: WorktreeId,
     pub path: Arc<Path>,
@@ -300,8 +315,11 @@ impl ProjectIndex {
                     for results in results_by_worker.iter_mut() {
                         cx.spawn(async {
                             while let Ok((worktree_id, path, chunk)) = chunks_rx.recv().await {
-                                let (score, query_index) =
+                                let (mut score, query_index) =
                                     chunk.embedding.similarity(&query_embeddings);
+                                if clarifications::is_clarifications_path(&path) {
+                                    score += CLARIFICATION_SCORE_BOOST;
+                                }
 
                                 let ix = match results.binary_search_by(|probe| {
                                     score.partial_cmp(&probe.score).unwrap_or(Ordering::Equal)
@@ -410,6 +428,75 @@ impl ProjectIndex {
         result
     }
 
+    /// The text of each indexed document in one of the
+    /// [`MARKITDOWN_EXTENSIONS`](crate::MARKITDOWN_EXTENSIONS) formats, as it
+    /// was converted for indexing, so it can be searched without converting
+    /// it again.
+    pub fn converted_texts(&self, cx: &App) -> Task<Result<Vec<ConvertedText>>> {
+        let scan_tasks = self
+            .worktree_indices(cx)
+            .into_iter()
+            .map(|index| {
+                let index = index.read(cx);
+                let worktree = index.worktree().clone();
+                let db_connection = index.db_connection().clone();
+                let db = *index.embedding_index().db();
+                cx.background_spawn(async move {
+                    let txn = db_connection
+                        .read_txn()
+                        .context("failed to create read transaction")?;
+                    let mut texts = Vec::new();
+                    for db_entry in db.iter(&txn).context("failed to iterate database")? {
+                        let (_key, db_embedded_file) = db_entry?;
+                        let is_converted = db_embedded_file
+                            .path
+                            .extension()
+                            .and_then(|extension| extension.to_str())
+                            .is_some_and(|extension| {
+                                crate::MARKITDOWN_EXTENSIONS
+                                    .contains(&extension.to_lowercase().as_str())
+                            });
+                        if is_converted {
+                            texts.push(ConvertedText {
+                                worktree: worktree.clone(),
+                                path: db_embedded_file.path,
+                                text: db_embedded_file.text,
+                            });
+                        }
+                    }
+                    anyhow::Ok(texts)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        cx.background_spawn(async move {
+            let mut texts = Vec::new();
+            for scan_task in futures::future::join_all(scan_tasks).await {
+                texts.extend(scan_task?);
+            }
+            Ok(texts)
+        })
+    }
+
+    /// The text of a document in one of the
+    /// [`MARKITDOWN_EXTENSIONS`](crate::MARKITDOWN_EXTENSIONS) formats as it
+    /// was converted for indexing, or `None` if its current version hasn't
+    /// been indexed yet.
+    pub fn converted_text(&self, path: &ProjectPath, cx: &App) -> Task<Result<Option<String>>> {
+        let Some(index) = self.worktree_index(path.worktree_id, cx) else {
+            return Task::ready(Ok(None));
+        };
+        let index = index.read(cx);
+        let mtime = index
+            .worktree()
+            .read(cx)
+            .entry_for_path(&path.path)
+            .and_then(|entry| entry.mtime);
+        index
+            .embedding_index()
+            .text_for_path(path.path.clone(), mtime, cx)
+    }
+
     pub fn all_summaries(&self, cx: &App) -> Task<Result<Vec<FileSummary>>> {
         let (summaries_tx, summaries_rx) = channel::bounded(1024);
         let mut worktree_scan_tasks = Vec::new();
@@ -8,22 +8,23 @@ mod summary_backlog;
 mod summary_index;
 mod worktree_index;
 
-use anyhow::{anyhow, Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 use collections::HashMap;
-use gpui::{App, AppContext as _, AsyncApp, BorrowAppContext, Context, Entity, Global, WeakEntity};
+use gpui::{App, AppContext as _, AsyncApp, BorrowAppContext, Context, Entity, Global, Task, WeakEntity};
 use heed::{types::{SerdeBincode, Str}};
 use language::LineEnding;
-use project::{Project, Worktree};
+use project::{Project, ProjectPath, Worktree};
 use std::{
     cmp::Ordering,
     path::{Path, PathBuf},
-    process::Command,
     sync::Arc,
 };
+use util::ResultExt as _;
 use workspace::Workspace;
 
 pub use embedding::*;
-pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
+pub use embedding_index::{DOCUMENT_EXTENSIONS, MARKITDOWN_EXTENSIONS, convert_document};
+pub use project_index::{ConvertedText, LoadedSearchResult, ProjectIndex, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
 pub use summary_index::FileSummary;
 
@@ -225,10 +226,26 @@ impl SemanticDb {
                 file_content[start_line_byte_offset..end_line_byte_offset].to_string();
             LineEnding::normalize(&mut excerpt_content);
 
+            // Converted documents separate their pages with form feeds, so
+            // the excerpt's pages can be cited alongside its lines.
+            let page_range = file_content.contains('\x0c').then(|| {
+                let excerpt = &file_content[range_start..range_end];
+                let last_byte = range_start + excerpt.trim_end_matches(['\x0c', '\n']).len();
+                let page_at = |offset: usize| {
+                    file_content[..offset].matches('\x0c').count() as u32 + 1
+                };
+                page_at(range_start)..=page_at(last_byte)
+            });
+
             if let Some(prev_result) = loaded_results.last_mut() {
                 if prev_result.full_path == full_path {
                     if *prev_result.row_range.end() + 1 == start_row {
                         prev_result.row_range = *prev_result.row_range.start()..=end_row;
+                        if let (Some(prev_pages), Some(pages)) =
+                            (&prev_result.page_range, &page_range)
+                        {
+                            prev_result.page_range = Some(*prev_pages.start()..=*pages.end());
+                        }
                         prev_result.excerpt_content.push_str(&excerpt_content);
                         continue;
                     }
@@ -240,6 +257,7 @@ impl SemanticDb {
                 full_path,
                 excerpt_content,
                 row_range: start_row..=end_row,
+                page_range,
                 query_index,
             });
         }
@@ -255,6 +273,12 @@ impl SemanticDb {
         Ok(loaded_results)
     }
 
+    /// The provider the index is embedded with, for searching other text
+    /// alongside it.
+    pub fn embedding_provider(&self) -> Arc<dyn EmbeddingProvider> {
+        self.embedding_provider.clone()
+    }
+
     pub fn project_index(
         &mut self,
         project: Entity<Project>,
@@ -320,24 +344,50 @@ impl Drop for SemanticDb {
     }
 }
 
-// Convert document using Microsoft MarkItDown for display purposes
-async fn convert_document_for_display(file_path: &Path) -> Result<String> {
-    let path_str = file_path.to_string_lossy().to_string();
-    smol::unblock(move || {
-        let output = Command::new("markitdown")
-            .arg(&path_str)
-            .output()
-            .map_err(|e| anyhow!("Failed to run markitdown: {}. Make sure Microsoft MarkItDown is installed with 'pip install markitdown[all]'", e))?;
-        
-        if output.status.success() {
-            let content = String::from_utf8(output.stdout)
-                .map_err(|e| anyhow!("MarkItDown output is not valid UTF-8: {}", e))?;
-            Ok(content)
-        } else {
-            let error = String::from_utf8_lossy(&output.stderr);
-            Err(anyhow!("MarkItDown conversion failed for {:?}: {}", path_str, error))
+/// Loads a document's text as Markdown. Documents in one of the
+/// [`MARKITDOWN_EXTENSIONS`] formats are read from the project's index when
+/// it has converted their current version, and converted with MarkItDown
+/// otherwise; Markdown and plain text files are read as they are.
+pub fn load_document(
+    project: &Entity<Project>,
+    path: &ProjectPath,
+    cx: &mut App,
+) -> Task<Result<String>> {
+    let extension = path
+        .path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    let is_plain_text = matches!(extension.as_deref(), Some("md" | "markdown" | "txt"));
+    let is_converted = extension
+        .as_deref()
+        .is_some_and(|extension| MARKITDOWN_EXTENSIONS.contains(&extension));
+    if !is_plain_text && !is_converted {
+        return Task::ready(Err(anyhow!("Unsupported document type")));
+    }
+
+    let fs = project.read(cx).fs().clone();
+    let abs_path = project.read(cx).absolute_path(path, cx);
+    let converted_text = if !is_plain_text && cx.has_global::<SemanticDb>() {
+        cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+            semantic_db.project_index(project.clone(), cx)
+        })
+        .map(|project_index| project_index.read(cx).converted_text(path, cx))
+    } else {
+        None
+    };
+    cx.background_spawn(async move {
+        let abs_path = abs_path.context("Failed to find the absolute path")?;
+        if is_plain_text {
+            return fs.load(&abs_path).await;
+        }
+        if let Some(converted_text) = converted_text {
+            if let Some(text) = converted_text.await.log_err().flatten() {
+                return Ok(text);
+            }
         }
-    }).await
+        convert_document(abs_path).await
+    })
 }
 
 #[cfg(test)]
@@ -638,6 +688,7 @@ mod tests {
                 full_path: "fake_project/file1.txt".into(),
                 excerpt_content: "one\ntwo\nthree\n".into(),
                 row_range: 0..=2,
+                page_range: None,
                 query_index: 0,
             }]
         );
@@ -659,6 +710,7 @@ mod tests {
                 full_path: "fake_project/file1.txt".into(),
                 excerpt_content: "two\nthree\nfour\n".into(),
                 row_range: 1..=3,
+                page_range: None,
                 query_index: 0,
             }]
         );
@@ -698,6 +750,7 @@ mod tests {
                     full_path: "fake_project/file2.txt".into(),
                     excerpt_content: file2_content.into(),
                     row_range: 0..=4,
+                    page_range: None,
                     query_index: 1,
                 },
                 LoadedSearchResult {
@@ -705,9 +758,17 @@ mod tests {
                     full_path: "fake_project/file1.txt".into(),
                     excerpt_content: file1_content.into(),
                     row_range: 0..=4,
+                    page_range: None,
                     query_index: 0,
                 }
             ]
         );
     }
+
+    #[test]
+    fn test_document_extensions() {
+        assert_eq!(DOCUMENT_EXTENSIONS.last(), Some(&"pdf"));
+        assert!(DOCUMENT_EXTENSIONS.contains(&"xlsb"));
+        assert!(!DOCUMENT_EXTENSIONS.contains(&"png"));
+    }
 }
@@ -1,4 +1,5 @@
 mod appearance_settings_controls;
+mod writing_settings_page;
 
 use std::any::TypeId;
 
@@ -19,6 +20,8 @@ use workspace::item::{Item, ItemEvent};
 
 use crate::appearance_settings_controls::AppearanceSettingsControls;
 
+pub use writing_settings_page::WritingSettingsPage;
+
 pub struct SettingsUiFeatureFlag;
 
 impl FeatureFlag for SettingsUiFeatureFlag {
@@ -32,7 +35,7 @@ pub struct ImportVsCodeSettings {
 }
 
 impl_actions!(zed, [ImportVsCodeSettings]);
-actions!(zed, [OpenSettingsEditor]);
+actions!(zed, [OpenSettingsEditor, OpenWritingSettings]);
 
 pub fn init(cx: &mut App) {
     cx.observe_new(|workspace: &mut Workspace, window, cx| {
@@ -55,6 +58,21 @@ pub fn init(cx: &mut App) {
             }
         });
 
+        workspace.register_action(|workspace, _: &OpenWritingSettings, window, cx| {
+            let existing = workspace
+                .active_pane()
+                .read(cx)
+                .items()
+                .find_map(|item| item.downcast::<WritingSettingsPage>());
+
+            if let Some(existing) = existing {
+                workspace.activate_item(&existing, true, true, window, cx);
+            } else {
+                let settings_page = WritingSettingsPage::new(workspace, cx);
+                workspace.add_item_to_active_pane(Box::new(settings_page), None, true, window, cx)
+            }
+        });
+
         workspace.register_action(|_workspace, action: &ImportVsCodeSettings, window, cx| {
             let fs = <dyn Fs>::global(cx);
             let action = *action;
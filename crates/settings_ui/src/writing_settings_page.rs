@@ -0,0 +1,520 @@
+use fs::Fs;
+use gpui::{App, Entity, EventEmitter, FocusHandle, Focusable, Subscription};
+use settings::{EditableSettingControl, Settings, SettingsStore, update_settings_file};
+use ui::{
+    CheckboxWithLabel, ContextMenu, DropdownMenu, NumericStepper, SettingsContainer, SettingsGroup,
+    prelude::*,
+};
+use workspace::Workspace;
+use workspace::item::{Item, ItemEvent};
+use writing_app_settings::{
+    FocusModeScope, PageSize, SpellingDictionary, WritingAppSettings, WritingAppSettingsContent,
+};
+
+/// The step, in seconds, of the auto-save interval stepper.
+const AUTO_SAVE_STEP_SECS: u64 = 15;
+/// The step, in millimetres, of the page margin stepper.
+const PAGE_MARGIN_STEP_MM: f32 = 5.;
+/// The step, in points, of the page font size stepper.
+const PAGE_FONT_SIZE_STEP: f32 = 0.5;
+
+/// A page for changing the writing options without editing `settings.json`.
+pub struct WritingSettingsPage {
+    focus_handle: FocusHandle,
+    _settings_subscription: Subscription,
+}
+
+impl WritingSettingsPage {
+    pub fn new(_workspace: &Workspace, cx: &mut Context<Workspace>) -> Entity<Self> {
+        cx.new(|cx| Self {
+            focus_handle: cx.focus_handle(),
+            _settings_subscription: cx.observe_global::<SettingsStore>(|_, cx| cx.notify()),
+        })
+    }
+}
+
+impl EventEmitter<ItemEvent> for WritingSettingsPage {}
+
+impl Focusable for WritingSettingsPage {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for WritingSettingsPage {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Settings))
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Writing Settings".into()
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for WritingSettingsPage {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("writing-settings")
+            .track_focus(&self.focus_handle)
+            .p_4()
+            .size_full()
+            .gap_4()
+            .overflow_y_scroll()
+            .child(Label::new("Writing Settings").size(LabelSize::Large))
+            .child(
+                v_flex().gap_1().child(Label::new("General")).child(
+                    v_flex().elevation_2(cx).child(
+                        SettingsContainer::new()
+                            .child(
+                                SettingsGroup::new("Checking")
+                                    .child(
+                                        h_flex()
+                                            .gap_2()
+                                            .justify_between()
+                                            .child(WritingToggle {
+                                                id: "spelling-check",
+                                                label: "Check Spelling",
+                                                read: |settings| settings.spelling_check_enabled,
+                                                apply: |settings, value| {
+                                                    settings.spelling_check_enabled = Some(value)
+                                                },
+                                            })
+                                            .child(SpellingDictionaryControl),
+                                    )
+                                    .child(WritingToggle {
+                                        id: "grammar-check",
+                                        label: "Check Grammar and Style",
+                                        read: |settings| settings.grammar_check_enabled,
+                                        apply: |settings, value| {
+                                            settings.grammar_check_enabled = Some(value)
+                                        },
+                                    }),
+                            )
+                            .child(
+                                SettingsGroup::new("Status Bar")
+                                    .child(WritingToggle {
+                                        id: "word-count",
+                                        label: "Word Count",
+                                        read: |settings| settings.word_count_visible,
+                                        apply: |settings, value| {
+                                            settings.word_count_visible = Some(value)
+                                        },
+                                    })
+                                    .child(WritingToggle {
+                                        id: "reading-time",
+                                        label: "Reading Time",
+                                        read: |settings| settings.reading_time_visible,
+                                        apply: |settings, value| {
+                                            settings.reading_time_visible = Some(value)
+                                        },
+                                    }),
+                            )
+                            .child(
+                                SettingsGroup::new("Focus Mode").child(
+                                    h_flex()
+                                        .gap_2()
+                                        .justify_between()
+                                        .child(WritingToggle {
+                                            id: "focus-mode",
+                                            label: "Open Workspaces in Focus Mode",
+                                            read: |settings| settings.focus_mode_enabled,
+                                            apply: |settings, value| {
+                                                settings.focus_mode_enabled = Some(value)
+                                            },
+                                        })
+                                        .child(FocusModeScopeControl),
+                                ),
+                            )
+                            .child(SettingsGroup::new("Saving").child(AutoSaveIntervalControl))
+                            .child(
+                                SettingsGroup::new("Page Setup")
+                                    .child(PageSizeControl)
+                                    .child(PageMarginControl)
+                                    .child(PageFontSizeControl)
+                                    .child(WritingToggle {
+                                        id: "number-headings",
+                                        label: "Number Headings",
+                                        read: |settings| settings.number_headings,
+                                        apply: |settings, value| {
+                                            settings.number_headings = Some(value)
+                                        },
+                                    }),
+                            ),
+                    ),
+                ),
+            )
+            .child(
+                v_flex().gap_1().child(Label::new("Interface")).child(
+                    v_flex().elevation_2(cx).child(
+                        SettingsContainer::new().child(
+                            SettingsGroup::new("Features")
+                                .child(WritingToggle {
+                                    id: "simplified-interface",
+                                    label: "Simplified Interface",
+                                    read: |settings| settings.simplified_interface,
+                                    apply: |settings, value| {
+                                        settings.simplified_interface = Some(value)
+                                    },
+                                })
+                                .child(WritingToggle {
+                                    id: "show-git-panel",
+                                    label: "Git Panel",
+                                    read: |settings| settings.show_git_panel,
+                                    apply: |settings, value| settings.show_git_panel = Some(value),
+                                })
+                                .child(WritingToggle {
+                                    id: "show-themes",
+                                    label: "Theme Selector",
+                                    read: |settings| settings.show_themes,
+                                    apply: |settings, value| settings.show_themes = Some(value),
+                                })
+                                .child(WritingToggle {
+                                    id: "show-icon-themes",
+                                    label: "Icon Theme Selector",
+                                    read: |settings| settings.show_icon_themes,
+                                    apply: |settings, value| {
+                                        settings.show_icon_themes = Some(value)
+                                    },
+                                })
+                                .child(WritingToggle {
+                                    id: "show-extensions",
+                                    label: "Extensions",
+                                    read: |settings| settings.show_extensions,
+                                    apply: |settings, value| settings.show_extensions = Some(value),
+                                })
+                                .child(WritingToggle {
+                                    id: "show-api-settings",
+                                    label: "API Provider Settings",
+                                    read: |settings| settings.show_api_settings,
+                                    apply: |settings, value| {
+                                        settings.show_api_settings = Some(value)
+                                    },
+                                }),
+                        ),
+                    ),
+                ),
+            )
+    }
+}
+
+/// A checkbox bound to one of the boolean writing settings.
+#[derive(IntoElement)]
+struct WritingToggle {
+    id: &'static str,
+    label: &'static str,
+    read: fn(&WritingAppSettings) -> bool,
+    apply: fn(&mut WritingAppSettingsContent, bool),
+}
+
+impl RenderOnce for WritingToggle {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let value = (self.read)(WritingAppSettings::get_global(cx));
+        let apply = self.apply;
+
+        CheckboxWithLabel::new(
+            self.id,
+            Label::new(self.label),
+            value.into(),
+            move |selection, _, cx| {
+                let value = match selection {
+                    ToggleState::Selected => true,
+                    ToggleState::Unselected | ToggleState::Indeterminate => false,
+                };
+                update_settings_file::<WritingAppSettings>(
+                    <dyn Fs>::global(cx),
+                    cx,
+                    move |settings, _| apply(settings, value),
+                );
+            },
+        )
+    }
+}
+
+#[derive(IntoElement)]
+struct SpellingDictionaryControl;
+
+impl EditableSettingControl for SpellingDictionaryControl {
+    type Value = SpellingDictionary;
+    type Settings = WritingAppSettings;
+
+    fn name(&self) -> SharedString {
+        "Dictionary".into()
+    }
+
+    fn read(cx: &App) -> Self::Value {
+        WritingAppSettings::get_global(cx).spelling_dictionary
+    }
+
+    fn apply(
+        settings: &mut <Self::Settings as Settings>::FileContent,
+        value: Self::Value,
+        _cx: &App,
+    ) {
+        settings.spelling_dictionary = Some(value);
+    }
+}
+
+impl RenderOnce for SpellingDictionaryControl {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let value = Self::read(cx);
+
+        DropdownMenu::new(
+            "spelling-dictionary",
+            value.label(),
+            ContextMenu::build(window, cx, |mut menu, _window, _cx| {
+                for dictionary in SpellingDictionary::ALL {
+                    menu = menu.custom_entry(
+                        move |_window, _cx| Label::new(dictionary.label()).into_any_element(),
+                        move |_, cx| Self::write(dictionary, cx),
+                    )
+                }
+
+                menu
+            }),
+        )
+    }
+}
+
+#[derive(IntoElement)]
+struct FocusModeScopeControl;
+
+impl EditableSettingControl for FocusModeScopeControl {
+    type Value = FocusModeScope;
+    type Settings = WritingAppSettings;
+
+    fn name(&self) -> SharedString {
+        "Focus Mode Scope".into()
+    }
+
+    fn read(cx: &App) -> Self::Value {
+        WritingAppSettings::get_global(cx).focus_mode_scope
+    }
+
+    fn apply(
+        settings: &mut <Self::Settings as Settings>::FileContent,
+        value: Self::Value,
+        _cx: &App,
+    ) {
+        settings.focus_mode_scope = Some(value);
+    }
+}
+
+impl RenderOnce for FocusModeScopeControl {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let value = Self::read(cx);
+
+        DropdownMenu::new(
+            "focus-mode-scope",
+            value.label(),
+            ContextMenu::build(window, cx, |menu, _window, _cx| {
+                menu.custom_entry(
+                    |_window, _cx| Label::new("Paragraph").into_any_element(),
+                    move |_, cx| Self::write(FocusModeScope::Paragraph, cx),
+                )
+                .custom_entry(
+                    |_window, _cx| Label::new("Sentence").into_any_element(),
+                    move |_, cx| Self::write(FocusModeScope::Sentence, cx),
+                )
+            }),
+        )
+    }
+}
+
+#[derive(IntoElement)]
+struct AutoSaveIntervalControl;
+
+impl EditableSettingControl for AutoSaveIntervalControl {
+    type Value = u64;
+    type Settings = WritingAppSettings;
+
+    fn name(&self) -> SharedString {
+        "Auto-save After".into()
+    }
+
+    fn read(cx: &App) -> Self::Value {
+        WritingAppSettings::get_global(cx).auto_save_interval_ms / 1000
+    }
+
+    fn apply(
+        settings: &mut <Self::Settings as Settings>::FileContent,
+        value: Self::Value,
+        _cx: &App,
+    ) {
+        settings.auto_save_interval_ms = Some(value * 1000);
+    }
+}
+
+impl RenderOnce for AutoSaveIntervalControl {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let value = Self::read(cx);
+        let label = if value == 0 {
+            "Off".to_string()
+        } else {
+            format!("{value}s")
+        };
+
+        h_flex()
+            .gap_2()
+            .justify_between()
+            .child(Label::new(self.name()))
+            .child(NumericStepper::new(
+                "auto-save-interval",
+                label,
+                move |_, _, cx| {
+                    Self::write(value.saturating_sub(AUTO_SAVE_STEP_SECS), cx);
+                },
+                move |_, _, cx| {
+                    Self::write(value + AUTO_SAVE_STEP_SECS, cx);
+                },
+            ))
+    }
+}
+
+#[derive(IntoElement)]
+struct PageSizeControl;
+
+impl EditableSettingControl for PageSizeControl {
+    type Value = PageSize;
+    type Settings = WritingAppSettings;
+
+    fn name(&self) -> SharedString {
+        "Paper Size".into()
+    }
+
+    fn read(cx: &App) -> Self::Value {
+        WritingAppSettings::get_global(cx).page_size
+    }
+
+    fn apply(
+        settings: &mut <Self::Settings as Settings>::FileContent,
+        value: Self::Value,
+        _cx: &App,
+    ) {
+        settings.page_size = Some(value);
+    }
+}
+
+impl RenderOnce for PageSizeControl {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let value = Self::read(cx);
+
+        h_flex()
+            .gap_2()
+            .justify_between()
+            .child(Label::new(self.name()))
+            .child(DropdownMenu::new(
+                "page-size",
+                value.label(),
+                ContextMenu::build(window, cx, |mut menu, _window, _cx| {
+                    for page_size in PageSize::ALL {
+                        menu = menu.custom_entry(
+                            move |_window, _cx| Label::new(page_size.label()).into_any_element(),
+                            move |_, cx| Self::write(page_size, cx),
+                        )
+                    }
+
+                    menu
+                }),
+            ))
+    }
+}
+
+#[derive(IntoElement)]
+struct PageMarginControl;
+
+impl EditableSettingControl for PageMarginControl {
+    type Value = f32;
+    type Settings = WritingAppSettings;
+
+    fn name(&self) -> SharedString {
+        "Margins".into()
+    }
+
+    fn read(cx: &App) -> Self::Value {
+        WritingAppSettings::get_global(cx).page_margin_mm
+    }
+
+    fn apply(
+        settings: &mut <Self::Settings as Settings>::FileContent,
+        value: Self::Value,
+        _cx: &App,
+    ) {
+        settings.page_margin_mm = Some(value);
+    }
+}
+
+impl RenderOnce for PageMarginControl {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let value = Self::read(cx);
+
+        h_flex()
+            .gap_2()
+            .justify_between()
+            .child(Label::new(self.name()))
+            .child(NumericStepper::new(
+                "page-margin",
+                format!("{value} mm"),
+                move |_, _, cx| {
+                    Self::write((value - PAGE_MARGIN_STEP_MM).max(0.), cx);
+                },
+                move |_, _, cx| {
+                    Self::write(value + PAGE_MARGIN_STEP_MM, cx);
+                },
+            ))
+    }
+}
+
+#[derive(IntoElement)]
+struct PageFontSizeControl;
+
+impl EditableSettingControl for PageFontSizeControl {
+    type Value = f32;
+    type Settings = WritingAppSettings;
+
+    fn name(&self) -> SharedString {
+        "Body Text Size".into()
+    }
+
+    fn read(cx: &App) -> Self::Value {
+        WritingAppSettings::get_global(cx).page_font_size
+    }
+
+    fn apply(
+        settings: &mut <Self::Settings as Settings>::FileContent,
+        value: Self::Value,
+        _cx: &App,
+    ) {
+        settings.page_font_size = Some(value);
+    }
+}
+
+impl RenderOnce for PageFontSizeControl {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let value = Self::read(cx);
+
+        h_flex()
+            .gap_2()
+            .justify_between()
+            .child(Label::new(self.name()))
+            .child(NumericStepper::new(
+                "page-font-size",
+                format!("{value} pt"),
+                move |_, _, cx| {
+                    Self::write((value - PAGE_FONT_SIZE_STEP).max(6.), cx);
+                },
+                move |_, _, cx| {
+                    Self::write((value + PAGE_FONT_SIZE_STEP).min(24.), cx);
+                },
+            ))
+    }
+}
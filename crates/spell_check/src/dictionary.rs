@@ -0,0 +1,677 @@
+//! A reader for Hunspell `.aff`/`.dic` dictionary pairs.
+//!
+//! This supports the subset of the affix file format used by the English
+//! dictionaries we ship support for: prefix and suffix rules with conditions
+//! and cross products, the `FLAG` encodings, `TRY` and `REP` for suggestions,
+//! and the `FORBIDDENWORD`, `NEEDAFFIX`, `NOSUGGEST`, `KEEPCASE` and
+//! `ONLYINCOMPOUND` flags. Compounding is not supported.
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::{HashMap, HashSet};
+
+type Flag = u32;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum FlagFormat {
+    #[default]
+    Char,
+    Long,
+    Numeric,
+    Utf8,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<Flag> {
+        let mut result = match self {
+            FlagFormat::Char | FlagFormat::Utf8 => flags.chars().map(|c| c as Flag).collect(),
+            FlagFormat::Long => {
+                let chars = flags.chars().collect::<Vec<_>>();
+                chars
+                    .chunks(2)
+                    .map(|pair| {
+                        let high = pair[0] as Flag;
+                        let low = pair.get(1).map_or(0, |c| *c as Flag);
+                        (high << 16) | low
+                    })
+                    .collect()
+            }
+            FlagFormat::Numeric => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect::<Vec<_>>(),
+        };
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
+    fn parse_one(self, flag: &str) -> Option<Flag> {
+        self.parse(flag).first().copied()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConditionPart {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl ConditionPart {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ConditionPart::Any => true,
+            ConditionPart::Char(expected) => *expected == c,
+            ConditionPart::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Condition(Vec<ConditionPart>);
+
+impl Condition {
+    fn parse(condition: &str) -> Self {
+        if condition == "." {
+            return Self::default();
+        }
+        let mut parts = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => parts.push(ConditionPart::Any),
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            ']' => break,
+                            '^' if set.is_empty() && !negated => negated = true,
+                            c => set.push(c),
+                        }
+                    }
+                    parts.push(ConditionPart::Set {
+                        chars: set,
+                        negated,
+                    });
+                }
+                c => parts.push(ConditionPart::Char(c)),
+            }
+        }
+        Self(parts)
+    }
+
+    fn matches_start(&self, stem: &[char]) -> bool {
+        stem.len() >= self.0.len()
+            && self
+                .0
+                .iter()
+                .zip(stem.iter())
+                .all(|(part, c)| part.matches(*c))
+    }
+
+    fn matches_end(&self, stem: &[char]) -> bool {
+        stem.len() >= self.0.len()
+            && self
+                .0
+                .iter()
+                .rev()
+                .zip(stem.iter().rev())
+                .all(|(part, c)| part.matches(*c))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Condition,
+}
+
+#[derive(Default)]
+struct AffixFile {
+    flag_format: FlagFormat,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    forbidden_word: Option<Flag>,
+    need_affix: Option<Flag>,
+    no_suggest: Option<Flag>,
+    keep_case: Option<Flag>,
+    only_in_compound: Option<Flag>,
+}
+
+impl AffixFile {
+    fn parse(source: &str) -> Result<Self> {
+        let mut affix_file = Self::default();
+        let mut pending_affixes: HashMap<(bool, String), bool> = HashMap::default();
+
+        for (row, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let field = |ix: usize| -> Result<&str> {
+                fields
+                    .get(ix)
+                    .copied()
+                    .with_context(|| format!("missing field {ix} on line {}", row + 1))
+            };
+            match fields[0] {
+                "FLAG" => {
+                    affix_file.flag_format = match field(1)? {
+                        "long" => FlagFormat::Long,
+                        "num" => FlagFormat::Numeric,
+                        "UTF-8" => FlagFormat::Utf8,
+                        other => return Err(anyhow!("unsupported flag format {other}")),
+                    }
+                }
+                "TRY" => affix_file.try_chars = field(1)?.chars().collect(),
+                "REP" if fields.len() >= 3 => {
+                    affix_file
+                        .replacements
+                        .push((field(1)?.replace('_', " "), field(2)?.replace('_', " ")));
+                }
+                "FORBIDDENWORD" => {
+                    affix_file.forbidden_word = affix_file.flag_format.parse_one(field(1)?)
+                }
+                "NEEDAFFIX" | "PSEUDOROOT" => {
+                    affix_file.need_affix = affix_file.flag_format.parse_one(field(1)?)
+                }
+                "NOSUGGEST" => affix_file.no_suggest = affix_file.flag_format.parse_one(field(1)?),
+                "KEEPCASE" => affix_file.keep_case = affix_file.flag_format.parse_one(field(1)?),
+                "ONLYINCOMPOUND" => {
+                    affix_file.only_in_compound = affix_file.flag_format.parse_one(field(1)?)
+                }
+                kind @ ("PFX" | "SFX") => {
+                    let is_prefix = kind == "PFX";
+                    let flag_name = field(1)?.to_string();
+                    let key = (is_prefix, flag_name.clone());
+                    if let Some(cross_product) = pending_affixes.get(&key).copied() {
+                        let flag = affix_file
+                            .flag_format
+                            .parse_one(&flag_name)
+                            .with_context(|| format!("invalid flag on line {}", row + 1))?;
+                        let strip = match field(2)? {
+                            "0" => String::new(),
+                            strip => strip.to_string(),
+                        };
+                        // Continuation classes (`add/flags`) are dropped, as
+                        // twofold affixation is not supported.
+                        let add = match field(3)?.split('/').next().unwrap_or_default() {
+                            "0" => String::new(),
+                            add => add.to_string(),
+                        };
+                        let condition = Condition::parse(fields.get(4).copied().unwrap_or("."));
+                        let affix = Affix {
+                            flag,
+                            cross_product,
+                            strip,
+                            add,
+                            condition,
+                        };
+                        if is_prefix {
+                            affix_file.prefixes.push(affix);
+                        } else {
+                            affix_file.suffixes.push(affix);
+                        }
+                    } else {
+                        pending_affixes.insert(key, field(2)? == "Y");
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(affix_file)
+    }
+}
+
+/// A spelling dictionary loaded from a Hunspell `.aff`/`.dic` pair.
+pub struct Dictionary {
+    affixes: AffixFile,
+    words: HashMap<String, Vec<Vec<Flag>>>,
+    prefixes_by_add: HashMap<String, Vec<usize>>,
+    suffixes_by_add: HashMap<String, Vec<usize>>,
+}
+
+impl Dictionary {
+    /// Parses a dictionary from the raw bytes of its affix and word files,
+    /// honoring the encoding declared by the affix file's `SET` directive.
+    pub fn from_bytes(aff: &[u8], dic: &[u8]) -> Result<Self> {
+        let encoding = aff
+            .split(|byte| *byte == b'\n')
+            .find_map(|line| line.strip_prefix(b"SET "))
+            .map(|encoding| {
+                String::from_utf8_lossy(encoding)
+                    .trim()
+                    .to_ascii_uppercase()
+            });
+        let decode = |bytes: &[u8]| match encoding.as_deref() {
+            Some("ISO8859-1" | "ISO-8859-1") => bytes.iter().map(|byte| *byte as char).collect(),
+            _ => String::from_utf8_lossy(bytes).into_owned(),
+        };
+        Self::new(&decode(aff), &decode(dic))
+    }
+
+    pub fn new(aff: &str, dic: &str) -> Result<Self> {
+        let affixes = AffixFile::parse(aff)?;
+        let mut words: HashMap<String, Vec<Vec<Flag>>> = HashMap::default();
+        let mut lines = dic.lines();
+        lines.next().context("dictionary is empty")?;
+        for line in lines {
+            let Some(entry) = line.split(['\t', ' ']).next() else {
+                continue;
+            };
+            if entry.is_empty() {
+                continue;
+            }
+            let (word, flags) = split_entry(entry);
+            let flags = affixes.flag_format.parse(flags);
+            words.entry(normalize(&word)).or_default().push(flags);
+        }
+
+        let mut prefixes_by_add: HashMap<String, Vec<usize>> = HashMap::default();
+        for (ix, prefix) in affixes.prefixes.iter().enumerate() {
+            prefixes_by_add
+                .entry(prefix.add.clone())
+                .or_default()
+                .push(ix);
+        }
+        let mut suffixes_by_add: HashMap<String, Vec<usize>> = HashMap::default();
+        for (ix, suffix) in affixes.suffixes.iter().enumerate() {
+            suffixes_by_add
+                .entry(suffix.add.clone())
+                .or_default()
+                .push(ix);
+        }
+
+        Ok(Self {
+            affixes,
+            words,
+            prefixes_by_add,
+            suffixes_by_add,
+        })
+    }
+
+    /// Returns whether `word` is spelled correctly.
+    pub fn check(&self, word: &str) -> bool {
+        let word = normalize(word);
+        if word.is_empty() {
+            return true;
+        }
+        if self.check_exact(&word, false) {
+            return true;
+        }
+
+        match Casing::of(&word) {
+            Casing::Title => self.check_exact(&word.to_lowercase(), true),
+            Casing::Upper => {
+                self.check_exact(&word.to_lowercase(), true)
+                    || self.check_exact(&title_case(&word), true)
+            }
+            Casing::Lower | Casing::Mixed => false,
+        }
+    }
+
+    /// Returns likely corrections for a misspelled word, best first.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let word = normalize(word);
+        let casing = Casing::of(&word);
+        let lowercase = word.to_lowercase();
+        let mut seen = HashSet::default();
+        let mut suggestions = Vec::new();
+
+        let mut consider = |candidate: String, suggestions: &mut Vec<String>| {
+            if suggestions.len() >= limit || !seen.insert(candidate.clone()) {
+                return;
+            }
+            let is_valid = candidate.split(' ').all(|part| self.check_suggestion(part));
+            if is_valid {
+                suggestions.push(casing.apply(&candidate));
+            }
+        };
+
+        for (from, to) in &self.affixes.replacements {
+            for (ix, _) in lowercase.match_indices(from.as_str()) {
+                let mut candidate = lowercase.clone();
+                candidate.replace_range(ix..ix + from.len(), to);
+                consider(candidate, &mut suggestions);
+            }
+        }
+
+        let chars = lowercase.chars().collect::<Vec<_>>();
+        let try_chars = if self.affixes.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.affixes
+                .try_chars
+                .iter()
+                .flat_map(|c| c.to_lowercase())
+                .collect::<Vec<_>>()
+        };
+
+        for ix in 0..chars.len().saturating_sub(1) {
+            let mut candidate = chars.clone();
+            candidate.swap(ix, ix + 1);
+            consider(candidate.into_iter().collect(), &mut suggestions);
+        }
+        for &c in &try_chars {
+            for ix in 0..chars.len() {
+                if chars[ix] != c {
+                    let mut candidate = chars.clone();
+                    candidate[ix] = c;
+                    consider(candidate.into_iter().collect(), &mut suggestions);
+                }
+            }
+        }
+        for ix in 0..chars.len() {
+            let mut candidate = chars.clone();
+            candidate.remove(ix);
+            consider(candidate.into_iter().collect(), &mut suggestions);
+        }
+        for &c in &try_chars {
+            for ix in 0..=chars.len() {
+                let mut candidate = chars.clone();
+                candidate.insert(ix, c);
+                consider(candidate.into_iter().collect(), &mut suggestions);
+            }
+        }
+        for ix in 1..chars.len() {
+            let (left, right) = chars.split_at(ix);
+            let candidate = format!(
+                "{} {}",
+                left.iter().collect::<String>(),
+                right.iter().collect::<String>()
+            );
+            consider(candidate, &mut suggestions);
+        }
+
+        suggestions
+    }
+
+    fn check_suggestion(&self, word: &str) -> bool {
+        if word.is_empty() {
+            return false;
+        }
+        if let Some(no_suggest) = self.affixes.no_suggest {
+            if let Some(homonyms) = self.words.get(word) {
+                if homonyms.iter().all(|flags| has_flag(flags, no_suggest)) {
+                    return false;
+                }
+            }
+        }
+        self.check(word)
+    }
+
+    fn check_exact(&self, word: &str, case_folded: bool) -> bool {
+        let root_allowed = |flags: &Vec<Flag>| {
+            !self.is_forbidden(flags)
+                && !self
+                    .affixes
+                    .need_affix
+                    .map_or(false, |f| has_flag(flags, f))
+                && !self
+                    .affixes
+                    .only_in_compound
+                    .map_or(false, |f| has_flag(flags, f))
+                && !(case_folded && self.affixes.keep_case.map_or(false, |f| has_flag(flags, f)))
+        };
+        if let Some(homonyms) = self.words.get(word) {
+            if homonyms.iter().any(|flags| self.is_forbidden(flags)) {
+                return false;
+            }
+            if homonyms.iter().any(root_allowed) {
+                return true;
+            }
+        }
+
+        let chars = word.chars().collect::<Vec<_>>();
+        self.check_suffixed(&chars, case_folded) || self.check_prefixed(&chars, None, case_folded)
+    }
+
+    fn check_suffixed(&self, chars: &[char], case_folded: bool) -> bool {
+        for add_len in 0..chars.len() {
+            let add = chars[chars.len() - add_len..].iter().collect::<String>();
+            let Some(suffixes) = self.suffixes_by_add.get(&add) else {
+                continue;
+            };
+            for suffix in suffixes.iter().map(|ix| &self.affixes.suffixes[*ix]) {
+                let mut stem = chars[..chars.len() - add_len].to_vec();
+                stem.extend(suffix.strip.chars());
+                if !suffix.condition.matches_end(&stem) {
+                    continue;
+                }
+                if self.stem_has_flags(&stem, &[suffix.flag], case_folded) {
+                    return true;
+                }
+                if suffix.cross_product
+                    && self.check_prefixed(&stem, Some(suffix.flag), case_folded)
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn check_prefixed(&self, chars: &[char], suffix_flag: Option<Flag>, case_folded: bool) -> bool {
+        for add_len in 0..chars.len() {
+            let add = chars[..add_len].iter().collect::<String>();
+            let Some(prefixes) = self.prefixes_by_add.get(&add) else {
+                continue;
+            };
+            for prefix in prefixes.iter().map(|ix| &self.affixes.prefixes[*ix]) {
+                if suffix_flag.is_some() && !prefix.cross_product {
+                    continue;
+                }
+                let mut stem = prefix.strip.chars().collect::<Vec<_>>();
+                stem.extend_from_slice(&chars[add_len..]);
+                if !prefix.condition.matches_start(&stem) {
+                    continue;
+                }
+                let mut required = vec![prefix.flag];
+                required.extend(suffix_flag);
+                if self.stem_has_flags(&stem, &required, case_folded) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn stem_has_flags(&self, stem: &[char], required: &[Flag], case_folded: bool) -> bool {
+        let stem = stem.iter().collect::<String>();
+        self.words.get(&stem).map_or(false, |homonyms| {
+            homonyms.iter().any(|flags| {
+                !self.is_forbidden(flags)
+                    && required.iter().all(|flag| has_flag(flags, *flag))
+                    && !(case_folded
+                        && self.affixes.keep_case.map_or(false, |f| has_flag(flags, f)))
+            })
+        })
+    }
+
+    fn is_forbidden(&self, flags: &[Flag]) -> bool {
+        self.affixes
+            .forbidden_word
+            .map_or(false, |forbidden| has_flag(flags, forbidden))
+    }
+}
+
+fn has_flag(flags: &[Flag], flag: Flag) -> bool {
+    flags.binary_search(&flag).is_ok()
+}
+
+fn split_entry(entry: &str) -> (String, &str) {
+    let mut word = String::new();
+    let mut chars = entry.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    word.push(escaped);
+                }
+            }
+            '/' if ix > 0 => return (word, &entry[ix + 1..]),
+            c => word.push(c),
+        }
+    }
+    (word, "")
+}
+
+/// Folds typographic apostrophes into ASCII ones, matching how dictionaries
+/// spell contractions.
+fn normalize(word: &str) -> String {
+    word.replace(['\u{2019}', '\u{02BC}'], "'")
+}
+
+fn title_case(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Casing {
+    Lower,
+    Title,
+    Upper,
+    Mixed,
+}
+
+impl Casing {
+    fn of(word: &str) -> Self {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let Some(first) = letters.next() else {
+            return Casing::Lower;
+        };
+        let rest = letters.collect::<Vec<_>>();
+        let rest_lower = rest.iter().all(|c| !c.is_uppercase());
+        let rest_upper = rest.iter().all(|c| !c.is_lowercase());
+        match (first.is_uppercase(), rest_lower, rest_upper) {
+            (false, true, _) => Casing::Lower,
+            (true, _, true) if !rest.is_empty() => Casing::Upper,
+            (true, true, _) => Casing::Title,
+            _ => Casing::Mixed,
+        }
+    }
+
+    fn apply(self, word: &str) -> String {
+        match self {
+            Casing::Title => title_case(word),
+            Casing::Upper => word.to_uppercase(),
+            Casing::Lower | Casing::Mixed => word.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn dictionary() -> Dictionary {
+        let aff = indoc! {"
+            SET UTF-8
+            TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+            NOSUGGEST !
+            KEEPCASE K
+
+            REP 2
+            REP f ph
+            REP ise ize
+
+            PFX U Y 1
+            PFX U   0     un         .
+
+            SFX D Y 4
+            SFX D   0     d          e
+            SFX D   y     ied        [^aeiou]y
+            SFX D   0     ed         [^ey]
+            SFX D   0     ed         [aeiou]y
+
+            SFX S Y 3
+            SFX S   y     ies        [^aeiou]y
+            SFX S   0     s          [aeiou]y
+            SFX S   0     s          [^sy]
+        "};
+        let dic = indoc! {"
+            8
+            tender/S
+            submit
+            organise/DS
+            lock/UD
+            carry/DS
+            phone/S
+            NASA/K
+            damn/!
+        "};
+        Dictionary::new(aff, dic).unwrap()
+    }
+
+    #[test]
+    fn test_check_roots_and_affixes() {
+        let dictionary = dictionary();
+        for word in [
+            "tender",
+            "tenders",
+            "submit",
+            "organised",
+            "organises",
+            "carried",
+            "carries",
+            "unlocked",
+            "unlock",
+            "locked",
+        ] {
+            assert!(dictionary.check(word), "{word} should be valid");
+        }
+        for word in ["tendered", "submits", "carryed", "unsubmit", "organized"] {
+            assert!(!dictionary.check(word), "{word} should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_check_casing() {
+        let dictionary = dictionary();
+        assert!(dictionary.check("Tender"));
+        assert!(dictionary.check("TENDERS"));
+        assert!(dictionary.check("NASA"));
+        assert!(!dictionary.check("Nasa"));
+        assert!(!dictionary.check("tEnder"));
+        assert!(dictionary.check("Unlocked"));
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("tendr", 3), vec!["tender"]);
+        assert_eq!(dictionary.suggest("Fone", 3), vec!["Phone"]);
+        assert_eq!(dictionary.suggest("organize", 3), vec!["organise"]);
+        assert_eq!(dictionary.suggest("submittender", 3), vec!["submit tender"]);
+        assert!(dictionary.suggest("damm", 3).is_empty());
+    }
+
+    #[test]
+    fn test_flag_formats() {
+        let aff = "FLAG long\nSFX Aa Y 1\nSFX Aa 0 s .\n";
+        let dic = "1\nbid/AaBb\n";
+        let dictionary = Dictionary::new(aff, dic).unwrap();
+        assert!(dictionary.check("bids"));
+
+        let aff = "FLAG num\nSFX 101 Y 1\nSFX 101 0 s .\n";
+        let dic = "1\nbid/7,101\n";
+        let dictionary = Dictionary::new(aff, dic).unwrap();
+        assert!(dictionary.check("bids"));
+    }
+}
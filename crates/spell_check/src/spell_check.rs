@@ -0,0 +1,640 @@
+//! Offline spell checking for Markdown buffers.
+//!
+//! Misspelled words are published as buffer diagnostics, and every editor
+//! offers code actions to replace them with a suggestion, add them to the
+//! project dictionary, or ignore them for the rest of the session.
+
+mod dictionary;
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::{HashMap, HashSet};
+use editor::{CodeActionProvider, Editor, EditorEvent, ExcerptId};
+use fs::Fs;
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity, Window,
+};
+use language::{
+    AnchorRangeExt as _, Buffer, BufferEvent, BufferSnapshot, Diagnostic, DiagnosticSeverity,
+};
+use lsp::NumberOrString;
+use project::{CodeAction, LspAction, ProjectTransaction};
+use prose::DiagnosticSource;
+use serde::{Deserialize, Serialize};
+use settings::SettingsStore;
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
+use writing_app_settings::{SpellingDictionary, writing_settings};
+
+pub use dictionary::Dictionary;
+
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(300);
+const MAX_SUGGESTIONS: usize = 5;
+const SPELL_CHECK_CODE_ACTION_PROVIDER_ID: &str = "spell_check";
+
+struct GlobalSpellChecker(Entity<SpellChecker>);
+
+impl Global for GlobalSpellChecker {}
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut App) {
+    let spell_checker = cx.new(|cx| SpellChecker::new(fs, cx));
+    cx.set_global(GlobalSpellChecker(spell_checker));
+
+    cx.observe_new(|editor: &mut Editor, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        register_editor(editor, window, cx);
+    })
+    .detach();
+}
+
+fn register_editor(editor: &mut Editor, window: &mut Window, cx: &mut Context<Editor>) {
+    if !editor.mode().is_full() || editor.project.is_none() {
+        return;
+    }
+
+    let spell_checker = cx.global::<GlobalSpellChecker>().0.clone();
+    editor.add_code_action_provider(
+        Rc::new(SpellingCodeActionProvider {
+            spell_checker: spell_checker.clone(),
+        }),
+        window,
+        cx,
+    );
+
+    let buffers = editor.buffer().read(cx).all_buffers();
+    for buffer in buffers {
+        spell_checker.update(cx, |spell_checker, cx| {
+            spell_checker.watch_buffer(buffer, cx)
+        });
+    }
+    cx.subscribe(&cx.entity(), move |_, _, event, cx| {
+        if let EditorEvent::ExcerptsAdded { buffer, .. } = event {
+            spell_checker.update(cx, |spell_checker, cx| {
+                spell_checker.watch_buffer(buffer.clone(), cx)
+            });
+        }
+    })
+    .detach();
+}
+
+/// Checks the spelling of every Markdown buffer open in an editor.
+pub struct SpellChecker {
+    fs: Arc<dyn Fs>,
+    /// The dictionary for each language in use, or `None` while it loads or
+    /// if it couldn't be loaded.
+    dictionaries: HashMap<SpellingDictionary, Option<Arc<Dictionary>>>,
+    ignored_words: Arc<HashSet<String>>,
+    project_dictionaries: HashMap<Arc<Path>, Arc<HashSet<String>>>,
+    buffers: HashMap<EntityId, WatchedBuffer>,
+    _settings_subscription: Subscription,
+}
+
+struct WatchedBuffer {
+    buffer: WeakEntity<Buffer>,
+    /// The dictionary the buffer was last checked with, or `None` if spell
+    /// checking is off for it.
+    language: Option<SpellingDictionary>,
+    check: Task<()>,
+    _subscriptions: [Subscription; 2],
+}
+
+/// Words accepted in addition to the dictionary.
+#[derive(Clone, Default)]
+struct KnownWords {
+    ignored: Arc<HashSet<String>>,
+    project: Arc<HashSet<String>>,
+}
+
+impl KnownWords {
+    fn contains(&self, word: &str) -> bool {
+        let lowercase = word.to_lowercase();
+        [&self.ignored, &self.project]
+            .into_iter()
+            .any(|words| words.contains(word) || words.contains(&lowercase))
+    }
+}
+
+impl SpellChecker {
+    fn new(fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Self {
+        let settings_subscription =
+            cx.observe_global::<SettingsStore>(|this, cx| this.settings_changed(cx));
+        Self {
+            fs,
+            dictionaries: HashMap::default(),
+            ignored_words: Arc::default(),
+            project_dictionaries: HashMap::default(),
+            buffers: HashMap::default(),
+            _settings_subscription: settings_subscription,
+        }
+    }
+
+    /// The dictionary for `language`, which starts loading the first time
+    /// it's asked for.
+    fn dictionary(
+        &mut self,
+        language: SpellingDictionary,
+        cx: &mut Context<Self>,
+    ) -> Option<Arc<Dictionary>> {
+        if let Some(dictionary) = self.dictionaries.get(&language) {
+            return dictionary.clone();
+        }
+        self.dictionaries.insert(language, None);
+        self.load_dictionary(language, cx);
+        None
+    }
+
+    fn load_dictionary(&mut self, language: SpellingDictionary, cx: &mut Context<Self>) {
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let dictionary = cx
+                .background_spawn(async move { load_dictionary(fs.as_ref(), language).await })
+                .await;
+            let dictionary = match dictionary {
+                Ok(dictionary) => Some(Arc::new(dictionary)),
+                Err(error) => {
+                    log::warn!(
+                        "failed to load {} spelling dictionary: {error:#}",
+                        language.file_stem()
+                    );
+                    None
+                }
+            };
+            this.update(cx, |this, cx| {
+                this.dictionaries.insert(language, dictionary);
+                this.check_all_buffers(cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn watch_buffer(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.entity_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+
+        let subscriptions = [
+            cx.subscribe(&buffer, |this, buffer, event, cx| match event {
+                BufferEvent::Edited
+                | BufferEvent::Reloaded
+                | BufferEvent::LanguageChanged
+                | BufferEvent::FileHandleChanged => {
+                    this.check_buffer(&buffer, Some(CHECK_DEBOUNCE), cx)
+                }
+                BufferEvent::Saved => {
+                    let is_project_dictionary = buffer.read(cx).file().map_or(false, |file| {
+                        file.path().as_ref() == paths::local_dictionary_file_relative_path()
+                    });
+                    if is_project_dictionary {
+                        if let Some(worktree_root) = worktree_root(&buffer, cx) {
+                            this.reload_project_dictionary(worktree_root, cx);
+                        }
+                    }
+                }
+                _ => {}
+            }),
+            cx.observe_release(&buffer, move |this, _, _| {
+                this.buffers.remove(&buffer_id);
+            }),
+        ];
+        self.buffers.insert(
+            buffer_id,
+            WatchedBuffer {
+                buffer: buffer.downgrade(),
+                language: None,
+                check: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.check_buffer(&buffer, None, cx);
+    }
+
+    fn check_all_buffers(&mut self, cx: &mut Context<Self>) {
+        let buffers = self
+            .buffers
+            .values()
+            .filter_map(|watched| watched.buffer.upgrade())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.check_buffer(&buffer, None, cx);
+        }
+    }
+
+    /// Checks the buffers whose spelling settings have changed.
+    fn settings_changed(&mut self, cx: &mut Context<Self>) {
+        let buffers = self
+            .buffers
+            .values()
+            .filter_map(|watched| {
+                let buffer = watched.buffer.upgrade()?;
+                (spelling_language(&buffer, cx) != watched.language).then_some(buffer)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.check_buffer(&buffer, None, cx);
+        }
+    }
+
+    fn check_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        debounce: Option<Duration>,
+        cx: &mut Context<Self>,
+    ) {
+        let language = spelling_language(buffer, cx);
+        let Some(watched) = self.buffers.get_mut(&buffer.entity_id()) else {
+            return;
+        };
+        watched.language = language;
+        let dictionary = language.and_then(|language| self.dictionary(language, cx));
+        let Some(dictionary) = dictionary else {
+            if let Some(watched) = self.buffers.get_mut(&buffer.entity_id()) {
+                watched.check = Task::ready(());
+            }
+            prose::clear_diagnostics(buffer, DiagnosticSource::Spelling, cx);
+            return;
+        };
+
+        let known_words = KnownWords {
+            ignored: self.ignored_words.clone(),
+            project: worktree_root(buffer, cx)
+                .map(|worktree_root| self.project_dictionary(worktree_root, cx))
+                .unwrap_or_default(),
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let weak_buffer = buffer.downgrade();
+        let check = cx.spawn(async move |_, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+            let diagnostics = cx
+                .background_spawn({
+                    let snapshot = snapshot.clone();
+                    async move { misspellings(&snapshot, &dictionary, &known_words) }
+                })
+                .await;
+            if let Some(buffer) = weak_buffer.upgrade() {
+                cx.update(|cx| {
+                    prose::publish_diagnostics(
+                        &buffer,
+                        DiagnosticSource::Spelling,
+                        &snapshot,
+                        diagnostics,
+                        cx,
+                    )
+                })
+                .ok();
+            }
+        });
+        if let Some(watched) = self.buffers.get_mut(&buffer.entity_id()) {
+            watched.check = check;
+        }
+    }
+
+    fn project_dictionary(
+        &mut self,
+        worktree_root: Arc<Path>,
+        cx: &mut Context<Self>,
+    ) -> Arc<HashSet<String>> {
+        if let Some(words) = self.project_dictionaries.get(&worktree_root) {
+            return words.clone();
+        }
+        self.project_dictionaries
+            .insert(worktree_root.clone(), Arc::default());
+        self.reload_project_dictionary(worktree_root, cx);
+        Arc::default()
+    }
+
+    fn reload_project_dictionary(&mut self, worktree_root: Arc<Path>, cx: &mut Context<Self>) {
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let path = worktree_root.join(paths::local_dictionary_file_relative_path());
+            let words = fs
+                .load(&path)
+                .await
+                .map(|contents| parse_word_list(&contents))
+                .unwrap_or_default();
+            this.update(cx, |this, cx| {
+                this.project_dictionaries
+                    .insert(worktree_root, Arc::new(words));
+                this.check_all_buffers(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn add_to_project_dictionary(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        word: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(worktree_root) = worktree_root(buffer, cx) else {
+            return Task::ready(Err(anyhow!("buffer does not belong to a project")));
+        };
+
+        let mut words = self
+            .project_dictionary(worktree_root.clone(), cx)
+            .as_ref()
+            .clone();
+        words.insert(word.clone());
+        self.project_dictionaries
+            .insert(worktree_root.clone(), Arc::new(words));
+        self.check_all_buffers(cx);
+
+        let fs = self.fs.clone();
+        cx.background_spawn(async move {
+            let path = worktree_root.join(paths::local_dictionary_file_relative_path());
+            let mut contents = fs.load(&path).await.unwrap_or_default();
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&word);
+            contents.push('\n');
+            if let Some(parent) = path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(path, contents).await
+        })
+    }
+
+    fn ignore_word(&mut self, word: String, cx: &mut Context<Self>) {
+        Arc::make_mut(&mut self.ignored_words).insert(word);
+        self.check_all_buffers(cx);
+    }
+}
+
+/// The dictionary to check `buffer` with, or `None` if spell checking is off
+/// for it.
+fn spelling_language(buffer: &Entity<Buffer>, cx: &App) -> Option<SpellingDictionary> {
+    let buffer = buffer.read(cx);
+    let settings = writing_settings(buffer.file(), cx);
+    (settings.spelling_check_enabled && prose::is_markdown(buffer))
+        .then_some(settings.spelling_dictionary)
+}
+
+fn worktree_root(buffer: &Entity<Buffer>, cx: &App) -> Option<Arc<Path>> {
+    let file = project::File::from_dyn(buffer.read(cx).file())?;
+    Some(file.worktree.read(cx).abs_path())
+}
+
+fn parse_word_list(contents: &str) -> HashSet<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn dictionary_dirs(fs: &dyn Fs) -> Vec<PathBuf> {
+    let mut dirs = vec![paths::dictionaries_dir().clone()];
+    if cfg!(target_os = "macos") {
+        if let Some(home_dir) = fs.home_dir() {
+            dirs.push(home_dir.join("Library/Spelling"));
+        }
+        dirs.push(PathBuf::from("/Library/Spelling"));
+    } else if cfg!(any(target_os = "linux", target_os = "freebsd")) {
+        dirs.extend(
+            [
+                "/usr/share/hunspell",
+                "/usr/share/myspell",
+                "/usr/share/myspell/dicts",
+                "/usr/local/share/hunspell",
+            ]
+            .map(PathBuf::from),
+        );
+    }
+    dirs
+}
+
+async fn load_dictionary(fs: &dyn Fs, language: SpellingDictionary) -> Result<Dictionary> {
+    let stem = language.file_stem();
+    for dir in dictionary_dirs(fs) {
+        let aff_path = dir.join(format!("{stem}.aff"));
+        let dic_path = dir.join(format!("{stem}.dic"));
+        if fs.is_file(&aff_path).await && fs.is_file(&dic_path).await {
+            let aff = fs.load_bytes(&aff_path).await?;
+            let dic = fs.load_bytes(&dic_path).await?;
+            return Dictionary::from_bytes(&aff, &dic)
+                .with_context(|| format!("parsing {}", aff_path.display()));
+        }
+    }
+    Err(anyhow!(
+        "no {stem}.aff and {stem}.dic found, install them in {}",
+        paths::dictionaries_dir().display()
+    ))
+}
+
+/// Returns whether a word is worth checking. Numbers, identifiers and
+/// acronyms are skipped, as dictionaries don't cover them.
+fn should_check(word: &str) -> bool {
+    word.chars().any(char::is_alphabetic)
+        && !word.chars().any(|c| c.is_numeric() || c == '_')
+        && !(word.chars().count() > 1 && word.chars().all(|c| !c.is_lowercase()))
+}
+
+fn misspellings(
+    snapshot: &BufferSnapshot,
+    dictionary: &Dictionary,
+    known_words: &KnownWords,
+) -> Vec<(Range<usize>, Diagnostic)> {
+    let text = snapshot.text();
+    let prose_ranges = prose::prose_ranges(&text);
+    prose::words(&text, &prose_ranges)
+        .filter(|range| {
+            let word = &text[range.clone()];
+            should_check(word) && !known_words.contains(word) && !dictionary.check(word)
+        })
+        .map(|range| {
+            let diagnostic = Diagnostic {
+                code: Some(NumberOrString::String("unknown-word".into())),
+                severity: DiagnosticSeverity::INFORMATION,
+                message: format!("Unknown word \"{}\"", &text[range.clone()]),
+                ..Default::default()
+            };
+            (range, diagnostic)
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SpellingAction {
+    Replace { replacement: String },
+    AddToProjectDictionary { word: String },
+    Ignore { word: String },
+}
+
+struct SpellingCodeActionProvider {
+    spell_checker: Entity<SpellChecker>,
+}
+
+impl CodeActionProvider for SpellingCodeActionProvider {
+    fn id(&self) -> Arc<str> {
+        SPELL_CHECK_CODE_ACTION_PROVIDER_ID.into()
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<text::Anchor>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let language = writing_settings(buffer.read(cx).file(), cx).spelling_dictionary;
+        let dictionary = self
+            .spell_checker
+            .read(cx)
+            .dictionaries
+            .get(&language)
+            .cloned()
+            .flatten();
+        let Some(dictionary) = dictionary else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let snapshot = buffer.read(cx).snapshot();
+        let range = range.to_offset(&snapshot);
+        let misspellings =
+            prose::diagnostics_in_range(&snapshot, DiagnosticSource::Spelling, range)
+                .map(|entry| {
+                    let word = snapshot
+                        .text_for_range(entry.range.clone())
+                        .collect::<String>();
+                    let range = snapshot.anchor_before(entry.range.start)
+                        ..snapshot.anchor_after(entry.range.end);
+                    (range, word)
+                })
+                .collect::<Vec<_>>();
+        if misspellings.is_empty() {
+            return Task::ready(Ok(Vec::new()));
+        }
+
+        cx.background_spawn(async move {
+            let mut actions = Vec::new();
+            for (range, word) in misspellings {
+                for replacement in dictionary.suggest(&word, MAX_SUGGESTIONS) {
+                    actions.push(code_action(
+                        range.clone(),
+                        format!("Change to \"{replacement}\""),
+                        SpellingAction::Replace { replacement },
+                    ));
+                }
+                actions.push(code_action(
+                    range.clone(),
+                    format!("Add \"{word}\" to project dictionary"),
+                    SpellingAction::AddToProjectDictionary { word: word.clone() },
+                ));
+                actions.push(code_action(
+                    range,
+                    format!("Ignore \"{word}\""),
+                    SpellingAction::Ignore { word },
+                ));
+            }
+            Ok(actions)
+        })
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Entity<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        _push_to_history: bool,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<ProjectTransaction>> {
+        let LspAction::Action(lsp_action) = action.lsp_action else {
+            return Task::ready(Ok(ProjectTransaction::default()));
+        };
+        let spelling_action = match lsp_action
+            .data
+            .map(serde_json::from_value::<SpellingAction>)
+        {
+            Some(Ok(spelling_action)) => spelling_action,
+            _ => return Task::ready(Err(anyhow!("invalid spelling code action"))),
+        };
+
+        match spelling_action {
+            SpellingAction::Replace { replacement } => {
+                let transaction = buffer.update(cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    buffer.edit([(action.range, replacement)], None, cx);
+                    buffer.end_transaction(cx);
+                    buffer.finalize_last_transaction().cloned()
+                });
+                let mut project_transaction = ProjectTransaction::default();
+                if let Some(transaction) = transaction {
+                    project_transaction.0.insert(buffer, transaction);
+                }
+                Task::ready(Ok(project_transaction))
+            }
+            SpellingAction::AddToProjectDictionary { word } => {
+                let add = self.spell_checker.update(cx, |spell_checker, cx| {
+                    spell_checker.add_to_project_dictionary(&buffer, word, cx)
+                });
+                cx.spawn(async move |_| {
+                    add.await?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+            SpellingAction::Ignore { word } => {
+                self.spell_checker
+                    .update(cx, |spell_checker, cx| spell_checker.ignore_word(word, cx));
+                Task::ready(Ok(ProjectTransaction::default()))
+            }
+        }
+    }
+}
+
+fn code_action(range: Range<text::Anchor>, title: String, action: SpellingAction) -> CodeAction {
+    CodeAction {
+        server_id: DiagnosticSource::Spelling.server_id(),
+        range,
+        lsp_action: LspAction::Action(Box::new(lsp::CodeAction {
+            title,
+            kind: Some(lsp::CodeActionKind::QUICKFIX),
+            data: serde_json::to_value(action).ok(),
+            ..Default::default()
+        })),
+        resolved: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_check() {
+        assert!(should_check("tender"));
+        assert!(should_check("Tender"));
+        assert!(should_check("I"));
+        assert!(!should_check("RFT"));
+        assert!(!should_check("ISO9001"));
+        assert!(!should_check("snake_case"));
+        assert!(!should_check("2025"));
+    }
+
+    #[test]
+    fn test_known_words() {
+        let known_words = KnownWords {
+            ignored: Arc::new(HashSet::from_iter(["Acme".to_string()])),
+            project: Arc::new(HashSet::from_iter(["tenderer".to_string()])),
+        };
+        assert!(known_words.contains("Acme"));
+        assert!(!known_words.contains("acme"));
+        assert!(known_words.contains("tenderer"));
+        assert!(known_words.contains("Tenderer"));
+        assert!(!known_words.contains("bidder"));
+    }
+}
@@ -0,0 +1,476 @@
+use anyhow::{Context as _, Result, anyhow};
+use assistant_tool::TrackedBufferSaver;
+use collections::HashMap;
+use gpui::{App, AppContext as _, Context, Entity, Global, Task, WeakEntity};
+use language::{Buffer, Capability, DiskState, LineEnding, Rope, TextBuffer};
+use project::{Project, ProjectPath, WorktreeId};
+use proto::ToProto as _;
+use std::{
+    any::Any,
+    ffi::{OsStr, OsString},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{CellAddress, Sheet, Workbook};
+
+/// The file of a buffer listing the cells of one sheet of a workbook, which
+/// is how values written to the workbook are tracked for review.
+struct SheetFile {
+    worktree_id: WorktreeId,
+    path: Arc<Path>,
+    abs_path: PathBuf,
+    full_path: PathBuf,
+    file_name: OsString,
+    sheet: String,
+}
+
+impl SheetFile {
+    fn from_buffer(buffer: &Buffer) -> Option<&Self> {
+        let file: &dyn language::File = buffer.file()?.as_ref();
+        let file: &dyn Any = file;
+        file.downcast_ref()
+    }
+}
+
+impl language::File for SheetFile {
+    fn as_local(&self) -> Option<&dyn language::LocalFile> {
+        None
+    }
+
+    fn disk_state(&self) -> DiskState {
+        DiskState::New
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &App) -> PathBuf {
+        self.full_path.clone()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a App) -> &'a OsStr {
+        &self.file_name
+    }
+
+    fn worktree_id(&self, _: &App) -> WorktreeId {
+        self.worktree_id
+    }
+
+    fn to_proto(&self, _: &App) -> language::proto::File {
+        language::proto::File {
+            worktree_id: self.worktree_id.to_proto(),
+            entry_id: None,
+            path: self.path.as_ref().to_proto(),
+            mtime: None,
+            is_deleted: false,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
+
+/// The buffers listing the cells of sheets, by workbook and sheet, so that
+/// each sheet keeps one buffer while the action log tracks it.
+#[derive(Default)]
+struct SheetBuffers(HashMap<(PathBuf, String), WeakEntity<Buffer>>);
+
+impl Global for SheetBuffers {}
+
+/// A sheet of a workbook, with the buffer listing its cells.
+pub struct OpenSheet {
+    pub sheet: Sheet,
+    pub buffer: Entity<Buffer>,
+}
+
+/// Reads the workbook at `path`.
+pub fn load_workbook(
+    project: &Entity<Project>,
+    path: &ProjectPath,
+    cx: &App,
+) -> Task<Result<Workbook>> {
+    let project = project.read(cx);
+    if !project.is_local() {
+        return Task::ready(Err(anyhow!(
+            "Spreadsheets can only be opened in local projects"
+        )));
+    }
+    let Some(abs_path) = project.absolute_path(path, cx) else {
+        return Task::ready(Err(anyhow!("Failed to find the absolute path")));
+    };
+    let fs = project.fs().clone();
+    cx.background_spawn(async move {
+        let bytes = fs.load_bytes(&abs_path).await?;
+        Workbook::read(bytes)
+            .await
+            .with_context(|| format!("Failed to read {}", abs_path.display()))
+    })
+}
+
+/// Reads a sheet of the workbook at `path`, or its first sheet, and returns
+/// it with the buffer listing its cells. If the sheet already has a buffer,
+/// it's brought up to date with the workbook.
+pub fn open_sheet(
+    project: &Entity<Project>,
+    path: &ProjectPath,
+    sheet_name: Option<String>,
+    cx: &mut App,
+) -> Task<Result<OpenSheet>> {
+    let workbook = load_workbook(project, path, cx);
+    let project = project.read(cx);
+    let abs_path = project.absolute_path(path, cx).unwrap_or_default();
+    let Some(worktree) = project.worktree_for_id(path.worktree_id, cx) else {
+        return Task::ready(Err(anyhow!("Failed to find the worktree")));
+    };
+    let full_path = Path::new(worktree.read(cx).root_name()).join(&path.path);
+    let path = path.clone();
+    cx.spawn(async move |cx| {
+        let workbook = workbook.await?;
+        let sheet = match &sheet_name {
+            Some(sheet_name) => workbook.sheet(sheet_name).cloned().ok_or_else(|| {
+                anyhow!(
+                    "The workbook has no sheet named {sheet_name:?}. Its sheets are: {}",
+                    sheet_names(&workbook).join(", ")
+                )
+            })?,
+            None => workbook.sheets[0].clone(),
+        };
+        let text = sheet_text(&sheet);
+
+        let buffer = cx.update(|cx| {
+            let key = (abs_path.clone(), sheet.name.clone());
+            let existing = cx
+                .default_global::<SheetBuffers>()
+                .0
+                .get(&key)
+                .and_then(|buffer| buffer.upgrade());
+            if let Some(buffer) = existing {
+                buffer.update(cx, |buffer, cx| {
+                    if buffer.text() != text {
+                        buffer.set_text(text, cx);
+                    }
+                });
+                return buffer;
+            }
+
+            let file_name = path.path.file_name().unwrap_or(path.path.as_os_str());
+            let file = Arc::new(SheetFile {
+                worktree_id: path.worktree_id,
+                path: path.path.clone(),
+                abs_path: abs_path.clone(),
+                full_path: PathBuf::from(sheet_label(full_path.as_os_str(), &sheet.name)),
+                file_name: sheet_label(file_name, &sheet.name),
+                sheet: sheet.name.clone(),
+            });
+            let buffer = cx.new(|cx| {
+                let buffer = TextBuffer::new_normalized(
+                    0,
+                    cx.entity_id().as_non_zero_u64().into(),
+                    LineEnding::Unix,
+                    Rope::from(text.as_str()),
+                );
+                Buffer::build(buffer, Some(file), Capability::ReadOnly)
+            });
+            let sheet_buffers = cx.default_global::<SheetBuffers>();
+            sheet_buffers
+                .0
+                .retain(|_, buffer| buffer.upgrade().is_some());
+            sheet_buffers.0.insert(key, buffer.downgrade());
+            buffer
+        })?;
+
+        Ok(OpenSheet { sheet, buffer })
+    })
+}
+
+fn sheet_names(workbook: &Workbook) -> Vec<&str> {
+    workbook
+        .sheets
+        .iter()
+        .map(|sheet| sheet.name.as_str())
+        .collect()
+}
+
+/// How a sheet's buffer is named: the workbook's file, then the sheet.
+fn sheet_label(workbook: &OsStr, sheet: &str) -> OsString {
+    let mut label = workbook.to_os_string();
+    label.push(format!(" ({sheet})"));
+    label
+}
+
+/// Sets the values of cells in the buffer listing `sheet`. The cells must be
+/// writable; the workbook is only changed when the buffer is saved.
+pub fn set_cell_values(
+    buffer: &mut Buffer,
+    sheet: &Sheet,
+    values: &[(CellAddress, String)],
+    cx: &mut Context<Buffer>,
+) -> Result<()> {
+    let edits = cell_line_edits(&buffer.text(), sheet, values)?;
+    buffer.edit(edits, None, cx);
+    Ok(())
+}
+
+/// Writes the values listed in a sheet's buffer to its workbook, for the
+/// cells that can be written to and have changed. Cells that are no longer
+/// listed, such as blank cells filled by edits that were rejected, are
+/// cleared.
+pub fn save_sheet_buffer(
+    buffer: &Entity<Buffer>,
+    project: &Entity<Project>,
+    cx: &App,
+) -> Task<Result<()>> {
+    let buffer = buffer.read(cx);
+    let Some(file) = SheetFile::from_buffer(buffer) else {
+        return Task::ready(Err(anyhow!("The buffer doesn't list a sheet")));
+    };
+    let abs_path = file.abs_path.clone();
+    let sheet_name = file.sheet.clone();
+    let text = buffer.text();
+    let fs = project.read(cx).fs().clone();
+    cx.background_spawn(async move {
+        let workbook = Workbook::read(fs.load_bytes(&abs_path).await?).await?;
+        let sheet = workbook
+            .sheet(&sheet_name)
+            .ok_or_else(|| anyhow!("The workbook no longer has a sheet named {sheet_name:?}"))?;
+        let listed = parse_sheet_text(&text);
+        let unlisted = sheet
+            .cells
+            .iter()
+            .filter(|cell| {
+                cell.writable
+                    && !cell.value.is_empty()
+                    && !listed.iter().any(|(address, _)| *address == cell.address)
+            })
+            .map(|cell| (cell.address, String::new()));
+        let values = listed
+            .iter()
+            .filter(|(address, value)| {
+                sheet
+                    .check_write(*address, value)
+                    .is_ok_and(|cell| display_value(&cell.value) != *value)
+            })
+            .cloned()
+            .chain(unlisted)
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            return Ok(());
+        }
+        let bytes = workbook.write(&sheet_name, &values).await?;
+        fs.write(&abs_path, &bytes).await
+    })
+}
+
+/// Saves sheet buffers to their workbooks when the edits to them are
+/// rejected.
+pub(crate) struct SheetBufferSaver;
+
+impl TrackedBufferSaver for SheetBufferSaver {
+    fn can_save(&self, buffer: &Buffer, _: &App) -> bool {
+        SheetFile::from_buffer(buffer).is_some()
+    }
+
+    fn save(
+        &self,
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        save_sheet_buffer(&buffer, &project, cx)
+    }
+}
+
+/// Lists the cells of a sheet one per line, as their address and value
+/// separated by a tab, followed by the formula of calculated cells or
+/// `locked` for other cells that can't be written to.
+pub fn sheet_text(sheet: &Sheet) -> String {
+    let mut text = String::new();
+    for cell in &sheet.cells {
+        text.push_str(&cell_line(
+            cell.address,
+            &cell.value,
+            cell.formula.as_deref(),
+            cell.writable,
+        ));
+        text.push('\n');
+    }
+    text
+}
+
+fn cell_line(address: CellAddress, value: &str, formula: Option<&str>, writable: bool) -> String {
+    let mut line = format!("{address}\t{}", display_value(value));
+    match formula {
+        Some("") => line.push_str("\t= (shared formula)"),
+        Some(formula) => {
+            line.push_str("\t=");
+            line.push_str(formula);
+        }
+        None if !writable => line.push_str("\tlocked"),
+        None => {}
+    }
+    line
+}
+
+/// A value as it's listed: on one line, without tabs.
+fn display_value(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ")
+}
+
+/// The value each line of a sheet's listing gives its cell.
+fn parse_sheet_text(text: &str) -> Vec<(CellAddress, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (address, rest) = line.split_once('\t')?;
+            let address = CellAddress::parse(address).ok()?;
+            let value = rest.split('\t').next().unwrap_or_default();
+            Some((address, value.to_string()))
+        })
+        .collect()
+}
+
+/// The edits to a sheet's listing that set the values of cells. Blank cells
+/// that aren't listed get a line where they'd be in row-major order.
+fn cell_line_edits(
+    text: &str,
+    sheet: &Sheet,
+    values: &[(CellAddress, String)],
+) -> Result<Vec<(Range<usize>, String)>> {
+    sheet.check_writes(values)?;
+
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches('\n');
+        if let Some(address) = content
+            .split_once('\t')
+            .and_then(|(address, _)| CellAddress::parse(address).ok())
+        {
+            lines.push((address, offset..offset + content.len()));
+        }
+        offset += line.len();
+    }
+
+    let mut edits = Vec::new();
+    for (address, value) in values {
+        let line = cell_line(*address, value, None, true);
+        let edit = if let Some((_, range)) = lines.iter().find(|(listed, _)| listed == address) {
+            (range.clone(), line)
+        } else if let Some((_, range)) = lines.iter().find(|(listed, _)| listed > address) {
+            (range.start..range.start, format!("{line}\n"))
+        } else if text.is_empty() || text.ends_with('\n') {
+            (text.len()..text.len(), format!("{line}\n"))
+        } else {
+            (text.len()..text.len(), format!("\n{line}"))
+        };
+        edits.push((*address, edit));
+    }
+
+    // Lines inserted in the same place are kept in row-major order, and
+    // merged with each other and with the edit of the line after them, so
+    // that no two edits touch.
+    edits.sort_by_key(|(address, (range, _))| (range.start, range.end, *address));
+    let mut merged: Vec<(Range<usize>, String)> = Vec::new();
+    for (_, (range, line)) in edits {
+        match merged.last_mut() {
+            Some((last_range, last_line)) if last_range.end == range.start => {
+                last_range.end = range.end;
+                last_line.push_str(&line);
+            }
+            _ => merged.push((range, line)),
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xlsx::tests::pricing_schedule;
+    use gpui::TestAppContext;
+    use language::File as _;
+    use pretty_assertions::assert_eq;
+
+    #[gpui::test]
+    async fn test_sheet_text() {
+        let workbook = Workbook::read(pricing_schedule().await).await.unwrap();
+        let sheet = &workbook.sheets[0];
+        let text = sheet_text(sheet);
+        assert_eq!(
+            text,
+            "A1\tItem\tlocked\n\
+             B1\tUnit rate\tlocked\n\
+             C1\tTotal\tlocked\n\
+             A2\tDesign\tlocked\n\
+             B2\t1200\n\
+             C2\t1320\t=B2*1.1\n\
+             A3\tBuild\tlocked\n\
+             B3\t\n\
+             C3\t0\t=B3*1.1\n"
+        );
+
+        let edit = |values: &[(&str, &str)]| {
+            let values = values
+                .iter()
+                .map(|(cell, value)| (CellAddress::parse(cell).unwrap(), value.to_string()))
+                .collect::<Vec<_>>();
+            let mut edited = text.clone();
+            for (range, line) in cell_line_edits(&text, sheet, &values)?.into_iter().rev() {
+                edited.replace_range(range, &line);
+            }
+            anyhow::Ok(edited)
+        };
+
+        let edited = edit(&[("B3", "950"), ("B2", "1500")]).unwrap();
+        assert!(edited.contains("B2\t1500\nC2\t1320\t=B2*1.1\nA3\tBuild\tlocked\nB3\t950\n"));
+        assert_eq!(
+            parse_sheet_text(&edited)
+                .into_iter()
+                .filter(|(address, value)| {
+                    sheet.cell(*address).unwrap().writable
+                        && display_value(&sheet.cell(*address).unwrap().value) != *value
+                })
+                .collect::<Vec<_>>(),
+            [
+                (CellAddress::parse("B2").unwrap(), "1500".to_string()),
+                (CellAddress::parse("B3").unwrap(), "950".to_string()),
+            ]
+        );
+
+        // Blank cells that can be written are listed where they belong.
+        let edited = edit(&[("D4", "10"), ("D2", "Fixed"), ("A5", "Contingency")]).unwrap();
+        assert!(edited.contains("C2\t1320\t=B2*1.1\nD2\tFixed\nA3\tBuild\tlocked\n"));
+        assert!(edited.ends_with("C3\t0\t=B3*1.1\nD4\t10\nA5\tContingency\n"));
+
+        for (values, error) in [
+            (&[("C2", "1")][..], "C2 is calculated by a formula"),
+            (&[("E2", "1")][..], "E2 is locked"),
+            (
+                &[("B2", "1"), ("B2", "2")][..],
+                "B2 is written more than once",
+            ),
+        ] {
+            assert_eq!(edit(values).unwrap_err().to_string(), error);
+        }
+    }
+
+    #[gpui::test]
+    fn test_sheet_file_to_proto(cx: &mut TestAppContext) {
+        let file = SheetFile {
+            worktree_id: WorktreeId::from_usize(3),
+            path: Path::new("pricing/Schedule.xlsx").into(),
+            abs_path: PathBuf::from("/tender/pricing/Schedule.xlsx"),
+            full_path: PathBuf::from("tender/pricing/Schedule.xlsx (Prices)"),
+            file_name: OsString::from("Schedule.xlsx (Prices)"),
+            sheet: "Prices".to_string(),
+        };
+        let proto = cx.update(|cx| file.to_proto(cx));
+        assert_eq!(proto.worktree_id, 3);
+        assert_eq!(proto.entry_id, None);
+        assert_eq!(proto.path, Path::new("pricing/Schedule.xlsx").to_proto());
+        assert!(!proto.is_deleted);
+    }
+}
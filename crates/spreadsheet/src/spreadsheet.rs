@@ -0,0 +1,21 @@
+//! Reads and writes the cells of XLSX workbooks, such as pricing schedules,
+//! keeping their formulas, formats and protection.
+//!
+//! Workbooks open as a grid of their cells. Values are written through a
+//! buffer listing a sheet's cells, so that the action log can track them for
+//! review like edits to any other file.
+
+mod sheet_buffer;
+mod spreadsheet_view;
+mod xlsx;
+
+use gpui::App;
+
+pub use sheet_buffer::*;
+pub use spreadsheet_view::*;
+pub use xlsx::*;
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<SpreadsheetView>(cx);
+    assistant_tool::set_tracked_buffer_saver(SheetBufferSaver, cx);
+}
@@ -0,0 +1,461 @@
+use anyhow::Result;
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ListHorizontalSizingBehavior,
+    Subscription, Task, UniformListScrollHandle, uniform_list,
+};
+use project::{Project, ProjectEntryId, ProjectPath};
+use settings::Settings as _;
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+use ui::{Tooltip, prelude::*};
+use util::{ResultExt as _, paths::PathExt as _};
+use workspace::{Item, ItemSettings, Pane, item::ProjectItem};
+
+use crate::{Cell, CellAddress, Sheet, Workbook, column_name, load_workbook};
+
+const SPREADSHEET_EXTENSIONS: &[&str] = &["xlsx", "xlsm"];
+
+const CELL_WIDTH: Pixels = px(112.);
+const ROW_HEADER_WIDTH: Pixels = px(48.);
+
+pub fn is_spreadsheet(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            SPREADSHEET_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        })
+}
+
+/// An XLSX workbook in the project, reloaded when it changes on disk.
+pub struct SpreadsheetItem {
+    abs_path: PathBuf,
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    workbook: Workbook,
+    _reload_task: Task<()>,
+    _project_subscription: Subscription,
+}
+
+impl project::ProjectItem for SpreadsheetItem {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        if !is_spreadsheet(&path.path) {
+            return None;
+        }
+
+        let load = load_workbook(project, path, cx);
+        let project = project.clone();
+        let path = path.clone();
+        let abs_path = project
+            .read(cx)
+            .absolute_path(&path, cx)
+            .unwrap_or_default();
+        let entry_id = project
+            .read(cx)
+            .entry_for_path(&path, cx)
+            .map(|entry| entry.id);
+        Some(cx.spawn(async move |cx| {
+            let workbook = load.await?;
+            cx.new(|cx| {
+                let project_subscription =
+                    cx.subscribe(&project, |this: &mut Self, project, event, cx| {
+                        if let project::Event::WorktreeUpdatedEntries(worktree_id, changes) = event
+                        {
+                            if *worktree_id == this.project_path.worktree_id
+                                && changes
+                                    .iter()
+                                    .any(|(path, _, _)| *path == this.project_path.path)
+                            {
+                                this.reload(project, cx);
+                            }
+                        }
+                    });
+                Self {
+                    abs_path,
+                    project_path: path,
+                    entry_id,
+                    workbook,
+                    _reload_task: Task::ready(()),
+                    _project_subscription: project_subscription,
+                }
+            })
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
+
+impl SpreadsheetItem {
+    pub fn workbook(&self) -> &Workbook {
+        &self.workbook
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.project_path.path
+    }
+
+    fn reload(&mut self, project: Entity<Project>, cx: &mut Context<Self>) {
+        let load = load_workbook(&project, &self.project_path, cx);
+        self._reload_task = cx.spawn(async move |this, cx| {
+            // A workbook that's being saved may not be readable yet.
+            let Some(workbook) = load.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.workbook = workbook;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+/// Shows the cells of a workbook's sheets as a grid, with the formula of the
+/// selected cell. Cells that can be written to are highlighted.
+pub struct SpreadsheetView {
+    item: Entity<SpreadsheetItem>,
+    active_sheet: usize,
+    selected: Option<CellAddress>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    _observe_item: Subscription,
+}
+
+impl SpreadsheetView {
+    pub fn new(item: Entity<SpreadsheetItem>, cx: &mut Context<Self>) -> Self {
+        let observe_item = cx.observe(&item, |this, item, cx| {
+            let sheet_count = item.read(cx).workbook.sheets.len();
+            this.active_sheet = this.active_sheet.min(sheet_count.saturating_sub(1));
+            cx.notify();
+        });
+        Self {
+            item,
+            active_sheet: 0,
+            selected: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            _observe_item: observe_item,
+        }
+    }
+
+    fn activate_sheet(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.active_sheet = ix;
+        self.selected = None;
+        cx.notify();
+    }
+
+    fn render_sheet_tabs(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let workbook = self.item.read(cx).workbook();
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().panel_background)
+            .children(workbook.sheets.iter().enumerate().map(|(ix, sheet)| {
+                Button::new(ix, sheet.name.clone())
+                    .label_size(LabelSize::Small)
+                    .toggle_state(ix == self.active_sheet)
+                    .when(sheet.protected, |button| {
+                        button
+                            .icon(IconName::LockOutlined)
+                            .icon_size(IconSize::XSmall)
+                            .icon_position(IconPosition::Start)
+                            .icon_color(Color::Muted)
+                            .tooltip(Tooltip::text(
+                                "Protected: only highlighted cells can be written to",
+                            ))
+                    })
+                    .on_click(cx.listener(move |this, _, _, cx| this.activate_sheet(ix, cx)))
+            }))
+    }
+
+    fn render_formula_bar(&self, sheet: &Sheet, cx: &mut Context<Self>) -> impl IntoElement {
+        let cell = self.selected.map(|address| sheet.cell_or_blank(address));
+        let (contents, status) = match cell {
+            Some(cell) => match &cell.formula {
+                Some(formula) if formula.is_empty() => {
+                    (format!("{} (shared formula)", cell.value), "Formula")
+                }
+                Some(formula) => (format!("={formula}"), "Formula"),
+                None if cell.writable => (cell.value.clone(), "Writable"),
+                None => (cell.value.clone(), "Locked"),
+            },
+            None => (String::new(), ""),
+        };
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                div().w(ROW_HEADER_WIDTH * 2.).child(
+                    Label::new(
+                        self.selected
+                            .map_or(String::new(), |address| address.to_string()),
+                    )
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+                ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .child(Label::new(contents).size(LabelSize::Small).truncate()),
+            )
+            .child(
+                Label::new(status)
+                    .size(LabelSize::XSmall)
+                    .color(Color::Muted),
+            )
+    }
+
+    fn render_rows(&mut self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let item = self.item.read(cx);
+        let Some(sheet) = item.workbook().sheets.get(self.active_sheet) else {
+            return Vec::new();
+        };
+        let columns = sheet.used_range().map_or(0, |range| range.end.column + 1);
+        let colors = cx.theme().colors();
+        let border = colors.border_variant;
+        let header_background = colors.panel_background;
+
+        range
+            .map(|ix| {
+                let mut row = h_flex().h_6().border_b_1().border_color(border);
+                if ix == 0 {
+                    row = row
+                        .bg(header_background)
+                        .child(
+                            div()
+                                .w(ROW_HEADER_WIDTH)
+                                .h_full()
+                                .border_r_1()
+                                .border_color(border),
+                        )
+                        .children((0..columns).map(|column| {
+                            div()
+                                .w(CELL_WIDTH)
+                                .h_full()
+                                .flex()
+                                .justify_center()
+                                .items_center()
+                                .border_r_1()
+                                .border_color(border)
+                                .child(
+                                    Label::new(column_name(column))
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Muted),
+                                )
+                        }));
+                    return row.into_any_element();
+                }
+
+                let row_ix = ix as u32 - 1;
+                let start = sheet
+                    .cells
+                    .partition_point(|cell| cell.address.row < row_ix);
+                let end = sheet
+                    .cells
+                    .partition_point(|cell| cell.address.row <= row_ix);
+                let mut cells = sheet.cells[start..end].iter().peekable();
+
+                row = row.child(
+                    div()
+                        .w(ROW_HEADER_WIDTH)
+                        .h_full()
+                        .flex()
+                        .items_center()
+                        .justify_end()
+                        .px_1()
+                        .bg(header_background)
+                        .border_r_1()
+                        .border_color(border)
+                        .child(
+                            Label::new((row_ix + 1).to_string())
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted),
+                        ),
+                );
+                for column in 0..columns {
+                    let address = CellAddress {
+                        row: row_ix,
+                        column,
+                    };
+                    let cell = cells.next_if(|cell| cell.address == address);
+                    // Blank cells are only highlighted where the buyer left
+                    // them unlocked in a protected sheet.
+                    let writable = match cell {
+                        Some(cell) => cell.writable,
+                        None => sheet.protected && sheet.cell_or_blank(address).writable,
+                    };
+                    row = row.child(self.render_cell(address, cell, writable, columns, cx));
+                }
+                row.into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_cell(
+        &self,
+        address: CellAddress,
+        cell: Option<&Cell>,
+        writable: bool,
+        columns: u32,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let is_selected = self.selected == Some(address);
+        let value = cell.map_or("", |cell| cell.value.as_str());
+        let is_number = !value.is_empty() && value.parse::<f64>().is_ok();
+        let color = match cell {
+            Some(cell) if cell.formula.is_some() => Color::Accent,
+            _ => Color::Default,
+        };
+
+        div()
+            .id((
+                "cell",
+                address.row as usize * columns as usize + address.column as usize,
+            ))
+            .w(CELL_WIDTH)
+            .h_full()
+            .flex()
+            .items_center()
+            .px_1()
+            .overflow_hidden()
+            .border_r_1()
+            .border_color(colors.border_variant)
+            .when(is_number, |element| element.justify_end())
+            .when(writable, |element| {
+                element.bg(cx.theme().status().info_background.opacity(0.2))
+            })
+            .when(is_selected, |element| {
+                element.border_1().border_color(colors.border_focused)
+            })
+            .child(
+                Label::new(value.replace(['\t', '\r', '\n'], " "))
+                    .size(LabelSize::Small)
+                    .color(color)
+                    .truncate(),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.selected = Some(address);
+                window.focus(&this.focus_handle);
+                cx.notify();
+            }))
+    }
+}
+
+impl Render for SpreadsheetView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let item = self.item.read(cx);
+        let Some(sheet) = item.workbook().sheets.get(self.active_sheet).cloned() else {
+            return v_flex().size_full().track_focus(&self.focus_handle);
+        };
+        let row_count = sheet.used_range().map_or(0, |range| range.end.row + 1) as usize;
+
+        v_flex()
+            .key_context("SpreadsheetView")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_sheet_tabs(cx))
+            .child(self.render_formula_bar(&sheet, cx))
+            .child(
+                uniform_list(
+                    cx.entity(),
+                    "spreadsheet-rows",
+                    row_count + 1,
+                    |this, range, _window, cx| this.render_rows(range, cx),
+                )
+                .flex_1()
+                .size_full()
+                .with_horizontal_sizing_behavior(ListHorizontalSizingBehavior::Unconstrained)
+                .with_width_from_item(Some(0))
+                .track_scroll(self.scroll_handle.clone()),
+            )
+    }
+}
+
+impl Focusable for SpreadsheetView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<()> for SpreadsheetView {}
+
+impl Item for SpreadsheetView {
+    type Event = ();
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        let path = self.item.read(cx).path();
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .to_string()
+            .into()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.item.read(cx).path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = &self.item.read(cx).abs_path;
+        Some(abs_path.compact().to_string_lossy().to_string().into())
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.item.entity_id(), self.item.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &App) -> bool {
+        true
+    }
+}
+
+impl ProjectItem for SpreadsheetView {
+    type Item = SpreadsheetItem;
+
+    fn for_project_item(
+        _: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, cx)
+    }
+}
@@ -0,0 +1,1027 @@
+use anyhow::{Context as _, Result, anyhow};
+use async_zip::base::read::mem::ZipFileReader;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use collections::{HashMap, HashSet};
+use futures::AsyncReadExt as _;
+use std::fmt::{self, Write as _};
+use std::ops::{Range, RangeInclusive};
+
+const WORKBOOK_PART: &str = "xl/workbook.xml";
+const WORKBOOK_RELS_PART: &str = "xl/_rels/workbook.xml.rels";
+const SHARED_STRINGS_PART: &str = "xl/sharedStrings.xml";
+const STYLES_PART: &str = "xl/styles.xml";
+
+const RELATIONSHIPS_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// The position of a cell, 0-based, written like `B4`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CellAddress {
+    pub row: u32,
+    pub column: u32,
+}
+
+impl CellAddress {
+    pub fn parse(address: &str) -> Result<Self> {
+        let address = address.trim().replace('$', "");
+        let letters = address
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .ok_or_else(|| anyhow!("{address:?} isn't a cell address"))?;
+        let (column_name, row) = address.split_at(letters);
+        anyhow::ensure!(
+            !column_name.is_empty() && column_name.len() <= 3,
+            "{address:?} isn't a cell address"
+        );
+        let row: u32 = row
+            .parse()
+            .ok()
+            .filter(|row| *row > 0)
+            .ok_or_else(|| anyhow!("{address:?} isn't a cell address"))?;
+        let column = column_name.bytes().fold(0, |column, letter| {
+            column * 26 + (letter.to_ascii_uppercase() - b'A') as u32 + 1
+        }) - 1;
+        Ok(Self {
+            row: row - 1,
+            column,
+        })
+    }
+}
+
+impl fmt::Display for CellAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", column_name(self.column), self.row + 1)
+    }
+}
+
+/// A rectangle of cells, written like `A1:F40`, or a single cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellRange {
+    pub start: CellAddress,
+    pub end: CellAddress,
+}
+
+impl CellRange {
+    pub fn parse(range: &str) -> Result<Self> {
+        let (start, end) = match range.split_once(':') {
+            Some((start, end)) => (CellAddress::parse(start)?, CellAddress::parse(end)?),
+            None => {
+                let cell = CellAddress::parse(range)?;
+                (cell, cell)
+            }
+        };
+        Ok(Self {
+            start: CellAddress {
+                row: start.row.min(end.row),
+                column: start.column.min(end.column),
+            },
+            end: CellAddress {
+                row: start.row.max(end.row),
+                column: start.column.max(end.column),
+            },
+        })
+    }
+
+    pub fn contains(&self, address: CellAddress) -> bool {
+        (self.start.row..=self.end.row).contains(&address.row)
+            && (self.start.column..=self.end.column).contains(&address.column)
+    }
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}:{}", self.start, self.end)
+        }
+    }
+}
+
+/// The letters naming a 0-based column: `A` to `Z`, then `AA` and so on.
+pub fn column_name(mut column: u32) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (column % 26) as u8);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub address: CellAddress,
+    /// The value as Excel last calculated it, for formula cells.
+    pub value: String,
+    /// The formula, without its leading `=`. Cells that share a formula
+    /// written in another cell have an empty one.
+    pub formula: Option<String>,
+    /// Whether the cell's format locks it when its sheet is protected.
+    pub locked: bool,
+    /// Whether a value can be written to the cell: it has no formula, and
+    /// either it isn't locked or its sheet isn't protected.
+    pub writable: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sheet {
+    pub name: String,
+    pub protected: bool,
+    /// The cells in the sheet's XML, in row-major order. Cells that have
+    /// neither a value nor a format aren't included.
+    pub cells: Vec<Cell>,
+    part: String,
+    /// The format of the blank cells in rows that have one, by row.
+    row_formats: HashMap<u32, usize>,
+    /// The format of the blank cells in ranges of columns that have one.
+    column_formats: Vec<(RangeInclusive<u32>, usize)>,
+    /// Whether each cell format locks its cells, by index.
+    locked_formats: Vec<bool>,
+}
+
+impl Sheet {
+    pub fn cell(&self, address: CellAddress) -> Option<&Cell> {
+        self.cells
+            .binary_search_by_key(&address, |cell| cell.address)
+            .ok()
+            .map(|ix| &self.cells[ix])
+    }
+
+    /// The cell at `address`, or the blank cell there if the sheet's XML
+    /// has none, formatted like its row or column.
+    pub fn cell_or_blank(&self, address: CellAddress) -> Cell {
+        if let Some(cell) = self.cell(address) {
+            return cell.clone();
+        }
+        let locked = self.is_locked(self.blank_format(address));
+        Cell {
+            address,
+            value: String::new(),
+            formula: None,
+            locked,
+            writable: !self.protected || !locked,
+        }
+    }
+
+    fn blank_format(&self, address: CellAddress) -> usize {
+        if let Some(format) = self.row_formats.get(&address.row) {
+            return *format;
+        }
+        self.column_formats
+            .iter()
+            .find(|(columns, _)| columns.contains(&address.column))
+            .map_or(0, |(_, format)| *format)
+    }
+
+    fn is_locked(&self, format: usize) -> bool {
+        self.locked_formats.get(format).copied().unwrap_or(true)
+    }
+
+    /// Checks that `value` can be written to the cell at `address`, which
+    /// may be blank.
+    pub fn check_write(&self, address: CellAddress, value: &str) -> Result<Cell> {
+        let cell = self.cell_or_blank(address);
+        if cell.formula.is_some() {
+            anyhow::bail!("{address} is calculated by a formula");
+        }
+        if !cell.writable {
+            anyhow::bail!("{address} is locked");
+        }
+        anyhow::ensure!(
+            !value.contains(['\t', '\n', '\r']),
+            "the value for {address} has a tab or line break"
+        );
+        Ok(cell)
+    }
+
+    /// Checks that each of `values` can be written, and that no cell is
+    /// written more than once.
+    pub fn check_writes(&self, values: &[(CellAddress, String)]) -> Result<()> {
+        let mut written = HashSet::default();
+        for (address, value) in values {
+            anyhow::ensure!(
+                written.insert(*address),
+                "{address} is written more than once"
+            );
+            self.check_write(*address, value)?;
+        }
+        Ok(())
+    }
+
+    pub fn cells_in(&self, range: CellRange) -> impl Iterator<Item = &Cell> {
+        self.cells
+            .iter()
+            .filter(move |cell| range.contains(cell.address))
+    }
+
+    /// The range from `A1` to the last row and column with a cell.
+    pub fn used_range(&self) -> Option<CellRange> {
+        let end = CellAddress {
+            row: self.cells.iter().map(|cell| cell.address.row).max()?,
+            column: self.cells.iter().map(|cell| cell.address.column).max()?,
+        };
+        Some(CellRange {
+            start: CellAddress { row: 0, column: 0 },
+            end,
+        })
+    }
+}
+
+/// An XLSX workbook, keeping every part of its package so that values can
+/// be written to it without losing formulas, formats or anything else.
+pub struct Workbook {
+    pub sheets: Vec<Sheet>,
+    parts: Vec<(String, Vec<u8>)>,
+}
+
+impl Workbook {
+    pub async fn read(bytes: Vec<u8>) -> Result<Self> {
+        let reader = ZipFileReader::new(bytes)
+            .await
+            .context("it isn't an XLSX workbook")?;
+        let mut parts = Vec::new();
+        for ix in 0..reader.file().entries().len() {
+            let name = reader.file().entries()[ix].filename().as_str()?.to_string();
+            if name.ends_with('/') {
+                continue;
+            }
+            let mut contents = Vec::new();
+            reader
+                .reader_with_entry(ix)
+                .await?
+                .read_to_end(&mut contents)
+                .await?;
+            parts.push((name, contents));
+        }
+        Self::from_parts(parts)
+    }
+
+    fn from_parts(parts: Vec<(String, Vec<u8>)>) -> Result<Self> {
+        let text = |name: &str| {
+            parts
+                .iter()
+                .find(|(part, _)| part == name)
+                .map(|(_, contents)| String::from_utf8_lossy(contents).into_owned())
+        };
+
+        let workbook = text(WORKBOOK_PART).context("it isn't an XLSX workbook")?;
+        let workbook = roxmltree::Document::parse(&workbook)?;
+        let rels = text(WORKBOOK_RELS_PART).unwrap_or_default();
+        let targets = relationship_targets(&rels)?;
+        let shared_strings = match text(SHARED_STRINGS_PART) {
+            Some(shared_strings) => parse_shared_strings(&shared_strings)?,
+            None => Vec::new(),
+        };
+        let locked_formats = match text(STYLES_PART) {
+            Some(styles) => parse_locked_formats(&styles)?,
+            None => Vec::new(),
+        };
+
+        let mut sheets = Vec::new();
+        for sheet in workbook
+            .descendants()
+            .filter(|node| node.tag_name().name() == "sheet")
+        {
+            let name = sheet.attribute("name").unwrap_or_default().to_string();
+            let Some(part) = sheet
+                .attribute((RELATIONSHIPS_NAMESPACE, "id"))
+                .and_then(|id| targets.get(id))
+            else {
+                continue;
+            };
+            // Chart sheets and macro sheets have no cells to read.
+            let Some(xml) = text(part) else {
+                continue;
+            };
+            let sheet = parse_sheet(
+                &xml,
+                name.clone(),
+                part.clone(),
+                &shared_strings,
+                &locked_formats,
+            )
+            .with_context(|| format!("failed to read sheet {name:?}"))?;
+            sheets.push(sheet);
+        }
+        anyhow::ensure!(!sheets.is_empty(), "the workbook has no worksheets");
+
+        Ok(Self { sheets, parts })
+    }
+
+    pub fn sheet(&self, name: &str) -> Option<&Sheet> {
+        self.sheets
+            .iter()
+            .find(|sheet| sheet.name == name)
+            .or_else(|| {
+                self.sheets
+                    .iter()
+                    .find(|sheet| sheet.name.eq_ignore_ascii_case(name))
+            })
+    }
+
+    /// Writes `values` to cells of the sheet named `sheet_name`, keeping each
+    /// cell's format, and returns the workbook's new contents.
+    ///
+    /// Every cell must be writable. Formulas are left as they are, and the
+    /// workbook is marked to be recalculated when it's next opened so that
+    /// they reflect the new values.
+    pub async fn write(
+        &self,
+        sheet_name: &str,
+        values: &[(CellAddress, String)],
+    ) -> Result<Vec<u8>> {
+        let sheet = self
+            .sheet(sheet_name)
+            .ok_or_else(|| anyhow!("the workbook has no sheet named {sheet_name:?}"))?;
+        sheet.check_writes(values)?;
+
+        let mut parts = self.parts.clone();
+        for (name, contents) in &mut parts {
+            if *name == sheet.part {
+                let xml = write_cells(&String::from_utf8_lossy(contents), sheet, values)?;
+                *contents = xml.into_bytes();
+            } else if name == WORKBOOK_PART {
+                let xml = recalculate_on_load(&String::from_utf8_lossy(contents))?;
+                *contents = xml.into_bytes();
+            }
+        }
+
+        let mut writer = ZipFileWriter::new(Vec::new());
+        for (name, contents) in parts {
+            let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+            writer.write_entry_whole(entry, &contents).await?;
+        }
+        Ok(writer.close().await?)
+    }
+}
+
+/// The part each relationship of the workbook refers to, by its ID.
+fn relationship_targets(rels: &str) -> Result<HashMap<String, String>> {
+    if rels.is_empty() {
+        return Ok(HashMap::default());
+    }
+    let rels = roxmltree::Document::parse(rels)?;
+    Ok(rels
+        .descendants()
+        .filter(|node| node.tag_name().name() == "Relationship")
+        .filter_map(|node| {
+            let id = node.attribute("Id")?;
+            let target = node.attribute("Target")?;
+            let target = match target.strip_prefix('/') {
+                Some(target) => target.to_string(),
+                None => format!("xl/{target}"),
+            };
+            Some((id.to_string(), target))
+        })
+        .collect())
+}
+
+fn parse_shared_strings(xml: &str) -> Result<Vec<String>> {
+    let document = roxmltree::Document::parse(xml)?;
+    Ok(document
+        .root_element()
+        .children()
+        .filter(|node| node.tag_name().name() == "si")
+        .map(string_item_text)
+        .collect())
+}
+
+/// The text of a shared or inline string, leaving out phonetic guides.
+fn string_item_text(item: roxmltree::Node) -> String {
+    item.descendants()
+        .filter(|node| node.tag_name().name() == "t")
+        .filter(|node| {
+            !node
+                .ancestors()
+                .any(|ancestor| ancestor.tag_name().name() == "rPh")
+        })
+        .filter_map(|node| node.text())
+        .collect()
+}
+
+/// Whether each cell format locks its cells, by index.
+fn parse_locked_formats(xml: &str) -> Result<Vec<bool>> {
+    let document = roxmltree::Document::parse(xml)?;
+    let Some(formats) = document
+        .root_element()
+        .children()
+        .find(|node| node.tag_name().name() == "cellXfs")
+    else {
+        return Ok(Vec::new());
+    };
+    Ok(formats
+        .children()
+        .filter(|node| node.tag_name().name() == "xf")
+        .map(|format| {
+            format
+                .children()
+                .find(|node| node.tag_name().name() == "protection")
+                .and_then(|protection| protection.attribute("locked"))
+                .is_none_or(is_true)
+        })
+        .collect())
+}
+
+fn is_true(value: &str) -> bool {
+    value == "1" || value == "true"
+}
+
+fn parse_sheet(
+    xml: &str,
+    name: String,
+    part: String,
+    shared_strings: &[String],
+    locked_formats: &[bool],
+) -> Result<Sheet> {
+    let document = roxmltree::Document::parse(xml)?;
+    let protected = document
+        .descendants()
+        .find(|node| node.tag_name().name() == "sheetProtection")
+        .and_then(|protection| protection.attribute("sheet"))
+        .is_some_and(is_true);
+    let format_index = |node: roxmltree::Node, attribute: &str| {
+        node.attribute(attribute)
+            .and_then(|format| format.parse::<usize>().ok())
+    };
+
+    let mut row_formats = HashMap::default();
+    for (row_ix, row) in sheet_rows(&document) {
+        if row.attribute("customFormat").is_some_and(is_true) {
+            if let Some(format) = format_index(row, "s") {
+                row_formats.insert(row_ix, format);
+            }
+        }
+    }
+    let column_formats = document
+        .descendants()
+        .filter(|node| node.tag_name().name() == "col")
+        .filter_map(|column| {
+            let min = column.attribute("min")?.parse::<u32>().ok()?;
+            let max = column.attribute("max")?.parse::<u32>().ok()?;
+            Some((
+                min.checked_sub(1)?..=max.checked_sub(1)?,
+                format_index(column, "style")?,
+            ))
+        })
+        .collect();
+
+    let mut cells = Vec::new();
+    for cell in document
+        .descendants()
+        .filter(|node| node.tag_name().name() == "c")
+    {
+        let Some(address) = cell.attribute("r") else {
+            continue;
+        };
+        let address = CellAddress::parse(address)?;
+        let child = |name: &str| cell.children().find(|node| node.tag_name().name() == name);
+        let raw_value = child("v").and_then(|node| node.text()).unwrap_or_default();
+        let value = match cell.attribute("t") {
+            Some("s") => raw_value
+                .parse::<usize>()
+                .ok()
+                .and_then(|ix| shared_strings.get(ix))
+                .cloned()
+                .unwrap_or_default(),
+            Some("inlineStr") => child("is").map(string_item_text).unwrap_or_default(),
+            Some("b") => match raw_value {
+                "1" => "TRUE".to_string(),
+                _ => "FALSE".to_string(),
+            },
+            _ => raw_value.to_string(),
+        };
+        let formula = child("f").map(|node| node.text().unwrap_or_default().to_string());
+        let locked = locked_formats
+            .get(format_index(cell, "s").unwrap_or(0))
+            .copied()
+            .unwrap_or(true);
+        let writable = formula.is_none() && (!protected || !locked);
+        cells.push(Cell {
+            address,
+            value,
+            formula,
+            locked,
+            writable,
+        });
+    }
+    cells.sort_by_key(|cell| cell.address);
+
+    Ok(Sheet {
+        name,
+        protected,
+        cells,
+        part,
+        row_formats,
+        column_formats,
+        locked_formats: locked_formats.to_vec(),
+    })
+}
+
+/// The rows of a sheet's XML with their 0-based index. Rows without an
+/// index follow the row before them.
+fn sheet_rows<'a, 'input>(
+    document: &'a roxmltree::Document<'input>,
+) -> impl Iterator<Item = (u32, roxmltree::Node<'a, 'input>)> {
+    let mut next_row_ix = 0;
+    document
+        .descendants()
+        .filter(|node| node.tag_name().name() == "row")
+        .map(move |row| {
+            let row_ix = row
+                .attribute("r")
+                .and_then(|r| r.parse::<u32>().ok())
+                .and_then(|r| r.checked_sub(1))
+                .unwrap_or(next_row_ix);
+            next_row_ix = row_ix + 1;
+            (row_ix, row)
+        })
+}
+
+/// Replaces the values of cells in a sheet's XML, keeping their other
+/// attributes, such as their format. Blank cells that have no element are
+/// added to their row, formatted like it or their column.
+fn write_cells(xml: &str, sheet: &Sheet, values: &[(CellAddress, String)]) -> Result<String> {
+    let document = roxmltree::Document::parse(xml)?;
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut existing = HashSet::default();
+    for cell in document
+        .descendants()
+        .filter(|node| node.tag_name().name() == "c")
+    {
+        let Some(address) = cell
+            .attribute("r")
+            .and_then(|address| CellAddress::parse(address).ok())
+        else {
+            continue;
+        };
+        existing.insert(address);
+        let Some((_, value)) = values.iter().find(|(written, _)| *written == address) else {
+            continue;
+        };
+
+        let tag_name = tag_name(xml, cell);
+        let mut replacement = format!("<{tag_name}");
+        for attribute in cell.attributes() {
+            if attribute.namespace().is_some() || attribute.name() == "t" {
+                continue;
+            }
+            write!(
+                replacement,
+                r#" {}="{}""#,
+                attribute.name(),
+                escape(attribute.value())
+            )
+            .unwrap();
+        }
+        push_value(&mut replacement, tag_name, value);
+        edits.push((cell.range(), replacement));
+    }
+
+    let mut new_cells = values
+        .iter()
+        .filter(|(address, value)| !value.is_empty() && !existing.contains(address))
+        .collect::<Vec<_>>();
+    new_cells.sort_by_key(|(address, _)| *address);
+    if !new_cells.is_empty() {
+        let sheet_data = document
+            .descendants()
+            .find(|node| node.tag_name().name() == "sheetData")
+            .context("the sheet has no cell data")?;
+        let prefix = prefix(tag_name(xml, sheet_data));
+        let new_cell = |address: CellAddress, value: &str| {
+            let mut cell = format!(r#"<{prefix}c r="{address}""#);
+            let format = sheet.blank_format(address);
+            if format != 0 {
+                write!(cell, r#" s="{format}""#).unwrap();
+            }
+            push_value(&mut cell, &format!("{prefix}c"), value);
+            cell
+        };
+
+        let rows = sheet_rows(&document).collect::<Vec<_>>();
+        let mut appended_rows = String::new();
+        for row_cells in new_cells.chunk_by(|a, b| a.0.row == b.0.row) {
+            let row_ix = row_cells[0].0.row;
+            if let Some((_, row)) = rows.iter().find(|(ix, _)| *ix == row_ix) {
+                let columns = row
+                    .children()
+                    .filter(|node| node.tag_name().name() == "c")
+                    .filter_map(|cell| {
+                        let address = CellAddress::parse(cell.attribute("r")?).ok()?;
+                        Some((address.column, cell.range()))
+                    })
+                    .collect::<Vec<_>>();
+                if columns.is_empty() {
+                    let cells = row_cells
+                        .iter()
+                        .map(|(address, value)| new_cell(*address, value))
+                        .collect::<String>();
+                    edits.push(append_to_element(xml, *row, &cells));
+                    continue;
+                }
+                for (address, value) in row_cells {
+                    let position = columns
+                        .iter()
+                        .find(|(column, _)| *column > address.column)
+                        .map_or(columns[columns.len() - 1].1.end, |(_, range)| range.start);
+                    edits.push((position..position, new_cell(*address, value)));
+                }
+            } else {
+                let row = format!(
+                    r#"<{prefix}row r="{}">{}</{prefix}row>"#,
+                    row_ix + 1,
+                    row_cells
+                        .iter()
+                        .map(|(address, value)| new_cell(*address, value))
+                        .collect::<String>()
+                );
+                let position = rows
+                    .iter()
+                    .find(|(ix, _)| *ix > row_ix)
+                    .map(|(_, row)| row.range().start)
+                    .or_else(|| rows.last().map(|(_, row)| row.range().end));
+                match position {
+                    Some(position) => edits.push((position..position, row)),
+                    None => appended_rows.push_str(&row),
+                }
+            }
+        }
+        if !appended_rows.is_empty() {
+            edits.push(append_to_element(xml, sheet_data, &appended_rows));
+        }
+    }
+
+    // Edits at the same position are applied in the order they were made,
+    // with insertions before replacements.
+    edits.sort_by_key(|(range, _)| (range.start, range.end));
+    let mut xml = xml.to_string();
+    for (range, replacement) in edits.into_iter().rev() {
+        xml.replace_range(range, &replacement);
+    }
+    Ok(xml)
+}
+
+/// Writes the rest of a cell's element after its attributes, with the value
+/// as a number or an inline string.
+fn push_value(cell: &mut String, tag_name: &str, value: &str) {
+    let prefix = prefix(tag_name);
+    if value.is_empty() {
+        cell.push_str("/>");
+    } else if is_number(value) {
+        write!(cell, "><{prefix}v>{value}</{prefix}v></{tag_name}>").unwrap();
+    } else {
+        write!(
+            cell,
+            r#" t="inlineStr"><{prefix}is><{prefix}t xml:space="preserve">{}</{prefix}t></{prefix}is></{tag_name}>"#,
+            escape(value)
+        )
+        .unwrap();
+    }
+}
+
+/// The edit that adds `content` to the end of an element, opening it up if
+/// it's empty.
+fn append_to_element(xml: &str, element: roxmltree::Node, content: &str) -> (Range<usize>, String) {
+    let range = element.range();
+    if xml[range.clone()].ends_with("/>") {
+        let tag_name = tag_name(xml, element);
+        (range.end - 2..range.end, format!(">{content}</{tag_name}>"))
+    } else {
+        let end = xml[..range.end].rfind("</").unwrap_or(range.end);
+        (end..end, content.to_string())
+    }
+}
+
+/// The name of an element as it's written, with its namespace prefix.
+fn tag_name<'a>(xml: &'a str, element: roxmltree::Node) -> &'a str {
+    xml[element.range().start + 1..]
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or_default()
+}
+
+/// The namespace prefix of a tag name, with its colon.
+fn prefix(tag_name: &str) -> String {
+    tag_name
+        .rsplit_once(':')
+        .map_or(String::new(), |(prefix, _)| format!("{prefix}:"))
+}
+
+fn is_number(value: &str) -> bool {
+    value.trim() == value && value.parse::<f64>().is_ok_and(|number| number.is_finite())
+}
+
+/// Marks the workbook to have its formulas recalculated when it's opened.
+fn recalculate_on_load(xml: &str) -> Result<String> {
+    let document = roxmltree::Document::parse(xml)?;
+    let root = document.root_element();
+    let element = |name: &str| root.children().find(|node| node.tag_name().name() == name);
+    let prefix = prefix(tag_name(xml, root));
+
+    let mut xml = xml.to_string();
+    if let Some(calculation) = element("calcPr") {
+        if calculation.attribute("fullCalcOnLoad").is_some_and(is_true) {
+            return Ok(xml);
+        }
+        let start = calculation.range().start;
+        let name_end = start + 1 + prefix.len() + "calcPr".len();
+        xml.insert_str(name_end, r#" fullCalcOnLoad="1""#);
+    } else {
+        // `calcPr` follows the sheets, function groups, external references
+        // and defined names, whichever of them the workbook has.
+        let Some(preceding) = root
+            .children()
+            .filter(|node| {
+                matches!(
+                    node.tag_name().name(),
+                    "sheets" | "functionGroups" | "externalReferences" | "definedNames"
+                )
+            })
+            .last()
+        else {
+            return Ok(xml);
+        };
+        xml.insert_str(
+            preceding.range().end,
+            &format!(r#"<{prefix}calcPr fullCalcOnLoad="1"/>"#),
+        );
+    }
+    Ok(xml)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const WORKBOOK: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Pricing" sheetId="1" r:id="rId1"/></sheets><calcPr calcId="191029"/></workbook>"#;
+
+    const WORKBOOK_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+    const SHARED_STRINGS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><si><t>Item</t></si><si><r><t>Unit </t></r><r><rPr><b/></rPr><t>rate</t></r></si><si><t>Total</t></si></sst>"#;
+
+    /// Format 0 is locked, and format 1 isn't.
+    const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><cellXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="4" fontId="0" fillId="1" borderId="0" xfId="0" applyProtection="1"><protection locked="0"/></xf></cellXfs></styleSheet>"#;
+
+    /// Column D and row 5 are formatted to be unlocked.
+    const SHEET: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><cols><col min="4" max="4" width="14" style="1" customWidth="1"/></cols><sheetData><row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c><c r="C1" t="s"><v>2</v></c></row><row r="2"><c r="A2" t="inlineStr"><is><t>Design</t></is></c><c r="B2" s="1"><v>1200</v></c><c r="C2"><f>B2*1.1</f><v>1320</v></c></row><row r="3"><c r="A3" t="inlineStr"><is><t>Build</t></is></c><c r="B3" s="1"/><c r="C3"><f>B3*1.1</f><v>0</v></c></row><row r="5" s="1" customFormat="1"/></sheetData><sheetProtection sheet="1" objects="1"/></worksheet>"#;
+
+    pub(crate) async fn pricing_schedule() -> Vec<u8> {
+        let parts = [
+            ("xl/workbook.xml", WORKBOOK),
+            ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS),
+            ("xl/sharedStrings.xml", SHARED_STRINGS),
+            ("xl/styles.xml", STYLES),
+            ("xl/worksheets/sheet1.xml", SHEET),
+        ];
+        let mut writer = ZipFileWriter::new(Vec::new());
+        for (name, contents) in parts {
+            let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+            writer
+                .write_entry_whole(entry, contents.as_bytes())
+                .await
+                .unwrap();
+        }
+        writer.close().await.unwrap()
+    }
+
+    fn part(workbook: &Workbook, name: &str) -> String {
+        workbook
+            .parts
+            .iter()
+            .find(|(part, _)| part == name)
+            .map(|(_, contents)| String::from_utf8(contents.clone()).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cell_addresses() {
+        assert_eq!(
+            CellAddress::parse("B4").unwrap(),
+            CellAddress { row: 3, column: 1 }
+        );
+        assert_eq!(
+            CellAddress::parse("$AA$10").unwrap(),
+            CellAddress { row: 9, column: 26 }
+        );
+        assert!(CellAddress::parse("B0").is_err());
+        assert!(CellAddress::parse("4").is_err());
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(
+            CellAddress {
+                row: 0,
+                column: 702
+            }
+            .to_string(),
+            "AAA1"
+        );
+
+        let range = CellRange::parse("C5:a1").unwrap();
+        assert_eq!(range.to_string(), "A1:C5");
+        assert!(range.contains(CellAddress::parse("B3").unwrap()));
+        assert!(!range.contains(CellAddress::parse("D3").unwrap()));
+    }
+
+    #[gpui::test]
+    async fn test_read_workbook() {
+        let workbook = Workbook::read(pricing_schedule().await).await.unwrap();
+        let sheet = workbook.sheet("pricing").unwrap();
+        assert!(sheet.protected);
+        assert_eq!(sheet.used_range().unwrap().to_string(), "A1:C3");
+
+        let cells = sheet
+            .cells
+            .iter()
+            .map(|cell| {
+                (
+                    cell.address.to_string(),
+                    cell.value.as_str(),
+                    cell.formula.as_deref(),
+                    cell.writable,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            [
+                ("A1".to_string(), "Item", None, false),
+                ("B1".to_string(), "Unit rate", None, false),
+                ("C1".to_string(), "Total", None, false),
+                ("A2".to_string(), "Design", None, false),
+                ("B2".to_string(), "1200", None, true),
+                ("C2".to_string(), "1320", Some("B2*1.1"), false),
+                ("A3".to_string(), "Build", None, false),
+                ("B3".to_string(), "", None, true),
+                ("C3".to_string(), "0", Some("B3*1.1"), false),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_write_workbook() {
+        let workbook = Workbook::read(pricing_schedule().await).await.unwrap();
+        let values = [
+            (CellAddress::parse("B2").unwrap(), "1500".to_string()),
+            (
+                CellAddress::parse("B3").unwrap(),
+                "TBC & <later>".to_string(),
+            ),
+        ];
+        let written = Workbook::read(workbook.write("Pricing", &values).await.unwrap())
+            .await
+            .unwrap();
+
+        let sheet = part(&written, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(r#"<c r="B2" s="1"><v>1500</v></c>"#));
+        assert!(sheet.contains(
+            r#"<c r="B3" s="1" t="inlineStr"><is><t xml:space="preserve">TBC &amp; &lt;later&gt;</t></is></c>"#
+        ));
+        assert!(sheet.contains(r#"<c r="C2"><f>B2*1.1</f><v>1320</v></c>"#));
+        assert!(sheet.contains(r#"<sheetProtection sheet="1" objects="1"/>"#));
+        assert!(
+            part(&written, "xl/workbook.xml")
+                .contains(r#"<calcPr fullCalcOnLoad="1" calcId="191029"/>"#)
+        );
+        assert_eq!(
+            written.sheets[0]
+                .cell(CellAddress::parse("B3").unwrap())
+                .unwrap()
+                .value,
+            "TBC & <later>"
+        );
+
+        for (cell, error) in [
+            ("A2", "A2 is locked"),
+            ("C2", "C2 is calculated by a formula"),
+            ("E9", "E9 is locked"),
+        ] {
+            let values = [(CellAddress::parse(cell).unwrap(), "1".to_string())];
+            let result = workbook.write("Pricing", &values).await;
+            assert_eq!(result.unwrap_err().to_string(), error);
+        }
+
+        let values = [
+            (CellAddress::parse("B2").unwrap(), "1".to_string()),
+            (CellAddress::parse("B2").unwrap(), "2".to_string()),
+        ];
+        let result = workbook.write("Pricing", &values).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "B2 is written more than once"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_write_blank_cells() {
+        let workbook = Workbook::read(pricing_schedule().await).await.unwrap();
+        let sheet = &workbook.sheets[0];
+        assert!(sheet.cell(CellAddress::parse("D2").unwrap()).is_none());
+        assert!(
+            sheet
+                .cell_or_blank(CellAddress::parse("D2").unwrap())
+                .writable
+        );
+        assert!(
+            sheet
+                .cell_or_blank(CellAddress::parse("A5").unwrap())
+                .writable
+        );
+        assert!(
+            !sheet
+                .cell_or_blank(CellAddress::parse("E2").unwrap())
+                .writable
+        );
+
+        let values = [
+            ("D2", "Fixed"),
+            ("B5", "500"),
+            ("A5", "Contingency"),
+            ("D4", "10"),
+        ]
+        .map(|(cell, value)| (CellAddress::parse(cell).unwrap(), value.to_string()));
+        let written = Workbook::read(workbook.write("Pricing", &values).await.unwrap())
+            .await
+            .unwrap();
+
+        let sheet = part(&written, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(
+            r#"<c r="C2"><f>B2*1.1</f><v>1320</v></c><c r="D2" s="1" t="inlineStr"><is><t xml:space="preserve">Fixed</t></is></c></row>"#
+        ));
+        assert!(sheet.contains(
+            r#"</row><row r="4"><c r="D4" s="1"><v>10</v></c></row><row r="5" s="1" customFormat="1"><c r="A5" s="1" t="inlineStr"><is><t xml:space="preserve">Contingency</t></is></c><c r="B5" s="1"><v>500</v></c></row></sheetData>"#
+        ));
+        let cells = written.sheets[0]
+            .cells_in(CellRange::parse("A4:D5").unwrap())
+            .map(|cell| (cell.address.to_string(), cell.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            [
+                ("D4".to_string(), "10"),
+                ("A5".to_string(), "Contingency"),
+                ("B5".to_string(), "500"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_cells_to_empty_sheet() {
+        let xml = r#"<x:worksheet xmlns:x="main"><x:sheetData/></x:worksheet>"#;
+        let sheet = parse_sheet(xml, "A".into(), "sheet.xml".into(), &[], &[]).unwrap();
+        let values = [
+            (CellAddress::parse("B2").unwrap(), "2".to_string()),
+            (CellAddress::parse("A1").unwrap(), "1".to_string()),
+        ];
+        assert_eq!(
+            write_cells(xml, &sheet, &values).unwrap(),
+            r#"<x:worksheet xmlns:x="main"><x:sheetData><x:row r="1"><x:c r="A1"><x:v>1</x:v></x:c></x:row><x:row r="2"><x:c r="B2"><x:v>2</x:v></x:c></x:row></x:sheetData></x:worksheet>"#
+        );
+    }
+
+    #[test]
+    fn test_recalculate_on_load() {
+        let xml = r#"<workbook xmlns="main"><sheets><sheet name="A" sheetId="1"/></sheets><definedNames><definedName name="X">A!$A$1</definedName></definedNames></workbook>"#;
+        assert_eq!(
+            recalculate_on_load(xml).unwrap(),
+            r#"<workbook xmlns="main"><sheets><sheet name="A" sheetId="1"/></sheets><definedNames><definedName name="X">A!$A$1</definedName></definedNames><calcPr fullCalcOnLoad="1"/></workbook>"#
+        );
+        let xml = r#"<x:workbook xmlns:x="main"><x:sheets/></x:workbook>"#;
+        assert_eq!(
+            recalculate_on_load(xml).unwrap(),
+            r#"<x:workbook xmlns:x="main"><x:sheets/><x:calcPr fullCalcOnLoad="1"/></x:workbook>"#
+        );
+        let xml = r#"<workbook xmlns="main"><sheets/><externalReferences><externalReference/></externalReferences><extLst/></workbook>"#;
+        assert_eq!(
+            recalculate_on_load(xml).unwrap(),
+            r#"<workbook xmlns="main"><sheets/><externalReferences><externalReference/></externalReferences><calcPr fullCalcOnLoad="1"/><extLst/></workbook>"#
+        );
+    }
+}
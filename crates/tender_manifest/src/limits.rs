@@ -0,0 +1,366 @@
+use crate::TenderManifest;
+use prose::Section;
+use std::ops::Range;
+
+/// Used to estimate page counts when the manifest doesn't say how many words
+/// fit on a page: roughly a page of 11pt single-spaced text.
+pub const DEFAULT_WORDS_PER_PAGE: usize = 500;
+
+/// A limit on the length of a document or section.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LengthLimit {
+    pub words: Option<usize>,
+    pub pages: Option<usize>,
+}
+
+impl LengthLimit {
+    pub fn is_empty(&self) -> bool {
+        self.words.is_none() && self.pages.is_none()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LimitUnit {
+    Words,
+    Pages,
+}
+
+/// A document or section that's longer than its limit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitOverrun {
+    /// The section's title, or `None` for a limit on the whole document.
+    pub section: Option<String>,
+    pub heading_range: Option<Range<usize>>,
+    pub unit: LimitUnit,
+    pub limit: usize,
+    pub words: usize,
+    pub words_per_page: usize,
+    /// The text beyond the limit, which evaluators would discard.
+    pub excess_range: Range<usize>,
+}
+
+impl LimitOverrun {
+    /// The estimated page count, for page limits.
+    pub fn pages(&self) -> usize {
+        self.words.div_ceil(self.words_per_page)
+    }
+
+    pub fn message(&self) -> String {
+        let subject = match &self.section {
+            Some(section) => format!("\"{section}\""),
+            None => "This document".to_string(),
+        };
+        match self.unit {
+            LimitUnit::Words => format!(
+                "{subject} is {} words, over its {}-word limit. Evaluators will discard the last {} words.",
+                self.words,
+                self.limit,
+                self.words - self.limit
+            ),
+            LimitUnit::Pages => format!(
+                "{subject} is about {} pages ({} words at {} words per page), over its {}-page limit. Evaluators will discard the last {} words.",
+                self.pages(),
+                self.words,
+                self.words_per_page,
+                self.limit,
+                self.words - self.limit * self.words_per_page
+            ),
+        }
+    }
+}
+
+/// Parses a limit annotation such as `<!-- limit: 500 words -->` or
+/// `<!-- limit: 2 pages -->`, given the comment's contents.
+pub fn parse_limit_comment(comment: &str) -> Option<LengthLimit> {
+    let (key, amounts) = comment.split_once(':')?;
+    if !key.trim().eq_ignore_ascii_case("limit") {
+        return None;
+    }
+
+    let mut limit = LengthLimit::default();
+    let mut amount = None;
+    for token in amounts.split_whitespace() {
+        let token = token.trim_end_matches([',', '.', ';']);
+        if token.starts_with(|c: char| c.is_ascii_digit()) {
+            amount = token.replace(',', "").parse::<usize>().ok();
+            continue;
+        }
+        let token = token.to_lowercase();
+        match amount.take().filter(|amount| *amount > 0) {
+            Some(amount) if token.starts_with("word") => limit.words = Some(amount),
+            Some(amount) if token.starts_with("page") => limit.pages = Some(amount),
+            _ => {}
+        }
+    }
+    (!limit.is_empty()).then_some(limit)
+}
+
+/// Returns the limit of each section in `sections`.
+///
+/// A limit annotation in the section's own content, before any subsection,
+/// takes precedence over a limit for its heading in the manifest.
+pub fn section_limits(
+    text: &str,
+    sections: &[Section],
+    manifest: Option<&TenderManifest>,
+) -> Vec<LengthLimit> {
+    sections
+        .iter()
+        .enumerate()
+        .map(|(ix, section)| {
+            let own_content_end = sections
+                .get(ix + 1)
+                .map_or(section.range.end, |next| next.heading_range.start);
+            let annotated = comments(&text[section.heading_range.end..own_content_end])
+                .find_map(parse_limit_comment);
+            annotated
+                .or_else(|| {
+                    let limit = manifest?.section_limit(&section.title)?;
+                    Some(LengthLimit {
+                        words: limit.words,
+                        pages: limit.pages,
+                    })
+                })
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Finds the document and sections of a Markdown document that exceed their
+/// limits, from its annotations and the manifest. The manifest's limit on the
+/// whole document is only checked for response documents.
+pub fn limit_overruns(
+    text: &str,
+    manifest: Option<&TenderManifest>,
+    is_response: bool,
+) -> Vec<LimitOverrun> {
+    let words_per_page = manifest
+        .and_then(|manifest| manifest.limits.words_per_page)
+        .unwrap_or(DEFAULT_WORDS_PER_PAGE);
+    let prose_ranges = prose::prose_ranges(text);
+    let words = prose::words(text, &prose_ranges).collect::<Vec<_>>();
+    let sections = prose::sections(text);
+
+    let mut overruns = Vec::new();
+    if let Some(manifest) = manifest.filter(|_| is_response) {
+        let limit = LengthLimit {
+            words: manifest.limits.words,
+            pages: manifest.limits.pages,
+        };
+        overruns.extend(overrun(&words, 0..text.len(), limit, words_per_page).map(
+            |(unit, limit, word_count, excess_range)| LimitOverrun {
+                section: None,
+                heading_range: None,
+                unit,
+                limit,
+                words: word_count,
+                words_per_page,
+                excess_range,
+            },
+        ));
+    }
+
+    let limits = section_limits(text, &sections, manifest);
+    for (section, limit) in sections.iter().zip(limits) {
+        let content = section.heading_range.end..section.range.end;
+        overruns.extend(overrun(&words, content, limit, words_per_page).map(
+            |(unit, limit, word_count, excess_range)| LimitOverrun {
+                section: Some(section.title.clone()),
+                heading_range: Some(section.heading_range.clone()),
+                unit,
+                limit,
+                words: word_count,
+                words_per_page,
+                excess_range,
+            },
+        ));
+    }
+    overruns
+}
+
+/// Returns the overruns in `new_text` that an edit from `old_text` either
+/// introduced or made worse.
+pub fn limit_breaches(
+    old_text: &str,
+    new_text: &str,
+    manifest: Option<&TenderManifest>,
+    is_response: bool,
+) -> Vec<LimitOverrun> {
+    let old_overruns = limit_overruns(old_text, manifest, is_response);
+    limit_overruns(new_text, manifest, is_response)
+        .into_iter()
+        .filter(|overrun| {
+            !old_overruns.iter().any(|old| {
+                old.section == overrun.section
+                    && old.unit == overrun.unit
+                    && old.limit == overrun.limit
+                    && old.words >= overrun.words
+            })
+        })
+        .collect()
+}
+
+/// Checks the words within `range` against `limit`. When both a word and a
+/// page limit are exceeded, the one that cuts off more text is reported.
+fn overrun(
+    words: &[Range<usize>],
+    range: Range<usize>,
+    limit: LengthLimit,
+    words_per_page: usize,
+) -> Option<(LimitUnit, usize, usize, Range<usize>)> {
+    let start = words.partition_point(|word| word.start < range.start);
+    let end = words.partition_point(|word| word.start < range.end);
+    let words = &words[start..end];
+
+    let word_limits = [
+        limit.words.map(|words| (LimitUnit::Words, words, words)),
+        limit
+            .pages
+            .map(|pages| (LimitUnit::Pages, pages, pages * words_per_page)),
+    ];
+    word_limits
+        .into_iter()
+        .flatten()
+        .filter(|(_, _, max_words)| words.len() > *max_words)
+        .min_by_key(|(_, _, max_words)| *max_words)
+        .map(|(unit, limit, max_words)| {
+            let excess_range = words[max_words].start..words[words.len() - 1].end;
+            (unit, limit, words.len(), excess_range)
+        })
+}
+
+fn comments(text: &str) -> impl Iterator<Item = &str> {
+    text.split("<!--")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("-->").map(|(comment, _)| comment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Limits, SectionLimit};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_limit_comment() {
+        assert_eq!(
+            parse_limit_comment(" limit: 500 words "),
+            Some(LengthLimit {
+                words: Some(500),
+                pages: None
+            })
+        );
+        assert_eq!(
+            parse_limit_comment("Limit: 1,000 words and 2 pages"),
+            Some(LengthLimit {
+                words: Some(1000),
+                pages: Some(2)
+            })
+        );
+        assert_eq!(parse_limit_comment(" REQ-001: drafted "), None);
+        assert_eq!(parse_limit_comment(" limit: none "), None);
+    }
+
+    #[test]
+    fn test_limit_overruns() {
+        let text = indoc! {"
+            # Response
+
+            ## Executive Summary
+            <!-- limit: 5 words -->
+
+            One two three four five six seven.
+
+            ## Methodology
+
+            We will deliver.
+
+            ### Transition
+
+            In three phases.
+        "};
+        let manifest = TenderManifest {
+            limits: Limits {
+                words: Some(16),
+                words_per_page: Some(2),
+                sections: vec![
+                    SectionLimit {
+                        heading: "Executive Summary".into(),
+                        pages: None,
+                        words: Some(100),
+                    },
+                    SectionLimit {
+                        heading: "2. Methodology".into(),
+                        pages: Some(2),
+                        words: None,
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let overruns = limit_overruns(text, Some(&manifest), true);
+        let summary = overruns
+            .iter()
+            .map(|overrun| {
+                (
+                    overrun.section.as_deref(),
+                    overrun.unit,
+                    overrun.words,
+                    &text[overrun.excess_range.clone()],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (None, LimitUnit::Words, 18, "three phases"),
+                (Some("Executive Summary"), LimitUnit::Words, 7, "six seven"),
+                (Some("Methodology"), LimitUnit::Pages, 7, "In three phases"),
+            ]
+        );
+        assert_eq!(
+            overruns[1].message(),
+            "\"Executive Summary\" is 7 words, over its 5-word limit. Evaluators will discard the last 2 words."
+        );
+        assert_eq!(
+            overruns[2].message(),
+            "\"Methodology\" is about 4 pages (7 words at 2 words per page), over its 2-page limit. Evaluators will discard the last 3 words."
+        );
+
+        assert_eq!(limit_overruns(text, Some(&manifest), false).len(), 2);
+        assert_eq!(limit_overruns(text, None, true).len(), 1);
+    }
+
+    #[test]
+    fn test_limit_breaches() {
+        let old_text = indoc! {"
+            ## Summary
+            <!-- limit: 3 words -->
+            One two three four.
+
+            ## Pricing
+            <!-- limit: 3 words -->
+            One two.
+        "};
+        let breaches = |new_text: &str| {
+            limit_breaches(old_text, new_text, None, true)
+                .into_iter()
+                .map(|overrun| (overrun.section.unwrap(), overrun.words))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(breaches(old_text), []);
+        assert_eq!(
+            breaches(&old_text.replace("One two.", "One two three four.")),
+            [("Pricing".to_string(), 4)]
+        );
+        assert_eq!(
+            breaches(&old_text.replace("three four.", "three four five.")),
+            [("Summary".to_string(), 5)]
+        );
+        assert_eq!(breaches(&old_text.replace("four.", ".")), []);
+    }
+}
@@ -0,0 +1,686 @@
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use toml::Spanned;
+use toml::value::Datetime;
+
+/// Evaluation weightings are percentages, so they should add up to this.
+const TOTAL_WEIGHT: f64 = 100.;
+
+/// The metadata of a tender, as described by its `tender.toml`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TenderManifest {
+    pub client: Option<String>,
+    pub reference: Option<String>,
+    pub title: Option<String>,
+    /// When submissions close, in the time zone the tender was issued in.
+    pub closes_at: Option<DateTime<FixedOffset>>,
+    /// A label for the closing time's zone, such as `Australia/Sydney`. The
+    /// offset in [`Self::closes_at`] is what's used for the countdown.
+    pub time_zone: Option<String>,
+    pub portal: Option<String>,
+    pub folders: Folders,
+    pub limits: Limits,
+    pub evaluation: Vec<EvaluationCriterion>,
+}
+
+/// Where the tender's files are, relative to the manifest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Folders {
+    /// The documents issued by the client: the request for tender and any
+    /// addenda.
+    pub documents: PathBuf,
+    /// The response documents, which the top-level limits apply to.
+    pub response: PathBuf,
+}
+
+impl Default for Folders {
+    fn default() -> Self {
+        Self {
+            documents: PathBuf::from("rft"),
+            response: PathBuf::from("response"),
+        }
+    }
+}
+
+/// Page and word limits. The top-level limits apply to each response
+/// document; section limits apply to the sections with a matching heading.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub pages: Option<usize>,
+    pub words: Option<usize>,
+    /// How many words fit on a page in the required format, for estimating
+    /// page counts.
+    pub words_per_page: Option<usize>,
+    pub sections: Vec<SectionLimit>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionLimit {
+    pub heading: String,
+    pub pages: Option<usize>,
+    pub words: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvaluationCriterion {
+    pub criterion: String,
+    /// The criterion's share of the score, as a percentage.
+    pub weight: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Something wrong with a manifest, at a byte range of its source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub range: Range<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// The result of parsing a manifest.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParsedManifest {
+    /// The manifest, or `None` if the source isn't valid TOML or doesn't
+    /// match the schema. Other problems still produce a manifest, with the
+    /// offending values left out.
+    pub manifest: Option<TenderManifest>,
+    pub problems: Vec<Problem>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestContent {
+    client: Option<Spanned<String>>,
+    reference: Option<Spanned<String>>,
+    title: Option<String>,
+    closes: Option<Spanned<Datetime>>,
+    time_zone: Option<String>,
+    portal: Option<Spanned<String>>,
+    #[serde(default)]
+    folders: FoldersContent,
+    #[serde(default)]
+    limits: LimitsContent,
+    evaluation: Option<Spanned<Vec<CriterionContent>>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FoldersContent {
+    documents: Option<Spanned<String>>,
+    response: Option<Spanned<String>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LimitsContent {
+    pages: Option<Spanned<i64>>,
+    words: Option<Spanned<i64>>,
+    words_per_page: Option<Spanned<i64>>,
+    #[serde(default)]
+    sections: Vec<SectionLimitContent>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SectionLimitContent {
+    heading: Spanned<String>,
+    pages: Option<Spanned<i64>>,
+    words: Option<Spanned<i64>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CriterionContent {
+    criterion: Spanned<String>,
+    weight: Spanned<f64>,
+}
+
+impl TenderManifest {
+    /// Parses and validates the source of a `tender.toml`.
+    pub fn parse(text: &str) -> ParsedManifest {
+        let content = match toml::from_str::<ManifestContent>(text) {
+            Ok(content) => content,
+            Err(error) => {
+                let range = error
+                    .span()
+                    .map(|span| span.start.min(text.len())..span.end.min(text.len()))
+                    .unwrap_or_else(|| first_line(text));
+                return ParsedManifest {
+                    manifest: None,
+                    problems: vec![Problem {
+                        range,
+                        severity: Severity::Error,
+                        message: error.message().trim().to_string(),
+                    }],
+                };
+            }
+        };
+
+        let mut validator = Validator {
+            text,
+            problems: Vec::new(),
+        };
+        let manifest = TenderManifest {
+            client: validator.required_text("client", content.client),
+            reference: validator.required_text("reference", content.reference),
+            title: content.title,
+            closes_at: validator.closing_time(content.closes),
+            time_zone: content.time_zone,
+            portal: content.portal.map(|portal| {
+                if !portal.get_ref().starts_with("https://")
+                    && !portal.get_ref().starts_with("http://")
+                {
+                    validator.warn(portal.span(), "`portal` should be a web address");
+                }
+                portal.into_inner()
+            }),
+            folders: {
+                let default = Folders::default();
+                Folders {
+                    documents: validator.folder(
+                        "documents",
+                        content.folders.documents,
+                        default.documents,
+                    ),
+                    response: validator.folder(
+                        "response",
+                        content.folders.response,
+                        default.response,
+                    ),
+                }
+            },
+            limits: Limits {
+                pages: validator.limit("pages", content.limits.pages),
+                words: validator.limit("words", content.limits.words),
+                words_per_page: validator.limit("words_per_page", content.limits.words_per_page),
+                sections: validator.section_limits(content.limits.sections),
+            },
+            evaluation: validator.evaluation(content.evaluation),
+        };
+        ParsedManifest {
+            manifest: Some(manifest),
+            problems: validator.problems,
+        }
+    }
+
+    /// The limits for the section with the given heading, ignoring case and
+    /// any leading section number.
+    pub fn section_limit(&self, heading: &str) -> Option<&SectionLimit> {
+        let heading = normalize_heading(heading);
+        self.limits
+            .sections
+            .iter()
+            .find(|section| normalize_heading(&section.heading) == heading)
+    }
+
+    /// Whether `path`, relative to the manifest, is in the response folder.
+    pub fn is_response_path(&self, path: &Path) -> bool {
+        path.starts_with(&self.folders.response)
+    }
+
+    /// How long is left until submissions close, negative once they have.
+    pub fn time_remaining(&self, now: DateTime<Utc>) -> Option<TimeDelta> {
+        Some(self.closes_at?.with_timezone(&Utc) - now)
+    }
+
+    /// The closing time, formatted in the tender's own time zone.
+    pub fn closing_time_label(&self) -> Option<String> {
+        let closes_at = self.closes_at?;
+        let mut label = closes_at.format("%a %-d %b %Y, %-I:%M %p").to_string();
+        match &self.time_zone {
+            Some(time_zone) => write!(label, " {time_zone} (UTC{})", closes_at.offset()),
+            None => write!(label, " (UTC{})", closes_at.offset()),
+        }
+        .unwrap();
+        Some(label)
+    }
+
+    /// A Markdown summary of the manifest, for giving to the assistant.
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        let fields = [
+            ("Client", self.client.clone()),
+            ("Reference", self.reference.clone()),
+            ("Title", self.title.clone()),
+            ("Closes", self.closing_time_label()),
+            ("Submission portal", self.portal.clone()),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                writeln!(summary, "- {name}: {value}").unwrap();
+            }
+        }
+
+        let document_limits = limits_label(self.limits.pages, self.limits.words);
+        if !document_limits.is_empty() {
+            writeln!(
+                summary,
+                "- Limits per response document, in {}/: {document_limits}",
+                self.folders.response.display()
+            )
+            .unwrap();
+        }
+        if !self.limits.sections.is_empty() {
+            writeln!(summary, "- Section limits:").unwrap();
+            for section in &self.limits.sections {
+                writeln!(
+                    summary,
+                    "  - {}: {}",
+                    section.heading,
+                    limits_label(section.pages, section.words)
+                )
+                .unwrap();
+            }
+        }
+        if !self.evaluation.is_empty() {
+            writeln!(summary, "- Evaluation criteria:").unwrap();
+            for criterion in &self.evaluation {
+                writeln!(
+                    summary,
+                    "  - {}: {}%",
+                    criterion.criterion,
+                    format_weight(criterion.weight)
+                )
+                .unwrap();
+            }
+        }
+        summary
+    }
+}
+
+/// A short description of the time left to submit, e.g. `Closes in 3d 4h`.
+pub fn countdown_label(remaining: TimeDelta) -> String {
+    let minutes = remaining.num_minutes();
+    if remaining <= TimeDelta::zero() {
+        return "Closed".to_string();
+    }
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("Closes in {days}d {hours}h")
+    } else if hours > 0 {
+        format!("Closes in {hours}h {minutes}m")
+    } else {
+        format!("Closes in {}m", minutes.max(1))
+    }
+}
+
+struct Validator<'a> {
+    text: &'a str,
+    problems: Vec<Problem>,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, range: Range<usize>, message: impl Into<String>) {
+        self.problems.push(Problem {
+            range,
+            severity: Severity::Error,
+            message: message.into(),
+        });
+    }
+
+    fn warn(&mut self, range: Range<usize>, message: impl Into<String>) {
+        self.problems.push(Problem {
+            range,
+            severity: Severity::Warning,
+            message: message.into(),
+        });
+    }
+
+    fn required_text(&mut self, key: &str, value: Option<Spanned<String>>) -> Option<String> {
+        let Some(value) = value else {
+            self.error(first_line(self.text), format!("missing `{key}`"));
+            return None;
+        };
+        if value.get_ref().trim().is_empty() {
+            self.error(value.span(), format!("`{key}` must not be empty"));
+            return None;
+        }
+        Some(value.into_inner())
+    }
+
+    fn closing_time(&mut self, closes: Option<Spanned<Datetime>>) -> Option<DateTime<FixedOffset>> {
+        let Some(closes) = closes else {
+            self.error(first_line(self.text), "missing `closes`");
+            return None;
+        };
+        let datetime = closes.get_ref();
+        if datetime.date.is_none() || datetime.time.is_none() || datetime.offset.is_none() {
+            self.error(
+                closes.span(),
+                "`closes` must be a date and time with a UTC offset, e.g. 2026-11-14T14:00:00+11:00",
+            );
+            return None;
+        }
+        match DateTime::parse_from_rfc3339(&datetime.to_string()) {
+            Ok(closes_at) => Some(closes_at),
+            Err(error) => {
+                self.error(closes.span(), format!("invalid closing time: {error}"));
+                None
+            }
+        }
+    }
+
+    fn folder(&mut self, key: &str, folder: Option<Spanned<String>>, default: PathBuf) -> PathBuf {
+        let Some(folder) = folder else {
+            return default;
+        };
+        let path = Path::new(folder.get_ref().trim().trim_end_matches('/'));
+        let is_within_project = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if path.as_os_str().is_empty() || !is_within_project {
+            self.error(
+                folder.span(),
+                format!(
+                    "`folders.{key}` must be a folder within the project, e.g. \"{}\"",
+                    default.display()
+                ),
+            );
+            return default;
+        }
+        path.to_path_buf()
+    }
+
+    fn limit(&mut self, key: &str, limit: Option<Spanned<i64>>) -> Option<usize> {
+        let limit = limit?;
+        match usize::try_from(*limit.get_ref()) {
+            Ok(value) if value > 0 => Some(value),
+            _ => {
+                self.error(limit.span(), format!("`{key}` must be a positive number"));
+                None
+            }
+        }
+    }
+
+    fn section_limits(&mut self, sections: Vec<SectionLimitContent>) -> Vec<SectionLimit> {
+        let mut limits = Vec::<SectionLimit>::new();
+        for section in sections {
+            let span = section.heading.span();
+            let heading = section.heading.into_inner();
+            if heading.trim().is_empty() {
+                self.error(span, "`heading` must not be empty");
+                continue;
+            }
+            let pages = self.limit("pages", section.pages);
+            let words = self.limit("words", section.words);
+            if pages.is_none() && words.is_none() {
+                self.warn(
+                    span,
+                    format!("no `pages` or `words` limit for \"{heading}\""),
+                );
+                continue;
+            }
+            if limits
+                .iter()
+                .any(|limit| normalize_heading(&limit.heading) == normalize_heading(&heading))
+            {
+                self.warn(span, format!("\"{heading}\" already has a limit"));
+                continue;
+            }
+            limits.push(SectionLimit {
+                heading,
+                pages,
+                words,
+            });
+        }
+        limits
+    }
+
+    fn evaluation(
+        &mut self,
+        evaluation: Option<Spanned<Vec<CriterionContent>>>,
+    ) -> Vec<EvaluationCriterion> {
+        let Some(evaluation) = evaluation else {
+            return Vec::new();
+        };
+        let span = evaluation.span();
+        let mut criteria = Vec::<EvaluationCriterion>::new();
+        for content in evaluation.into_inner() {
+            let criterion_span = content.criterion.span();
+            let criterion = content.criterion.into_inner();
+            if criterion.trim().is_empty() {
+                self.error(criterion_span, "`criterion` must not be empty");
+                continue;
+            }
+            let weight = *content.weight.get_ref();
+            if !weight.is_finite() || weight <= 0. || weight > TOTAL_WEIGHT {
+                self.error(
+                    content.weight.span(),
+                    "`weight` must be a percentage greater than 0",
+                );
+                continue;
+            }
+            if criteria
+                .iter()
+                .any(|existing| existing.criterion.eq_ignore_ascii_case(&criterion))
+            {
+                self.warn(criterion_span, format!("\"{criterion}\" is listed twice"));
+            }
+            criteria.push(EvaluationCriterion { criterion, weight });
+        }
+
+        let total = criteria
+            .iter()
+            .map(|criterion| criterion.weight)
+            .sum::<f64>();
+        if !criteria.is_empty() && (total - TOTAL_WEIGHT).abs() > 0.01 {
+            self.warn(
+                span,
+                format!(
+                    "evaluation weightings add up to {}%, not 100%",
+                    format_weight(total)
+                ),
+            );
+        }
+        criteria
+    }
+}
+
+fn first_line(text: &str) -> Range<usize> {
+    0..text.find('\n').unwrap_or(text.len())
+}
+
+fn normalize_heading(heading: &str) -> String {
+    heading
+        .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+        .trim()
+        .to_lowercase()
+}
+
+fn limits_label(pages: Option<usize>, words: Option<usize>) -> String {
+    let mut limits = Vec::new();
+    if let Some(pages) = pages {
+        limits.push(format!(
+            "{pages} {}",
+            if pages == 1 { "page" } else { "pages" }
+        ));
+    }
+    if let Some(words) = words {
+        limits.push(format!(
+            "{words} {}",
+            if words == 1 { "word" } else { "words" }
+        ));
+    }
+    limits.join(", ")
+}
+
+fn format_weight(weight: f64) -> String {
+    if weight.fract() == 0. {
+        format!("{weight:.0}")
+    } else {
+        format!("{weight}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const MANIFEST: &str = indoc! {r#"
+        client = "Department of Transport"
+        reference = "DOT-2026-041"
+        title = "Managed Help Desk Services"
+        closes = 2026-11-14T14:00:00+11:00
+        time_zone = "Australia/Sydney"
+        portal = "https://tenders.example.gov.au/DOT-2026-041"
+
+        [limits]
+        pages = 40
+        words = 12000
+
+        [[limits.sections]]
+        heading = "Executive Summary"
+        words = 500
+
+        [[evaluation]]
+        criterion = "Methodology"
+        weight = 60
+
+        [[evaluation]]
+        criterion = "Price"
+        weight = 40
+    "#};
+
+    fn problems(text: &str) -> Vec<(Severity, String)> {
+        TenderManifest::parse(text)
+            .problems
+            .into_iter()
+            .map(|problem| (problem.severity, problem.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let parsed = TenderManifest::parse(MANIFEST);
+        assert_eq!(parsed.problems, []);
+        let manifest = parsed.manifest.unwrap();
+        assert_eq!(manifest.client.as_deref(), Some("Department of Transport"));
+        assert_eq!(
+            manifest.closes_at.unwrap().to_rfc3339(),
+            "2026-11-14T14:00:00+11:00"
+        );
+        assert_eq!(manifest.limits.words, Some(12000));
+        assert_eq!(
+            manifest
+                .section_limit("2. executive summary")
+                .and_then(|limit| limit.words),
+            Some(500)
+        );
+        assert_eq!(manifest.section_limit("Pricing"), None);
+        assert!(manifest.is_response_path(Path::new("response/Response.md")));
+        assert!(!manifest.is_response_path(Path::new("notes/Response.md")));
+        assert_eq!(
+            manifest.closing_time_label().unwrap(),
+            "Sat 14 Nov 2026, 2:00 PM Australia/Sydney (UTC+11:00)"
+        );
+        assert_eq!(
+            manifest.summary(),
+            indoc! {"
+                - Client: Department of Transport
+                - Reference: DOT-2026-041
+                - Title: Managed Help Desk Services
+                - Closes: Sat 14 Nov 2026, 2:00 PM Australia/Sydney (UTC+11:00)
+                - Submission portal: https://tenders.example.gov.au/DOT-2026-041
+                - Limits per response document, in response/: 40 pages, 12000 words
+                - Section limits:
+                  - Executive Summary: 500 words
+                - Evaluation criteria:
+                  - Methodology: 60%
+                  - Price: 40%
+            "}
+        );
+    }
+
+    #[test]
+    fn test_manifest_problems() {
+        let text = indoc! {r#"
+            client = ""
+            closes = 2026-11-14
+            portal = "tenders.example.gov.au"
+
+            [limits]
+            words = -5
+
+            [[evaluation]]
+            criterion = "Methodology"
+            weight = 60
+        "#};
+        assert_eq!(
+            problems(text),
+            [
+                (Severity::Error, "`client` must not be empty".into()),
+                (Severity::Error, "missing `reference`".into()),
+                (
+                    Severity::Error,
+                    "`closes` must be a date and time with a UTC offset, e.g. 2026-11-14T14:00:00+11:00"
+                        .into()
+                ),
+                (Severity::Warning, "`portal` should be a web address".into()),
+                (Severity::Error, "`words` must be a positive number".into()),
+                (
+                    Severity::Warning,
+                    "evaluation weightings add up to 60%, not 100%".into()
+                ),
+            ]
+        );
+        let parsed = TenderManifest::parse(text);
+        assert_eq!(&text[parsed.problems[2].range.clone()], "2026-11-14");
+        assert_eq!(&text[parsed.problems[4].range.clone()], "-5");
+        assert_eq!(parsed.manifest.unwrap().client, None);
+
+        let parsed = TenderManifest::parse("client = \"Acme\"\nbudget = 5\n");
+        assert_eq!(parsed.manifest, None);
+        assert_eq!(parsed.problems.len(), 1);
+        assert_eq!(parsed.problems[0].severity, Severity::Error);
+        assert!(parsed.problems[0].message.contains("budget"));
+
+        let text = indoc! {r#"
+            client = "Acme"
+            reference = "ACME-1"
+            closes = 2026-11-14T14:00:00+11:00
+
+            [folders]
+            documents = "issued/"
+            response = "../submission"
+        "#};
+        assert_eq!(
+            problems(text),
+            [(
+                Severity::Error,
+                "`folders.response` must be a folder within the project, e.g. \"response\"".into()
+            )]
+        );
+        assert_eq!(
+            TenderManifest::parse(text).manifest.unwrap().folders,
+            Folders {
+                documents: PathBuf::from("issued"),
+                response: PathBuf::from("response"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_countdown_label() {
+        assert_eq!(
+            countdown_label(TimeDelta::days(3) + TimeDelta::hours(4)),
+            "Closes in 3d 4h"
+        );
+        assert_eq!(
+            countdown_label(TimeDelta::hours(5) + TimeDelta::minutes(12)),
+            "Closes in 5h 12m"
+        );
+        assert_eq!(countdown_label(TimeDelta::seconds(20)), "Closes in 1m");
+        assert_eq!(countdown_label(TimeDelta::zero()), "Closed");
+        assert_eq!(countdown_label(TimeDelta::hours(-2)), "Closed");
+    }
+}
@@ -0,0 +1,301 @@
+//! The tender manifest: a `tender.toml` at the root of a tender project that
+//! records the client, reference number, closing time, submission portal,
+//! page and word limits, and evaluation weightings.
+//!
+//! Each project's manifest is parsed from its buffer, so it updates as it's
+//! edited, and any problems with it are published as diagnostics.
+//!
+//! The manifest's page and word limits for whole documents apply to those in
+//! its response folder, `response/` unless `[folders]` says otherwise. Limits
+//! can also be set on a single section of a response by annotating it with a
+//! comment under its heading, such as `<!-- limit: 500 words -->`.
+
+mod limits;
+mod manifest;
+
+use collections::HashMap;
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity,
+};
+use language::{Buffer, BufferEvent, Diagnostic, DiagnosticSeverity};
+use project::{Project, ProjectPath};
+use prose::DiagnosticSource;
+use std::{path::Path, sync::Arc, time::Duration};
+use workspace::Workspace;
+
+pub use limits::*;
+pub use manifest::*;
+
+/// The name of the manifest file, at the root of the project's worktree.
+pub const MANIFEST_FILE_NAME: &str = "tender.toml";
+
+const PARSE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Default)]
+struct GlobalTenderManifests(HashMap<EntityId, Entity<TenderManifestStore>>);
+
+impl Global for GlobalTenderManifests {}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, cx| {
+        TenderManifestStore::for_project(workspace.project(), cx);
+    })
+    .detach();
+}
+
+/// Tracks the manifest of a project, from the first visible worktree with a
+/// `tender.toml` at its root.
+pub struct TenderManifestStore {
+    project: WeakEntity<Project>,
+    path: Option<ProjectPath>,
+    buffer: Option<Entity<Buffer>>,
+    manifest: Option<Arc<TenderManifest>>,
+    load: Task<()>,
+    parse: Task<()>,
+    _buffer_subscription: Option<Subscription>,
+    _project_subscription: Subscription,
+}
+
+impl TenderManifestStore {
+    /// Returns the store for `project`, creating it the first time.
+    pub fn for_project(project: &Entity<Project>, cx: &mut App) -> Entity<Self> {
+        let project_id = project.entity_id();
+        if let Some(store) = cx
+            .default_global::<GlobalTenderManifests>()
+            .0
+            .get(&project_id)
+        {
+            return store.clone();
+        }
+
+        let store = cx.new(|cx| Self::new(project, cx));
+        cx.default_global::<GlobalTenderManifests>()
+            .0
+            .insert(project_id, store.clone());
+        cx.observe_release(project, move |_, cx| {
+            cx.default_global::<GlobalTenderManifests>()
+                .0
+                .remove(&project_id);
+        })
+        .detach();
+        store
+    }
+
+    fn new(project: &Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.subscribe(project, |this, _, event, cx| match event {
+            project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                this.reload(cx)
+            }
+            project::Event::WorktreeUpdatedEntries(_, changes) => {
+                if changes
+                    .iter()
+                    .any(|(path, _, _)| path.as_ref() == Path::new(MANIFEST_FILE_NAME))
+                {
+                    this.reload(cx);
+                }
+            }
+            _ => {}
+        });
+        let mut this = Self {
+            project: project.downgrade(),
+            path: None,
+            buffer: None,
+            manifest: None,
+            load: Task::ready(()),
+            parse: Task::ready(()),
+            _buffer_subscription: None,
+            _project_subscription: subscription,
+        };
+        this.reload(cx);
+        this
+    }
+
+    /// The project's manifest. While the manifest is being edited, this is
+    /// the last version that could be parsed.
+    pub fn manifest(&self) -> Option<Arc<TenderManifest>> {
+        self.manifest.clone()
+    }
+
+    /// The location of the manifest, if the project has one.
+    pub fn path(&self) -> Option<&ProjectPath> {
+        self.path.as_ref()
+    }
+
+    /// Whether `path` is a response document, in the manifest's response
+    /// folder. The manifest's limits on whole documents only apply to these.
+    pub fn is_response(&self, path: &ProjectPath) -> bool {
+        let (Some(manifest_path), Some(manifest)) = (&self.path, &self.manifest) else {
+            return false;
+        };
+        manifest_path.worktree_id == path.worktree_id && manifest.is_response_path(&path.path)
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let path = project.read(cx).visible_worktrees(cx).find_map(|worktree| {
+            let worktree = worktree.read(cx);
+            worktree
+                .entry_for_path(MANIFEST_FILE_NAME)
+                .filter(|entry| entry.is_file())
+                .map(|entry| ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: entry.path.clone(),
+                })
+        });
+        if path == self.path && (path.is_none() || self.buffer.is_some()) {
+            return;
+        }
+
+        if let Some(buffer) = self.buffer.take() {
+            prose::clear_diagnostics(&buffer, DiagnosticSource::TenderManifest, cx);
+        }
+        self._buffer_subscription = None;
+        self.parse = Task::ready(());
+        self.path = path.clone();
+        let Some(path) = path else {
+            self.load = Task::ready(());
+            self.manifest = None;
+            cx.notify();
+            return;
+        };
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(path, cx));
+        self.load = cx.spawn(async move |this, cx| {
+            let buffer = match open_buffer.await {
+                Ok(buffer) => buffer,
+                Err(error) => {
+                    log::error!("failed to open {MANIFEST_FILE_NAME}: {error:#}");
+                    return;
+                }
+            };
+            this.update(cx, |this, cx| {
+                this._buffer_subscription =
+                    Some(cx.subscribe(&buffer, |this, buffer, event, cx| {
+                        if matches!(event, BufferEvent::Edited | BufferEvent::Reloaded) {
+                            this.parse_buffer(buffer, Some(PARSE_DEBOUNCE), cx);
+                        }
+                    }));
+                this.buffer = Some(buffer.clone());
+                this.parse_buffer(buffer, None, cx);
+            })
+            .ok();
+        });
+    }
+
+    fn parse_buffer(
+        &mut self,
+        buffer: Entity<Buffer>,
+        debounce: Option<Duration>,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = buffer.read(cx).snapshot();
+        self.parse = cx.spawn(async move |this, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+            let text = snapshot.text();
+            let parsed = cx
+                .background_spawn(async move { TenderManifest::parse(&text) })
+                .await;
+            this.update(cx, |this, cx| {
+                let diagnostics = parsed
+                    .problems
+                    .into_iter()
+                    .map(|problem| {
+                        let severity = match problem.severity {
+                            Severity::Error => DiagnosticSeverity::ERROR,
+                            Severity::Warning => DiagnosticSeverity::WARNING,
+                        };
+                        let diagnostic = Diagnostic {
+                            severity,
+                            message: problem.message,
+                            ..Default::default()
+                        };
+                        (problem.range, diagnostic)
+                    })
+                    .collect();
+                prose::publish_diagnostics(
+                    &buffer,
+                    DiagnosticSource::TenderManifest,
+                    &snapshot,
+                    diagnostics,
+                    cx,
+                );
+                if let Some(manifest) = parsed.manifest {
+                    this.manifest = Some(Arc::new(manifest));
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_manifest_follows_buffer_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                "tender.toml": indoc! {r#"
+                    client = "Department of Transport"
+                    reference = "DOT-2026-041"
+                    closes = 2026-11-14T14:00:00+11:00
+                "#},
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/tender").as_ref()], cx).await;
+        let store = cx.update(|cx| TenderManifestStore::for_project(&project, cx));
+        cx.run_until_parked();
+
+        let manifest = store.read_with(cx, |store, _| store.manifest().unwrap());
+        assert_eq!(manifest.reference.as_deref(), Some("DOT-2026-041"));
+        let buffer = store.read_with(cx, |store, _| store.buffer.clone().unwrap());
+        let diagnostic_count = |cx: &mut TestAppContext| {
+            buffer.read_with(cx, |buffer, _| {
+                buffer
+                    .snapshot()
+                    .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+                    .count()
+            })
+        };
+        assert_eq!(diagnostic_count(cx), 0);
+
+        buffer.update(cx, |buffer, cx| {
+            let start = buffer.text().find("DOT").unwrap();
+            buffer.edit([(start..start + 3, "DOH")], None, cx);
+            let end = buffer.len();
+            buffer.edit([(end..end, "[limits]\nwords = 0\n")], None, cx);
+        });
+        cx.executor().advance_clock(PARSE_DEBOUNCE);
+        cx.run_until_parked();
+
+        let manifest = store.read_with(cx, |store, _| store.manifest().unwrap());
+        assert_eq!(manifest.reference.as_deref(), Some("DOH-2026-041"));
+        assert_eq!(manifest.limits.words, None);
+        assert_eq!(diagnostic_count(cx), 1);
+    }
+}
@@ -7,7 +7,7 @@ use gpui::{
     Window, actions,
 };
 use settings::Settings;
-use writing_app_settings::{WritingAppSettings, get_global};
+use writing_app_settings::WritingAppSettings;
 use picker::{Picker, PickerDelegate};
 use settings::{SettingsStore, update_settings_file};
 use std::sync::Arc;
@@ -39,7 +39,7 @@ fn toggle_theme_selector(
     cx: &mut Context<Workspace>,
 ) {
     // Only show theme selector if enabled in writing app settings
-    if !get_global(cx).show_themes {
+    if !WritingAppSettings::get_global(cx).show_themes {
         return;
     }
     
@@ -62,7 +62,7 @@ fn toggle_icon_theme_selector(
     cx: &mut Context<Workspace>,
 ) {
     // Only show icon theme selector if enabled in writing app settings
-    if !get_global(cx).show_icon_themes {
+    if !WritingAppSettings::get_global(cx).show_icon_themes {
         return;
     }
     
@@ -18,18 +18,20 @@ use crate::application_menu::{
 use crate::platforms::{platform_linux, platform_mac, platform_windows};
 use auto_update::AutoUpdateStatus;
 use call::ActiveCall;
+use chrono::{TimeDelta, Utc};
 use client::{Client, UserStore};
 use gpui::{
     Action, AnyElement, App, Context, Corner, Decorations, Element, Entity, InteractiveElement,
     Interactivity, IntoElement, MouseButton, ParentElement, Render, Stateful,
-    StatefulInteractiveElement, Styled, Subscription, WeakEntity, Window, actions, div, px,
+    StatefulInteractiveElement, Styled, Subscription, Task, WeakEntity, Window, actions, div, px,
 };
 use onboarding_banner::OnboardingBanner;
 use project::Project;
 use rpc::proto;
-use settings::Settings as _;
+use settings::{Settings as _, SettingsStore};
 use smallvec::SmallVec;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+use tender_manifest::{TenderManifestStore, countdown_label};
 use theme::ActiveTheme;
 use title_bar_settings::TitleBarSettings;
 use ui::{
@@ -49,6 +51,8 @@ const MAX_PROJECT_NAME_LENGTH: usize = 40;
 const MAX_BRANCH_NAME_LENGTH: usize = 40;
 const MAX_SHORT_SHA_LENGTH: usize = 8;
 
+const COUNTDOWN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 const BOOK_ONBOARDING: &str = "https://dub.sh/zed-c-onboarding";
 
 actions!(collab, [ToggleUserMenu, ToggleProjectMenu, SwitchBranch]);
@@ -122,6 +126,8 @@ pub struct TitleBar {
     workspace: WeakEntity<Workspace>,
     should_move: bool,
     application_menu: Option<Entity<ApplicationMenu>>,
+    tender_manifest: Entity<TenderManifestStore>,
+    refresh_countdown: Option<Task<()>>,
     _subscriptions: Vec<Subscription>,
     banner: Entity<OnboardingBanner>,
 }
@@ -202,6 +208,7 @@ impl Render for TitleBar {
                                             .children(self.render_project_host(cx))
                                             .child(self.render_project_name(cx))
                                             .children(self.render_project_branch(cx))
+                                            .children(self.render_tender_countdown(cx))
                                     })
                             })
                             .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation()),
@@ -329,6 +336,13 @@ impl TitleBar {
         subscriptions.push(cx.observe_window_activation(window, Self::window_activation_changed));
         subscriptions.push(cx.observe(&user_store, |_, _, cx| cx.notify()));
 
+        let tender_manifest = TenderManifestStore::for_project(&project, cx);
+        subscriptions.push(cx.observe(&tender_manifest, |this, _, cx| {
+            this.tender_countdown_changed(cx)
+        }));
+        subscriptions
+            .push(cx.observe_global::<SettingsStore>(|this, cx| this.tender_countdown_changed(cx)));
+
         let banner = cx.new(|cx| {
             OnboardingBanner::new(
                 "Agentic Onboarding",
@@ -340,7 +354,7 @@ impl TitleBar {
             )
         });
 
-        Self {
+        let mut this = Self {
             platform_style,
             content: div().id(id.into()),
             children: SmallVec::new(),
@@ -350,9 +364,13 @@ impl TitleBar {
             project,
             user_store,
             client,
+            tender_manifest,
+            refresh_countdown: None,
             _subscriptions: subscriptions,
             banner,
-        }
+        };
+        this.tender_countdown_changed(cx);
+        this
     }
 
     #[cfg(not(target_os = "windows"))]
@@ -533,6 +551,80 @@ impl TitleBar {
             }))
     }
 
+    fn shows_tender_countdown(&self, cx: &App) -> bool {
+        TitleBarSettings::get_global(cx).show_tender_countdown
+            && self
+                .tender_manifest
+                .read(cx)
+                .manifest()
+                .is_some_and(|manifest| manifest.closes_at.is_some())
+    }
+
+    /// Restarts the countdown's refresh timer while a closing time is shown,
+    /// and stops it otherwise.
+    fn tender_countdown_changed(&mut self, cx: &mut Context<Self>) {
+        self.refresh_countdown = self.shows_tender_countdown(cx).then(|| {
+            cx.spawn(async move |this, cx| {
+                loop {
+                    cx.background_executor()
+                        .timer(COUNTDOWN_REFRESH_INTERVAL)
+                        .await;
+                    if this.update(cx, |_, cx| cx.notify()).is_err() {
+                        break;
+                    }
+                }
+            })
+        });
+        cx.notify();
+    }
+
+    /// A countdown to the close of the tender described by the project's
+    /// `tender.toml`, which opens the manifest when clicked.
+    pub fn render_tender_countdown(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        if !TitleBarSettings::get_global(cx).show_tender_countdown {
+            return None;
+        }
+        let store = self.tender_manifest.read(cx);
+        let manifest = store.manifest()?;
+        let path = store.path()?.clone();
+        let remaining = manifest.time_remaining(Utc::now())?;
+        let closing_time = manifest.closing_time_label()?;
+        let title = match &manifest.reference {
+            Some(reference) => format!("{reference} closes"),
+            None => "Submissions close".to_string(),
+        };
+        let color = if remaining <= TimeDelta::zero() {
+            Color::Error
+        } else if remaining < TimeDelta::days(1) {
+            Color::Warning
+        } else {
+            Color::Muted
+        };
+
+        Some(
+            Button::new("tender_countdown_trigger", countdown_label(remaining))
+                .icon(IconName::CountdownTimer)
+                .icon_size(IconSize::Small)
+                .icon_position(IconPosition::Start)
+                .icon_color(color)
+                .color(color)
+                .style(ButtonStyle::Subtle)
+                .label_size(LabelSize::Small)
+                .tooltip(move |window, cx| {
+                    Tooltip::with_meta(title.clone(), None, closing_time.clone(), window, cx)
+                })
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    this.workspace
+                        .update(cx, |workspace, cx| {
+                            workspace
+                                .open_path(path.clone(), None, true, window, cx)
+                                .detach_and_log_err(cx);
+                        })
+                        .log_err();
+                })),
+        )
+    }
+
     pub fn render_project_branch(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
         let repository = self.project.read(cx).active_repository(cx)?;
         let workspace = self.workspace.upgrade()?;
@@ -8,6 +8,7 @@ pub struct TitleBarSettings {
     pub show_branch_icon: bool,
     pub show_onboarding_banner: bool,
     pub show_user_picture: bool,
+    pub show_tender_countdown: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
@@ -24,6 +25,11 @@ pub struct TitleBarSettingsContent {
     ///
     /// Default: true
     pub show_user_picture: Option<bool>,
+    /// Whether to show a countdown to the close of the tender described by
+    /// the project's `tender.toml` in the title bar.
+    ///
+    /// Default: true
+    pub show_tender_countdown: Option<bool>,
 }
 
 impl Settings for TitleBarSettings {
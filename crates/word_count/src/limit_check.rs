@@ -0,0 +1,306 @@
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity,
+};
+use language::{Buffer, BufferEvent, Diagnostic, DiagnosticSeverity};
+use project::{Project, ProjectPath};
+use prose::DiagnosticSource;
+use std::time::Duration;
+use tender_manifest::TenderManifestStore;
+use ui::ActiveTheme as _;
+
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(300);
+
+struct GlobalLimitChecker(Entity<LimitChecker>);
+
+impl Global for GlobalLimitChecker {}
+
+pub(crate) fn init(cx: &mut App) {
+    let limit_checker = cx.new(|_| LimitChecker::default());
+    cx.set_global(GlobalLimitChecker(limit_checker));
+
+    cx.observe_new(|editor: &mut Editor, _, cx| register_editor(editor, cx))
+        .detach();
+}
+
+fn register_editor(editor: &mut Editor, cx: &mut Context<Editor>) {
+    if !editor.mode().is_full() {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let limit_checker = cx.global::<GlobalLimitChecker>().0.clone();
+    let buffers = editor.buffer().read(cx).all_buffers();
+    for buffer in buffers {
+        limit_checker.update(cx, |limit_checker, cx| {
+            limit_checker.watch_buffer(buffer, &project, cx)
+        });
+    }
+    cx.subscribe(&cx.entity(), move |_, _, event, cx| {
+        if let EditorEvent::ExcerptsAdded { buffer, .. } = event {
+            limit_checker.update(cx, |limit_checker, cx| {
+                limit_checker.watch_buffer(buffer.clone(), &project, cx)
+            });
+        }
+    })
+    .detach();
+
+    // Mark overruns in the gutter as well as underlining them, so that text
+    // past a limit stands out while scrolling.
+    if let Some(buffer) = editor.buffer().read(cx).as_singleton() {
+        cx.subscribe(&buffer, |editor, _, event, cx| {
+            if matches!(event, BufferEvent::DiagnosticsUpdated) {
+                update_gutter_highlights(editor, cx);
+            }
+        })
+        .detach();
+        update_gutter_highlights(editor, cx);
+    }
+}
+
+fn update_gutter_highlights(editor: &mut Editor, cx: &mut Context<Editor>) {
+    enum LimitOverrunGutter {}
+
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let snapshot = buffer.read(cx).snapshot();
+    let multibuffer = editor.buffer().read(cx).snapshot(cx);
+    let ranges =
+        prose::diagnostics_in_range(&snapshot, DiagnosticSource::WordLimit, 0..snapshot.len())
+            .map(|entry| {
+                multibuffer.anchor_before(entry.range.start)
+                    ..multibuffer.anchor_after(entry.range.end)
+            })
+            .collect::<Vec<_>>();
+    if ranges.is_empty() {
+        editor.clear_gutter_highlights::<LimitOverrunGutter>(cx);
+    } else {
+        editor.highlight_gutter::<LimitOverrunGutter>(&ranges, |cx| cx.theme().status().error, cx);
+    }
+}
+
+/// Checks every Markdown buffer open in an editor against the limits set by
+/// its annotations and its project's manifest.
+#[derive(Default)]
+struct LimitChecker {
+    buffers: HashMap<EntityId, WatchedBuffer>,
+}
+
+struct WatchedBuffer {
+    buffer: WeakEntity<Buffer>,
+    tender_manifest: Entity<TenderManifestStore>,
+    check: Task<()>,
+    _subscriptions: [Subscription; 3],
+}
+
+impl LimitChecker {
+    fn watch_buffer(
+        &mut self,
+        buffer: Entity<Buffer>,
+        project: &Entity<Project>,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer_id = buffer.entity_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+
+        let tender_manifest = TenderManifestStore::for_project(project, cx);
+        let subscriptions = [
+            cx.subscribe(&buffer, |this, buffer, event, cx| match event {
+                BufferEvent::Edited | BufferEvent::LanguageChanged => {
+                    this.check_buffer(&buffer, Some(CHECK_DEBOUNCE), cx)
+                }
+                _ => {}
+            }),
+            cx.observe(&tender_manifest, move |this, _, cx| {
+                let buffer = this
+                    .buffers
+                    .get(&buffer_id)
+                    .and_then(|watched| watched.buffer.upgrade());
+                if let Some(buffer) = buffer {
+                    this.check_buffer(&buffer, None, cx);
+                }
+            }),
+            cx.observe_release(&buffer, move |this, _, _| {
+                this.buffers.remove(&buffer_id);
+            }),
+        ];
+        self.buffers.insert(
+            buffer_id,
+            WatchedBuffer {
+                buffer: buffer.downgrade(),
+                tender_manifest,
+                check: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.check_buffer(&buffer, None, cx);
+    }
+
+    fn check_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        debounce: Option<Duration>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(watched) = self.buffers.get_mut(&buffer.entity_id()) else {
+            return;
+        };
+        if !prose::is_markdown(buffer.read(cx)) {
+            watched.check = Task::ready(());
+            prose::clear_diagnostics(buffer, DiagnosticSource::WordLimit, cx);
+            return;
+        }
+
+        let manifest = watched.tender_manifest.read(cx).manifest();
+        let is_response = buffer.read(cx).file().is_some_and(|file| {
+            watched
+                .tender_manifest
+                .read(cx)
+                .is_response(&ProjectPath::from_file(file.as_ref(), cx))
+        });
+        let snapshot = buffer.read(cx).snapshot();
+        let weak_buffer = buffer.downgrade();
+        watched.check = cx.spawn(async move |_, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+            let diagnostics = cx
+                .background_spawn({
+                    let text = snapshot.text();
+                    async move {
+                        tender_manifest::limit_overruns(&text, manifest.as_deref(), is_response)
+                            .into_iter()
+                            .map(|overrun| {
+                                let diagnostic = Diagnostic {
+                                    severity: DiagnosticSeverity::ERROR,
+                                    message: overrun.message(),
+                                    ..Default::default()
+                                };
+                                (overrun.excess_range, diagnostic)
+                            })
+                            .collect::<Vec<_>>()
+                    }
+                })
+                .await;
+            if let Some(buffer) = weak_buffer.upgrade() {
+                cx.update(|cx| {
+                    prose::publish_diagnostics(
+                        &buffer,
+                        DiagnosticSource::WordLimit,
+                        &snapshot,
+                        diagnostics,
+                        cx,
+                    )
+                })
+                .ok();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::{AppContext as _, TestAppContext};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_limit_overruns_are_reported(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/tender"),
+            json!({
+                "tender.toml": indoc! {r#"
+                    client = "Department of Transport"
+                    reference = "DOT-2026-041"
+                    closes = 2026-11-14T14:00:00+11:00
+
+                    [limits]
+                    words = 3
+
+                    [[limits.sections]]
+                    heading = "Methodology"
+                    words = 3
+                "#},
+                "response": {
+                    "Response.md": "We deliver help desks.\n",
+                },
+                "response.md": indoc! {"
+                    ## Executive Summary
+                    <!-- limit: 4 words -->
+
+                    We deliver help desks.
+
+                    ## Methodology
+
+                    Three phases over twelve weeks.
+                "},
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/tender").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/tender/response.md"), cx)
+            })
+            .await
+            .unwrap();
+        let response_buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/tender/response/Response.md"), cx)
+            })
+            .await
+            .unwrap();
+        let limit_checker = cx.new(|_| LimitChecker::default());
+        limit_checker.update(cx, |limit_checker, cx| {
+            limit_checker.watch_buffer(buffer.clone(), &project, cx);
+            limit_checker.watch_buffer(response_buffer.clone(), &project, cx);
+        });
+        cx.run_until_parked();
+
+        let overruns = |buffer: &Entity<Buffer>, cx: &mut TestAppContext| {
+            buffer.read_with(cx, |buffer, _| {
+                let snapshot = buffer.snapshot();
+                prose::diagnostics_in_range(
+                    &snapshot,
+                    DiagnosticSource::WordLimit,
+                    0..snapshot.len(),
+                )
+                .map(|entry| snapshot.text_for_range(entry.range).collect::<String>())
+                .collect::<Vec<_>>()
+            })
+        };
+        // The document limit only applies to documents in the response folder.
+        assert_eq!(overruns(&buffer, cx), ["twelve weeks"]);
+        assert_eq!(overruns(&response_buffer, cx), ["desks"]);
+
+        buffer.update(cx, |buffer, cx| {
+            let offset = buffer.text().find("desks").unwrap();
+            buffer.edit([(offset..offset, "service ")], None, cx);
+        });
+        cx.executor().advance_clock(CHECK_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(overruns(&buffer, cx), ["desks", "twelve weeks"]);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{format_count, reading_minutes, words_label};
+use editor::{Editor, scroll::Autoscroll};
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ParentElement,
+    Render, Styled, Task, WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use prose::Section;
+use std::sync::Arc;
+use tender_manifest::TenderManifestStore;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+/// Lists the headings of the active Markdown document with the word count of
+/// each section against its limit, and jumps to the selected heading.
+pub struct SectionBreakdown {
+    picker: Entity<Picker<SectionBreakdownDelegate>>,
+}
+
+impl SectionBreakdown {
+    pub fn toggle(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Option<()> {
+        let editor = workspace.active_item(cx)?.act_as::<Editor>(cx)?;
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        if !prose::is_markdown(buffer.read(cx)) {
+            return None;
+        }
+        let text = buffer.read(cx).text();
+        let sections = prose::sections(&text);
+        let manifest = TenderManifestStore::for_project(workspace.project(), cx)
+            .read(cx)
+            .manifest();
+        let word_limits = tender_manifest::section_limits(&text, &sections, manifest.as_deref())
+            .into_iter()
+            .map(|limit| limit.words)
+            .collect();
+
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            SectionBreakdown::new(sections, word_limits, editor, window, cx)
+        });
+        Some(())
+    }
+
+    fn new(
+        sections: Vec<Section>,
+        word_limits: Vec<Option<usize>>,
+        editor: Entity<Editor>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            SectionBreakdownDelegate::new(cx.entity().downgrade(), sections, word_limits, editor);
+        let picker = cx.new(|cx| {
+            Picker::uniform_list(delegate, window, cx).max_height(Some(vh(0.75, window)))
+        });
+        Self { picker }
+    }
+}
+
+impl Render for SectionBreakdown {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for SectionBreakdown {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SectionBreakdown {}
+impl ModalView for SectionBreakdown {}
+
+struct SectionBreakdownDelegate {
+    section_breakdown: WeakEntity<SectionBreakdown>,
+    editor: Entity<Editor>,
+    sections: Vec<Section>,
+    /// The word limit of each section, by index into `sections`.
+    word_limits: Vec<Option<usize>>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl SectionBreakdownDelegate {
+    fn new(
+        section_breakdown: WeakEntity<SectionBreakdown>,
+        sections: Vec<Section>,
+        word_limits: Vec<Option<usize>>,
+        editor: Entity<Editor>,
+    ) -> Self {
+        let candidates = sections
+            .iter()
+            .enumerate()
+            .map(|(ix, section)| StringMatchCandidate::new(ix, &section.title))
+            .collect();
+        Self {
+            section_breakdown,
+            editor,
+            sections,
+            word_limits,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for SectionBreakdownDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search sections…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("This document has no headings.".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                // Keep the document order, which is more useful than the score
+                // when reviewing a response section by section.
+                matches.sort_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let offset = self.sections[mat.candidate_id].heading_range.start;
+            self.editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                    s.select_ranges([offset..offset])
+                });
+                window.focus(&editor.focus_handle(cx));
+            });
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.section_breakdown
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let section = self.sections.get(mat.candidate_id)?;
+        let words = section.stats.words;
+        let word_limit = self.word_limits.get(mat.candidate_id).copied().flatten();
+        let details = if words > 0 {
+            format!(
+                "{} · {} min",
+                words_label(words, word_limit),
+                format_count(reading_minutes(words))
+            )
+        } else {
+            words_label(words, word_limit)
+        };
+        let details_color = if word_limit.is_some_and(|word_limit| words > word_limit) {
+            Color::Error
+        } else {
+            Color::Muted
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .justify_between()
+                        .child(
+                            div()
+                                .pl(rems(section.level.saturating_sub(1) as f32))
+                                .child(HighlightedLabel::new(
+                                    section.title.clone(),
+                                    mat.positions.clone(),
+                                )),
+                        )
+                        .child(
+                            Label::new(details)
+                                .size(LabelSize::Small)
+                                .color(details_color),
+                        ),
+                ),
+        )
+    }
+}
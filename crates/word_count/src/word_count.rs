@@ -0,0 +1,145 @@
+//! Word counts for Markdown documents: a status bar item showing words,
+//! characters and reading time for the active buffer or selection, and a
+//! per-section breakdown for checking responses against word limits.
+//!
+//! Word and page limits come from the project's `tender.toml`, or from a
+//! `<!-- limit: 500 words -->` annotation under a heading. Text past a limit
+//! is reported as a diagnostic and marked in the gutter.
+
+mod limit_check;
+mod section_breakdown;
+mod word_count_indicator;
+
+use gpui::{App, actions};
+use prose::TextStats;
+use std::fmt::Write as _;
+use workspace::Workspace;
+
+pub use section_breakdown::SectionBreakdown;
+pub use word_count_indicator::WordCountIndicator;
+
+actions!(word_count, [ToggleSectionBreakdown]);
+
+/// A typical silent reading speed for non-fiction.
+const WORDS_PER_MINUTE: usize = 230;
+
+pub fn init(cx: &mut App) {
+    limit_check::init(cx);
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleSectionBreakdown, window, cx| {
+            SectionBreakdown::toggle(workspace, window, cx);
+        });
+    })
+    .detach();
+}
+
+pub fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+/// Formats a count with thousands separators, e.g. `12,345`.
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (ix, digit) in digits.chars().enumerate() {
+        if ix > 0 && (digits.len() - ix) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+fn pluralize(count: usize, singular: &str) -> String {
+    let suffix = if count == 1 { "" } else { "s" };
+    format!("{} {singular}{suffix}", format_count(count))
+}
+
+/// A word count, with the limit it's measured against if there is one, e.g.
+/// `480 / 500 words`.
+fn words_label(words: usize, limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) => format!("{} / {}", format_count(words), pluralize(limit, "word")),
+        None => pluralize(words, "word"),
+    }
+}
+
+/// The status bar label for a document and, when text is selected, the
+/// selection within it.
+fn stats_label(
+    document: TextStats,
+    selection: Option<TextStats>,
+    word_limit: Option<usize>,
+    show_word_count: bool,
+    show_reading_time: bool,
+) -> String {
+    let mut label = String::new();
+    let stats = selection.unwrap_or(document);
+    if show_word_count {
+        match selection {
+            Some(selection) => write!(
+                label,
+                "{} of {}",
+                format_count(selection.words),
+                words_label(document.words, word_limit)
+            )
+            .unwrap(),
+            None => label.push_str(&words_label(document.words, word_limit)),
+        }
+        write!(label, " · {}", pluralize(stats.characters, "character")).unwrap();
+    }
+    if show_reading_time && stats.words > 0 {
+        if !label.is_empty() {
+            label.push_str(" · ");
+        }
+        write!(label, "{} min read", reading_minutes(stats.words)).unwrap();
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_stats_label() {
+        let document = TextStats {
+            words: 1500,
+            characters: 9001,
+        };
+        let selection = TextStats {
+            words: 1,
+            characters: 6,
+        };
+        assert_eq!(
+            stats_label(document, None, None, true, true),
+            "1,500 words · 9,001 characters · 7 min read"
+        );
+        assert_eq!(
+            stats_label(document, Some(selection), None, true, true),
+            "1 of 1,500 words · 6 characters · 1 min read"
+        );
+        assert_eq!(
+            stats_label(document, None, Some(1200), true, false),
+            "1,500 / 1,200 words · 9,001 characters"
+        );
+        assert_eq!(
+            stats_label(document, Some(selection), Some(1200), true, false),
+            "1 of 1,500 / 1,200 words · 6 characters"
+        );
+        assert_eq!(stats_label(document, None, None, false, true), "7 min read");
+        assert_eq!(
+            stats_label(TextStats::default(), None, None, false, true),
+            ""
+        );
+    }
+}
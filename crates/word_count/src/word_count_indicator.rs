@@ -0,0 +1,217 @@
+use crate::{SectionBreakdown, ToggleSectionBreakdown, stats_label};
+use editor::{Editor, EditorEvent};
+use gpui::{AppContext as _, Entity, Subscription, Task, WeakEntity};
+use project::ProjectPath;
+use prose::TextStats;
+use settings::{Settings as _, SettingsLocation, SettingsStore};
+use std::{ops::Range, time::Duration};
+use tender_manifest::TenderManifestStore;
+use ui::{
+    Button, ButtonCommon, Clickable, Color, Context, FluentBuilder, IntoElement, LabelSize,
+    ParentElement, Render, Tooltip, Window, div,
+};
+use workspace::{StatusItemView, Workspace, item::ItemHandle};
+use writing_app_settings::WritingAppSettings;
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct DocumentStats {
+    document: TextStats,
+    selection: Option<TextStats>,
+}
+
+/// Shows the word count, character count and reading time of the active
+/// Markdown buffer, or of the selected text within it, against the document
+/// word limit from the project's `tender.toml` for response documents.
+pub struct WordCountIndicator {
+    stats: Option<DocumentStats>,
+    project_path: Option<ProjectPath>,
+    workspace: WeakEntity<Workspace>,
+    tender_manifest: Entity<TenderManifestStore>,
+    update_stats: Task<()>,
+    _observe_active_editor: Option<Subscription>,
+    _subscriptions: [Subscription; 2],
+}
+
+impl WordCountIndicator {
+    pub fn new(workspace: &Workspace, cx: &mut Context<Self>) -> Self {
+        let tender_manifest = TenderManifestStore::for_project(workspace.project(), cx);
+        let subscriptions = [
+            cx.observe_global::<SettingsStore>(|_, cx| cx.notify()),
+            cx.observe(&tender_manifest, |_, _, cx| cx.notify()),
+        ];
+        Self {
+            stats: None,
+            project_path: None,
+            workspace: workspace.weak_handle(),
+            tender_manifest,
+            update_stats: Task::ready(()),
+            _observe_active_editor: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn update_stats(
+        &mut self,
+        editor: Entity<Editor>,
+        debounce: Option<Duration>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let editor = editor.downgrade();
+        self.update_stats = cx.spawn_in(window, async move |this, cx| {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+
+            let input = editor
+                .update(cx, |editor, cx| {
+                    if !editor.mode().is_full() {
+                        return None;
+                    }
+                    let buffer = editor.buffer().read(cx).as_singleton()?;
+                    if !prose::is_markdown(buffer.read(cx)) {
+                        return None;
+                    }
+                    let selections = editor
+                        .selections
+                        .all::<usize>(cx)
+                        .into_iter()
+                        .filter(|selection| !selection.is_empty())
+                        .map(|selection| selection.range())
+                        .collect::<Vec<_>>();
+                    let project_path = buffer
+                        .read(cx)
+                        .file()
+                        .map(|file| ProjectPath::from_file(file.as_ref(), cx));
+                    Some((buffer.read(cx).snapshot(), selections, project_path))
+                })
+                .ok()
+                .flatten();
+
+            let (stats, project_path) = match input {
+                Some((snapshot, selections, project_path)) => (
+                    Some(
+                        cx.background_spawn(async move {
+                            document_stats(&snapshot.text(), &selections)
+                        })
+                        .await,
+                    ),
+                    project_path,
+                ),
+                None => (None, None),
+            };
+            this.update(cx, |this, cx| {
+                this.stats = stats;
+                this.project_path = project_path;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+fn document_stats(text: &str, selections: &[Range<usize>]) -> DocumentStats {
+    let prose_ranges = prose::prose_ranges(text);
+    let document = prose::text_stats(text, &prose_ranges, 0..text.len());
+    let selection = (!selections.is_empty()).then(|| {
+        selections
+            .iter()
+            .map(|selection| prose::text_stats(text, &prose_ranges, selection.clone()))
+            .fold(TextStats::default(), |total, stats| TextStats {
+                words: total.words + stats.words,
+                characters: total.characters + stats.characters,
+            })
+    });
+    DocumentStats {
+        document,
+        selection,
+    }
+}
+
+impl Render for WordCountIndicator {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let location = self
+            .project_path
+            .as_ref()
+            .map(|project_path| SettingsLocation {
+                worktree_id: project_path.worktree_id,
+                path: &project_path.path,
+            });
+        let settings = WritingAppSettings::get(location, cx);
+        let tender_manifest = self.tender_manifest.read(cx);
+        let word_limit = tender_manifest
+            .manifest()
+            .filter(|_| {
+                self.project_path
+                    .as_ref()
+                    .is_some_and(|project_path| tender_manifest.is_response(project_path))
+            })
+            .and_then(|manifest| manifest.limits.words);
+        let over_limit = self.stats.is_some_and(|stats| {
+            word_limit.is_some_and(|word_limit| stats.document.words > word_limit)
+        });
+        let label = self
+            .stats
+            .map(|stats| {
+                stats_label(
+                    stats.document,
+                    stats.selection,
+                    word_limit,
+                    settings.word_count_visible,
+                    settings.reading_time_visible,
+                )
+            })
+            .filter(|label| !label.is_empty());
+
+        div().when_some(label, |el, label| {
+            el.child(
+                Button::new("word-count", label)
+                    .label_size(LabelSize::Small)
+                    .when(over_limit && settings.word_count_visible, |button| {
+                        button.color(Color::Error)
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        if let Some(workspace) = this.workspace.upgrade() {
+                            workspace.update(cx, |workspace, cx| {
+                                SectionBreakdown::toggle(workspace, window, cx);
+                            });
+                        }
+                    }))
+                    .tooltip(Tooltip::for_action_title(
+                        "Show Word Count by Section",
+                        &ToggleSectionBreakdown,
+                    )),
+            )
+        })
+    }
+}
+
+impl StatusItemView for WordCountIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.subscribe_in(
+                &editor,
+                window,
+                |this, editor, event, window, cx| match event {
+                    EditorEvent::BufferEdited | EditorEvent::SelectionsChanged { .. } => {
+                        this.update_stats(editor.clone(), Some(UPDATE_DEBOUNCE), window, cx)
+                    }
+                    _ => {}
+                },
+            ));
+            self.update_stats(editor, None, window, cx);
+        } else {
+            self.stats = None;
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
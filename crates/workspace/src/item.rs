@@ -29,6 +29,7 @@ use std::{
 use theme::Theme;
 use ui::{Color, Icon, IntoElement, Label, LabelCommon};
 use util::ResultExt;
+use writing_app_settings::WritingAppSettings;
 
 pub const LEADER_UPDATE_THROTTLE: Duration = Duration::from_millis(200);
 
@@ -561,6 +562,9 @@ pub trait ItemHandle: 'static + Send {
     fn pixel_position_of_cursor(&self, cx: &App) -> Option<Point<Pixels>>;
     fn downgrade_item(&self) -> Box<dyn WeakItemHandle>;
     fn workspace_settings<'a>(&self, cx: &'a App) -> &'a WorkspaceSettings;
+    /// The `autosave` setting for this item, falling back to the writing
+    /// app's auto-save interval when `autosave` is off.
+    fn autosave_setting(&self, cx: &App) -> AutosaveSetting;
     fn preserve_preview(&self, cx: &App) -> bool;
     fn include_in_nav_history(&self) -> bool;
     fn relay_action(&self, action: Box<dyn Action>, window: &mut Window, cx: &mut App);
@@ -663,6 +667,24 @@ impl<T: Item> ItemHandle for Entity<T> {
         }
     }
 
+    fn autosave_setting(&self, cx: &App) -> AutosaveSetting {
+        let project_path = self.project_path(cx);
+        let location = project_path.as_ref().map(|project_path| SettingsLocation {
+            worktree_id: project_path.worktree_id,
+            path: &project_path.path,
+        });
+        let autosave = WorkspaceSettings::get(location, cx).autosave;
+        let delay_ms = project_path.as_ref().and_then(|project_path| {
+            WritingAppSettings::get(location, cx).auto_save_delay_ms(&project_path.path)
+        });
+        match delay_ms {
+            Some(milliseconds) if autosave == AutosaveSetting::Off => {
+                AutosaveSetting::AfterDelay { milliseconds }
+            }
+            _ => autosave,
+        }
+    }
+
     fn project_entry_ids(&self, cx: &App) -> SmallVec<[ProjectEntryId; 3]> {
         let mut result = SmallVec::new();
         self.read(cx).for_each_project_item(cx, &mut |_, item| {
@@ -859,7 +881,7 @@ impl<T: Item> ItemHandle for Entity<T> {
                         }
 
                         ItemEvent::Edit => {
-                            let autosave = item.workspace_settings(cx).autosave;
+                            let autosave = item.autosave_setting(cx);
 
                             if let AutosaveSetting::AfterDelay { milliseconds } = autosave {
                                 let delay = Duration::from_millis(milliseconds);
@@ -891,7 +913,7 @@ impl<T: Item> ItemHandle for Entity<T> {
                 window,
                 move |workspace, window, cx| {
                     if let Some(item) = weak_item.upgrade() {
-                        if item.workspace_settings(cx).autosave == AutosaveSetting::OnFocusChange {
+                        if item.autosave_setting(cx) == AutosaveSetting::OnFocusChange {
                             Pane::autosave_item(&item, workspace.project.clone(), window, cx)
                                 .detach_and_log_err(cx);
                         }
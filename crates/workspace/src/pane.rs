@@ -305,6 +305,7 @@ pub struct Pane {
     can_split_predicate:
         Option<Arc<dyn Fn(&mut Self, &dyn Any, &mut Window, &mut Context<Self>) -> bool>>,
     should_display_tab_bar: Rc<dyn Fn(&Window, &mut Context<Pane>) -> bool>,
+    in_focus_mode: bool,
     render_tab_bar_buttons: Rc<
         dyn Fn(
             &mut Pane,
@@ -439,6 +440,7 @@ impl Pane {
             custom_drop_handle: None,
             can_split_predicate: None,
             should_display_tab_bar: Rc::new(|_, cx| TabBarSettings::get_global(cx).show),
+            in_focus_mode: false,
             render_tab_bar_buttons: Rc::new(default_render_tab_bar_buttons),
             render_tab_bar: Rc::new(Self::render_tab_bar),
             show_tab_bar_buttons: TabBarSettings::get_global(cx).show_tab_bar_buttons,
@@ -629,6 +631,12 @@ impl Pane {
         self.should_display_tab_bar = Rc::new(should_display_tab_bar);
     }
 
+    /// Hides the tab bar while the workspace is in focus mode.
+    pub fn set_in_focus_mode(&mut self, in_focus_mode: bool, cx: &mut Context<Self>) {
+        self.in_focus_mode = in_focus_mode;
+        cx.notify();
+    }
+
     pub fn set_can_split(
         &mut self,
         can_split_predicate: Option<
@@ -1855,7 +1863,7 @@ impl Pane {
             if save_intent == SaveIntent::Close {
                 let will_autosave = cx.update(|_window, cx| {
                     matches!(
-                        item.workspace_settings(cx).autosave,
+                        item.autosave_setting(cx),
                         AutosaveSetting::OnFocusChange | AutosaveSetting::OnWindowChange
                     ) && Self::can_autosave_item(item, cx)
                 })?;
@@ -1957,7 +1965,7 @@ impl Pane {
         cx: &mut App,
     ) -> Task<Result<()>> {
         let format = !matches!(
-            item.workspace_settings(cx).autosave,
+            item.autosave_setting(cx),
             AutosaveSetting::AfterDelay { .. }
         );
         if Self::can_autosave_item(item, cx) {
@@ -3185,7 +3193,7 @@ impl Render for Pane {
         }
 
         let should_display_tab_bar = self.should_display_tab_bar.clone();
-        let display_tab_bar = should_display_tab_bar(window, cx);
+        let display_tab_bar = !self.in_focus_mode && should_display_tab_bar(window, cx);
         let Some(project) = self.project.upgrade() else {
             return div().track_focus(&self.focus_handle(cx));
         };
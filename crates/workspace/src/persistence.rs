@@ -294,6 +294,7 @@ define_connection! {
     //   display: Option<Uuid>, // Display id
     //   fullscreen: Option<bool>, // Is the window fullscreen?
     //   centered_layout: Option<bool>, // Is the Centered Layout mode activated?
+    //   focus_mode: Option<bool>, // Is the writing Focus Mode activated?
     //   session_id: Option<String>, // Session id
     //   window_id: Option<u64>, // Window Id
     // )
@@ -542,6 +543,10 @@ define_connection! {
         ALTER TABLE breakpoints ADD COLUMN condition TEXT;
         ALTER TABLE breakpoints ADD COLUMN hit_condition TEXT;
     ),
+    // Add focus_mode field to workspace
+    sql!(
+        ALTER TABLE workspaces ADD COLUMN focus_mode INTEGER; //bool
+    ),
     ];
 }
 
@@ -566,6 +571,7 @@ impl WorkspaceDb {
             window_bounds,
             display,
             centered_layout,
+            focus_mode,
             docks,
             window_id,
         ): (
@@ -575,6 +581,7 @@ impl WorkspaceDb {
             Option<SerializedWindowBounds>,
             Option<Uuid>,
             Option<bool>,
+            Option<bool>,
             DockStructure,
             Option<u64>,
         ) = self
@@ -590,6 +597,7 @@ impl WorkspaceDb {
                     window_height,
                     display,
                     centered_layout,
+                    focus_mode,
                     left_dock_visible,
                     left_dock_active_panel,
                     left_dock_zoom,
@@ -626,6 +634,7 @@ impl WorkspaceDb {
                 .log_err()?,
             window_bounds,
             centered_layout: centered_layout.unwrap_or(false),
+            focus_mode,
             display,
             docks,
             session_id: None,
@@ -638,11 +647,20 @@ impl WorkspaceDb {
         &self,
         ssh_project: &SerializedSshProject,
     ) -> Option<SerializedWorkspace> {
-        let (workspace_id, window_bounds, display, centered_layout, docks, window_id): (
+        let (
+            workspace_id,
+            window_bounds,
+            display,
+            centered_layout,
+            focus_mode,
+            docks,
+            window_id,
+        ): (
             WorkspaceId,
             Option<SerializedWindowBounds>,
             Option<Uuid>,
             Option<bool>,
+            Option<bool>,
             DockStructure,
             Option<u64>,
         ) = self
@@ -656,6 +674,7 @@ impl WorkspaceDb {
                     window_height,
                     display,
                     centered_layout,
+                    focus_mode,
                     left_dock_visible,
                     left_dock_active_panel,
                     left_dock_zoom,
@@ -683,6 +702,7 @@ impl WorkspaceDb {
                 .log_err()?,
             window_bounds,
             centered_layout: centered_layout.unwrap_or(false),
+            focus_mode,
             breakpoints: self.breakpoints(workspace_id),
             display,
             docks,
@@ -1336,6 +1356,14 @@ impl WorkspaceDb {
         }
     }
 
+    query! {
+        pub(crate) async fn set_focus_mode(workspace_id: WorkspaceId, focus_mode: bool) -> Result<()> {
+            UPDATE workspaces
+            SET focus_mode = ?2
+            WHERE workspace_id = ?1
+        }
+    }
+
     pub async fn toolchain(
         &self,
         workspace_id: WorkspaceId,
@@ -1521,6 +1549,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: {
                 let mut map = collections::BTreeMap::default();
                 map.insert(
@@ -1674,6 +1703,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: {
                 let mut map = collections::BTreeMap::default();
                 map.insert(
@@ -1720,6 +1750,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: collections::BTreeMap::default(),
             session_id: None,
             window_id: None,
@@ -1814,6 +1845,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: None,
             window_id: None,
@@ -1827,6 +1859,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: None,
             window_id: None,
@@ -1936,6 +1969,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             session_id: None,
             window_id: Some(999),
         };
@@ -1971,6 +2005,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             session_id: None,
             window_id: Some(1),
         };
@@ -1983,6 +2018,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: None,
             window_id: Some(2),
@@ -2027,6 +2063,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             session_id: None,
             window_id: Some(3),
         };
@@ -2063,6 +2100,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: Some("session-id-1".to_owned()),
             window_id: Some(10),
@@ -2076,6 +2114,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: Some("session-id-1".to_owned()),
             window_id: Some(20),
@@ -2089,6 +2128,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: Some("session-id-2".to_owned()),
             window_id: Some(30),
@@ -2102,6 +2142,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: None,
             window_id: None,
@@ -2120,6 +2161,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             breakpoints: Default::default(),
             session_id: Some("session-id-2".to_owned()),
             window_id: Some(50),
@@ -2137,6 +2179,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             session_id: Some("session-id-3".to_owned()),
             window_id: Some(60),
         };
@@ -2193,6 +2236,7 @@ mod tests {
             docks: Default::default(),
             breakpoints: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             session_id: None,
             window_id: None,
         }
@@ -2238,6 +2282,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             session_id: Some("one-session".to_owned()),
             breakpoints: Default::default(),
             window_id: Some(window_id),
@@ -2331,6 +2376,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            focus_mode: None,
             session_id: Some("one-session".to_owned()),
             breakpoints: Default::default(),
             window_id: Some(window_id),
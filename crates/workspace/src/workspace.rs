@@ -106,6 +106,7 @@ use uuid::Uuid;
 pub use workspace_settings::{
     AutosaveSetting, BottomDockLayout, RestoreOnStartupBehavior, TabBarSettings, WorkspaceSettings,
 };
+use writing_app_settings::WritingAppSettings;
 use zed_actions::feedback::FileBugReport;
 
 use crate::notifications::NotificationId;
@@ -184,6 +185,7 @@ actions!(
         SuppressNotification,
         ToggleBottomDock,
         ToggleCenteredLayout,
+        ToggleFocusMode,
         ToggleLeftDock,
         ToggleRightDock,
         ToggleZoom,
@@ -374,6 +376,7 @@ pub fn init_settings(cx: &mut App) {
     ItemSettings::register(cx);
     PreviewTabsSettings::register(cx);
     TabBarSettings::register(cx);
+    writing_app_settings::init(cx);
 }
 
 fn prompt_and_open_paths(app_state: Arc<AppState>, options: PathPromptOptions, cx: &mut App) {
@@ -869,6 +872,7 @@ pub enum Event {
         language: &'static str,
     },
     ZoomChanged,
+    FocusModeChanged,
     ModalOpened,
     ClearActivityIndicator,
 }
@@ -943,6 +947,7 @@ pub struct Workspace {
     pane_history_timestamp: Arc<AtomicUsize>,
     bounds: Bounds<Pixels>,
     pub centered_layout: bool,
+    focus_mode: bool,
     bounds_save_task_queued: Option<Task<()>>,
     on_prompt_for_new_path: Option<PromptForNewPath>,
     on_prompt_for_open_path: Option<PromptForOpenPath>,
@@ -1091,6 +1096,7 @@ impl Workspace {
         let weak_handle = cx.entity().downgrade();
         let pane_history_timestamp = Arc::new(AtomicUsize::new(0));
 
+        let focus_mode = WritingAppSettings::get_global(cx).focus_mode_enabled;
         let center_pane = cx.new(|cx| {
             let mut center_pane = Pane::new(
                 weak_handle.clone(),
@@ -1102,6 +1108,7 @@ impl Workspace {
                 cx,
             );
             center_pane.set_can_split(Some(Arc::new(|_, _, _, _| true)));
+            center_pane.set_in_focus_mode(focus_mode, cx);
             center_pane
         });
         cx.subscribe_in(&center_pane, window, Self::handle_pane_event)
@@ -1275,6 +1282,7 @@ impl Workspace {
             // This data will be incorrect, but it will be overwritten by the time it needs to be used.
             bounds: Default::default(),
             centered_layout: false,
+            focus_mode,
             bounds_save_task_queued: None,
             on_prompt_for_new_path: None,
             on_prompt_for_open_path: None,
@@ -1417,6 +1425,7 @@ impl Workspace {
                     .as_ref()
                     .map(|w| w.centered_layout)
                     .unwrap_or(false);
+                let focus_mode = serialized_workspace.as_ref().and_then(|w| w.focus_mode);
                 cx.open_window(options, {
                     let app_state = app_state.clone();
                     let project_handle = project_handle.clone();
@@ -1430,6 +1439,9 @@ impl Workspace {
                                 cx,
                             );
                             workspace.centered_layout = centered_layout;
+                            if let Some(focus_mode) = focus_mode {
+                                workspace.apply_focus_mode(focus_mode, cx);
+                            }
                             workspace
                         })
                     }
@@ -2926,6 +2938,7 @@ impl Workspace {
                 cx,
             );
             pane.set_can_split(Some(Arc::new(|_, _, _, _| true)));
+            pane.set_in_focus_mode(self.focus_mode, cx);
             pane
         });
         cx.subscribe_in(&pane, window, Self::handle_pane_event)
@@ -4760,7 +4773,7 @@ impl Workspace {
                     }
                     for item in pane.items() {
                         if matches!(
-                            item.workspace_settings(cx).autosave,
+                            item.autosave_setting(cx),
                             AutosaveSetting::OnWindowChange | AutosaveSetting::OnFocusChange
                         ) {
                             Pane::autosave_item(item.as_ref(), self.project.clone(), window, cx)
@@ -5001,6 +5014,7 @@ impl Workspace {
                 display: Default::default(),
                 docks,
                 centered_layout: self.centered_layout,
+                focus_mode: Some(self.focus_mode),
                 session_id: self.session_id.clone(),
                 breakpoints,
                 window_id: Some(window.window_handle().window_id().as_u64()),
@@ -5339,6 +5353,7 @@ impl Workspace {
                 },
             ))
             .on_action(cx.listener(Workspace::toggle_centered_layout))
+            .on_action(cx.listener(Workspace::toggle_focus_mode))
             .on_action(cx.listener(Workspace::cancel))
     }
 
@@ -5433,6 +5448,40 @@ impl Workspace {
         cx.notify();
     }
 
+    pub fn focus_mode(&self) -> bool {
+        self.focus_mode
+    }
+
+    /// Toggles the writing focus mode, which hides the docks, the tab bar and
+    /// the status bar. Editors dim everything but the text being written.
+    pub fn toggle_focus_mode(
+        &mut self,
+        _: &ToggleFocusMode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let focus_mode = !self.focus_mode;
+        if focus_mode {
+            window.focus(&self.active_pane.focus_handle(cx));
+        }
+        self.apply_focus_mode(focus_mode, cx);
+        if let Some(database_id) = self.database_id() {
+            cx.background_spawn(DB.set_focus_mode(database_id, focus_mode))
+                .detach_and_log_err(cx);
+        }
+    }
+
+    /// Turns focus mode on or off without saving it, as when restoring a
+    /// workspace.
+    pub fn apply_focus_mode(&mut self, focus_mode: bool, cx: &mut Context<Self>) {
+        self.focus_mode = focus_mode;
+        for pane in &self.panes {
+            pane.update(cx, |pane, cx| pane.set_in_focus_mode(focus_mode, cx));
+        }
+        cx.emit(Event::FocusModeChanged);
+        cx.notify();
+    }
+
     fn adjust_padding(padding: Option<f32>) -> f32 {
         padding
             .unwrap_or(Self::DEFAULT_PADDING)
@@ -5446,7 +5495,9 @@ impl Workspace {
         window: &mut Window,
         cx: &mut App,
     ) -> Option<Div> {
-        if self.zoomed_position == Some(position) {
+        // Focus mode hides the docks without closing them, so that they
+        // reappear as they were when it's turned off.
+        if self.focus_mode || self.zoomed_position == Some(position) {
             return None;
         }
 
@@ -6145,7 +6196,7 @@ impl Render for Workspace {
                                 }))
                                 .children(self.render_notifications(window, cx)),
                         )
-                        .child(self.status_bar.clone())
+                        .when(!self.focus_mode, |this| this.child(self.status_bar.clone()))
                         .child(self.modal_layer.clone())
                         .child(self.toast_layer.clone()),
                 ),
@@ -6935,6 +6986,9 @@ async fn open_ssh_project_inner(
             .unwrap_or_else(|| anyhow!("no paths given")));
     }
 
+    let focus_mode = serialized_workspace
+        .as_ref()
+        .and_then(|workspace| workspace.focus_mode);
     cx.update_window(window.into(), |_, window, cx| {
         window.replace_root(cx, |window, cx| {
             telemetry::event!("SSH Project Opened");
@@ -6942,6 +6996,9 @@ async fn open_ssh_project_inner(
             let mut workspace =
                 Workspace::new(Some(workspace_id), project, app_state.clone(), window, cx);
             workspace.set_serialized_ssh_project(serialized_ssh_project);
+            if let Some(focus_mode) = focus_mode {
+                workspace.apply_focus_mode(focus_mode, cx);
+            }
             workspace.update_history(cx);
             workspace
         });
@@ -7504,6 +7561,7 @@ pub struct WorkspacePosition {
     pub window_bounds: Option<WindowBounds>,
     pub display: Option<Uuid>,
     pub centered_layout: bool,
+    pub focus_mode: Option<bool>,
 }
 
 pub fn ssh_workspace_position_from_db(
@@ -7548,11 +7606,13 @@ pub fn ssh_workspace_position_from_db(
             .as_ref()
             .map(|w| w.centered_layout)
             .unwrap_or(false);
+        let focus_mode = serialized_workspace.as_ref().and_then(|w| w.focus_mode);
 
         Ok(WorkspacePosition {
             window_bounds,
             display,
             centered_layout,
+            focus_mode,
         })
     })
 }
@@ -8177,6 +8237,69 @@ mod tests {
         item.update(cx, |item, _| assert_eq!(item.save_count, 5));
     }
 
+    #[gpui::test]
+    async fn test_autosave_uses_writing_interval_when_off(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+
+        let item = cx.new(|cx| {
+            TestItem::new(cx).with_project_items(&[TestProjectItem::new(1, "1.txt", cx)])
+        });
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.add_item_to_active_pane(Box::new(item.clone()), None, true, window, cx);
+        });
+
+        item.update(cx, |item, cx| {
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings::<WritingAppSettings>(cx, |settings| {
+                    settings.auto_save_interval_ms = Some(1000);
+                })
+            });
+            item.is_dirty = true;
+            cx.emit(ItemEvent::Edit);
+        });
+        cx.executor().advance_clock(Duration::from_millis(500));
+        item.update(cx, |item, _| assert_eq!(item.save_count, 0));
+        cx.executor().advance_clock(Duration::from_millis(500));
+        item.update(cx, |item, _| assert_eq!(item.save_count, 1));
+
+        // An interval of zero turns auto-save off.
+        item.update(cx, |item, cx| {
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings::<WritingAppSettings>(cx, |settings| {
+                    settings.auto_save_interval_ms = Some(0);
+                })
+            });
+            item.is_dirty = true;
+            cx.emit(ItemEvent::Edit);
+        });
+        cx.executor().advance_clock(Duration::from_secs(60));
+        item.update(cx, |item, _| assert_eq!(item.save_count, 1));
+
+        // Files other than prose documents are left to the `autosave` setting.
+        let code_item = cx.new(|cx| {
+            TestItem::new(cx).with_project_items(&[TestProjectItem::new(2, "main.rs", cx)])
+        });
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.add_item_to_active_pane(Box::new(code_item.clone()), None, true, window, cx);
+        });
+        code_item.update(cx, |item, cx| {
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings::<WritingAppSettings>(cx, |settings| {
+                    settings.auto_save_interval_ms = Some(1000);
+                })
+            });
+            item.is_dirty = true;
+            cx.emit(ItemEvent::Edit);
+        });
+        cx.executor().advance_clock(Duration::from_secs(60));
+        code_item.update(cx, |item, _| assert_eq!(item.save_count, 0));
+    }
+
     #[gpui::test]
     async fn test_pane_navigation(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -8384,6 +8507,46 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_focus_mode_keeps_dock_state(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+
+        workspace.update_in(cx, |workspace, window, cx| {
+            let panel = cx.new(|cx| TestPanel::new(DockPosition::Right, cx));
+            workspace.add_panel(panel, window, cx);
+            workspace
+                .right_dock()
+                .update(cx, |right_dock, cx| right_dock.set_open(true, window, cx));
+        });
+
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.toggle_focus_mode(&ToggleFocusMode, window, cx);
+            assert!(workspace.focus_mode());
+            assert!(workspace.right_dock().read(cx).is_open());
+            assert!(
+                workspace
+                    .render_dock(DockPosition::Right, &workspace.right_dock, window, cx)
+                    .is_none()
+            );
+        });
+
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.toggle_focus_mode(&ToggleFocusMode, window, cx);
+            assert!(!workspace.focus_mode());
+            assert!(workspace.right_dock().read(cx).is_open());
+            assert!(
+                workspace
+                    .render_dock(DockPosition::Right, &workspace.right_dock, window, cx)
+                    .is_some()
+            );
+        });
+    }
+
     #[gpui::test]
     async fn test_join_pane_into_next(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -1,75 +1,328 @@
-use gpui::{App, Global};
-use serde::{Deserialize, Serialize};
-use settings::Settings;
-use std::sync::Arc;
+use anyhow::Result;
+use gpui::App;
+use language::File;
 use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsLocation, SettingsSources};
+use std::{ffi::OsStr, path::Path, sync::Arc};
+
+pub fn init(cx: &mut App) {
+    WritingAppSettings::register(cx);
+}
+
+/// Returns the writing settings for the specified file, including those of
+/// its project.
+pub fn writing_settings<'a>(file: Option<&Arc<dyn File>>, cx: &'a App) -> &'a WritingAppSettings {
+    let location = file.map(|file| SettingsLocation {
+        worktree_id: file.worktree_id(cx),
+        path: file.path().as_ref(),
+    });
+    WritingAppSettings::get(location, cx)
+}
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct WritingAppSettings {
-    // Settings for controlling feature visibility
     pub show_git_panel: bool,
     pub show_themes: bool,
     pub show_icon_themes: bool,
     pub show_extensions: bool,
     pub simplified_interface: bool,
     pub show_api_settings: bool,
-    
-    // Writing-focused settings
-    pub auto_save_interval_ms: Option<u64>,
+    pub auto_save_interval_ms: u64,
     pub spelling_check_enabled: bool,
+    pub spelling_dictionary: SpellingDictionary,
     pub grammar_check_enabled: bool,
+    pub grammar_check_url: Option<String>,
     pub word_count_visible: bool,
     pub reading_time_visible: bool,
     pub focus_mode_enabled: bool,
+    pub focus_mode_scope: FocusModeScope,
+    pub docx_reference: Option<String>,
+    pub page_size: PageSize,
+    pub page_margin_mm: f32,
+    pub page_font_size: f32,
+    pub number_headings: bool,
 }
 
-impl Global for WritingAppSettings {}
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct WritingAppSettingsContent {
+    /// Whether to show the Git panel and its actions.
+    ///
+    /// Default: false
+    pub show_git_panel: Option<bool>,
+    /// Whether the theme selector can be opened.
+    ///
+    /// Default: false
+    pub show_themes: Option<bool>,
+    /// Whether the icon theme selector can be opened.
+    ///
+    /// Default: false
+    pub show_icon_themes: Option<bool>,
+    /// Whether the extensions page can be opened.
+    ///
+    /// Default: false
+    pub show_extensions: Option<bool>,
+    /// Whether to hide developer-focused parts of the interface.
+    ///
+    /// Default: true
+    pub simplified_interface: Option<bool>,
+    /// Whether to show the API provider settings.
+    ///
+    /// Default: false
+    pub show_api_settings: Option<bool>,
+    /// How long, in milliseconds, an edited Markdown or plain text document
+    /// stays idle before it is saved. Applies when `autosave` is `"off"`;
+    /// set to 0 to turn it off.
+    ///
+    /// Default: 30000
+    pub auto_save_interval_ms: Option<u64>,
+    /// Whether to check the spelling of Markdown buffers.
+    ///
+    /// Default: true
+    pub spelling_check_enabled: Option<bool>,
+    /// The Hunspell dictionary used for spell checking.
+    ///
+    /// Default: "en-AU"
+    pub spelling_dictionary: Option<SpellingDictionary>,
+    /// Whether to check the grammar and style of Markdown buffers. Off by
+    /// default, since it needs a LanguageTool server to be running.
+    ///
+    /// Default: false
+    pub grammar_check_enabled: Option<bool>,
+    /// Base URL of the LanguageTool-compatible server used for grammar and
+    /// style checking. Unset uses a local server on port 8081.
+    ///
+    /// Default: null
+    pub grammar_check_url: Option<String>,
+    /// Whether to show the word count of Markdown buffers in the status bar.
+    ///
+    /// Default: true
+    pub word_count_visible: Option<bool>,
+    /// Whether to show the reading time of Markdown buffers in the status bar.
+    ///
+    /// Default: true
+    pub reading_time_visible: Option<bool>,
+    /// Whether new workspaces open in focus mode.
+    ///
+    /// Default: false
+    pub focus_mode_enabled: Option<bool>,
+    /// How much of the text around the cursor stays undimmed in focus mode.
+    ///
+    /// Default: "paragraph"
+    pub focus_mode_scope: Option<FocusModeScope>,
+    /// The Word document whose styles, page setup, headers and footers
+    /// exported DOCX files use. Relative paths are resolved against the
+    /// project root. Unset uses built-in styles on A4 pages.
+    ///
+    /// Default: null
+    pub docx_reference: Option<String>,
+    /// The paper size of exported PDFs.
+    ///
+    /// Default: "a4"
+    pub page_size: Option<PageSize>,
+    /// The margin on each side of exported and previewed pages, in millimetres.
+    ///
+    /// Default: 20
+    pub page_margin_mm: Option<f32>,
+    /// The size of body text on exported and previewed pages, in points.
+    ///
+    /// Default: 11
+    pub page_font_size: Option<f32>,
+    /// Whether the Markdown preview and exports number headings, as in
+    /// "4.2 Pricing". Cross-references are numbered either way.
+    ///
+    /// Default: false
+    pub number_headings: Option<bool>,
+}
 
-impl Settings for WritingAppSettings {
-    const KEY: Option<&'static str> = Some("writing_app");
+/// A paper size, in points.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+impl PageSize {
+    pub const ALL: [PageSize; 2] = [PageSize::A4, PageSize::Letter];
 
-    type FileContent = Self;
-    
-    fn load(
-        sources: settings::SettingsSources<'_, Self::FileContent>,
-        _: &mut App,
-    ) -> anyhow::Result<Self> {
-        if let Some(user_settings) = sources.user {
-            Ok(user_settings.clone())
-        } else {
-            Ok(Self::default())
+    /// The width and height of the page, in points.
+    pub fn dimensions(&self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612., 792.),
         }
     }
 
-    fn import_from_vscode(_vscode_settings: &settings::VsCodeSettings, _settings: &mut Self::FileContent) {
-        // No implementation needed
+    pub fn label(&self) -> &'static str {
+        match self {
+            PageSize::A4 => "A4",
+            PageSize::Letter => "Letter",
+        }
     }
 }
 
-pub fn init(cx: &mut App) {
-    // Initialize with default settings
-    let settings = WritingAppSettings {
-        // Default to hiding developer-focused features
-        show_git_panel: false,
-        show_themes: false,
-        show_icon_themes: false,
-        show_extensions: false,
-        simplified_interface: true,
-        show_api_settings: false,
-        
-        // Enable writing-focused features by default
-        auto_save_interval_ms: Some(30000), // 30 seconds
-        spelling_check_enabled: true,
-        grammar_check_enabled: true,
-        word_count_visible: true,
-        reading_time_visible: true,
-        focus_mode_enabled: false,
-    };
-
-    cx.set_global(settings);
+/// How much of the text around the cursor stays undimmed in focus mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusModeScope {
+    #[default]
+    Paragraph,
+    Sentence,
 }
 
-pub fn get_global(cx: &App) -> Arc<WritingAppSettings> {
-    let settings = cx.global::<WritingAppSettings>();
-    Arc::new(settings.clone())
-}
\ No newline at end of file
+impl FocusModeScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FocusModeScope::Paragraph => "Paragraph",
+            FocusModeScope::Sentence => "Sentence",
+        }
+    }
+}
+
+/// The Hunspell dictionary used for spell checking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum SpellingDictionary {
+    #[default]
+    #[serde(rename = "en-AU")]
+    EnAu,
+    #[serde(rename = "en-GB")]
+    EnGb,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl SpellingDictionary {
+    pub const ALL: [SpellingDictionary; 3] = [
+        SpellingDictionary::EnAu,
+        SpellingDictionary::EnGb,
+        SpellingDictionary::EnUs,
+    ];
+
+    /// The file stem Hunspell dictionaries for this language are installed under.
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            SpellingDictionary::EnAu => "en_AU",
+            SpellingDictionary::EnGb => "en_GB",
+            SpellingDictionary::EnUs => "en_US",
+        }
+    }
+
+    /// The language code LanguageTool expects for this language.
+    pub fn language_code(&self) -> &'static str {
+        match self {
+            SpellingDictionary::EnAu => "en-AU",
+            SpellingDictionary::EnGb => "en-GB",
+            SpellingDictionary::EnUs => "en-US",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpellingDictionary::EnAu => "English (Australia)",
+            SpellingDictionary::EnGb => "English (United Kingdom)",
+            SpellingDictionary::EnUs => "English (United States)",
+        }
+    }
+}
+
+impl WritingAppSettings {
+    /// How long the document at `path` stays idle before it's auto-saved, if
+    /// it's a Markdown or plain text document. Other files are left to the
+    /// `autosave` setting.
+    pub fn auto_save_delay_ms(&self, path: &Path) -> Option<u64> {
+        let is_prose = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|extension| {
+                matches!(
+                    extension.to_ascii_lowercase().as_str(),
+                    "md" | "markdown" | "txt"
+                )
+            });
+        (is_prose && self.auto_save_interval_ms > 0).then_some(self.auto_save_interval_ms)
+    }
+}
+
+impl Settings for WritingAppSettings {
+    const KEY: Option<&'static str> = Some("writing_app");
+
+    type FileContent = WritingAppSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+
+    fn import_from_vscode(vscode: &settings::VsCodeSettings, current: &mut Self::FileContent) {
+        if let Some(delay) = vscode
+            .read_value("files.autoSaveDelay")
+            .and_then(|v| v.as_u64())
+        {
+            if vscode.read_string("files.autoSave") == Some("afterDelay") {
+                current.auto_save_interval_ms = Some(delay);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use settings::{LocalSettingsKind, SettingsStore, WorktreeId};
+
+    #[gpui::test]
+    fn test_settings_layers(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            init(cx);
+
+            let settings = WritingAppSettings::get_global(cx);
+            assert!(settings.spelling_check_enabled);
+            assert!(!settings.show_git_panel);
+            assert_eq!(settings.auto_save_interval_ms, 30000);
+            assert_eq!(
+                settings.auto_save_delay_ms(Path::new("response/Response.md")),
+                Some(30000)
+            );
+            assert_eq!(settings.auto_save_delay_ms(Path::new("src/main.rs")), None);
+
+            cx.update_global(|store: &mut SettingsStore, cx| {
+                store
+                    .set_user_settings(
+                        r#"{ "writing_app": { "show_git_panel": true, "auto_save_interval_ms": 0 } }"#,
+                        cx,
+                    )
+                    .unwrap();
+                store
+                    .set_local_settings(
+                        WorktreeId::from_usize(1),
+                        Path::new("").into(),
+                        LocalSettingsKind::Settings,
+                        Some(r#"{ "writing_app": { "spelling_dictionary": "en-US" } }"#),
+                        cx,
+                    )
+                    .unwrap();
+            });
+
+            let settings = WritingAppSettings::get_global(cx);
+            assert!(settings.show_git_panel);
+            assert!(settings.spelling_check_enabled);
+            assert_eq!(settings.auto_save_interval_ms, 0);
+            assert_eq!(settings.auto_save_delay_ms(Path::new("notes.txt")), None);
+            assert_eq!(settings.spelling_dictionary, SpellingDictionary::EnAu);
+
+            let project_settings = WritingAppSettings::get(
+                Some(SettingsLocation {
+                    worktree_id: WorktreeId::from_usize(1),
+                    path: Path::new("proposal.md"),
+                }),
+                cx,
+            );
+            assert_eq!(project_settings.spelling_dictionary, SpellingDictionary::EnUs);
+            assert!(project_settings.show_git_panel);
+        });
+    }
+}
@@ -625,6 +625,8 @@ fn main() {
 
         audio::init(Assets, cx);
         writing_app_settings::init(cx);
+        spell_check::init(app_state.fs.clone(), cx);
+        grammar_check::init(app_state.client.http_client(), cx);
         workspace::init(app_state.clone(), cx);
         ui_prompt::init(cx);
 
@@ -632,9 +634,22 @@ fn main() {
         file_finder::init(cx);
         tab_switcher::init(cx);
         outline::init(cx);
+        word_count::init(cx);
+        cross_references::init(cx);
+        requirements::init(cx);
+        tender_manifest::init(cx);
+        document_compare::init(cx);
+        document_export::init(cx);
+        document_viewer::init(cx);
+        spreadsheet::init(cx);
+        scorecard::init(cx);
+        answer_library::init(cx);
+        clarifications::init(cx);
+        new_tender::init(cx);
         project_symbols::init(cx);
         project_panel::init(cx);
         outline_panel::init(cx);
+        compliance_panel::init(cx);
         tasks_ui::init(cx);
         snippets_ui::init(cx);
         channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
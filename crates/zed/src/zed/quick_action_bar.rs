@@ -481,6 +481,8 @@ impl Render for QuickActionBar {
             .gap(DynamicSpacing::Base01.rems(cx))
             .children(self.render_repl_menu(cx))
             .children(self.render_toggle_markdown_preview(self.workspace.clone(), cx))
+            .children(self.render_export_docx(cx))
+            .children(self.render_export_pdf(cx))
             .children(search_button)
             .when(
                 AssistantSettings::get_global(cx).enabled
@@ -1,3 +1,4 @@
+use document_export::{ExportDocx, ExportPdf};
 use gpui::{AnyElement, Modifiers, WeakEntity};
 use markdown_preview::{
     OpenPreview, OpenPreviewToTheSide, markdown_preview_view::MarkdownPreviewView,
@@ -13,17 +14,7 @@ impl QuickActionBar {
         workspace: WeakEntity<Workspace>,
         cx: &mut Context<Self>,
     ) -> Option<AnyElement> {
-        let mut active_editor_is_markdown = false;
-
-        if let Some(workspace) = self.workspace.upgrade() {
-            workspace.update(cx, |workspace, cx| {
-                active_editor_is_markdown =
-                    MarkdownPreviewView::resolve_active_item_as_markdown_editor(workspace, cx)
-                        .is_some();
-            });
-        }
-
-        if !active_editor_is_markdown {
+        if !self.active_editor_is_markdown(cx) {
             return None;
         }
 
@@ -60,4 +51,48 @@ impl QuickActionBar {
 
         Some(button.into_any_element())
     }
+
+    pub fn render_export_docx(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        if !self.active_editor_is_markdown(cx) {
+            return None;
+        }
+
+        let button = IconButton::new("export-docx", IconName::Download)
+            .shape(IconButtonShape::Square)
+            .icon_size(IconSize::Small)
+            .style(ButtonStyle::Subtle)
+            .tooltip(Tooltip::for_action_title("Export to Word", &ExportDocx))
+            .on_click(|_, window, cx| window.dispatch_action(Box::new(ExportDocx), cx));
+
+        Some(button.into_any_element())
+    }
+
+    pub fn render_export_pdf(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        if !self.active_editor_is_markdown(cx) {
+            return None;
+        }
+
+        let button = IconButton::new("export-pdf", IconName::FileText)
+            .shape(IconButtonShape::Square)
+            .icon_size(IconSize::Small)
+            .style(ButtonStyle::Subtle)
+            .tooltip(Tooltip::for_action_title("Export to PDF", &ExportPdf))
+            .on_click(|_, window, cx| window.dispatch_action(Box::new(ExportPdf), cx));
+
+        Some(button.into_any_element())
+    }
+
+    fn active_editor_is_markdown(&self, cx: &mut Context<Self>) -> bool {
+        let mut active_editor_is_markdown = false;
+
+        if let Some(workspace) = self.workspace.upgrade() {
+            workspace.update(cx, |workspace, cx| {
+                active_editor_is_markdown =
+                    MarkdownPreviewView::resolve_active_item_as_markdown_editor(workspace, cx)
+                        .is_some();
+            });
+        }
+
+        active_editor_is_markdown
+    }
 }
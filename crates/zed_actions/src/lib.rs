@@ -193,6 +193,28 @@ pub mod agent {
     );
 }
 
+pub mod answer_library {
+    use gpui::impl_actions;
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct OpenAnswerLibrary {
+        #[serde(skip)]
+        pub answer_to_select: Option<Uuid>,
+    }
+
+    impl_actions!(answer_library, [OpenAnswerLibrary]);
+}
+
+pub mod new_tender {
+    use gpui::actions;
+
+    actions!(new_tender, [StartNewTender]);
+}
+
 pub mod assistant {
     use gpui::{
         action_with_deprecated_aliases, actions, impl_action_with_deprecated_aliases, impl_actions,